@@ -4,8 +4,10 @@ use anyhow::Context;
 use clap::CommandFactory;
 
 use crate::args::{
-    Cli, CompletionsArgs, ManpagesArgs, MigrationListArgs, MigrationMakeArgs, ProjectNewArgs,
+    Cli, CompletionsArgs, InspectDbArgs, ManpagesArgs, MigrationListArgs, MigrationMakeArgs,
+    ProjectNewArgs,
 };
+use crate::inspect_db::{InspectDbOptions, inspect_db};
 use crate::migration_generator::{MigrationGeneratorOptions, list_migrations, make_migrations};
 use crate::new_project::{CotSource, new_project};
 
@@ -59,6 +61,18 @@ pub fn handle_migration_make(
     make_migrations(&path, options).with_context(|| "unable to create migrations")
 }
 
+pub fn handle_inspect_db(
+    InspectDbArgs {
+        database_url,
+        tables,
+        output,
+    }: InspectDbArgs,
+) -> anyhow::Result<()> {
+    let options = InspectDbOptions { tables };
+    inspect_db(&database_url, output.as_deref(), &options)
+        .with_context(|| "unable to generate models")
+}
+
 pub fn handle_cli_manpages(
     ManpagesArgs { output_dir, create }: ManpagesArgs,
 ) -> anyhow::Result<()> {
@@ -126,6 +140,19 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn inspect_db_invalid_url() {
+        let args = InspectDbArgs {
+            database_url: "sqlite:///nonexistent/directory/db.sqlite3".to_owned(),
+            tables: Vec::new(),
+            output: None,
+        };
+
+        let result = handle_inspect_db(args);
+
+        assert!(result.is_err());
+    }
+
     #[test]
     fn generate_manpages() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,395 @@
+use std::path::Path;
+
+use anyhow::Context;
+use cot::db::introspection::{ColumnSchema, TableSchema};
+use cot::db::{ColumnType, Database};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::utils::{StatusType, print_status_msg};
+
+/// The prefix of the tables created by Cot itself, such as the one storing
+/// the applied migrations. They are never included in the generated models.
+const COT_TABLE_PREFIX: &str = "cot__";
+
+#[derive(Debug, Clone, Default)]
+pub struct InspectDbOptions {
+    /// The tables to generate the models for; all the tables if empty.
+    pub tables: Vec<String>,
+}
+
+/// Connects to the database at `database_url` and writes the models for its
+/// tables to `output`, or to the standard output if `output` is `None`.
+pub fn inspect_db(
+    database_url: &str,
+    output: Option<&Path>,
+    options: &InspectDbOptions,
+) -> anyhow::Result<()> {
+    let tables = introspect_tables(database_url).context("unable to inspect the database")?;
+    let tables: Vec<_> = tables
+        .into_iter()
+        .filter(|table| !table.name.starts_with(COT_TABLE_PREFIX))
+        .filter(|table| options.tables.is_empty() || options.tables.contains(&table.name))
+        .collect();
+    for name in &options.tables {
+        if !tables.iter().any(|table| &table.name == name) {
+            anyhow::bail!("table `{name}` not found in the database");
+        }
+    }
+
+    for table in &tables {
+        if table.primary_key().count() != 1 {
+            print_status_msg(
+                StatusType::Warning,
+                &format!(
+                    "Table `{}` doesn't have a single-column primary key; \
+                    the generated model needs to be adjusted",
+                    table.name
+                ),
+            );
+        }
+    }
+
+    let source = generate_models(&tables);
+    match output {
+        Some(path) => {
+            std::fs::write(path, source)
+                .with_context(|| format!("unable to write {}", path.display()))?;
+            print_status_msg(
+                StatusType::Created,
+                &format!("{} models in {}", tables.len(), path.display()),
+            );
+        }
+        None => print!("{source}"),
+    }
+
+    Ok(())
+}
+
+fn introspect_tables(database_url: &str) -> anyhow::Result<Vec<TableSchema>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("unable to start the async runtime")?;
+
+    runtime.block_on(async {
+        let database = Database::new(database_url).await?;
+        let tables = database.introspect().await;
+        database.close().await?;
+        Ok(tables?)
+    })
+}
+
+/// Returns the source code of the models for the given tables.
+///
+/// The models are declared with `model_type = "external"`, so that they use
+/// the existing tables as they are, and are ignored by the migration
+/// generator.
+#[must_use]
+pub fn generate_models(tables: &[TableSchema]) -> String {
+    let models: Vec<_> = tables.iter().map(table_to_model).collect();
+    let tokens = quote! {
+        use cot::db::{Auto, LimitedString, model};
+
+        #(#models)*
+    };
+
+    let parsed: syn::File = syn::parse2(tokens).expect("generated models should be valid Rust");
+    prettyplease::unparse(&parsed)
+}
+
+fn table_to_model(table: &TableSchema) -> TokenStream {
+    let name = format_ident!("{}", struct_name(&table.name));
+    let table_name = &table.name;
+    let single_primary_key = table.primary_key().count() == 1;
+
+    let mut docs = vec![format!(" The `{table_name}` table.")];
+    if !single_primary_key {
+        let primary_key: Vec<_> = table
+            .primary_key()
+            .map(|column| format!("`{}`", column.name))
+            .collect();
+        docs.push(String::new());
+        docs.push(if primary_key.is_empty() {
+            " The table has no primary key; mark one of the fields with \
+            `#[model(primary_key)]`."
+                .to_owned()
+        } else {
+            format!(
+                " The table has a composite primary key ({}), which is not \
+                supported; mark one of the fields with `#[model(primary_key)]`.",
+                primary_key.join(", ")
+            )
+        });
+    }
+
+    let indexes = table.indexes.iter().filter_map(|index| {
+        // single-column unique constraints are declared on the fields
+        if index.unique && index.columns.len() == 1 {
+            return None;
+        }
+
+        let kind = if index.unique {
+            format_ident!("unique")
+        } else {
+            format_ident!("index")
+        };
+        let columns = &index.columns;
+        let index_name = &index.name;
+        Some(quote! { #kind(fields = [#(#columns),*], name = #index_name) })
+    });
+    let fields = table.columns.iter().map(|column| {
+        let unique = table.indexes.iter().any(|index| {
+            index.unique && index.columns.len() == 1 && index.columns[0] == column.name
+        });
+        column_to_field(column, single_primary_key, unique)
+    });
+
+    quote! {
+        #(#[doc = #docs])*
+        #[model(model_type = "external", table_name = #table_name #(, #indexes)*)]
+        pub struct #name {
+            #(#fields)*
+        }
+    }
+}
+
+fn column_to_field(column: &ColumnSchema, single_primary_key: bool, unique: bool) -> TokenStream {
+    let mut docs = Vec::new();
+
+    let field_name = column.name.to_snake_case();
+    let field_name = if is_valid_field_name(&column.name) {
+        column.name.clone()
+    } else {
+        docs.push(format!(
+            " The `{}` column name is not a valid field name; rename the column \
+            to match the field.",
+            column.name
+        ));
+        if syn::parse_str::<syn::Ident>(&field_name).is_ok() {
+            field_name
+        } else {
+            format!("{field_name}_")
+        }
+    };
+    let field_name = format_ident!("{field_name}");
+
+    let mut ty = if let Some(column_type) = column.column_type {
+        column_type_to_rust(column_type)
+    } else {
+        docs.push(format!(
+            " The `{}` database type has no Cot equivalent; review the type of \
+            this field.",
+            column.sql_type
+        ));
+        quote! { String }
+    };
+    let primary_key = single_primary_key && column.primary_key;
+    if primary_key && column.auto_increment {
+        ty = quote! { Auto<#ty> };
+    }
+    if column.nullable && !primary_key {
+        ty = quote! { Option<#ty> };
+    }
+
+    let mut attrs = Vec::new();
+    if primary_key {
+        attrs.push(quote! { #[model(primary_key)] });
+    } else if unique {
+        attrs.push(quote! { #[model(unique)] });
+    }
+
+    quote! {
+        #(#[doc = #docs])*
+        #(#attrs)*
+        pub #field_name: #ty,
+    }
+}
+
+fn column_type_to_rust(column_type: ColumnType) -> TokenStream {
+    match column_type {
+        ColumnType::Boolean => quote! { bool },
+        ColumnType::TinyInteger => quote! { i8 },
+        ColumnType::SmallInteger => quote! { i16 },
+        ColumnType::Integer => quote! { i32 },
+        ColumnType::BigInteger => quote! { i64 },
+        ColumnType::TinyUnsignedInteger => quote! { u8 },
+        ColumnType::SmallUnsignedInteger => quote! { u16 },
+        ColumnType::UnsignedInteger => quote! { u32 },
+        ColumnType::BigUnsignedInteger => quote! { u64 },
+        ColumnType::Float => quote! { f32 },
+        ColumnType::Double => quote! { f64 },
+        ColumnType::Time => quote! { chrono::NaiveTime },
+        ColumnType::Date => quote! { chrono::NaiveDate },
+        ColumnType::DateTime => quote! { chrono::NaiveDateTime },
+        ColumnType::DateTimeWithTimeZone => quote! { chrono::DateTime<chrono::FixedOffset> },
+        ColumnType::Text => quote! { String },
+        ColumnType::Blob => quote! { Vec<u8> },
+        ColumnType::String(length) => {
+            let length = proc_macro2::Literal::u32_unsuffixed(length);
+            quote! { LimitedString<#length> }
+        }
+        ColumnType::Json => quote! { cot::db::Json<serde_json::Value> },
+        ColumnType::Uuid => quote! { uuid::Uuid },
+    }
+}
+
+fn struct_name(table_name: &str) -> String {
+    let name = table_name.to_upper_camel_case();
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name
+    } else {
+        format!("Table{name}")
+    }
+}
+
+/// Returns whether the column name can be used as is as the name of a model
+/// field, which is also used as the column name.
+fn is_valid_field_name(name: &str) -> bool {
+    name == name.to_snake_case() && syn::parse_str::<syn::Ident>(name).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use cot::db::introspection::IndexSchema;
+
+    use super::*;
+
+    fn column(name: &str, column_type: Option<ColumnType>) -> ColumnSchema {
+        ColumnSchema::new(name, "integer", column_type)
+    }
+
+    #[test]
+    fn generate_models_simple() {
+        let table = TableSchema::new(
+            "blog_posts",
+            vec![
+                column("id", Some(ColumnType::BigInteger))
+                    .primary_key()
+                    .auto_increment(),
+                column("title", Some(ColumnType::String(200))),
+                column("published_at", Some(ColumnType::DateTimeWithTimeZone)).nullable(),
+            ],
+        );
+
+        let source = generate_models(&[table]);
+
+        assert_eq!(
+            source,
+            r#"use cot::db::{Auto, LimitedString, model};
+/// The `blog_posts` table.
+#[model(model_type = "external", table_name = "blog_posts")]
+pub struct BlogPosts {
+    #[model(primary_key)]
+    pub id: Auto<i64>,
+    pub title: LimitedString<200>,
+    pub published_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn generate_models_indexes() {
+        let table = TableSchema::new(
+            "pages",
+            vec![
+                column("id", Some(ColumnType::Integer)).primary_key(),
+                column("slug", Some(ColumnType::Text)),
+                column("tenant", Some(ColumnType::Integer)),
+                column("title", Some(ColumnType::Text)),
+            ],
+        )
+        .index(IndexSchema::new("pages_title_key", ["title"]).unique())
+        .index(IndexSchema::new("pages_tenant_slug", ["tenant", "slug"]).unique())
+        .index(IndexSchema::new("pages_slug", ["slug"]));
+
+        let source = generate_models(&[table]);
+
+        assert!(source.contains(
+            r#"#[model(
+    model_type = "external",
+    table_name = "pages",
+    unique(fields = ["tenant", "slug"], name = "pages_tenant_slug"),
+    index(fields = ["slug"], name = "pages_slug")
+)]"#
+        ));
+        assert!(source.contains("    #[model(unique)]\n    pub title: String,"));
+        assert!(source.contains("    #[model(primary_key)]\n    pub id: i32,"));
+    }
+
+    #[test]
+    fn generate_models_needs_review() {
+        let table = TableSchema::new(
+            "Order Items",
+            vec![
+                column("OrderId", Some(ColumnType::Integer)).primary_key(),
+                column("type", Some(ColumnType::Text)).primary_key(),
+                column("price", None),
+            ],
+        );
+
+        let source = generate_models(&[table]);
+
+        assert!(source.contains("pub struct OrderItems {"));
+        assert!(source.contains("composite primary key (`OrderId`, `type`)"));
+        assert!(source.contains("The `OrderId` column name is not a valid field name"));
+        assert!(source.contains("pub order_id: i32,"));
+        assert!(source.contains("pub type_: String,"));
+        assert!(source.contains("The `integer` database type has no Cot equivalent"));
+        assert!(!source.contains("    #[model(primary_key)]\n"));
+    }
+
+    #[test]
+    fn struct_name_from_table_name() {
+        assert_eq!(struct_name("users"), "Users");
+        assert_eq!(struct_name("user_profiles"), "UserProfiles");
+        assert_eq!(struct_name("2fa_codes"), "Table2faCodes");
+    }
+
+    #[test]
+    fn inspect_db_sqlite() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let database_path = temp_dir.path().join("legacy.db");
+        let database_url = format!("sqlite://{}?mode=rwc", database_path.display());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let database = Database::new(&database_url).await.unwrap();
+            database
+                .raw("CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR(254) NOT NULL UNIQUE)")
+                .await
+                .unwrap();
+            database
+                .raw("CREATE TABLE cot__migrations (id INTEGER PRIMARY KEY)")
+                .await
+                .unwrap();
+            database.close().await.unwrap();
+        });
+        let output = temp_dir.path().join("models.rs");
+
+        inspect_db(&database_url, Some(&output), &InspectDbOptions::default()).unwrap();
+
+        let source = std::fs::read_to_string(output).unwrap();
+        assert!(source.contains("pub struct Users {"));
+        assert!(source.contains("    #[model(unique)]\n    pub email: LimitedString<254>,"));
+        assert!(!source.contains("cot__migrations"));
+    }
+
+    #[test]
+    fn inspect_db_unknown_table() {
+        let options = InspectDbOptions {
+            tables: vec!["missing".to_owned()],
+        };
+
+        let error = inspect_db("sqlite::memory:", None, &options).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "table `missing` not found in the database"
+        );
+    }
+}
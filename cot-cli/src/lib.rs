@@ -2,6 +2,7 @@
 
 pub mod args;
 pub mod handlers;
+pub mod inspect_db;
 pub mod migration_generator;
 pub mod new_project;
 #[cfg(feature = "test_utils")]
@@ -26,5 +26,6 @@ fn main() -> anyhow::Result<()> {
             MigrationCommands::List(args) => handlers::handle_migration_list(args),
             MigrationCommands::Make(args) => handlers::handle_migration_make(args),
         },
+        Commands::InspectDb(args) => handlers::handle_inspect_db(args),
     }
 }
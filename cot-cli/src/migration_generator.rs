@@ -7,7 +7,9 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Context, bail};
 use cot::db::migrations::{DynMigration, MigrationEngine};
-use cot_codegen::model::{Field, Model, ModelArgs, ModelOpts, ModelType};
+use cot_codegen::model::{
+    Field, ForeignKeyOnDeletePolicy, ForeignKeySpec, Index, Model, ModelArgs, ModelOpts, ModelType,
+};
 use cot_codegen::symbol_resolver::SymbolResolver;
 use darling::FromMeta;
 use heck::ToSnakeCase;
@@ -303,7 +305,7 @@ impl MigrationGenerator {
                                 );
                                 migration_models.push(model_in_source);
                             }
-                            ModelType::Internal => {}
+                            ModelType::Internal | ModelType::External => {}
                         }
 
                         break;
@@ -375,23 +377,62 @@ impl MigrationGenerator {
                     operations.push(MigrationOperationGenerator::make_create_model_operation(
                         app_model,
                     ));
+                    operations.extend(MigrationOperationGenerator::make_many_to_many_operations(
+                        Some(app_model),
+                        None,
+                    ));
+                    operations.extend(MigrationOperationGenerator::make_index_operations(
+                        Some(app_model),
+                        None,
+                    ));
                     modified_models.push(app_model.clone());
                 }
                 (Some(&app_model), Some(&migration_model)) => {
                     if app_model.model.table_name != migration_model.model.table_name
-                        || app_model.model.pk_field != migration_model.model.pk_field
+                        || app_model.model.pk_fields != migration_model.model.pk_fields
                         || app_model.model.fields != migration_model.model.fields
+                        || app_model.model.indexes != migration_model.model.indexes
                     {
                         modified_models.push(app_model.clone());
+                        let (removed_indexes, added_indexes): (Vec<_>, Vec<_>) =
+                            MigrationOperationGenerator::make_index_operations(
+                                Some(app_model),
+                                Some(migration_model),
+                            )
+                            .into_iter()
+                            .partition(|operation| {
+                                matches!(operation, DynOperation::RemoveIndex { .. })
+                            });
+                        // the indexes have to be removed before the fields they
+                        // are created on, and added after them
+                        operations.extend(removed_indexes);
                         operations.extend(
                             MigrationOperationGenerator::make_alter_model_operations(
                                 app_model,
                                 migration_model,
                             ),
                         );
+                        operations.extend(
+                            MigrationOperationGenerator::make_many_to_many_operations(
+                                Some(app_model),
+                                Some(migration_model),
+                            ),
+                        );
+                        operations.extend(added_indexes);
                     }
                 }
                 (None, Some(&migration_model)) => {
+                    // join tables reference the model, so they go first
+                    operations.extend(MigrationOperationGenerator::make_many_to_many_operations(
+                        None,
+                        Some(migration_model),
+                    ));
+                    // removing the indexes explicitly allows them to be
+                    // recreated when the migration is reverted
+                    operations.extend(MigrationOperationGenerator::make_index_operations(
+                        None,
+                        Some(migration_model),
+                    ));
                     operations.push(MigrationOperationGenerator::make_remove_model_operation(
                         migration_model,
                     ));
@@ -507,9 +548,20 @@ impl MigrationGenerator {
         model_source
             .attrs
             .push(syn::parse_quote! {#[derive(::core::fmt::Debug)]});
-        model_source
-            .attrs
-            .push(syn::parse_quote! {#[::cot::db::model(model_type = "migration")]});
+        let indexes = model.model.indexes.iter().map(|index| {
+            let kind = if index.unique {
+                format_ident!("unique")
+            } else {
+                format_ident!("index")
+            };
+            let fields = index.fields.iter().map(ToString::to_string);
+            let name = index.name.as_ref().map(|name| quote! {, name = #name});
+            quote! {#kind(fields = [#(#fields),*] #name)}
+        });
+        let soft_delete = model.model.soft_delete.then(|| quote! {, soft_delete});
+        model_source.attrs.push(syn::parse_quote! {
+            #[::cot::db::model(model_type = "migration" #(, #indexes)* #soft_delete)]
+        });
         quote! {
             #model_source
         }
@@ -594,7 +646,7 @@ impl MigrationOperationGenerator {
         let op = DynOperation::CreateModel {
             table_name: app_model.model.table_name.clone(),
             model_ty: app_model.model.resolved_ty.clone(),
-            fields: app_model.model.fields.clone(),
+            fields: app_model.model.column_fields().cloned().collect(),
         };
         print_status_msg(
             StatusType::Created,
@@ -615,12 +667,12 @@ impl MigrationOperationGenerator {
             &format!("Model '{}'", app_model.model.table_name),
         );
 
-        for field in &app_model.model.fields {
+        for field in app_model.model.column_fields() {
             all_field_names.insert(field.column_name.clone());
             app_model_fields.insert(field.column_name.clone(), field);
         }
         let mut migration_model_fields = HashMap::new();
-        for field in &migration_model.model.fields {
+        for field in migration_model.model.column_fields() {
             all_field_names.insert(field.column_name.clone());
             migration_model_fields.insert(field.column_name.clone(), field);
         }
@@ -676,7 +728,7 @@ impl MigrationOperationGenerator {
         let op = DynOperation::RemoveModel {
             table_name: migration_model.model.table_name.clone(),
             model_ty: migration_model.model.resolved_ty.clone(),
-            fields: migration_model.model.fields.clone(),
+            fields: migration_model.model.column_fields().cloned().collect(),
         };
 
         print_status_msg(
@@ -687,6 +739,165 @@ impl MigrationOperationGenerator {
         op
     }
 
+    /// Generates the operations creating and removing the join tables of
+    /// many-to-many relationship fields that have been added to, or removed
+    /// from, given model.
+    ///
+    /// A relationship whose target model has changed is treated as removed
+    /// and then added again, as the join table has to be recreated.
+    #[must_use]
+    fn make_many_to_many_operations(
+        app_model: Option<&ModelInSource>,
+        migration_model: Option<&ModelInSource>,
+    ) -> Vec<DynOperation> {
+        let app_fields: Vec<_> = app_model
+            .map(|model| model.model.many_to_many_fields().collect())
+            .unwrap_or_default();
+        let migration_fields: Vec<_> = migration_model
+            .map(|model| model.model.many_to_many_fields().collect())
+            .unwrap_or_default();
+
+        let mut operations = Vec::new();
+        if let Some(migration_model) = migration_model {
+            for &field in &migration_fields {
+                if !app_fields.contains(&field) {
+                    operations.push(Self::make_remove_join_table_operation(
+                        migration_model,
+                        field,
+                    ));
+                }
+            }
+        }
+        if let Some(app_model) = app_model {
+            for &field in &app_fields {
+                if !migration_fields.contains(&field) {
+                    operations.push(Self::make_create_join_table_operation(app_model, field));
+                }
+            }
+        }
+
+        operations
+    }
+
+    /// Generates the operations adding and removing the indexes that have been
+    /// added to, or removed from, given model.
+    ///
+    /// An index whose definition has changed is treated as removed and then
+    /// added again.
+    #[must_use]
+    fn make_index_operations(
+        app_model: Option<&ModelInSource>,
+        migration_model: Option<&ModelInSource>,
+    ) -> Vec<DynOperation> {
+        let app_indexes: Vec<_> = app_model
+            .map(|model| model.model.indexes.iter().collect())
+            .unwrap_or_default();
+        let migration_indexes: Vec<_> = migration_model
+            .map(|model| model.model.indexes.iter().collect())
+            .unwrap_or_default();
+
+        let mut operations = Vec::new();
+        if let Some(migration_model) = migration_model {
+            for &index in &migration_indexes {
+                if !app_indexes.contains(&index) {
+                    operations.push(Self::make_remove_index_operation(migration_model, index));
+                }
+            }
+        }
+        if let Some(app_model) = app_model {
+            for &index in &app_indexes {
+                if !migration_indexes.contains(&index) {
+                    operations.push(Self::make_add_index_operation(app_model, index));
+                }
+            }
+        }
+
+        operations
+    }
+
+    #[must_use]
+    fn make_add_index_operation(app_model: &ModelInSource, index: &Index) -> DynOperation {
+        let index_name = index.db_name(&app_model.model.table_name);
+        print_status_msg(
+            StatusType::Adding,
+            &format!("Index '{index_name}' to Model '{}'", app_model.model.name),
+        );
+
+        let op = DynOperation::AddIndex {
+            table_name: app_model.model.table_name.clone(),
+            model_ty: app_model.model.resolved_ty.clone(),
+            index_name: index_name.clone(),
+            index: index.clone(),
+        };
+
+        print_status_msg(
+            StatusType::Added,
+            &format!("Index '{index_name}' to Model '{}'", app_model.model.name),
+        );
+        op
+    }
+
+    #[must_use]
+    fn make_remove_index_operation(migration_model: &ModelInSource, index: &Index) -> DynOperation {
+        let index_name = index.db_name(&migration_model.model.table_name);
+        print_status_msg(
+            StatusType::Removing,
+            &format!(
+                "Index '{index_name}' from Model '{}'",
+                migration_model.model.name
+            ),
+        );
+
+        let op = DynOperation::RemoveIndex {
+            table_name: migration_model.model.table_name.clone(),
+            model_ty: migration_model.model.resolved_ty.clone(),
+            index_name: index_name.clone(),
+            index: index.clone(),
+        };
+
+        print_status_msg(
+            StatusType::Removed,
+            &format!(
+                "Index '{index_name}' from Model '{}'",
+                migration_model.model.name
+            ),
+        );
+        op
+    }
+
+    #[must_use]
+    fn make_create_join_table_operation(app_model: &ModelInSource, field: &Field) -> DynOperation {
+        let table_name = app_model.model.join_table_name(field);
+        print_status_msg(StatusType::Creating, &format!("Join table '{table_name}'"));
+
+        let op = DynOperation::CreateModel {
+            model_ty: join_table_type(&table_name),
+            fields: join_table_fields(app_model, field),
+            table_name: table_name.clone(),
+        };
+
+        print_status_msg(StatusType::Created, &format!("Join table '{table_name}'"));
+        op
+    }
+
+    #[must_use]
+    fn make_remove_join_table_operation(
+        migration_model: &ModelInSource,
+        field: &Field,
+    ) -> DynOperation {
+        let table_name = migration_model.model.join_table_name(field);
+        print_status_msg(StatusType::Removing, &format!("Join table '{table_name}'"));
+
+        let op = DynOperation::RemoveModel {
+            model_ty: join_table_type(&table_name),
+            fields: join_table_fields(migration_model, field),
+            table_name: table_name.clone(),
+        };
+
+        print_status_msg(StatusType::Removed, &format!("Join table '{table_name}'"));
+        op
+    }
+
     #[must_use]
     fn make_add_field_operation(app_model: &ModelInSource, field: &Field) -> DynOperation {
         print_status_msg(
@@ -716,7 +927,7 @@ impl MigrationOperationGenerator {
 
     #[must_use]
     fn make_alter_field_operation(
-        _app_model: &ModelInSource,
+        app_model: &ModelInSource,
         app_field: &Field,
         migration_model: &ModelInSource,
         migration_field: &Field,
@@ -732,9 +943,13 @@ impl MigrationOperationGenerator {
             ),
         );
 
-        todo!();
+        let op = DynOperation::AlterField {
+            table_name: app_model.model.table_name.clone(),
+            model_ty: app_model.model.resolved_ty.clone(),
+            old_field: Box::new(migration_field.clone()),
+            new_field: Box::new(app_field.clone()),
+        };
 
-        #[expect(unreachable_code)]
         print_status_msg(
             StatusType::Modified,
             &format!(
@@ -742,6 +957,8 @@ impl MigrationOperationGenerator {
                 &migration_field.field_name, migration_model.model.name
             ),
         );
+
+        Some(op)
     }
 
     #[must_use]
@@ -1021,12 +1238,24 @@ impl GeneratedMigration {
                         because it doesn't create a new model"
                         )
                     }
+                    DynOperation::AlterField { .. } => {
+                        unreachable!(
+                            "AlterField operation shouldn't be a dependency of CreateModel \
+                        because it doesn't create a new model"
+                        )
+                    }
                     DynOperation::RemoveModel { .. } => {
                         unreachable!(
                             "RemoveModel operation shouldn't be a dependency of CreateModel \
                         because it doesn't create a new model"
                         )
                     }
+                    DynOperation::AddIndex { .. } | DynOperation::RemoveIndex { .. } => {
+                        unreachable!(
+                            "index operations shouldn't be a dependency of CreateModel \
+                        because they don't create a new model"
+                        )
+                    }
                 };
                 trace!(
                     "Removing foreign keys from {} to {}",
@@ -1059,10 +1288,18 @@ impl GeneratedMigration {
                 // RemoveField doesn't create dependencies, it only removes a field
                 unreachable!("RemoveField operation should never create cycles")
             }
+            DynOperation::AlterField { .. } => {
+                // AlterField only changes a field of an already existing model
+                unreachable!("AlterField operation should never create cycles")
+            }
             DynOperation::RemoveModel { .. } => {
                 // RemoveModel doesn't create dependencies, it only removes a model
                 unreachable!("RemoveModel operation should never create cycles")
             }
+            DynOperation::AddIndex { .. } | DynOperation::RemoveIndex { .. } => {
+                // index operations only change an already existing model
+                unreachable!("index operations should never create cycles")
+            }
         }
     }
 
@@ -1161,10 +1398,22 @@ impl GeneratedMigration {
                     // RemoveField Doesnt Add Foreign Keys
                     Vec::new()
                 }
+                DynOperation::AlterField { model_ty, .. } => {
+                    // AlterField doesn't change foreign keys, but the model has to exist
+                    vec![(i, model_ty.clone())]
+                }
                 DynOperation::RemoveModel { .. } => {
                     // RemoveModel Doesnt Add Foreign Keys
                     Vec::new()
                 }
+                DynOperation::AddIndex { model_ty, .. } => {
+                    // AddIndex doesn't add foreign keys, but the model has to exist
+                    vec![(i, model_ty.clone())]
+                }
+                DynOperation::RemoveIndex { .. } => {
+                    // RemoveIndex doesn't add foreign keys
+                    Vec::new()
+                }
             })
             .collect()
     }
@@ -1214,12 +1463,13 @@ impl Repr for Field {
         }
         if let Some(fk_spec) = self.foreign_key.clone() {
             let to_model = &fk_spec.to_model;
+            let on_delete = fk_spec.on_delete.repr();
 
             tokens = quote! {
                 #tokens.foreign_key(
                     <#to_model as ::cot::db::Model>::TABLE_NAME,
                     <#to_model as ::cot::db::Model>::PRIMARY_KEY_NAME,
-                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    #on_delete,
                     ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
                 )
             }
@@ -1232,6 +1482,18 @@ impl Repr for Field {
     }
 }
 
+impl Repr for ForeignKeyOnDeletePolicy {
+    fn repr(&self) -> TokenStream {
+        match self {
+            Self::NoAction => quote! { ::cot::db::ForeignKeyOnDeletePolicy::NoAction },
+            Self::Restrict => quote! { ::cot::db::ForeignKeyOnDeletePolicy::Restrict },
+            Self::Cascade => quote! { ::cot::db::ForeignKeyOnDeletePolicy::Cascade },
+            Self::SetNone => quote! { ::cot::db::ForeignKeyOnDeletePolicy::SetNone },
+            Self::SetDefault => quote! { ::cot::db::ForeignKeyOnDeletePolicy::SetDefault },
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Migration {
     app_name: String,
@@ -1312,11 +1574,93 @@ pub enum DynOperation {
         // boxed to reduce size difference between enum variations
         field: Box<Field>,
     },
+    AlterField {
+        table_name: String,
+        model_ty: syn::Type,
+        // boxed to reduce size difference between enum variations
+        old_field: Box<Field>,
+        new_field: Box<Field>,
+    },
     RemoveModel {
         table_name: String,
         model_ty: syn::Type,
         fields: Vec<Field>,
     },
+    AddIndex {
+        table_name: String,
+        model_ty: syn::Type,
+        index_name: String,
+        index: Index,
+    },
+    RemoveIndex {
+        table_name: String,
+        model_ty: syn::Type,
+        index_name: String,
+        index: Index,
+    },
+}
+
+/// Returns the type used to identify the join table with given name.
+///
+/// Join tables do not have a model type, but [`DynOperation`]s are keyed by
+/// the model type when resolving dependencies between operations, so a unique
+/// placeholder type is used instead.
+fn join_table_type(table_name: &str) -> syn::Type {
+    let ident = format_ident!("{}", table_name);
+    parse_quote!(#ident)
+}
+
+/// Returns the columns of the join table of given many-to-many relationship
+/// field.
+///
+/// The columns must be kept in sync with `cot::db::ManyToManyRef`.
+fn join_table_fields(model: &ModelInSource, field: &Field) -> Vec<Field> {
+    let many_to_many = field
+        .many_to_many
+        .as_ref()
+        .expect("join tables can only be created for many-to-many fields");
+    let foreign_key_field = |name: &str, to_model: &syn::Type| Field {
+        field_name: format_ident!("{}", name),
+        column_name: name.to_owned(),
+        ty: parse_quote!(::cot::db::ForeignKey<#to_model>),
+        auto_value: false,
+        primary_key: false,
+        foreign_key: Some(ForeignKeySpec {
+            to_model: to_model.clone(),
+            on_delete: ForeignKeyOnDeletePolicy::Cascade,
+        }),
+        many_to_many: None,
+        unique: false,
+        auto_now: None,
+    };
+
+    vec![
+        Field {
+            field_name: format_ident!("id"),
+            column_name: "id".to_owned(),
+            ty: parse_quote!(::cot::db::Auto<i32>),
+            auto_value: true,
+            primary_key: true,
+            foreign_key: None,
+            many_to_many: None,
+            unique: false,
+            auto_now: None,
+        },
+        foreign_key_field("source_id", &model.model.resolved_ty),
+        foreign_key_field("target_id", &many_to_many.to_model),
+    ]
+}
+
+/// Returns the `cot::db::Index` expression for given index.
+fn index_repr(index_name: &str, index: &Index) -> TokenStream {
+    let columns = &index.columns;
+    let unique = index.unique.then(|| quote! { .unique() });
+    quote! {
+        ::cot::db::Index::new(
+            ::cot::db::Identifier::new(#index_name),
+            &[#(::cot::db::Identifier::new(#columns),)*],
+        )#unique
+    }
 }
 
 /// Returns whether given [`Field`] is a foreign key to given type.
@@ -1371,6 +1715,22 @@ impl Repr for DynOperation {
                         .build()
                 }
             }
+            Self::AlterField {
+                table_name,
+                old_field,
+                new_field,
+                ..
+            } => {
+                let old_field = old_field.repr();
+                let new_field = new_field.repr();
+                quote! {
+                    ::cot::db::migrations::Operation::alter_field()
+                        .table_name(::cot::db::Identifier::new(#table_name))
+                        .old_field(#old_field)
+                        .new_field(#new_field)
+                        .build()
+                }
+            }
             Self::RemoveModel {
                 table_name, fields, ..
             } => {
@@ -1384,6 +1744,34 @@ impl Repr for DynOperation {
                         .build()
                 }
             }
+            Self::AddIndex {
+                table_name,
+                index_name,
+                index,
+                ..
+            } => {
+                let index = index_repr(index_name, index);
+                quote! {
+                    ::cot::db::migrations::Operation::add_index()
+                        .table_name(::cot::db::Identifier::new(#table_name))
+                        .index(#index)
+                        .build()
+                }
+            }
+            Self::RemoveIndex {
+                table_name,
+                index_name,
+                index,
+                ..
+            } => {
+                let index = index_repr(index_name, index);
+                quote! {
+                    ::cot::db::migrations::Operation::remove_index()
+                        .table_name(::cot::db::Identifier::new(#table_name))
+                        .index(#index)
+                        .build()
+                }
+            }
         }
     }
 }
@@ -1426,8 +1814,6 @@ impl Error for ParsingError {}
 
 #[cfg(test)]
 mod tests {
-    use cot_codegen::model::ForeignKeySpec;
-
     use super::*;
 
     #[test]
@@ -1479,9 +1865,12 @@ mod tests {
                     ty: parse_quote!(i32),
                     auto_value: false,
                     primary_key: false,
+                    many_to_many: None,
                     unique: false,
+                    auto_now: None,
                     foreign_key: Some(ForeignKeySpec {
                         to_model: parse_quote!(Table1),
+                        on_delete: ForeignKeyOnDeletePolicy::Restrict,
                     }),
                 }),
             },
@@ -1519,9 +1908,12 @@ mod tests {
                     ty: parse_quote!(ForeignKey<Table2>),
                     auto_value: false,
                     primary_key: false,
+                    many_to_many: None,
                     unique: false,
+                    auto_now: None,
                     foreign_key: Some(ForeignKeySpec {
                         to_model: parse_quote!(Table2),
+                        on_delete: ForeignKeyOnDeletePolicy::Restrict,
                     }),
                 }],
             },
@@ -1534,9 +1926,12 @@ mod tests {
                     ty: parse_quote!(ForeignKey<Table1>),
                     auto_value: false,
                     primary_key: false,
+                    many_to_many: None,
                     unique: false,
+                    auto_now: None,
                     foreign_key: Some(ForeignKeySpec {
                         to_model: parse_quote!(Table1),
+                        on_delete: ForeignKeyOnDeletePolicy::Restrict,
                     }),
                 }],
             },
@@ -1581,9 +1976,12 @@ mod tests {
                 ty: parse_quote!(ForeignKey<Table2>),
                 auto_value: false,
                 primary_key: false,
+                many_to_many: None,
                 unique: false,
+                auto_now: None,
                 foreign_key: Some(ForeignKeySpec {
                     to_model: parse_quote!(Table2),
+                    on_delete: ForeignKeyOnDeletePolicy::Restrict,
                 }),
             }],
         };
@@ -1636,9 +2034,12 @@ mod tests {
                 ty: parse_quote!(ForeignKey<Table2>),
                 auto_value: false,
                 primary_key: false,
+                many_to_many: None,
                 unique: false,
+                auto_now: None,
                 foreign_key: Some(ForeignKeySpec {
                     to_model: parse_quote!(crate::Table2),
+                    on_delete: ForeignKeyOnDeletePolicy::Restrict,
                 }),
             }],
         }];
@@ -1665,9 +2066,12 @@ mod tests {
                     ty: parse_quote!(ForeignKey<Table2>),
                     auto_value: false,
                     primary_key: false,
+                    many_to_many: None,
                     unique: false,
+                    auto_now: None,
                     foreign_key: Some(ForeignKeySpec {
                         to_model: parse_quote!(my_crate::Table2),
+                        on_delete: ForeignKeyOnDeletePolicy::Restrict,
                     }),
                 }],
             },
@@ -1680,9 +2084,12 @@ mod tests {
                     ty: parse_quote!(ForeignKey<Table4>),
                     auto_value: false,
                     primary_key: false,
+                    many_to_many: None,
                     unique: false,
+                    auto_now: None,
                     foreign_key: Some(ForeignKeySpec {
                         to_model: parse_quote!(crate::Table4),
+                        on_delete: ForeignKeyOnDeletePolicy::Restrict,
                     }),
                 }],
             },
@@ -1714,24 +2121,32 @@ mod tests {
                 resolved_ty: parse_quote!(TestModel),
                 model_type: ModelType::default(),
                 table_name: "test_model".to_string(),
-                pk_field: Field {
+                pk_fields: vec![Field {
                     field_name: format_ident!("id"),
                     column_name: "id".to_string(),
                     ty: parse_quote!(i32),
                     auto_value: true,
                     primary_key: true,
+                    many_to_many: None,
                     unique: false,
+                    auto_now: None,
                     foreign_key: None,
-                },
+                }],
                 fields: vec![Field {
                     field_name: format_ident!("field1"),
                     column_name: "field1".to_string(),
                     ty: parse_quote!(String),
                     auto_value: false,
                     primary_key: false,
+                    many_to_many: None,
                     unique: false,
+                    auto_now: None,
                     foreign_key: None,
                 }],
+                indexes: Vec::new(),
+                soft_delete: false,
+                version_field: None,
+                search_columns: Vec::new(),
             },
         }
     }
@@ -1752,15 +2167,17 @@ mod tests {
                 resolved_ty: parse_quote!(TestModel),
                 model_type: ModelType::default(),
                 table_name: "test_model".to_string(),
-                pk_field: Field {
+                pk_fields: vec![Field {
                     field_name: format_ident!("id"),
                     column_name: "id".to_string(),
                     ty: parse_quote!(i32),
                     auto_value: true,
                     primary_key: true,
+                    many_to_many: None,
                     unique: false,
+                    auto_now: None,
                     foreign_key: None,
-                },
+                }],
                 fields: vec![
                     Field {
                         field_name: format_ident!("field1"),
@@ -1768,7 +2185,9 @@ mod tests {
                         ty: parse_quote!(String),
                         auto_value: false,
                         primary_key: false,
+                        many_to_many: None,
                         unique: false,
+                        auto_now: None,
                         foreign_key: None,
                     },
                     Field {
@@ -1777,10 +2196,16 @@ mod tests {
                         ty: parse_quote!(f32),
                         auto_value: false,
                         primary_key: false,
+                        many_to_many: None,
                         unique: false,
+                        auto_now: None,
                         foreign_key: None,
                     },
                 ],
+                indexes: Vec::new(),
+                soft_delete: false,
+                version_field: None,
+                search_columns: Vec::new(),
             },
         }
     }
@@ -1794,7 +2219,9 @@ mod tests {
             ty: parse_quote!(i32),
             auto_value: false,
             primary_key: false,
+            many_to_many: None,
             unique: false,
+            auto_now: None,
             foreign_key: None,
         };
 
@@ -1943,7 +2370,9 @@ mod tests {
                 ty: parse_quote!(String),
                 auto_value: false,
                 primary_key: false,
+                many_to_many: None,
                 unique: false,
+                auto_now: None,
                 foreign_key: None,
             }),
         };
@@ -1968,6 +2397,32 @@ mod tests {
             "Should call build() but got: {tokens_str}"
         );
     }
+
+    #[test]
+    fn repr_for_foreign_key_on_delete() {
+        let field = Field {
+            field_name: format_ident!("parent"),
+            column_name: "parent".to_string(),
+            ty: parse_quote!(Option<ForeignKey<Parent>>),
+            auto_value: false,
+            primary_key: false,
+            many_to_many: None,
+            unique: false,
+            auto_now: None,
+            foreign_key: Some(ForeignKeySpec {
+                to_model: parse_quote!(crate::Parent),
+                on_delete: ForeignKeyOnDeletePolicy::SetDefault,
+            }),
+        };
+
+        let tokens_str = field.repr().to_string();
+
+        assert!(
+            tokens_str.contains("ForeignKeyOnDeletePolicy :: SetDefault"),
+            "Should use the SetDefault policy but got: {tokens_str}"
+        );
+    }
+
     #[test]
     fn generate_operations_with_removed_field() {
         let app_model = get_test_model();
@@ -1992,6 +2447,98 @@ mod tests {
             "Expected a RemoveField operation for 'field2'"
         );
     }
+    #[test]
+    fn generate_operations_with_changed_indexes() {
+        let mut app_model = get_bigger_test_model();
+        app_model.model.indexes.push(Index {
+            name: None,
+            fields: vec![format_ident!("field1"), format_ident!("field2")],
+            columns: vec!["field1".to_string(), "field2".to_string()],
+            unique: true,
+        });
+        let mut migration_model = get_bigger_test_model();
+        migration_model.model.indexes.push(Index {
+            name: Some("custom_name".to_string()),
+            fields: vec![format_ident!("field1")],
+            columns: vec!["field1".to_string()],
+            unique: false,
+        });
+
+        let (modified_models, operations) =
+            MigrationGenerator::generate_operations(&vec![app_model], &vec![migration_model]);
+
+        assert_eq!(modified_models.len(), 1);
+        assert_eq!(operations.len(), 2);
+        match &operations[0] {
+            DynOperation::RemoveIndex { index_name, .. } => assert_eq!(index_name, "custom_name"),
+            _ => panic!("Expected DynOperation::RemoveIndex"),
+        }
+        match &operations[1] {
+            DynOperation::AddIndex {
+                index_name, index, ..
+            } => {
+                assert_eq!(index_name, "test_model_field1_field2_uniq");
+                assert!(index.unique);
+            }
+            _ => panic!("Expected DynOperation::AddIndex"),
+        }
+
+        let tokens_str = operations[1].repr().to_string();
+        assert!(
+            tokens_str.contains("add_index") && tokens_str.contains(". unique ()"),
+            "Should add a unique index but got: {tokens_str}"
+        );
+    }
+
+    #[test]
+    fn model_to_migration_model_keeps_indexes() {
+        let mut model = get_bigger_test_model();
+        model.model.indexes.push(Index {
+            name: Some("custom_name".to_string()),
+            fields: vec![format_ident!("field1"), format_ident!("field2")],
+            columns: vec!["field1".to_string(), "field2".to_string()],
+            unique: true,
+        });
+
+        let migration_model: syn::ItemStruct =
+            syn::parse2(MigrationGenerator::model_to_migration_model(&model)).unwrap();
+        let attr = migration_model
+            .attrs
+            .iter()
+            .find(|attr| is_model_attr(attr))
+            .unwrap();
+        let args = ModelArgs::from_meta(&attr.meta).unwrap();
+
+        assert_eq!(args.model_type, ModelType::Migration);
+        assert!(args.index.is_empty());
+        assert_eq!(args.unique.len(), 1);
+        assert_eq!(args.unique[0].name.as_deref(), Some("custom_name"));
+        let fields: Vec<_> = args.unique[0]
+            .fields
+            .iter()
+            .map(syn::LitStr::value)
+            .collect();
+        assert_eq!(fields, ["field1", "field2"]);
+    }
+
+    #[test]
+    fn model_to_migration_model_keeps_soft_delete() {
+        let mut model = get_test_model();
+        model.model.soft_delete = true;
+
+        let migration_model: syn::ItemStruct =
+            syn::parse2(MigrationGenerator::model_to_migration_model(&model)).unwrap();
+        let attr = migration_model
+            .attrs
+            .iter()
+            .find(|attr| is_model_attr(attr))
+            .unwrap();
+        let args = ModelArgs::from_meta(&attr.meta).unwrap();
+
+        assert_eq!(args.model_type, ModelType::Migration);
+        assert!(args.soft_delete.is_present());
+    }
+
     #[test]
     fn get_migration_list() {
         let tempdir = tempfile::tempdir().unwrap();
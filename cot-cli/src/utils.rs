@@ -28,7 +28,6 @@ pub(crate) enum StatusType {
     // Status types
     #[expect(dead_code)]
     Error, // Should be used in Error handling inside remove operations
-    #[expect(dead_code)]
     Warning, // Should be used as cautionary messages.
     Notice,
 }
@@ -160,6 +160,97 @@ fn create_models_foreign_key_two_migrations() {
     assert_eq!(table_name, "cot__child");
 }
 
+#[test]
+fn alter_field_two_migrations() {
+    let generator = test_generator();
+
+    let src = include_str!("migration_generator/alter_field/step_1.rs");
+    let source_files = vec![SourceFile::parse(PathBuf::from("main.rs"), src).unwrap()];
+    let migration_file = generator
+        .generate_migrations_as_source_from_files(source_files)
+        .unwrap()
+        .unwrap();
+
+    let src = include_str!("migration_generator/alter_field/step_2.rs");
+    let source_files = vec![
+        SourceFile::parse(PathBuf::from("main.rs"), src).unwrap(),
+        SourceFile::parse(PathBuf::from(&migration_file.name), &migration_file.content).unwrap(),
+    ];
+    let migration = generator
+        .generate_migrations_as_generated_from_files(source_files)
+        .unwrap()
+        .unwrap();
+
+    assert!(migration.migration_name.starts_with("m_0002_auto"));
+    assert_eq!(migration.operations.len(), 2);
+
+    let mut operations: Vec<_> = migration
+        .operations
+        .iter()
+        .map(unwrap_alter_field)
+        .collect();
+    operations.sort_by(|(_, a, _), (_, b, _)| a.column_name.cmp(&b.column_name));
+
+    let (table_name, old_field, new_field) = &operations[0];
+    assert_eq!(*table_name, "cot__my_model");
+    assert_eq!(old_field.column_name, "count");
+    assert_eq!(old_field.ty, parse_quote!(i32));
+    assert_eq!(new_field.ty, parse_quote!(i64));
+
+    let (table_name, old_field, new_field) = &operations[1];
+    assert_eq!(*table_name, "cot__my_model");
+    assert_eq!(old_field.column_name, "name");
+    assert!(!old_field.unique);
+    assert!(new_field.unique);
+    assert_eq!(new_field.ty, parse_quote!(Option<String>));
+}
+
+#[test]
+fn many_to_many_two_migrations() {
+    let generator = test_generator();
+
+    let src = include_str!("migration_generator/many_to_many/step_1.rs");
+    let source_files = vec![SourceFile::parse(PathBuf::from("main.rs"), src).unwrap()];
+    let migration_file = generator
+        .generate_migrations_as_source_from_files(source_files.clone())
+        .unwrap()
+        .unwrap();
+    let migration = generator
+        .generate_migrations_as_generated_from_files(source_files)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(migration.operations.len(), 3);
+    let (table_name, fields) = unwrap_create_model(&migration.operations[2]);
+    assert_eq!(table_name, "cot__post_tags");
+    let column_names: Vec<_> = fields.iter().map(|field| &field.column_name).collect();
+    assert_eq!(column_names, ["id", "source_id", "target_id"]);
+    assert_eq!(
+        fields[2].foreign_key.as_ref().unwrap().to_model,
+        parse_quote!(crate::Tag)
+    );
+    for op in &migration.operations[..2] {
+        let (_, fields) = unwrap_create_model(op);
+        assert_eq!(fields.len(), 1);
+    }
+
+    let src = include_str!("migration_generator/many_to_many/step_2.rs");
+    let source_files = vec![
+        SourceFile::parse(PathBuf::from("main.rs"), src).unwrap(),
+        SourceFile::parse(PathBuf::from(&migration_file.name), &migration_file.content).unwrap(),
+    ];
+    let migration = generator
+        .generate_migrations_as_generated_from_files(source_files)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(migration.operations.len(), 1);
+    let DynOperation::RemoveModel { table_name, .. } = &migration.operations[0] else {
+        panic!("expected remove model operation");
+    };
+    assert_eq!(table_name, "cot__post_tags");
+}
+
 /// Test that the migration generator can generate a "create model" migration
 /// for a given model which compiles successfully.
 #[test]
@@ -330,3 +421,19 @@ fn unwrap_add_field(op: &DynOperation) -> (&str, cot_codegen::model::Field) {
         panic!("expected create model operation");
     }
 }
+
+fn unwrap_alter_field(
+    op: &DynOperation,
+) -> (&str, cot_codegen::model::Field, cot_codegen::model::Field) {
+    if let DynOperation::AlterField {
+        table_name,
+        old_field,
+        new_field,
+        ..
+    } = op
+    {
+        (table_name, *old_field.clone(), *new_field.clone())
+    } else {
+        panic!("expected alter field operation");
+    }
+}
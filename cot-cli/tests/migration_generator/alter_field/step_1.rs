@@ -0,0 +1,11 @@
+use cot::db::{model, Auto};
+
+#[model]
+struct MyModel {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    count: i32,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use cot::db::{model, Auto};
+
+#[model]
+struct MyModel {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    count: i64,
+    #[model(unique)]
+    name: Option<String>,
+}
+
+fn main() {}
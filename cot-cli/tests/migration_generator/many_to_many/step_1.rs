@@ -0,0 +1,16 @@
+use cot::db::{model, Auto, ManyToMany};
+
+#[model]
+struct Post {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    tags: ManyToMany<Tag>,
+}
+
+#[model]
+struct Tag {
+    #[model(primary_key)]
+    id: Auto<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use cot::db::{model, Auto};
+
+#[model]
+struct Post {
+    #[model(primary_key)]
+    id: Auto<i32>,
+}
+
+#[model]
+struct Tag {
+    #[model(primary_key)]
+    id: Auto<i32>,
+}
+
+fn main() {}
@@ -63,6 +63,14 @@ fn help_migration_make() {
     );
 }
 
+#[test]
+fn help_inspect_db() {
+    insta::with_settings!(
+        { filters => GENERIC_FILTERS.to_owned() },
+        { assert_cmd_snapshot!(cot_cli!("help", "inspect-db")) }
+    );
+}
+
 #[test]
 fn help_cli_manpages() {
     insta::with_settings!(
@@ -284,6 +284,10 @@ impl BindingPriority<u8, u8> {
 
 type InfixBindingPriority = BindingPriority<u8, u8>;
 
+/// The binding priority of the prefix `!` operator. It binds tighter than any
+/// infix operator, but not tighter than method calls, just like in Rust.
+const PREFIX_BINDING_PRIORITY: u8 = 19;
+
 /// A parsed expression.
 ///
 /// This type represents a parsed expression that can be used to generate code.
@@ -327,6 +331,7 @@ pub enum Expr {
     },
     And(Box<Expr>, Box<Expr>),
     Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
     Eq(Box<Expr>, Box<Expr>),
     Ne(Box<Expr>, Box<Expr>),
     Lt(Box<Expr>, Box<Expr>),
@@ -356,6 +361,9 @@ impl Expr {
             let content;
             let _ = syn::parenthesized!(content in input);
             Self::parse_impl(&content, 0)?
+        } else if input.peek(Token![!]) && !input.peek(Token![!=]) {
+            input.parse::<Token![!]>()?;
+            Expr::Not(Box::new(Self::parse_impl(input, PREFIX_BINDING_PRIORITY)?))
         } else {
             let lhs_item = input.parse::<ItemToken>()?;
 
@@ -514,6 +522,10 @@ impl Expr {
                 let rhs_tokens = rhs.as_tokens_impl(mode)?;
                 Some(quote! {#lhs_tokens || #rhs_tokens})
             }
+            Expr::Not(expr) => {
+                let expr_tokens = expr.as_tokens_impl(mode)?;
+                Some(quote! {!#expr_tokens})
+            }
             Expr::Eq(lhs, rhs) => {
                 let lhs_tokens = lhs.as_tokens_impl(mode)?;
                 let rhs_tokens = rhs.as_tokens_impl(mode)?;
@@ -649,6 +661,34 @@ mod tests {
         assert_eq!(expected, unwrap_syn(Expr::parse(input)));
     }
 
+    #[test]
+    fn not() {
+        let input = quote! { !$a == 42 || !($b == 42) };
+        let expected = Expr::Or(
+            Box::new(Expr::Eq(
+                Box::new(Expr::Not(Box::new(field("a")))),
+                Box::new(Expr::Value(parse_quote!(42))),
+            )),
+            Box::new(Expr::Not(Box::new(Expr::Eq(
+                Box::new(field("b")),
+                Box::new(Expr::Value(parse_quote!(42))),
+            )))),
+        );
+
+        assert_eq!(expected, unwrap_syn(Expr::parse(input)));
+    }
+
+    #[test]
+    fn not_method_call() {
+        let input = quote! { !$a.is_null() };
+        let expected = Expr::Not(Box::new(Expr::FunctionCall {
+            function: Box::new(member_access(field("a"), "is_null")),
+            args: Vec::new(),
+        }));
+
+        assert_eq!(expected, unwrap_syn(Expr::parse(input)));
+    }
+
     #[test]
     fn parenthesis_literal() {
         let input = quote! { (((($a)))) };
@@ -829,6 +869,14 @@ mod tests {
         assert_eq!(input.to_string(), expr.as_tokens().unwrap().to_string());
     }
 
+    #[test]
+    fn tokens_not() {
+        let input = quote! { !x };
+        let expr = unwrap_syn(Expr::parse(input.clone()));
+
+        assert_eq!(input.to_string(), expr.as_tokens().unwrap().to_string());
+    }
+
     #[test]
     fn tokens_and() {
         let input = quote! { x && y };
@@ -11,6 +11,50 @@ pub struct ModelArgs {
     #[darling(default)]
     pub model_type: ModelType,
     pub table_name: Option<String>,
+    /// The indexes declared with `#[model(index(fields = [...]))]`.
+    #[darling(multiple)]
+    pub index: Vec<IndexArgs>,
+    /// The unique constraints declared with `#[model(unique(fields = [...]))]`.
+    #[darling(multiple)]
+    pub unique: Vec<IndexArgs>,
+    /// Whether the model uses soft deletes (`#[model(soft_delete)]`).
+    pub soft_delete: darling::util::Flag,
+    /// Whether the model implements `ModelHooks` (`#[model(hooks)]`).
+    pub hooks: darling::util::Flag,
+    /// The fields used for the full-text search, declared with
+    /// `#[model(search(fields = [...]))]`.
+    pub search: Option<SearchArgs>,
+}
+
+/// The arguments of an `index(...)` or `unique(...)` model parameter.
+#[derive(Debug, Clone, FromMeta)]
+pub struct IndexArgs {
+    pub fields: Vec<syn::LitStr>,
+    pub name: Option<String>,
+}
+
+/// The arguments of the `search(...)` model parameter.
+#[derive(Debug, Clone, FromMeta)]
+pub struct SearchArgs {
+    pub fields: Vec<syn::LitStr>,
+}
+
+/// The maximum number of fields in a composite primary key.
+const MAX_PRIMARY_KEY_FIELDS: usize = 4;
+
+/// The name of the field (and the column) storing the deletion time of the
+/// models declared with `#[model(soft_delete)]`.
+pub const SOFT_DELETE_FIELD_NAME: &str = "deleted_at";
+
+/// Returns the type of the field storing the deletion time of the models
+/// declared with `#[model(soft_delete)]`.
+#[must_use]
+pub fn soft_delete_field_type() -> syn::Type {
+    syn::parse_quote! {
+        ::core::option::Option<
+            ::cot::__private::chrono::DateTime<::cot::__private::chrono::Utc>
+        >
+    }
 }
 
 #[expect(clippy::module_name_repetitions)]
@@ -20,6 +64,7 @@ pub enum ModelType {
     Application,
     Migration,
     Internal,
+    External,
 }
 
 #[expect(clippy::module_name_repetitions)]
@@ -74,11 +119,15 @@ impl ModelOpts {
         #[cfg(not(feature = "symbol-resolver"))]
         let as_field = |field: &&FieldOpts| field.as_field();
 
-        let fields = self
+        let mut fields = self
             .fields()
             .iter()
             .map(as_field)
             .collect::<Result<Vec<_>, _>>()?;
+        let soft_delete = args.soft_delete.is_present();
+        if soft_delete {
+            fields.push(Self::soft_delete_field(&fields)?);
+        }
 
         let mut original_name = self.ident.to_string();
         if args.model_type == ModelType::Migration {
@@ -98,7 +147,24 @@ impl ModelOpts {
             original_name.to_string().to_snake_case()
         };
 
-        let primary_key_field = self.get_primary_key_field(&fields)?;
+        let primary_key_fields = self.get_primary_key_fields(&fields)?;
+        let version_field = self.get_version_field(&fields)?.cloned();
+        let indexes = args
+            .index
+            .iter()
+            .map(|index| Self::as_index(index, &fields, false))
+            .chain(
+                args.unique
+                    .iter()
+                    .map(|index| Self::as_index(index, &fields, true)),
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        let search_columns = args
+            .search
+            .as_ref()
+            .map(|search| Self::as_search_columns(search, &fields))
+            .transpose()?
+            .unwrap_or_default();
 
         #[cfg(feature = "symbol-resolver")]
         let ty = {
@@ -118,13 +184,121 @@ impl ModelOpts {
             resolved_ty: ty,
             model_type: args.model_type,
             table_name,
-            pk_field: primary_key_field.clone(),
+            pk_fields: primary_key_fields,
             fields,
+            indexes,
+            soft_delete,
+            version_field,
+            search_columns,
+        })
+    }
+
+    /// Returns the `deleted_at` field added to the models declared with
+    /// `#[model(soft_delete)]`.
+    fn soft_delete_field(fields: &[Field]) -> Result<Field, syn::Error> {
+        if let Some(field) = fields
+            .iter()
+            .find(|field| field.field_name == SOFT_DELETE_FIELD_NAME)
+        {
+            return Err(syn::Error::new(
+                field.field_name.span(),
+                format!(
+                    "the `{SOFT_DELETE_FIELD_NAME}` field is added automatically to the models \
+                    with `soft_delete` and cannot be declared explicitly"
+                ),
+            ));
+        }
+
+        Ok(Field {
+            field_name: syn::Ident::new(SOFT_DELETE_FIELD_NAME, proc_macro2::Span::call_site()),
+            column_name: SOFT_DELETE_FIELD_NAME.to_owned(),
+            ty: soft_delete_field_type(),
+            #[cfg(feature = "symbol-resolver")]
+            auto_value: false,
+            primary_key: false,
+            #[cfg(feature = "symbol-resolver")]
+            foreign_key: None,
+            many_to_many: None,
+            unique: false,
+            auto_now: None,
         })
     }
 
-    fn get_primary_key_field<'a>(&self, fields: &'a [Field]) -> Result<&'a Field, syn::Error> {
-        let pks: Vec<_> = fields.iter().filter(|field| field.primary_key).collect();
+    fn as_index(args: &IndexArgs, fields: &[Field], unique: bool) -> Result<Index, syn::Error> {
+        if args.fields.is_empty() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "indexes must contain at least one field",
+            ));
+        }
+
+        let mut index_fields = Vec::with_capacity(args.fields.len());
+        let mut columns = Vec::with_capacity(args.fields.len());
+        for field_name in &args.fields {
+            let field = fields
+                .iter()
+                .find(|field| field.field_name == field_name.value())
+                .ok_or_else(|| {
+                    syn::Error::new(
+                        field_name.span(),
+                        format!("unknown field `{}` in the index", field_name.value()),
+                    )
+                })?;
+            if field.many_to_many.is_some() {
+                return Err(syn::Error::new(
+                    field_name.span(),
+                    "many-to-many fields cannot be used in indexes",
+                ));
+            }
+            index_fields.push(field.field_name.clone());
+            columns.push(field.column_name.clone());
+        }
+
+        Ok(Index {
+            name: args.name.clone(),
+            fields: index_fields,
+            columns,
+            unique,
+        })
+    }
+
+    fn as_search_columns(args: &SearchArgs, fields: &[Field]) -> Result<Vec<String>, syn::Error> {
+        if args.fields.is_empty() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "search must contain at least one field",
+            ));
+        }
+
+        args.fields
+            .iter()
+            .map(|field_name| {
+                let field = fields
+                    .iter()
+                    .find(|field| field.field_name == field_name.value())
+                    .ok_or_else(|| {
+                        syn::Error::new(
+                            field_name.span(),
+                            format!("unknown field `{}` in the search", field_name.value()),
+                        )
+                    })?;
+                if field.many_to_many.is_some() {
+                    return Err(syn::Error::new(
+                        field_name.span(),
+                        "many-to-many fields cannot be searched",
+                    ));
+                }
+                Ok(field.column_name.clone())
+            })
+            .collect()
+    }
+
+    fn get_primary_key_fields(&self, fields: &[Field]) -> Result<Vec<Field>, syn::Error> {
+        let pks: Vec<_> = fields
+            .iter()
+            .filter(|field| field.primary_key)
+            .cloned()
+            .collect();
         if pks.is_empty() {
             return Err(syn::Error::new(
                 self.ident.span(),
@@ -133,13 +307,50 @@ impl ModelOpts {
             ));
         }
         if pks.len() > 1 {
+            Self::validate_composite_primary_key(&pks, fields)?;
+        }
+
+        Ok(pks)
+    }
+
+    fn validate_composite_primary_key(pks: &[Field], fields: &[Field]) -> Result<(), syn::Error> {
+        if pks.len() > MAX_PRIMARY_KEY_FIELDS {
+            return Err(syn::Error::new(
+                pks[MAX_PRIMARY_KEY_FIELDS].field_name.span(),
+                format!(
+                    "composite primary keys can consist of at most {MAX_PRIMARY_KEY_FIELDS} fields"
+                ),
+            ));
+        }
+        if let Some(field) = pks.iter().find(|field| last_segment_is(&field.ty, "Auto")) {
             return Err(syn::Error::new(
-                pks[1].field_name.span(),
-                "composite primary keys are not supported; only one primary key field is allowed",
+                field.field_name.span(),
+                "`Auto` fields cannot be a part of a composite primary key",
+            ));
+        }
+        if let Some(field) = fields.iter().find(|field| field.many_to_many.is_some()) {
+            return Err(syn::Error::new(
+                field.field_name.span(),
+                "many-to-many fields cannot be used in the models with a composite primary key",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn get_version_field<'a>(&self, fields: &'a [Field]) -> Result<Option<&'a Field>, syn::Error> {
+        let mut version_fields = std::iter::zip(self.fields(), fields)
+            .filter(|(field_opts, _)| field_opts.version.is_present())
+            .map(|(_, field)| field);
+        let version_field = version_fields.next();
+        if let Some(field) = version_fields.next() {
+            return Err(syn::Error::new(
+                field.field_name.span(),
+                "only one field can be marked with `#[model(version)]`",
             ));
         }
 
-        Ok(pks[0])
+        Ok(version_field)
     }
 }
 
@@ -150,6 +361,10 @@ pub struct FieldOpts {
     pub ty: syn::Type,
     pub primary_key: darling::util::Flag,
     pub unique: darling::util::Flag,
+    pub on_delete: Option<ForeignKeyOnDeletePolicy>,
+    pub auto_now: darling::util::Flag,
+    pub auto_now_add: darling::util::Flag,
+    pub version: darling::util::Flag,
 }
 
 impl FieldOpts {
@@ -201,6 +416,34 @@ impl FieldOpts {
         })
     }
 
+    fn validate_on_delete(&self) -> Result<(), syn::Error> {
+        let Some(on_delete) = self.on_delete else {
+            return Ok(());
+        };
+
+        let inner_ty = option_inner_type(&self.ty);
+        if !last_segment_is(inner_ty.unwrap_or(&self.ty), "ForeignKey") {
+            return Err(syn::Error::new(
+                self.ty.span(),
+                "`on_delete` can only be specified for `ForeignKey` fields",
+            ));
+        }
+        if inner_ty.is_none()
+            && matches!(
+                on_delete,
+                ForeignKeyOnDeletePolicy::SetNone | ForeignKeyOnDeletePolicy::SetDefault
+            )
+        {
+            return Err(syn::Error::new(
+                self.ty.span(),
+                "`on_delete = \"set_null\"` and `on_delete = \"set_default\"` require \
+                the foreign key to be wrapped in an `Option`",
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Convert the field options into a field.
     ///
     /// # Panics
@@ -219,9 +462,49 @@ impl FieldOpts {
             self.find_type("cot::db::Auto", symbol_resolver).is_some(),
             self.find_type("cot::db::ForeignKey", symbol_resolver)
                 .map(ForeignKeySpec::try_from)
-                .transpose()?,
+                .transpose()?
+                .map(|spec| ForeignKeySpec {
+                    on_delete: self.on_delete.unwrap_or_default(),
+                    ..spec
+                }),
         );
         let is_primary_key = self.primary_key.is_present();
+        self.validate_on_delete()?;
+
+        #[cfg(feature = "symbol-resolver")]
+        let resolved_ty = {
+            let mut ty = self.ty.clone();
+            symbol_resolver.resolve(&mut ty);
+            ty
+        };
+        #[cfg(not(feature = "symbol-resolver"))]
+        let resolved_ty = self.ty.clone();
+        let many_to_many = ManyToManySpec::from_type(&resolved_ty)?;
+        if many_to_many.is_some() && (is_primary_key || self.unique.is_present()) {
+            return Err(syn::Error::new(
+                name.span(),
+                "many-to-many fields cannot be primary keys or unique",
+            ));
+        }
+
+        let auto_now = self.auto_now_kind()?;
+        if auto_now.is_some() && (is_primary_key || many_to_many.is_some()) {
+            return Err(syn::Error::new(
+                name.span(),
+                "`auto_now` and `auto_now_add` cannot be used on primary keys or many-to-many \
+                fields",
+            ));
+        }
+
+        if self.version.is_present()
+            && (is_primary_key || many_to_many.is_some() || auto_now.is_some())
+        {
+            return Err(syn::Error::new(
+                name.span(),
+                "`version` cannot be used on primary keys, many-to-many fields, or fields with \
+                `auto_now` or `auto_now_add`",
+            ));
+        }
 
         Ok(Field {
             field_name: name.clone(),
@@ -232,9 +515,23 @@ impl FieldOpts {
             primary_key: is_primary_key,
             #[cfg(feature = "symbol-resolver")]
             foreign_key,
+            many_to_many,
             unique: self.unique.is_present(),
+            auto_now,
         })
     }
+
+    fn auto_now_kind(&self) -> Result<Option<AutoNowKind>, syn::Error> {
+        match (self.auto_now.is_present(), self.auto_now_add.is_present()) {
+            (true, true) => Err(syn::Error::new(
+                self.auto_now_add.span(),
+                "`auto_now` and `auto_now_add` cannot be used together",
+            )),
+            (true, false) => Ok(Some(AutoNowKind::Always)),
+            (false, true) => Ok(Some(AutoNowKind::OnCreate)),
+            (false, false) => Ok(None),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -247,8 +544,19 @@ pub struct Model {
     pub resolved_ty: syn::Type,
     pub model_type: ModelType,
     pub table_name: String,
-    pub pk_field: Field,
+    /// The primary key fields; more than one for the models with a composite
+    /// primary key.
+    pub pk_fields: Vec<Field>,
     pub fields: Vec<Field>,
+    pub indexes: Vec<Index>,
+    /// Whether the model uses soft deletes (`#[model(soft_delete)]`); if so,
+    /// the `deleted_at` field is the last one in [`Self::fields`].
+    pub soft_delete: bool,
+    /// The field marked with `#[model(version)]`, used for optimistic locking.
+    pub version_field: Option<Field>,
+    /// The columns used for the full-text search
+    /// (`#[model(search(fields = [...]))]`).
+    pub search_columns: Vec<String>,
 }
 
 impl Model {
@@ -256,6 +564,28 @@ impl Model {
     pub fn field_count(&self) -> usize {
         self.fields.len()
     }
+
+    /// Returns the fields that are stored as columns in the model's table,
+    /// i.e. all the fields except many-to-many relationships.
+    pub fn column_fields(&self) -> impl Iterator<Item = &Field> {
+        self.fields
+            .iter()
+            .filter(|field| field.many_to_many.is_none())
+    }
+
+    /// Returns the many-to-many relationship fields of the model.
+    pub fn many_to_many_fields(&self) -> impl Iterator<Item = &Field> {
+        self.fields
+            .iter()
+            .filter(|field| field.many_to_many.is_some())
+    }
+
+    /// Returns the name of the join table of a many-to-many relationship
+    /// field of this model.
+    #[must_use]
+    pub fn join_table_name(&self, field: &Field) -> String {
+        format!("{}_{}", self.table_name, field.column_name)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -271,53 +601,166 @@ pub struct Field {
     /// determined not to be a foreign key.
     #[cfg(feature = "symbol-resolver")]
     pub foreign_key: Option<ForeignKeySpec>,
+    /// [`Some`] if this field is a many-to-many relationship (and hence is not
+    /// stored as a column in the model's table); [`None`] otherwise.
+    pub many_to_many: Option<ManyToManySpec>,
     pub unique: bool,
+    /// [`Some`] if the field is set to the current time automatically when
+    /// saving the model (`#[model(auto_now)]` or `#[model(auto_now_add)]`).
+    pub auto_now: Option<AutoNowKind>,
+}
+
+/// When a field marked with `auto_now` or `auto_now_add` is set to the
+/// current time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AutoNowKind {
+    /// Every time the model is saved (`#[model(auto_now)]`).
+    Always,
+    /// Only when the model is created (`#[model(auto_now_add)]`).
+    OnCreate,
+}
+
+/// An index, or a unique constraint, on one or more fields of a model.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Index {
+    /// The name of the index, if specified explicitly with `name = "..."`.
+    pub name: Option<String>,
+    pub fields: Vec<syn::Ident>,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+impl Index {
+    /// Returns the name of the index in the database, given the (full) name
+    /// of the table of the model.
+    #[must_use]
+    pub fn db_name(&self, table_name: &str) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            let suffix = if self.unique { "uniq" } else { "idx" };
+            format!("{table_name}_{}_{suffix}", self.columns.join("_"))
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ForeignKeySpec {
     pub to_model: syn::Type,
+    pub on_delete: ForeignKeyOnDeletePolicy,
+}
+
+/// The codegen counterpart of `cot::db::ForeignKeyOnDeletePolicy`, as
+/// specified with `#[model(on_delete = "...")]`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, FromMeta)]
+pub enum ForeignKeyOnDeletePolicy {
+    NoAction,
+    #[default]
+    Restrict,
+    Cascade,
+    #[darling(rename = "set_null")]
+    SetNone,
+    SetDefault,
 }
 
 impl TryFrom<syn::Type> for ForeignKeySpec {
     type Error = syn::Error;
 
     fn try_from(ty: syn::Type) -> Result<Self, Self::Error> {
-        let syn::Type::Path(type_path) = &ty else {
-            panic!("Expected a path type for a foreign key");
-        };
+        Ok(Self {
+            to_model: single_type_argument(&ty, "ForeignKey")?,
+            on_delete: ForeignKeyOnDeletePolicy::default(),
+        })
+    }
+}
 
-        let syn::PathArguments::AngleBracketed(args) = &type_path
-            .path
-            .segments
-            .last()
-            .expect("type path must have at least one segment")
-            .arguments
-        else {
-            return Err(syn::Error::new(
-                ty.span(),
-                "expected ForeignKey to have angle-bracketed generic arguments",
-            ));
-        };
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManyToManySpec {
+    pub to_model: syn::Type,
+}
 
-        if args.args.len() != 1 {
-            return Err(syn::Error::new(
-                ty.span(),
-                "expected ForeignKey to have only one generic parameter",
-            ));
+impl ManyToManySpec {
+    /// Returns the many-to-many relationship spec if the given type is a
+    /// `ManyToMany<T>`, or [`None`] otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the type is a `ManyToMany` with invalid generic
+    /// arguments.
+    pub fn from_type(ty: &syn::Type) -> Result<Option<Self>, syn::Error> {
+        if !last_segment_is(ty, "ManyToMany") {
+            return Ok(None);
         }
 
-        let inner = &args.args[0];
-        if let syn::GenericArgument::Type(ty) = inner {
-            Ok(Self {
-                to_model: ty.clone(),
-            })
-        } else {
-            Err(syn::Error::new(
-                ty.span(),
-                "expected ForeignKey to have a type generic argument",
-            ))
-        }
+        Ok(Some(Self {
+            to_model: single_type_argument(ty, "ManyToMany")?,
+        }))
+    }
+}
+
+/// Returns `T` if given type is an `Option<T>`, or [`None`] otherwise.
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    if !last_segment_is(ty, "Option") {
+        return None;
+    }
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let syn::PathArguments::AngleBracketed(args) = &type_path.path.segments.last()?.arguments
+    else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// Returns whether given type is a path type whose last segment has given
+/// name, such as `cot::db::ForeignKey<T>` for `ForeignKey`.
+fn last_segment_is(ty: &syn::Type, name: &str) -> bool {
+    let syn::Type::Path(type_path) = ty else {
+        return false;
+    };
+    type_path
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == name)
+}
+
+/// Returns the only generic type argument of given path type, such as `T` in
+/// `ForeignKey<T>`.
+fn single_type_argument(ty: &syn::Type, type_name: &str) -> Result<syn::Type, syn::Error> {
+    let syn::Type::Path(type_path) = ty else {
+        panic!("Expected a path type for {type_name}");
+    };
+
+    let syn::PathArguments::AngleBracketed(args) = &type_path
+        .path
+        .segments
+        .last()
+        .expect("type path must have at least one segment")
+        .arguments
+    else {
+        return Err(syn::Error::new(
+            ty.span(),
+            format!("expected {type_name} to have angle-bracketed generic arguments"),
+        ));
+    };
+
+    if args.args.len() != 1 {
+        return Err(syn::Error::new(
+            ty.span(),
+            format!("expected {type_name} to have only one generic parameter"),
+        ));
+    }
+
+    if let syn::GenericArgument::Type(ty) = &args.args[0] {
+        Ok(ty.clone())
+    } else {
+        Err(syn::Error::new(
+            ty.span(),
+            format!("expected {type_name} to have a type generic argument"),
+        ))
     }
 }
 
@@ -414,6 +857,178 @@ mod tests {
         assert!(model.fields[0].primary_key);
     }
 
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_indexes() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(index(fields = ["slug"]), unique(fields = ["tenant_id", "slug"], name = "tenant_slug"))]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                tenant_id: i32,
+                slug: String,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let model = opts.as_model(&args, &SymbolResolver::new(vec![])).unwrap();
+
+        assert_eq!(model.indexes.len(), 2);
+        assert_eq!(model.indexes[0].columns, ["slug"]);
+        assert!(!model.indexes[0].unique);
+        assert_eq!(
+            model.indexes[0].db_name("app__test_model"),
+            "app__test_model_slug_idx"
+        );
+        assert_eq!(model.indexes[1].columns, ["tenant_id", "slug"]);
+        assert!(model.indexes[1].unique);
+        assert_eq!(model.indexes[1].db_name("app__test_model"), "tenant_slug");
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_index_unknown_field() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(unique(fields = ["name"]))]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let err = opts
+            .as_model(&args, &SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "unknown field `name` in the index");
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_search() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(search(fields = ["title", "body"]))]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                title: String,
+                body: String,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let model = opts.as_model(&args, &SymbolResolver::new(vec![])).unwrap();
+
+        assert_eq!(model.search_columns, ["title", "body"]);
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_search_unknown_field() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(search(fields = ["title"]))]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let err = opts
+            .as_model(&args, &SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "unknown field `title` in the search");
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_soft_delete() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(soft_delete)]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                name: String,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let model = opts.as_model(&args, &SymbolResolver::new(vec![])).unwrap();
+
+        assert!(model.soft_delete);
+        assert_eq!(model.fields.len(), 3);
+        let deleted_at = model.fields.last().unwrap();
+        assert_eq!(deleted_at.field_name.to_string(), "deleted_at");
+        assert_eq!(deleted_at.column_name, "deleted_at");
+        assert_eq!(deleted_at.ty, soft_delete_field_type());
+        assert!(!deleted_at.primary_key);
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_version() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                #[model(version)]
+                version: i64,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::default();
+        let model = opts.as_model(&args, &SymbolResolver::new(vec![])).unwrap();
+
+        let version_field = model.version_field.unwrap();
+        assert_eq!(version_field.field_name.to_string(), "version");
+        assert_eq!(version_field.column_name, "version");
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_multiple_versions() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                #[model(version)]
+                version: i64,
+                #[model(version)]
+                other_version: i64,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::default();
+        let err = opts
+            .as_model(&args, &SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "only one field can be marked with `#[model(version)]`"
+        );
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_soft_delete_field_declared() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(soft_delete)]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                deleted_at: Option<i64>,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let err = opts
+            .as_model(&args, &SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be declared explicitly"));
+    }
+
     #[cfg(feature = "symbol-resolver")]
     #[test]
     fn model_opts_as_model_no_pk() {
@@ -437,7 +1052,7 @@ mod tests {
 
     #[cfg(feature = "symbol-resolver")]
     #[test]
-    fn model_opts_as_model_multiple_pks() {
+    fn model_opts_as_model_composite_pk() {
         let input: syn::DeriveInput = parse_quote! {
             #[model]
             struct TestModel {
@@ -450,12 +1065,64 @@ mod tests {
         };
         let opts = ModelOpts::new_from_derive_input(&input).unwrap();
         let args = ModelArgs::default();
+        let model = opts.as_model(&args, &SymbolResolver::new(vec![])).unwrap();
+        let pk_names: Vec<_> = model
+            .pk_fields
+            .iter()
+            .map(|field| field.field_name.to_string())
+            .collect();
+        assert_eq!(pk_names, ["id", "id_2"]);
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_composite_pk_auto() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model]
+            struct TestModel {
+                #[model(primary_key)]
+                id: Auto<i64>,
+                #[model(primary_key)]
+                id_2: i64,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::default();
+        let err = opts
+            .as_model(&args, &SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`Auto` fields cannot be a part of a composite primary key"
+        );
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn model_opts_as_model_composite_pk_too_many_fields() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model]
+            struct TestModel {
+                #[model(primary_key)]
+                a: i64,
+                #[model(primary_key)]
+                b: i64,
+                #[model(primary_key)]
+                c: i64,
+                #[model(primary_key)]
+                d: i64,
+                #[model(primary_key)]
+                e: i64,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::default();
         let err = opts
             .as_model(&args, &SymbolResolver::new(vec![]))
             .unwrap_err();
         assert_eq!(
             err.to_string(),
-            "composite primary keys are not supported; only one primary key field is allowed"
+            "composite primary keys can consist of at most 4 fields"
         );
     }
 
@@ -474,6 +1141,216 @@ mod tests {
         assert!(field.unique);
     }
 
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn field_opts_as_field_many_to_many() {
+        let input: syn::Field = parse_quote! {
+            tags: ManyToMany<Tag>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let field = field_opts.as_field(&SymbolResolver::new(vec![])).unwrap();
+        assert_eq!(
+            field.many_to_many,
+            Some(ManyToManySpec {
+                to_model: parse_quote!(Tag)
+            })
+        );
+        assert!(field.foreign_key.is_none());
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn field_opts_as_field_many_to_many_primary_key() {
+        let input: syn::Field = parse_quote! {
+            #[model(primary_key)]
+            tags: ManyToMany<Tag>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let err = field_opts
+            .as_field(&SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "many-to-many fields cannot be primary keys or unique"
+        );
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn field_opts_as_field_on_delete() {
+        let input: syn::Field = parse_quote! {
+            #[model(on_delete = "cascade")]
+            parent: cot::db::ForeignKey<Parent>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let field = field_opts.as_field(&SymbolResolver::new(vec![])).unwrap();
+        assert_eq!(
+            field.foreign_key,
+            Some(ForeignKeySpec {
+                to_model: parse_quote!(Parent),
+                on_delete: ForeignKeyOnDeletePolicy::Cascade,
+            })
+        );
+
+        let input: syn::Field = parse_quote! {
+            #[model(on_delete = "set_null")]
+            parent: Option<cot::db::ForeignKey<Parent>>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let field = field_opts.as_field(&SymbolResolver::new(vec![])).unwrap();
+        assert_eq!(
+            field.foreign_key.unwrap().on_delete,
+            ForeignKeyOnDeletePolicy::SetNone
+        );
+    }
+
+    #[test]
+    fn field_opts_auto_now_kind() {
+        let input: syn::Field = parse_quote! {
+            #[model(auto_now)]
+            updated_at: DateTime<Utc>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        assert_eq!(
+            field_opts.auto_now_kind().unwrap(),
+            Some(AutoNowKind::Always)
+        );
+
+        let input: syn::Field = parse_quote! {
+            #[model(auto_now_add)]
+            created_at: DateTime<Utc>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        assert_eq!(
+            field_opts.auto_now_kind().unwrap(),
+            Some(AutoNowKind::OnCreate)
+        );
+
+        let input: syn::Field = parse_quote! {
+            created_at: DateTime<Utc>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        assert_eq!(field_opts.auto_now_kind().unwrap(), None);
+    }
+
+    #[test]
+    fn field_opts_auto_now_kind_both() {
+        let input: syn::Field = parse_quote! {
+            #[model(auto_now, auto_now_add)]
+            created_at: DateTime<Utc>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let err = field_opts.auto_now_kind().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`auto_now` and `auto_now_add` cannot be used together"
+        );
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn field_opts_as_field_auto_now_primary_key() {
+        let input: syn::Field = parse_quote! {
+            #[model(primary_key, auto_now_add)]
+            created_at: DateTime<Utc>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let err = field_opts
+            .as_field(&SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`auto_now` and `auto_now_add` cannot be used on primary keys or many-to-many fields"
+        );
+    }
+
+    #[cfg(feature = "symbol-resolver")]
+    #[test]
+    fn field_opts_as_field_version_primary_key() {
+        let input: syn::Field = parse_quote! {
+            #[model(primary_key, version)]
+            version: i64
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let err = field_opts
+            .as_field(&SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`version` cannot be used on primary keys, many-to-many fields, or fields with \
+            `auto_now` or `auto_now_add`"
+        );
+    }
+
+    #[test]
+    fn field_opts_on_delete_parse() {
+        let input: syn::Field = parse_quote! {
+            #[model(on_delete = "set_default")]
+            parent: Option<ForeignKey<Parent>>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        assert_eq!(
+            field_opts.on_delete,
+            Some(ForeignKeyOnDeletePolicy::SetDefault)
+        );
+
+        let input: syn::Field = parse_quote! {
+            #[model(on_delete = "delete_everything")]
+            parent: ForeignKey<Parent>
+        };
+        assert!(FieldOpts::from_field(&input).is_err());
+    }
+
+    #[test]
+    fn field_opts_validate_on_delete_not_foreign_key() {
+        let input: syn::Field = parse_quote! {
+            #[model(on_delete = "cascade")]
+            parent: i32
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let err = field_opts.validate_on_delete().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`on_delete` can only be specified for `ForeignKey` fields"
+        );
+    }
+
+    #[test]
+    fn field_opts_validate_on_delete_set_null_not_optional() {
+        let input: syn::Field = parse_quote! {
+            #[model(on_delete = "set_null")]
+            parent: ForeignKey<Parent>
+        };
+        let field_opts = FieldOpts::from_field(&input).unwrap();
+        let err = field_opts.validate_on_delete().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`on_delete = \"set_null\"` and `on_delete = \"set_default\"` require \
+            the foreign key to be wrapped in an `Option`"
+        );
+    }
+
+    #[test]
+    fn many_to_many_spec_from_type() {
+        let spec = ManyToManySpec::from_type(&parse_quote!(cot::db::ManyToMany<crate::Tag>))
+            .unwrap()
+            .unwrap();
+        assert_eq!(spec.to_model, parse_quote!(crate::Tag));
+
+        assert!(
+            ManyToManySpec::from_type(&parse_quote!(Vec<Tag>))
+                .unwrap()
+                .is_none()
+        );
+        let err = ManyToManySpec::from_type(&parse_quote!(ManyToMany))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "expected ManyToMany to have angle-bracketed generic arguments"
+        );
+    }
+
     #[cfg(feature = "symbol-resolver")]
     #[test]
     fn find_type_resolved() {
@@ -500,6 +1377,10 @@ mod tests {
             ty: parse_quote! { MyContainer<std::string::String> },
             primary_key: darling::util::Flag::default(),
             unique: darling::util::Flag::default(),
+            on_delete: None,
+            auto_now: darling::util::Flag::default(),
+            auto_now_add: darling::util::Flag::default(),
+            version: darling::util::Flag::default(),
         };
 
         assert!(opts.find_type("my_crate::MyContainer", &resolver).is_some());
@@ -101,23 +101,37 @@ impl AdminModelDeriveBuilder {
                     self
                 }
 
+                fn is_searchable() -> bool {
+                    !<Self as #crate_ident::db::Model>::SEARCH_FIELDS.is_empty()
+                }
+
                 async fn get_total_object_counts(
                     request: &#crate_ident::request::Request,
+                    search: ::core::option::Option<&str>,
                 ) -> #crate_ident::Result<u64> {
                     use #crate_ident::db::Model;
                     use #crate_ident::request::RequestExt;
 
-                    Ok(Self::objects().count(request.db()).await?)
+                    let mut query = Self::objects();
+                    if let ::core::option::Option::Some(search) = search {
+                        query.search(search);
+                    }
+                    Ok(query.count(request.db()).await?)
                 }
 
                 async fn get_objects(
                     request: &#crate_ident::request::Request,
                     pagination: #crate_ident::admin::Pagination,
+                    search: ::core::option::Option<&str>,
                 ) -> #crate_ident::Result<::std::vec::Vec<Self>> {
                     use #crate_ident::db::Model;
                     use #crate_ident::request::RequestExt;
 
-                    Ok(Self::objects().limit(pagination.limit()).offset(pagination.offset()).all(request.db()).await?)
+                    let mut query = Self::objects();
+                    if let ::core::option::Option::Some(search) = search {
+                        query.search(search);
+                    }
+                    Ok(query.limit(pagination.limit()).offset(pagination.offset()).all(request.db()).await?)
                 }
 
                 async fn get_object_by_id(
@@ -180,7 +194,24 @@ impl AdminModelDeriveBuilder {
                                 let id = parse_id::<Self>(object_id)?;
 
                                 object_from_form.set_primary_key(id);
-                                object_from_form.update(request.db()).await?;
+                                match object_from_form.update(request.db()).await {
+                                    ::std::result::Result::Err(#crate_ident::db::DatabaseError::StaleObject { .. }) => {
+                                        use #crate_ident::form::FormContext;
+
+                                        let mut context = object_from_form.to_context();
+                                        context.add_error(
+                                            #crate_ident::form::FormErrorTarget::Form,
+                                            #crate_ident::form::FormFieldValidationError::from_static(
+                                                "This object has been modified by someone else since it was loaded. \
+                                                Reload the page to see the changes.",
+                                            ),
+                                        );
+                                        return ::std::result::Result::Ok(::core::option::Option::Some(
+                                            ::std::boxed::Box::new(context),
+                                        ));
+                                    }
+                                    result => result?,
+                                }
                             } else {
                                 object_from_form.insert(request.db()).await?;
                             }
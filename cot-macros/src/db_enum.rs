@@ -0,0 +1,142 @@
+use darling::{FromDeriveInput, FromMeta, FromVariant};
+use heck::{ToSnakeCase, ToTitleCase};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::cot_ident;
+
+pub(super) fn impl_db_enum_for_enum(ast: &syn::DeriveInput) -> TokenStream {
+    let opts = match DbEnumOpts::from_derive_input(ast) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+
+    opts.build()
+}
+
+#[derive(Debug, Default, Copy, Clone, FromMeta)]
+#[darling(rename_all = "snake_case")]
+enum DbEnumRepr {
+    #[default]
+    String,
+    Integer,
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(db_enum), supports(enum_unit))]
+struct DbEnumOpts {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: darling::ast::Data<DbEnumVariant, darling::util::Ignored>,
+    #[darling(default)]
+    repr: DbEnumRepr,
+}
+
+#[derive(Debug, FromVariant)]
+#[darling(attributes(db_enum))]
+struct DbEnumVariant {
+    ident: syn::Ident,
+    #[darling(default)]
+    rename: Option<syn::LitStr>,
+    #[darling(default)]
+    label: Option<syn::LitStr>,
+}
+
+impl DbEnumOpts {
+    fn build(&self) -> TokenStream {
+        let crate_ident = cot_ident();
+        let name = &self.ident;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let variants = self
+            .data
+            .as_ref()
+            .take_enum()
+            .expect("Only enums are supported");
+
+        if variants.is_empty() {
+            return syn::Error::new_spanned(
+                name,
+                "`DbEnum` can only be derived for enums with at least one variant",
+            )
+            .to_compile_error();
+        }
+
+        let (repr, repr_ty, to_repr, from_repr) = match self.repr {
+            DbEnumRepr::String => (
+                quote! { #crate_ident::db::DbEnumRepr::String },
+                quote! { ::std::string::String },
+                quote! { ::std::borrow::ToOwned::to_owned(#crate_ident::db::DbEnum::name(self)) },
+                quote! { #crate_ident::__private::db_enum_from_name(&repr) },
+            ),
+            DbEnumRepr::Integer => (
+                quote! { #crate_ident::db::DbEnumRepr::Integer },
+                quote! { i32 },
+                quote! { #crate_ident::db::DbEnum::discriminant(self) },
+                quote! { #crate_ident::__private::db_enum_from_discriminant(repr) },
+            ),
+        };
+        let idents: Vec<_> = variants.iter().map(|variant| &variant.ident).collect();
+        let names = variants.iter().map(|variant| {
+            variant.rename.as_ref().map_or_else(
+                || variant.ident.to_string().to_snake_case(),
+                syn::LitStr::value,
+            )
+        });
+        let labels = variants.iter().map(|variant| {
+            variant.label.as_ref().map_or_else(
+                || variant.ident.to_string().to_title_case(),
+                syn::LitStr::value,
+            )
+        });
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #crate_ident::db::DbEnum for #name #ty_generics #where_clause {
+                const REPR: #crate_ident::db::DbEnumRepr = #repr;
+
+                const VARIANTS: &'static [Self] = &[#( Self::#idents ),*];
+
+                fn name(&self) -> &'static str {
+                    match self {
+                        #( Self::#idents => #names, )*
+                    }
+                }
+
+                #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+                fn discriminant(&self) -> i32 {
+                    *self as i32
+                }
+
+                fn label(&self) -> &'static str {
+                    match self {
+                        #( Self::#idents => #labels, )*
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_generics #crate_ident::db::CustomDbField for #name #ty_generics #where_clause {
+                type Repr = #repr_ty;
+
+                fn to_repr(&self) -> Self::Repr {
+                    #to_repr
+                }
+
+                fn from_repr(repr: Self::Repr) -> #crate_ident::db::Result<Self> {
+                    #from_repr
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_generics #crate_ident::db::ToDbValue for #name #ty_generics #where_clause {
+                fn to_db_value(&self) -> #crate_ident::db::DbValue {
+                    #crate_ident::db::ToDbValue::to_db_value(
+                        &#crate_ident::db::CustomDbField::to_repr(self)
+                    )
+                }
+            }
+        }
+    }
+}
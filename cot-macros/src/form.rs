@@ -51,6 +51,7 @@ impl FormOpts {
             fields_as_struct_fields: Vec::with_capacity(self.field_count()),
             fields_as_struct_fields_new: Vec::with_capacity(self.field_count()),
             fields_as_context_from_request: Vec::with_capacity(self.field_count()),
+            fields_as_set_file: Vec::with_capacity(self.field_count()),
             fields_as_from_context_vars: Vec::with_capacity(self.field_count()),
             fields_as_from_context: Vec::with_capacity(self.field_count()),
             fields_as_to_context: Vec::with_capacity(self.field_count()),
@@ -81,6 +82,7 @@ struct FormDeriveBuilder {
     fields_as_struct_fields: Vec<TokenStream>,
     fields_as_struct_fields_new: Vec<TokenStream>,
     fields_as_context_from_request: Vec<TokenStream>,
+    fields_as_set_file: Vec<TokenStream>,
     fields_as_from_context_vars: Vec<TokenStream>,
     fields_as_from_context: Vec<TokenStream>,
     fields_as_to_context: Vec<TokenStream>,
@@ -150,6 +152,11 @@ impl FormDeriveBuilder {
                 #crate_ident::form::FormField::set_value(&mut self.#field_ident, value)
             }));
 
+        self.fields_as_set_file
+            .push(quote!(stringify!(#field_ident) => {
+                #crate_ident::form::FormField::set_file(&mut self.#field_ident, file)
+            }));
+
         let val_ident = format_ident!("val_{}", field_ident);
         self.fields_as_from_context_vars.push(quote! {
             let #val_ident = <#ty as #crate_ident::form::AsFormField>::clean_value(&context.#field_ident).map_err(|error| {
@@ -240,6 +247,7 @@ impl FormDeriveBuilder {
         let fields_as_struct_fields = &self.fields_as_struct_fields;
         let fields_as_struct_fields_new = &self.fields_as_struct_fields_new;
         let fields_as_context_from_request = &self.fields_as_context_from_request;
+        let fields_as_set_file = &self.fields_as_set_file;
         let fields_as_errors_for = &self.fields_as_errors_for;
         let fields_as_errors_for_mut = &self.fields_as_errors_for_mut;
         let fields_as_has_errors = &self.fields_as_has_errors;
@@ -301,6 +309,17 @@ impl FormDeriveBuilder {
                     Ok(())
                 }
 
+                fn set_file(
+                    &mut self,
+                    field_id: &str,
+                    file: #crate_ident::request::UploadedFile,
+                ) -> ::core::result::Result<(), #crate_ident::form::FormFieldValidationError> {
+                    match field_id {
+                        #( #fields_as_set_file, )*
+                        _ => Ok(()),
+                    }
+                }
+
                 fn errors_for(
                     &self,
                     target: #crate_ident::form::FormErrorTarget
@@ -347,6 +366,7 @@ impl FormDeriveBuilder {
             #[automatically_derived]
             impl #display_dummy_lifetime_decl ::core::fmt::Display for #context_struct_name #display_where_clause {
                 fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    #crate_ident::__private::write_csrf_token_field(f)?;
                     #( #fields_as_display; )*
 
                     Ok(())
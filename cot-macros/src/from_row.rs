@@ -0,0 +1,77 @@
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::cot_ident;
+
+pub(super) fn impl_from_row_for_struct(ast: &syn::DeriveInput) -> TokenStream {
+    let opts = match FromRowOpts::from_derive_input(ast) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+
+    opts.build()
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(supports(struct_named, struct_tuple))]
+struct FromRowOpts {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: darling::ast::Data<darling::util::Ignored, FromRowField>,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(from_row))]
+struct FromRowField {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    #[darling(default)]
+    rename: Option<syn::LitStr>,
+}
+
+impl FromRowOpts {
+    fn build(&self) -> TokenStream {
+        let crate_ident = cot_ident();
+        let name = &self.ident;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let fields = self
+            .data
+            .as_ref()
+            .take_struct()
+            .expect("Only structs are supported");
+
+        let constructor = if fields.is_tuple() {
+            let values = fields.iter().enumerate().map(|(index, field)| {
+                let ty = &field.ty;
+                quote! { row.get::<#ty>(#index)? }
+            });
+
+            quote! { Self(#(#values),*) }
+        } else {
+            let values = fields.iter().map(|field| {
+                let ident = field.ident.as_ref().expect("named fields have idents");
+                let ty = &field.ty;
+                let column = field.rename.as_ref().map_or_else(
+                    || ident.to_string().trim_start_matches("r#").to_owned(),
+                    syn::LitStr::value,
+                );
+
+                quote! { #ident: row.get_by_name::<#ty>(#column)? }
+            });
+
+            quote! { Self { #(#values),* } }
+        };
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #crate_ident::db::FromRow for #name #ty_generics #where_clause {
+                fn from_row(row: &#crate_ident::db::Row) -> #crate_ident::db::Result<Self> {
+                    Ok(#constructor)
+                }
+            }
+        }
+    }
+}
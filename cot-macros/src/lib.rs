@@ -1,9 +1,13 @@
 mod admin;
+mod db_enum;
 mod dbtest;
 mod form;
+mod from_row;
 mod main_fn;
 mod model;
+mod model_form;
 mod query;
+mod route;
 
 use darling::Error;
 use darling::ast::NestedMeta;
@@ -13,11 +17,15 @@ use quote::quote;
 use syn::{ItemFn, parse_macro_input};
 
 use crate::admin::impl_admin_model_for_struct;
+use crate::db_enum::impl_db_enum_for_enum;
 use crate::dbtest::fn_to_dbtest;
 use crate::form::impl_form_for_struct;
+use crate::from_row::impl_from_row_for_struct;
 use crate::main_fn::{fn_to_cot_main, fn_to_cot_test};
 use crate::model::impl_model_for_struct;
+use crate::model_form::impl_model_form_for_struct;
 use crate::query::{Query, query_to_tokens};
+use crate::route::impl_route_ref_for_struct;
 
 #[proc_macro_derive(Form, attributes(form))]
 pub fn derive_form(input: TokenStream) -> TokenStream {
@@ -26,6 +34,34 @@ pub fn derive_form(input: TokenStream) -> TokenStream {
     token_stream.into()
 }
 
+#[proc_macro_derive(ModelForm, attributes(form))]
+pub fn derive_model_form(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    let token_stream = impl_model_form_for_struct(&ast);
+    token_stream.into()
+}
+
+#[proc_macro_derive(RouteRef, attributes(route))]
+pub fn derive_route_ref(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    let token_stream = impl_route_ref_for_struct(&ast);
+    token_stream.into()
+}
+
+#[proc_macro_derive(FromRow, attributes(from_row))]
+pub fn derive_from_row(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    let token_stream = impl_from_row_for_struct(&ast);
+    token_stream.into()
+}
+
+#[proc_macro_derive(DbEnum, attributes(db_enum))]
+pub fn derive_db_enum(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    let token_stream = impl_db_enum_for_enum(&ast);
+    token_stream.into()
+}
+
 #[proc_macro_derive(AdminModel)]
 pub fn derive_admin_model(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
@@ -98,9 +134,215 @@ pub fn derive_admin_model(input: TokenStream) -> TokenStream {
 ///     name: String,
 /// }
 /// ```
+/// * `external`: The model represents an existing table that is not managed by
+///   Cot, such as a table of a legacy application. The table name given with
+///   the `table_name` parameter is used as is, without the app name prefix, and
+///   the model is ignored by the migration generator. The `cot inspect-db`
+///   command generates such models for all the tables of an existing database.
+/// ```
+/// use cot::db::{Model, model};
+///
+/// #[model(model_type = "external", table_name = "legacy_users")]
+/// struct LegacyUser {
+///     #[model(primary_key)]
+///     id: i32,
+///     email: String,
+/// }
+///
+/// assert_eq!(LegacyUser::TABLE_NAME.as_str(), "legacy_users");
+/// ```
+///
+/// # Indexes
+///
+/// Indexes and unique constraints spanning one or more fields can be declared
+/// with the `index` and `unique` parameters, which can be repeated. The Cot
+/// CLI adds them to the generated migrations, and saving a model instance
+/// that violates a unique constraint returns a
+/// [`DatabaseError::UniqueViolation`] error. The name of the index in the
+/// database is generated from the table and column names, unless given
+/// explicitly with the `name` parameter.
+///
+/// ```
+/// use cot::db::model;
+///
+/// #[model(
+///     index(fields = ["email"]),
+///     unique(fields = ["tenant_id", "slug"], name = "user_tenant_slug"),
+/// )]
+/// struct User {
+///     #[model(primary_key)]
+///     id: i32,
+///     tenant_id: i32,
+///     slug: String,
+///     email: String,
+/// }
+/// ```
+///
+/// # Composite primary keys
+///
+/// Marking more than one field (up to four) with `primary_key` makes them a
+/// composite primary key. The [`Model::PrimaryKey`] type is then a tuple of
+/// the types of these fields, in the order of declaration, so the instances
+/// can be retrieved with e.g. `Model::get_by_primary_key(db, (a, b))`. The
+/// fields of a composite primary key can be foreign keys, but they cannot be
+/// [`Auto`], and the models with a composite primary key cannot have
+/// many-to-many fields, nor be referenced by foreign keys.
+///
+/// ```
+/// use cot::db::{Auto, ForeignKey, model};
+///
+/// #[model]
+/// #[derive(Clone)]
+/// struct Warehouse {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     name: String,
+/// }
+///
+/// #[model]
+/// struct Stock {
+///     #[model(primary_key)]
+///     warehouse: ForeignKey<Warehouse>,
+///     #[model(primary_key)]
+///     sku: String,
+///     quantity: i32,
+/// }
+/// ```
+///
+/// # Automatic timestamps
+///
+/// Fields marked with `auto_now` are set to the current time every time the
+/// model instance is saved, and fields marked with `auto_now_add` are set only
+/// when it is created. The field type must implement [`AutoNow`], which is
+/// the case for the `chrono` date and time types (storing
+/// `chrono::DateTime<Utc>` is recommended, as it's unambiguous regardless of
+/// the timezone of the server) and their [`Option`]s.
+///
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use cot::db::{Auto, model};
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     #[model(auto_now_add)]
+///     created_at: DateTime<Utc>,
+///     #[model(auto_now)]
+///     updated_at: DateTime<Utc>,
+/// }
+/// ```
+///
+/// # Soft deletes
+///
+/// Models declared with the `soft_delete` parameter get an additional
+/// `deleted_at: Option<DateTime<Utc>>` field (and column). Deleting the rows
+/// of such a model with [`Query::delete`] only sets this field to the current
+/// time, and the deleted rows are excluded from the queries unless
+/// [`Query::with_deleted`] is called. The rows can be brought back with
+/// [`Query::restore`], or removed for good with [`Query::hard_delete`].
+///
+/// Since the field is added by this macro, the `#[model]` attribute must be
+/// placed before any `#[derive]` attributes of the struct.
+///
+/// ```
+/// use cot::db::{Auto, model};
+///
+/// #[model(soft_delete)]
+/// #[derive(Debug, Clone)]
+/// struct Invoice {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     number: String,
+/// }
+///
+/// let invoice = Invoice {
+///     id: Auto::auto(),
+///     number: "2025/001".to_owned(),
+///     deleted_at: None,
+/// };
+/// ```
+///
+/// # Optimistic locking
+///
+/// A field of type `i64` can be marked with `version` to protect the model
+/// against lost updates. Saving or updating an existing instance then only
+/// succeeds if the row in the database still has the same version as the
+/// instance, and increments it; otherwise, the row is left intact and
+/// [`DatabaseError::StaleObject`] is returned. This is useful when the same
+/// row can be edited concurrently, e.g. in the admin panel.
+///
+/// ```
+/// use cot::db::{Auto, model};
+///
+/// #[model]
+/// struct Article {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     body: String,
+///     #[model(version)]
+///     version: i64,
+/// }
+/// ```
+///
+/// # Full-text search
+///
+/// The `search` parameter lists the text fields the model instances can be
+/// searched by with [`Query::search`]. On PostgreSQL, the fields are matched
+/// with its full-text search (`tsvector`); the other backends check whether
+/// the fields contain each of the words of the search term. The admin panel
+/// shows a search box for the models declared with this parameter.
+///
+/// ```
+/// use cot::db::{Auto, model};
+///
+/// #[model(search(fields = ["title", "body"]))]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+///     body: String,
+/// }
+/// ```
+///
+/// # Lifecycle hooks
+///
+/// Models declared with the `hooks` parameter run the methods of their
+/// [`ModelHooks`] implementation when their instances are saved or deleted.
+/// The implementation must be provided separately.
+///
+/// ```
+/// use cot::db::{Auto, DatabaseBackend, ModelHooks, model};
+///
+/// #[model(hooks)]
+/// struct Comment {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     text: String,
+/// }
+///
+/// #[async_trait::async_trait]
+/// impl ModelHooks for Comment {
+///     async fn post_delete<DB: DatabaseBackend>(&mut self, _db: &DB) -> cot::db::Result<()> {
+///         println!("comment {} deleted", self.id);
+///         Ok(())
+///     }
+/// }
+/// ```
 ///
 /// [`Model`]: trait.Model.html
 /// [`DatabaseField`]: trait.DatabaseField.html
+/// [`DatabaseError::UniqueViolation`]: enum.DatabaseError.html#variant.UniqueViolation
+/// [`DatabaseError::StaleObject`]: enum.DatabaseError.html#variant.StaleObject
+/// [`AutoNow`]: trait.AutoNow.html
+/// [`Auto`]: enum.Auto.html
+/// [`Model::PrimaryKey`]: trait.Model.html#associatedtype.PrimaryKey
+/// [`ModelHooks`]: trait.ModelHooks.html
+/// [`Query::delete`]: query/struct.Query.html#method.delete
+/// [`Query::with_deleted`]: query/struct.Query.html#method.with_deleted
+/// [`Query::restore`]: query/struct.Query.html#method.restore
+/// [`Query::hard_delete`]: query/struct.Query.html#method.hard_delete
+/// [`Query::search`]: query/struct.Query.html#method.search
 #[proc_macro_attribute]
 pub fn model(args: TokenStream, input: TokenStream) -> TokenStream {
     let attr_args = match NestedMeta::parse_meta_list(args.into()) {
@@ -1,4 +1,6 @@
-use cot_codegen::model::{Field, Model, ModelArgs, ModelOpts, ModelType};
+use cot_codegen::model::{
+    AutoNowKind, Field, Index, Model, ModelArgs, ModelOpts, ModelType, SOFT_DELETE_FIELD_NAME,
+};
 use darling::FromMeta;
 use darling::ast::NestedMeta;
 use heck::ToSnakeCase;
@@ -34,7 +36,21 @@ pub(super) fn impl_model_for_struct(
             return err.to_compile_error();
         }
     };
-    let builder = ModelBuilder::from_model(model);
+    let soft_delete_field = model.soft_delete.then(|| {
+        let field = model
+            .fields
+            .last()
+            .expect("soft delete models must have the `deleted_at` field");
+        let name = &field.field_name;
+        let ty = &field.ty;
+        let vis = &ast.vis;
+        quote! {
+            /// The time the instance has been soft-deleted at, or `None` if it
+            /// hasn't been deleted.
+            #vis #name: #ty,
+        }
+    });
+    let builder = ModelBuilder::from_model(model, args.hooks.is_present());
 
     let attrs = &ast.attrs;
     let vis = &ast.vis;
@@ -46,11 +62,13 @@ pub(super) fn impl_model_for_struct(
         _ => panic!("Only structs are supported"),
     };
     let fields = remove_helper_field_attributes(fields);
+    let separator = (!fields.empty_or_trailing()).then(|| quote!(,));
 
     quote!(
         #(#attrs)*
         #vis struct #ident {
-            #fields
+            #fields #separator
+            #soft_delete_field
         }
         #builder
     )
@@ -74,13 +92,19 @@ struct ModelBuilder {
     name: Ident,
     vis: syn::Visibility,
     table_name: String,
-    pk_field: Field,
+    pk_fields: Vec<Field>,
     fields_struct_name: Ident,
     fields_as_columns: Vec<TokenStream>,
     fields_as_from_db: Vec<TokenStream>,
     fields_as_update_from_db: Vec<TokenStream>,
     fields_as_get_values: Vec<TokenStream>,
     fields_as_field_refs: Vec<TokenStream>,
+    fields_as_set_timestamps: Vec<TokenStream>,
+    indexes: Vec<TokenStream>,
+    soft_delete: bool,
+    version_field: Option<Field>,
+    search_columns: Vec<String>,
+    hooks: bool,
 }
 
 impl ToTokens for ModelBuilder {
@@ -91,12 +115,12 @@ impl ToTokens for ModelBuilder {
 }
 
 impl ModelBuilder {
-    fn from_model(model: Model) -> Self {
+    fn from_model(model: Model, hooks: bool) -> Self {
         let field_count = model.field_count();
         let app_name = std::env::var("CARGO_PKG_NAME")
             .expect("cargo should set the `CARGO_PKG_NAME` environment variable");
         let table_name = match model.model_type {
-            ModelType::Internal => model.table_name,
+            ModelType::Internal | ModelType::External => model.table_name,
             _ => format!(
                 "{}__{}",
                 app_name.to_snake_case(),
@@ -108,17 +132,26 @@ impl ModelBuilder {
             name: model.name.clone(),
             vis: model.vis,
             table_name,
-            pk_field: model.pk_field.clone(),
+            pk_fields: model.pk_fields.clone(),
             fields_struct_name: format_ident!("{}Fields", model.name),
             fields_as_columns: Vec::with_capacity(field_count),
             fields_as_from_db: Vec::with_capacity(field_count),
             fields_as_update_from_db: Vec::with_capacity(field_count),
             fields_as_get_values: Vec::with_capacity(field_count),
             fields_as_field_refs: Vec::with_capacity(field_count),
+            fields_as_set_timestamps: Vec::new(),
+            indexes: Vec::with_capacity(model.indexes.len()),
+            soft_delete: model.soft_delete,
+            version_field: model.version_field,
+            search_columns: model.search_columns,
+            hooks,
         };
         for field in &model.fields {
             model_builder.push_field(field);
         }
+        for index in &model.indexes {
+            model_builder.push_index(index);
+        }
 
         model_builder
     }
@@ -131,6 +164,22 @@ impl ModelBuilder {
         let index = self.fields_as_columns.len();
         let column_name = &field.column_name;
 
+        if let Some(many_to_many) = &field.many_to_many {
+            let model_name = &self.name;
+            let to_model = &many_to_many.to_model;
+            let join_table = format!("{}_{}", self.table_name, column_name);
+
+            self.fields_as_from_db.push(quote!(
+                #name: #orm_ident::ManyToMany::new()
+            ));
+            self.fields_as_field_refs.push(quote!(
+                #[doc = concat!("Many-to-many relationship reference to [`", stringify!(#name), "`].")]
+                pub const #name: #orm_ident::ManyToManyRef<#model_name, #to_model> =
+                    #orm_ident::ManyToManyRef::new(#orm_ident::Identifier::new(#join_table));
+            ));
+            return;
+        }
+
         {
             let field_as_column = quote!(#orm_ident::Column::new(
                 #orm_ident::Identifier::new(#column_name)
@@ -150,6 +199,18 @@ impl ModelBuilder {
             #index => &self.#name as &dyn #orm_ident::ToDbFieldValue
         ));
 
+        match field.auto_now {
+            Some(AutoNowKind::Always) => self.fields_as_set_timestamps.push(quote!(
+                self.#name = <#ty as #orm_ident::AutoNow>::from_now(now);
+            )),
+            Some(AutoNowKind::OnCreate) => self.fields_as_set_timestamps.push(quote!(
+                if created {
+                    self.#name = <#ty as #orm_ident::AutoNow>::from_now(now);
+                }
+            )),
+            None => {}
+        }
+
         self.fields_as_field_refs.push(quote!(
             #[doc = concat!("Field reference to [`", stringify!(#name), "::", stringify!(#column_name), "`].")]
             pub const #name: #orm_ident::query::FieldRef<#ty> =
@@ -157,6 +218,21 @@ impl ModelBuilder {
         ));
     }
 
+    fn push_index(&mut self, index: &Index) {
+        let orm_ident = orm_ident();
+
+        let name = index.db_name(&self.table_name);
+        let columns = &index.columns;
+        let unique = index.unique.then(|| quote!(.unique()));
+
+        self.indexes.push(quote!(
+            #orm_ident::Index::new(
+                #orm_ident::Identifier::new(#name),
+                &[#(#orm_ident::Identifier::new(#columns),)*],
+            )#unique
+        ));
+    }
+
     #[must_use]
     fn build_model_impl(&self) -> TokenStream {
         let crate_ident = cot_ident();
@@ -167,34 +243,68 @@ impl ModelBuilder {
         let table_name = &self.table_name;
         let fields_struct_name = &self.fields_struct_name;
         let fields_as_columns = &self.fields_as_columns;
-        let pk_field_name = &self.pk_field.field_name;
-        let pk_column_name = &self.pk_field.column_name;
-        let pk_type = &self.pk_field.ty;
         let fields_as_from_db = &self.fields_as_from_db;
         let fields_as_update_from_db = &self.fields_as_update_from_db;
         let fields_as_get_values = &self.fields_as_get_values;
+        let indexes = &self.indexes;
+        let soft_delete_column = self.soft_delete.then(|| {
+            quote! {
+                const SOFT_DELETE_COLUMN: ::core::option::Option<#orm_ident::Identifier> =
+                    ::core::option::Option::Some(#orm_ident::Identifier::new(#SOFT_DELETE_FIELD_NAME));
+            }
+        });
+        let search_fields = (!self.search_columns.is_empty()).then(|| {
+            let search_columns = &self.search_columns;
+            quote! {
+                const SEARCH_FIELDS: &'static [#orm_ident::Identifier] = &[
+                    #(#orm_ident::Identifier::new(#search_columns),)*
+                ];
+            }
+        });
+        let primary_key = self.build_primary_key_impl();
+        let version = self.build_version_impl();
+        let run_hooks = self.hooks.then(|| {
+            quote! {
+                async fn run_hooks<DB: #orm_ident::DatabaseBackend>(
+                    &mut self,
+                    event: #orm_ident::ModelEvent,
+                    db: &DB,
+                ) -> #orm_ident::Result<()> {
+                    event.run(self, db).await
+                }
+            }
+        });
+        let set_timestamps = (!self.fields_as_set_timestamps.is_empty()).then(|| {
+            let fields_as_set_timestamps = &self.fields_as_set_timestamps;
+            quote! {
+                fn set_timestamps(
+                    &mut self,
+                    now: #crate_ident::__private::chrono::DateTime<#crate_ident::__private::chrono::Utc>,
+                    created: bool,
+                ) {
+                    #(#fields_as_set_timestamps)*
+                }
+            }
+        });
 
         quote! {
             #[#crate_ident::__private::async_trait]
             #[automatically_derived]
             impl #orm_ident::Model for #name {
                 type Fields = #fields_struct_name;
-                type PrimaryKey = #pk_type;
 
                 const COLUMNS: &'static [#orm_ident::Column] = &[
                     #(#fields_as_columns,)*
                 ];
                 const APP_NAME: &'static str = #app_name;
                 const TABLE_NAME: #orm_ident::Identifier = #orm_ident::Identifier::new(#table_name);
-                const PRIMARY_KEY_NAME: #orm_ident::Identifier = #orm_ident::Identifier::new(#pk_column_name);
-
-                fn primary_key(&self) -> &Self::PrimaryKey {
-                    &self.#pk_field_name
-                }
-
-                fn set_primary_key(&mut self, primary_key: Self::PrimaryKey) {
-                    self.#pk_field_name = primary_key;
-                }
+                const INDEXES: &'static [#orm_ident::Index] = &[
+                    #(#indexes,)*
+                ];
+                #soft_delete_column
+                #search_fields
+                #primary_key
+                #version
 
                 fn from_db(db_row: #orm_ident::Row) -> #orm_ident::Result<Self> {
                     Ok(Self {
@@ -223,18 +333,103 @@ impl ModelBuilder {
                         .collect()
                 }
 
-                async fn get_by_primary_key<DB: #orm_ident::DatabaseBackend>(
-                    db: &DB,
-                    pk: Self::PrimaryKey,
-                ) -> #orm_ident::Result<Option<Self>> {
-                    #orm_ident::query!(Self, $#pk_field_name == pk)
-                        .get(db)
-                        .await
-                }
+                #set_timestamps
+
+                #run_hooks
+            }
+        }
+    }
+
+    /// Builds the `Model` trait items related to the primary key, which is a
+    /// tuple of the primary key fields if the model has a composite primary
+    /// key.
+    #[must_use]
+    fn build_primary_key_impl(&self) -> TokenStream {
+        let orm_ident = orm_ident();
+
+        let pk_field_names: Vec<_> = self
+            .pk_fields
+            .iter()
+            .map(|field| &field.field_name)
+            .collect();
+        let pk_column_names = self.pk_fields.iter().map(|field| &field.column_name);
+        let pk_column_name = &self.pk_fields[0].column_name;
+
+        let (pk_type, primary_key, set_primary_key, pk_binding, pk_filter) =
+            if let [pk_field] = self.pk_fields.as_slice() {
+                let pk_field_name = &pk_field.field_name;
+                (
+                    pk_field.ty.to_token_stream(),
+                    quote! { ::core::clone::Clone::clone(&self.#pk_field_name) },
+                    quote! { self.#pk_field_name = primary_key; },
+                    quote! { pk },
+                    quote! { $#pk_field_name == pk },
+                )
+            } else {
+                let pk_types = self.pk_fields.iter().map(|field| &field.ty);
+                let pk_values: Vec<_> = (0..self.pk_fields.len())
+                    .map(|index| format_ident!("pk_{}", index))
+                    .collect();
+                (
+                    quote! { (#(#pk_types,)*) },
+                    quote! { (#(::core::clone::Clone::clone(&self.#pk_field_names),)*) },
+                    quote! { (#(self.#pk_field_names,)*) = primary_key; },
+                    quote! { (#(#pk_values,)*) },
+                    quote! { #($#pk_field_names == #pk_values)&&* },
+                )
+            };
+
+        quote! {
+            type PrimaryKey = #pk_type;
+
+            const PRIMARY_KEY_NAME: #orm_ident::Identifier = #orm_ident::Identifier::new(#pk_column_name);
+            const PRIMARY_KEY_COLUMNS: &'static [#orm_ident::Identifier] = &[
+                #(#orm_ident::Identifier::new(#pk_column_names),)*
+            ];
+
+            fn primary_key(&self) -> Self::PrimaryKey {
+                #primary_key
+            }
+
+            fn set_primary_key(&mut self, primary_key: Self::PrimaryKey) {
+                #set_primary_key
+            }
+
+            async fn get_by_primary_key<DB: #orm_ident::DatabaseBackend>(
+                db: &DB,
+                #pk_binding: Self::PrimaryKey,
+            ) -> #orm_ident::Result<Option<Self>> {
+                #orm_ident::query!(Self, #pk_filter)
+                    .get(db)
+                    .await
             }
         }
     }
 
+    /// Builds the `Model` trait items used for optimistic locking, if the model
+    /// has a field marked with `#[model(version)]`.
+    #[must_use]
+    fn build_version_impl(&self) -> Option<TokenStream> {
+        let orm_ident = orm_ident();
+
+        self.version_field.as_ref().map(|field| {
+            let field_name = &field.field_name;
+            let column_name = &field.column_name;
+            quote! {
+                const VERSION_COLUMN: ::core::option::Option<#orm_ident::Identifier> =
+                    ::core::option::Option::Some(#orm_ident::Identifier::new(#column_name));
+
+                fn version(&self) -> ::core::option::Option<i64> {
+                    ::core::option::Option::Some(self.#field_name)
+                }
+
+                fn set_version(&mut self, version: i64) {
+                    self.#field_name = version;
+                }
+            }
+        })
+    }
+
     #[must_use]
     fn build_fields_struct(&self) -> TokenStream {
         let name = &self.name;
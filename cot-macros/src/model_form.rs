@@ -0,0 +1,225 @@
+use cot_codegen::model::{Field, Model, ModelArgs, ModelOpts, SOFT_DELETE_FIELD_NAME};
+use darling::FromMeta;
+use darling::ast::NestedMeta;
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+
+use crate::cot_ident;
+
+pub(super) fn impl_model_form_for_struct(ast: &syn::DeriveInput) -> TokenStream {
+    let args = match model_args(&ast.attrs) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+
+    let opts = match ModelOpts::new_from_derive_input(ast) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+
+    let model = match opts.as_model(&args) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.to_compile_error();
+        }
+    };
+
+    let mut builder = ModelFormDeriveBuilder::new(&model);
+    for field in &model.fields {
+        let attrs = form_attrs(ast, &field.field_name);
+        builder.push_field(&model, field, &attrs);
+    }
+
+    quote!(#builder)
+}
+
+/// Parses the arguments of the `#[model(...)]` attribute of the struct, if
+/// there is one.
+fn model_args(attrs: &[syn::Attribute]) -> Result<ModelArgs, darling::Error> {
+    let Some(attr) = attrs.iter().find(|attr| {
+        attr.path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "model")
+    }) else {
+        return Ok(ModelArgs::default());
+    };
+
+    match &attr.meta {
+        syn::Meta::List(list) => {
+            let args = NestedMeta::parse_meta_list(list.tokens.clone())?;
+            ModelArgs::from_list(&args)
+        }
+        _ => Ok(ModelArgs::default()),
+    }
+}
+
+/// Returns the `#[form(...)]` attributes of the given field, which are copied
+/// over to the generated form.
+fn form_attrs<'a>(ast: &'a syn::DeriveInput, field_name: &syn::Ident) -> Vec<&'a syn::Attribute> {
+    let syn::Data::Struct(data) = &ast.data else {
+        return Vec::new();
+    };
+
+    data.fields
+        .iter()
+        .find(|field| field.ident.as_ref() == Some(field_name))
+        .map(|field| {
+            field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("form"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the type is an [`Auto`](cot::db::Auto) value, i.e. its value is
+/// generated by the database rather than entered by the user.
+fn is_auto(ty: &syn::Type) -> bool {
+    let syn::Type::Path(type_path) = ty else {
+        return false;
+    };
+
+    type_path
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Auto")
+}
+
+#[derive(Debug)]
+struct ModelFormDeriveBuilder {
+    name: syn::Ident,
+    vis: syn::Visibility,
+    form_name: syn::Ident,
+    fields_as_struct_fields: Vec<TokenStream>,
+    fields_as_from_model: Vec<TokenStream>,
+    fields_as_to_model: Vec<TokenStream>,
+    fields_as_apply_to: Vec<TokenStream>,
+}
+
+impl ToTokens for ModelFormDeriveBuilder {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let form_struct = self.build_form_struct();
+        let model_form_impl = self.build_model_form_impl();
+
+        let new_tokens = quote! {
+            #form_struct
+            #model_form_impl
+        };
+
+        new_tokens.to_tokens(tokens);
+    }
+}
+
+impl ModelFormDeriveBuilder {
+    fn new(model: &Model) -> Self {
+        let field_count = model.field_count();
+        Self {
+            name: model.name.clone(),
+            vis: model.vis.clone(),
+            form_name: format_ident!("{}Form", model.name),
+            fields_as_struct_fields: Vec::with_capacity(field_count),
+            fields_as_from_model: Vec::with_capacity(field_count),
+            fields_as_to_model: Vec::with_capacity(field_count),
+            fields_as_apply_to: Vec::with_capacity(field_count),
+        }
+    }
+
+    fn push_field(&mut self, model: &Model, field: &Field, form_attrs: &[&syn::Attribute]) {
+        let crate_ident = cot_ident();
+        let name = &field.field_name;
+        let ty = &field.ty;
+
+        let is_version = model
+            .version_field
+            .as_ref()
+            .is_some_and(|version_field| version_field.field_name == *name);
+        let is_soft_delete = model.soft_delete && name == SOFT_DELETE_FIELD_NAME;
+
+        // the values that are not entered by the user: these are set when the
+        // model instance is saved, or are not stored in the model's table at all
+        if field.auto_now.is_some() {
+            self.fields_as_to_model.push(quote!(
+                #name: <#ty as #crate_ident::db::AutoNow>::from_now(
+                    #crate_ident::__private::chrono::Utc::now()
+                )
+            ));
+            return;
+        }
+        if (field.primary_key && is_auto(ty))
+            || field.many_to_many.is_some()
+            || is_version
+            || is_soft_delete
+        {
+            self.fields_as_to_model
+                .push(quote!(#name: ::core::default::Default::default()));
+            return;
+        }
+
+        let vis = &self.vis;
+        self.fields_as_struct_fields.push(quote!(
+            #( #form_attrs )*
+            #vis #name: #ty
+        ));
+        self.fields_as_from_model
+            .push(quote!(#name: ::core::clone::Clone::clone(&model.#name)));
+        self.fields_as_to_model
+            .push(quote!(#name: ::core::clone::Clone::clone(&self.#name)));
+        self.fields_as_apply_to
+            .push(quote!(model.#name = ::core::clone::Clone::clone(&self.#name)));
+    }
+
+    fn build_form_struct(&self) -> TokenStream {
+        let crate_ident = cot_ident();
+        let name = &self.name;
+        let vis = &self.vis;
+        let form_name = &self.form_name;
+        let fields_as_struct_fields = &self.fields_as_struct_fields;
+
+        quote! {
+            #[doc = concat!("A form for creating and editing [`", stringify!(#name), "`] instances.")]
+            #[derive(::core::fmt::Debug, ::core::clone::Clone, #crate_ident::form::Form)]
+            #vis struct #form_name {
+                #( #fields_as_struct_fields, )*
+            }
+        }
+    }
+
+    fn build_model_form_impl(&self) -> TokenStream {
+        let crate_ident = cot_ident();
+        let name = &self.name;
+        let form_name = &self.form_name;
+        let fields_as_from_model = &self.fields_as_from_model;
+        let fields_as_to_model = &self.fields_as_to_model;
+        let fields_as_apply_to = &self.fields_as_apply_to;
+
+        quote! {
+            #[automatically_derived]
+            impl #crate_ident::form::ModelForm for #form_name {
+                type Model = #name;
+
+                fn from_model(model: &Self::Model) -> Self {
+                    Self {
+                        #( #fields_as_from_model, )*
+                    }
+                }
+
+                fn to_model(&self) -> Self::Model {
+                    #name {
+                        #( #fields_as_to_model, )*
+                    }
+                }
+
+                fn apply_to(&self, model: &mut Self::Model) {
+                    #( #fields_as_apply_to; )*
+                }
+            }
+        }
+    }
+}
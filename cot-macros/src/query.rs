@@ -74,16 +74,32 @@ pub(super) fn expr_to_tokens(model_name: &syn::Type, expr: Expr) -> TokenStream
             )
             .to_compile_error(),
         },
-        Expr::FunctionCall { function, args } => match function.as_tokens() {
-            Some(tokens) => {
-                quote!(#crate_name::db::query::Expr::value(#tokens(#(#args),*)))
+        Expr::FunctionCall { function, args } => {
+            if let Expr::MemberAccess {
+                parent,
+                member_name,
+                ..
+            } = &*function
+            {
+                if let Expr::FieldRef { field_name, .. } = &**parent {
+                    // `$field.method(args)` calls a method of the field reference
+                    return quote!(
+                        <#model_name as #crate_name::db::Model>::Fields::#field_name.#member_name(#(#args),*)
+                    );
+                }
             }
-            None => syn::Error::new_spanned(
-                function.as_tokens_full(),
-                "calling functions that reference database fields is unsupported",
-            )
-            .to_compile_error(),
-        },
+
+            match function.as_tokens() {
+                Some(tokens) => {
+                    quote!(#crate_name::db::query::Expr::value(#tokens(#(#args),*)))
+                }
+                None => syn::Error::new_spanned(
+                    function.as_tokens_full(),
+                    "calling functions that reference database fields is unsupported",
+                )
+                .to_compile_error(),
+            }
+        }
         Expr::And(lhs, rhs) => {
             let lhs = expr_to_tokens(model_name, *lhs);
             let rhs = expr_to_tokens(model_name, *rhs);
@@ -94,6 +110,10 @@ pub(super) fn expr_to_tokens(model_name: &syn::Type, expr: Expr) -> TokenStream
             let rhs = expr_to_tokens(model_name, *rhs);
             quote!(#crate_name::db::query::Expr::or(#lhs, #rhs))
         }
+        Expr::Not(expr) => {
+            let expr = expr_to_tokens(model_name, *expr);
+            quote!(#crate_name::db::query::Expr::not(#expr))
+        }
         Expr::Eq(lhs, rhs) => handle_binary_comparison(model_name, *lhs, *rhs, "eq", "ExprEq"),
         Expr::Ne(lhs, rhs) => handle_binary_comparison(model_name, *lhs, *rhs, "ne", "ExprEq"),
         Expr::Lt(lhs, rhs) => handle_binary_comparison(model_name, *lhs, *rhs, "lt", "ExprOrd"),
@@ -0,0 +1,139 @@
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::cot_ident;
+
+pub(super) fn impl_route_ref_for_struct(ast: &syn::DeriveInput) -> TokenStream {
+    let opts = match RouteRefOpts::from_derive_input(ast) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+
+    match opts.build() {
+        Ok(tokens) => tokens,
+        Err(err) => err.write_errors(),
+    }
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(route), supports(struct_named, struct_unit))]
+struct RouteRefOpts {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: darling::ast::Data<darling::util::Ignored, RouteRefField>,
+    path: syn::LitStr,
+    name: syn::LitStr,
+    #[darling(default)]
+    app: Option<syn::LitStr>,
+}
+
+#[derive(Debug, FromField)]
+struct RouteRefField {
+    ident: Option<syn::Ident>,
+}
+
+impl RouteRefOpts {
+    fn fields(&self) -> Vec<&syn::Ident> {
+        self.data
+            .as_ref()
+            .take_struct()
+            .expect("Only structs are supported")
+            .fields
+            .into_iter()
+            .filter_map(|field| field.ident.as_ref())
+            .collect()
+    }
+
+    fn build(&self) -> darling::Result<TokenStream> {
+        let crate_ident = cot_ident();
+        let fields = self.fields();
+        let params = path_params(&self.path.value())
+            .map_err(|message| darling::Error::custom(message).with_span(&self.path))?;
+
+        let mut errors = darling::Error::accumulator();
+        for param in &params {
+            if !fields.iter().any(|field| *field == param) {
+                errors.push(
+                    darling::Error::custom(format!(
+                        "path parameter `{param}` has no corresponding field"
+                    ))
+                    .with_span(&self.path),
+                );
+            }
+        }
+        for field in &fields {
+            if !params.iter().any(|param| *field == param) {
+                errors.push(
+                    darling::Error::custom(format!(
+                        "field `{field}` is not a parameter of the route path"
+                    ))
+                    .with_span(field),
+                );
+            }
+        }
+        errors.finish()?;
+
+        let name = &self.ident;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let path = &self.path;
+        let route_name = &self.name;
+        let app_name = self.app.as_ref().map_or_else(
+            || quote! { ::core::option::Option::None },
+            |app| quote! { ::core::option::Option::Some(#app) },
+        );
+        let field_names = fields.iter().map(ToString::to_string);
+
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #crate_ident::router::RouteRef for #name #ty_generics #where_clause {
+                const PATH: &'static str = #path;
+                const NAME: &'static str = #route_name;
+                const APP_NAME: ::core::option::Option<&'static str> = #app_name;
+
+                fn params(&self) -> #crate_ident::router::path::ReverseParamMap {
+                    #[allow(unused_mut)] // for the case when there are no parameters
+                    let mut params = #crate_ident::router::path::ReverseParamMap::new();
+                    #( params.insert(#field_names, &self.#fields); )*
+                    params
+                }
+            }
+        })
+    }
+}
+
+/// Returns the names of the parameters in a path pattern, following the
+/// syntax accepted by `cot::router::Route`.
+fn path_params(path: &str) -> Result<Vec<String>, String> {
+    let mut params = Vec::new();
+    let mut chars = path.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '{' => {
+                let param: String = chars.by_ref().take_while(|ch| *ch != '}').collect();
+                let param = param.trim();
+                let param = param.strip_prefix('*').unwrap_or(param);
+                let param = param.split_once(':').map_or(param, |(name, _)| name).trim();
+                if param.is_empty() {
+                    return Err("empty parameter name in the route path".to_owned());
+                }
+                params.push(param.to_owned());
+            }
+            '}' => {
+                return Err("closing brace without an opening brace in the route path".to_owned());
+            }
+            _ => {}
+        }
+    }
+
+    Ok(params)
+}
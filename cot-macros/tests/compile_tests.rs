@@ -6,18 +6,57 @@ fn derive_form() {
     t.pass("tests/ui/derive_form.rs");
 }
 
+#[rustversion::attr(not(nightly), ignore)]
+#[test]
+#[cfg_attr(miri, ignore)] // unsupported operation: extern static `pidfd_spawnp` is not supported by Miri
+fn derive_model_form() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_model_form.rs");
+}
+
+#[rustversion::attr(not(nightly), ignore)]
+#[test]
+#[cfg_attr(miri, ignore)] // unsupported operation: extern static `pidfd_spawnp` is not supported by Miri
+fn derive_route_ref() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_route_ref.rs");
+    t.compile_fail("tests/ui/derive_route_ref_missing_field.rs");
+    t.compile_fail("tests/ui/derive_route_ref_extra_field.rs");
+}
+
+#[rustversion::attr(not(nightly), ignore)]
+#[test]
+#[cfg_attr(miri, ignore)] // unsupported operation: extern static `pidfd_spawnp` is not supported by Miri
+fn derive_from_row() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_from_row.rs");
+}
+
+#[rustversion::attr(not(nightly), ignore)]
+#[test]
+#[cfg_attr(miri, ignore)] // unsupported operation: extern static `pidfd_spawnp` is not supported by Miri
+fn derive_db_enum() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_db_enum.rs");
+    t.compile_fail("tests/ui/derive_db_enum_tuple_variant.rs");
+}
+
 #[rustversion::attr(not(nightly), ignore)]
 #[test]
 #[cfg_attr(miri, ignore)] // unsupported operation: extern static `pidfd_spawnp` is not supported by Miri
 fn attr_model() {
     let t = trybuild::TestCases::new();
     t.pass("tests/ui/attr_model.rs");
+    t.pass("tests/ui/attr_model_many_to_many.rs");
     t.compile_fail("tests/ui/attr_model_migration_invalid_name.rs");
     t.compile_fail("tests/ui/attr_model_tuple.rs");
     t.compile_fail("tests/ui/attr_model_enum.rs");
     t.compile_fail("tests/ui/attr_model_generic.rs");
     t.compile_fail("tests/ui/attr_model_no_pk.rs");
     t.compile_fail("tests/ui/attr_model_multiple_pks.rs");
+    t.compile_fail("tests/ui/attr_model_on_delete_not_optional.rs");
+    t.compile_fail("tests/ui/attr_model_index_unknown_field.rs");
+    t.compile_fail("tests/ui/attr_model_search_unknown_field.rs");
 }
 
 #[rustversion::attr(not(nightly), ignore)]
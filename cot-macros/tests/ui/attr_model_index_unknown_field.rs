@@ -0,0 +1,10 @@
+use cot::db::model;
+
+#[model(unique(fields = ["tenant_id", "slug"]))]
+struct MyModel {
+    #[model(primary_key)]
+    id: i32,
+    tenant_id: i32,
+}
+
+fn main() {}
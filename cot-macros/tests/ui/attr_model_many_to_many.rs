@@ -0,0 +1,29 @@
+use cot::db::{model, query, Auto, ManyToMany, Model};
+
+#[derive(Debug)]
+#[model]
+struct Post {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    title: String,
+    tags: ManyToMany<Tag>,
+}
+
+#[derive(Debug)]
+#[model]
+struct Tag {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    name: String,
+}
+
+fn main() {
+    assert_eq!(Post::COLUMNS.len(), 2);
+    println!("{:?}", PostFields::tags.join_table());
+
+    let tag = Tag {
+        id: Auto::fixed(1),
+        name: String::from("rust"),
+    };
+    let _ = query!(Post, $tags.contains(&tag) && $title == "Hello");
+}
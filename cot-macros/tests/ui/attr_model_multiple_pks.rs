@@ -6,6 +6,12 @@ struct MyModel {
     id: i64,
     #[model(primary_key)]
     id_2: i64,
+    #[model(primary_key)]
+    id_3: i64,
+    #[model(primary_key)]
+    id_4: i64,
+    #[model(primary_key)]
+    id_5: i64,
     name: String,
 }
 
@@ -0,0 +1,17 @@
+use cot::db::{model, Auto};
+
+#[model]
+struct Parent {
+    #[model(primary_key)]
+    id: Auto<i32>,
+}
+
+#[model]
+struct Child {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    #[model(on_delete = "set_null")]
+    parent: cot::db::ForeignKey<Parent>,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use cot::db::model;
+
+#[model(search(fields = ["title", "body"]))]
+struct MyModel {
+    #[model(primary_key)]
+    id: i32,
+    title: String,
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+use cot::db::{Auto, DbEnum, model};
+use cot::form::Form;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DbEnum)]
+enum Status {
+    Draft,
+    #[db_enum(rename = "live", label = "Live")]
+    Published,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DbEnum)]
+#[db_enum(repr = "integer")]
+enum Priority {
+    Low = 1,
+    High = 10,
+}
+
+#[model]
+struct Task {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    status: Status,
+    priority: Option<Priority>,
+}
+
+#[derive(Form)]
+struct TaskForm {
+    status: Status,
+    priority: Option<Priority>,
+}
+
+fn main() {
+    assert_eq!(Status::Published.name(), "live");
+    assert_eq!(Priority::from_discriminant(10), Some(Priority::High));
+}
@@ -0,0 +1,9 @@
+use cot::db::DbEnum;
+
+#[derive(Debug, Copy, Clone, DbEnum)]
+enum Status {
+    Draft,
+    Published(u8),
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use cot::db::{FromRow, Row};
+
+#[derive(FromRow)]
+struct Named {
+    id: i32,
+    #[from_row(rename = "title")]
+    name: String,
+    description: Option<String>,
+}
+
+#[derive(FromRow)]
+struct Tuple(i32, String);
+
+fn read(row: &Row) -> cot::db::Result<(Named, Tuple)> {
+    Ok((Named::from_row(row)?, Tuple::from_row(row)?))
+}
+
+fn main() {
+    let _ = read;
+}
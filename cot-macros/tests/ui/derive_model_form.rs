@@ -0,0 +1,30 @@
+use cot::db::{Auto, Database, model};
+use cot::form::{Form, ModelForm};
+use cot::request::Request;
+
+#[derive(Debug, Clone, ModelForm)]
+#[model]
+struct Article {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    #[form(opt(max_length = 100))]
+    title: String,
+    body: Option<String>,
+    #[model(auto_now)]
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[expect(unused)]
+async fn test_endpoint(mut request: Request, db: Database) {
+    let form = ArticleForm::from_request(&mut request)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut article = form.save(&db).await.unwrap();
+    println!("title = {}, body = {:?}", form.title, form.body);
+
+    let form = ArticleForm::from_model(&article);
+    form.update(&mut article, &db).await.unwrap();
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+use cot::request::Request;
+use cot::response::Response;
+use cot::router::{RouteRef, Router};
+
+#[derive(RouteRef)]
+#[route(path = "/posts/{id:i64}/{{literal}}/{*rest}", name = "post")]
+struct PostRoute {
+    id: i64,
+    rest: String,
+}
+
+#[derive(RouteRef)]
+#[route(path = "/", name = "home", app = "main")]
+struct HomeRoute;
+
+async fn handler(_request: Request) -> cot::Result<Response> {
+    unimplemented!()
+}
+
+fn main() {
+    let _ = Router::with_urls([PostRoute::route(handler), HomeRoute::route(handler)]);
+    let _ = PostRoute {
+        id: 1,
+        rest: String::from("a/b"),
+    }
+    .params();
+}
@@ -0,0 +1,10 @@
+use cot::router::RouteRef;
+
+#[derive(RouteRef)]
+#[route(path = "/posts/{id}", name = "post")]
+struct PostRoute {
+    id: i64,
+    slug: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use cot::router::RouteRef;
+
+#[derive(RouteRef)]
+#[route(path = "/posts/{id}/{slug}", name = "post")]
+struct PostRoute {
+    id: i64,
+}
+
+fn main() {}
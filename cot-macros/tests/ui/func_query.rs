@@ -8,6 +8,7 @@ struct MyModel {
     name: std::string::String,
     description: String,
     visits: i32,
+    nickname: Option<String>,
 }
 
 fn main() {
@@ -15,4 +16,11 @@ fn main() {
         MyModel,
         $name == "hello" && $description == "world" || $visits == 0
     );
+    query!(
+        MyModel,
+        !($name.starts_with("he") || $description.icontains("WORLD"))
+            && $visits.between(1, 10)
+            && $id.is_in([1, 2, 3])
+            && !$nickname.is_null()
+    );
 }
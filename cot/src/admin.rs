@@ -171,16 +171,17 @@ impl Pagination {
 }
 
 #[derive(Debug, Deserialize)]
-struct PaginationParams {
+struct ModelListParams {
     page: Option<u64>,
     page_size: Option<u64>,
+    search: Option<String>,
 }
 
 async fn view_model(
     urls: Urls,
     managers: AdminModelManagers,
     Path(model_name): Path<String>,
-    UrlQuery(pagination_params): UrlQuery<PaginationParams>,
+    UrlQuery(list_params): UrlQuery<ModelListParams>,
     request: Request,
 ) -> cot::Result<Response> {
     #[derive(Debug, Template)]
@@ -193,6 +194,7 @@ async fn view_model(
         objects: Vec<Box<dyn AdminModel>>,
         page: u64,
         page_size: &'a u64,
+        search: &'a str,
         total_object_counts: u64,
         total_pages: u64,
     }
@@ -201,10 +203,15 @@ async fn view_model(
 
     let manager = get_manager(managers, &model_name)?;
 
-    let page = pagination_params.page.unwrap_or(1);
-    let page_size = pagination_params.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    let page = list_params.page.unwrap_or(1);
+    let page_size = list_params.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    let search = list_params
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|search| !search.is_empty() && manager.is_searchable());
 
-    let total_object_counts = manager.get_total_object_counts(&request).await?;
+    let total_object_counts = manager.get_total_object_counts(&request, search).await?;
     let total_pages = total_object_counts.div_ceil(page_size);
 
     if (page == 0 || page > total_pages) && total_pages > 0 {
@@ -213,7 +220,7 @@ async fn view_model(
 
     let pagination = Pagination::new(page_size, page);
 
-    let objects = manager.get_objects(&request, pagination).await?;
+    let objects = manager.get_objects(&request, pagination, search).await?;
 
     let template = ModelTemplate {
         urls: &urls,
@@ -221,6 +228,7 @@ async fn view_model(
         objects,
         page,
         page_size: &page_size,
+        search: search.unwrap_or_default(),
         total_object_counts,
         total_pages,
     };
@@ -399,15 +407,26 @@ pub trait AdminModelManager: Send + Sync {
     /// Returns the URL slug for the model.
     fn url_name(&self) -> &str;
 
-    /// Returns the list of objects of this model.
+    /// Returns whether the objects of this model can be searched, in which
+    /// case the admin panel shows a search box for the model.
+    fn is_searchable(&self) -> bool;
+
+    /// Returns the list of objects of this model, limited to the objects
+    /// matching `search` if given.
     async fn get_objects(
         &self,
         request: &Request,
         pagination: Pagination,
+        search: Option<&str>,
     ) -> cot::Result<Vec<Box<dyn AdminModel>>>;
 
-    /// Returns the total count of objects of this model.
-    async fn get_total_object_counts(&self, request: &Request) -> cot::Result<u64>;
+    /// Returns the total count of objects of this model, limited to the
+    /// objects matching `search` if given.
+    async fn get_total_object_counts(
+        &self,
+        request: &Request,
+        search: Option<&str>,
+    ) -> cot::Result<u64>;
 
     /// Returns the object with the given ID.
     async fn get_object_by_id(
@@ -483,22 +502,33 @@ impl<T: AdminModel + Send + Sync + 'static> AdminModelManager for DefaultAdminMo
         T::url_name()
     }
 
-    async fn get_total_object_counts(&self, request: &Request) -> cot::Result<u64> {
-        T::get_total_object_counts(request).await
+    fn is_searchable(&self) -> bool {
+        T::is_searchable()
+    }
+
+    async fn get_total_object_counts(
+        &self,
+        request: &Request,
+        search: Option<&str>,
+    ) -> cot::Result<u64> {
+        T::get_total_object_counts(request, search).await
     }
 
     async fn get_objects(
         &self,
         request: &Request,
         pagination: Pagination,
+        search: Option<&str>,
     ) -> cot::Result<Vec<Box<dyn AdminModel>>> {
         #[expect(trivial_casts)] // Upcast to the correct Box type
-        T::get_objects(request, pagination).await.map(|objects| {
-            objects
-                .into_iter()
-                .map(|object| Box::new(object) as Box<dyn AdminModel>)
-                .collect()
-        })
+        T::get_objects(request, pagination, search)
+            .await
+            .map(|objects| {
+                objects
+                    .into_iter()
+                    .map(|object| Box::new(object) as Box<dyn AdminModel>)
+                    .collect()
+            })
     }
 
     async fn get_object_by_id(
@@ -551,13 +581,28 @@ pub trait AdminModel: Any + Send + 'static {
     // bump the MSRV (lands in Rust 1.86)
     fn as_any(&self) -> &dyn Any;
 
-    /// Get the objects of this model.
-    async fn get_objects(request: &Request, pagination: Pagination) -> cot::Result<Vec<Self>>
+    /// Get whether the objects of this model can be searched.
+    ///
+    /// This is the case for the models declared with
+    /// `#[model(search(fields = [...]))]`.
+    fn is_searchable() -> bool
+    where
+        Self: Sized;
+
+    /// Get the objects of this model, limited to the objects matching
+    /// `search` (see [`Query::search`](crate::db::query::Query::search)) if
+    /// given.
+    async fn get_objects(
+        request: &Request,
+        pagination: Pagination,
+        search: Option<&str>,
+    ) -> cot::Result<Vec<Self>>
     where
         Self: Sized;
 
-    /// Get the total count of objects of this model.
-    async fn get_total_object_counts(request: &Request) -> cot::Result<u64>
+    /// Get the total count of objects of this model, limited to the objects
+    /// matching `search` if given.
+    async fn get_total_object_counts(request: &Request, search: Option<&str>) -> cot::Result<u64>
     where
         Self: Sized;
 
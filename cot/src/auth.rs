@@ -952,14 +952,15 @@ impl AuthInner {
     async fn login(&self, user: Box<dyn User + Send + Sync + 'static>) -> Result<()> {
         // Mitigate the session fixation attack by changing the session ID:
         // https://cheatsheetseries.owasp.org/cheatsheets/Session_Management_Cheat_Sheet.html#renew-the-session-id-after-any-privilege-level-change
-        self.session.cycle_id().await?;
+        let session: &tower_sessions::Session = &self.session;
+        session.cycle_id().await?;
 
         if let Some(user_id) = user.id() {
-            self.session.insert(USER_ID_SESSION_KEY, user_id).await?;
+            session.insert(USER_ID_SESSION_KEY, user_id).await?;
         }
         let secret_key = &self.secret_key;
         if let Some(session_auth_hash) = user.session_auth_hash(secret_key) {
-            self.session
+            session
                 .insert(SESSION_HASH_SESSION_KEY, session_auth_hash.as_bytes())
                 .await?;
         }
@@ -989,7 +990,7 @@ const USER_ID_SESSION_KEY: &str = "__cot_auth_user_id";
 const SESSION_HASH_SESSION_KEY: &str = "__cot_auth_session_hash";
 
 async fn get_user_with_saved_id(
-    session: &Session,
+    session: &tower_sessions::Session,
     auth_backend: &dyn AuthBackend,
     secret_key: &SecretKey,
     fallback_secret_keys: &[SecretKey],
@@ -1011,7 +1012,7 @@ async fn get_user_with_saved_id(
 
 async fn session_auth_hash_valid(
     user: &(dyn User + Send + Sync),
-    session: &Session,
+    session: &tower_sessions::Session,
     secret_key: &SecretKey,
     fallback_secret_keys: &[SecretKey],
 ) -> Result<bool> {
@@ -25,6 +25,7 @@ use crate::auth::{
 };
 use crate::config::SecretKey;
 use crate::db::migrations::SyncDynMigration;
+use crate::db::seed::{SeedEnvironment, Seeder};
 use crate::db::{Database, DatabaseBackend, LimitedString, Model, model, query};
 use crate::form::Form;
 
@@ -613,6 +614,98 @@ impl App for DatabaseUserApp {
     }
 }
 
+/// A seeder that creates a database user with the given username and
+/// password, unless a user with that username already exists.
+///
+/// This is typically used to create a default admin user for local
+/// development, so by default, it is only run in the
+/// [`Development`](SeedEnvironment::Development) environment. See the
+/// [`seed`](crate::db::seed) module for more information about seeding.
+///
+/// # Examples
+///
+/// ```
+/// use cot::App;
+/// use cot::auth::db::DatabaseUserSeeder;
+/// use cot::db::seed::Seeder;
+///
+/// struct MyApp;
+/// impl App for MyApp {
+///     fn name(&self) -> &str {
+///         "my_app"
+///     }
+///
+///     fn seeders(&self) -> Vec<Box<dyn Seeder>> {
+///         vec![Box::new(DatabaseUserSeeder::new("admin", "admin"))]
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DatabaseUserSeeder {
+    username: String,
+    password: Password,
+    environments: &'static [SeedEnvironment],
+}
+
+impl DatabaseUserSeeder {
+    /// Creates a new seeder for a user with the given username and password.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUserSeeder;
+    ///
+    /// let seeder = DatabaseUserSeeder::new("admin", "admin");
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>, U: Into<Password>>(username: T, password: U) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+            environments: &[SeedEnvironment::Development],
+        }
+    }
+
+    /// Sets the environments the seeder is run in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUserSeeder;
+    /// use cot::db::seed::{SeedEnvironment, Seeder};
+    ///
+    /// let seeder = DatabaseUserSeeder::new("admin", "admin").with_environments(SeedEnvironment::ALL);
+    /// assert_eq!(seeder.environments(), SeedEnvironment::ALL);
+    /// ```
+    #[must_use]
+    pub fn with_environments(mut self, environments: &'static [SeedEnvironment]) -> Self {
+        self.environments = environments;
+        self
+    }
+}
+
+#[async_trait]
+impl Seeder for DatabaseUserSeeder {
+    fn name(&self) -> &'static str {
+        "database_user"
+    }
+
+    fn environments(&self) -> &[SeedEnvironment] {
+        self.environments
+    }
+
+    async fn seed(&self, database: &Database) -> cot::Result<()> {
+        if DatabaseUser::get_by_username(database, &self.username)
+            .await?
+            .is_none()
+        {
+            DatabaseUser::create_user(database, self.username.clone(), &self.password).await?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,6 @@
 use std::fmt::{Debug, Formatter};
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::task::{Context, Poll, ready};
 
 use bytes::Bytes;
 use futures_core::Stream;
@@ -139,7 +139,9 @@ impl Body {
     ///
     /// This method returns an error if reading the body fails.
     ///
-    /// If the body is larger than the limit, an error is returned.
+    /// If the body is larger than the limit, an error is returned. When
+    /// returned from a request handler, this error is converted to a
+    /// `413 Payload Too Large` response.
     ///
     /// # Examples
     ///
@@ -161,7 +163,37 @@ impl Body {
             .collect()
             .await
             .map(http_body_util::Collected::to_bytes)
-            .map_err(|source| ErrorRepr::ReadRequestBody { source })?)
+            .map_err(|source| {
+                if source.is::<http_body_util::LengthLimitError>() {
+                    ErrorRepr::BodyTooLarge { limit }
+                } else {
+                    ErrorRepr::ReadRequestBody { source }
+                }
+            })?)
+    }
+
+    /// Convert this [`Body`] instance into a [`Stream`] of its data chunks.
+    ///
+    /// Unlike [`Self::into_bytes`], this doesn't read the entire body into
+    /// memory. See [`BodyStream`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use futures::StreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let mut stream = Body::fixed("Hello, world!").into_stream();
+    /// while let Some(chunk) = stream.next().await {
+    ///     println!("Received {} bytes", chunk?.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_stream(self) -> BodyStream {
+        BodyStream { body: self }
     }
 
     #[must_use]
@@ -243,6 +275,66 @@ impl http_body::Body for Body {
     }
 }
 
+/// A [`Stream`] of the data chunks of a [`Body`].
+///
+/// The chunks are read from the underlying body only when the stream is
+/// polled, so a slow consumer (e.g. one writing the chunks to a file or to
+/// a remote storage service) makes the client slow down its sending instead
+/// of the data piling up in memory. Empty chunks and HTTP trailers are
+/// skipped.
+///
+/// Created with [`Body::into_stream`] or
+/// [`RequestBodyExt::body_stream`](crate::request::RequestBodyExt::body_stream).
+///
+/// # Examples
+///
+/// ```
+/// use cot::Body;
+/// use futures::StreamExt;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let mut stream = Body::fixed("Hello, world!").into_stream();
+/// assert_eq!(stream.next().await.transpose()?.unwrap(), "Hello, world!");
+/// assert!(stream.next().await.is_none());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct BodyStream {
+    body: Body,
+}
+
+impl Stream for BodyStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let body = &mut self.get_mut().body;
+        loop {
+            match ready!(http_body::Body::poll_frame(Pin::new(&mut *body), cx)) {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        if !data.is_empty() {
+                            return Poll::Ready(Some(Ok(data)));
+                        }
+                    }
+                }
+                Some(Err(error)) => return Poll::Ready(Some(Err(error))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if http_body::Body::is_end_stream(&self.body) {
+            (0, Some(0))
+        } else {
+            (0, None)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::pin::Pin;
@@ -285,6 +377,59 @@ mod tests {
         }
     }
 
+    #[cot::test]
+    async fn body_into_bytes_limited() {
+        let body = Body::streaming(stream::once(async { Ok(Bytes::from("Hello")) }));
+        assert_eq!(body.into_bytes_limited(5).await.unwrap(), "Hello");
+
+        let body = Body::streaming(stream::once(async { Ok(Bytes::from("Hello, world!")) }));
+        let error = body.into_bytes_limited(5).await.unwrap_err();
+        assert!(matches!(error.inner, ErrorRepr::BodyTooLarge { limit: 5 }));
+    }
+
+    #[cot::test]
+    async fn body_into_stream() {
+        use futures::StreamExt;
+
+        let body = Body::streaming(stream::iter([
+            Ok(Bytes::from("Hello")),
+            Ok(Bytes::new()),
+            Ok(Bytes::from(", world!")),
+        ]));
+        let chunks: Vec<_> = body
+            .into_stream()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(chunks, ["Hello", ", world!"]);
+    }
+
+    #[cot::test]
+    async fn body_into_stream_error() {
+        use futures::StreamExt;
+
+        let body = Body::streaming(stream::iter([
+            Ok(Bytes::from("Hello")),
+            Err(Error::custom("connection reset")),
+        ]));
+        let mut stream = body.into_stream();
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), "Hello");
+        assert_eq!(
+            stream.next().await.unwrap().unwrap_err().to_string(),
+            "connection reset"
+        );
+    }
+
+    #[test]
+    fn body_stream_size_hint() {
+        use futures::Stream;
+
+        assert_eq!(Body::empty().into_stream().size_hint(), (0, Some(0)));
+        assert_eq!(Body::fixed("Hello").into_stream().size_hint(), (0, None));
+    }
+
     #[cot::test]
     async fn http_body_poll_frame_fixed() {
         let content = "Hello, world!";
@@ -1,22 +1,58 @@
 //! A command line interface for Cot-based applications.
 
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use async_trait::async_trait;
 pub use clap;
-use clap::{Arg, ArgMatches, Command, value_parser};
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
 use derive_more::Debug;
+use http::Method;
 
 use crate::error::ErrorRepr;
+use crate::router::RouteInfo;
 use crate::{Bootstrapper, Error, Result};
 
 const CONFIG_PARAM: &str = "config";
 const COLLECT_STATIC_SUBCOMMAND: &str = "collect-static";
 const CHECK_SUBCOMMAND: &str = "check";
+const MIDDLEWARES_SUBCOMMAND: &str = "middlewares";
+const ROUTES_SUBCOMMAND: &str = "routes";
+#[cfg(feature = "db")]
+const MIGRATE_SUBCOMMAND: &str = "migrate";
 const LISTEN_PARAM: &str = "listen";
 const COLLECT_STATIC_DIR_PARAM: &str = "dir";
+#[cfg(feature = "db")]
+const MIGRATE_APP_PARAM: &str = "app";
+#[cfg(feature = "db")]
+const MIGRATE_MIGRATION_PARAM: &str = "migration";
+#[cfg(feature = "db")]
+const MIGRATE_LIST_PARAM: &str = "list";
+#[cfg(feature = "db")]
+const SEED_SUBCOMMAND: &str = "seed";
+#[cfg(feature = "db")]
+const SEED_NAME_PARAM: &str = "name";
+#[cfg(feature = "db")]
+const SEED_ENV_PARAM: &str = "env";
+#[cfg(feature = "db")]
+const SEED_LIST_PARAM: &str = "list";
+#[cfg(feature = "db")]
+const DUMP_DATA_SUBCOMMAND: &str = "dump-data";
+#[cfg(feature = "db")]
+const LOAD_DATA_SUBCOMMAND: &str = "load-data";
+#[cfg(feature = "db")]
+const DATA_TABLES_PARAM: &str = "tables";
+#[cfg(feature = "db")]
+const DATA_FORMAT_PARAM: &str = "format";
+#[cfg(feature = "db")]
+const DATA_OUTPUT_PARAM: &str = "output";
+#[cfg(feature = "db")]
+const DATA_FILES_PARAM: &str = "files";
+/// The migration name meaning "before the first migration of the app".
+#[cfg(feature = "db")]
+const MIGRATE_ZERO: &str = "zero";
 
 /// A central point for configuring the default Command Line Interface (CLI) for
 /// Cot-powered projects.
@@ -91,6 +127,16 @@ impl Cli {
         let mut cli = Self { command, tasks };
         cli.add_task(Check);
         cli.add_task(CollectStatic);
+        cli.add_task(Middlewares);
+        cli.add_task(Routes);
+        #[cfg(feature = "db")]
+        cli.add_task(Migrate);
+        #[cfg(feature = "db")]
+        cli.add_task(Seed);
+        #[cfg(feature = "db")]
+        cli.add_task(DumpData);
+        #[cfg(feature = "db")]
+        cli.add_task(LoadData);
 
         cli
     }
@@ -386,6 +432,114 @@ impl CliTask for Check {
     }
 }
 
+struct Middlewares;
+#[async_trait(?Send)]
+impl CliTask for Middlewares {
+    fn subcommand(&self) -> Command {
+        Command::new(MIDDLEWARES_SUBCOMMAND)
+            .about("Lists the middlewares applied to the requests, starting with the outermost one")
+    }
+
+    async fn execute(
+        &mut self,
+        _matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let bootstrapper = bootstrapper.boot().await?;
+        print!(
+            "{}",
+            format_middleware_stack(bootstrapper.context().middleware_stack())
+        );
+        Ok(())
+    }
+}
+
+fn format_middleware_stack(middleware_stack: &[String]) -> String {
+    if middleware_stack.is_empty() {
+        return "No middlewares\n".to_owned();
+    }
+
+    let mut output = String::new();
+    for (index, name) in middleware_stack.iter().enumerate() {
+        writeln!(output, "{}. {name}", index + 1).expect("writing to a String cannot fail");
+    }
+    output
+}
+
+struct Routes;
+#[async_trait(?Send)]
+impl CliTask for Routes {
+    fn subcommand(&self) -> Command {
+        Command::new(ROUTES_SUBCOMMAND)
+            .about("Lists the routes of the project, in the order they are matched")
+    }
+
+    async fn execute(
+        &mut self,
+        _matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let bootstrapper = bootstrapper.boot().await?;
+        print!("{}", format_routes(&bootstrapper.context().routes()));
+        Ok(())
+    }
+}
+
+fn format_routes(routes: &[RouteInfo]) -> String {
+    if routes.is_empty() {
+        return "No routes\n".to_owned();
+    }
+
+    let header = ["METHODS", "PATH", "NAME", "APP", "HANDLER"].map(ToOwned::to_owned);
+    let rows: Vec<[String; 5]> = routes
+        .iter()
+        .map(|route| {
+            let methods = route.methods().map_or_else(
+                || "ANY".to_owned(),
+                |methods| {
+                    methods
+                        .iter()
+                        .map(Method::as_str)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                },
+            );
+            let mut path = route.path().to_owned();
+            if route.is_service() {
+                path.push_str("/*");
+            }
+            if let Some(host) = route.host() {
+                path = format!("{host}{path}");
+            }
+
+            [
+                methods,
+                path,
+                route.name().unwrap_or("-").to_owned(),
+                route.app_name().unwrap_or("-").to_owned(),
+                route.handler_type_name().to_owned(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.clone().map(|column| column.len());
+    for row in &rows {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.len());
+        }
+    }
+
+    let mut output = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let mut line = String::new();
+        for (column, width) in row.iter().zip(widths) {
+            write!(line, "{column:width$}  ").expect("writing to a String cannot fail");
+        }
+        writeln!(output, "{}", line.trim_end()).expect("writing to a String cannot fail");
+    }
+    output
+}
+
 /// A macro to generate a [`CliMetadata`] struct from the Cargo manifest.
 #[macro_export]
 macro_rules! metadata {
@@ -401,6 +555,320 @@ macro_rules! metadata {
 
 pub use metadata;
 
+#[cfg(feature = "db")]
+struct Migrate;
+#[cfg(feature = "db")]
+#[async_trait(?Send)]
+impl CliTask for Migrate {
+    fn subcommand(&self) -> Command {
+        Command::new(MIGRATE_SUBCOMMAND)
+            .about("Applies or reverts the database migrations")
+            .arg(
+                Arg::new(MIGRATE_APP_PARAM)
+                    .help("The app to migrate; if not given, all the migrations are applied"),
+            )
+            .arg(
+                Arg::new(MIGRATE_MIGRATION_PARAM)
+                    .help(
+                        "The migration to migrate the app to, e.g. `0003`; \
+                        use `zero` to revert all the migrations of the app",
+                    )
+                    .requires(MIGRATE_APP_PARAM),
+            )
+            .arg(
+                Arg::new(MIGRATE_LIST_PARAM)
+                    .long("list")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with(MIGRATE_APP_PARAM)
+                    .help("Lists the migrations along with whether they are applied"),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let bootstrapper = bootstrapper.with_apps().with_database().await?;
+        let context = bootstrapper.context();
+        let database = context
+            .try_database()
+            .ok_or_else(|| Error::custom("Database is not configured for the project"))?;
+        let engine = crate::project::migration_engine(context.apps())?;
+
+        if matches.get_flag(MIGRATE_LIST_PARAM) {
+            print!(
+                "{}",
+                format_migration_status(&engine.status(database).await?)
+            );
+            return Ok(());
+        }
+
+        match matches.get_one::<String>(MIGRATE_APP_PARAM) {
+            None => engine.run(database).await?,
+            Some(app_name) => {
+                let target = match matches.get_one::<String>(MIGRATE_MIGRATION_PARAM) {
+                    Some(migration) if migration == MIGRATE_ZERO => None,
+                    Some(migration) => Some(migration.clone()),
+                    None => engine
+                        .status(database)
+                        .await?
+                        .into_iter()
+                        .rev()
+                        .find(|status| status.app_name() == app_name)
+                        .map(|status| status.name().to_owned()),
+                };
+                engine
+                    .migrate_to(database, app_name, target.as_deref())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "db")]
+fn format_migration_status(statuses: &[MigrationStatus]) -> String {
+    if statuses.is_empty() {
+        return "No migrations\n".to_owned();
+    }
+
+    let mut app_names: Vec<&str> = Vec::new();
+    for status in statuses {
+        if !app_names.contains(&status.app_name()) {
+            app_names.push(status.app_name());
+        }
+    }
+
+    let mut output = String::new();
+    for app_name in app_names {
+        writeln!(output, "{app_name}").expect("writing to a String cannot fail");
+        for status in statuses.iter().filter(|s| s.app_name() == app_name) {
+            let mark = if status.is_applied() { 'X' } else { ' ' };
+            writeln!(output, " [{mark}] {}", status.name())
+                .expect("writing to a String cannot fail");
+        }
+    }
+    output
+}
+
+#[cfg(feature = "db")]
+struct Seed;
+#[cfg(feature = "db")]
+#[async_trait(?Send)]
+impl CliTask for Seed {
+    fn subcommand(&self) -> Command {
+        Command::new(SEED_SUBCOMMAND)
+            .about("Applies the pending migrations and seeds the database with initial data")
+            .arg(
+                Arg::new(SEED_NAME_PARAM)
+                    .help("The name of the seeder to run; if not given, all the seeders are run"),
+            )
+            .arg(
+                Arg::new(SEED_ENV_PARAM)
+                    .long("env")
+                    .value_name("ENV")
+                    .value_parser(value_parser!(SeedEnvironment))
+                    .help(
+                        "The environment to run the seeders for (`development` or \
+                        `production`); defaults to `development` if the `debug` \
+                        config option is enabled, and `production` otherwise",
+                    ),
+            )
+            .arg(
+                Arg::new(SEED_LIST_PARAM)
+                    .long("list")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with(SEED_NAME_PARAM)
+                    .help("Lists the seeders that would be run, without running them"),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let bootstrapper = bootstrapper.with_apps().with_database().await?;
+        let context = bootstrapper.context();
+        let environment = matches
+            .get_one::<SeedEnvironment>(SEED_ENV_PARAM)
+            .copied()
+            .unwrap_or_else(|| SeedEnvironment::from_debug(context.config().debug));
+        let engine = crate::project::seed_engine(context.apps());
+
+        if matches.get_flag(SEED_LIST_PARAM) {
+            print!("{}", format_seeders(&engine, environment));
+            return Ok(());
+        }
+
+        let database = context
+            .try_database()
+            .ok_or_else(|| Error::custom("Database is not configured for the project"))?;
+        crate::project::migration_engine(context.apps())?
+            .run(database)
+            .await?;
+        let seeded = engine
+            .run(
+                database,
+                environment,
+                matches
+                    .get_one::<String>(SEED_NAME_PARAM)
+                    .map(String::as_str),
+            )
+            .await?;
+        println!(
+            "Ran {} seeder(s) for the {environment} environment",
+            seeded.len()
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "db")]
+fn format_seeders(engine: &SeedEngine, environment: SeedEnvironment) -> String {
+    let mut output = String::new();
+    for seeder in engine.seeders(environment) {
+        writeln!(output, "{}", seeder.name()).expect("writing to a String cannot fail");
+    }
+    if output.is_empty() {
+        output = format!("No seeders for the {environment} environment\n");
+    }
+    output
+}
+
+#[cfg(feature = "db")]
+struct DumpData;
+#[cfg(feature = "db")]
+#[async_trait(?Send)]
+impl CliTask for DumpData {
+    fn subcommand(&self) -> Command {
+        Command::new(DUMP_DATA_SUBCOMMAND)
+            .about("Dumps the data stored in the database to a fixture")
+            .arg(
+                Arg::new(DATA_TABLES_PARAM)
+                    .num_args(0..)
+                    .help("The tables to dump; if not given, all the tables are dumped"),
+            )
+            .arg(
+                Arg::new(DATA_OUTPUT_PARAM)
+                    .short('o')
+                    .long("output")
+                    .value_name("FILE")
+                    .value_parser(value_parser!(PathBuf))
+                    .help("The file to write the fixture to; if not given, it is printed"),
+            )
+            .arg(
+                Arg::new(DATA_FORMAT_PARAM)
+                    .short('f')
+                    .long("format")
+                    .value_name("FORMAT")
+                    .value_parser(value_parser!(FixtureFormat))
+                    .help(
+                        "The format of the fixture; if not given, it is determined by \
+                        the extension of the output file, and defaults to JSON",
+                    ),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let output = matches.get_one::<PathBuf>(DATA_OUTPUT_PARAM);
+        let format = match (matches.get_one::<FixtureFormat>(DATA_FORMAT_PARAM), output) {
+            (Some(format), _) => *format,
+            (None, Some(output)) => FixtureFormat::from_path(output)
+                .ok_or_else(|| FixtureError::UnknownFormat {
+                    path: output.clone(),
+                })
+                .map_err(crate::db::DatabaseError::from)?,
+            (None, None) => FixtureFormat::Json,
+        };
+        let tables: Vec<&str> = matches
+            .get_many::<String>(DATA_TABLES_PARAM)
+            .unwrap_or_default()
+            .map(String::as_str)
+            .collect();
+
+        let bootstrapper = bootstrapper.with_apps().with_database().await?;
+        let context = bootstrapper.context();
+        let database = context
+            .try_database()
+            .ok_or_else(|| Error::custom("Database is not configured for the project"))?;
+        let schema = FixtureSchema::new(&crate::project::migration_engine(context.apps())?);
+
+        let fixture = Fixture::dump(database, &schema, &tables)
+            .await?
+            .to_format(format)?;
+        match output {
+            Some(output) => std::fs::write(output, fixture).map_err(|error| {
+                crate::db::DatabaseError::from(FixtureError::Io {
+                    path: output.clone(),
+                    message: error.to_string(),
+                })
+            })?,
+            None => println!("{fixture}"),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "db")]
+struct LoadData;
+#[cfg(feature = "db")]
+#[async_trait(?Send)]
+impl CliTask for LoadData {
+    fn subcommand(&self) -> Command {
+        Command::new(LOAD_DATA_SUBCOMMAND)
+            .about("Loads fixtures into the database")
+            .arg(
+                Arg::new(DATA_FILES_PARAM)
+                    .num_args(1..)
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("The fixture files to load; the format is determined by the extension"),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let fixtures = matches
+            .get_many::<PathBuf>(DATA_FILES_PARAM)
+            .expect("required argument")
+            .map(Fixture::read)
+            .collect::<crate::db::Result<Vec<_>>>()?;
+
+        let bootstrapper = bootstrapper.with_apps().with_database().await?;
+        let context = bootstrapper.context();
+        let database = context
+            .try_database()
+            .ok_or_else(|| Error::custom("Database is not configured for the project"))?;
+        let schema = FixtureSchema::new(&crate::project::migration_engine(context.apps())?);
+
+        let mut loaded = 0;
+        for fixture in &fixtures {
+            loaded += fixture.load(database, &schema).await?;
+        }
+        println!("Loaded {loaded} row(s)");
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "db")]
+use crate::db::fixtures::{Fixture, FixtureError, FixtureFormat, FixtureSchema};
+#[cfg(feature = "db")]
+use crate::db::migrations::MigrationStatus;
+#[cfg(feature = "db")]
+use crate::db::seed::{SeedEngine, SeedEnvironment};
 use crate::project::WithConfig;
 use crate::static_files::StaticFiles;
 
@@ -415,6 +883,7 @@ mod tests {
     use super::*;
     use crate::config::ProjectConfig;
     use crate::project::RegisterAppsContext;
+    use crate::router::{Route, Router};
     use crate::{App, AppBuilder};
 
     #[test]
@@ -561,6 +1030,399 @@ mod tests {
         check.execute(&matches, bootstrapper).await
     }
 
+    #[test]
+    fn format_middleware_stack_empty() {
+        assert_eq!(format_middleware_stack(&[]), "No middlewares\n");
+    }
+
+    #[test]
+    fn format_middleware_stack_numbered() {
+        let stack = ["SessionMiddleware".to_owned(), "auth".to_owned()];
+
+        assert_eq!(
+            format_middleware_stack(&stack),
+            "1. SessionMiddleware\n2. auth\n"
+        );
+    }
+
+    #[test]
+    fn format_routes_empty() {
+        assert_eq!(format_routes(&[]), "No routes\n");
+    }
+
+    #[test]
+    fn format_routes_table() {
+        async fn index(_request: crate::request::Request) -> Result<crate::response::Response> {
+            unimplemented!()
+        }
+        async fn login(_request: crate::request::Request) -> Result<crate::response::Response> {
+            unimplemented!()
+        }
+
+        let router = Router::with_urls([
+            Route::with_handler_and_name("/", index, "index"),
+            Route::with_methods("/login").get(login).post(login),
+        ]);
+        let output = format_routes(&router.route_infos());
+        let lines: Vec<_> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("METHODS   PATH    NAME   APP  HANDLER"));
+        assert!(lines[1].starts_with("ANY       /       index  -    "));
+        assert!(lines[1].ends_with("index"));
+        assert!(lines[2].starts_with("GET,POST  /login  -      -    "));
+    }
+
+    #[cot::test]
+    async fn routes_execute() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let mut routes = Routes;
+        let matches = Routes.subcommand().get_matches_from(Vec::<&str>::new());
+
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(ProjectConfig::default());
+        let result = routes.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[cot::test]
+    async fn middlewares_execute() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let mut middlewares = Middlewares;
+        let matches = Middlewares
+            .subcommand()
+            .get_matches_from(Vec::<&str>::new());
+
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(ProjectConfig::default());
+        let result = middlewares.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn format_migration_status_empty() {
+        assert_eq!(format_migration_status(&[]), "No migrations\n");
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    async fn format_migration_status_grouped_by_app() {
+        let engine = crate::db::migrations::MigrationEngine::new([
+            crate::test::TestMigration::new("app1", "m_0001_initial", [], []),
+            crate::test::TestMigration::new(
+                "app2",
+                "m_0001_initial",
+                [crate::db::migrations::MigrationDependency::migration(
+                    "app1",
+                    "m_0001_initial",
+                )],
+                [],
+            ),
+            crate::test::TestMigration::new(
+                "app1",
+                "m_0002_second",
+                [crate::db::migrations::MigrationDependency::migration(
+                    "app2",
+                    "m_0001_initial",
+                )],
+                [],
+            ),
+        ])
+        .unwrap();
+        let database = crate::db::Database::new("sqlite::memory:").await.unwrap();
+        engine
+            .migrate_to(&database, "app2", Some("0001"))
+            .await
+            .unwrap();
+
+        let output = format_migration_status(&engine.status(&database).await.unwrap());
+
+        assert_eq!(
+            output,
+            "app1\n [X] m_0001_initial\n [ ] m_0002_second\napp2\n [X] m_0001_initial\n"
+        );
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    async fn migrate_execute_no_database() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let mut migrate = Migrate;
+        let matches = Migrate.subcommand().get_matches_from(Vec::<&str>::new());
+
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(ProjectConfig::default());
+        let result = migrate.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    async fn migrate_execute_list() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let mut migrate = Migrate;
+        let matches = Migrate.subcommand().get_matches_from(["migrate", "--list"]);
+
+        let config = ProjectConfig::builder()
+            .database(
+                crate::config::DatabaseConfig::builder()
+                    .url("sqlite::memory:")
+                    .build(),
+            )
+            .build();
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(config);
+        let result = migrate.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn migrate_subcommand_migration_requires_app() {
+        let result = Migrate
+            .subcommand()
+            .try_get_matches_from(["migrate", "--list", "myapp"]);
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "db")]
+    struct TestSeeder(&'static str, &'static [SeedEnvironment]);
+
+    #[cfg(feature = "db")]
+    #[async_trait]
+    impl crate::db::seed::Seeder for TestSeeder {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn environments(&self) -> &[SeedEnvironment] {
+            self.1
+        }
+
+        async fn seed(&self, _database: &crate::db::Database) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn format_seeders_empty() {
+        let engine = SeedEngine::new([]);
+
+        assert_eq!(
+            format_seeders(&engine, SeedEnvironment::Production),
+            "No seeders for the production environment\n"
+        );
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn format_seeders_filters_environment() {
+        let seeders: [Box<dyn crate::db::seed::Seeder>; 2] = [
+            Box::new(TestSeeder("admin_user", &[SeedEnvironment::Development])),
+            Box::new(TestSeeder("countries", SeedEnvironment::ALL)),
+        ];
+        let engine = SeedEngine::new(seeders);
+
+        assert_eq!(
+            format_seeders(&engine, SeedEnvironment::Development),
+            "admin_user\ncountries\n"
+        );
+        assert_eq!(
+            format_seeders(&engine, SeedEnvironment::Production),
+            "countries\n"
+        );
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn seed_subcommand_env() {
+        let matches = Seed
+            .subcommand()
+            .get_matches_from(["seed", "--env", "prod"]);
+        assert_eq!(
+            matches.get_one::<SeedEnvironment>(SEED_ENV_PARAM),
+            Some(&SeedEnvironment::Production)
+        );
+
+        let result = Seed
+            .subcommand()
+            .try_get_matches_from(["seed", "--env", "staging"]);
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    async fn seed_execute_no_database() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let mut seed = Seed;
+        let matches = Seed.subcommand().get_matches_from(["seed"]);
+
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(ProjectConfig::default());
+        let result = seed.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn seed_execute() {
+        struct TestApp;
+        impl App for TestApp {
+            fn name(&self) -> &'static str {
+                "test_app"
+            }
+
+            fn seeders(&self) -> Vec<Box<dyn crate::db::seed::Seeder>> {
+                vec![Box::new(TestSeeder("countries", SeedEnvironment::ALL))]
+            }
+        }
+
+        struct TestProject;
+        impl cot::Project for TestProject {
+            fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+                apps.register(TestApp);
+            }
+        }
+
+        let mut seed = Seed;
+        let matches = Seed.subcommand().get_matches_from(["seed", "countries"]);
+
+        let config = ProjectConfig::builder()
+            .database(
+                crate::config::DatabaseConfig::builder()
+                    .url("sqlite::memory:")
+                    .build(),
+            )
+            .build();
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(config);
+        let result = seed.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[cfg(feature = "db")]
+    fn sqlite_memory_config() -> ProjectConfig {
+        ProjectConfig::builder()
+            .database(
+                crate::config::DatabaseConfig::builder()
+                    .url("sqlite::memory:")
+                    .build(),
+            )
+            .build()
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn dump_data_subcommand() {
+        let matches = DumpData.subcommand().get_matches_from([
+            "dump-data",
+            "cot__database_user",
+            "--format",
+            "JSON",
+            "-o",
+            "users.json",
+        ]);
+
+        assert_eq!(
+            matches
+                .get_many::<String>(DATA_TABLES_PARAM)
+                .unwrap()
+                .collect::<Vec<_>>(),
+            ["cot__database_user"]
+        );
+        assert_eq!(
+            matches.get_one::<FixtureFormat>(DATA_FORMAT_PARAM),
+            Some(&FixtureFormat::Json)
+        );
+        assert_eq!(
+            matches.get_one::<PathBuf>(DATA_OUTPUT_PARAM),
+            Some(&PathBuf::from("users.json"))
+        );
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn load_data_subcommand_requires_file() {
+        let result = LoadData.subcommand().try_get_matches_from(["load-data"]);
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    async fn dump_data_execute_no_database() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let mut dump_data = DumpData;
+        let matches = DumpData.subcommand().get_matches_from(["dump-data"]);
+
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(ProjectConfig::default());
+        let result = dump_data.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    async fn dump_data_execute_unknown_output_format() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let mut dump_data = DumpData;
+        let matches = DumpData
+            .subcommand()
+            .get_matches_from(["dump-data", "-o", "data.txt"]);
+
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(sqlite_memory_config());
+        let result = dump_data.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn dump_and_load_data_execute() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("data.json");
+        let path_str = path.to_str().unwrap();
+
+        let mut dump_data = DumpData;
+        let matches = DumpData
+            .subcommand()
+            .get_matches_from(["dump-data", "-o", path_str]);
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(sqlite_memory_config());
+        let result = dump_data.execute(&matches, bootstrapper).await;
+        assert!(result.is_ok(), "{result:?}");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[]");
+
+        let mut load_data = LoadData;
+        let matches = LoadData
+            .subcommand()
+            .get_matches_from(["load-data", path_str]);
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(sqlite_memory_config());
+        let result = load_data.execute(&matches, bootstrapper).await;
+        assert!(result.is_ok(), "{result:?}");
+    }
+
     #[test]
     fn get_user_friendly_error_addr_in_use() {
         let source = std::io::Error::new(std::io::ErrorKind::AddrInUse, "error");
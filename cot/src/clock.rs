@@ -0,0 +1,98 @@
+//! Time source used by the framework.
+//!
+//! Everything in Cot that depends on the current time (such as the
+//! [`RateLimiter`](crate::middleware::RateLimiter)) reads it from a [`Clock`]
+//! instead of calling [`Utc::now`] directly. By default this is the
+//! [`SystemClock`], but a different clock can be set with
+//! [`Bootstrapper::with_clock`](crate::Bootstrapper::with_clock) — most
+//! notably [`TestClock`](crate::test::TestClock), which makes it possible to
+//! test expiry logic deterministically.
+
+use std::fmt::Debug;
+
+use chrono::{DateTime, Utc};
+use time::OffsetDateTime;
+
+/// A source of the current time.
+///
+/// The clock used by the project can be accessed with
+/// [`ProjectContext::clock`](crate::ProjectContext::clock).
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use cot::clock::Clock;
+///
+/// /// A clock that is always one hour ahead.
+/// #[derive(Debug)]
+/// struct AheadClock;
+///
+/// impl Clock for AheadClock {
+///     fn now(&self) -> DateTime<Utc> {
+///         Utc::now() + chrono::Duration::hours(1)
+///     }
+/// }
+/// ```
+pub trait Clock: Debug + Send + Sync + 'static {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The default [`Clock`] that returns the real, system time.
+///
+/// # Examples
+///
+/// ```
+/// use cot::clock::{Clock, SystemClock};
+///
+/// let now = SystemClock.now();
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Returns the current time of the clock as an [`OffsetDateTime`], which is
+/// the type used by the session records.
+pub(crate) fn now_offset(clock: &dyn Clock) -> OffsetDateTime {
+    let now = clock.now();
+    let nanos =
+        i128::from(now.timestamp()) * 1_000_000_000 + i128::from(now.timestamp_subsec_nanos());
+
+    OffsetDateTime::from_unix_timestamp_nanos(nanos)
+        .expect("the current time should be in the range supported by `OffsetDateTime`")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::test::TestClock;
+
+    #[test]
+    fn system_clock_returns_current_time() {
+        let before = Utc::now();
+        let now = SystemClock.now();
+        let after = Utc::now();
+
+        assert!(before <= now && now <= after);
+    }
+
+    #[test]
+    fn now_offset_matches_clock() {
+        let time = Utc.with_ymd_and_hms(2025, 1, 1, 12, 30, 0).unwrap()
+            + chrono::Duration::nanoseconds(123);
+        let clock = TestClock::at(time);
+
+        let now = now_offset(&clock);
+
+        assert_eq!(now.unix_timestamp(), time.timestamp());
+        assert_eq!(now.nanosecond(), 123);
+    }
+}
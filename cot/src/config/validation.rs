@@ -0,0 +1,1211 @@
+//! Validation of the project configuration.
+//!
+//! The configuration is validated when the project starts, before the server
+//! starts listening for connections. All the problems found are collected
+//! into a [`ConfigReport`]: the errors make the startup fail, and the
+//! warnings (such as insecure settings used in production) are logged.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::config::ProjectConfig;
+//!
+//! let config = ProjectConfig::from_toml(
+//!     r#"
+//! debug = false
+//! secret_key = "too short"
+//! "#,
+//! )?;
+//!
+//! let report = config.validate();
+//! assert!(report.has_errors());
+//! assert_eq!(report.errors().next().unwrap().key(), "secret_key");
+//! # Ok::<(), cot::Error>(())
+//! ```
+
+mod keys;
+
+use std::fmt::{Display, Formatter};
+
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::config::validation::keys::KeyTracker;
+use crate::config::{
+    MaintenanceMiddlewareConfig, ProjectConfig, RateLimitMiddlewareConfig, RateLimitStoreType,
+    SecurityHeadersMiddlewareConfig, SessionMiddlewareConfig, SessionStoreType,
+};
+use crate::session::cookie::SameSite;
+
+/// The minimum length of the secret keys, in bytes, when running in
+/// production.
+pub const MIN_SECRET_KEY_LENGTH: usize = 32;
+
+/// The minimum value of the `server.buffers.http1_max_buf_size` setting
+/// accepted by the HTTP/1 server.
+const MIN_HTTP1_MAX_BUF_SIZE: usize = 8192;
+
+/// Keys that are still accepted under an old name, along with the keys that
+/// replace them.
+const DEPRECATED_KEYS: &[DeprecatedKey] = &[
+    DeprecatedKey {
+        key: "middlewares.session.http_only",
+        replacement: "middlewares.session.cookie_http_only",
+    },
+    DeprecatedKey {
+        key: "middlewares.session.same_site",
+        replacement: "middlewares.session.cookie_same_site",
+    },
+];
+
+#[derive(Debug, Copy, Clone)]
+struct DeprecatedKey {
+    key: &'static str,
+    replacement: &'static str,
+}
+
+/// The severity of a [`ConfigIssue`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::validation::{ConfigIssue, Severity};
+///
+/// let issue = ConfigIssue::warning("debug", "debug mode is enabled");
+/// assert_eq!(issue.severity(), Severity::Warning);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The configuration works, but is likely a mistake or is insecure.
+    Warning,
+    /// The configuration is invalid and the project can't start.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A single problem found in the configuration.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::validation::{ConfigIssue, Severity};
+///
+/// let issue = ConfigIssue::error("middlewares.session.secure", "must be `true`");
+/// assert_eq!(issue.severity(), Severity::Error);
+/// assert_eq!(issue.key(), "middlewares.session.secure");
+/// assert_eq!(issue.message(), "must be `true`");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    severity: Severity,
+    key: String,
+    message: String,
+}
+
+impl ConfigIssue {
+    /// Creates a new [`ConfigIssue`] with [`Severity::Error`].
+    ///
+    /// `key` is the dotted path of the offending key, as written in the TOML
+    /// config (e.g. `middlewares.session.secure`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::ConfigIssue;
+    ///
+    /// let issue = ConfigIssue::error("secret_key", "the secret key is too short");
+    /// ```
+    #[must_use]
+    pub fn error(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, key, message)
+    }
+
+    /// Creates a new [`ConfigIssue`] with [`Severity::Warning`].
+    ///
+    /// `key` is the dotted path of the offending key, as written in the TOML
+    /// config (e.g. `middlewares.session.secure`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::ConfigIssue;
+    ///
+    /// let issue = ConfigIssue::warning("middlewares.session.secure", "cookies are not secure");
+    /// ```
+    #[must_use]
+    pub fn warning(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, key, message)
+    }
+
+    fn new(severity: Severity, key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Returns the severity of the issue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::{ConfigIssue, Severity};
+    ///
+    /// let issue = ConfigIssue::error("secret_key", "the secret key is too short");
+    /// assert_eq!(issue.severity(), Severity::Error);
+    /// ```
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the dotted path of the offending key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::ConfigIssue;
+    ///
+    /// let issue = ConfigIssue::error("secret_key", "the secret key is too short");
+    /// assert_eq!(issue.key(), "secret_key");
+    /// ```
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the description of the issue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::ConfigIssue;
+    ///
+    /// let issue = ConfigIssue::error("secret_key", "the secret key is too short");
+    /// assert_eq!(issue.message(), "the secret key is too short");
+    /// ```
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ConfigIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at `{}`: {}", self.severity, self.key, self.message)
+    }
+}
+
+/// The result of validating the configuration: a list of [`ConfigIssue`]s.
+///
+/// When returned as an error, the report only lists the errors.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::ProjectConfig;
+///
+/// let report = ProjectConfig::dev_default().validate();
+/// assert!(!report.has_errors());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Error)]
+pub struct ConfigReport {
+    issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    /// Creates an empty [`ConfigReport`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::ConfigReport;
+    ///
+    /// let report = ConfigReport::new();
+    /// assert!(report.is_empty());
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an issue to the report.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::{ConfigIssue, ConfigReport};
+    ///
+    /// let mut report = ConfigReport::new();
+    /// report.push(ConfigIssue::error(
+    ///     "secret_key",
+    ///     "the secret key is too short",
+    /// ));
+    /// assert!(report.has_errors());
+    /// ```
+    pub fn push(&mut self, issue: ConfigIssue) {
+        self.issues.push(issue);
+    }
+
+    /// Returns all the issues in the report.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::{ConfigIssue, ConfigReport};
+    ///
+    /// let mut report = ConfigReport::new();
+    /// report.push(ConfigIssue::warning("debug", "debug mode is enabled"));
+    /// assert_eq!(report.issues().len(), 1);
+    /// ```
+    #[must_use]
+    pub fn issues(&self) -> &[ConfigIssue] {
+        &self.issues
+    }
+
+    /// Returns the issues with [`Severity::Error`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::{ConfigIssue, ConfigReport};
+    ///
+    /// let mut report = ConfigReport::new();
+    /// report.push(ConfigIssue::warning("debug", "debug mode is enabled"));
+    /// assert_eq!(report.errors().count(), 0);
+    /// ```
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.with_severity(Severity::Error)
+    }
+
+    /// Returns the issues with [`Severity::Warning`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::{ConfigIssue, ConfigReport};
+    ///
+    /// let mut report = ConfigReport::new();
+    /// report.push(ConfigIssue::warning("debug", "debug mode is enabled"));
+    /// assert_eq!(report.warnings().count(), 1);
+    /// ```
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.with_severity(Severity::Warning)
+    }
+
+    fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.severity == severity)
+    }
+
+    /// Returns `true` if the report contains any errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::{ConfigIssue, ConfigReport};
+    ///
+    /// let mut report = ConfigReport::new();
+    /// assert!(!report.has_errors());
+    /// report.push(ConfigIssue::error(
+    ///     "secret_key",
+    ///     "the secret key is too short",
+    /// ));
+    /// assert!(report.has_errors());
+    /// ```
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Returns `true` if no issues were found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::validation::ConfigReport;
+    ///
+    /// assert!(ConfigReport::new().is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Logs the warnings and returns an error containing the report if there
+    /// are any errors.
+    pub(crate) fn into_result(self) -> Result<(), Self> {
+        for warning in self.warnings() {
+            warn!(key = warning.key(), "Configuration {warning}");
+        }
+
+        if self.has_errors() { Err(self) } else { Ok(()) }
+    }
+}
+
+impl Display for ConfigReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let count = self.errors().count();
+        write!(
+            f,
+            "found {count} {}",
+            if count == 1 { "error" } else { "errors" }
+        )?;
+        for error in self.errors() {
+            write!(f, "\n  - {error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the semantics of the configuration. Used by
+/// [`ProjectConfig::validate`].
+pub(super) fn validate(config: &ProjectConfig) -> ConfigReport {
+    let mut report = ConfigReport::new();
+    let production = !config.debug;
+
+    if production {
+        check_secret_key(&mut report, "secret_key", config.secret_key.as_bytes());
+        for (index, key) in config.fallback_secret_keys.iter().enumerate() {
+            check_secret_key(
+                &mut report,
+                &format!("fallback_secret_keys[{index}]"),
+                key.as_bytes(),
+            );
+        }
+    }
+
+    check_session(&mut report, config, production);
+
+    if production && config.middlewares.live_reload.enabled {
+        report.push(ConfigIssue::warning(
+            "middlewares.live_reload.enabled",
+            "live reload is enabled in production",
+        ));
+    }
+
+    for method in &config.middlewares.method_override.allowed_methods {
+        if method.is_safe() || method == http::Method::POST {
+            report.push(ConfigIssue::error(
+                "middlewares.method_override.allowed_methods",
+                format!("`POST` requests can't be overridden with `{method}`"),
+            ));
+        }
+    }
+
+    let cors = &config.middlewares.cors;
+    for origin in &cors.allowed_origins {
+        if origin != "*" && (!origin.contains("://") || origin.ends_with('/')) {
+            report.push(ConfigIssue::error(
+                "middlewares.cors.allowed_origins",
+                format!("`{origin}` is not a valid origin, such as `https://example.com`"),
+            ));
+        }
+    }
+    if cors.allow_credentials && cors.allowed_origins.iter().any(|origin| origin == "*") {
+        report.push(ConfigIssue::error(
+            "middlewares.cors.allow_credentials",
+            "credentials can't be allowed for any origin (`*`)",
+        ));
+    }
+
+    check_rate_limit(&mut report, &config.middlewares.rate_limit);
+    check_security_headers(&mut report, &config.middlewares.security);
+    check_maintenance(&mut report, &config.middlewares.maintenance);
+
+    if config.server.max_requests_per_connection == Some(0) {
+        report.push(ConfigIssue::error(
+            "server.max_requests_per_connection",
+            "at least one request must be allowed per connection",
+        ));
+    }
+
+    if let Some(max_buf_size) = config.server.buffers.http1_max_buf_size {
+        if max_buf_size < MIN_HTTP1_MAX_BUF_SIZE {
+            report.push(ConfigIssue::error(
+                "server.buffers.http1_max_buf_size",
+                format!(
+                    "the buffer size must be at least {MIN_HTTP1_MAX_BUF_SIZE} bytes, \
+                     but is {max_buf_size} bytes"
+                ),
+            ));
+        }
+    }
+
+    if config.middlewares.timeout.duration.is_zero() {
+        report.push(ConfigIssue::error(
+            "middlewares.timeout.duration",
+            "the timeout must be greater than zero",
+        ));
+    }
+
+    if config.middlewares.body_limit.max_bytes == 0 {
+        report.push(ConfigIssue::error(
+            "middlewares.body_limit.max_bytes",
+            "the body limit must be greater than zero",
+        ));
+    }
+
+    report
+}
+
+fn check_session(report: &mut ConfigReport, config: &ProjectConfig, production: bool) {
+    let session = &config.middlewares.session;
+    if let Err(error) = session.validate() {
+        report.push(ConfigIssue::error(
+            "middlewares.session.cookie_prefix",
+            error.to_string(),
+        ));
+    }
+    if production && !session.secure {
+        report.push(ConfigIssue::warning(
+            "middlewares.session.secure",
+            "the session cookie is sent over plain HTTP in production",
+        ));
+    }
+    if session.cookie_same_site == SameSite::None && !session.secure {
+        report.push(ConfigIssue::error(
+            "middlewares.session.cookie_same_site",
+            "cookies with `SameSite=None` must be secure",
+        ));
+    }
+
+    match session.store {
+        SessionStoreType::Memory => {
+            if production {
+                report.push(ConfigIssue::warning(
+                    "middlewares.session.store",
+                    "the sessions are stored in the memory of a single process, so they are \
+                     lost on restart and not shared between multiple workers",
+                ));
+            }
+        }
+        SessionStoreType::Redis => check_redis_session_store(report, session),
+        SessionStoreType::SignedCookie | SessionStoreType::EncryptedCookie => {
+            check_cookie_session_store(report, config);
+        }
+    }
+}
+
+fn check_maintenance(report: &mut ConfigReport, maintenance: &MaintenanceMiddlewareConfig) {
+    for (index, path) in maintenance.allowed_paths.iter().enumerate() {
+        if !path.starts_with('/') {
+            report.push(ConfigIssue::error(
+                format!("middlewares.maintenance.allowed_paths[{index}]"),
+                "the path must start with `/`",
+            ));
+        }
+    }
+}
+
+fn check_redis_session_store(report: &mut ConfigReport, session: &SessionMiddlewareConfig) {
+    check_redis_store(
+        report,
+        "middlewares.session",
+        "session store",
+        session.redis.url.as_deref(),
+    );
+
+    if session.cleanup_interval.is_some() {
+        report.push(ConfigIssue::warning(
+            "middlewares.session.cleanup_interval",
+            "Redis deletes the expired sessions by itself, the cleanup interval is ignored",
+        ));
+    }
+}
+
+fn check_rate_limit(report: &mut ConfigReport, rate_limit: &RateLimitMiddlewareConfig) {
+    if rate_limit.capacity == 0 {
+        report.push(ConfigIssue::error(
+            "middlewares.rate_limit.capacity",
+            "at least one request must be allowed",
+        ));
+    }
+
+    for (index, route) in rate_limit.routes.iter().enumerate() {
+        if !route.path.starts_with('/') {
+            report.push(ConfigIssue::error(
+                format!("middlewares.rate_limit.routes[{index}].path"),
+                "the path must start with `/`",
+            ));
+        }
+        if route.capacity == 0 {
+            report.push(ConfigIssue::error(
+                format!("middlewares.rate_limit.routes[{index}].capacity"),
+                "at least one request must be allowed",
+            ));
+        }
+    }
+
+    if rate_limit.store == RateLimitStoreType::Redis {
+        check_redis_store(
+            report,
+            "middlewares.rate_limit",
+            "rate limit store",
+            rate_limit.redis.url.as_deref(),
+        );
+    }
+}
+
+fn check_security_headers(report: &mut ConfigReport, security: &SecurityHeadersMiddlewareConfig) {
+    const REFERRER_POLICIES: &[&str] = &[
+        "no-referrer",
+        "no-referrer-when-downgrade",
+        "origin",
+        "origin-when-cross-origin",
+        "same-origin",
+        "strict-origin",
+        "strict-origin-when-cross-origin",
+        "unsafe-url",
+    ];
+
+    // several policies can be given, the last one supported by the browser is
+    // used
+    for policy in security
+        .referrer_policy
+        .split(',')
+        .map(str::trim)
+        .filter(|policy| !policy.is_empty())
+    {
+        if !REFERRER_POLICIES.contains(&policy) {
+            report.push(ConfigIssue::error(
+                "middlewares.security.referrer_policy",
+                format!("`{policy}` is not a valid referrer policy"),
+            ));
+        }
+    }
+
+    if let Some(policy) = &security.content_security_policy {
+        if policy.trim().is_empty() || http::HeaderValue::from_str(policy).is_err() {
+            report.push(ConfigIssue::error(
+                "middlewares.security.content_security_policy",
+                "the content security policy must be a non-empty, single-line header value",
+            ));
+        }
+    }
+
+    if security.hsts && security.hsts_preload {
+        if !security.hsts_include_subdomains {
+            report.push(ConfigIssue::warning(
+                "middlewares.security.hsts_preload",
+                "HSTS preloading requires `hsts_include_subdomains` to be enabled",
+            ));
+        }
+        if security.hsts_max_age.as_secs() < 365 * 24 * 60 * 60 {
+            report.push(ConfigIssue::warning(
+                "middlewares.security.hsts_preload",
+                "HSTS preloading requires `hsts_max_age` to be at least a year",
+            ));
+        }
+    }
+}
+
+/// Checks the settings of a Redis store configured in the `section` of the
+/// config, with its URL in the `redis.url` key.
+fn check_redis_store(report: &mut ConfigReport, section: &str, name: &str, url: Option<&str>) {
+    if !cfg!(feature = "redis") {
+        report.push(ConfigIssue::error(
+            format!("{section}.store"),
+            format!("the Redis {name} requires the `redis` feature to be enabled"),
+        ));
+    }
+
+    match url {
+        None => report.push(ConfigIssue::error(
+            format!("{section}.redis.url"),
+            format!("the URL is required for the Redis {name}"),
+        )),
+        #[cfg(feature = "redis")]
+        Some(url) => {
+            if let Err(error) = redis::Client::open(url) {
+                report.push(ConfigIssue::error(
+                    format!("{section}.redis.url"),
+                    format!("invalid Redis URL: {error}"),
+                ));
+            }
+        }
+        #[cfg(not(feature = "redis"))]
+        Some(_) => {}
+    }
+}
+
+fn check_cookie_session_store(report: &mut ConfigReport, config: &ProjectConfig) {
+    if config.secret_key.as_bytes().is_empty() {
+        report.push(ConfigIssue::error(
+            "secret_key",
+            "the secret key is required to sign the session cookies",
+        ));
+    }
+
+    if config.middlewares.session.cleanup_interval.is_some() {
+        report.push(ConfigIssue::warning(
+            "middlewares.session.cleanup_interval",
+            "the sessions are not stored on the server, the cleanup interval is ignored",
+        ));
+    }
+}
+
+fn check_secret_key(report: &mut ConfigReport, key: &str, secret_key: &[u8]) {
+    if secret_key.is_empty() {
+        report.push(ConfigIssue::warning(
+            key,
+            "the secret key is empty, so the signed data can be forged",
+        ));
+    } else if secret_key.len() < MIN_SECRET_KEY_LENGTH {
+        report.push(ConfigIssue::error(
+            key,
+            format!(
+                "the secret key must be at least {MIN_SECRET_KEY_LENGTH} bytes long in \
+                 production, but is {} bytes long",
+                secret_key.len()
+            ),
+        ));
+    }
+}
+
+/// Checks the keys of the TOML config for the unknown (likely misspelled)
+/// and deprecated ones. Used by [`ProjectConfig::from_toml`].
+///
+/// # Errors
+///
+/// Returns an error if the config can't be deserialized.
+pub(super) fn check_keys(raw: toml::Table) -> Result<ConfigReport, toml::de::Error> {
+    check_keys_with(raw, DEPRECATED_KEYS)
+}
+
+fn check_keys_with(
+    raw: toml::Table,
+    deprecated: &[DeprecatedKey],
+) -> Result<ConfigReport, toml::de::Error> {
+    let tracker = KeyTracker::new(deprecated);
+    ProjectConfig::deserialize(tracker.deserializer(toml::Value::Table(raw)))?;
+    Ok(tracker.into_report())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::config::{MiddlewareConfig, TimeoutMiddlewareConfig};
+
+    fn keys_report(toml_content: &str, deprecated: &[DeprecatedKey]) -> ConfigReport {
+        let raw: toml::Table = toml::from_str(toml_content).unwrap();
+        check_keys_with(raw, deprecated).unwrap()
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let report = keys_report(
+            r#"
+            secret_key = "123abc"
+            debgu = true
+            [middlewares.session]
+            secure = false
+            cookie_nmae = "session"
+            "#,
+            &[],
+        );
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(keys, ["debgu", "middlewares.session.cookie_nmae"]);
+    }
+
+    #[test]
+    fn known_keys() {
+        let report = keys_report(
+            r#"
+            debug = true
+            secret_key = "123abc"
+            fallback_secret_keys = ["abc"]
+            [auth_backend]
+            type = "none"
+            [middlewares]
+            live_reload.enabled = true
+            [middlewares.session]
+            secure = false
+            cookie_name = "session"
+            cleanup_interval = 60
+            [middlewares.body_limit]
+            max_bytes = 1024
+            [server]
+            auto_options = false
+            [features]
+            new_ui = true
+            "#,
+            &[],
+        );
+
+        assert!(report.is_empty(), "{report:?}");
+    }
+
+    #[test]
+    #[cfg(feature = "db")]
+    fn known_database_keys() {
+        let report = keys_report(
+            r#"
+            [database]
+            url = "sqlite://db.sqlite3?mode=rwc"
+            [auth_backend]
+            type = "database"
+            "#,
+            &[],
+        );
+
+        assert!(report.is_empty(), "{report:?}");
+    }
+
+    #[test]
+    fn unknown_keys_in_arrays_of_tables() {
+        let report = keys_report(
+            r#"
+            [[middlewares.rate_limit.routes]]
+            path = "/login"
+            capacity = 5
+            period = 60
+
+            [[middlewares.rate_limit.routes]]
+            path = "/api"
+            capacity = 100
+            period = 60
+            burst = 10
+            "#,
+            &[],
+        );
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(keys, ["middlewares.rate_limit.routes[1].burst"]);
+    }
+
+    #[test]
+    #[cfg(feature = "db")]
+    fn named_database_keys() {
+        let report = keys_report(
+            r#"
+            [database]
+            url = "sqlite::memory:"
+            [database.replica]
+            url = "sqlite::memory:"
+            max_connections = 5
+            "#,
+            &[],
+        );
+
+        assert!(report.is_empty(), "{report:?}");
+    }
+
+    #[test]
+    fn keys_of_invalid_config() {
+        let raw: toml::Table = toml::from_str("debug = 5").unwrap();
+
+        assert!(check_keys(raw).is_err());
+    }
+
+    #[test]
+    fn deprecated_keys() {
+        let toml_content = r#"
+            [middlewares.session]
+            same_site = "lax"
+            http_only = false
+            "#;
+        let report = keys_report(toml_content, DEPRECATED_KEYS);
+
+        assert!(!report.has_errors());
+        let warnings: Vec<_> = report.warnings().map(ConfigIssue::key).collect();
+        assert_eq!(
+            warnings,
+            [
+                "middlewares.session.http_only",
+                "middlewares.session.same_site"
+            ]
+        );
+        let warning = report.warnings().last().unwrap();
+        assert!(warning.message().contains("cookie_same_site"));
+
+        // the deprecated keys are still used
+        let config = ProjectConfig::from_toml(toml_content).unwrap();
+        assert_eq!(config.middlewares.session.cookie_same_site, SameSite::Lax);
+        assert!(!config.middlewares.session.cookie_http_only);
+    }
+
+    #[test]
+    fn memory_session_store_in_production() {
+        let config = ProjectConfig::builder()
+            .debug(false)
+            .secret_key("a".repeat(MIN_SECRET_KEY_LENGTH).into())
+            .middlewares(
+                MiddlewareConfig::builder()
+                    .session(SessionMiddlewareConfig::builder().secure(true).build())
+                    .build(),
+            )
+            .build();
+
+        let report = validate(&config);
+        let warnings: Vec<_> = report.warnings().map(ConfigIssue::key).collect();
+        assert_eq!(warnings, ["middlewares.session.store"]);
+
+        let config = ProjectConfig::builder()
+            .debug(true)
+            .middlewares(
+                MiddlewareConfig::builder()
+                    .session(SessionMiddlewareConfig::builder().secure(true).build())
+                    .build(),
+            )
+            .build();
+        assert!(validate(&config).is_empty());
+    }
+
+    #[test]
+    fn short_secret_key_in_production() {
+        let config = ProjectConfig::builder()
+            .debug(false)
+            .secret_key("123abc".into())
+            .fallback_secret_keys(vec!["a".repeat(MIN_SECRET_KEY_LENGTH).into(), "x".into()])
+            .build();
+
+        let report = validate(&config);
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(keys, ["secret_key", "fallback_secret_keys[1]"]);
+    }
+
+    #[test]
+    fn short_secret_key_in_debug() {
+        let config = ProjectConfig::builder()
+            .debug(true)
+            .secret_key("123abc".into())
+            .build();
+
+        assert!(validate(&config).is_empty());
+    }
+
+    #[test]
+    fn maintenance_allowed_paths() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.maintenance]
+            allowed_paths = ["/health", "admin"]
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(keys, ["middlewares.maintenance.allowed_paths[1]"]);
+    }
+
+    #[test]
+    fn zero_timeout() {
+        let config = ProjectConfig::builder()
+            .middlewares(
+                MiddlewareConfig::builder()
+                    .timeout(
+                        TimeoutMiddlewareConfig::builder()
+                            .duration(Duration::ZERO)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+
+        let report = validate(&config);
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(keys, ["middlewares.timeout.duration"]);
+    }
+
+    #[test]
+    fn insecure_defaults_in_production() {
+        let config = ProjectConfig::from_toml(
+            r"
+            debug = false
+            [middlewares]
+            live_reload.enabled = true
+            session.secure = false
+            ",
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        assert!(!report.has_errors());
+        let keys: Vec<_> = report.warnings().map(ConfigIssue::key).collect();
+        assert_eq!(
+            keys,
+            [
+                "secret_key",
+                "middlewares.session.secure",
+                "middlewares.session.store",
+                "middlewares.live_reload.enabled"
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicting_middleware_settings() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.method_override]
+            allowed_methods = ["DELETE", "GET"]
+            [middlewares.body_limit]
+            max_bytes = 0
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(
+            keys,
+            [
+                "middlewares.method_override.allowed_methods",
+                "middlewares.body_limit.max_bytes"
+            ]
+        );
+    }
+
+    #[test]
+    fn same_site_none_requires_secure() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.session]
+            secure = false
+            cookie_same_site = "none"
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let errors: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(errors, ["middlewares.session.cookie_same_site"]);
+    }
+
+    #[test]
+    fn invalid_cors_settings() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.cors]
+            allowed_origins = ["*", "https://example.com/", "example.com"]
+            allow_credentials = true
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(
+            keys,
+            [
+                "middlewares.cors.allowed_origins",
+                "middlewares.cors.allowed_origins",
+                "middlewares.cors.allow_credentials"
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_security_headers_settings() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.security]
+            hsts_max_age = 3600
+            hsts_preload = true
+            referrer_policy = "no-referrer, strict-origin, everything"
+            content_security_policy = ""
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let errors: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(
+            errors,
+            [
+                "middlewares.security.referrer_policy",
+                "middlewares.security.content_security_policy"
+            ]
+        );
+        let warnings: Vec<_> = report.warnings().map(ConfigIssue::key).collect();
+        assert_eq!(
+            warnings,
+            [
+                "middlewares.security.hsts_preload",
+                "middlewares.security.hsts_preload"
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_rate_limit_settings() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.rate_limit]
+            capacity = 0
+            store = "redis"
+            [[middlewares.rate_limit.routes]]
+            path = "login"
+            capacity = 0
+            period = 60
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let errors: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        let mut expected = vec![
+            "middlewares.rate_limit.capacity",
+            "middlewares.rate_limit.routes[0].path",
+            "middlewares.rate_limit.routes[0].capacity",
+            "middlewares.rate_limit.redis.url",
+        ];
+        if !cfg!(feature = "redis") {
+            expected.insert(3, "middlewares.rate_limit.store");
+        }
+        assert_eq!(errors, expected);
+    }
+
+    #[test]
+    fn redis_session_store() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.session]
+            store = "redis"
+            cleanup_interval = 3600
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let errors: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        let mut expected = vec!["middlewares.session.redis.url"];
+        if !cfg!(feature = "redis") {
+            expected.insert(0, "middlewares.session.store");
+        }
+        assert_eq!(errors, expected);
+        let warnings: Vec<_> = report.warnings().map(ConfigIssue::key).collect();
+        assert_eq!(warnings, ["middlewares.session.cleanup_interval"]);
+    }
+
+    #[test]
+    fn cookie_session_store_without_secret_key() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.session]
+            store = "signed_cookie"
+            cleanup_interval = 3600
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let errors: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(errors, ["secret_key"]);
+        let warnings: Vec<_> = report.warnings().map(ConfigIssue::key).collect();
+        assert_eq!(warnings, ["middlewares.session.cleanup_interval"]);
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn redis_session_store_invalid_url() {
+        let config = ProjectConfig::from_toml(
+            r#"
+            [middlewares.session]
+            store = "redis"
+            redis.url = "not a url"
+            "#,
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let errors: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(errors, ["middlewares.session.redis.url"]);
+    }
+
+    #[test]
+    fn zero_requests_per_connection() {
+        let config = ProjectConfig::from_toml(
+            r"
+            [server]
+            max_requests_per_connection = 0
+            ",
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(keys, ["server.max_requests_per_connection"]);
+    }
+
+    #[test]
+    fn small_http1_max_buf_size() {
+        let config = ProjectConfig::from_toml(
+            r"
+            [server.buffers]
+            http1_max_buf_size = 4096
+            ",
+        )
+        .unwrap();
+
+        let report = validate(&config);
+
+        let keys: Vec<_> = report.errors().map(ConfigIssue::key).collect();
+        assert_eq!(keys, ["server.buffers.http1_max_buf_size"]);
+    }
+
+    #[test]
+    fn min_http1_max_buf_size() {
+        let config = ProjectConfig::from_toml(
+            r"
+            [server.buffers]
+            http1_max_buf_size = 8192
+            ",
+        )
+        .unwrap();
+
+        assert!(validate(&config).is_empty());
+    }
+
+    #[test]
+    fn report_display() {
+        let mut report = ConfigReport::new();
+        report.push(ConfigIssue::error("secret_key", "too short"));
+        report.push(ConfigIssue::warning("debug", "ignored"));
+        report.push(ConfigIssue::error("debgu", "unknown key"));
+
+        assert_eq!(
+            report.to_string(),
+            "found 2 errors\n  - error at `secret_key`: too short\n  - error at `debgu`: unknown key"
+        );
+    }
+
+    #[test]
+    fn report_into_result() {
+        let mut report = ConfigReport::new();
+        report.push(ConfigIssue::warning("debug", "ignored"));
+        assert!(report.clone().into_result().is_ok());
+
+        report.push(ConfigIssue::error("debgu", "unknown key"));
+        assert!(report.into_result().is_err());
+    }
+}
@@ -0,0 +1,223 @@
+//! Checking the keys of the TOML config.
+//!
+//! The config is deserialized once more from the original TOML, through a
+//! deserializer that compares the keys of each table with the fields of the
+//! struct it is deserialized into. This way, the unknown keys are found in
+//! all the tables, including the ones in arrays (such as
+//! `middlewares.rate_limit.routes`), without relying on the config being
+//! serializable back to TOML.
+//!
+//! The keys of the tables deserialized into maps or buffered by serde (such
+//! as the ones of the internally tagged enums) can't be checked this way, and
+//! are always accepted.
+
+use std::cell::RefCell;
+
+use serde::Deserializer;
+use serde::de::value::StringDeserializer;
+use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+
+use super::{ConfigIssue, ConfigReport, DeprecatedKey};
+
+/// Collects the issues with the keys found while deserializing the config.
+pub(super) struct KeyTracker<'a> {
+    deprecated: &'a [DeprecatedKey],
+    report: RefCell<ConfigReport>,
+}
+
+impl<'a> KeyTracker<'a> {
+    pub(super) fn new(deprecated: &'a [DeprecatedKey]) -> Self {
+        Self {
+            deprecated,
+            report: RefCell::new(ConfigReport::new()),
+        }
+    }
+
+    /// Returns a deserializer for the whole config.
+    pub(super) fn deserializer(&self, value: toml::Value) -> TrackedValue<'_> {
+        TrackedValue {
+            value,
+            location: Location {
+                path: String::new(),
+                tracker: self,
+            },
+        }
+    }
+
+    pub(super) fn into_report(self) -> ConfigReport {
+        self.report.into_inner()
+    }
+
+    fn check_key(&self, path: String, known: bool) {
+        let issue = match self
+            .deprecated
+            .iter()
+            .find(|deprecated| deprecated.key == path)
+        {
+            Some(deprecated) => ConfigIssue::warning(
+                path,
+                format!(
+                    "the key is deprecated; use `{}` instead",
+                    deprecated.replacement
+                ),
+            ),
+            None if known => return,
+            None => ConfigIssue::error(path, "unknown key"),
+        };
+        self.report.borrow_mut().push(issue);
+    }
+}
+
+/// The dotted path of a value in the config, as used in the [`ConfigIssue`]s.
+struct Location<'a> {
+    path: String,
+    tracker: &'a KeyTracker<'a>,
+}
+
+impl Location<'_> {
+    fn key(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{key}", self.path)
+        }
+    }
+
+    fn at(&self, path: String, value: toml::Value) -> TrackedValue<'_> {
+        TrackedValue {
+            value,
+            location: Location {
+                path,
+                tracker: self.tracker,
+            },
+        }
+    }
+}
+
+/// A TOML value along with its location in the config.
+pub(super) struct TrackedValue<'a> {
+    value: toml::Value,
+    location: Location<'a>,
+}
+
+impl<'de> Deserializer<'de> for TrackedValue<'_> {
+    type Error = toml::de::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            toml::Value::Table(table) => visitor.visit_map(TrackedTable {
+                location: self.location,
+                entries: table.into_iter(),
+                value: None,
+            }),
+            toml::Value::Array(array) => visitor.visit_seq(TrackedArray {
+                location: self.location,
+                elements: array.into_iter().enumerate(),
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if let toml::Value::Table(table) = &self.value {
+            for key in table.keys() {
+                self.location
+                    .tracker
+                    .check_key(self.location.key(key), fields.contains(&key.as_str()));
+            }
+            self.deserialize_any(visitor)
+        } else {
+            self.value.deserialize_struct(name, fields, visitor)
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        // there are no null values in TOML, so a present value is always `Some`
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string seq
+        bytes byte_buf map unit_struct tuple_struct tuple ignored_any
+        unit identifier
+    }
+}
+
+struct TrackedTable<'a> {
+    location: Location<'a>,
+    entries: toml::map::IntoIter,
+    value: Option<(String, toml::Value)>,
+}
+
+impl<'de> MapAccess<'de> for TrackedTable<'_> {
+    type Error = toml::de::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some((self.location.key(&key), value));
+        let key: StringDeserializer<toml::de::Error> = key.into_deserializer();
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (path, value) = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(self.location.at(path, value))
+    }
+}
+
+struct TrackedArray<'a> {
+    location: Location<'a>,
+    elements: std::iter::Enumerate<std::vec::IntoIter<toml::Value>>,
+}
+
+impl<'de> SeqAccess<'de> for TrackedArray<'_> {
+    type Error = toml::de::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let Some((index, value)) = self.elements.next() else {
+            return Ok(None);
+        };
+        let path = format!("{}[{index}]", self.location.path);
+        seed.deserialize(self.location.at(path, value)).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
@@ -0,0 +1,641 @@
+//! HTTP cookies.
+//!
+//! This module provides the [`Cookie`] type that can be set on a response with
+//! [`ResponseExt::with_cookie`](crate::response::ResponseExt::with_cookie) and
+//! removed with
+//! [`ResponseExt::delete_cookie`](crate::response::ResponseExt::delete_cookie),
+//! as well as the [`Cookies`] type holding the cookies sent by the client,
+//! which can be retrieved with
+//! [`RequestExt::cookies`](crate::request::RequestExt::cookies) or used as an
+//! extractor.
+//!
+//! Cookie names and values are percent-encoded when sent to the client and
+//! decoded when read from a request, so they can contain any characters.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::cookie::{Cookie, Cookies, SameSite};
+//! use cot::response::{Response, ResponseExt};
+//!
+//! async fn set_theme(cookies: Cookies) -> Response {
+//!     let theme = match cookies.get("theme").map(Cookie::value) {
+//!         Some("dark") => "light",
+//!         _ => "dark",
+//!     };
+//!
+//!     Response::new_redirect("/").with_cookie(
+//!         Cookie::build("theme", theme)
+//!             .path("/")
+//!             .secure(true)
+//!             .same_site(SameSite::Lax),
+//!     )
+//! }
+//! ```
+
+use std::time::Duration;
+
+use http::request::Parts;
+use http::{HeaderMap, header};
+
+use crate::request::extractors::FromRequestParts;
+pub use crate::session::cookie::SameSite;
+
+/// An HTTP cookie.
+///
+/// Cookies are created with [`Cookie::new`], or with [`Cookie::build`] to set
+/// their attributes, and sent to the client with
+/// [`ResponseExt::with_cookie`](crate::response::ResponseExt::with_cookie). The
+/// cookies sent by the client are available through [`Cookies`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::cookie::Cookie;
+///
+/// let cookie = Cookie::new("theme", "dark");
+/// assert_eq!(cookie.name(), "theme");
+/// assert_eq!(cookie.value(), "dark");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cookie {
+    inner: cookie::Cookie<'static>,
+}
+
+impl Cookie {
+    /// Creates a new cookie with the given name and value, and no attributes.
+    ///
+    /// Browsers keep such a cookie until they are closed, and send it back
+    /// only for the requests to the path it was set on (and its
+    /// subdirectories).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::new("theme", "dark");
+    /// assert_eq!(cookie.to_string(), "theme=dark");
+    /// ```
+    #[must_use]
+    pub fn new<N: Into<String>, V: Into<String>>(name: N, value: V) -> Self {
+        Self {
+            inner: cookie::Cookie::new(name.into(), value.into()),
+        }
+    }
+
+    /// Creates a builder for a cookie with the given name and value.
+    ///
+    /// The builder can be passed directly to
+    /// [`ResponseExt::with_cookie`](crate::response::ResponseExt::with_cookie),
+    /// or turned into a cookie with [`CookieBuilder::build`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::cookie::{Cookie, SameSite};
+    ///
+    /// let cookie = Cookie::build("theme", "dark")
+    ///     .path("/")
+    ///     .secure(true)
+    ///     .http_only(true)
+    ///     .same_site(SameSite::Lax)
+    ///     .max_age(Duration::from_secs(3600))
+    ///     .build();
+    /// assert_eq!(
+    ///     cookie.to_string(),
+    ///     "theme=dark; HttpOnly; SameSite=Lax; Secure; Path=/; Max-Age=3600"
+    /// );
+    /// ```
+    pub fn build<N: Into<String>, V: Into<String>>(name: N, value: V) -> CookieBuilder {
+        CookieBuilder {
+            cookie: Self::new(name, value),
+        }
+    }
+
+    /// Returns the name of the cookie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// assert_eq!(Cookie::new("theme", "dark").name(), "theme");
+    /// ```
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    /// Returns the value of the cookie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// assert_eq!(Cookie::new("theme", "dark").value(), "dark");
+    /// ```
+    #[must_use]
+    pub fn value(&self) -> &str {
+        self.inner.value()
+    }
+
+    /// Returns the `Path` attribute of the cookie, if set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark").path("/").build();
+    /// assert_eq!(cookie.path(), Some("/"));
+    /// ```
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        self.inner.path()
+    }
+
+    /// Returns the `Domain` attribute of the cookie, if set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark").domain("example.com").build();
+    /// assert_eq!(cookie.domain(), Some("example.com"));
+    /// ```
+    #[must_use]
+    pub fn domain(&self) -> Option<&str> {
+        self.inner.domain()
+    }
+
+    /// Returns whether the cookie has the `Secure` attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// assert!(Cookie::build("theme", "dark").secure(true).build().secure());
+    /// assert!(!Cookie::new("theme", "dark").secure());
+    /// ```
+    #[must_use]
+    pub fn secure(&self) -> bool {
+        self.inner.secure().unwrap_or(false)
+    }
+
+    /// Returns whether the cookie has the `HttpOnly` attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// assert!(
+    ///     Cookie::build("theme", "dark")
+    ///         .http_only(true)
+    ///         .build()
+    ///         .http_only()
+    /// );
+    /// assert!(!Cookie::new("theme", "dark").http_only());
+    /// ```
+    #[must_use]
+    pub fn http_only(&self) -> bool {
+        self.inner.http_only().unwrap_or(false)
+    }
+
+    /// Returns the `SameSite` attribute of the cookie, if set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::{Cookie, SameSite};
+    ///
+    /// let cookie = Cookie::build("theme", "dark")
+    ///     .same_site(SameSite::Strict)
+    ///     .build();
+    /// assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+    /// ```
+    #[must_use]
+    pub fn same_site(&self) -> Option<SameSite> {
+        self.inner.same_site().map(|same_site| match same_site {
+            cookie::SameSite::Strict => SameSite::Strict,
+            cookie::SameSite::Lax => SameSite::Lax,
+            cookie::SameSite::None => SameSite::None,
+        })
+    }
+
+    /// Returns the `Max-Age` attribute of the cookie, if set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark")
+    ///     .max_age(Duration::from_secs(60))
+    ///     .build();
+    /// assert_eq!(cookie.max_age(), Some(Duration::from_secs(60)));
+    /// ```
+    #[must_use]
+    pub fn max_age(&self) -> Option<Duration> {
+        self.inner
+            .max_age()
+            .map(|max_age| Duration::try_from(max_age).unwrap_or_default())
+    }
+
+    /// Returns the value of the `Set-Cookie` header setting this cookie, with
+    /// the name and the value percent-encoded.
+    pub(crate) fn to_set_cookie_header(&self) -> String {
+        self.inner.encoded().to_string()
+    }
+
+    /// Returns a cookie that makes the browser remove this cookie, keeping its
+    /// name, `Path` and `Domain`.
+    pub(crate) fn into_removal(self) -> Self {
+        let mut inner = self.inner;
+        inner.make_removal();
+        Self { inner }
+    }
+}
+
+impl std::fmt::Display for Cookie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl From<CookieBuilder> for Cookie {
+    fn from(builder: CookieBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// A builder for a [`Cookie`] with attributes.
+///
+/// Created with [`Cookie::build`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::cookie::{Cookie, SameSite};
+///
+/// let cookie = Cookie::build("theme", "dark")
+///     .secure(true)
+///     .same_site(SameSite::Lax)
+///     .build();
+/// assert_eq!(cookie.to_string(), "theme=dark; SameSite=Lax; Secure");
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct CookieBuilder {
+    cookie: Cookie,
+}
+
+impl CookieBuilder {
+    /// Sets the `Path` attribute of the cookie, limiting the paths the
+    /// browser sends the cookie for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark").path("/admin").build();
+    /// assert_eq!(cookie.to_string(), "theme=dark; Path=/admin");
+    /// ```
+    pub fn path<P: Into<String>>(mut self, path: P) -> Self {
+        self.cookie.inner.set_path(path.into());
+        self
+    }
+
+    /// Sets the `Domain` attribute of the cookie, making the browser send the
+    /// cookie to the subdomains of the domain as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark").domain("example.com").build();
+    /// assert_eq!(cookie.to_string(), "theme=dark; Domain=example.com");
+    /// ```
+    pub fn domain<D: Into<String>>(mut self, domain: D) -> Self {
+        self.cookie.inner.set_domain(domain.into());
+        self
+    }
+
+    /// Sets whether the cookie has the `Secure` attribute, which makes the
+    /// browser send it only over HTTPS.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark").secure(true).build();
+    /// assert_eq!(cookie.to_string(), "theme=dark; Secure");
+    /// ```
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.cookie.inner.set_secure(secure);
+        self
+    }
+
+    /// Sets whether the cookie has the `HttpOnly` attribute, which makes it
+    /// inaccessible to JavaScript.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark").http_only(true).build();
+    /// assert_eq!(cookie.to_string(), "theme=dark; HttpOnly");
+    /// ```
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.cookie.inner.set_http_only(http_only);
+        self
+    }
+
+    /// Sets the `SameSite` attribute of the cookie, controlling whether it is
+    /// sent with cross-site requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::{Cookie, SameSite};
+    ///
+    /// let cookie = Cookie::build("theme", "dark")
+    ///     .same_site(SameSite::Strict)
+    ///     .build();
+    /// assert_eq!(cookie.to_string(), "theme=dark; SameSite=Strict");
+    /// ```
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.cookie.inner.set_same_site(same_site.to_cookie());
+        self
+    }
+
+    /// Sets the `Max-Age` attribute of the cookie, making the browser keep it
+    /// for the given duration, even after it is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark")
+    ///     .max_age(Duration::from_secs(86400))
+    ///     .build();
+    /// assert_eq!(cookie.to_string(), "theme=dark; Max-Age=86400");
+    /// ```
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        let max_age = time::Duration::try_from(max_age).unwrap_or(time::Duration::MAX);
+        self.cookie.inner.set_max_age(max_age);
+        self
+    }
+
+    /// Builds the cookie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    ///
+    /// let cookie = Cookie::build("theme", "dark").build();
+    /// assert_eq!(cookie, Cookie::new("theme", "dark"));
+    /// ```
+    #[must_use]
+    pub fn build(self) -> Cookie {
+        self.cookie
+    }
+}
+
+/// The cookies sent by the client with a request.
+///
+/// This can be retrieved with
+/// [`RequestExt::cookies`](crate::request::RequestExt::cookies), or used as an
+/// extractor. Cookies that can't be parsed are skipped; if the client sent
+/// multiple cookies with the same name, [`Cookies::get`] returns the first
+/// one.
+///
+/// # Examples
+///
+/// ```
+/// use cot::cookie::Cookies;
+/// use cot::html::Html;
+///
+/// async fn my_handler(cookies: Cookies) -> Html {
+///     let theme = cookies
+///         .get("theme")
+///         .map_or("light", |cookie| cookie.value());
+///     Html::new(format!("Current theme: {theme}"))
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Cookies {
+    cookies: Vec<Cookie>,
+}
+
+impl Cookies {
+    /// Parses the `Cookie` headers of a request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookies;
+    /// use cot::http::{HeaderMap, HeaderValue, header};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(
+    ///     header::COOKIE,
+    ///     HeaderValue::from_static("theme=dark; lang=en"),
+    /// );
+    ///
+    /// let cookies = Cookies::from_headers(&headers);
+    /// assert_eq!(cookies.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let cookies = headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(cookie::Cookie::split_parse_encoded)
+            .filter_map(Result::ok)
+            .map(|cookie| Cookie {
+                inner: cookie.into_owned(),
+            })
+            .collect();
+
+        Self { cookies }
+    }
+
+    /// Returns the cookie with the given name, if the client sent one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookies;
+    /// use cot::http::{HeaderMap, HeaderValue, header};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(header::COOKIE, HeaderValue::from_static("theme=dark"));
+    ///
+    /// let cookies = Cookies::from_headers(&headers);
+    /// assert_eq!(cookies.get("theme").unwrap().value(), "dark");
+    /// assert!(cookies.get("lang").is_none());
+    /// ```
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Cookie> {
+        self.cookies.iter().find(|cookie| cookie.name() == name)
+    }
+
+    /// Returns an iterator over the cookies, in the order they were sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookies;
+    /// use cot::http::{HeaderMap, HeaderValue, header};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(
+    ///     header::COOKIE,
+    ///     HeaderValue::from_static("theme=dark; lang=en"),
+    /// );
+    ///
+    /// let names: Vec<_> = Cookies::from_headers(&headers)
+    ///     .iter()
+    ///     .map(|cookie| cookie.name().to_owned())
+    ///     .collect();
+    /// assert_eq!(names, ["theme", "lang"]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &Cookie> {
+        self.cookies.iter()
+    }
+
+    /// Returns the number of cookies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookies;
+    /// use cot::http::HeaderMap;
+    ///
+    /// assert_eq!(Cookies::from_headers(&HeaderMap::new()).len(), 0);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    /// Returns whether the client sent no cookies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookies;
+    /// use cot::http::HeaderMap;
+    ///
+    /// assert!(Cookies::from_headers(&HeaderMap::new()).is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a Cookies {
+    type Item = &'a Cookie;
+    type IntoIter = std::slice::Iter<'a, Cookie>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cookies.iter()
+    }
+}
+
+impl FromRequestParts for Cookies {
+    async fn from_request_parts(parts: &mut Parts) -> crate::Result<Self> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    #[test]
+    fn cookie_set_cookie_header_encoded() {
+        let cookie = Cookie::build("user name", "a; b=c").path("/").build();
+
+        assert_eq!(
+            cookie.to_set_cookie_header(),
+            "user%20name=a%3B%20b%3Dc; Path=/"
+        );
+    }
+
+    #[test]
+    fn cookie_removal() {
+        let cookie = Cookie::build("theme", "dark")
+            .path("/admin")
+            .domain("example.com")
+            .secure(true)
+            .build()
+            .into_removal();
+
+        let header = cookie.to_set_cookie_header();
+        assert!(header.starts_with("theme=; Secure; Path=/admin; Domain=example.com"));
+        assert!(header.contains("Max-Age=0; Expires="));
+    }
+
+    #[test]
+    fn cookie_max_age_saturates() {
+        let cookie = Cookie::build("theme", "dark")
+            .max_age(Duration::MAX)
+            .build();
+
+        assert!(cookie.max_age().is_some());
+    }
+
+    #[test]
+    fn cookies_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; user%20name=a%3B%20b; invalid; theme=light"),
+        );
+        headers.append(header::COOKIE, HeaderValue::from_static("lang=en"));
+
+        let cookies = Cookies::from_headers(&headers);
+
+        assert_eq!(cookies.len(), 4);
+        assert_eq!(cookies.get("theme").unwrap().value(), "dark");
+        assert_eq!(cookies.get("user name").unwrap().value(), "a; b");
+        assert_eq!(cookies.get("lang").unwrap().value(), "en");
+        assert!(cookies.get("invalid").is_none());
+    }
+
+    #[cot::test]
+    async fn cookies_extractor() {
+        let mut request = TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert(header::COOKIE, HeaderValue::from_static("theme=dark"));
+        let (mut parts, _body) = request.into_parts();
+
+        let cookies = Cookies::from_request_parts(&mut parts).await.unwrap();
+
+        assert_eq!(cookies.get("theme").unwrap().value(), "dark");
+    }
+}
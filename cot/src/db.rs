@@ -3,29 +3,42 @@
 //! This module contains the database connection structure, the model trait, and
 //! the error types that can occur when interacting with the database.
 
-mod fields;
+pub(crate) mod fields;
+pub mod fixtures;
 #[cfg(feature = "mysql")]
 pub mod impl_mysql;
 #[cfg(feature = "postgres")]
 pub mod impl_postgres;
 #[cfg(feature = "sqlite")]
 pub mod impl_sqlite;
+pub mod introspection;
 pub mod migrations;
+mod pagination;
 pub mod query;
+mod query_log;
 mod relations;
 mod sea_query_db;
+mod search;
+pub mod seed;
 
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Write};
 use std::hash::Hash;
 use std::str::FromStr;
 
 use async_trait::async_trait;
-pub use cot_macros::{model, query};
-use derive_more::{Debug, Deref, Display};
+pub use cot_macros::{DbEnum, FromRow, model, query};
+use derive_more::{Debug, Deref, Display, From};
 #[cfg(test)]
 use mockall::automock;
+pub use pagination::{Page, Paginator};
 use query::Query;
-pub use relations::{ForeignKey, ForeignKeyOnDeletePolicy, ForeignKeyOnUpdatePolicy};
+use query_log::QueryLog;
+pub use query_log::{QueryLogging, QueryStats};
+use relations::ForeignKeyRelation;
+pub use relations::{
+    ForeignKey, ForeignKeyOnDeletePolicy, ForeignKeyOnUpdatePolicy, ManyToMany, ManyToManyRef,
+};
 use sea_query::{
     Iden, IntoColumnRef, OnConflict, ReturningClause, SchemaStatementBuilder, SimpleExpr,
 };
@@ -34,6 +47,7 @@ use sqlx::{Type, TypeInfo};
 use thiserror::Error;
 use tracing::{Instrument, Level, span, trace};
 
+use crate::clock::{Clock, SystemClock};
 #[cfg(feature = "mysql")]
 use crate::db::impl_mysql::{DatabaseMySql, MySqlRow, MySqlValueRef};
 #[cfg(feature = "postgres")]
@@ -68,16 +82,80 @@ pub enum DatabaseError {
     /// Error when applying migrations.
     #[error("Error when applying migrations: {0}")]
     MigrationError(#[from] migrations::MigrationEngineError),
+    /// Error when dumping or loading a fixture.
+    #[error("Error when processing a fixture: {0}")]
+    FixtureError(#[from] fixtures::FixtureError),
     /// An object could not be found in the database.
     #[error("Record with primary key `{primary_key}` not found in the database")]
     RecordNotFound {
         /// The primary key of the record that was not found.
+        ///
+        /// For composite primary keys, this is a string listing the values of
+        /// all the primary key columns.
         primary_key: DbValue,
     },
     /// Foreign Key could not be retrieved from the database because the record
     /// was not found.
     #[error("Error retrieving a Foreign Key from the database: record not found")]
     ForeignKeyNotFound,
+    /// A model instance has to be saved to the database before it can be used
+    /// in a relationship.
+    #[error("Model instance has not been saved to the database yet")]
+    UnsavedModel,
+    /// The rows could not be deleted, because they are referenced by a
+    /// foreign key with a [`ForeignKeyOnDeletePolicy::Restrict`] or
+    /// [`ForeignKeyOnDeletePolicy::NoAction`] policy.
+    ///
+    /// This is only returned on the database backends that don't enforce
+    /// foreign key constraints themselves; the others return a
+    /// [`DatabaseError::DatabaseEngineError`] instead.
+    #[error("Cannot delete the rows referenced by the foreign key `{table}.{column}`")]
+    ForeignKeyViolation {
+        /// The table that references the rows being deleted.
+        table: String,
+        /// The foreign key column that references the rows being deleted.
+        column: String,
+    },
+    /// The transaction has already been committed or rolled back, so no more
+    /// statements can be executed in it.
+    #[error("The transaction has already been committed or rolled back")]
+    TransactionFinished,
+    /// The row could not be saved, because another row with the same values
+    /// of the columns with a unique constraint already exists.
+    #[error(
+        "A row with the same value of `{}` already exists in table `{table}`",
+        columns.join("`, `")
+    )]
+    UniqueViolation {
+        /// The table the row was saved to.
+        table: String,
+        /// The columns of the violated unique constraint.
+        columns: Vec<String>,
+    },
+    /// The row could not be updated, because it has been modified since the
+    /// model instance was read from the database.
+    ///
+    /// This is only returned for the models with a field marked with
+    /// `#[model(version)]`; see [`Model::VERSION_COLUMN`].
+    #[error(
+        "Record with primary key `{primary_key}` has been modified since version `{version}` was read"
+    )]
+    StaleObject {
+        /// The primary key of the record that was modified.
+        ///
+        /// For composite primary keys, this is a string listing the values of
+        /// all the primary key columns.
+        primary_key: DbValue,
+        /// The version of the model instance that was being saved.
+        version: i64,
+    },
+    /// A query or a [`DatabaseRouter`] referred to a database that hasn't been
+    /// configured.
+    #[error("Database `{name}` is not configured")]
+    UnknownDatabase {
+        /// The name of the database.
+        name: String,
+    },
 }
 
 impl DatabaseError {
@@ -88,6 +166,151 @@ impl DatabaseError {
     }
 }
 
+/// Sets the automatic timestamps of a model instance that is about to be
+/// inserted (or updated, if `update` is `true`), and generates its primary key
+/// if it's [`Auto`] and not generated by the database.
+fn prepare_for_insert<T: Model>(
+    instance: &mut T,
+    now: chrono::DateTime<chrono::Utc>,
+    update: bool,
+) {
+    let auto_primary_key = has_auto_primary_key(instance);
+    instance.set_timestamps(now, !update || auto_primary_key);
+
+    if auto_primary_key {
+        if let Some(primary_key) = T::PrimaryKey::generate() {
+            instance.set_primary_key(primary_key);
+        }
+    }
+}
+
+/// Returns whether the primary key of a model instance (or any of its columns,
+/// for composite primary keys) is [`Auto`] and hasn't been generated yet.
+fn has_auto_primary_key<T: Model>(instance: &T) -> bool {
+    instance
+        .primary_key()
+        .to_db_field_values()
+        .iter()
+        .any(DbFieldValue::is_auto)
+}
+
+/// Returns the values of the primary key columns of a model instance, or
+/// [`DatabaseError::UnsavedModel`] if the primary key hasn't been generated
+/// yet.
+fn primary_key_values<T: Model>(instance: &T) -> Result<Vec<DbValue>> {
+    instance
+        .primary_key()
+        .to_db_field_values()
+        .into_iter()
+        .map(|value| match value {
+            DbFieldValue::Auto => Err(DatabaseError::UnsavedModel),
+            DbFieldValue::Value(value) => Ok(value),
+        })
+        .collect()
+}
+
+/// Combines the values of the primary key columns into a single value that
+/// is reported in the errors, such as [`DatabaseError::RecordNotFound`].
+fn primary_key_error_value(mut values: Vec<DbValue>) -> DbValue {
+    if values.len() == 1 {
+        return values.remove(0);
+    }
+
+    let values: Vec<_> = values.iter().map(ToString::to_string).collect();
+    format!("({})", values.join(", ")).into()
+}
+
+/// Returns the condition matching the row with given values of the primary
+/// key columns.
+fn primary_key_condition<T: Model>(primary_key: &[DbValue]) -> sea_query::Condition {
+    std::iter::zip(T::PRIMARY_KEY_COLUMNS, primary_key).fold(
+        sea_query::Condition::all(),
+        |condition, (&column, value)| condition.add(sea_query::Expr::col(column).eq(value.clone())),
+    )
+}
+
+/// Converts a unique constraint violation error returned by the database
+/// when saving an instance of given model into
+/// [`DatabaseError::UniqueViolation`]. Any other errors, or the violations of
+/// the constraints that can't be matched with the model's columns, are
+/// returned unchanged.
+fn unique_violation<T: Model>(error: DatabaseError) -> DatabaseError {
+    let DatabaseError::DatabaseEngineError(sqlx::Error::Database(database_error)) = &error else {
+        return error;
+    };
+    if database_error.kind() != sqlx::error::ErrorKind::UniqueViolation {
+        return error;
+    }
+
+    match unique_violation_columns::<T>(database_error.as_ref()) {
+        Some(columns) => DatabaseError::UniqueViolation {
+            table: T::TABLE_NAME.as_str().to_owned(),
+            columns,
+        },
+        None => error,
+    }
+}
+
+fn unique_violation_columns<T: Model>(
+    error: &dyn sqlx::error::DatabaseError,
+) -> Option<Vec<String>> {
+    let message = error.message();
+    let constraint_name = if let Some(constraint) = error.constraint() {
+        // PostgreSQL reports the name of the constraint
+        constraint
+    } else if let Some((_, key)) = message.rsplit_once(" for key '") {
+        // MySQL: "Duplicate entry '...' for key 'table.constraint'"
+        let key = key.trim_end_matches('\'');
+        key.rsplit_once('.').map_or(key, |(_, name)| name)
+    } else if let Some((_, columns)) = message.split_once("UNIQUE constraint failed: ") {
+        // SQLite: "UNIQUE constraint failed: table.column1, table.column2"
+        return Some(
+            columns
+                .split(", ")
+                .map(|column| {
+                    column
+                        .rsplit_once('.')
+                        .map_or(column, |(_, name)| name)
+                        .to_owned()
+                })
+                .collect(),
+        );
+    } else {
+        return None;
+    };
+
+    if let Some(index) = T::INDEXES
+        .iter()
+        .find(|index| index.is_unique() && index.name().as_str() == constraint_name)
+    {
+        return Some(
+            index
+                .columns()
+                .iter()
+                .map(|column| column.as_str().to_owned())
+                .collect(),
+        );
+    }
+    // the names of the constraints created for the primary key and the unique
+    // columns by PostgreSQL and MySQL
+    let table_name = T::TABLE_NAME.as_str();
+    if constraint_name == format!("{table_name}_pkey") || constraint_name == "PRIMARY" {
+        return Some(
+            T::PRIMARY_KEY_COLUMNS
+                .iter()
+                .map(|column| column.as_str().to_owned())
+                .collect(),
+        );
+    }
+    T::COLUMNS
+        .iter()
+        .find(|column| {
+            constraint_name == column.name.as_str()
+                || constraint_name == format!("{table_name}_{}_key", column.name)
+        })
+        .map(|column| vec![column.name.as_str().to_owned()])
+}
+
 /// An alias for [`Result`] that uses [`DatabaseError`] as the error type.
 pub type Result<T> = std::result::Result<T, DatabaseError>;
 
@@ -129,7 +352,10 @@ pub trait Model: Sized + Send + 'static {
     type Fields;
 
     /// The primary key type of the model.
-    type PrimaryKey: PrimaryKey;
+    ///
+    /// This is the type of the primary key field, or a tuple of the types of
+    /// the primary key fields for the models with a composite primary key.
+    type PrimaryKey: ModelPrimaryKey;
 
     /// The name of the app this model is defined in.
     const APP_NAME: &'static str;
@@ -138,11 +364,45 @@ pub trait Model: Sized + Send + 'static {
     const TABLE_NAME: Identifier;
 
     /// The name of the primary key column in the database.
+    ///
+    /// For the models with a composite primary key, this is the name of the
+    /// first primary key column; see [`Self::PRIMARY_KEY_COLUMNS`].
     const PRIMARY_KEY_NAME: Identifier;
 
+    /// The names of all the primary key columns in the database.
+    ///
+    /// This contains only [`Self::PRIMARY_KEY_NAME`], unless the model has a
+    /// composite primary key.
+    const PRIMARY_KEY_COLUMNS: &'static [Identifier] = &[Self::PRIMARY_KEY_NAME];
+
     /// The columns of the model.
     const COLUMNS: &'static [Column];
 
+    /// The indexes and the unique constraints spanning multiple columns,
+    /// declared with `#[model(index(...))]` and `#[model(unique(...))]`.
+    const INDEXES: &'static [Index] = &[];
+
+    /// The column storing the deletion time of the soft-deleted rows, if the
+    /// model is declared with `#[model(soft_delete)]`.
+    ///
+    /// The rows with a non-null value in this column are excluded from the
+    /// queries by default; see [`Query::with_deleted`].
+    const SOFT_DELETE_COLUMN: Option<Identifier> = None;
+
+    /// The column storing the version of the row, if the model has a field
+    /// marked with `#[model(version)]`.
+    ///
+    /// The version is used for optimistic locking: [`Self::save`] and
+    /// [`Self::update`] only update the row if its version is still the same
+    /// as the version of the instance, and increment it. Otherwise, they
+    /// return [`DatabaseError::StaleObject`], instead of silently overwriting
+    /// the changes made in the meantime.
+    const VERSION_COLUMN: Option<Identifier> = None;
+
+    /// The columns the model instances are searched by with
+    /// [`Query::search`], declared with `#[model(search(fields = [...]))]`.
+    const SEARCH_FIELDS: &'static [Identifier] = &[];
+
     /// Creates a model instance from a database row.
     ///
     /// # Errors
@@ -163,7 +423,7 @@ pub trait Model: Sized + Send + 'static {
     fn update_from_db(&mut self, db_row: Row, columns: &[usize]) -> Result<()>;
 
     /// Returns the primary key of the model.
-    fn primary_key(&self) -> &Self::PrimaryKey;
+    fn primary_key(&self) -> Self::PrimaryKey;
 
     /// Used by the ORM to set the primary key of the model after it has been
     /// saved to the database.
@@ -172,6 +432,33 @@ pub trait Model: Sized + Send + 'static {
     /// Gets the values of the model for the given columns.
     fn get_values(&self, columns: &[usize]) -> Vec<&dyn ToDbFieldValue>;
 
+    /// Sets the fields marked with `#[model(auto_now)]`, and if the instance
+    /// is being created, the fields marked with `#[model(auto_now_add)]`, to
+    /// the given time.
+    ///
+    /// This is called by the ORM right before the model instance is saved to
+    /// the database. The time is read from the [`Clock`](crate::clock::Clock)
+    /// of the database, see [`Database::with_clock`]. The instance is
+    /// considered created when it's inserted, or when it's saved with an
+    /// [`Auto`] primary key that hasn't been generated yet.
+    fn set_timestamps(&mut self, now: chrono::DateTime<chrono::Utc>, created: bool) {
+        let _ = (now, created);
+    }
+
+    /// Returns the value of the field marked with `#[model(version)]`, or
+    /// [`None`] if the model doesn't have one.
+    fn version(&self) -> Option<i64> {
+        None
+    }
+
+    /// Sets the value of the field marked with `#[model(version)]`.
+    ///
+    /// This is called by the ORM after the row has been updated. By default,
+    /// it does nothing.
+    fn set_version(&mut self, version: i64) {
+        let _ = version;
+    }
+
     /// Returns a query for all objects of this model.
     #[must_use]
     fn objects() -> Query<Self> {
@@ -202,8 +489,14 @@ pub trait Model: Sized + Send + 'static {
     /// inserted into the database, for instance because the migrations
     /// haven't been applied, or there was a problem with the database
     /// connection.
+    ///
+    /// This method returns [`DatabaseError::StaleObject`] if the model has a
+    /// version field (see [`Self::VERSION_COLUMN`]) and the row has been
+    /// updated since the instance was read from the database.
     async fn save<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
+        self.run_hooks(ModelEvent::PreSave, db).await?;
         db.insert_or_update(self).await?;
+        self.run_hooks(ModelEvent::PostSave, db).await?;
         Ok(())
     }
 
@@ -216,7 +509,81 @@ pub trait Model: Sized + Send + 'static {
     /// haven't been applied, or there was a problem with the database
     /// connection.
     async fn insert<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
+        self.run_hooks(ModelEvent::PreSave, db).await?;
         db.insert(self).await?;
+        self.run_hooks(ModelEvent::PostSave, db).await?;
+        Ok(())
+    }
+
+    /// Inserts multiple model instances into the database.
+    ///
+    /// The instances are inserted using as few `INSERT` statements as
+    /// possible, which is much faster than calling [`Self::insert`] for each
+    /// of them. The [`Auto`] fields of the instances are filled in with the
+    /// values generated by the database.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the model instances could not be
+    /// inserted into the database, for instance because the migrations
+    /// haven't been applied, or there was a problem with the database
+    /// connection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, Database, Model, model};
+    ///
+    /// #[model]
+    /// struct Tag {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     name: String,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let mut tags: Vec<_> = ["rust", "web", "orm"]
+    ///     .into_iter()
+    ///     .map(|name| Tag {
+    ///         id: Auto::auto(),
+    ///         name: name.to_owned(),
+    ///     })
+    ///     .collect();
+    /// Tag::bulk_create(db, &mut tags).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn bulk_create<DB: DatabaseBackend>(db: &DB, instances: &mut [Self]) -> Result<()> {
+        for instance in instances.iter_mut() {
+            instance.run_hooks(ModelEvent::PreSave, db).await?;
+        }
+        db.bulk_insert(instances).await?;
+        for instance in instances.iter_mut() {
+            instance.run_hooks(ModelEvent::PostSave, db).await?;
+        }
+        Ok(())
+    }
+
+    /// Inserts multiple model instances into the database, or updates the
+    /// instances with the same primary keys if they already exist.
+    ///
+    /// This is the bulk version of [`Self::save`]. See [`Self::bulk_create`]
+    /// for the details.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the model instances could not be
+    /// inserted into the database, for instance because the migrations
+    /// haven't been applied, or there was a problem with the database
+    /// connection.
+    async fn bulk_save<DB: DatabaseBackend>(db: &DB, instances: &mut [Self]) -> Result<()> {
+        for instance in instances.iter_mut() {
+            instance.run_hooks(ModelEvent::PreSave, db).await?;
+        }
+        db.bulk_insert_or_update(instances).await?;
+        for instance in instances.iter_mut() {
+            instance.run_hooks(ModelEvent::PostSave, db).await?;
+        }
         Ok(())
     }
 
@@ -231,12 +598,187 @@ pub trait Model: Sized + Send + 'static {
     ///
     /// This method can return an error if the model with the given primary key
     /// could not be found in the database.
+    ///
+    /// This method returns [`DatabaseError::StaleObject`] if the model has a
+    /// version field (see [`Self::VERSION_COLUMN`]) and the row has been
+    /// updated since the instance was read from the database.
     async fn update<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
+        self.run_hooks(ModelEvent::PreSave, db).await?;
         db.update(self).await?;
+        self.run_hooks(ModelEvent::PostSave, db).await?;
+        Ok(())
+    }
+
+    /// Deletes the model instance from the database.
+    ///
+    /// If the model uses soft deletes (see [`Self::SOFT_DELETE_COLUMN`]), the
+    /// row is only marked as deleted, just like with [`Query::delete`].
+    ///
+    /// # Errors
+    ///
+    /// This method returns [`DatabaseError::UnsavedModel`] if the instance has
+    /// an [`Auto`] primary key that hasn't been generated yet.
+    ///
+    /// This method returns [`DatabaseError::RecordNotFound`] if there is no
+    /// row with the primary key of the instance in the database.
+    ///
+    /// This method can return an error if the row could not be deleted, for
+    /// instance because it is referenced by a foreign key, or there was a
+    /// problem with the database connection.
+    async fn delete<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
+        let primary_key = primary_key_values(self)?;
+
+        self.run_hooks(ModelEvent::PreDelete, db).await?;
+        let filter = std::iter::zip(Self::PRIMARY_KEY_COLUMNS, &primary_key)
+            .map(|(&column, value)| {
+                query::Expr::eq(
+                    query::Expr::field(column),
+                    query::Expr::Value(value.clone()),
+                )
+            })
+            .reduce(query::Expr::and)
+            .expect("models must have at least one primary key column");
+        let result = Query::<Self>::new().filter(filter).delete(db).await?;
+        if result.rows_affected() == RowsNum(0) {
+            return Err(DatabaseError::RecordNotFound {
+                primary_key: primary_key_error_value(primary_key),
+            });
+        }
+        self.run_hooks(ModelEvent::PostDelete, db).await?;
+        Ok(())
+    }
+
+    /// Runs the lifecycle hooks of the model for the given event.
+    ///
+    /// This is called by [`Self::save`], [`Self::insert`], [`Self::update`],
+    /// [`Self::bulk_create`], [`Self::bulk_save`], and [`Self::delete`]. By
+    /// default, it does nothing; the models declared with `#[model(hooks)]`
+    /// run the corresponding [`ModelHooks`] method instead.
+    ///
+    /// # Errors
+    ///
+    /// This method returns the error returned by the hook, if any.
+    async fn run_hooks<DB: DatabaseBackend>(&mut self, event: ModelEvent, db: &DB) -> Result<()> {
+        let _ = (event, db);
+        Ok(())
+    }
+}
+
+/// Lifecycle hooks of a model, run when its instances are saved or deleted.
+///
+/// The hooks can be used to maintain denormalized data, invalidate caches, or
+/// enqueue tasks whenever a model changes, without having to wrap every call
+/// site. They are enabled by declaring the model with `#[model(hooks)]` and
+/// implementing this trait for it; all the methods do nothing by default.
+///
+/// The "save" hooks are run by [`Model::save`], [`Model::insert`],
+/// [`Model::update`], [`Model::bulk_create`], and [`Model::bulk_save`], and
+/// the "delete" hooks by [`Model::delete`]. The statements affecting multiple
+/// rows at once, such as [`Query::delete`] and [`Query::update_all`], don't
+/// run any hooks. If a "pre" hook returns an error, the operation is aborted
+/// and the error is returned.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, DatabaseBackend, ModelHooks, model};
+///
+/// #[model(hooks)]
+/// struct Article {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+///     slug: String,
+/// }
+///
+/// #[async_trait::async_trait]
+/// impl ModelHooks for Article {
+///     async fn pre_save<DB: DatabaseBackend>(&mut self, _db: &DB) -> cot::db::Result<()> {
+///         self.slug = self.title.to_lowercase().replace(' ', "-");
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait ModelHooks: Model {
+    /// Called before the instance is saved to the database.
+    ///
+    /// # Errors
+    ///
+    /// Returning an error aborts saving the instance.
+    async fn pre_save<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
+        let _ = db;
+        Ok(())
+    }
+
+    /// Called after the instance has been saved to the database.
+    ///
+    /// # Errors
+    ///
+    /// The error is returned from the method that saved the instance; note
+    /// that the instance has already been saved at this point.
+    async fn post_save<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
+        let _ = db;
+        Ok(())
+    }
+
+    /// Called before the instance is deleted from the database.
+    ///
+    /// # Errors
+    ///
+    /// Returning an error aborts deleting the instance.
+    async fn pre_delete<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
+        let _ = db;
+        Ok(())
+    }
+
+    /// Called after the instance has been deleted from the database.
+    ///
+    /// # Errors
+    ///
+    /// The error is returned from [`Model::delete`]; note that the instance
+    /// has already been deleted at this point.
+    async fn post_delete<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
+        let _ = db;
         Ok(())
     }
 }
 
+/// A lifecycle event of a model instance, passed to [`Model::run_hooks`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ModelEvent {
+    /// The instance is about to be saved.
+    PreSave,
+    /// The instance has been saved.
+    PostSave,
+    /// The instance is about to be deleted.
+    PreDelete,
+    /// The instance has been deleted.
+    PostDelete,
+}
+
+impl ModelEvent {
+    /// Runs the [`ModelHooks`] method corresponding to this event on the given
+    /// instance.
+    ///
+    /// # Errors
+    ///
+    /// This method returns the error returned by the hook, if any.
+    pub async fn run<T: ModelHooks, DB: DatabaseBackend>(
+        self,
+        instance: &mut T,
+        db: &DB,
+    ) -> Result<()> {
+        match self {
+            Self::PreSave => instance.pre_save(db).await,
+            Self::PostSave => instance.post_save(db).await,
+            Self::PreDelete => instance.pre_delete(db).await,
+            Self::PostDelete => instance.post_delete(db).await,
+        }
+    }
+}
+
 /// An identifier structure that holds table or column name as a string.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Deref)]
 pub struct Identifier(&'static str);
@@ -287,57 +829,382 @@ impl Column {
     }
 }
 
-/// A marker trait that denotes that a type can be used as a primary key in a
-/// database.
-pub trait PrimaryKey: DatabaseField + Clone {}
+/// An index, or a unique constraint, on one or more columns of a model.
+///
+/// Typically, you shouldn't need to use this directly. Instead, the indexes
+/// can be declared with the `index` and `unique` parameters of the [`model`]
+/// attribute macro, which are then available in [`Model::INDEXES`] and
+/// automatically added to the migrations by the Cot CLI.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Identifier, Index};
+///
+/// const INDEX: Index = Index::new(
+///     Identifier::new("app__post_tenant_id_slug_uniq"),
+///     &[Identifier::new("tenant_id"), Identifier::new("slug")],
+/// )
+/// .unique();
+///
+/// assert!(INDEX.is_unique());
+/// assert_eq!(INDEX.columns().len(), 2);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Index {
+    name: Identifier,
+    columns: &'static [Identifier],
+    unique: bool,
+}
 
-/// A row structure that holds the data of a single row retrieved from the
-/// database.
-#[non_exhaustive]
-#[derive(Debug)]
-pub enum Row {
-    /// A row from a SQLite database.
-    #[cfg(feature = "sqlite")]
-    Sqlite(SqliteRow),
-    /// A row from a PostgreSQL database.
-    #[cfg(feature = "postgres")]
-    Postgres(PostgresRow),
-    /// A row from a MySQL database.
-    #[cfg(feature = "mysql")]
-    MySql(MySqlRow),
+impl Index {
+    /// Creates a new (non-unique) index with the given name on the given
+    /// columns.
+    #[must_use]
+    pub const fn new(name: Identifier, columns: &'static [Identifier]) -> Self {
+        Self {
+            name,
+            columns,
+            unique: false,
+        }
+    }
+
+    /// Marks the index as unique, i.e. makes it a unique constraint.
+    #[must_use]
+    pub const fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    /// Returns the name of the index in the database.
+    #[must_use]
+    pub const fn name(&self) -> Identifier {
+        self.name
+    }
+
+    /// Returns the columns the index is created on.
+    #[must_use]
+    pub const fn columns(&self) -> &'static [Identifier] {
+        self.columns
+    }
+
+    /// Returns whether the index is a unique constraint.
+    #[must_use]
+    pub const fn is_unique(&self) -> bool {
+        self.unique
+    }
 }
 
-impl Row {
-    /// Gets the value at the given index and converts it to the given type.
-    /// The index is zero-based.
+/// A trait that denotes that a type can be used as a primary key in a
+/// database.
+///
+/// The primary keys wrapped in [`Auto`] are generated automatically when
+/// inserting new rows. Integer keys are generated by the database (as
+/// auto-incrementing columns), while the other types, such as [`uuid::Uuid`],
+/// are generated by Cot with [`PrimaryKey::generate`] before inserting.
+///
+/// Note that MySQL doesn't support primary keys on `TEXT` columns, so
+/// [`LimitedString`] should be used instead of [`String`] for string keys if
+/// the application needs to support MySQL.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, LimitedString, model};
+/// use uuid::Uuid;
+///
+/// #[model]
+/// struct Document {
+///     #[model(primary_key)]
+///     id: Auto<Uuid>,
+///     title: String,
+/// }
+///
+/// #[model]
+/// struct Country {
+///     #[model(primary_key)]
+///     code: LimitedString<2>,
+///     name: String,
+/// }
+/// ```
+pub trait PrimaryKey: DatabaseField + Clone {
+    /// Generates a new value of the primary key before inserting a row with
+    /// an [`Auto`] primary key.
     ///
-    /// # Errors
+    /// Returns `None` if the value is generated by the database instead,
+    /// which is the default.
     ///
-    /// This method can return an error if the value at the given index is not
-    /// compatible with the Rust type.
+    /// # Examples
     ///
-    /// This can also return an error if the index is out of bounds of the row
-    /// returned by the database.
-    pub fn get<T: FromDbValue>(&self, index: usize) -> Result<T> {
-        let result = match self {
-            #[cfg(feature = "sqlite")]
-            Row::Sqlite(sqlite_row) => sqlite_row
-                .get_raw(index)
-                .and_then(|value| T::from_sqlite(value))?,
-            #[cfg(feature = "postgres")]
-            Row::Postgres(postgres_row) => postgres_row
-                .get_raw(index)
-                .and_then(|value| T::from_postgres(value))?,
-            #[cfg(feature = "mysql")]
-            Row::MySql(mysql_row) => mysql_row
-                .get_raw(index)
-                .and_then(|value| T::from_mysql(value))?,
-        };
-
-        Ok(result)
+    /// ```
+    /// use cot::db::{Auto, PrimaryKey};
+    /// use uuid::Uuid;
+    ///
+    /// assert!(<Auto<i32> as PrimaryKey>::generate().is_none());
+    /// assert!(<Auto<Uuid> as PrimaryKey>::generate().is_some());
+    /// ```
+    #[must_use]
+    fn generate() -> Option<Self> {
+        None
     }
 }
 
+/// A trait for the primary key of a model, spanning one or more columns.
+///
+/// This is implemented for all the [`PrimaryKey`] types, which are used as
+/// single-column primary keys, and for the tuples of up to four
+/// [`PrimaryKey`] types, which are used as composite primary keys of the
+/// models with more than one field marked with `#[model(primary_key)]`.
+///
+/// Note that the fields of a composite primary key cannot be [`Auto`], and
+/// that a [`ForeignKey`] can only reference a model with a single-column
+/// primary key.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, ForeignKey, Model, model};
+///
+/// #[model]
+/// #[derive(Debug, Clone)]
+/// struct Student {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     name: String,
+/// }
+///
+/// #[model]
+/// struct Enrollment {
+///     #[model(primary_key)]
+///     student: ForeignKey<Student>,
+///     #[model(primary_key)]
+///     course_code: String,
+///     grade: Option<i32>,
+/// }
+///
+/// let enrollment = Enrollment {
+///     student: ForeignKey::PrimaryKey(Auto::fixed(1)),
+///     course_code: "CS101".to_owned(),
+///     grade: None,
+/// };
+/// assert_eq!(
+///     enrollment.primary_key(),
+///     (ForeignKey::PrimaryKey(Auto::fixed(1)), "CS101".to_owned())
+/// );
+/// ```
+pub trait ModelPrimaryKey: Clone {
+    /// Generates a new value of the primary key before inserting a row with
+    /// an [`Auto`] primary key.
+    ///
+    /// Returns `None` if the value is generated by the database instead, or
+    /// if the primary key is composite.
+    #[must_use]
+    fn generate() -> Option<Self> {
+        None
+    }
+
+    /// Returns the values of the primary key columns, in the order of
+    /// [`Model::PRIMARY_KEY_COLUMNS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, DbFieldValue, ModelPrimaryKey};
+    ///
+    /// assert_eq!(
+    ///     (1, "a".to_owned()).to_db_field_values(),
+    ///     vec![
+    ///         DbFieldValue::Value(1.into()),
+    ///         DbFieldValue::Value("a".into())
+    ///     ]
+    /// );
+    /// assert_eq!(
+    ///     Auto::<i32>::auto().to_db_field_values(),
+    ///     vec![DbFieldValue::Auto]
+    /// );
+    /// ```
+    fn to_db_field_values(&self) -> Vec<DbFieldValue>;
+}
+
+impl<T: PrimaryKey> ModelPrimaryKey for T {
+    fn generate() -> Option<Self> {
+        <T as PrimaryKey>::generate()
+    }
+
+    fn to_db_field_values(&self) -> Vec<DbFieldValue> {
+        vec![self.to_db_field_value()]
+    }
+}
+
+macro_rules! impl_model_primary_key_for_tuple {
+    ($($ty:ident: $index:tt),+) => {
+        impl<$($ty: PrimaryKey),+> ModelPrimaryKey for ($($ty,)+) {
+            fn to_db_field_values(&self) -> Vec<DbFieldValue> {
+                vec![$(self.$index.to_db_field_value()),+]
+            }
+        }
+    };
+}
+
+impl_model_primary_key_for_tuple!(T0: 0, T1: 1);
+impl_model_primary_key_for_tuple!(T0: 0, T1: 1, T2: 2);
+impl_model_primary_key_for_tuple!(T0: 0, T1: 1, T2: 2, T3: 3);
+
+/// A row structure that holds the data of a single row retrieved from the
+/// database.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Row {
+    /// A row from a SQLite database.
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteRow),
+    /// A row from a PostgreSQL database.
+    #[cfg(feature = "postgres")]
+    Postgres(PostgresRow),
+    /// A row from a MySQL database.
+    #[cfg(feature = "mysql")]
+    MySql(MySqlRow),
+}
+
+impl Row {
+    /// Gets the value at the given index and converts it to the given type.
+    /// The index is zero-based.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the value at the given index is not
+    /// compatible with the Rust type.
+    ///
+    /// This can also return an error if the index is out of bounds of the row
+    /// returned by the database.
+    pub fn get<T: FromDbValue>(&self, index: usize) -> Result<T> {
+        let result = match self {
+            #[cfg(feature = "sqlite")]
+            Row::Sqlite(sqlite_row) => sqlite_row
+                .get_raw(index)
+                .and_then(|value| T::from_sqlite(value))?,
+            #[cfg(feature = "postgres")]
+            Row::Postgres(postgres_row) => postgres_row
+                .get_raw(index)
+                .and_then(|value| T::from_postgres(value))?,
+            #[cfg(feature = "mysql")]
+            Row::MySql(mysql_row) => mysql_row
+                .get_raw(index)
+                .and_then(|value| T::from_mysql(value))?,
+        };
+
+        Ok(result)
+    }
+
+    /// Gets the value of the column with the given name and converts it to
+    /// the given type.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the value of the column is not
+    /// compatible with the Rust type.
+    ///
+    /// This can also return an error if there is no column with the given
+    /// name in the row returned by the database.
+    pub fn get_by_name<T: FromDbValue>(&self, name: &str) -> Result<T> {
+        let index = match self {
+            #[cfg(feature = "sqlite")]
+            Row::Sqlite(sqlite_row) => sqlite_row.column_index(name)?,
+            #[cfg(feature = "postgres")]
+            Row::Postgres(postgres_row) => postgres_row.column_index(name)?,
+            #[cfg(feature = "mysql")]
+            Row::MySql(mysql_row) => mysql_row.column_index(name)?,
+        };
+
+        self.get(index)
+    }
+}
+
+/// A trait for types that can be created from a database [`Row`].
+///
+/// This is used to retrieve the results of queries that don't return whole
+/// models, such as the aggregate queries created with
+/// [`Query::aggregate`](query::Query::aggregate) and the raw SQL queries
+/// executed with [`Database::raw_query`]. The trait is implemented for
+/// tuples of up to 12 elements, which read the columns of the row in order.
+///
+/// # Deriving
+///
+/// The trait can be derived for structs. The fields of a struct with named
+/// fields are read from the columns with the same names (which can be
+/// changed with the `#[from_row(rename = "...")]` attribute), and the fields
+/// of a tuple struct are read from the columns in order. All the field types
+/// must implement [`FromDbValue`].
+///
+/// ```
+/// use cot::db::FromRow;
+///
+/// #[derive(FromRow)]
+/// struct AuthorStats {
+///     author: String,
+///     #[from_row(rename = "count")]
+///     post_count: i64,
+/// }
+/// ```
+///
+/// # Examples
+///
+/// Implementing the trait manually:
+///
+/// ```
+/// use cot::db::{FromRow, Row};
+///
+/// struct AuthorStats {
+///     author: String,
+///     post_count: i64,
+/// }
+///
+/// impl FromRow for AuthorStats {
+///     fn from_row(row: &Row) -> cot::db::Result<Self> {
+///         Ok(Self {
+///             author: row.get(0)?,
+///             post_count: row.get(1)?,
+///         })
+///     }
+/// }
+/// ```
+pub trait FromRow: Sized {
+    /// Creates a new instance of the type from the given row.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the data in the row is not
+    /// compatible with the type.
+    fn from_row(row: &Row) -> Result<Self>;
+}
+
+macro_rules! impl_from_row_for_tuple {
+    ($($ty:ident: $index:tt),+) => {
+        impl<$($ty: FromDbValue),+> FromRow for ($($ty,)+) {
+            fn from_row(row: &Row) -> Result<Self> {
+                Ok(($(row.get::<$ty>($index)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_row_for_tuple!(T0: 0);
+impl_from_row_for_tuple!(T0: 0, T1: 1);
+impl_from_row_for_tuple!(T0: 0, T1: 1, T2: 2);
+impl_from_row_for_tuple!(T0: 0, T1: 1, T2: 2, T3: 3);
+impl_from_row_for_tuple!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4);
+impl_from_row_for_tuple!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5);
+impl_from_row_for_tuple!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6);
+impl_from_row_for_tuple!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7);
+impl_from_row_for_tuple!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7, T8: 8);
+impl_from_row_for_tuple!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7, T8: 8, T9: 9);
+impl_from_row_for_tuple!(
+    T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7, T8: 8, T9: 9, T10: 10
+);
+impl_from_row_for_tuple!(
+    T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7, T8: 8, T9: 9, T10: 10, T11: 11
+);
+
 /// A trait denoting that some type can be used as a field in a database.
 pub trait DatabaseField: FromDbValue + ToDbFieldValue {
     /// Whether the field can be `NULL` in the database.
@@ -366,6 +1233,255 @@ pub trait DatabaseField: FromDbValue + ToDbFieldValue {
     const TYPE: ColumnType;
 }
 
+/// A trait for the field types that can be filled in with the current time
+/// by the `auto_now` and `auto_now_add` options of the [`model`] attribute.
+///
+/// This is implemented for [`chrono::DateTime<Utc>`](chrono::DateTime),
+/// [`chrono::DateTime<FixedOffset>`](chrono::DateTime) (set to the UTC
+/// offset), [`chrono::NaiveDateTime`] (set to the UTC time), and for the
+/// [`Option`]s of these types.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use cot::db::{Auto, model};
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     #[model(auto_now_add)]
+///     created_at: DateTime<Utc>,
+///     #[model(auto_now)]
+///     updated_at: Option<DateTime<Utc>>,
+/// }
+/// ```
+pub trait AutoNow {
+    /// Converts the current time into a value of the field.
+    fn from_now(now: chrono::DateTime<chrono::Utc>) -> Self;
+}
+
+/// A trait for enums that can be used as model fields.
+///
+/// The enums are stored in the database either as strings (the names of the
+/// variants) or as integers (the discriminants of the variants), depending on
+/// [`DbEnum::REPR`]. The values loaded from the database are validated, so
+/// reading a value that doesn't correspond to any of the variants results in
+/// a [`DatabaseError::ValueDecode`] error. The enums implementing this trait
+/// are also rendered as select fields in forms and the admin panel.
+///
+/// Note that native PostgreSQL enum types are not supported; the enums are
+/// stored in regular text or integer columns in all the database backends.
+///
+/// # Deriving
+///
+/// The trait can be derived for enums with only unit variants, which must
+/// also implement [`Copy`]. By default, the variants are stored as strings
+/// named after the variants in `snake_case`; the
+/// `#[db_enum(repr = "integer")]` attribute on the enum makes them stored as
+/// their discriminants instead. Each variant can be given a custom name with
+/// `#[db_enum(rename = "...")]` and a custom label shown in forms with
+/// `#[db_enum(label = "...")]`.
+///
+/// ```
+/// use cot::db::{Auto, DbEnum, model};
+///
+/// #[derive(Debug, Copy, Clone, PartialEq, Eq, DbEnum)]
+/// enum Status {
+///     Draft,
+///     #[db_enum(rename = "live", label = "Published")]
+///     Published,
+/// }
+///
+/// #[derive(Debug, Copy, Clone, PartialEq, Eq, DbEnum)]
+/// #[db_enum(repr = "integer")]
+/// enum Priority {
+///     Low = 1,
+///     High = 10,
+/// }
+///
+/// #[model]
+/// struct Task {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     status: Status,
+///     priority: Option<Priority>,
+/// }
+///
+/// assert_eq!(Status::Published.name(), "live");
+/// assert_eq!(Status::from_name("draft"), Some(Status::Draft));
+/// assert_eq!(Priority::High.discriminant(), 10);
+/// ```
+pub trait DbEnum: Copy + Send + Sync + 'static {
+    /// The way the enum is stored in the database.
+    const REPR: DbEnumRepr;
+
+    /// All the variants of the enum, in the order of declaration.
+    const VARIANTS: &'static [Self];
+
+    /// Returns the name of the variant, which is stored in the database if
+    /// [`DbEnum::REPR`] is [`DbEnumRepr::String`] and used as the value of
+    /// the form fields.
+    fn name(&self) -> &'static str;
+
+    /// Returns the discriminant of the variant, which is stored in the
+    /// database if [`DbEnum::REPR`] is [`DbEnumRepr::Integer`].
+    fn discriminant(&self) -> i32;
+
+    /// Returns the human-readable label of the variant, shown in forms.
+    fn label(&self) -> &'static str;
+
+    /// Returns the variant with given name, or `None` if there is no such
+    /// variant.
+    #[must_use]
+    fn from_name(name: &str) -> Option<Self> {
+        Self::VARIANTS
+            .iter()
+            .copied()
+            .find(|variant| variant.name() == name)
+    }
+
+    /// Returns the variant with given discriminant, or `None` if there is no
+    /// such variant.
+    #[must_use]
+    fn from_discriminant(discriminant: i32) -> Option<Self> {
+        Self::VARIANTS
+            .iter()
+            .copied()
+            .find(|variant| variant.discriminant() == discriminant)
+    }
+}
+
+/// The way a [`DbEnum`] is stored in the database.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DbEnumRepr {
+    /// The enum is stored as the name of the variant in a text column.
+    String,
+    /// The enum is stored as the discriminant of the variant in an integer
+    /// column.
+    Integer,
+}
+
+impl DbEnumRepr {
+    /// Returns the type of the database column used to store the enum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{ColumnType, DbEnumRepr};
+    ///
+    /// assert_eq!(DbEnumRepr::String.column_type(), ColumnType::Text);
+    /// assert_eq!(DbEnumRepr::Integer.column_type(), ColumnType::Integer);
+    /// ```
+    #[must_use]
+    pub const fn column_type(self) -> ColumnType {
+        match self {
+            Self::String => ColumnType::Text,
+            Self::Integer => ColumnType::Integer,
+        }
+    }
+}
+
+/// A trait for custom model field types that are stored in the database as
+/// one of the types already supported by Cot.
+///
+/// Implementing this trait (along with [`ToDbValue`]) makes the type usable
+/// as a model field: it implements [`DatabaseField`] and [`FromDbValue`], so
+/// it can be used in the migrations and loaded from the database in all the
+/// supported backends, and it can be compared with other values in the
+/// queries. [`Option`]s of the type are supported as well, as long as the
+/// [`Option`] of the [`Repr`](CustomDbField::Repr) type is. This allows
+/// third-party crates to provide field types such as money amounts, IP
+/// addresses, or encrypted strings, without having to implement the
+/// conversions for each database backend.
+///
+/// The values loaded from the database are converted with
+/// [`CustomDbField::from_repr`]; returning an error from it (for instance,
+/// [`DatabaseError::value_decode`]) makes the query fail with a
+/// [`DatabaseError::ValueDecode`] error.
+///
+/// To use the type in forms (and, consequently, in the admin panel),
+/// implement [`AsFormField`](crate::form::AsFormField) for it as well.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::IpAddr;
+///
+/// use cot::db::{Auto, CustomDbField, DatabaseError, DbValue, ToDbValue, model};
+/// use cot::form::fields::StringField;
+/// use cot::form::{AsFormField, FormField, FormFieldValidationError};
+///
+/// /// An IP address, stored in the database as text.
+/// #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// struct IpAddress(IpAddr);
+///
+/// impl CustomDbField for IpAddress {
+///     type Repr = String;
+///
+///     fn to_repr(&self) -> String {
+///         self.0.to_string()
+///     }
+///
+///     fn from_repr(repr: String) -> cot::db::Result<Self> {
+///         repr.parse().map(Self).map_err(DatabaseError::value_decode)
+///     }
+/// }
+///
+/// impl ToDbValue for IpAddress {
+///     fn to_db_value(&self) -> DbValue {
+///         self.to_repr().into()
+///     }
+/// }
+///
+/// impl AsFormField for IpAddress {
+///     type Type = StringField;
+///
+///     fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+///         let value = field
+///             .value()
+///             .filter(|value| !value.is_empty())
+///             .ok_or(FormFieldValidationError::Required)?;
+///         value
+///             .parse()
+///             .map(Self)
+///             .map_err(|_| FormFieldValidationError::invalid_value(value))
+///     }
+///
+///     fn to_field_value(&self) -> String {
+///         self.0.to_string()
+///     }
+/// }
+///
+/// #[model]
+/// struct Login {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     ip_address: IpAddress,
+///     forwarded_for: Option<IpAddress>,
+/// }
+/// ```
+pub trait CustomDbField: ToDbValue + Sized {
+    /// The type the field is stored as in the database.
+    ///
+    /// Its [`DatabaseField::TYPE`] is used as the type of the database
+    /// column.
+    type Repr: DatabaseField + ToDbValue;
+
+    /// Converts the value to the type it is stored as in the database.
+    fn to_repr(&self) -> Self::Repr;
+
+    /// Converts the value loaded from the database back to the field type.
+    ///
+    /// # Errors
+    ///
+    /// This method should return an error (usually created with
+    /// [`DatabaseError::value_decode`]) if the value is not valid for the
+    /// field type.
+    fn from_repr(repr: Self::Repr) -> Result<Self>;
+}
+
 /// A trait for converting a database value to a Rust value.
 pub trait FromDbValue {
     /// Converts the given SQLite database value to a Rust value.
@@ -594,6 +1710,8 @@ trait SqlxRowRef {
         Self: 'r;
 
     fn get_raw(&self, index: usize) -> Result<Self::ValueRef<'_>>;
+
+    fn column_index(&self, name: &str) -> Result<usize>;
 }
 
 /// A trait for accessing raw database values from a specific database backend.
@@ -663,8 +1781,14 @@ pub trait SqlxValueRef<'r>: Sized {
 /// [`Self::close()`] is called.
 #[derive(Debug)]
 pub struct Database {
-    _url: String,
+    url: String,
     inner: DatabaseImpl,
+    supports_foreign_keys: bool,
+    foreign_key_relations: std::sync::RwLock<Vec<ForeignKeyRelation>>,
+    clock: std::sync::Arc<dyn Clock>,
+    databases: HashMap<String, std::sync::Arc<Database>>,
+    #[debug("..")]
+    router: Option<std::sync::Arc<dyn DatabaseRouter>>,
 }
 
 #[derive(Debug)]
@@ -690,7 +1814,11 @@ impl Database {
     ///
     /// # Panics
     ///
-    /// This method will panic if the database URL is not supported.
+    /// This method will panic if the database URL is not supported. The
+    /// supported URLs start with `sqlite:` (with the `sqlite` feature
+    /// enabled), `postgresql:` (with the `postgres` feature), and `mysql:` or
+    /// `mariadb:` (with the `mysql` feature, which supports both MySQL and
+    /// MariaDB servers).
     ///
     /// # Examples
     ///
@@ -703,39 +1831,303 @@ impl Database {
     /// }
     /// ```
     pub async fn new<T: Into<String>>(url: T) -> Result<Self> {
+        Self::new_with_options(url, &PoolOptions::default()).await
+    }
+
+    /// Creates a new database connection with given connection pool
+    /// options. The connection string should be in the format of the database
+    /// URL.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the connection to the database could
+    /// not be established.
+    ///
+    /// This method can return an error if the database URL is invalid.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the database URL is not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::{Database, PoolOptions};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let options = PoolOptions::new()
+    ///     .max_connections(20)
+    ///     .acquire_timeout(Duration::from_secs(5));
+    /// let db = Database::new_with_options("sqlite::memory:", &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_with_options<T: Into<String>>(url: T, options: &PoolOptions) -> Result<Self> {
         let url = url.into();
 
         #[cfg(feature = "sqlite")]
         if url.starts_with("sqlite:") {
-            let inner = DatabaseSqlite::new(&url).await?;
-            return Ok(Self {
-                _url: url,
-                inner: DatabaseImpl::Sqlite(inner),
-            });
+            let inner = DatabaseSqlite::new(&url, options).await?;
+            return Self::from_impl(url, DatabaseImpl::Sqlite(inner)).await;
         }
 
         #[cfg(feature = "postgres")]
         if url.starts_with("postgresql:") {
-            let inner = DatabasePostgres::new(&url).await?;
-            return Ok(Self {
-                _url: url,
-                inner: DatabaseImpl::Postgres(inner),
-            });
+            let inner = DatabasePostgres::new(&url, options).await?;
+            return Self::from_impl(url, DatabaseImpl::Postgres(inner)).await;
         }
 
         #[cfg(feature = "mysql")]
-        if url.starts_with("mysql:") {
-            let inner = DatabaseMySql::new(&url).await?;
-            return Ok(Self {
-                _url: url,
-                inner: DatabaseImpl::MySql(inner),
-            });
+        if url.starts_with("mysql:") || url.starts_with("mariadb:") {
+            let inner = DatabaseMySql::new(&url, options).await?;
+            return Self::from_impl(url, DatabaseImpl::MySql(inner)).await;
         }
 
         panic!("Unsupported database URL: {url}");
     }
 
-    /// Closes the database connection.
+    async fn from_impl(url: String, inner: DatabaseImpl) -> Result<Self> {
+        let supports_foreign_keys = match &inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.supports_foreign_keys().await?,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.supports_foreign_keys().await?,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.supports_foreign_keys().await?,
+        };
+
+        Ok(Self {
+            url,
+            inner,
+            supports_foreign_keys,
+            foreign_key_relations: std::sync::RwLock::default(),
+            clock: std::sync::Arc::new(SystemClock),
+            databases: HashMap::new(),
+            router: None,
+        })
+    }
+
+    /// Sets the clock used as the source of the current time for the
+    /// `auto_now` and `auto_now_add` model fields.
+    ///
+    /// This is the [`SystemClock`] by default. When the database is created
+    /// by the [`Bootstrapper`](crate::Bootstrapper), the clock of the project
+    /// is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::db::Database;
+    /// use cot::test::TestClock;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let clock = TestClock::new();
+    /// let db = Database::new("sqlite::memory:")
+    ///     .await?
+    ///     .with_clock(Arc::new(clock.clone()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_clock(mut self, clock: std::sync::Arc<dyn Clock>) -> Self {
+        self.set_clock(clock);
+        self
+    }
+
+    pub(crate) fn set_clock(&mut self, clock: std::sync::Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Sets the options of logging the queries executed by this database.
+    ///
+    /// See [`QueryLogging`] for the details. When the database is created by
+    /// the [`Bootstrapper`](crate::Bootstrapper), the options are taken from
+    /// the `[database]` section of the config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::{Database, QueryLogging};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let db = Database::new("sqlite::memory:")
+    ///     .await?
+    ///     .with_query_logging(QueryLogging::new().slow_query_threshold(Duration::from_millis(200)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_query_logging(mut self, query_logging: QueryLogging) -> Self {
+        match &mut self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.set_query_logging(query_logging),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.set_query_logging(query_logging),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.set_query_logging(query_logging),
+        }
+        self
+    }
+
+    /// Returns the clock used as the source of the current time for the
+    /// `auto_now` and `auto_now_add` model fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// let now = db.clock().now();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn clock(&self) -> &std::sync::Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Adds a named database, which the queries can be routed to with
+    /// [`Query::using`] or a [`DatabaseRouter`].
+    ///
+    /// This database is then the "default" one: the model operations use it
+    /// unless they are routed elsewhere. When the database is created by the
+    /// [`Bootstrapper`](crate::Bootstrapper), the databases declared in the
+    /// `[database.<name>]` sections of the config are added automatically.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the name is `"default"`, which is reserved for
+    /// this database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let replica = Database::new("sqlite::memory:").await?;
+    /// let db = Database::new("sqlite::memory:")
+    ///     .await?
+    ///     .with_database("replica", Arc::new(replica));
+    /// assert!(db.named("replica").is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_database<N: Into<String>>(
+        mut self,
+        name: N,
+        database: std::sync::Arc<Database>,
+    ) -> Self {
+        let name = name.into();
+        assert_ne!(
+            name, DEFAULT_DATABASE,
+            "the `{DEFAULT_DATABASE}` database name is reserved"
+        );
+        self.databases.insert(name, database);
+        self
+    }
+
+    /// Sets the router deciding which database the model operations use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::db::{Database, DatabaseRouter, ModelInfo};
+    ///
+    /// struct ReadReplicaRouter;
+    ///
+    /// impl DatabaseRouter for ReadReplicaRouter {
+    ///     fn db_for_read(&self, _model: &ModelInfo) -> Option<&str> {
+    ///         Some("replica")
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let replica = Database::new("sqlite::memory:").await?;
+    /// let db = Database::new("sqlite::memory:")
+    ///     .await?
+    ///     .with_database("replica", Arc::new(replica))
+    ///     .with_router(Arc::new(ReadReplicaRouter));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_router(mut self, router: std::sync::Arc<dyn DatabaseRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Returns the named database added with [`Self::with_database`], or this
+    /// database if the name is `"default"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// assert!(db.named("default").is_some());
+    /// assert!(db.named("analytics").is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn named(&self, name: &str) -> Option<&Database> {
+        if name == DEFAULT_DATABASE {
+            Some(self)
+        } else {
+            self.databases.get(name).map(|database| &**database)
+        }
+    }
+
+    /// Returns the database the operation on the model `T` should be run on:
+    /// the one given explicitly with [`Query::using`], the one chosen by the
+    /// router, or this database.
+    pub(crate) fn route<T: Model>(&self, using: Option<&str>, write: bool) -> Result<&Database> {
+        let name = using.or_else(|| {
+            let router = self.router.as_ref()?;
+            let model = ModelInfo::of::<T>();
+            if write {
+                router.db_for_write(&model)
+            } else {
+                router.db_for_read(&model)
+            }
+        });
+
+        match name {
+            None => Ok(self),
+            // transactions never route the statements elsewhere
+            Some(_) if self.is_transaction() => Ok(self),
+            Some(name) => self
+                .named(name)
+                .ok_or_else(|| DatabaseError::UnknownDatabase {
+                    name: name.to_owned(),
+                }),
+        }
+    }
+
+    /// Closes the database connection.
     ///
     /// This method should be called when the database connection is no longer
     /// needed. The connection is closed and the resources are released.
@@ -758,6 +2150,14 @@ impl Database {
     /// }
     /// ```
     pub async fn close(&self) -> Result<()> {
+        for database in self.databases.values() {
+            database.close_pool().await?;
+        }
+
+        self.close_pool().await
+    }
+
+    async fn close_pool(&self) -> Result<()> {
         match &self.inner {
             #[cfg(feature = "sqlite")]
             DatabaseImpl::Sqlite(inner) => inner.close().await,
@@ -768,6 +2168,84 @@ impl Database {
         }
     }
 
+    /// Returns the schema of all the tables in the database: their columns
+    /// and indexes.
+    ///
+    /// For PostgreSQL, only the tables in the current schema (typically
+    /// `public`) are returned. The tables are sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the database catalog could not be
+    /// queried.
+    ///
+    /// Can return an error if the database connection is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{ColumnType, Database};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// db.raw("CREATE TABLE books (id INTEGER PRIMARY KEY, title VARCHAR(100) NOT NULL)")
+    ///     .await?;
+    ///
+    /// let tables = db.introspect().await?;
+    /// let title = &tables[0].columns[1];
+    /// assert_eq!(title.name, "title");
+    /// assert_eq!(title.column_type, Some(ColumnType::String(100)));
+    /// assert!(!title.nullable);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn introspect(&self) -> Result<Vec<introspection::TableSchema>> {
+        match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.introspect().await,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.introspect().await,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.introspect().await,
+        }
+    }
+
+    /// Returns the current state of the connection pool.
+    ///
+    /// This can be used to monitor the database connections, e.g. by exposing
+    /// the values as metrics, or in a health check endpoint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    ///
+    /// let status = db.pool_status();
+    /// println!(
+    ///     "{} of {} connections in use",
+    ///     status.in_use(),
+    ///     status.max_connections()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn pool_status(&self) -> PoolStatus {
+        match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.pool_status(),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.pool_status(),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.pool_status(),
+        }
+    }
+
     /// Inserts a new row into the database.
     ///
     /// # Errors
@@ -775,12 +2253,16 @@ impl Database {
     /// This method can return an error if the row could not be inserted into
     /// the database, for instance because the migrations haven't been
     /// applied, or there was a problem with the database connection.
+    ///
+    /// This method can return [`DatabaseError::UniqueViolation`] if a row
+    /// with the same values of the unique columns already exists.
     pub async fn insert<T: Model>(&self, data: &mut T) -> Result<()> {
         let span = span!(Level::TRACE, "insert", table = %T::TABLE_NAME);
 
-        Self::insert_or_update_impl(self, data, false)
+        Self::insert_or_update_impl(self.route::<T>(None, true)?, data, false)
             .instrument(span)
             .await
+            .map_err(unique_violation::<T>)
     }
 
     /// Inserts a new row into the database, or updates it if a row with the
@@ -791,6 +2273,9 @@ impl Database {
     /// This method can return an error if the row could not be inserted into
     /// the database, for instance because the migrations haven't been
     /// applied, or there was a problem with the database connection.
+    ///
+    /// This method can return [`DatabaseError::UniqueViolation`] if a row
+    /// with the same values of the unique columns already exists.
     pub async fn insert_or_update<T: Model>(&self, data: &mut T) -> Result<()> {
         let span = span!(
             Level::TRACE,
@@ -798,12 +2283,26 @@ impl Database {
             table = %T::TABLE_NAME
         );
 
-        Self::insert_or_update_impl(self, data, true)
+        Self::insert_or_update_impl(self.route::<T>(None, true)?, data, true)
             .instrument(span)
             .await
+            .map_err(unique_violation::<T>)
     }
 
     async fn insert_or_update_impl<T: Model>(&self, data: &mut T, update: bool) -> Result<()> {
+        // an upsert would overwrite the row regardless of its version, so the
+        // versioned rows are updated first, and only inserted if they don't exist
+        let update = if update && T::VERSION_COLUMN.is_some() && !has_auto_primary_key(data) {
+            match self.update_impl(data).await {
+                Err(DatabaseError::RecordNotFound { .. }) => false,
+                result => return result,
+            }
+        } else {
+            update
+        };
+
+        prepare_for_insert(data, self.clock.now(), update);
+
         let column_identifiers = T::COLUMNS
             .iter()
             .map(|column| Identifier::from(column.name.as_str()));
@@ -847,7 +2346,7 @@ impl Database {
             .to_owned();
         if update && !value_identifiers.is_empty() {
             insert_statement.on_conflict(
-                OnConflict::column(T::PRIMARY_KEY_NAME)
+                OnConflict::columns(T::PRIMARY_KEY_COLUMNS.iter().copied())
                     .update_columns(value_identifiers)
                     .to_owned(),
             );
@@ -880,9 +2379,184 @@ impl Database {
         }
 
         if update {
-            trace!(primary_key = ?data.primary_key().to_db_field_value(), "Inserted or updated row");
+            trace!(primary_key = ?data.primary_key().to_db_field_values(), "Inserted or updated row");
+        } else {
+            trace!(primary_key = ?data.primary_key().to_db_field_values(), "Inserted row");
+        }
+
+        Ok(())
+    }
+
+    /// Inserts multiple rows into the database.
+    ///
+    /// The rows are inserted in batches, each using a single `INSERT`
+    /// statement, as long as the database backend can return the values of
+    /// the [`Auto`] fields (or there are no such fields). Otherwise, e.g. on
+    /// MySQL, the rows with the [`Auto`] fields are inserted one by one.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the rows could not be inserted into
+    /// the database, for instance because the migrations haven't been
+    /// applied, or there was a problem with the database connection.
+    ///
+    /// This method can return [`DatabaseError::UniqueViolation`] if a row
+    /// with the same values of the unique columns already exists.
+    pub async fn bulk_insert<T: Model>(&self, data: &mut [T]) -> Result<()> {
+        let span = span!(Level::TRACE, "bulk_insert", table = %T::TABLE_NAME, count = data.len());
+
+        Self::bulk_insert_impl(self.route::<T>(None, true)?, data, false)
+            .instrument(span)
+            .await
+            .map_err(unique_violation::<T>)
+    }
+
+    /// Inserts multiple rows into the database, or updates them if rows with
+    /// the same primary keys already exist.
+    ///
+    /// See [`Self::bulk_insert`] for the details.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the rows could not be inserted into
+    /// the database, for instance because the migrations haven't been
+    /// applied, or there was a problem with the database connection.
+    ///
+    /// This method can return [`DatabaseError::UniqueViolation`] if a row
+    /// with the same values of the unique columns already exists.
+    pub async fn bulk_insert_or_update<T: Model>(&self, data: &mut [T]) -> Result<()> {
+        let span = span!(
+            Level::TRACE,
+            "bulk_insert_or_update",
+            table = %T::TABLE_NAME,
+            count = data.len()
+        );
+
+        Self::bulk_insert_impl(self.route::<T>(None, true)?, data, true)
+            .instrument(span)
+            .await
+            .map_err(unique_violation::<T>)
+    }
+
+    async fn bulk_insert_impl<T: Model>(&self, data: &mut [T], update: bool) -> Result<()> {
+        if update && T::VERSION_COLUMN.is_some() {
+            for instance in data.iter_mut() {
+                self.insert_or_update_impl(instance, update).await?;
+            }
+            trace!(count = data.len(), "Inserted or updated rows");
+            return Ok(());
+        }
+
+        let now = self.clock.now();
+        for instance in data.iter_mut() {
+            prepare_for_insert(instance, now, update);
+        }
+
+        let value_indices: Vec<_> = (0..T::COLUMNS.len()).collect();
+        let values: Vec<Vec<DbFieldValue>> = data
+            .iter()
+            .map(|instance| {
+                instance
+                    .get_values(&value_indices)
+                    .into_iter()
+                    .map(ToDbFieldValue::to_db_field_value)
+                    .collect()
+            })
+            .collect();
+        let auto_columns = |row: &[DbFieldValue]| -> Vec<usize> {
+            row.iter()
+                .enumerate()
+                .filter(|(_, value)| matches!(value, DbFieldValue::Auto))
+                .map(|(index, _)| index)
+                .collect()
+        };
+
+        let max_rows = (self.max_bind_params() / T::COLUMNS.len().max(1)).max(1);
+        let mut start = 0;
+        while start < data.len() {
+            // a single INSERT statement must set the same columns in all the rows
+            let auto_col_ids = auto_columns(&values[start]);
+            let mut end = start + 1;
+            while end < data.len()
+                && end - start < max_rows
+                && auto_columns(&values[end]) == auto_col_ids
+            {
+                end += 1;
+            }
+
+            let value_col_count = T::COLUMNS.len() - auto_col_ids.len();
+            if value_col_count == 0 || (!auto_col_ids.is_empty() && !self.supports_returning()) {
+                for instance in &mut data[start..end] {
+                    self.insert_or_update_impl(instance, update).await?;
+                }
+            } else {
+                self.insert_batch(
+                    &mut data[start..end],
+                    values[start..end].to_vec(),
+                    &auto_col_ids,
+                    update,
+                )
+                .await?;
+            }
+
+            start = end;
+        }
+
+        trace!(count = data.len(), "Inserted rows");
+
+        Ok(())
+    }
+
+    async fn insert_batch<T: Model>(
+        &self,
+        data: &mut [T],
+        values: Vec<Vec<DbFieldValue>>,
+        auto_col_ids: &[usize],
+        update: bool,
+    ) -> Result<()> {
+        let value_identifiers: Vec<_> = T::COLUMNS
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !auto_col_ids.contains(index))
+            .map(|(_, column)| Identifier::from(column.name.as_str()))
+            .collect();
+
+        let mut insert_statement = sea_query::Query::insert();
+        insert_statement
+            .into_table(T::TABLE_NAME)
+            .columns(value_identifiers.iter().copied());
+        for row in values {
+            insert_statement.values(row.into_iter().filter_map(|value| match value {
+                DbFieldValue::Auto => None,
+                DbFieldValue::Value(value) => Some(SimpleExpr::Value(value)),
+            }))?;
+        }
+        if update {
+            insert_statement.on_conflict(
+                OnConflict::columns(T::PRIMARY_KEY_COLUMNS.iter().copied())
+                    .update_columns(value_identifiers)
+                    .to_owned(),
+            );
+        }
+
+        if auto_col_ids.is_empty() {
+            self.execute_statement(&insert_statement).await?;
         } else {
-            trace!(primary_key = ?data.primary_key().to_db_field_value(), "Inserted row");
+            let auto_col_identifiers = auto_col_ids
+                .iter()
+                .map(|&index| Identifier::from(T::COLUMNS[index].name.as_str()).into_column_ref())
+                .collect();
+            insert_statement.returning(ReturningClause::Columns(auto_col_identifiers));
+
+            let rows = self.fetch_all(&insert_statement).await?;
+            assert_eq!(
+                rows.len(),
+                data.len(),
+                "the INSERT statement should return a row for each inserted row"
+            );
+            for (instance, row) in std::iter::zip(data, rows) {
+                instance.update_from_db(row, auto_col_ids)?;
+            }
         }
 
         Ok(())
@@ -898,18 +2572,26 @@ impl Database {
     ///
     /// This method can return an error if the row with the given primary key
     /// could not be found in the database.
+    ///
+    /// This method can return [`DatabaseError::UniqueViolation`] if a row
+    /// with the same values of the unique columns already exists.
     pub async fn update<T: Model>(&self, data: &mut T) -> Result<()> {
         let span = span!(
             Level::TRACE,
             "update",
             table = %T::TABLE_NAME,
-            primary_key = ?data.primary_key().to_db_field_value(),
+            primary_key = ?data.primary_key().to_db_field_values(),
         );
 
-        Self::update_impl(self, data).instrument(span).await
+        Self::update_impl(self.route::<T>(None, true)?, data)
+            .instrument(span)
+            .await
+            .map_err(unique_violation::<T>)
     }
 
     async fn update_impl<T: Model>(&self, data: &mut T) -> Result<()> {
+        data.set_timestamps(self.clock.now(), false);
+
         let column_identifiers = T::COLUMNS
             .iter()
             .map(|column| Identifier::from(column.name.as_str()));
@@ -928,24 +2610,46 @@ impl Database {
             DbFieldValue::Auto => {
                 panic!("Auto values are not supported in update queries");
             }
+            DbFieldValue::Value(_) if Some(identifier) == T::VERSION_COLUMN => {
+                statement_values.push((identifier, sea_query::Expr::col(identifier).add(1)));
+            }
             DbFieldValue::Value(value) => {
                 statement_values.push((identifier, SimpleExpr::Value(value)));
             }
         });
 
-        let primary_key = data
+        let primary_key: Vec<_> = data
             .primary_key()
-            .to_db_field_value()
-            .expect_value("primary key cannot be auto when updating");
-        let update_statement = sea_query::Query::update()
+            .to_db_field_values()
+            .into_iter()
+            .map(|value| value.expect_value("primary key cannot be auto when updating"))
+            .collect();
+        let mut update_statement = sea_query::Query::update()
             .table(T::TABLE_NAME)
             .values(statement_values)
-            .and_where(sea_query::Expr::col(T::PRIMARY_KEY_NAME).eq(primary_key.clone()))
+            .cond_where(primary_key_condition::<T>(&primary_key))
             .to_owned();
+        let version = T::VERSION_COLUMN.zip(data.version());
+        if let Some((version_column, version)) = version {
+            update_statement.and_where(sea_query::Expr::col(version_column).eq(version));
+        }
 
         let result = self.execute_statement(&update_statement).await?;
         if result.rows_affected == RowsNum(0) {
-            return Err(DatabaseError::RecordNotFound { primary_key });
+            if let Some((_, version)) = version {
+                if self.primary_key_exists::<T>(&primary_key).await? {
+                    return Err(DatabaseError::StaleObject {
+                        primary_key: primary_key_error_value(primary_key),
+                        version,
+                    });
+                }
+            }
+            return Err(DatabaseError::RecordNotFound {
+                primary_key: primary_key_error_value(primary_key),
+            });
+        }
+        if let Some((_, version)) = version {
+            data.set_version(version + 1);
         }
 
         trace!("Updated row");
@@ -953,6 +2657,16 @@ impl Database {
         Ok(())
     }
 
+    async fn primary_key_exists<T: Model>(&self, primary_key: &[DbValue]) -> Result<bool> {
+        let query = sea_query::Query::select()
+            .expr(sea_query::Expr::value(1))
+            .from(T::TABLE_NAME)
+            .cond_where(primary_key_condition::<T>(primary_key))
+            .to_owned();
+
+        Ok(self.fetch_option(&query).await?.is_some())
+    }
+
     /// Executes the given query and returns the results converted to the model
     /// type.
     ///
@@ -966,14 +2680,18 @@ impl Database {
     ///
     /// Can return an error if the database connection is lost.
     pub async fn query<T: Model>(&self, query: &Query<T>) -> Result<Vec<T>> {
+        let db = self.route::<T>(query.database_name(), false)?;
         let columns_to_get: Vec<_> = T::COLUMNS.iter().map(|column| column.name).collect();
         let mut select = sea_query::Query::select();
         select.columns(columns_to_get).from(T::TABLE_NAME);
         query.add_filter_to_statement(&mut select);
+        query.add_search_to_statement(&mut select, db);
+        query.add_order_by_to_statement(&mut select);
+        query.add_search_rank_to_statement(&mut select, db);
         query.add_limit_to_statement(&mut select);
         query.add_offset_to_statement(&mut select);
 
-        let rows = self.fetch_all(&select).await?;
+        let rows = db.fetch_all(&select).await?;
         let result = rows.into_iter().map(T::from_db).collect::<Result<_>>()?;
 
         Ok(result)
@@ -992,13 +2710,17 @@ impl Database {
     ///
     /// Can return an error if the database connection is lost.
     pub async fn get<T: Model>(&self, query: &Query<T>) -> Result<Option<T>> {
+        let db = self.route::<T>(query.database_name(), false)?;
         let columns_to_get: Vec<_> = T::COLUMNS.iter().map(|column| column.name).collect();
         let mut select = sea_query::Query::select();
         select.columns(columns_to_get).from(T::TABLE_NAME);
         query.add_filter_to_statement(&mut select);
+        query.add_search_to_statement(&mut select, db);
+        query.add_order_by_to_statement(&mut select);
+        query.add_search_rank_to_statement(&mut select, db);
         select.limit(1);
 
-        let row = self.fetch_option(&select).await?;
+        let row = db.fetch_option(&select).await?;
 
         let result = match row {
             Some(row) => Some(T::from_db(row)?),
@@ -1019,18 +2741,31 @@ impl Database {
     ///
     /// Can return an error if the database connection is lost.
     pub async fn exists<T: Model>(&self, query: &Query<T>) -> Result<bool> {
+        let db = self.route::<T>(query.database_name(), false)?;
         let mut select = sea_query::Query::select();
         select.expr(sea_query::Expr::value(1)).from(T::TABLE_NAME);
         query.add_filter_to_statement(&mut select);
+        query.add_search_to_statement(&mut select, db);
         select.limit(1);
 
-        let rows = self.fetch_option(&select).await?;
+        let rows = db.fetch_option(&select).await?;
 
         Ok(rows.is_some())
     }
 
     /// Deletes all rows that match the given query.
     ///
+    /// If the model uses soft deletes (see [`Model::SOFT_DELETE_COLUMN`]), the
+    /// rows are only marked as deleted by setting the soft delete column to
+    /// the current time; the foreign key policies are not applied then. Use
+    /// [`Database::hard_delete`] to remove the rows for good.
+    ///
+    /// The [`ForeignKeyOnDeletePolicy`] of the foreign keys referencing the
+    /// deleted rows is normally enforced by the database. On the backends that
+    /// don't support foreign key constraints (such as MySQL with the `MyISAM`
+    /// storage engine), it is emulated by the ORM instead, based on the
+    /// foreign keys created by the migrations.
+    ///
     /// # Errors
     ///
     /// This method can return an error if the query is invalid.
@@ -1039,15 +2774,122 @@ impl Database {
     /// database (usually meaning the migrations haven't been generated or
     /// applied).
     ///
+    /// This method can return an error if the rows are referenced by a foreign
+    /// key with the [`ForeignKeyOnDeletePolicy::Restrict`] or
+    /// [`ForeignKeyOnDeletePolicy::NoAction`] policy.
+    ///
     /// Can return an error if the database connection is lost.
     pub async fn delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        let db = self.route::<T>(query.database_name(), true)?;
+        match T::SOFT_DELETE_COLUMN {
+            Some(column) => {
+                let now = db.clock.now().to_db_value();
+                let mut update = sea_query::Query::update();
+                update
+                    .table(T::TABLE_NAME)
+                    .value(column, now)
+                    // keep the original deletion time of already deleted rows
+                    .and_where(sea_query::Expr::col(column).is_null());
+                query.add_filter_to_statement(&mut update);
+                query.add_search_to_statement(&mut update, db);
+
+                db.execute_statement(&update).await
+            }
+            None => db.hard_delete_impl(query).await,
+        }
+    }
+
+    /// Permanently deletes all rows that match the given query, even if the
+    /// model uses soft deletes.
+    ///
+    /// The [`ForeignKeyOnDeletePolicy`] of the foreign keys referencing the
+    /// deleted rows is normally enforced by the database. On the backends that
+    /// don't support foreign key constraints (such as MySQL with the `MyISAM`
+    /// storage engine), it is emulated by the ORM instead, based on the
+    /// foreign keys created by the migrations.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the query is invalid.
+    ///
+    /// This method can return an error if the model doesn't exist in the
+    /// database (usually meaning the migrations haven't been generated or
+    /// applied).
+    ///
+    /// This method can return an error if the rows are referenced by a foreign
+    /// key with the [`ForeignKeyOnDeletePolicy::Restrict`] or
+    /// [`ForeignKeyOnDeletePolicy::NoAction`] policy.
+    ///
+    /// Can return an error if the database connection is lost.
+    pub async fn hard_delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        self.route::<T>(query.database_name(), true)?
+            .hard_delete_impl(query)
+            .await
+    }
+
+    async fn hard_delete_impl<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        if !self.supports_foreign_keys {
+            let relations = self
+                .foreign_key_relations
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .clone();
+            let mut condition = query.filter_condition();
+            if let Some(search_condition) = query.search_condition(self) {
+                condition = condition.add(search_condition);
+            }
+            relations::emulate_on_delete(self, &relations, T::TABLE_NAME, &condition).await?;
+        }
+
         let mut delete = sea_query::Query::delete();
         delete.from_table(T::TABLE_NAME);
         query.add_filter_to_statement(&mut delete);
+        query.add_search_to_statement(&mut delete, self);
 
         self.execute_statement(&delete).await
     }
 
+    /// Restores all soft-deleted rows that match the given query, by setting
+    /// the soft delete column (see [`Model::SOFT_DELETE_COLUMN`]) to `NULL`.
+    ///
+    /// The soft-deleted rows are matched regardless of
+    /// [`Query::with_deleted`]. If the model doesn't use soft deletes, no
+    /// statement is executed.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the query is invalid.
+    ///
+    /// This method can return an error if the model doesn't exist in the
+    /// database (usually meaning the migrations haven't been generated or
+    /// applied).
+    ///
+    /// Can return an error if the database connection is lost.
+    pub async fn restore<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        let db = self.route::<T>(query.database_name(), true)?;
+        let Some(column) = T::SOFT_DELETE_COLUMN else {
+            return Ok(StatementResult {
+                rows_affected: RowsNum(0),
+                last_inserted_row_id: None,
+            });
+        };
+
+        let mut query = query.clone();
+        query.with_deleted();
+        let mut update = sea_query::Query::update();
+        update
+            .table(T::TABLE_NAME)
+            .value(
+                column,
+                Option::<chrono::DateTime<chrono::Utc>>::None.to_db_value(),
+            )
+            .and_where(sea_query::Expr::col(column).is_not_null());
+        query.add_filter_to_statement(&mut update);
+        query.add_search_to_statement(&mut update, db);
+
+        db.execute_statement(&update).await
+    }
+
     /// Executes a raw SQL query.
     ///
     /// # Errors
@@ -1073,6 +2915,134 @@ impl Database {
         self.raw_with(query, &[]).await
     }
 
+    /// Executes a raw SQL query with parameters and converts the returned
+    /// rows to the given type.
+    ///
+    /// This can be used for the queries that can't be expressed using the
+    /// [`Query`] API. The rows can be converted to any type implementing
+    /// [`FromRow`], which can be derived for structs.
+    ///
+    /// The placeholders for the parameters depend on the database backend:
+    /// `?` for SQLite and MySQL, and `$1`, `$2`, etc. for PostgreSQL. The
+    /// query is executed in the current transaction if this is a
+    /// [transaction handle](Self::transaction).
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the query is invalid.
+    ///
+    /// This method can return an error if the returned rows are not
+    /// compatible with the given type.
+    ///
+    /// Can return an error if the database connection is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Database, FromRow};
+    ///
+    /// #[derive(Debug, PartialEq, FromRow)]
+    /// struct Item {
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// db.raw("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+    ///     .await?;
+    /// db.raw_execute(
+    ///     "INSERT INTO items (id, name) VALUES (?, ?)",
+    ///     &[&1, &"apple"],
+    /// )
+    /// .await?;
+    ///
+    /// let items: Vec<Item> = db
+    ///     .raw_query("SELECT id, name FROM items WHERE name = ?", &[&"apple"])
+    ///     .await?;
+    /// assert_eq!(
+    ///     items,
+    ///     vec![Item {
+    ///         id: 1,
+    ///         name: "apple".to_owned()
+    ///     }]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_query<T: FromRow>(
+        &self,
+        query: &str,
+        values: &[&dyn ToDbValue],
+    ) -> Result<Vec<T>> {
+        let values = Self::raw_values(values);
+
+        let rows: Vec<Row> = match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner
+                .raw_fetch_all(query, values)
+                .await?
+                .into_iter()
+                .map(Row::Sqlite)
+                .collect(),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner
+                .raw_fetch_all(query, values)
+                .await?
+                .into_iter()
+                .map(Row::Postgres)
+                .collect(),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner
+                .raw_fetch_all(query, values)
+                .await?
+                .into_iter()
+                .map(Row::MySql)
+                .collect(),
+        };
+
+        rows.iter().map(T::from_row).collect()
+    }
+
+    /// Executes a raw SQL statement with parameters, such as `INSERT`,
+    /// `UPDATE`, or `DELETE`.
+    ///
+    /// The placeholders for the parameters depend on the database backend:
+    /// `?` for SQLite and MySQL, and `$1`, `$2`, etc. for PostgreSQL. Use
+    /// [`Self::raw_query`] to execute a query returning rows.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the statement is invalid.
+    ///
+    /// Can return an error if the database connection is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// db.raw("CREATE TABLE test (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT)")
+    ///     .await?;
+    /// let result = db
+    ///     .raw_execute("INSERT INTO test (name) VALUES (?)", &[&"test"])
+    ///     .await?;
+    /// assert_eq!(result.rows_affected().0, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_execute(
+        &self,
+        query: &str,
+        values: &[&dyn ToDbValue],
+    ) -> Result<StatementResult> {
+        self.raw_with(query, values).await
+    }
+
     /// Executes a raw SQL query with parameters.
     ///
     /// # Errors
@@ -1099,13 +3069,9 @@ impl Database {
     pub async fn raw_with(
         &self,
         query: &str,
-        values: &[&(dyn ToDbValue)],
+        values: &[&dyn ToDbValue],
     ) -> Result<StatementResult> {
-        let values = values
-            .iter()
-            .map(ToDbValue::to_db_value)
-            .collect::<Vec<_>>();
-        let values = SqlxValues(sea_query::Values(values));
+        let values = Self::raw_values(values);
 
         let result = match &self.inner {
             #[cfg(feature = "sqlite")]
@@ -1119,6 +3085,210 @@ impl Database {
         Ok(result)
     }
 
+    /// Executes the given function in a database transaction.
+    ///
+    /// The function is passed a handle to the transaction, which can be used
+    /// just like a regular [`Database`]. The transaction is committed if the
+    /// function returns `Ok`, and rolled back if it returns `Err` (or if the
+    /// returned future is dropped before completion, e.g. because of a
+    /// panic).
+    ///
+    /// Transactions can be nested: calling this method on a transaction
+    /// handle creates a savepoint, which is released or rolled back to
+    /// depending on the result of the nested function, without finishing the
+    /// outer transaction.
+    ///
+    /// Any error type that can be created from a [`DatabaseError`] can be
+    /// returned by the function, so that both [`cot::Error`](crate::Error)
+    /// and [`DatabaseError`] can be used.
+    ///
+    /// To run the whole request handler in a transaction, use
+    /// [`TransactionMiddleware`](crate::middleware::TransactionMiddleware).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by the function.
+    ///
+    /// Returns an error if the transaction could not be started or
+    /// committed. An error when rolling back the transaction is only logged,
+    /// and the error returned by the function is returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Database, Model, model};
+    ///
+    /// #[model]
+    /// struct Account {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     balance: i64,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let mut from = Account::get_by_primary_key(db, 1).await?.unwrap();
+    /// let mut to = Account::get_by_primary_key(db, 2).await?.unwrap();
+    ///
+    /// db.transaction(|tx| async move {
+    ///     from.balance -= 100;
+    ///     from.save(&tx).await?;
+    ///     to.balance += 100;
+    ///     to.save(&tx).await?;
+    ///
+    ///     Ok::<_, cot::db::DatabaseError>(())
+    /// })
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transaction<F, Fut, T, E>(&self, f: F) -> std::result::Result<T, E>
+    where
+        F: FnOnce(std::sync::Arc<Database>) -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+        E: From<DatabaseError>,
+    {
+        let transaction = std::sync::Arc::new(self.begin().await?);
+
+        match f(std::sync::Arc::clone(&transaction)).await {
+            Ok(value) => {
+                transaction.commit().await?;
+                Ok(value)
+            }
+            Err(error) => {
+                if let Err(rollback_error) = transaction.rollback().await {
+                    tracing::error!(?rollback_error, "Failed to roll back the transaction");
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns whether this is a handle to a transaction, as passed to the
+    /// function given to [`Self::transaction`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// assert!(!db.is_transaction());
+    ///
+    /// db.transaction(|tx| async move {
+    ///     assert!(tx.is_transaction());
+    ///     Ok::<_, cot::db::DatabaseError>(())
+    /// })
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn is_transaction(&self) -> bool {
+        match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.is_transaction(),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.is_transaction(),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.is_transaction(),
+        }
+    }
+
+    /// Starts a new transaction, or creates a savepoint if this is already a
+    /// transaction. The returned database executes all the statements in
+    /// that transaction.
+    pub(crate) async fn begin(&self) -> Result<Self> {
+        let inner = match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => DatabaseImpl::Sqlite(inner.begin().await?),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => DatabaseImpl::Postgres(inner.begin().await?),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => DatabaseImpl::MySql(inner.begin().await?),
+        };
+
+        Ok(Self {
+            url: self.url.clone(),
+            inner,
+            supports_foreign_keys: self.supports_foreign_keys,
+            foreign_key_relations: std::sync::RwLock::new(
+                self.foreign_key_relations
+                    .read()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .clone(),
+            ),
+            clock: std::sync::Arc::clone(&self.clock),
+            // all the statements run in the transaction, so there is nothing
+            // to route
+            databases: HashMap::new(),
+            router: None,
+        })
+    }
+
+    /// Makes sure the values generated for the auto-incremented `column` of
+    /// `table` don't collide with the existing rows.
+    ///
+    /// This is needed after inserting rows with explicit values of the column,
+    /// because PostgreSQL doesn't update the sequence the values are generated
+    /// from in that case. The other backends do this automatically.
+    pub(crate) async fn reset_auto_value(
+        &self,
+        table: Identifier,
+        column: Identifier,
+    ) -> Result<()> {
+        match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(_) => {}
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(_) => {
+                let (table, column) = (table.as_str(), column.as_str());
+                self.raw(&format!(
+                    "SELECT setval(pg_get_serial_sequence('\"{table}\"', '{column}'), \
+                    COALESCE(MAX(\"{column}\"), 0) + 1, false) FROM \"{table}\""
+                ))
+                .await?;
+            }
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Commits the transaction started with [`Self::begin`].
+    pub(crate) async fn commit(&self) -> Result<()> {
+        match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.commit().await,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.commit().await,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.commit().await,
+        }
+    }
+
+    /// Rolls back the transaction started with [`Self::begin`].
+    pub(crate) async fn rollback(&self) -> Result<()> {
+        match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.rollback().await,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.rollback().await,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.rollback().await,
+        }
+    }
+
+    fn raw_values(values: &[&dyn ToDbValue]) -> SqlxValues {
+        let values = values
+            .iter()
+            .map(ToDbValue::to_db_value)
+            .collect::<Vec<_>>();
+        SqlxValues(sea_query::Values(values))
+    }
+
     async fn fetch_option<T>(&self, statement: &T) -> Result<Option<Row>>
     where
         T: SqlxBinder + Send + Sync,
@@ -1134,81 +3304,342 @@ impl Database {
             DatabaseImpl::MySql(inner) => inner.fetch_option(statement).await?.map(Row::MySql),
         };
 
-        Ok(result)
+        Ok(result)
+    }
+
+    /// Sets the foreign keys that exist in the database schema, as defined by
+    /// the migrations.
+    pub(crate) fn set_foreign_key_relations(&self, relations: Vec<ForeignKeyRelation>) {
+        *self
+            .foreign_key_relations
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = relations;
+    }
+
+    /// Returns the name of the type that numeric values should be cast to, so
+    /// that they can be consistently read as `i64` or `f64` values.
+    fn numeric_cast_type(&self, is_float: bool) -> &'static str {
+        match (&self.inner, is_float) {
+            #[cfg(feature = "sqlite")]
+            (DatabaseImpl::Sqlite(_), false) => "INTEGER",
+            #[cfg(feature = "sqlite")]
+            (DatabaseImpl::Sqlite(_), true) => "REAL",
+            #[cfg(feature = "postgres")]
+            (DatabaseImpl::Postgres(_), false) => "BIGINT",
+            #[cfg(feature = "postgres")]
+            (DatabaseImpl::Postgres(_), true) => "DOUBLE PRECISION",
+            #[cfg(feature = "mysql")]
+            (DatabaseImpl::MySql(_), false) => "SIGNED",
+            #[cfg(feature = "mysql")]
+            (DatabaseImpl::MySql(_), true) => "DOUBLE",
+        }
+    }
+
+    /// Returns whether [`Query::search`] uses the full-text search of the
+    /// database. Otherwise, it falls back to matching the words of the search
+    /// term with `LIKE`.
+    fn supports_full_text_search(&self) -> bool {
+        match self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(_) => false,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(_) => true,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(_) => false,
+        }
+    }
+
+    /// Returns the maximum number of values that can be bound to a single
+    /// statement.
+    fn max_bind_params(&self) -> usize {
+        match self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(_) => 32766,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(_) => 65535,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(_) => 65535,
+        }
+    }
+
+    fn supports_returning(&self) -> bool {
+        match self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(_) => true,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(_) => true,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(_) => false,
+        }
+    }
+
+    async fn fetch_all<T>(&self, statement: &T) -> Result<Vec<Row>>
+    where
+        T: SqlxBinder + Send + Sync,
+    {
+        let result = match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner
+                .fetch_all(statement)
+                .await?
+                .into_iter()
+                .map(Row::Sqlite)
+                .collect(),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner
+                .fetch_all(statement)
+                .await?
+                .into_iter()
+                .map(Row::Postgres)
+                .collect(),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner
+                .fetch_all(statement)
+                .await?
+                .into_iter()
+                .map(Row::MySql)
+                .collect(),
+        };
+
+        Ok(result)
+    }
+
+    async fn execute_statement<T>(&self, statement: &T) -> Result<StatementResult>
+    where
+        T: SqlxBinder + Send + Sync,
+    {
+        let result = match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.execute_statement(statement).await?,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.execute_statement(statement).await?,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.execute_statement(statement).await?,
+        };
+
+        Ok(result)
+    }
+
+    async fn execute_schema<T: SchemaStatementBuilder>(
+        &self,
+        statement: T,
+    ) -> Result<StatementResult> {
+        let result = match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.execute_schema(statement).await?,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.execute_schema(statement).await?,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.execute_schema(statement).await?,
+        };
+
+        Ok(result)
+    }
+
+    async fn alter_column(
+        &self,
+        table_name: Identifier,
+        old_field: &migrations::Field,
+        new_field: &migrations::Field,
+    ) -> Result<()> {
+        match &self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => {
+                inner.alter_column(table_name, old_field, new_field).await
+            }
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => {
+                inner.alter_column(table_name, old_field, new_field).await
+            }
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => {
+                inner.alter_column(table_name, old_field, new_field).await
+            }
+        }
+    }
+}
+
+/// The options of the database connection pool, used by
+/// [`Database::new_with_options`].
+///
+/// The options that are not set use the defaults of the underlying database
+/// driver: at most 10 connections, no minimum number of idle connections, 30
+/// seconds acquire timeout, 10 minutes idle timeout, and 30 minutes maximum
+/// lifetime of a connection.
+///
+/// In a Cot project, the options are usually set in the `[database]` section
+/// of the config (see [`DatabaseConfig`](crate::config::DatabaseConfig)).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::db::PoolOptions;
+///
+/// let options = PoolOptions::new()
+///     .max_connections(50)
+///     .min_connections(5)
+///     .idle_timeout(Duration::from_secs(300));
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PoolOptions {
+    max_connections: Option<u32>,
+    min_connections: Option<u32>,
+    acquire_timeout: Option<std::time::Duration>,
+    idle_timeout: Option<std::time::Duration>,
+    max_lifetime: Option<std::time::Duration>,
+}
+
+impl PoolOptions {
+    /// Creates new pool options with all the values set to the defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::PoolOptions;
+    ///
+    /// let options = PoolOptions::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of connections the pool can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::PoolOptions;
+    ///
+    /// let options = PoolOptions::new().max_connections(50);
+    /// ```
+    #[must_use]
+    pub fn max_connections(mut self, max_connections: u32) -> Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    /// Sets the minimum number of connections the pool keeps open, even when
+    /// they are idle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::PoolOptions;
+    ///
+    /// let options = PoolOptions::new().min_connections(5);
+    /// ```
+    #[must_use]
+    pub fn min_connections(mut self, min_connections: u32) -> Self {
+        self.min_connections = Some(min_connections);
+        self
+    }
+
+    /// Sets the maximum time to wait for a connection to become available
+    /// before returning an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::PoolOptions;
+    ///
+    /// let options = PoolOptions::new().acquire_timeout(Duration::from_secs(5));
+    /// ```
+    #[must_use]
+    pub fn acquire_timeout(mut self, acquire_timeout: std::time::Duration) -> Self {
+        self.acquire_timeout = Some(acquire_timeout);
+        self
+    }
+
+    /// Sets the time after which an idle connection is closed (unless the
+    /// pool has no more than the minimum number of connections).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::PoolOptions;
+    ///
+    /// let options = PoolOptions::new().idle_timeout(Duration::from_secs(300));
+    /// ```
+    #[must_use]
+    pub fn idle_timeout(mut self, idle_timeout: std::time::Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
     }
 
-    fn supports_returning(&self) -> bool {
-        match self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(_) => true,
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(_) => true,
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(_) => false,
-        }
+    /// Sets the maximum time a connection is kept open for, after which it is
+    /// closed and replaced with a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::PoolOptions;
+    ///
+    /// let options = PoolOptions::new().max_lifetime(Duration::from_secs(3600));
+    /// ```
+    #[must_use]
+    pub fn max_lifetime(mut self, max_lifetime: std::time::Duration) -> Self {
+        self.max_lifetime = Some(max_lifetime);
+        self
     }
+}
 
-    async fn fetch_all<T>(&self, statement: &T) -> Result<Vec<Row>>
-    where
-        T: SqlxBinder + Send + Sync,
-    {
-        let result = match &self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(inner) => inner
-                .fetch_all(statement)
-                .await?
-                .into_iter()
-                .map(Row::Sqlite)
-                .collect(),
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(inner) => inner
-                .fetch_all(statement)
-                .await?
-                .into_iter()
-                .map(Row::Postgres)
-                .collect(),
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(inner) => inner
-                .fetch_all(statement)
-                .await?
-                .into_iter()
-                .map(Row::MySql)
-                .collect(),
-        };
+/// The state of the database connection pool, as returned by
+/// [`Database::pool_status`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::Database;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::db::Result<()> {
+/// let db = Database::new("sqlite::memory:").await?;
+///
+/// let status = db.pool_status();
+/// assert!(status.size() <= status.max_connections());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PoolStatus {
+    size: u32,
+    idle: u32,
+    max_connections: u32,
+}
 
-        Ok(result)
+impl PoolStatus {
+    /// Returns the number of connections currently open, both idle and in
+    /// use.
+    #[must_use]
+    pub fn size(&self) -> u32 {
+        self.size
     }
 
-    async fn execute_statement<T>(&self, statement: &T) -> Result<StatementResult>
-    where
-        T: SqlxBinder + Send + Sync,
-    {
-        let result = match &self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(inner) => inner.execute_statement(statement).await?,
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(inner) => inner.execute_statement(statement).await?,
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(inner) => inner.execute_statement(statement).await?,
-        };
-
-        Ok(result)
+    /// Returns the number of idle connections.
+    #[must_use]
+    pub fn idle(&self) -> u32 {
+        self.idle
     }
 
-    async fn execute_schema<T: SchemaStatementBuilder>(
-        &self,
-        statement: T,
-    ) -> Result<StatementResult> {
-        let result = match &self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(inner) => inner.execute_schema(statement).await?,
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(inner) => inner.execute_schema(statement).await?,
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(inner) => inner.execute_schema(statement).await?,
-        };
+    /// Returns the number of connections currently in use.
+    #[must_use]
+    pub fn in_use(&self) -> u32 {
+        self.size - self.idle
+    }
 
-        Ok(result)
+    /// Returns the maximum number of connections the pool can hold.
+    #[must_use]
+    pub fn max_connections(&self) -> u32 {
+        self.max_connections
     }
 }
 
@@ -1225,6 +3656,104 @@ impl ColumnTypeMapper for Database {
     }
 }
 
+/// The name of the default database, i.e. the one configured with the `url`
+/// in the `[database]` section of the config.
+pub const DEFAULT_DATABASE: &str = "default";
+
+/// Decides which database the operations on a model use, when multiple
+/// databases are configured.
+///
+/// The router is set with [`Database::with_router`], or, when using the
+/// [`Bootstrapper`](crate::Bootstrapper), returned from
+/// [`Project::database_router`](crate::project::Project::database_router). Its
+/// methods return the name of one of the databases added with
+/// [`Database::with_database`] (or [`DEFAULT_DATABASE`]), or `None` to use the
+/// default database. The database given explicitly with [`Query::using`]
+/// takes precedence over the router.
+///
+/// Migrations are only applied to the default database, and the statements
+/// executed in a [transaction](Database::transaction) are never routed.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{DatabaseRouter, ModelInfo};
+///
+/// /// Sends the reads to a read replica and keeps the analytics app in a
+/// /// separate database.
+/// struct AppRouter;
+///
+/// impl DatabaseRouter for AppRouter {
+///     fn db_for_read(&self, model: &ModelInfo) -> Option<&str> {
+///         if model.app_name == "analytics" {
+///             Some("analytics")
+///         } else {
+///             Some("replica")
+///         }
+///     }
+///
+///     fn db_for_write(&self, model: &ModelInfo) -> Option<&str> {
+///         (model.app_name == "analytics").then_some("analytics")
+///     }
+/// }
+/// ```
+pub trait DatabaseRouter: Send + Sync {
+    /// Returns the name of the database to read the instances of the given
+    /// model from, or `None` to use the default database.
+    fn db_for_read(&self, model: &ModelInfo) -> Option<&str> {
+        let _ = model;
+        None
+    }
+
+    /// Returns the name of the database to write the instances of the given
+    /// model to (including updating and deleting them), or `None` to use the
+    /// default database.
+    fn db_for_write(&self, model: &ModelInfo) -> Option<&str> {
+        let _ = model;
+        None
+    }
+}
+
+/// Information about a model, passed to a [`DatabaseRouter`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ModelInfo {
+    /// The name of the app the model is defined in ([`Model::APP_NAME`]).
+    pub app_name: &'static str,
+    /// The name of the table of the model ([`Model::TABLE_NAME`]).
+    pub table_name: Identifier,
+    /// The name of the Rust type of the model, as returned by
+    /// [`std::any::type_name`].
+    pub type_name: &'static str,
+}
+
+impl ModelInfo {
+    /// Returns the information about the model `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{ModelInfo, model};
+    ///
+    /// #[model]
+    /// struct Event {
+    ///     #[model(primary_key)]
+    ///     id: i64,
+    /// }
+    ///
+    /// let info = ModelInfo::of::<Event>();
+    /// assert!(info.table_name.ends_with("event"));
+    /// ```
+    #[must_use]
+    pub fn of<T: Model>() -> Self {
+        Self {
+            app_name: T::APP_NAME,
+            table_name: T::TABLE_NAME,
+            type_name: std::any::type_name::<T>(),
+        }
+    }
+}
+
 /// A trait that provides a backend for the database.
 ///
 /// This trait is used to provide a backend for the database.
@@ -1250,6 +3779,25 @@ pub trait DatabaseBackend: Send + Sync {
     /// applied, or there was a problem with the database connection.
     async fn insert<T: Model>(&self, data: &mut T) -> Result<()>;
 
+    /// Inserts multiple rows into the database.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the rows could not be inserted into
+    /// the database, for instance because the migrations haven't been
+    /// applied, or there was a problem with the database connection.
+    async fn bulk_insert<T: Model>(&self, data: &mut [T]) -> Result<()>;
+
+    /// Inserts multiple rows into the database, or updates them if rows with
+    /// the same primary keys already exist.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the rows could not be inserted into
+    /// the database, for instance because the migrations haven't been
+    /// applied, or there was a problem with the database connection.
+    async fn bulk_insert_or_update<T: Model>(&self, data: &mut [T]) -> Result<()>;
+
     /// Updates an existing row in the database.
     ///
     /// # Errors
@@ -1311,6 +3859,33 @@ pub trait DatabaseBackend: Send + Sync {
     ///
     /// Can return an error if the database connection is lost.
     async fn delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult>;
+
+    /// Permanently deletes all rows that match the given query, even if the
+    /// model uses soft deletes.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the query is invalid.
+    ///
+    /// This method can return an error if the model doesn't exist in the
+    /// database (usually meaning the migrations haven't been generated or
+    /// applied).
+    ///
+    /// Can return an error if the database connection is lost.
+    async fn hard_delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult>;
+
+    /// Restores all soft-deleted rows that match the given query.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the query is invalid.
+    ///
+    /// This method can return an error if the model doesn't exist in the
+    /// database (usually meaning the migrations haven't been generated or
+    /// applied).
+    ///
+    /// Can return an error if the database connection is lost.
+    async fn restore<T: Model>(&self, query: &Query<T>) -> Result<StatementResult>;
 }
 
 #[async_trait]
@@ -1323,6 +3898,14 @@ impl DatabaseBackend for Database {
         Database::insert(self, data).await
     }
 
+    async fn bulk_insert<T: Model>(&self, data: &mut [T]) -> Result<()> {
+        Database::bulk_insert(self, data).await
+    }
+
+    async fn bulk_insert_or_update<T: Model>(&self, data: &mut [T]) -> Result<()> {
+        Database::bulk_insert_or_update(self, data).await
+    }
+
     async fn update<T: Model>(&self, data: &mut T) -> Result<()> {
         Database::update(self, data).await
     }
@@ -1342,6 +3925,14 @@ impl DatabaseBackend for Database {
     async fn delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
         Database::delete(self, query).await
     }
+
+    async fn hard_delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        Database::hard_delete(self, query).await
+    }
+
+    async fn restore<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        Database::restore(self, query).await
+    }
 }
 
 #[async_trait]
@@ -1354,6 +3945,14 @@ impl DatabaseBackend for std::sync::Arc<Database> {
         Database::insert(self, data).await
     }
 
+    async fn bulk_insert<T: Model>(&self, data: &mut [T]) -> Result<()> {
+        Database::bulk_insert(self, data).await
+    }
+
+    async fn bulk_insert_or_update<T: Model>(&self, data: &mut [T]) -> Result<()> {
+        Database::bulk_insert_or_update(self, data).await
+    }
+
     async fn update<T: Model>(&self, data: &mut T) -> Result<()> {
         Database::update(self, data).await
     }
@@ -1373,6 +3972,14 @@ impl DatabaseBackend for std::sync::Arc<Database> {
     async fn delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
         Database::delete(self, query).await
     }
+
+    async fn hard_delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        Database::hard_delete(self, query).await
+    }
+
+    async fn restore<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        Database::restore(self, query).await
+    }
 }
 
 /// Result of a statement execution.
@@ -1656,8 +4263,8 @@ impl<const LIMIT: u32> LimitedString<LIMIT> {
 
 #[cfg(feature = "fake")]
 impl<const LIMIT: u32> fake::Dummy<usize> for LimitedString<LIMIT> {
-    fn dummy_with_rng<R: fake::rand::Rng + ?Sized>(len: &usize, rng: &mut R) -> Self {
-        use fake::rand::Rng;
+    fn dummy_with_rng<R: rand::Rng + ?Sized>(len: &usize, rng: &mut R) -> Self {
+        use rand::Rng;
 
         assert!(
             *len <= LIMIT as usize,
@@ -1669,7 +4276,7 @@ impl<const LIMIT: u32> fake::Dummy<usize> for LimitedString<LIMIT> {
         );
 
         let str: String = rng
-            .sample_iter(&fake::rand::distr::Alphanumeric)
+            .sample_iter(&rand::distr::Alphanumeric)
             .take(*len)
             .map(char::from)
             .collect();
@@ -1679,7 +4286,7 @@ impl<const LIMIT: u32> fake::Dummy<usize> for LimitedString<LIMIT> {
 
 #[cfg(feature = "fake")]
 impl<const LIMIT: u32> fake::Dummy<fake::Faker> for LimitedString<LIMIT> {
-    fn dummy_with_rng<R: fake::rand::Rng + ?Sized>(_: &fake::Faker, rng: &mut R) -> Self {
+    fn dummy_with_rng<R: rand::Rng + ?Sized>(_: &fake::Faker, rng: &mut R) -> Self {
         use fake::Fake;
 
         let len: usize = (0..LIMIT as usize).fake_with_rng(rng);
@@ -1687,6 +4294,73 @@ impl<const LIMIT: u32> fake::Dummy<fake::Faker> for LimitedString<LIMIT> {
     }
 }
 
+/// A wrapper over a value that is stored in the database as JSON.
+///
+/// This can be used to store semi-structured data in a model without having
+/// to serialize it into a string manually. Any type that implements
+/// [`Serialize`](serde::Serialize) and
+/// [`DeserializeOwned`](serde::de::DeserializeOwned) can be wrapped, including
+/// [`serde_json::Value`] itself.
+///
+/// # Database
+///
+/// This type is represented by the `JSONB` type in PostgreSQL, by the `JSON`
+/// type in MySQL, and by the `TEXT` type in SQLite.
+///
+/// JSON columns can be filtered by containment on PostgreSQL using
+/// [`Expr::json_contains`](query::Expr::json_contains).
+///
+/// Saving a value that cannot be serialized into JSON (for instance, a map
+/// with non-string keys) panics.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Json, model};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// struct Settings {
+///     theme: String,
+///     notifications: bool,
+/// }
+///
+/// #[model]
+/// struct User {
+///     #[model(primary_key)]
+///     id: i32,
+///     settings: Json<Settings>,
+/// }
+///
+/// let user = User {
+///     id: 1,
+///     settings: Json(Settings {
+///         theme: "dark".to_string(),
+///         notifications: true,
+///     }),
+/// };
+/// assert_eq!(user.settings.theme, "dark");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deref, From)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    /// Returns the wrapped value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Json;
+    ///
+    /// let json = Json(vec![1, 2, 3]);
+    /// assert_eq!(json.into_inner(), vec![1, 2, 3]);
+    /// ```
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
 /// A type that represents a column type in the database.
 ///
 /// # Examples
@@ -1767,6 +4441,37 @@ pub enum ColumnType {
     Blob,
     /// A string column type with a maximum length.
     String(u32),
+    /// A JSON column type.
+    Json,
+    /// A UUID column type.
+    Uuid,
+}
+
+impl ColumnType {
+    /// Returns whether the column type is an integer type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::ColumnType;
+    ///
+    /// assert!(ColumnType::BigInteger.is_integer());
+    /// assert!(!ColumnType::Uuid.is_integer());
+    /// ```
+    #[must_use]
+    pub const fn is_integer(self) -> bool {
+        matches!(
+            self,
+            Self::TinyInteger
+                | Self::SmallInteger
+                | Self::Integer
+                | Self::BigInteger
+                | Self::TinyUnsignedInteger
+                | Self::SmallUnsignedInteger
+                | Self::UnsignedInteger
+                | Self::BigUnsignedInteger
+        )
+    }
 }
 
 #[cfg(test)]
@@ -1785,6 +4490,47 @@ mod tests {
         assert_eq!(column.name.as_str(), "test");
     }
 
+    #[cfg(feature = "sqlite")]
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn pool_status() {
+        let db = Database::new_with_options(
+            "sqlite::memory:",
+            &PoolOptions::new().max_connections(3).min_connections(1),
+        )
+        .await
+        .unwrap();
+
+        let status = db.pool_status();
+
+        assert_eq!(status.max_connections(), 3);
+        assert!(status.size() >= 1);
+        assert_eq!(status.in_use(), status.size() - status.idle());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn raw_query_with_values() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        db.raw("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+            .await
+            .unwrap();
+        db.raw_execute(
+            "INSERT INTO items (id, name) VALUES (?, ?), (?, ?)",
+            &[&1, &"apple", &2, &"banana"],
+        )
+        .await
+        .unwrap();
+
+        let rows: Vec<(i32, String)> = db
+            .raw_query("SELECT id, name FROM items WHERE id > ?", &[&1])
+            .await
+            .unwrap();
+
+        assert_eq!(rows, vec![(2, "banana".to_owned())]);
+    }
+
     #[test]
     fn limited_string_new_within_limit() {
         let limited_string = LimitedString::<10>::new("short");
@@ -1808,6 +4554,60 @@ mod tests {
         assert_eq!(limited_string.unwrap(), "exact");
     }
 
+    #[test]
+    fn json_to_db_value() {
+        let json = Json(serde_json::json!({"theme": "dark"}));
+        assert_eq!(
+            json.to_db_value(),
+            DbValue::Json(Some(Box::new(serde_json::json!({"theme": "dark"}))))
+        );
+        assert_eq!(
+            Option::<Json<Vec<i32>>>::None.to_db_value(),
+            DbValue::Json(None)
+        );
+        assert_eq!(<Json<Vec<i32>> as DatabaseField>::TYPE, ColumnType::Json);
+    }
+
+    #[derive(std::fmt::Debug, Copy, Clone, PartialEq, Eq, DbEnum)]
+    enum Status {
+        Draft,
+        #[db_enum(rename = "live", label = "Live now")]
+        Published,
+    }
+
+    #[derive(std::fmt::Debug, Copy, Clone, PartialEq, Eq, DbEnum)]
+    #[db_enum(repr = "integer")]
+    enum Priority {
+        Low = -1,
+        High = 7,
+    }
+
+    #[test]
+    fn db_enum_derive() {
+        assert_eq!(Status::VARIANTS, [Status::Draft, Status::Published]);
+        assert_eq!(Status::Draft.name(), "draft");
+        assert_eq!(Status::Published.name(), "live");
+        assert_eq!(Status::Draft.label(), "Draft");
+        assert_eq!(Status::Published.label(), "Live now");
+        assert_eq!(Status::from_name("live"), Some(Status::Published));
+        assert_eq!(Status::from_name("Published"), None);
+        assert_eq!(Priority::Low.discriminant(), -1);
+        assert_eq!(Priority::from_discriminant(7), Some(Priority::High));
+        assert_eq!(Priority::from_discriminant(0), None);
+    }
+
+    #[test]
+    fn db_enum_to_db_value() {
+        assert_eq!(<Status as DatabaseField>::TYPE, ColumnType::Text);
+        assert_eq!(
+            Status::Published.to_db_value(),
+            DbValue::String(Some(Box::new("live".to_owned())))
+        );
+        assert_eq!(Option::<Status>::None.to_db_value(), DbValue::String(None));
+        assert_eq!(<Priority as DatabaseField>::TYPE, ColumnType::Integer);
+        assert_eq!(Some(Priority::High).to_db_value(), DbValue::Int(Some(7)));
+    }
+
     #[test]
     fn limited_string_eq() {
         assert_eq!(LimitedString::<5>::new("test").unwrap(), "test");
@@ -1,5 +1,9 @@
 //! `DatabaseField` implementations for common types.
 
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
 #[cfg(feature = "mysql")]
 use crate::db::impl_mysql::MySqlValueRef;
 #[cfg(feature = "postgres")]
@@ -7,8 +11,9 @@ use crate::db::impl_postgres::PostgresValueRef;
 #[cfg(feature = "sqlite")]
 use crate::db::impl_sqlite::SqliteValueRef;
 use crate::db::{
-    Auto, ColumnType, DatabaseError, DatabaseField, DbFieldValue, DbValue, ForeignKey, FromDbValue,
-    LimitedString, Model, PrimaryKey, Result, SqlxValueRef, ToDbFieldValue, ToDbValue,
+    Auto, AutoNow, ColumnType, CustomDbField, DatabaseError, DatabaseField, DbEnum, DbFieldValue,
+    DbValue, ForeignKey, FromDbValue, Json, LimitedString, Model, PrimaryKey, Result, SqlxValueRef,
+    ToDbFieldValue, ToDbValue,
 };
 
 macro_rules! impl_from_sqlite_default {
@@ -132,8 +137,10 @@ impl_db_field!(f64, Double);
 impl_db_field!(chrono::NaiveDate, Date);
 impl_db_field!(chrono::NaiveTime, Time);
 impl_db_field!(chrono::NaiveDateTime, DateTime);
+impl_db_field!(chrono::DateTime<chrono::Utc>, DateTimeWithTimeZone);
 impl_db_field!(String, Text);
 impl_db_field!(Vec<u8>, Blob);
+impl_db_field!(uuid::Uuid, Uuid);
 
 impl ToDbValue for &str {
     fn to_db_value(&self) -> DbValue {
@@ -170,6 +177,30 @@ impl FromDbValue for Option<chrono::DateTime<chrono::FixedOffset>> {
 
 impl_to_db_value_default!(chrono::DateTime<chrono::FixedOffset>);
 
+impl AutoNow for chrono::DateTime<chrono::Utc> {
+    fn from_now(now: chrono::DateTime<chrono::Utc>) -> Self {
+        now
+    }
+}
+
+impl AutoNow for chrono::DateTime<chrono::FixedOffset> {
+    fn from_now(now: chrono::DateTime<chrono::Utc>) -> Self {
+        now.fixed_offset()
+    }
+}
+
+impl AutoNow for chrono::NaiveDateTime {
+    fn from_now(now: chrono::DateTime<chrono::Utc>) -> Self {
+        now.naive_utc()
+    }
+}
+
+impl<T: AutoNow> AutoNow for Option<T> {
+    fn from_now(now: chrono::DateTime<chrono::Utc>) -> Self {
+        Some(T::from_now(now))
+    }
+}
+
 impl ToDbValue for Option<&str> {
     fn to_db_value(&self) -> DbValue {
         self.map(ToString::to_string).into()
@@ -220,12 +251,181 @@ impl<const LIMIT: u32> ToDbValue for Option<LimitedString<LIMIT>> {
     }
 }
 
-impl<T: Model + Send + Sync> DatabaseField for ForeignKey<T> {
+impl<T: Serialize + DeserializeOwned + Send + Sync> DatabaseField for Json<T> {
+    const TYPE: ColumnType = ColumnType::Json;
+}
+
+impl<T: DeserializeOwned> FromDbValue for Json<T> {
+    #[cfg(feature = "sqlite")]
+    fn from_sqlite(value: SqliteValueRef<'_>) -> Result<Self> {
+        json_from_value(value.get::<serde_json::Value>()?)
+    }
+
+    #[cfg(feature = "postgres")]
+    fn from_postgres(value: PostgresValueRef<'_>) -> Result<Self> {
+        json_from_value(value.get::<serde_json::Value>()?)
+    }
+
+    #[cfg(feature = "mysql")]
+    fn from_mysql(value: MySqlValueRef<'_>) -> Result<Self> {
+        json_from_value(value.get::<serde_json::Value>()?)
+    }
+}
+
+impl<T: DeserializeOwned> FromDbValue for Option<Json<T>> {
+    #[cfg(feature = "sqlite")]
+    fn from_sqlite(value: SqliteValueRef<'_>) -> Result<Self> {
+        value
+            .get::<Option<serde_json::Value>>()?
+            .map(json_from_value)
+            .transpose()
+    }
+
+    #[cfg(feature = "postgres")]
+    fn from_postgres(value: PostgresValueRef<'_>) -> Result<Self> {
+        value
+            .get::<Option<serde_json::Value>>()?
+            .map(json_from_value)
+            .transpose()
+    }
+
+    #[cfg(feature = "mysql")]
+    fn from_mysql(value: MySqlValueRef<'_>) -> Result<Self> {
+        value
+            .get::<Option<serde_json::Value>>()?
+            .map(json_from_value)
+            .transpose()
+    }
+}
+
+fn json_from_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<Json<T>> {
+    serde_json::from_value(value)
+        .map(Json)
+        .map_err(DatabaseError::value_decode)
+}
+
+fn json_to_value<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).expect("failed to serialize the value to JSON")
+}
+
+impl<T: Serialize + Send + Sync> ToDbValue for Json<T> {
+    fn to_db_value(&self) -> DbValue {
+        DbValue::Json(Some(Box::new(json_to_value(&self.0))))
+    }
+}
+
+impl<T: Serialize + Send + Sync> ToDbValue for Option<Json<T>> {
+    fn to_db_value(&self) -> DbValue {
+        DbValue::Json(self.as_ref().map(|json| Box::new(json_to_value(&json.0))))
+    }
+}
+
+impl<T: CustomDbField> DatabaseField for T {
+    const TYPE: ColumnType = T::Repr::TYPE;
+}
+
+impl<T: CustomDbField> FromDbValue for T {
+    #[cfg(feature = "sqlite")]
+    fn from_sqlite(value: SqliteValueRef<'_>) -> Result<Self> {
+        T::Repr::from_sqlite(value).and_then(T::from_repr)
+    }
+
+    #[cfg(feature = "postgres")]
+    fn from_postgres(value: PostgresValueRef<'_>) -> Result<Self> {
+        T::Repr::from_postgres(value).and_then(T::from_repr)
+    }
+
+    #[cfg(feature = "mysql")]
+    fn from_mysql(value: MySqlValueRef<'_>) -> Result<Self> {
+        T::Repr::from_mysql(value).and_then(T::from_repr)
+    }
+}
+
+impl<T: CustomDbField> FromDbValue for Option<T>
+where
+    Option<T::Repr>: FromDbValue,
+{
+    #[cfg(feature = "sqlite")]
+    fn from_sqlite(value: SqliteValueRef<'_>) -> Result<Self> {
+        Option::<T::Repr>::from_sqlite(value)?
+            .map(T::from_repr)
+            .transpose()
+    }
+
+    #[cfg(feature = "postgres")]
+    fn from_postgres(value: PostgresValueRef<'_>) -> Result<Self> {
+        Option::<T::Repr>::from_postgres(value)?
+            .map(T::from_repr)
+            .transpose()
+    }
+
+    #[cfg(feature = "mysql")]
+    fn from_mysql(value: MySqlValueRef<'_>) -> Result<Self> {
+        Option::<T::Repr>::from_mysql(value)?
+            .map(T::from_repr)
+            .transpose()
+    }
+}
+
+impl<T: CustomDbField> ToDbValue for Option<T>
+where
+    Option<T::Repr>: ToDbValue,
+{
+    fn to_db_value(&self) -> DbValue {
+        self.as_ref().map(T::to_repr).to_db_value()
+    }
+}
+
+/// An error returned when a value loaded from the database doesn't match any
+/// of the variants of a [`DbEnum`].
+#[derive(Debug, Error)]
+#[error("`{value}` is not a valid value of `{enum_name}`")]
+struct InvalidDbEnumValue {
+    enum_name: &'static str,
+    value: String,
+}
+
+impl InvalidDbEnumValue {
+    fn error<T: DbEnum>(value: &impl ToString) -> DatabaseError {
+        DatabaseError::value_decode(Self {
+            enum_name: std::any::type_name::<T>(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Converts the name of a [`DbEnum`] variant loaded from the database to the
+/// variant.
+///
+/// This is used by the [`DbEnum`](derive@crate::db::DbEnum) derive macro to
+/// implement [`CustomDbField`] for the enums stored as strings.
+#[doc(hidden)]
+pub fn db_enum_from_name<T: DbEnum>(name: &str) -> Result<T> {
+    T::from_name(name).ok_or_else(|| InvalidDbEnumValue::error::<T>(&name))
+}
+
+/// Converts the discriminant of a [`DbEnum`] variant loaded from the database
+/// to the variant.
+///
+/// This is used by the [`DbEnum`](derive@crate::db::DbEnum) derive macro to
+/// implement [`CustomDbField`] for the enums stored as integers.
+#[doc(hidden)]
+pub fn db_enum_from_discriminant<T: DbEnum>(discriminant: i32) -> Result<T> {
+    T::from_discriminant(discriminant).ok_or_else(|| InvalidDbEnumValue::error::<T>(&discriminant))
+}
+
+impl<T: Model + Send + Sync> DatabaseField for ForeignKey<T>
+where
+    T::PrimaryKey: PrimaryKey,
+{
     const NULLABLE: bool = T::PrimaryKey::NULLABLE;
     const TYPE: ColumnType = T::PrimaryKey::TYPE;
 }
 
-impl<T: Model + Send + Sync> FromDbValue for ForeignKey<T> {
+impl<T: Model + Send + Sync> FromDbValue for ForeignKey<T>
+where
+    T::PrimaryKey: PrimaryKey,
+{
     #[cfg(feature = "sqlite")]
     fn from_sqlite(value: SqliteValueRef<'_>) -> Result<Self> {
         T::PrimaryKey::from_sqlite(value).map(ForeignKey::PrimaryKey)
@@ -242,7 +442,10 @@ impl<T: Model + Send + Sync> FromDbValue for ForeignKey<T> {
     }
 }
 
-impl<T: Model + Send + Sync> ToDbFieldValue for ForeignKey<T> {
+impl<T: Model + Send + Sync> ToDbFieldValue for ForeignKey<T>
+where
+    T::PrimaryKey: PrimaryKey,
+{
     fn to_db_field_value(&self) -> DbFieldValue {
         self.primary_key().to_db_field_value()
     }
@@ -250,6 +453,7 @@ impl<T: Model + Send + Sync> ToDbFieldValue for ForeignKey<T> {
 
 impl<T: Model + Send + Sync> FromDbValue for Option<ForeignKey<T>>
 where
+    T::PrimaryKey: PrimaryKey,
     Option<T::PrimaryKey>: FromDbValue,
 {
     #[cfg(feature = "sqlite")]
@@ -270,6 +474,7 @@ where
 
 impl<T: Model + Send + Sync> ToDbFieldValue for Option<ForeignKey<T>>
 where
+    T::PrimaryKey: PrimaryKey,
     Option<T::PrimaryKey>: ToDbFieldValue,
 {
     fn to_db_field_value(&self) -> DbFieldValue {
@@ -361,8 +566,24 @@ where
     }
 }
 
-impl<T: PrimaryKey> PrimaryKey for Auto<T> {}
+impl<T: PrimaryKey> PrimaryKey for Auto<T> {
+    fn generate() -> Option<Self> {
+        T::generate().map(Self::fixed)
+    }
+}
 
 impl PrimaryKey for i32 {}
 
 impl PrimaryKey for i64 {}
+
+impl PrimaryKey for String {}
+
+impl<const LIMIT: u32> PrimaryKey for LimitedString<LIMIT> {}
+
+impl PrimaryKey for uuid::Uuid {
+    fn generate() -> Option<Self> {
+        Some(Self::new_v4())
+    }
+}
+
+impl<T: Model + Clone + Send + Sync> PrimaryKey for ForeignKey<T> where T::PrimaryKey: PrimaryKey {}
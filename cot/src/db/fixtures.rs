@@ -0,0 +1,1005 @@
+//! Dumping the data stored in the database to fixtures and loading it back.
+//!
+//! A fixture is a list of rows, each of them holding the name of the table it
+//! belongs to and the values of its columns. Fixtures can be stored as JSON,
+//! or as YAML if the `yaml` feature is enabled, which makes them handy for
+//! setting up a known database state in tests, or for moving data between
+//! databases. The project's CLI provides the `dump-data` and `load-data`
+//! commands for that, and [`TestDatabase`](crate::test::TestDatabase) has
+//! [`load_fixture`](crate::test::TestDatabase::load_fixture) to load a fixture
+//! in tests.
+//!
+//! The types of the columns, as well as the foreign keys between the tables,
+//! are taken from the migrations (see [`FixtureSchema`]), so no additional
+//! code is needed in the models. The rows are loaded so that the rows they
+//! reference through a foreign key are loaded first, regardless of their
+//! order in the fixture.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::db::fixtures::{Fixture, FixtureFormat};
+//!
+//! let fixture = Fixture::from_format(
+//!     r#"[
+//!         {"table": "blog__author", "fields": {"id": 1, "name": "Alice"}},
+//!         {"table": "blog__post", "fields": {"id": 1, "author": 1, "title": "Hello"}}
+//!     ]"#,
+//!     FixtureFormat::Json,
+//! )?;
+//! assert_eq!(fixture.records().len(), 2);
+//! # Ok::<(), cot::db::DatabaseError>(())
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::db::migrations::{Field, MigrationEngine, MigrationTable};
+use crate::db::relations::ForeignKeyRelation;
+use crate::db::{ColumnType, Database, DbValue, Identifier, Json, Result, Row, ToDbValue};
+
+/// An error that occurred while dumping or loading a fixture.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum FixtureError {
+    /// The fixture could not be parsed.
+    #[error("Could not parse the {format} fixture: {message}")]
+    Parse {
+        /// The format of the fixture.
+        format: FixtureFormat,
+        /// The error message of the parser.
+        message: String,
+    },
+    /// The fixture could not be serialized.
+    #[error("Could not serialize the fixture to {format}: {message}")]
+    Serialize {
+        /// The format of the fixture.
+        format: FixtureFormat,
+        /// The error message of the serializer.
+        message: String,
+    },
+    /// The fixture file could not be read or written.
+    #[error("Could not access the fixture file `{}`: {message}", path.display())]
+    Io {
+        /// The path of the fixture file.
+        path: PathBuf,
+        /// The error message.
+        message: String,
+    },
+    /// The format of the fixture file could not be determined from its
+    /// extension.
+    #[error("Unknown format of the fixture file `{}`", path.display())]
+    UnknownFormat {
+        /// The path of the fixture file.
+        path: PathBuf,
+    },
+    /// The table doesn't exist in the schema defined by the migrations.
+    #[error("Table `{table}` not found in the migrations")]
+    UnknownTable {
+        /// The name of the table.
+        table: String,
+    },
+    /// The column doesn't exist in the table.
+    #[error("Column `{column}` not found in table `{table}`")]
+    UnknownColumn {
+        /// The name of the table.
+        table: String,
+        /// The name of the column.
+        column: String,
+    },
+    /// The value of a column could not be converted.
+    #[error("Invalid value of column `{table}.{column}`: {message}")]
+    InvalidValue {
+        /// The name of the table.
+        table: String,
+        /// The name of the column.
+        column: String,
+        /// The reason the value is invalid.
+        message: String,
+    },
+}
+
+/// The format a fixture is stored in.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+///
+/// use cot::db::fixtures::FixtureFormat;
+///
+/// assert_eq!(
+///     FixtureFormat::from_path(Path::new("users.json")),
+///     Some(FixtureFormat::Json)
+/// );
+/// assert_eq!("json".parse(), Ok(FixtureFormat::Json));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FixtureFormat {
+    /// JSON.
+    Json,
+    /// YAML. Requires the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl FixtureFormat {
+    /// Returns the format of the fixture file with the given path, based on
+    /// its extension, or `None` if the extension is not known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use cot::db::fixtures::FixtureFormat;
+    ///
+    /// assert_eq!(
+    ///     FixtureFormat::from_path(Path::new("fixtures/posts.json")),
+    ///     Some(FixtureFormat::Json)
+    /// );
+    /// assert_eq!(FixtureFormat::from_path(Path::new("posts.txt")), None);
+    /// ```
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+
+    /// Returns the name of the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::FixtureFormat;
+    ///
+    /// assert_eq!(FixtureFormat::Json.as_str(), "json");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            #[cfg(feature = "yaml")]
+            Self::Yaml => "yaml",
+        }
+    }
+}
+
+impl Display for FixtureFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FixtureFormat {
+    type Err = UnknownFixtureFormat;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(UnknownFixtureFormat(s.to_owned())),
+        }
+    }
+}
+
+/// An error returned when parsing a [`FixtureFormat`] from an unknown name.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("unknown fixture format `{0}`")]
+pub struct UnknownFixtureFormat(String);
+
+/// The tables that fixtures can be dumped from and loaded to, along with the
+/// types of their columns and the foreign keys between them.
+///
+/// The schema is defined by the migrations, which means that the database is
+/// expected to be fully migrated when a fixture is dumped or loaded.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::fixtures::FixtureSchema;
+/// use cot::db::migrations::{Field, Migration, MigrationDependency, MigrationEngine, Operation};
+/// use cot::db::{DatabaseField, Identifier};
+///
+/// struct CreateAuthor;
+///
+/// impl Migration for CreateAuthor {
+///     const APP_NAME: &'static str = "blog";
+///     const MIGRATION_NAME: &'static str = "m_0001_initial";
+///     const DEPENDENCIES: &'static [MigrationDependency] = &[];
+///     const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+///         .table_name(Identifier::new("blog__author"))
+///         .fields(&[
+///             Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+///                 .primary_key()
+///                 .auto(),
+///             Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+///         ])
+///         .build()];
+/// }
+///
+/// # fn main() -> cot::Result<()> {
+/// let schema = FixtureSchema::new(&MigrationEngine::new([CreateAuthor])?);
+/// assert_eq!(schema.table_names().collect::<Vec<_>>(), ["blog__author"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FixtureSchema {
+    tables: Vec<MigrationTable>,
+    relations: Vec<ForeignKeyRelation>,
+}
+
+impl FixtureSchema {
+    /// Creates the schema defined by the migrations of the given migration
+    /// engine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::FixtureSchema;
+    /// use cot::db::migrations::{MigrationEngine, SyncDynMigration};
+    ///
+    /// # fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new(Vec::<Box<SyncDynMigration>>::new())?;
+    /// let schema = FixtureSchema::new(&engine);
+    /// assert_eq!(schema.table_names().count(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn new(engine: &MigrationEngine) -> Self {
+        let mut schema = Self::default();
+        schema.add_migrations(engine);
+        schema
+    }
+
+    /// Adds the tables defined by the migrations of the given migration engine
+    /// to the schema, replacing the existing tables with the same names.
+    pub(crate) fn add_migrations(&mut self, engine: &MigrationEngine) {
+        for table in engine.tables() {
+            self.tables.retain(|existing| existing.name != table.name);
+            self.relations
+                .retain(|relation| relation.table != table.name);
+            self.tables.push(table);
+        }
+        self.relations
+            .extend(engine.foreign_key_relations(|_| true));
+    }
+
+    /// Returns the names of the tables in the schema, ordered so that each
+    /// table comes after the tables it references through foreign keys
+    /// (unless the foreign keys form a cycle).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::FixtureSchema;
+    ///
+    /// let schema = FixtureSchema::default();
+    /// assert_eq!(schema.table_names().count(), 0);
+    /// ```
+    pub fn table_names(&self) -> impl Iterator<Item = &str> {
+        self.sorted_tables()
+            .into_iter()
+            .map(|table| table.name.as_str())
+    }
+
+    fn table(&self, name: &str) -> std::result::Result<&MigrationTable, FixtureError> {
+        self.tables
+            .iter()
+            .find(|table| table.name.as_str() == name)
+            .ok_or_else(|| FixtureError::UnknownTable {
+                table: name.to_owned(),
+            })
+    }
+
+    /// Sorts the tables topologically by the foreign keys between them,
+    /// keeping the order they were created in otherwise.
+    fn sorted_tables(&self) -> Vec<&MigrationTable> {
+        let mut remaining: Vec<&MigrationTable> = self.tables.iter().collect();
+        let mut sorted = Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let index = remaining
+                .iter()
+                .position(|table| {
+                    !self.relations.iter().any(|relation| {
+                        relation.table == table.name
+                            && relation.to_table != table.name
+                            && remaining
+                                .iter()
+                                .any(|other| other.name == relation.to_table)
+                    })
+                })
+                // a cycle; fall back to the order the tables were created in
+                .unwrap_or(0);
+            sorted.push(remaining.remove(index));
+        }
+
+        sorted
+    }
+
+    /// Returns the foreign keys of the table that reference the table itself.
+    fn self_references(&self, table: Identifier) -> Vec<&ForeignKeyRelation> {
+        self.relations
+            .iter()
+            .filter(|relation| relation.table == table && relation.to_table == table)
+            .collect()
+    }
+}
+
+/// A single row in a [`Fixture`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::fixtures::FixtureRecord;
+///
+/// let record = FixtureRecord::new(
+///     "blog__author",
+///     serde_json::json!({"id": 1, "name": "Alice"})
+///         .as_object()
+///         .unwrap()
+///         .clone(),
+/// );
+/// assert_eq!(record.table, "blog__author");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct FixtureRecord {
+    /// The name of the table the row belongs to.
+    pub table: String,
+    /// The values of the columns of the row. The columns that are missing
+    /// are set to their default values when the row is loaded.
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl FixtureRecord {
+    /// Creates a new fixture record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::FixtureRecord;
+    ///
+    /// let record = FixtureRecord::new("blog__author", serde_json::Map::new());
+    /// assert!(record.fields.is_empty());
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(
+        table: T,
+        fields: serde_json::Map<String, serde_json::Value>,
+    ) -> Self {
+        Self {
+            table: table.into(),
+            fields,
+        }
+    }
+}
+
+/// A list of rows that can be dumped from the database and loaded back.
+///
+/// See the [module documentation](self) for more information.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::fixtures::{Fixture, FixtureFormat, FixtureRecord};
+///
+/// let fixture = Fixture::new(vec![FixtureRecord::new(
+///     "blog__author",
+///     serde_json::json!({"id": 1, "name": "Alice"})
+///         .as_object()
+///         .unwrap()
+///         .clone(),
+/// )]);
+///
+/// let json = fixture.to_format(FixtureFormat::Json)?;
+/// assert_eq!(Fixture::from_format(&json, FixtureFormat::Json)?, fixture);
+/// # Ok::<(), cot::db::DatabaseError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Fixture {
+    records: Vec<FixtureRecord>,
+}
+
+impl Fixture {
+    /// Creates a new fixture with the given records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::Fixture;
+    ///
+    /// let fixture = Fixture::new(vec![]);
+    /// assert!(fixture.records().is_empty());
+    /// ```
+    #[must_use]
+    pub fn new(records: Vec<FixtureRecord>) -> Self {
+        Self { records }
+    }
+
+    /// Returns the records of the fixture.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::Fixture;
+    ///
+    /// let fixture = Fixture::default();
+    /// assert!(fixture.records().is_empty());
+    /// ```
+    #[must_use]
+    pub fn records(&self) -> &[FixtureRecord] {
+        &self.records
+    }
+
+    /// Parses a fixture stored in the given format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a valid fixture.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::{Fixture, FixtureFormat};
+    ///
+    /// let fixture = Fixture::from_format("[]", FixtureFormat::Json)?;
+    /// assert!(fixture.records().is_empty());
+    /// # Ok::<(), cot::db::DatabaseError>(())
+    /// ```
+    pub fn from_format(input: &str, format: FixtureFormat) -> Result<Self> {
+        let parse_error = |message: String| FixtureError::Parse { format, message };
+
+        let fixture = match format {
+            FixtureFormat::Json => {
+                serde_json::from_str(input).map_err(|error| parse_error(error.to_string()))?
+            }
+            #[cfg(feature = "yaml")]
+            FixtureFormat::Yaml => {
+                serde_yaml::from_str(input).map_err(|error| parse_error(error.to_string()))?
+            }
+        };
+        Ok(fixture)
+    }
+
+    /// Serializes the fixture to the given format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fixture could not be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::{Fixture, FixtureFormat};
+    ///
+    /// assert_eq!(Fixture::default().to_format(FixtureFormat::Json)?, "[]");
+    /// # Ok::<(), cot::db::DatabaseError>(())
+    /// ```
+    pub fn to_format(&self, format: FixtureFormat) -> Result<String> {
+        let serialize_error = |message: String| FixtureError::Serialize { format, message };
+
+        let output = match format {
+            FixtureFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|error| serialize_error(error.to_string()))?,
+            #[cfg(feature = "yaml")]
+            FixtureFormat::Yaml => {
+                serde_yaml::to_string(self).map_err(|error| serialize_error(error.to_string()))?
+            }
+        };
+        Ok(output)
+    }
+
+    /// Reads a fixture from a file. The format of the fixture is determined by
+    /// the extension of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be read, if its format is not
+    /// known, or if it doesn't contain a valid fixture.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::db::fixtures::Fixture;
+    ///
+    /// let fixture = Fixture::read("fixtures/users.json")?;
+    /// # Ok::<(), cot::db::DatabaseError>(())
+    /// ```
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let format = FixtureFormat::from_path(path).ok_or_else(|| FixtureError::UnknownFormat {
+            path: path.to_owned(),
+        })?;
+        let input = std::fs::read_to_string(path).map_err(|error| FixtureError::Io {
+            path: path.to_owned(),
+            message: error.to_string(),
+        })?;
+
+        Self::from_format(&input, format)
+    }
+
+    /// Dumps the rows of the given tables to a fixture. If `tables` is empty,
+    /// all the tables in the schema are dumped.
+    ///
+    /// The tables are dumped in the order returned by
+    /// [`FixtureSchema::table_names`], and the rows of each table are ordered
+    /// by the primary key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the tables doesn't exist in the schema.
+    ///
+    /// Returns an error if there is an error while interacting with the
+    /// database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::fixtures::{Fixture, FixtureSchema};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// let fixture = Fixture::dump(&database, &FixtureSchema::default(), &[]).await?;
+    /// assert!(fixture.records().is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn dump(
+        database: &Database,
+        schema: &FixtureSchema,
+        tables: &[&str],
+    ) -> Result<Self> {
+        for table in tables {
+            schema.table(table)?;
+        }
+
+        let mut records = Vec::new();
+        for table in schema.sorted_tables() {
+            if tables.is_empty() || tables.contains(&table.name.as_str()) {
+                records.extend(dump_table(database, table).await?);
+            }
+        }
+
+        Ok(Self { records })
+    }
+
+    /// Loads the rows of the fixture into the database, returning the number
+    /// of rows loaded.
+    ///
+    /// The rows that already exist in the database (i.e. the rows with the
+    /// same primary key) are updated. The rows are loaded in a single
+    /// transaction, so either all of them are loaded, or none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the tables or columns doesn't exist in the
+    /// schema, or if one of the values is not valid for its column.
+    ///
+    /// Returns an error if there is an error while interacting with the
+    /// database, e.g. if a row references a row that doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::fixtures::{Fixture, FixtureSchema};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// let loaded = Fixture::default()
+    ///     .load(&database, &FixtureSchema::default())
+    ///     .await?;
+    /// assert_eq!(loaded, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn load(&self, database: &Database, schema: &FixtureSchema) -> Result<usize> {
+        for record in &self.records {
+            schema.table(&record.table)?;
+        }
+
+        let mut statements = Vec::with_capacity(self.records.len());
+        let mut auto_columns = Vec::new();
+        for table in schema.sorted_tables() {
+            let records = self.ordered_records(schema, table);
+            if !records.is_empty() {
+                auto_columns.extend(
+                    table
+                        .fields
+                        .iter()
+                        .filter(|field| field.auto_value)
+                        .map(|field| (table.name, field.name)),
+                );
+            }
+            for record in records {
+                statements.push(insert_statement(table, record)?);
+            }
+        }
+
+        database
+            .transaction(|transaction| async move {
+                for statement in &statements {
+                    transaction.execute_statement(statement).await?;
+                }
+                for (table, column) in auto_columns {
+                    transaction.reset_auto_value(table, column).await?;
+                }
+                Ok::<_, crate::db::DatabaseError>(())
+            })
+            .await?;
+
+        Ok(self.records.len())
+    }
+
+    /// Returns the records of the given table, ordered so that the rows
+    /// referencing other rows of the same table come after them.
+    fn ordered_records<'a>(
+        &'a self,
+        schema: &FixtureSchema,
+        table: &MigrationTable,
+    ) -> Vec<&'a FixtureRecord> {
+        let mut remaining: Vec<&FixtureRecord> = self
+            .records
+            .iter()
+            .filter(|record| record.table == table.name.as_str())
+            .collect();
+        let self_references = schema.self_references(table.name);
+        if self_references.is_empty() {
+            return remaining;
+        }
+
+        let references_remaining = |record: &FixtureRecord, remaining: &[&FixtureRecord]| {
+            self_references.iter().any(|relation| {
+                let value = record.fields.get(relation.column.as_str());
+                value.is_some_and(|value| {
+                    !value.is_null()
+                        && remaining.iter().any(|other| {
+                            !std::ptr::eq(*other, record)
+                                && other.fields.get(relation.to_column.as_str()) == Some(value)
+                        })
+                })
+            })
+        };
+
+        let mut ordered = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let index = remaining
+                .iter()
+                .position(|record| !references_remaining(record, &remaining))
+                // a cycle; fall back to the order in the fixture
+                .unwrap_or(0);
+            ordered.push(remaining.remove(index));
+        }
+        ordered
+    }
+}
+
+async fn dump_table(database: &Database, table: &MigrationTable) -> Result<Vec<FixtureRecord>> {
+    let mut statement = sea_query::Query::select();
+    statement
+        .from(table.name)
+        .columns(table.fields.iter().map(|field| field.name));
+    for field in table.fields.iter().filter(|field| field.primary_key) {
+        statement.order_by(field.name, sea_query::Order::Asc);
+    }
+
+    let rows = database.fetch_all(&statement).await?;
+    rows.iter()
+        .map(|row| {
+            let mut fields = serde_json::Map::new();
+            for (index, field) in table.fields.iter().enumerate() {
+                let value =
+                    value_to_json(read_value(row, index, field.ty)?).map_err(|message| {
+                        invalid_value(table.name.as_str(), field.name.as_str(), message)
+                    })?;
+                fields.insert(field.name.as_str().to_owned(), value);
+            }
+            Ok(FixtureRecord::new(table.name.as_str(), fields))
+        })
+        .collect()
+}
+
+fn insert_statement(
+    table: &MigrationTable,
+    record: &FixtureRecord,
+) -> Result<sea_query::InsertStatement> {
+    let mut columns = Vec::with_capacity(record.fields.len());
+    let mut values = Vec::with_capacity(record.fields.len());
+    for (column, value) in &record.fields {
+        let field = find_field(table, column).ok_or_else(|| FixtureError::UnknownColumn {
+            table: record.table.clone(),
+            column: column.clone(),
+        })?;
+        let value = value_from_json(field.ty, value)
+            .map_err(|message| invalid_value(&record.table, column, message))?;
+        columns.push(field.name);
+        values.push(sea_query::SimpleExpr::Value(value));
+    }
+
+    let primary_key: Vec<_> = table
+        .fields
+        .iter()
+        .filter(|field| field.primary_key)
+        .map(|field| field.name)
+        .collect();
+    let update_columns: Vec<_> = columns
+        .iter()
+        .copied()
+        .filter(|column| !primary_key.contains(column))
+        .collect();
+
+    let mut statement = sea_query::Query::insert();
+    statement
+        .into_table(table.name)
+        .columns(columns)
+        .values(values)
+        .expect("the number of columns and values is the same")
+        .or_default_values();
+    if !primary_key.is_empty() {
+        let mut on_conflict = sea_query::OnConflict::columns(primary_key);
+        if update_columns.is_empty() {
+            on_conflict.do_nothing();
+        } else {
+            on_conflict.update_columns(update_columns);
+        }
+        statement.on_conflict(on_conflict);
+    }
+    Ok(statement)
+}
+
+fn find_field<'a>(table: &'a MigrationTable, column: &str) -> Option<&'a Field> {
+    table
+        .fields
+        .iter()
+        .find(|field| field.name.as_str() == column)
+}
+
+fn invalid_value(table: &str, column: &str, message: String) -> FixtureError {
+    FixtureError::InvalidValue {
+        table: table.to_owned(),
+        column: column.to_owned(),
+        message,
+    }
+}
+
+/// Reads the value of the column with the given type from the row.
+fn read_value(row: &Row, index: usize, column_type: ColumnType) -> Result<DbValue> {
+    let value = match column_type {
+        ColumnType::Boolean => row.get::<Option<bool>>(index)?.into(),
+        ColumnType::TinyInteger => row.get::<Option<i8>>(index)?.into(),
+        ColumnType::SmallInteger => row.get::<Option<i16>>(index)?.into(),
+        ColumnType::Integer => row.get::<Option<i32>>(index)?.into(),
+        ColumnType::BigInteger => row.get::<Option<i64>>(index)?.into(),
+        ColumnType::TinyUnsignedInteger => row.get::<Option<u8>>(index)?.into(),
+        ColumnType::SmallUnsignedInteger => row.get::<Option<u16>>(index)?.into(),
+        ColumnType::UnsignedInteger => row.get::<Option<u32>>(index)?.into(),
+        ColumnType::BigUnsignedInteger => row.get::<Option<u64>>(index)?.into(),
+        ColumnType::Float => row.get::<Option<f32>>(index)?.into(),
+        ColumnType::Double => row.get::<Option<f64>>(index)?.into(),
+        ColumnType::Time => row.get::<Option<chrono::NaiveTime>>(index)?.into(),
+        ColumnType::Date => row.get::<Option<chrono::NaiveDate>>(index)?.into(),
+        ColumnType::DateTime => row.get::<Option<chrono::NaiveDateTime>>(index)?.into(),
+        ColumnType::DateTimeWithTimeZone => row
+            .get::<Option<chrono::DateTime<chrono::FixedOffset>>>(index)?
+            .into(),
+        ColumnType::Text | ColumnType::String(_) => row.get::<Option<String>>(index)?.into(),
+        ColumnType::Blob => row.get::<Option<Vec<u8>>>(index)?.into(),
+        ColumnType::Json => row
+            .get::<Option<Json<serde_json::Value>>>(index)?
+            .to_db_value(),
+        ColumnType::Uuid => row.get::<Option<uuid::Uuid>>(index)?.into(),
+    };
+    Ok(value)
+}
+
+/// Converts a database value to its representation in a fixture.
+fn value_to_json(value: DbValue) -> std::result::Result<serde_json::Value, String> {
+    fn to_json<T: Serialize>(value: Option<T>) -> serde_json::Value {
+        serde_json::to_value(value).expect("serializing a primitive value cannot fail")
+    }
+    fn to_json_string<T: ToString>(value: Option<T>) -> serde_json::Value {
+        to_json(value.map(|value| value.to_string()))
+    }
+
+    let value = match value {
+        DbValue::Bool(value) => to_json(value),
+        DbValue::TinyInt(value) => to_json(value),
+        DbValue::SmallInt(value) => to_json(value),
+        DbValue::Int(value) => to_json(value),
+        DbValue::BigInt(value) => to_json(value),
+        DbValue::TinyUnsigned(value) => to_json(value),
+        DbValue::SmallUnsigned(value) => to_json(value),
+        DbValue::Unsigned(value) => to_json(value),
+        DbValue::BigUnsigned(value) => to_json(value),
+        DbValue::Float(value) => to_json(value),
+        DbValue::Double(value) => to_json(value),
+        DbValue::String(value) => to_json(value),
+        DbValue::Bytes(value) => to_json(value.map(|value| STANDARD.encode(*value))),
+        DbValue::Json(value) => value.map_or(serde_json::Value::Null, |value| *value),
+        DbValue::ChronoTime(value) => to_json_string(value),
+        DbValue::ChronoDate(value) => to_json_string(value),
+        DbValue::ChronoDateTime(value) => {
+            to_json(value.map(|value| value.format("%Y-%m-%dT%H:%M:%S%.f").to_string()))
+        }
+        DbValue::ChronoDateTimeWithTimeZone(value) => {
+            to_json(value.map(|value| value.to_rfc3339()))
+        }
+        DbValue::Uuid(value) => to_json_string(value),
+        value => return Err(format!("unsupported value: {value:?}")),
+    };
+    Ok(value)
+}
+
+/// Converts the representation of a value in a fixture to a database value of
+/// the given column type.
+fn value_from_json(
+    column_type: ColumnType,
+    value: &serde_json::Value,
+) -> std::result::Result<DbValue, String> {
+    fn parse<T: DeserializeOwned>(
+        value: &serde_json::Value,
+    ) -> std::result::Result<Option<T>, String> {
+        Option::<T>::deserialize(value).map_err(|error| error.to_string())
+    }
+    fn parse_str<T: FromStr>(value: &serde_json::Value) -> std::result::Result<Option<T>, String>
+    where
+        T::Err: Display,
+    {
+        parse::<String>(value)?
+            .map(|value| value.parse().map_err(|error: T::Err| error.to_string()))
+            .transpose()
+    }
+
+    let value = match column_type {
+        ColumnType::Boolean => parse::<bool>(value)?.into(),
+        ColumnType::TinyInteger => parse::<i8>(value)?.into(),
+        ColumnType::SmallInteger => parse::<i16>(value)?.into(),
+        ColumnType::Integer => parse::<i32>(value)?.into(),
+        ColumnType::BigInteger => parse::<i64>(value)?.into(),
+        ColumnType::TinyUnsignedInteger => parse::<u8>(value)?.into(),
+        ColumnType::SmallUnsignedInteger => parse::<u16>(value)?.into(),
+        ColumnType::UnsignedInteger => parse::<u32>(value)?.into(),
+        ColumnType::BigUnsignedInteger => parse::<u64>(value)?.into(),
+        ColumnType::Float => parse::<f32>(value)?.into(),
+        ColumnType::Double => parse::<f64>(value)?.into(),
+        ColumnType::Time => parse_str::<chrono::NaiveTime>(value)?.into(),
+        ColumnType::Date => parse_str::<chrono::NaiveDate>(value)?.into(),
+        ColumnType::DateTime => parse_str::<chrono::NaiveDateTime>(value)?.into(),
+        ColumnType::DateTimeWithTimeZone => {
+            parse_str::<chrono::DateTime<chrono::FixedOffset>>(value)?.into()
+        }
+        ColumnType::Text | ColumnType::String(_) => parse::<String>(value)?.into(),
+        ColumnType::Blob => parse::<String>(value)?
+            .map(|value| STANDARD.decode(value).map_err(|error| error.to_string()))
+            .transpose()?
+            .into(),
+        ColumnType::Json => (!value.is_null()).then(|| value.clone()).into(),
+        ColumnType::Uuid => parse_str::<uuid::Uuid>(value)?.into(),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn fixture_format_from_path() {
+        assert_eq!(
+            FixtureFormat::from_path(Path::new("data.JSON")),
+            Some(FixtureFormat::Json)
+        );
+        assert_eq!(FixtureFormat::from_path(Path::new("data")), None);
+        #[cfg(feature = "yaml")]
+        assert_eq!(
+            FixtureFormat::from_path(Path::new("data.yml")),
+            Some(FixtureFormat::Yaml)
+        );
+    }
+
+    #[test]
+    fn fixture_from_format_invalid() {
+        let result = Fixture::from_format(r#"{"table": "x"}"#, FixtureFormat::Json);
+
+        assert!(matches!(
+            result,
+            Err(crate::db::DatabaseError::FixtureError(
+                FixtureError::Parse { .. }
+            ))
+        ));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn fixture_yaml_roundtrip() {
+        let fixture = Fixture::from_format(
+            "- table: app__item\n  fields:\n    id: 1\n    name: Item\n",
+            FixtureFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(
+            fixture.records(),
+            [FixtureRecord::new(
+                "app__item",
+                json!({"id": 1, "name": "Item"})
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            )]
+        );
+
+        let yaml = fixture.to_format(FixtureFormat::Yaml).unwrap();
+        assert_eq!(
+            Fixture::from_format(&yaml, FixtureFormat::Yaml).unwrap(),
+            fixture
+        );
+    }
+
+    #[test]
+    fn value_json_roundtrip() {
+        let cases = [
+            (ColumnType::Boolean, json!(true)),
+            (ColumnType::TinyInteger, json!(-5)),
+            (ColumnType::BigUnsignedInteger, json!(u64::MAX)),
+            (ColumnType::Double, json!(1.5)),
+            (ColumnType::Time, json!("12:34:56")),
+            (ColumnType::Date, json!("2024-02-29")),
+            (ColumnType::DateTime, json!("2024-02-29T12:34:56.789")),
+            (
+                ColumnType::DateTimeWithTimeZone,
+                json!("2024-02-29T12:34:56+02:00"),
+            ),
+            (ColumnType::String(10), json!("text")),
+            (ColumnType::Blob, json!("AQID")),
+            (ColumnType::Json, json!({"key": [1, 2]})),
+            (
+                ColumnType::Uuid,
+                json!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            ),
+            (ColumnType::Integer, json!(null)),
+        ];
+
+        for (column_type, value) in cases {
+            let db_value = value_from_json(column_type, &value).unwrap();
+            assert_eq!(value_to_json(db_value).unwrap(), value, "{column_type:?}");
+        }
+    }
+
+    #[test]
+    fn value_from_json_typed_null() {
+        assert_eq!(
+            value_from_json(ColumnType::Text, &json!(null)).unwrap(),
+            DbValue::String(None)
+        );
+        assert_eq!(
+            value_from_json(ColumnType::BigInteger, &json!(null)).unwrap(),
+            DbValue::BigInt(None)
+        );
+    }
+
+    #[test]
+    fn value_from_json_invalid() {
+        assert!(value_from_json(ColumnType::TinyInteger, &json!(1000)).is_err());
+        assert!(value_from_json(ColumnType::Integer, &json!("1")).is_err());
+        assert!(value_from_json(ColumnType::Date, &json!("yesterday")).is_err());
+        assert!(value_from_json(ColumnType::Blob, &json!("not base64!")).is_err());
+    }
+}
@@ -1,7 +1,12 @@
 //! Database interface implementation – MySQL backend.
 
-use crate::db::ColumnType;
+use sea_query::{ColumnDef, Table};
+use sea_query_binder::SqlxValues;
+
+use crate::db::introspection::{ColumnSchema, TableSchema, column_type_from_sql, group_tables};
+use crate::db::migrations::Field;
 use crate::db::sea_query_db::impl_sea_query_db_backend;
+use crate::db::{ColumnType, Identifier};
 
 impl_sea_query_db_backend!(DatabaseMySql: sqlx::mysql::MySql, sqlx::mysql::MySqlPool, MySqlRow, MySqlValueRef, sea_query::MysqlQueryBuilder);
 
@@ -11,7 +16,71 @@ impl DatabaseMySql {
         Ok(())
     }
 
-    fn prepare_values(_values: &mut sea_query_binder::SqlxValues) {
+    /// Returns whether the foreign key constraints are enforced, which depends
+    /// on the storage engine used for the tables created by the migrations.
+    pub(super) async fn supports_foreign_keys(&self) -> crate::db::Result<bool> {
+        let engine: String = sqlx::query_scalar("SELECT @@default_storage_engine")
+            .fetch_one(&self.db_connection)
+            .await?;
+        Ok(matches!(
+            engine.to_ascii_lowercase().as_str(),
+            "innodb" | "ndbcluster"
+        ))
+    }
+
+    /// Returns the schema of all the tables in the current database.
+    pub(super) async fn introspect(&self) -> crate::db::Result<Vec<TableSchema>> {
+        // the comparisons return integers in MySQL, so they are converted to booleans
+        // in Rust
+        let columns: Vec<(String, String, String, i64, i64, i64)> = sqlx::query_as(
+            "SELECT CAST(c.TABLE_NAME AS CHAR), CAST(c.COLUMN_NAME AS CHAR), \
+                CAST(c.COLUMN_TYPE AS CHAR), c.IS_NULLABLE = 'YES', c.COLUMN_KEY = 'PRI', \
+                c.EXTRA LIKE '%auto_increment%' \
+            FROM information_schema.COLUMNS c \
+            JOIN information_schema.TABLES t \
+                ON t.TABLE_SCHEMA = c.TABLE_SCHEMA AND t.TABLE_NAME = c.TABLE_NAME \
+            WHERE c.TABLE_SCHEMA = DATABASE() AND t.TABLE_TYPE = 'BASE TABLE' \
+            ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION",
+        )
+        .fetch_all(&self.db_connection)
+        .await?;
+        let index_columns: Vec<(String, String, i64, String)> = sqlx::query_as(
+            "SELECT CAST(TABLE_NAME AS CHAR), CAST(INDEX_NAME AS CHAR), NON_UNIQUE = 0, \
+                CAST(COLUMN_NAME AS CHAR) \
+            FROM information_schema.STATISTICS \
+            WHERE TABLE_SCHEMA = DATABASE() AND INDEX_NAME <> 'PRIMARY' \
+                AND COLUMN_NAME IS NOT NULL \
+            ORDER BY TABLE_NAME, INDEX_NAME, SEQ_IN_INDEX",
+        )
+        .fetch_all(&self.db_connection)
+        .await?;
+
+        let columns = columns
+            .into_iter()
+            .map(
+                |(table_name, name, sql_type, nullable, primary_key, auto_increment)| {
+                    let column = ColumnSchema {
+                        name,
+                        column_type: column_type_from_sql(&sql_type),
+                        sql_type,
+                        nullable: nullable != 0,
+                        primary_key: primary_key != 0,
+                        auto_increment: auto_increment != 0,
+                    };
+                    (table_name, column)
+                },
+            )
+            .collect();
+        let index_columns = index_columns
+            .into_iter()
+            .map(|(table_name, index_name, unique, column_name)| {
+                (table_name, index_name, unique != 0, column_name)
+            })
+            .collect();
+        Ok(group_tables(columns, index_columns))
+    }
+
+    fn prepare_values(_values: &mut SqlxValues) {
         // No changes are needed for MySQL
     }
 
@@ -20,6 +89,45 @@ impl DatabaseMySql {
         Some(result.last_insert_id())
     }
 
+    pub(super) async fn alter_column(
+        &self,
+        table_name: Identifier,
+        old_field: &Field,
+        new_field: &Field,
+    ) -> crate::db::Result<()> {
+        let mut column =
+            ColumnDef::new_with_type(new_field.name, self.sea_query_column_type_for(new_field.ty));
+        if new_field.null {
+            column.null();
+        } else {
+            column.not_null();
+        }
+        if new_field.auto_value {
+            column.auto_increment();
+        }
+        let query = Table::alter()
+            .table(table_name)
+            .modify_column(column)
+            .to_owned();
+        self.execute_schema(query).await?;
+
+        let sql = if new_field.unique && !old_field.unique {
+            format!(
+                "ALTER TABLE `{table_name}` ADD UNIQUE (`{}`)",
+                new_field.name
+            )
+        } else if old_field.unique && !new_field.unique {
+            // the name MySQL gives to the indexes created with `UNIQUE`
+            format!("ALTER TABLE `{table_name}` DROP INDEX `{}`", old_field.name)
+        } else {
+            return Ok(());
+        };
+        self.raw_with(&sql, SqlxValues(sea_query::Values(Vec::new())))
+            .await?;
+
+        Ok(())
+    }
+
     #[expect(clippy::unused_self)] // to have a unified interface between database impls
     pub(super) fn sea_query_column_type_for(
         &self,
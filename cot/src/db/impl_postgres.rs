@@ -1,5 +1,11 @@
 //! Database interface implementation – PostgreSQL backend.
 
+use sea_query::{ColumnDef, Table};
+use sea_query_binder::SqlxValues;
+
+use crate::db::Identifier;
+use crate::db::introspection::{ColumnSchema, TableSchema, column_type_from_sql, group_tables};
+use crate::db::migrations::Field;
 use crate::db::sea_query_db::impl_sea_query_db_backend;
 
 impl_sea_query_db_backend!(DatabasePostgres: sqlx::postgres::Postgres, sqlx::postgres::PgPool, PostgresRow, PostgresValueRef, sea_query::PostgresQueryBuilder);
@@ -10,7 +16,71 @@ impl DatabasePostgres {
         Ok(())
     }
 
-    fn prepare_values(values: &mut sea_query_binder::SqlxValues) {
+    #[expect(clippy::unused_async)] // to have a unified interface between database impls
+    pub(super) async fn supports_foreign_keys(&self) -> crate::db::Result<bool> {
+        Ok(true)
+    }
+
+    /// Returns the schema of all the tables in the current schema of the
+    /// database.
+    pub(super) async fn introspect(&self) -> crate::db::Result<Vec<TableSchema>> {
+        let columns: Vec<(String, String, String, bool, bool, bool)> = sqlx::query_as(
+            "SELECT c.table_name::text, c.column_name::text, \
+                CASE WHEN c.character_maximum_length IS NULL THEN c.data_type::text \
+                    ELSE c.data_type || '(' || c.character_maximum_length || ')' END, \
+                c.is_nullable = 'YES', \
+                EXISTS ( \
+                    SELECT 1 FROM information_schema.table_constraints tc \
+                    JOIN information_schema.key_column_usage k \
+                        ON k.constraint_schema = tc.constraint_schema \
+                        AND k.constraint_name = tc.constraint_name \
+                    WHERE tc.constraint_type = 'PRIMARY KEY' \
+                        AND tc.table_schema = c.table_schema AND tc.table_name = c.table_name \
+                        AND k.column_name = c.column_name \
+                ), \
+                c.is_identity = 'YES' OR COALESCE(c.column_default LIKE 'nextval(%', false) \
+            FROM information_schema.columns c \
+            JOIN information_schema.tables t \
+                ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+            WHERE c.table_schema = current_schema() AND t.table_type = 'BASE TABLE' \
+            ORDER BY c.table_name, c.ordinal_position",
+        )
+        .fetch_all(&self.db_connection)
+        .await?;
+        let index_columns: Vec<(String, String, bool, String)> = sqlx::query_as(
+            "SELECT t.relname::text, i.relname::text, ix.indisunique, a.attname::text \
+            FROM pg_index ix \
+            JOIN pg_class t ON t.oid = ix.indrelid \
+            JOIN pg_class i ON i.oid = ix.indexrelid \
+            JOIN pg_namespace n ON n.oid = t.relnamespace \
+            CROSS JOIN LATERAL unnest(ix.indkey::int2[]) WITH ORDINALITY AS k(attnum, position) \
+            JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = k.attnum \
+            WHERE n.nspname = current_schema() AND NOT ix.indisprimary \
+            ORDER BY t.relname, i.relname, k.position",
+        )
+        .fetch_all(&self.db_connection)
+        .await?;
+
+        let columns = columns
+            .into_iter()
+            .map(
+                |(table_name, name, sql_type, nullable, primary_key, auto_increment)| {
+                    let column = ColumnSchema {
+                        name,
+                        column_type: column_type_from_sql(&sql_type),
+                        sql_type,
+                        nullable,
+                        primary_key,
+                        auto_increment,
+                    };
+                    (table_name, column)
+                },
+            )
+            .collect();
+        Ok(group_tables(columns, index_columns))
+    }
+
+    fn prepare_values(values: &mut SqlxValues) {
         for value in &mut values.0.0 {
             Self::tinyint_to_smallint(value);
             Self::unsigned_to_signed(value);
@@ -45,11 +115,51 @@ impl DatabasePostgres {
         None
     }
 
+    pub(super) async fn alter_column(
+        &self,
+        table_name: Identifier,
+        old_field: &Field,
+        new_field: &Field,
+    ) -> crate::db::Result<()> {
+        let mut column =
+            ColumnDef::new_with_type(new_field.name, self.sea_query_column_type_for(new_field.ty));
+        if new_field.null {
+            column.null();
+        } else {
+            column.not_null();
+        }
+        let query = Table::alter()
+            .table(table_name)
+            .modify_column(column)
+            .to_owned();
+        self.execute_schema(query).await?;
+
+        if new_field.unique && !old_field.unique {
+            let query = Table::alter()
+                .table(table_name)
+                .modify_column(ColumnDef::new(new_field.name).unique_key())
+                .to_owned();
+            self.execute_schema(query).await?;
+        } else if old_field.unique && !new_field.unique {
+            // the name PostgreSQL gives to the constraints created with `UNIQUE`
+            let constraint_name = format!("{table_name}_{}_key", old_field.name);
+            let sql = format!(r#"ALTER TABLE "{table_name}" DROP CONSTRAINT "{constraint_name}""#);
+            self.raw_with(&sql, SqlxValues(sea_query::Values(Vec::new())))
+                .await?;
+        }
+
+        Ok(())
+    }
+
     #[expect(clippy::unused_self)] // to have a unified interface between database impls
     pub(super) fn sea_query_column_type_for(
         &self,
         column_type: crate::db::ColumnType,
     ) -> sea_query::ColumnType {
+        if column_type == crate::db::ColumnType::Json {
+            return sea_query::ColumnType::JsonBinary;
+        }
+
         sea_query::ColumnType::from(column_type)
     }
 }
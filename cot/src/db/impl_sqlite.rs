@@ -1,7 +1,12 @@
 //! Database interface implementation – SQLite backend.
 
+use sea_query::TableBuilder;
 use sea_query_binder::SqlxValues;
+use sqlx::Connection;
 
+use crate::db::Identifier;
+use crate::db::introspection::{ColumnSchema, TableSchema, column_type_from_sql, group_tables};
+use crate::db::migrations::{ColumnTypeMapper, Field, MigrationEngineError};
 use crate::db::sea_query_db::impl_sea_query_db_backend;
 
 impl_sea_query_db_backend!(DatabaseSqlite: sqlx::sqlite::Sqlite, sqlx::sqlite::SqlitePool, SqliteRow, SqliteValueRef, sea_query::SqliteQueryBuilder);
@@ -12,6 +17,70 @@ impl DatabaseSqlite {
         Ok(())
     }
 
+    /// Returns whether the foreign key constraints are enforced. This is not
+    /// the case if SQLite has been compiled without the foreign key support.
+    pub(super) async fn supports_foreign_keys(&self) -> crate::db::Result<bool> {
+        let enabled: Option<i64> = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_optional(&self.db_connection)
+            .await?;
+        Ok(enabled == Some(1))
+    }
+
+    /// Returns the schema of all the tables in the database.
+    pub(super) async fn introspect(&self) -> crate::db::Result<Vec<TableSchema>> {
+        let columns: Vec<(String, String, String, bool, i64)> = sqlx::query_as(
+            "SELECT m.name, c.name, c.type, c.\"notnull\", c.pk \
+            FROM sqlite_master m JOIN pragma_table_info(m.name) c \
+            WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' \
+            ORDER BY m.name, c.cid",
+        )
+        .fetch_all(&self.db_connection)
+        .await?;
+        let index_columns: Vec<(String, String, bool, String)> = sqlx::query_as(
+            "SELECT m.name, l.name, l.\"unique\", i.name \
+            FROM sqlite_master m \
+            JOIN pragma_index_list(m.name) l \
+            JOIN pragma_index_info(l.name) i \
+            WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' AND l.origin <> 'pk' \
+            ORDER BY m.name, l.name, i.seqno",
+        )
+        .fetch_all(&self.db_connection)
+        .await?;
+
+        let columns = columns
+            .into_iter()
+            .map(|(table_name, name, sql_type, not_null, primary_key)| {
+                // REAL is an 8-byte floating point number in SQLite
+                let column_type = if sql_type.eq_ignore_ascii_case("real") {
+                    Some(crate::db::ColumnType::Double)
+                } else {
+                    column_type_from_sql(&sql_type)
+                };
+                let column = ColumnSchema {
+                    name,
+                    sql_type,
+                    column_type,
+                    nullable: !not_null,
+                    primary_key: primary_key > 0,
+                    auto_increment: false,
+                };
+                (table_name, column)
+            })
+            .collect();
+        let mut tables = group_tables(columns, index_columns);
+
+        // a single-column `INTEGER PRIMARY KEY` is an alias for the `ROWID`, which
+        // is assigned automatically
+        for table in &mut tables {
+            let mut primary_key = table.columns.iter_mut().filter(|column| column.primary_key);
+            if let (Some(column), None) = (primary_key.next(), primary_key.next()) {
+                column.auto_increment = column.sql_type.eq_ignore_ascii_case("integer");
+            }
+        }
+
+        Ok(tables)
+    }
+
     async fn raw(&self, sql: &str) -> crate::db::Result<crate::db::StatementResult> {
         self.raw_with(sql, SqlxValues(sea_query::Values(Vec::new())))
             .await
@@ -27,11 +96,202 @@ impl DatabaseSqlite {
         Some(result.last_insert_rowid() as u64)
     }
 
+    /// Changes the definition of a column.
+    ///
+    /// SQLite doesn't support modifying the columns, so the table is rebuilt
+    /// with the new column definition, as described in
+    /// <https://www.sqlite.org/lang_altertable.html#otheralter>.
+    pub(super) async fn alter_column(
+        &self,
+        table_name: Identifier,
+        old_field: &Field,
+        new_field: &Field,
+    ) -> crate::db::Result<()> {
+        let mut column_sql = String::new();
+        sea_query::SqliteQueryBuilder
+            .prepare_column_def(&new_field.as_column_def(self), &mut column_sql);
+
+        let mut connection = self.db_connection.acquire().await?;
+        let create_table_sql: String =
+            sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(table_name.as_str())
+                .fetch_one(&mut *connection)
+                .await?;
+        let new_table_name = format!("__cot_new_{table_name}");
+        let new_create_table_sql = replace_column_definition(
+            &create_table_sql,
+            &new_table_name,
+            old_field.name.as_str(),
+            &column_sql,
+        )
+        .ok_or_else(|| MigrationEngineError::ColumnNotFound {
+            table: table_name.to_string(),
+            column: old_field.name.to_string(),
+        })?;
+
+        let statements = [
+            new_create_table_sql,
+            format!(r#"INSERT INTO "{new_table_name}" SELECT * FROM "{table_name}""#),
+            format!(r#"DROP TABLE "{table_name}""#),
+            format!(r#"ALTER TABLE "{new_table_name}" RENAME TO "{table_name}""#),
+        ];
+
+        // foreign keys have to be disabled, so that dropping the old table doesn't
+        // fail nor cascade to the rows referencing it
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *connection)
+            .await?;
+        let result = Self::execute_in_transaction(&mut connection, &statements).await;
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *connection)
+            .await?;
+
+        result
+    }
+
+    async fn execute_in_transaction(
+        connection: &mut sqlx::SqliteConnection,
+        statements: &[String],
+    ) -> crate::db::Result<()> {
+        let mut transaction = connection.begin().await?;
+        for sql in statements {
+            tracing::debug!("Schema modification: {}", sql);
+            sqlx::query(sql).execute(&mut *transaction).await?;
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
     #[expect(clippy::unused_self)] // to have a unified interface between database impls
     pub(super) fn sea_query_column_type_for(
         &self,
         column_type: crate::db::ColumnType,
     ) -> sea_query::ColumnType {
+        match column_type {
+            // SQLite has no dedicated JSON type; its JSON functions operate on text
+            crate::db::ColumnType::Json => return sea_query::ColumnType::Text,
+            // UUIDs are stored as 16-byte blobs, the same way SQLx encodes them
+            crate::db::ColumnType::Uuid => return sea_query::ColumnType::Blob,
+            _ => {}
+        }
+
         sea_query::ColumnType::from(column_type)
     }
 }
+
+impl ColumnTypeMapper for DatabaseSqlite {
+    fn sea_query_column_type_for(
+        &self,
+        column_type: crate::db::ColumnType,
+    ) -> sea_query::ColumnType {
+        Self::sea_query_column_type_for(self, column_type)
+    }
+}
+
+/// Returns the `CREATE TABLE` statement for a table named `new_table_name`
+/// with the definition of `column_name` replaced by `column_sql`, or `None` if
+/// the column is not found.
+fn replace_column_definition(
+    create_table_sql: &str,
+    new_table_name: &str,
+    column_name: &str,
+    column_sql: &str,
+) -> Option<String> {
+    let start = create_table_sql.find('(')?;
+    let end = create_table_sql.rfind(')')?;
+    let quoted_column_name = format!(r#""{column_name}""#);
+
+    let mut found = false;
+    let definitions: Vec<&str> = split_definitions(&create_table_sql[start + 1..end])
+        .into_iter()
+        .map(|definition| {
+            let is_column = definition
+                .strip_prefix(&quoted_column_name)
+                .is_some_and(|rest| rest.starts_with(char::is_whitespace));
+            if is_column {
+                found = true;
+                column_sql
+            } else {
+                definition
+            }
+        })
+        .collect();
+
+    found.then(|| {
+        format!(
+            r#"CREATE TABLE "{new_table_name}" ( {} )"#,
+            definitions.join(", ")
+        )
+    })
+}
+
+/// Splits the body of a `CREATE TABLE` statement into the column and
+/// constraint definitions.
+fn split_definitions(body: &str) -> Vec<&str> {
+    let mut definitions = Vec::new();
+    let mut depth = 0_usize;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (index, char) in body.char_indices() {
+        match (quote, char) {
+            (Some(quote_char), _) if char == quote_char => quote = None,
+            (None, '"' | '\'' | '`') => quote = Some(char),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                definitions.push(body[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    definitions.push(body[start..].trim());
+
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_column_definition_replaces_column() {
+        let sql = r#"CREATE TABLE "app__item" ( "id" integer NOT NULL PRIMARY KEY AUTOINCREMENT, "name" text NOT NULL, "owner" integer NOT NULL, FOREIGN KEY ("owner") REFERENCES "app__user" ("id") ON DELETE RESTRICT )"#;
+
+        let new_sql =
+            replace_column_definition(sql, "__cot_new_app__item", "name", r#""name" text NULL"#);
+
+        assert_eq!(
+            new_sql.as_deref(),
+            Some(
+                r#"CREATE TABLE "__cot_new_app__item" ( "id" integer NOT NULL PRIMARY KEY AUTOINCREMENT, "name" text NULL, "owner" integer NOT NULL, FOREIGN KEY ("owner") REFERENCES "app__user" ("id") ON DELETE RESTRICT )"#
+            )
+        );
+    }
+
+    #[test]
+    fn replace_column_definition_missing_column() {
+        let sql = r#"CREATE TABLE "app__item" ( "id" integer NOT NULL, "name_2" text NOT NULL )"#;
+
+        assert_eq!(
+            replace_column_definition(sql, "new", "name", r#""name" text"#),
+            None
+        );
+    }
+
+    #[test]
+    fn split_definitions_nested() {
+        assert_eq!(
+            split_definitions(
+                r#" "a" decimal(10, 2), "b,c" text DEFAULT 'x, y', CHECK ("a" > 0) "#
+            ),
+            vec![
+                r#""a" decimal(10, 2)"#,
+                r#""b,c" text DEFAULT 'x, y'"#,
+                r#"CHECK ("a" > 0)"#
+            ]
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! Reading the schema of an existing database.
+//!
+//! This is mainly useful for generating the models for the tables of an
+//! existing application, for instance when migrating it to Cot. The Cot CLI
+//! does that with the `cot inspect-db` command, which uses
+//! [`Database::introspect`](crate::db::Database::introspect) under the hood.
+
+use crate::db::ColumnType;
+
+/// The schema of a single database table, as returned by
+/// [`Database::introspect`](crate::db::Database::introspect).
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::Database;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::db::Result<()> {
+/// let db = Database::new("sqlite::memory:").await?;
+/// db.raw("CREATE TABLE books (id INTEGER PRIMARY KEY, title VARCHAR(100) NOT NULL)")
+///     .await?;
+///
+/// let tables = db.introspect().await?;
+/// assert_eq!(tables[0].name, "books");
+/// assert_eq!(tables[0].columns.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TableSchema {
+    /// The name of the table.
+    pub name: String,
+    /// The columns of the table, in the order they are defined in.
+    pub columns: Vec<ColumnSchema>,
+    /// The indexes of the table, excluding the one backing the primary key.
+    pub indexes: Vec<IndexSchema>,
+}
+
+impl TableSchema {
+    /// Creates a new table schema with the given columns and no indexes.
+    ///
+    /// Typically, you shouldn't need to use this directly; the table schemas
+    /// are returned by
+    /// [`Database::introspect`](crate::db::Database::introspect).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::ColumnType;
+    /// use cot::db::introspection::{ColumnSchema, TableSchema};
+    ///
+    /// let table = TableSchema::new(
+    ///     "books",
+    ///     vec![ColumnSchema::new("id", "integer", Some(ColumnType::Integer)).primary_key()],
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new<N: Into<String>>(name: N, columns: Vec<ColumnSchema>) -> Self {
+        Self {
+            name: name.into(),
+            columns,
+            indexes: Vec::new(),
+        }
+    }
+
+    /// Adds an index to the table schema.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::ColumnType;
+    /// use cot::db::introspection::{ColumnSchema, IndexSchema, TableSchema};
+    ///
+    /// let table = TableSchema::new(
+    ///     "books",
+    ///     vec![ColumnSchema::new("isbn", "text", Some(ColumnType::Text))],
+    /// )
+    /// .index(IndexSchema::new("books_isbn_key", ["isbn"]).unique());
+    /// ```
+    #[must_use]
+    pub fn index(mut self, index: IndexSchema) -> Self {
+        self.indexes.push(index);
+        self
+    }
+
+    /// Returns the columns the primary key of the table consists of.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::db::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// db.raw("CREATE TABLE books (id INTEGER PRIMARY KEY, title TEXT NOT NULL)")
+    ///     .await?;
+    ///
+    /// let tables = db.introspect().await?;
+    /// let primary_key: Vec<_> = tables[0].primary_key().map(|column| &column.name).collect();
+    /// assert_eq!(primary_key, ["id"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn primary_key(&self) -> impl Iterator<Item = &ColumnSchema> {
+        self.columns.iter().filter(|column| column.primary_key)
+    }
+}
+
+/// The schema of a single column of a database table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ColumnSchema {
+    /// The name of the column.
+    pub name: String,
+    /// The type of the column, as reported by the database (e.g.
+    /// `varchar(255)`).
+    pub sql_type: String,
+    /// The Cot column type corresponding to [`Self::sql_type`], or `None` if
+    /// the type has no Cot equivalent.
+    pub column_type: Option<ColumnType>,
+    /// Whether the column can contain `NULL` values.
+    pub nullable: bool,
+    /// Whether the column is a part of the primary key of the table.
+    pub primary_key: bool,
+    /// Whether the values of the column are generated by the database, e.g.
+    /// with `AUTO_INCREMENT`, or a `SERIAL` column in PostgreSQL.
+    pub auto_increment: bool,
+}
+
+impl ColumnSchema {
+    /// Creates a new schema of a non-nullable column that is not a part of the
+    /// primary key.
+    ///
+    /// Typically, you shouldn't need to use this directly; the column schemas
+    /// are returned by
+    /// [`Database::introspect`](crate::db::Database::introspect).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::ColumnType;
+    /// use cot::db::introspection::ColumnSchema;
+    ///
+    /// let column = ColumnSchema::new("title", "varchar(100)", Some(ColumnType::String(100)));
+    /// ```
+    #[must_use]
+    pub fn new<N: Into<String>, T: Into<String>>(
+        name: N,
+        sql_type: T,
+        column_type: Option<ColumnType>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            sql_type: sql_type.into(),
+            column_type,
+            nullable: false,
+            primary_key: false,
+            auto_increment: false,
+        }
+    }
+
+    /// Marks the column as nullable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::ColumnType;
+    /// use cot::db::introspection::ColumnSchema;
+    ///
+    /// let column = ColumnSchema::new("bio", "text", Some(ColumnType::Text)).nullable();
+    /// ```
+    #[must_use]
+    pub fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+
+    /// Marks the column as a part of the primary key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::ColumnType;
+    /// use cot::db::introspection::ColumnSchema;
+    ///
+    /// let column = ColumnSchema::new("id", "integer", Some(ColumnType::Integer)).primary_key();
+    /// ```
+    #[must_use]
+    pub fn primary_key(mut self) -> Self {
+        self.primary_key = true;
+        self
+    }
+
+    /// Marks the column as generated by the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::ColumnType;
+    /// use cot::db::introspection::ColumnSchema;
+    ///
+    /// let column = ColumnSchema::new("id", "integer", Some(ColumnType::Integer))
+    ///     .primary_key()
+    ///     .auto_increment();
+    /// ```
+    #[must_use]
+    pub fn auto_increment(mut self) -> Self {
+        self.auto_increment = true;
+        self
+    }
+}
+
+/// The schema of an index of a database table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IndexSchema {
+    /// The name of the index.
+    pub name: String,
+    /// The names of the indexed columns, in the order they appear in the
+    /// index.
+    pub columns: Vec<String>,
+    /// Whether the index is a unique constraint.
+    pub unique: bool,
+}
+
+impl IndexSchema {
+    /// Creates a new schema of a non-unique index on the given columns.
+    ///
+    /// Typically, you shouldn't need to use this directly; the index schemas
+    /// are returned by
+    /// [`Database::introspect`](crate::db::Database::introspect).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::introspection::IndexSchema;
+    ///
+    /// let index = IndexSchema::new("books_author_title", ["author", "title"]);
+    /// ```
+    #[must_use]
+    pub fn new<N, I>(name: N, columns: I) -> Self
+    where
+        N: Into<String>,
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            columns: columns.into_iter().map(Into::into).collect(),
+            unique: false,
+        }
+    }
+
+    /// Marks the index as a unique constraint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::introspection::IndexSchema;
+    ///
+    /// let index = IndexSchema::new("books_isbn_key", ["isbn"]).unique();
+    /// ```
+    #[must_use]
+    pub fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+}
+
+/// Returns the Cot column type corresponding to the SQL type name, as
+/// reported by any of the supported databases, or `None` if there is no
+/// such type.
+pub(crate) fn column_type_from_sql(sql_type: &str) -> Option<ColumnType> {
+    let sql_type = sql_type.trim().to_ascii_lowercase();
+    let (name, unsigned) = match sql_type
+        .strip_suffix(" unsigned")
+        .or_else(|| sql_type.strip_suffix("_unsigned"))
+    {
+        Some(name) => (name.trim(), true),
+        None => (sql_type.as_str(), false),
+    };
+    let (name, length) = match name.split_once('(') {
+        Some((name, rest)) => (
+            name.trim(),
+            rest.strip_suffix(')')
+                .and_then(|length| length.trim().parse::<u32>().ok()),
+        ),
+        None => (name, None),
+    };
+
+    let column_type = match (name, unsigned) {
+        // MySQL doesn't have a boolean type; `BOOLEAN` is an alias for `TINYINT(1)`
+        ("tinyint", false) if length == Some(1) => ColumnType::Boolean,
+        ("bool" | "boolean", _) => ColumnType::Boolean,
+        ("tinyint" | "int1", false) => ColumnType::TinyInteger,
+        ("tinyint" | "int1", true) => ColumnType::TinyUnsignedInteger,
+        ("smallint" | "int2" | "smallserial", false) => ColumnType::SmallInteger,
+        ("smallint" | "int2", true) => ColumnType::SmallUnsignedInteger,
+        ("integer" | "int" | "int4" | "mediumint" | "serial", false) => ColumnType::Integer,
+        ("integer" | "int" | "int4" | "mediumint", true) => ColumnType::UnsignedInteger,
+        ("bigint" | "int8" | "bigserial", false) => ColumnType::BigInteger,
+        ("bigint" | "int8", true) => ColumnType::BigUnsignedInteger,
+        ("float" | "float4" | "real", _) => ColumnType::Float,
+        ("double" | "double precision" | "float8", _) => ColumnType::Double,
+        ("date" | "date_text", _) => ColumnType::Date,
+        ("time" | "time without time zone" | "time_text", _) => ColumnType::Time,
+        (
+            "datetime"
+            | "timestamp"
+            | "timestamp without time zone"
+            | "datetime_text"
+            | "timestamp_text",
+            _,
+        ) => ColumnType::DateTime,
+        ("timestamptz" | "timestamp with time zone" | "timestamp_with_timezone_text", _) => {
+            ColumnType::DateTimeWithTimeZone
+        }
+        ("varchar" | "character varying" | "char" | "character" | "nvarchar" | "nchar", _) => {
+            match length {
+                Some(length) => ColumnType::String(length),
+                None => ColumnType::Text,
+            }
+        }
+        ("text" | "tinytext" | "mediumtext" | "longtext" | "clob", _) => ColumnType::Text,
+        (
+            "blob" | "tinyblob" | "mediumblob" | "longblob" | "bytea" | "binary" | "varbinary"
+            | "varbinary_blob",
+            _,
+        ) => ColumnType::Blob,
+        ("json" | "jsonb" | "json_text" | "jsonb_text", _) => ColumnType::Json,
+        ("uuid" | "uuid_text", _) => ColumnType::Uuid,
+        _ => return None,
+    };
+    Some(column_type)
+}
+
+/// Groups the columns and the index columns, as returned by the database
+/// catalog queries, into tables, sorted by name.
+///
+/// The index rows are `(table name, index name, unique, column name)` tuples,
+/// with the columns of each index in the order they appear in the index.
+pub(crate) fn group_tables(
+    columns: Vec<(String, ColumnSchema)>,
+    index_columns: Vec<(String, String, bool, String)>,
+) -> Vec<TableSchema> {
+    let mut tables = std::collections::BTreeMap::new();
+    for (table_name, column) in columns {
+        tables
+            .entry(table_name.clone())
+            .or_insert_with(|| TableSchema {
+                name: table_name,
+                columns: Vec::new(),
+                indexes: Vec::new(),
+            })
+            .columns
+            .push(column);
+    }
+    for (table_name, index_name, unique, column_name) in index_columns {
+        let Some(table) = tables.get_mut(&table_name) else {
+            continue;
+        };
+        match table.indexes.last_mut() {
+            Some(index) if index.name == index_name => index.columns.push(column_name),
+            _ => table.indexes.push(IndexSchema {
+                name: index_name,
+                columns: vec![column_name],
+                unique,
+            }),
+        }
+    }
+
+    tables.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_type_from_sql_integers() {
+        assert_eq!(column_type_from_sql("INTEGER"), Some(ColumnType::Integer));
+        assert_eq!(column_type_from_sql("int4"), Some(ColumnType::Integer));
+        assert_eq!(column_type_from_sql("bigint"), Some(ColumnType::BigInteger));
+        assert_eq!(
+            column_type_from_sql("int unsigned"),
+            Some(ColumnType::UnsignedInteger)
+        );
+        assert_eq!(
+            column_type_from_sql("bigint(20) unsigned"),
+            Some(ColumnType::BigUnsignedInteger)
+        );
+        assert_eq!(
+            column_type_from_sql("tinyint(1)"),
+            Some(ColumnType::Boolean)
+        );
+        assert_eq!(
+            column_type_from_sql("tinyint(4)"),
+            Some(ColumnType::TinyInteger)
+        );
+    }
+
+    #[test]
+    fn column_type_from_sql_strings() {
+        assert_eq!(
+            column_type_from_sql("varchar(255)"),
+            Some(ColumnType::String(255))
+        );
+        assert_eq!(
+            column_type_from_sql("character varying(100)"),
+            Some(ColumnType::String(100))
+        );
+        assert_eq!(column_type_from_sql("varchar"), Some(ColumnType::Text));
+        assert_eq!(column_type_from_sql("LONGTEXT"), Some(ColumnType::Text));
+    }
+
+    #[test]
+    fn column_type_from_sql_other() {
+        assert_eq!(
+            column_type_from_sql("timestamp with time zone"),
+            Some(ColumnType::DateTimeWithTimeZone)
+        );
+        assert_eq!(
+            column_type_from_sql("datetime_text"),
+            Some(ColumnType::DateTime)
+        );
+        assert_eq!(column_type_from_sql("jsonb"), Some(ColumnType::Json));
+        assert_eq!(column_type_from_sql("bytea"), Some(ColumnType::Blob));
+        assert_eq!(column_type_from_sql("uuid"), Some(ColumnType::Uuid));
+        assert_eq!(column_type_from_sql("numeric(10, 2)"), None);
+        assert_eq!(column_type_from_sql("geometry"), None);
+    }
+
+    #[test]
+    fn group_tables_sorted_with_indexes() {
+        let column = |name: &str| ColumnSchema {
+            name: name.to_owned(),
+            sql_type: "integer".to_owned(),
+            column_type: Some(ColumnType::Integer),
+            nullable: false,
+            primary_key: false,
+            auto_increment: false,
+        };
+
+        let tables = group_tables(
+            vec![
+                ("posts".to_owned(), column("id")),
+                ("posts".to_owned(), column("author_id")),
+                ("authors".to_owned(), column("id")),
+            ],
+            vec![
+                (
+                    "posts".to_owned(),
+                    "posts_a".to_owned(),
+                    true,
+                    "author_id".to_owned(),
+                ),
+                (
+                    "posts".to_owned(),
+                    "posts_a".to_owned(),
+                    true,
+                    "id".to_owned(),
+                ),
+                (
+                    "posts".to_owned(),
+                    "posts_b".to_owned(),
+                    false,
+                    "id".to_owned(),
+                ),
+                (
+                    "views".to_owned(),
+                    "views_a".to_owned(),
+                    false,
+                    "id".to_owned(),
+                ),
+            ],
+        );
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].name, "authors");
+        assert_eq!(tables[1].name, "posts");
+        assert_eq!(tables[1].columns, [column("id"), column("author_id")]);
+        assert_eq!(
+            tables[1].indexes,
+            [
+                IndexSchema {
+                    name: "posts_a".to_owned(),
+                    columns: vec!["author_id".to_owned(), "id".to_owned()],
+                    unique: true,
+                },
+                IndexSchema {
+                    name: "posts_b".to_owned(),
+                    columns: vec!["id".to_owned()],
+                    unique: false,
+                },
+            ]
+        );
+    }
+}
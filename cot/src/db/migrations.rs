@@ -2,6 +2,7 @@
 
 mod sorter;
 
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 
@@ -10,8 +11,12 @@ use thiserror::Error;
 use tracing::info;
 
 use crate::db::migrations::sorter::{MigrationSorter, MigrationSorterError};
-use crate::db::relations::{ForeignKeyOnDeletePolicy, ForeignKeyOnUpdatePolicy};
-use crate::db::{Auto, ColumnType, Database, DatabaseField, Identifier, Result, model, query};
+use crate::db::relations::{
+    ForeignKeyOnDeletePolicy, ForeignKeyOnUpdatePolicy, ForeignKeyRelation,
+};
+use crate::db::{
+    Auto, ColumnType, Database, DatabaseField, Identifier, Index, Model, Result, model, query,
+};
 
 /// An error that occurred while running migrations.
 #[derive(Debug, Clone, Error)]
@@ -20,6 +25,63 @@ pub enum MigrationEngineError {
     /// An error occurred while determining the correct order of migrations.
     #[error("Error while determining the correct order of migrations")]
     MigrationSortError(#[from] MigrationSorterError),
+    /// The column to be altered doesn't exist in the table.
+    #[error("Column `{column}` not found in table `{table}`")]
+    ColumnNotFound {
+        /// The name of the table.
+        table: String,
+        /// The name of the column that was not found.
+        column: String,
+    },
+    /// No migrations are defined for the given app.
+    #[error("No migrations found for app `{app_name}`")]
+    UnknownApp {
+        /// The name of the app.
+        app_name: String,
+    },
+    /// The migration to migrate to doesn't exist.
+    #[error("Migration `{migration_name}` not found in app `{app_name}`")]
+    MigrationNotFound {
+        /// The name of the app.
+        app_name: String,
+        /// The name (or name prefix) of the migration that was not found.
+        migration_name: String,
+    },
+    /// The migration name given to migrate to matches more than one migration.
+    #[error("Migration name `{migration_name}` is ambiguous in app `{app_name}`")]
+    AmbiguousMigration {
+        /// The name of the app.
+        app_name: String,
+        /// The name (or name prefix) of the migration.
+        migration_name: String,
+    },
+    /// A migration is recorded as applied in the database, but one of its
+    /// dependencies is not.
+    #[error(
+        "Migration {}::{} is applied, but its dependency {}::{} is not",
+        .0.app_name,
+        .0.migration_name,
+        .0.dependency_app_name,
+        .0.dependency_name
+    )]
+    InconsistentHistory(Box<InconsistentHistory>),
+}
+
+/// The details of the [`MigrationEngineError::InconsistentHistory`] error.
+///
+/// These are boxed in the error, so that they don't grow the size of every
+/// [`Result`](crate::Result) in the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InconsistentHistory {
+    /// The name of the app of the applied migration.
+    pub app_name: String,
+    /// The name of the applied migration.
+    pub migration_name: String,
+    /// The name of the app of the missing dependency.
+    pub dependency_app_name: String,
+    /// The name of the missing dependency.
+    pub dependency_name: String,
 }
 
 /// A migration engine that can run migrations.
@@ -58,6 +120,8 @@ pub enum MigrationEngineError {
 #[derive(Debug)]
 pub struct MigrationEngine {
     migrations: Vec<MigrationWrapper>,
+    /// Indices of the direct dependencies of each migration in `migrations`.
+    dependencies: Vec<Vec<usize>>,
 }
 
 impl MigrationEngine {
@@ -109,7 +173,12 @@ impl MigrationEngine {
 
     fn from_wrapper(mut migrations: Vec<MigrationWrapper>) -> Result<Self> {
         Self::sort_migrations(&mut migrations)?;
-        Ok(Self { migrations })
+        let dependencies = MigrationSorter::resolve_dependencies(&migrations)
+            .map_err(MigrationEngineError::from)?;
+        Ok(Self {
+            migrations,
+            dependencies,
+        })
     }
 
     /// Sorts the migrations by app name and migration name to ensure that the
@@ -171,44 +240,357 @@ impl MigrationEngine {
     pub async fn run(&self, database: &Database) -> Result<()> {
         info!("Running migrations");
 
-        CREATE_APPLIED_MIGRATIONS_MIGRATION
-            .forwards(database)
-            .await?;
-
-        for migration in &self.migrations {
-            for operation in migration.operations() {
-                if Self::is_migration_applied(database, migration).await? {
-                    info!(
-                        "Migration {} for app {} is already applied",
-                        migration.name(),
-                        migration.app_name()
-                    );
-                    continue;
-                }
-
+        let applied = self.load_applied(database).await?;
+        for (migration, is_applied) in self.migrations.iter().zip(applied) {
+            if is_applied {
                 info!(
-                    "Applying migration {} for app {}",
+                    "Migration {} for app {} is already applied",
                     migration.name(),
                     migration.app_name()
                 );
-                operation.forwards(database).await?;
-                Self::mark_migration_applied(database, migration).await?;
+            } else {
+                Self::apply_migration(database, migration).await?;
             }
         }
+        database.set_foreign_key_relations(self.foreign_key_relations(|_| true));
 
         Ok(())
     }
 
-    async fn is_migration_applied(
+    /// Migrates given app forwards or backwards to the given migration.
+    ///
+    /// All the migrations of the app up to and including `target` (as well as
+    /// all their dependencies, possibly from other apps) are applied. All the
+    /// migrations of the app after `target` (as well as all the migrations
+    /// that depend on them, possibly from other apps) are reverted, in the
+    /// reverse order of applying. If `target` is [`None`], all the migrations
+    /// of the app are reverted.
+    ///
+    /// The target can be either the full name of the migration (e.g.
+    /// `m_0003_auto_20250101_120000`), or its unambiguous prefix, with or
+    /// without the `m_` part (e.g. `0003` or `m_0003`).
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the app has no migrations, if the target migration
+    /// doesn't exist or is ambiguous, if the migration history in the database
+    /// is inconsistent, or if any of the migrations fail to apply or revert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Migration, MigrationDependency, MigrationEngine, Operation};
+    /// use cot::db::{Database, DatabaseField, Identifier};
+    ///
+    /// struct MyMigration;
+    ///
+    /// impl Migration for MyMigration {
+    ///     const APP_NAME: &'static str = "todoapp";
+    ///     const MIGRATION_NAME: &'static str = "m_0001_initial";
+    ///     const DEPENDENCIES: &'static [MigrationDependency] = &[];
+    ///     const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+    ///         .table_name(Identifier::new("todoapp__my_model"))
+    ///         .fields(&[
+    ///             Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    ///                 .primary_key()
+    ///                 .auto(),
+    ///             Field::new(Identifier::new("app"), <String as DatabaseField>::TYPE),
+    ///         ])
+    ///         .build()];
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([MyMigration])?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// engine
+    ///     .migrate_to(&database, "todoapp", Some("0001"))
+    ///     .await?;
+    /// // revert all the migrations of the app
+    /// engine.migrate_to(&database, "todoapp", None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn migrate_to(
+        &self,
         database: &Database,
-        migration: &MigrationWrapper,
-    ) -> Result<bool> {
+        app_name: &str,
+        target: Option<&str>,
+    ) -> Result<()> {
+        if !self.migrations.iter().any(|m| m.app_name() == app_name) {
+            return Err(MigrationEngineError::UnknownApp {
+                app_name: app_name.to_owned(),
+            }
+            .into());
+        }
+        let target_index = target
+            .map(|target| self.find_migration(app_name, target))
+            .transpose()?;
+        info!(
+            "Migrating app {app_name} to {}",
+            target_index.map_or("the initial state", |index| self.migrations[index].name())
+        );
+
+        let in_app = |index: usize| self.migrations[index].app_name() == app_name;
+        let to_apply = self.with_dependencies(
+            (0..self.migrations.len())
+                .map(|index| in_app(index) && target_index.is_some_and(|target| index <= target))
+                .collect(),
+        );
+        let to_revert = self.with_dependents(
+            (0..self.migrations.len())
+                .map(|index| in_app(index) && target_index.is_none_or(|target| index > target))
+                .collect(),
+        );
+
+        let applied = self.load_applied(database).await?;
+        for (index, migration) in self.migrations.iter().enumerate().rev() {
+            if to_revert[index] && applied[index] {
+                Self::revert_migration(database, migration).await?;
+            }
+        }
+        for (index, migration) in self.migrations.iter().enumerate() {
+            if to_apply[index] && !applied[index] {
+                Self::apply_migration(database, migration).await?;
+            }
+        }
+        database.set_foreign_key_relations(self.foreign_key_relations(|index| {
+            to_apply[index] || (applied[index] && !to_revert[index])
+        }));
+
+        Ok(())
+    }
+
+    /// Returns the status of all the migrations known to the engine, in the
+    /// order they are applied.
+    ///
+    /// Unlike [`MigrationEngine::run`] and [`MigrationEngine::migrate_to`],
+    /// this doesn't fail if the migration history in the database is
+    /// inconsistent, so it can be used to find out which migrations are
+    /// applied when the database is in a partially migrated state.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if there is an error while interacting with the
+    /// database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::migrations::{Migration, MigrationDependency, MigrationEngine, Operation};
+    ///
+    /// struct MyMigration;
+    ///
+    /// impl Migration for MyMigration {
+    ///     const APP_NAME: &'static str = "todoapp";
+    ///     const MIGRATION_NAME: &'static str = "m_0001_initial";
+    ///     const DEPENDENCIES: &'static [MigrationDependency] = &[];
+    ///     const OPERATIONS: &'static [Operation] = &[];
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([MyMigration])?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// let status = engine.status(&database).await?;
+    /// assert!(!status[0].is_applied());
+    ///
+    /// engine.run(&database).await?;
+    /// let status = engine.status(&database).await?;
+    /// assert!(status[0].is_applied());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn status(&self, database: &Database) -> Result<Vec<MigrationStatus>> {
+        let mut applied = Self::applied_migrations(database).await?;
+
+        Ok(self
+            .migrations
+            .iter()
+            .map(|migration| MigrationStatus {
+                app_name: migration.app_name().to_owned(),
+                name: migration.name().to_owned(),
+                applied: applied
+                    .remove(&(migration.app_name().to_owned(), migration.name().to_owned())),
+            })
+            .collect())
+    }
+
+    /// Returns the tables existing in the database schema after applying all
+    /// the migrations, in the order they were created in.
+    pub(crate) fn tables(&self) -> Vec<MigrationTable> {
+        let mut tables = Vec::new();
+        for migration in &self.migrations {
+            for operation in migration.operations() {
+                operation.update_tables(&mut tables);
+            }
+        }
+
+        tables
+    }
+
+    /// Returns the foreign keys existing in the database schema after applying
+    /// the migrations for which `is_applied` returns `true`.
+    pub(crate) fn foreign_key_relations(
+        &self,
+        is_applied: impl Fn(usize) -> bool,
+    ) -> Vec<ForeignKeyRelation> {
+        let mut relations = Vec::new();
+        for (index, migration) in self.migrations.iter().enumerate() {
+            if is_applied(index) {
+                for operation in migration.operations() {
+                    operation.update_foreign_key_relations(&mut relations);
+                }
+            }
+        }
+
+        relations
+    }
+
+    fn find_migration(&self, app_name: &str, target: &str) -> Result<usize> {
+        let in_app = || {
+            self.migrations
+                .iter()
+                .enumerate()
+                .filter(|(_, migration)| migration.app_name() == app_name)
+        };
+
+        if let Some((index, _)) = in_app().find(|(_, migration)| migration.name() == target) {
+            return Ok(index);
+        }
+
+        let prefixed_target = format!("m_{target}");
+        let mut matching = in_app().filter(|(_, migration)| {
+            migration.name().starts_with(target) || migration.name().starts_with(&prefixed_target)
+        });
+        match (matching.next(), matching.next()) {
+            (Some((index, _)), None) => Ok(index),
+            (None, _) => Err(MigrationEngineError::MigrationNotFound {
+                app_name: app_name.to_owned(),
+                migration_name: target.to_owned(),
+            }
+            .into()),
+            (Some(_), Some(_)) => Err(MigrationEngineError::AmbiguousMigration {
+                app_name: app_name.to_owned(),
+                migration_name: target.to_owned(),
+            }
+            .into()),
+        }
+    }
+
+    /// Extends the selection with all the (transitive) dependencies of the
+    /// selected migrations.
+    fn with_dependencies(&self, mut selected: Vec<bool>) -> Vec<bool> {
+        // dependencies always precede their dependents, so one pass is enough
+        for index in (0..self.migrations.len()).rev() {
+            if selected[index] {
+                for &dependency in &self.dependencies[index] {
+                    selected[dependency] = true;
+                }
+            }
+        }
+        selected
+    }
+
+    /// Extends the selection with all the migrations that (transitively)
+    /// depend on the selected migrations.
+    fn with_dependents(&self, mut selected: Vec<bool>) -> Vec<bool> {
+        // dependencies always precede their dependents, so one pass is enough
+        for index in 0..self.migrations.len() {
+            if self.dependencies[index]
+                .iter()
+                .any(|&dependency| selected[dependency])
+            {
+                selected[index] = true;
+            }
+        }
+        selected
+    }
+
+    /// Returns whether each of the migrations is applied, making sure that
+    /// the migration history in the database is consistent.
+    async fn load_applied(&self, database: &Database) -> Result<Vec<bool>> {
+        let applied_migrations = Self::applied_migrations(database).await?;
+        let applied: Vec<bool> = self
+            .migrations
+            .iter()
+            .map(|migration| {
+                applied_migrations
+                    .contains_key(&(migration.app_name().to_owned(), migration.name().to_owned()))
+            })
+            .collect();
+
+        for (index, migration) in self.migrations.iter().enumerate() {
+            let missing_dependency = self.dependencies[index]
+                .iter()
+                .find(|&&dependency| !applied[dependency]);
+            if let (true, Some(&dependency)) = (applied[index], missing_dependency) {
+                let dependency = &self.migrations[dependency];
+                return Err(MigrationEngineError::InconsistentHistory(Box::new(
+                    InconsistentHistory {
+                        app_name: migration.app_name().to_owned(),
+                        migration_name: migration.name().to_owned(),
+                        dependency_app_name: dependency.app_name().to_owned(),
+                        dependency_name: dependency.name().to_owned(),
+                    },
+                ))
+                .into());
+            }
+        }
+
+        Ok(applied)
+    }
+
+    /// Returns the migrations recorded as applied in the database, along with
+    /// the time they were applied at.
+    ///
+    /// This also creates the `cot__migrations` table if it does not exist.
+    async fn applied_migrations(
+        database: &Database,
+    ) -> Result<HashMap<(String, String), chrono::DateTime<chrono::FixedOffset>>> {
+        CREATE_APPLIED_MIGRATIONS_MIGRATION
+            .forwards(database)
+            .await?;
+
+        Ok(AppliedMigration::objects()
+            .all(database)
+            .await?
+            .into_iter()
+            .map(|migration| ((migration.app, migration.name), migration.applied))
+            .collect())
+    }
+
+    async fn apply_migration(database: &Database, migration: &MigrationWrapper) -> Result<()> {
+        info!(
+            "Applying migration {} for app {}",
+            migration.name(),
+            migration.app_name()
+        );
+
+        for operation in migration.operations() {
+            operation.forwards(database).await?;
+        }
+        Self::mark_migration_applied(database, migration).await
+    }
+
+    async fn revert_migration(database: &Database, migration: &MigrationWrapper) -> Result<()> {
+        info!(
+            "Reverting migration {} for app {}",
+            migration.name(),
+            migration.app_name()
+        );
+
+        for operation in migration.operations().iter().rev() {
+            operation.backwards(database).await?;
+        }
         query!(
             AppliedMigration,
             $app == migration.app_name() && $name == migration.name()
         )
-        .exists(database)
-        .await
+        .delete(database)
+        .await?;
+        Ok(())
     }
 
     async fn mark_migration_applied(
@@ -227,6 +609,111 @@ impl MigrationEngine {
     }
 }
 
+/// The status of a single migration, as returned by
+/// [`MigrationEngine::status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    app_name: String,
+    name: String,
+    applied: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl MigrationStatus {
+    /// Returns the name of the app the migration belongs to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::migrations::MigrationEngine;
+    /// use cot::test::TestMigration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([TestMigration::new("todoapp", "m_0001_initial", [], [])])?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// let status = engine.status(&database).await?;
+    /// assert_eq!(status[0].app_name(), "todoapp");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn app_name(&self) -> &str {
+        &self.app_name
+    }
+
+    /// Returns the name of the migration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::migrations::MigrationEngine;
+    /// use cot::test::TestMigration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([TestMigration::new("todoapp", "m_0001_initial", [], [])])?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// let status = engine.status(&database).await?;
+    /// assert_eq!(status[0].name(), "m_0001_initial");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns whether the migration is applied in the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::migrations::MigrationEngine;
+    /// use cot::test::TestMigration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([TestMigration::new("todoapp", "m_0001_initial", [], [])])?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// engine.run(&database).await?;
+    /// let status = engine.status(&database).await?;
+    /// assert!(status[0].is_applied());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn is_applied(&self) -> bool {
+        self.applied.is_some()
+    }
+
+    /// Returns the time the migration was applied at, or [`None`] if it is
+    /// not applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::migrations::MigrationEngine;
+    /// use cot::test::TestMigration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([TestMigration::new("todoapp", "m_0001_initial", [], [])])?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// let status = engine.status(&database).await?;
+    /// assert!(status[0].applied_at().is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn applied_at(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.applied
+    }
+}
+
 /// A migration operation that can be run forwards or backwards.
 ///
 /// # Examples
@@ -371,6 +858,59 @@ impl Operation {
         RemoveFieldBuilder::new()
     }
 
+    /// Returns a builder for an operation that changes the definition of a
+    /// field in a model.
+    ///
+    /// This can change the type of the field, whether it's nullable, and
+    /// whether it has a unique constraint. The foreign key constraints are not
+    /// changed.
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
+    /// #     .table_name(Identifier::new("todoapp__my_model"))
+    /// #     .fields(&[
+    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    /// #             .primary_key()
+    /// #             .auto(),
+    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+    /// #     ])
+    /// #     .build();
+    /// const OPERATION: Operation = Operation::alter_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_field(Field::new(
+    ///         Identifier::new("name"),
+    ///         <String as DatabaseField>::TYPE,
+    ///     ))
+    ///     .new_field(
+    ///         Field::new(
+    ///             Identifier::new("name"),
+    ///             <Option<String> as DatabaseField>::TYPE,
+    ///         )
+    ///         .null(),
+    ///     )
+    ///     .build();
+    ///
+    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
+    /// # OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn alter_field() -> AlterFieldBuilder {
+        AlterFieldBuilder::new()
+    }
+
     /// Returns a builder for an operation that removes a model.
     ///
     /// Typically, you shouldn't need to use this directly. Instead, in most
@@ -414,7 +954,97 @@ impl Operation {
         RemoveModelBuilder::new()
     }
 
-    /// Runs the operation forwards.
+    /// Returns a builder for an operation that adds an index, or a unique
+    /// constraint, to a model.
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier, Index};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
+    /// #     .table_name(Identifier::new("todoapp__my_model"))
+    /// #     .fields(&[
+    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    /// #             .primary_key()
+    /// #             .auto(),
+    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+    /// #     ])
+    /// #     .build();
+    /// const OPERATION: Operation = Operation::add_index()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .index(Index::new(
+    ///         Identifier::new("todoapp__my_model_name_idx"),
+    ///         &[Identifier::new("name")],
+    ///     ))
+    ///     .build();
+    ///
+    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
+    /// # OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn add_index() -> AddIndexBuilder {
+        AddIndexBuilder::new()
+    }
+
+    /// Returns a builder for an operation that removes an index, or a unique
+    /// constraint, from a model.
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier, Index};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// const INDEX: Index = Index::new(
+    ///     Identifier::new("todoapp__my_model_name_idx"),
+    ///     &[Identifier::new("name")],
+    /// );
+    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
+    /// #     .table_name(Identifier::new("todoapp__my_model"))
+    /// #     .fields(&[
+    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    /// #             .primary_key()
+    /// #             .auto(),
+    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+    /// #     ])
+    /// #     .build();
+    /// # const ADD_INDEX_OPERATION: Operation = Operation::add_index()
+    /// #     .table_name(Identifier::new("todoapp__my_model"))
+    /// #     .index(INDEX)
+    /// #     .build();
+    /// const OPERATION: Operation = Operation::remove_index()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .index(INDEX)
+    ///     .build();
+    ///
+    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
+    /// # ADD_INDEX_OPERATION.forwards(&database).await?;
+    /// # OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn remove_index() -> RemoveIndexBuilder {
+        RemoveIndexBuilder::new()
+    }
+
+    /// Runs the operation forwards.
     ///
     /// # Errors
     ///
@@ -450,21 +1080,7 @@ impl Operation {
                 fields,
                 if_not_exists,
             } => {
-                let mut query = sea_query::Table::create().table(*table_name).to_owned();
-                for field in *fields {
-                    query.col(field.as_column_def(database));
-                    if let Some(foreign_key) = field.foreign_key {
-                        query.foreign_key(
-                            sea_query::ForeignKeyCreateStatement::new()
-                                .from_tbl(*table_name)
-                                .from_col(field.name)
-                                .to_tbl(foreign_key.model)
-                                .to_col(foreign_key.field)
-                                .on_delete(foreign_key.on_delete.into())
-                                .on_update(foreign_key.on_update.into()),
-                        );
-                    }
-                }
+                let mut query = create_table_statement(*table_name, fields, database);
                 if *if_not_exists {
                     query.if_not_exists();
                 }
@@ -484,6 +1100,15 @@ impl Operation {
                     .to_owned();
                 database.execute_schema(query).await?;
             }
+            OperationInner::AlterField {
+                table_name,
+                old_field,
+                new_field,
+            } => {
+                database
+                    .alter_column(*table_name, old_field, new_field)
+                    .await?;
+            }
             OperationInner::RemoveModel {
                 table_name,
                 fields: _,
@@ -491,6 +1116,16 @@ impl Operation {
                 let query = sea_query::Table::drop().table(*table_name).to_owned();
                 database.execute_schema(query).await?;
             }
+            OperationInner::AddIndex { table_name, index } => {
+                database
+                    .execute_schema(create_index_statement(*table_name, index))
+                    .await?;
+            }
+            OperationInner::RemoveIndex { table_name, index } => {
+                database
+                    .execute_schema(drop_index_statement(*table_name, index))
+                    .await?;
+            }
         }
         Ok(())
     }
@@ -550,27 +1185,152 @@ impl Operation {
                     .to_owned();
                 database.execute_schema(query).await?;
             }
+            OperationInner::AlterField {
+                table_name,
+                old_field,
+                new_field,
+            } => {
+                database
+                    .alter_column(*table_name, new_field, old_field)
+                    .await?;
+            }
             OperationInner::RemoveModel { table_name, fields } => {
-                let mut query = sea_query::Table::create().table(*table_name).to_owned();
-                for field in *fields {
-                    query.col(field.as_column_def(database));
-                    if let Some(foreign_key) = field.foreign_key {
-                        query.foreign_key(
-                            sea_query::ForeignKeyCreateStatement::new()
-                                .from_tbl(*table_name)
-                                .from_col(field.name)
-                                .to_tbl(foreign_key.model)
-                                .to_col(foreign_key.field)
-                                .on_delete(foreign_key.on_delete.into())
-                                .on_update(foreign_key.on_update.into()),
-                        );
-                    }
-                }
+                let query = create_table_statement(*table_name, fields, database);
                 database.execute_schema(query).await?;
             }
+            OperationInner::AddIndex { table_name, index } => {
+                database
+                    .execute_schema(drop_index_statement(*table_name, index))
+                    .await?;
+            }
+            OperationInner::RemoveIndex { table_name, index } => {
+                database
+                    .execute_schema(create_index_statement(*table_name, index))
+                    .await?;
+            }
         }
         Ok(())
     }
+
+    /// Updates the list of the foreign keys existing in the database schema
+    /// to reflect the state after this operation is applied.
+    fn update_foreign_key_relations(&self, relations: &mut Vec<ForeignKeyRelation>) {
+        match &self.inner {
+            OperationInner::CreateModel {
+                table_name, fields, ..
+            } => {
+                relations.extend(
+                    fields
+                        .iter()
+                        .filter_map(|field| field.foreign_key_relation(*table_name)),
+                );
+            }
+            OperationInner::AddField { table_name, field } => {
+                relations.extend(field.foreign_key_relation(*table_name));
+            }
+            OperationInner::RemoveField { table_name, field } => {
+                relations.retain(|relation| {
+                    relation.table != *table_name || relation.column != field.name
+                });
+            }
+            OperationInner::AlterField {
+                table_name,
+                old_field,
+                new_field,
+            } => {
+                relations.retain(|relation| {
+                    relation.table != *table_name || relation.column != old_field.name
+                });
+                relations.extend(new_field.foreign_key_relation(*table_name));
+            }
+            OperationInner::RemoveModel { table_name, .. } => {
+                relations.retain(|relation| relation.table != *table_name);
+            }
+            OperationInner::AddIndex { .. } | OperationInner::RemoveIndex { .. } => {}
+        }
+    }
+
+    /// Updates the list of the tables existing in the database schema to
+    /// reflect the state after this operation is applied.
+    fn update_tables(&self, tables: &mut Vec<MigrationTable>) {
+        fn find_table(
+            tables: &mut [MigrationTable],
+            table_name: Identifier,
+        ) -> Option<&mut MigrationTable> {
+            tables.iter_mut().find(|table| table.name == table_name)
+        }
+
+        match &self.inner {
+            OperationInner::CreateModel {
+                table_name, fields, ..
+            } => {
+                if find_table(tables, *table_name).is_none() {
+                    tables.push(MigrationTable {
+                        name: *table_name,
+                        fields: fields.to_vec(),
+                    });
+                }
+            }
+            OperationInner::AddField { table_name, field } => {
+                if let Some(table) = find_table(tables, *table_name) {
+                    table.fields.push(*field);
+                }
+            }
+            OperationInner::RemoveField { table_name, field } => {
+                if let Some(table) = find_table(tables, *table_name) {
+                    table.fields.retain(|existing| existing.name != field.name);
+                }
+            }
+            OperationInner::AlterField {
+                table_name,
+                old_field,
+                new_field,
+            } => {
+                if let Some(table) = find_table(tables, *table_name) {
+                    for field in &mut table.fields {
+                        if field.name == old_field.name {
+                            *field = *new_field;
+                        }
+                    }
+                }
+            }
+            OperationInner::RemoveModel { table_name, .. } => {
+                tables.retain(|table| table.name != *table_name);
+            }
+            OperationInner::AddIndex { .. } | OperationInner::RemoveIndex { .. } => {}
+        }
+    }
+}
+
+/// A table in the database schema, as defined by the migrations.
+#[derive(Debug, Clone)]
+pub(crate) struct MigrationTable {
+    pub(crate) name: Identifier,
+    pub(crate) fields: Vec<Field>,
+}
+
+fn create_index_statement(
+    table_name: Identifier,
+    index: &Index,
+) -> sea_query::IndexCreateStatement {
+    let mut statement = sea_query::Index::create()
+        .name(index.name().as_str())
+        .table(table_name)
+        .to_owned();
+    for column in index.columns() {
+        statement.col(*column);
+    }
+    if index.is_unique() {
+        statement.unique();
+    }
+    statement
+}
+
+fn drop_index_statement(table_name: Identifier, index: &Index) -> sea_query::IndexDropStatement {
+    sea_query::Index::drop()
+        .name(index.name().as_str())
+        .table(table_name)
+        .to_owned()
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -591,11 +1351,27 @@ enum OperationInner {
         table_name: Identifier,
         field: Field,
     },
+    /// Change the definition of a field in an existing model.
+    AlterField {
+        table_name: Identifier,
+        old_field: Field,
+        new_field: Field,
+    },
     /// Remove a model with the given fields
     RemoveModel {
         table_name: Identifier,
         fields: &'static [Field],
     },
+    /// Add an index (or a unique constraint) to an existing model.
+    AddIndex {
+        table_name: Identifier,
+        index: Index,
+    },
+    /// Remove an index (or a unique constraint) from an existing model.
+    RemoveIndex {
+        table_name: Identifier,
+        index: Index,
+    },
 }
 
 /// A field in a model.
@@ -801,13 +1577,22 @@ impl Field {
         self
     }
 
-    fn as_column_def<T: ColumnTypeMapper>(&self, mapper: &T) -> ColumnDef {
+    pub(super) fn as_column_def<T: ColumnTypeMapper>(&self, mapper: &T) -> ColumnDef {
+        self.as_column_def_impl(mapper, self.primary_key)
+    }
+
+    /// Creates the column definition; `primary_key` is `false` for the
+    /// columns of a composite primary key, which is defined for the whole
+    /// table instead.
+    fn as_column_def_impl<T: ColumnTypeMapper>(&self, mapper: &T, primary_key: bool) -> ColumnDef {
         let mut def =
             ColumnDef::new_with_type(self.name, mapper.sea_query_column_type_for(self.ty));
-        if self.primary_key {
+        if primary_key {
             def.primary_key();
         }
-        if self.auto_value {
+        // the values of non-integer automatic fields, such as UUIDs, are
+        // generated by Cot before inserting the rows
+        if self.auto_value && self.ty.is_integer() {
             def.auto_increment();
         }
         if self.null {
@@ -820,6 +1605,54 @@ impl Field {
         }
         def
     }
+
+    fn foreign_key_relation(&self, table: Identifier) -> Option<ForeignKeyRelation> {
+        self.foreign_key.map(|foreign_key| ForeignKeyRelation {
+            table,
+            column: self.name,
+            to_table: foreign_key.model,
+            to_column: foreign_key.field,
+            on_delete: foreign_key.on_delete,
+        })
+    }
+}
+
+/// Creates the statement creating a table with given fields.
+///
+/// If more than one field is marked as the primary key, the primary key is
+/// created as a composite primary key spanning all of them.
+fn create_table_statement<T: ColumnTypeMapper>(
+    table_name: Identifier,
+    fields: &[Field],
+    mapper: &T,
+) -> sea_query::TableCreateStatement {
+    let pk_fields: Vec<_> = fields.iter().filter(|field| field.primary_key).collect();
+    let composite_primary_key = pk_fields.len() > 1;
+
+    let mut query = sea_query::Table::create().table(table_name).to_owned();
+    for field in fields {
+        query.col(field.as_column_def_impl(mapper, field.primary_key && !composite_primary_key));
+        if let Some(foreign_key) = field.foreign_key {
+            query.foreign_key(
+                sea_query::ForeignKeyCreateStatement::new()
+                    .from_tbl(table_name)
+                    .from_col(field.name)
+                    .to_tbl(foreign_key.model)
+                    .to_col(foreign_key.field)
+                    .on_delete(foreign_key.on_delete.into())
+                    .on_update(foreign_key.on_update.into()),
+            );
+        }
+    }
+    if composite_primary_key {
+        let mut primary_key = sea_query::Index::create();
+        for field in pk_fields {
+            primary_key.col(field.name);
+        }
+        query.primary_key(&mut primary_key);
+    }
+
+    query
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -1389,6 +2222,165 @@ impl RemoveFieldBuilder {
     }
 }
 
+/// A builder for changing the definition of a field in a model.
+///
+/// Typically, you shouldn't need to use this directly. Instead, in most
+/// cases, this can be automatically generated by the Cot CLI.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::migrations::{Field, Operation};
+/// use cot::db::{DatabaseField, Identifier};
+///
+/// const OPERATION: Operation = Operation::alter_field()
+///     .table_name(Identifier::new("todoapp__my_model"))
+///     .old_field(Field::new(
+///         Identifier::new("count"),
+///         <i32 as DatabaseField>::TYPE,
+///     ))
+///     .new_field(Field::new(
+///         Identifier::new("count"),
+///         <i64 as DatabaseField>::TYPE,
+///     ))
+///     .build();
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct AlterFieldBuilder {
+    table_name: Option<Identifier>,
+    old_field: Option<Field>,
+    new_field: Option<Field>,
+}
+
+impl Default for AlterFieldBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlterFieldBuilder {
+    #[must_use]
+    const fn new() -> Self {
+        Self {
+            table_name: None,
+            old_field: None,
+            new_field: None,
+        }
+    }
+
+    /// Sets the name of the table containing the field.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Identifier;
+    /// use cot::db::migrations::Operation;
+    ///
+    /// let builder = Operation::alter_field().table_name(Identifier::new("todoapp__my_model"));
+    /// ```
+    #[must_use]
+    pub const fn table_name(mut self, table_name: Identifier) -> Self {
+        self.table_name = Some(table_name);
+        self
+    }
+
+    /// Sets the current definition of the field.
+    ///
+    /// This is used when the operation is reverted.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// let builder = Operation::alter_field().old_field(Field::new(
+    ///     Identifier::new("count"),
+    ///     <i32 as DatabaseField>::TYPE,
+    /// ));
+    /// ```
+    #[must_use]
+    pub const fn old_field(mut self, field: Field) -> Self {
+        self.old_field = Some(field);
+        self
+    }
+
+    /// Sets the new definition of the field.
+    ///
+    /// The name of the field must be the same as the name of the field passed
+    /// to [`Self::old_field`].
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// let builder = Operation::alter_field().new_field(Field::new(
+    ///     Identifier::new("count"),
+    ///     <i64 as DatabaseField>::TYPE,
+    /// ));
+    /// ```
+    #[must_use]
+    pub const fn new_field(mut self, field: Field) -> Self {
+        self.new_field = Some(field);
+        self
+    }
+
+    /// Builds the operation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table name, the old field, or the new field is not set.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// const OPERATION: Operation = Operation::alter_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i32 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .new_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i64 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub const fn build(self) -> Operation {
+        Operation::new(OperationInner::AlterField {
+            table_name: unwrap_builder_option!(self, table_name),
+            old_field: unwrap_builder_option!(self, old_field),
+            new_field: unwrap_builder_option!(self, new_field),
+        })
+    }
+}
+
 /// A builder for removing a model.
 ///
 /// Typically, you shouldn't need to use this directly. Instead, in most
@@ -1423,85 +2415,221 @@ impl RemoveFieldBuilder {
 ///     .fields(FIELDS)
 ///     .build();
 ///
-/// OPERATION.forwards(&database).await?;
-/// # Ok(())
-/// # }
-/// ```
+/// OPERATION.forwards(&database).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct RemoveModelBuilder {
+    table_name: Option<Identifier>,
+    fields: Option<&'static [Field]>,
+}
+
+impl Default for RemoveModelBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl RemoveModelBuilder {
+    #[must_use]
+    const fn new() -> Self {
+        Self {
+            table_name: None,
+            fields: None,
+        }
+    }
+
+    /// Sets the name of the table to remove.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// const FIELDS: &[Field] = &[
+    ///     Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    ///         .primary_key()
+    ///         .auto(),
+    ///     Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+    /// ];
+    ///
+    /// // First create the table
+    /// const CREATE_OPERATION: Operation = Operation::create_model()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .fields(FIELDS)
+    ///     .build();
+    /// let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// CREATE_OPERATION.forwards(&database).await?;
+    ///
+    /// // Then remove it
+    /// const OPERATION: Operation = Operation::remove_model()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .fields(FIELDS)
+    ///     .build();
+    ///
+    /// OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn table_name(mut self, table_name: Identifier) -> Self {
+        self.table_name = Some(table_name);
+        self
+    }
+
+    /// Sets the fields to store for potential backwards migration.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    #[must_use]
+    pub const fn fields(mut self, fields: &'static [Field]) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    /// Builds the operation.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    #[must_use]
+    pub const fn build(self) -> Operation {
+        Operation::new(OperationInner::RemoveModel {
+            table_name: unwrap_builder_option!(self, table_name),
+            fields: unwrap_builder_option!(self, fields),
+        })
+    }
+}
+
+/// A builder for adding an index, or a unique constraint, to a model.
+///
+/// # Cot CLI Usage
+///
+/// Typically, you shouldn't need to use this directly. Instead, in most
+/// cases, this can be automatically generated by the Cot CLI.
+///
+/// See [`Operation::add_index`] for an example.
+#[derive(Debug, Copy, Clone)]
+pub struct AddIndexBuilder {
+    table_name: Option<Identifier>,
+    index: Option<Index>,
+}
+
+impl Default for AddIndexBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AddIndexBuilder {
+    #[must_use]
+    const fn new() -> Self {
+        Self {
+            table_name: None,
+            index: None,
+        }
+    }
+
+    /// Sets the name of the table to add the index to.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    #[must_use]
+    pub const fn table_name(mut self, table_name: Identifier) -> Self {
+        self.table_name = Some(table_name);
+        self
+    }
+
+    /// Sets the index to add.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    #[must_use]
+    pub const fn index(mut self, index: Index) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Builds the operation.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    #[must_use]
+    pub const fn build(self) -> Operation {
+        Operation::new(OperationInner::AddIndex {
+            table_name: unwrap_builder_option!(self, table_name),
+            index: unwrap_builder_option!(self, index),
+        })
+    }
+}
+
+/// A builder for removing an index, or a unique constraint, from a model.
+///
+/// # Cot CLI Usage
+///
+/// Typically, you shouldn't need to use this directly. Instead, in most
+/// cases, this can be automatically generated by the Cot CLI.
+///
+/// See [`Operation::remove_index`] for an example.
 #[derive(Debug, Copy, Clone)]
-pub struct RemoveModelBuilder {
+pub struct RemoveIndexBuilder {
     table_name: Option<Identifier>,
-    fields: Option<&'static [Field]>,
+    index: Option<Index>,
 }
 
-impl Default for RemoveModelBuilder {
+impl Default for RemoveIndexBuilder {
     fn default() -> Self {
         Self::new()
     }
 }
-impl RemoveModelBuilder {
+
+impl RemoveIndexBuilder {
     #[must_use]
     const fn new() -> Self {
         Self {
             table_name: None,
-            fields: None,
+            index: None,
         }
     }
 
-    /// Sets the name of the table to remove.
+    /// Sets the name of the table to remove the index from.
     ///
     /// # Cot CLI Usage
     ///
     /// Typically, you shouldn't need to use this directly. Instead, in most
     /// cases, this can be automatically generated by the Cot CLI.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use cot::db::migrations::{Field, Operation};
-    /// use cot::db::{DatabaseField, Identifier};
-    ///
-    /// # #[tokio::main]
-    /// # async fn main() -> cot::Result<()> {
-    /// const FIELDS: &[Field] = &[
-    ///     Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
-    ///         .primary_key()
-    ///         .auto(),
-    ///     Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
-    /// ];
-    ///
-    /// // First create the table
-    /// const CREATE_OPERATION: Operation = Operation::create_model()
-    ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .fields(FIELDS)
-    ///     .build();
-    /// let database = cot::db::Database::new("sqlite::memory:").await?;
-    /// CREATE_OPERATION.forwards(&database).await?;
-    ///
-    /// // Then remove it
-    /// const OPERATION: Operation = Operation::remove_model()
-    ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .fields(FIELDS)
-    ///     .build();
-    ///
-    /// OPERATION.forwards(&database).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
     #[must_use]
     pub const fn table_name(mut self, table_name: Identifier) -> Self {
         self.table_name = Some(table_name);
         self
     }
 
-    /// Sets the fields to store for potential backwards migration.
+    /// Sets the index to remove.
     ///
     /// # Cot CLI Usage
     ///
     /// Typically, you shouldn't need to use this directly. Instead, in most
     /// cases, this can be automatically generated by the Cot CLI.
     #[must_use]
-    pub const fn fields(mut self, fields: &'static [Field]) -> Self {
-        self.fields = Some(fields);
+    pub const fn index(mut self, index: Index) -> Self {
+        self.index = Some(index);
         self
     }
 
@@ -1513,9 +2641,9 @@ impl RemoveModelBuilder {
     /// cases, this can be automatically generated by the Cot CLI.
     #[must_use]
     pub const fn build(self) -> Operation {
-        Operation::new(OperationInner::RemoveModel {
+        Operation::new(OperationInner::RemoveIndex {
             table_name: unwrap_builder_option!(self, table_name),
-            fields: unwrap_builder_option!(self, fields),
+            index: unwrap_builder_option!(self, index),
         })
     }
 }
@@ -1735,6 +2863,8 @@ impl From<ColumnType> for sea_query::ColumnType {
             ColumnType::Text => Self::Text,
             ColumnType::Blob => Self::Blob,
             ColumnType::String(len) => Self::String(StringLen::N(len)),
+            ColumnType::Json => Self::Json,
+            ColumnType::Uuid => Self::Uuid,
         }
     }
 }
@@ -1879,7 +3009,7 @@ mod tests {
     use sea_query::ColumnSpec;
 
     use super::*;
-    use crate::db::{ColumnType, DatabaseField, Identifier};
+    use crate::db::{ColumnType, DatabaseField, Identifier, RowsNum};
 
     struct TestMigration;
 
@@ -1907,6 +3037,66 @@ mod tests {
         const OPERATIONS: &'static [Operation] = &[];
     }
 
+    #[test]
+    fn operation_update_foreign_key_relations() {
+        const PARENT: Field = Field::new(Identifier::new("parent"), ColumnType::Integer)
+            .foreign_key(
+                Identifier::new("parent"),
+                Identifier::new("id"),
+                ForeignKeyOnDeletePolicy::Cascade,
+                ForeignKeyOnUpdatePolicy::Cascade,
+            );
+        const OTHER: Field = Field::new(Identifier::new("other"), ColumnType::Integer).foreign_key(
+            Identifier::new("other"),
+            Identifier::new("id"),
+            ForeignKeyOnDeletePolicy::Restrict,
+            ForeignKeyOnUpdatePolicy::Cascade,
+        );
+        let relation = |column: &'static str, on_delete| ForeignKeyRelation {
+            table: Identifier::new("child"),
+            column: Identifier::new(column),
+            to_table: Identifier::new(column),
+            to_column: Identifier::new("id"),
+            on_delete,
+        };
+
+        let mut relations = Vec::new();
+        Operation::create_model()
+            .table_name(Identifier::new("child"))
+            .fields(&[PARENT])
+            .build()
+            .update_foreign_key_relations(&mut relations);
+        Operation::add_field()
+            .table_name(Identifier::new("child"))
+            .field(OTHER)
+            .build()
+            .update_foreign_key_relations(&mut relations);
+        assert_eq!(
+            relations,
+            [
+                relation("parent", ForeignKeyOnDeletePolicy::Cascade),
+                relation("other", ForeignKeyOnDeletePolicy::Restrict),
+            ]
+        );
+
+        Operation::remove_field()
+            .table_name(Identifier::new("child"))
+            .field(PARENT)
+            .build()
+            .update_foreign_key_relations(&mut relations);
+        assert_eq!(
+            relations,
+            [relation("other", ForeignKeyOnDeletePolicy::Restrict)]
+        );
+
+        Operation::remove_model()
+            .table_name(Identifier::new("child"))
+            .fields(&[OTHER])
+            .build()
+            .update_foreign_key_relations(&mut relations);
+        assert!(relations.is_empty());
+    }
+
     #[cot_macros::dbtest]
     async fn test_migration_engine_run(test_db: &mut TestDatabase) {
         let engine = MigrationEngine::new([TestMigration]).unwrap();
@@ -1930,6 +3120,216 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    fn create_model_operation(table_name: &'static str) -> Operation {
+        const FIELDS: &[Field] = &[
+            Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+        ];
+
+        Operation::create_model()
+            .table_name(Identifier::new(table_name))
+            .fields(FIELDS)
+            .build()
+    }
+
+    fn rollback_test_engine() -> MigrationEngine {
+        MigrationEngine::new([
+            crate::test::TestMigration::new(
+                "testapp",
+                "m_0001_initial",
+                [],
+                [
+                    create_model_operation("testapp__first"),
+                    create_model_operation("testapp__second"),
+                ],
+            ),
+            crate::test::TestMigration::new(
+                "testapp",
+                "m_0002_third",
+                [MigrationDependency::migration("testapp", "m_0001_initial")],
+                [create_model_operation("testapp__third")],
+            ),
+            crate::test::TestMigration::new(
+                "otherapp",
+                "m_0001_initial",
+                [MigrationDependency::model(
+                    "testapp",
+                    Identifier::new("testapp__first"),
+                )],
+                [create_model_operation("otherapp__other")],
+            ),
+        ])
+        .unwrap()
+    }
+
+    async fn table_exists(database: &Database, table_name: &str) -> bool {
+        database
+            .raw(&format!("SELECT * FROM {table_name}"))
+            .await
+            .is_ok()
+    }
+
+    async fn applied_names(engine: &MigrationEngine, database: &Database) -> Vec<String> {
+        engine
+            .status(database)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(MigrationStatus::is_applied)
+            .map(|status| format!("{}::{}", status.app_name(), status.name()))
+            .collect()
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_run_applies_all_operations(test_db: &mut TestDatabase) {
+        let engine = rollback_test_engine();
+        let database = test_db.database();
+
+        engine.run(&database).await.unwrap();
+
+        for table_name in [
+            "testapp__first",
+            "testapp__second",
+            "testapp__third",
+            "otherapp__other",
+        ] {
+            assert!(table_exists(&database, table_name).await, "{table_name}");
+        }
+        assert_eq!(applied_names(&engine, &database).await.len(), 3);
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_migrate_to(test_db: &mut TestDatabase) {
+        let engine = rollback_test_engine();
+        let database = test_db.database();
+
+        engine
+            .migrate_to(&database, "testapp", Some("0001"))
+            .await
+            .unwrap();
+        assert_eq!(
+            applied_names(&engine, &database).await,
+            ["testapp::m_0001_initial"]
+        );
+        assert!(table_exists(&database, "testapp__second").await);
+        assert!(!table_exists(&database, "testapp__third").await);
+
+        engine
+            .migrate_to(&database, "testapp", Some("m_0002_third"))
+            .await
+            .unwrap();
+        assert!(table_exists(&database, "testapp__third").await);
+
+        engine
+            .migrate_to(&database, "testapp", Some("m_0001"))
+            .await
+            .unwrap();
+        assert_eq!(
+            applied_names(&engine, &database).await,
+            ["testapp::m_0001_initial"]
+        );
+        assert!(!table_exists(&database, "testapp__third").await);
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_migrate_to_reverts_dependents(test_db: &mut TestDatabase) {
+        let engine = rollback_test_engine();
+        let database = test_db.database();
+        engine.run(&database).await.unwrap();
+
+        engine.migrate_to(&database, "testapp", None).await.unwrap();
+
+        assert!(applied_names(&engine, &database).await.is_empty());
+        for table_name in [
+            "testapp__first",
+            "testapp__second",
+            "testapp__third",
+            "otherapp__other",
+        ] {
+            assert!(!table_exists(&database, table_name).await, "{table_name}");
+        }
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_migrate_to_applies_dependencies(test_db: &mut TestDatabase) {
+        let engine = rollback_test_engine();
+        let database = test_db.database();
+
+        engine
+            .migrate_to(&database, "otherapp", Some("0001"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            applied_names(&engine, &database).await,
+            ["testapp::m_0001_initial", "otherapp::m_0001_initial"]
+        );
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_inconsistent_history(test_db: &mut TestDatabase) {
+        let engine = rollback_test_engine();
+        let database = test_db.database();
+        engine.run(&database).await.unwrap();
+        query!(AppliedMigration, $app == "testapp" && $name == "m_0001_initial")
+            .delete(&database)
+            .await
+            .unwrap();
+
+        let result = engine.run(&database).await;
+
+        assert!(matches!(
+            result,
+            Err(crate::db::DatabaseError::MigrationError(
+                MigrationEngineError::InconsistentHistory(_)
+            ))
+        ));
+        assert_eq!(applied_names(&engine, &database).await.len(), 2);
+    }
+
+    #[test]
+    fn test_migration_engine_find_migration() {
+        let engine = MigrationEngine::new([
+            crate::test::TestMigration::new("testapp", "m_0001_initial", [], []),
+            crate::test::TestMigration::new("testapp", "m_0010_first", [], []),
+            crate::test::TestMigration::new("testapp", "m_0011_second", [], []),
+        ])
+        .unwrap();
+        let name = |index: usize| engine.migrations[index].name();
+
+        assert_eq!(
+            name(engine.find_migration("testapp", "0001").unwrap()),
+            "m_0001_initial"
+        );
+        assert_eq!(
+            name(engine.find_migration("testapp", "m_0010").unwrap()),
+            "m_0010_first"
+        );
+        assert_eq!(
+            name(engine.find_migration("testapp", "m_0011_second").unwrap()),
+            "m_0011_second"
+        );
+        assert!(matches!(
+            engine.find_migration("testapp", "001"),
+            Err(crate::db::DatabaseError::MigrationError(
+                MigrationEngineError::AmbiguousMigration { .. }
+            ))
+        ));
+        assert!(matches!(
+            engine.find_migration("testapp", "0002"),
+            Err(crate::db::DatabaseError::MigrationError(
+                MigrationEngineError::MigrationNotFound { .. }
+            ))
+        ));
+        assert!(matches!(
+            engine.find_migration("otherapp", "0001"),
+            Err(crate::db::DatabaseError::MigrationError(
+                MigrationEngineError::MigrationNotFound { .. }
+            ))
+        ));
+    }
+
     #[test]
     fn test_operation_create_model() {
         const OPERATION_CREATE_MODEL_FIELDS: &[Field; 2] = &[
@@ -2075,6 +3475,22 @@ mod tests {
         assert!(has_spec!(column_def, ColumnSpec::UniqueKey));
     }
 
+    #[test]
+    fn test_field_to_column_def_auto_uuid() {
+        let field = Field::new(Identifier::new("id"), ColumnType::Uuid)
+            .primary_key()
+            .auto();
+
+        let mut mapper = MockColumnTypeMapper::new();
+        mapper
+            .expect_sea_query_column_type_for()
+            .return_const(sea_query::ColumnType::Uuid);
+        let column_def = field.as_column_def(&mapper);
+
+        assert!(has_spec!(column_def, ColumnSpec::PrimaryKey));
+        assert!(!has_spec!(column_def, ColumnSpec::AutoIncrement));
+    }
+
     #[test]
     fn test_field_to_column_def_without_options() {
         let field = Field::new(Identifier::new("name"), ColumnType::Text);
@@ -2176,6 +3592,228 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_operation_alter_field() {
+        let operation = Operation::alter_field()
+            .table_name(Identifier::new("testapp__test_model"))
+            .old_field(Field::new(
+                Identifier::new("count"),
+                <i32 as DatabaseField>::TYPE,
+            ))
+            .new_field(Field::new(
+                Identifier::new("count"),
+                <i64 as DatabaseField>::TYPE,
+            ))
+            .build();
+
+        if let OperationInner::AlterField {
+            table_name,
+            old_field,
+            new_field,
+        } = operation.inner
+        {
+            assert_eq!(table_name.to_string(), "testapp__test_model");
+            assert_eq!(old_field.ty, ColumnType::Integer);
+            assert_eq!(new_field.ty, ColumnType::BigInteger);
+        } else {
+            panic!("Expected OperationInner::AlterField");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "`new_field` is required")]
+    fn test_alter_field_builder_missing_new_field() {
+        let _ = AlterFieldBuilder::new()
+            .table_name(Identifier::new("testapp__test_model"))
+            .old_field(Field::new(
+                Identifier::new("count"),
+                <i32 as DatabaseField>::TYPE,
+            ))
+            .build();
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_alter_field_operation(test_db: &mut TestDatabase) {
+        const FIELDS: &[Field] = &[
+            Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        ];
+        let database = test_db.database();
+        Operation::create_model()
+            .table_name(Identifier::new("testapp__test_model"))
+            .fields(FIELDS)
+            .build()
+            .forwards(&database)
+            .await
+            .unwrap();
+        database
+            .raw("INSERT INTO testapp__test_model (name) VALUES ('test')")
+            .await
+            .unwrap();
+
+        let operation = Operation::alter_field()
+            .table_name(Identifier::new("testapp__test_model"))
+            .old_field(FIELDS[1])
+            .new_field(
+                Field::new(
+                    Identifier::new("name"),
+                    <Option<String> as DatabaseField>::TYPE,
+                )
+                .null()
+                .unique(),
+            )
+            .build();
+
+        operation.forwards(&database).await.unwrap();
+        database
+            .raw("INSERT INTO testapp__test_model (name) VALUES (NULL)")
+            .await
+            .unwrap();
+        let duplicate = database
+            .raw("INSERT INTO testapp__test_model (name) VALUES ('test')")
+            .await;
+        assert!(duplicate.is_err());
+
+        database
+            .raw("DELETE FROM testapp__test_model WHERE name IS NULL")
+            .await
+            .unwrap();
+        operation.backwards(&database).await.unwrap();
+        let null_name = database
+            .raw("INSERT INTO testapp__test_model (name) VALUES (NULL)")
+            .await;
+        assert!(null_name.is_err());
+        let result = database
+            .raw("UPDATE testapp__test_model SET name = 'updated' WHERE name = 'test'")
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected(), RowsNum(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "`index` is required")]
+    fn test_add_index_builder_missing_index() {
+        let _ = AddIndexBuilder::new()
+            .table_name(Identifier::new("testapp__test_model"))
+            .build();
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_index_operations(test_db: &mut TestDatabase) {
+        const FIELDS: &[Field] = &[
+            Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("tenant"), <i32 as DatabaseField>::TYPE),
+            Field::new(Identifier::new("slug"), <String as DatabaseField>::TYPE),
+        ];
+        const INDEX: Index = Index::new(
+            Identifier::new("testapp__test_model_tenant_slug_uniq"),
+            &[Identifier::new("tenant"), Identifier::new("slug")],
+        )
+        .unique();
+        let database = test_db.database();
+        Operation::create_model()
+            .table_name(Identifier::new("testapp__test_model"))
+            .fields(FIELDS)
+            .build()
+            .forwards(&database)
+            .await
+            .unwrap();
+        let insert = async |tenant: i32| {
+            database
+                .raw(&format!(
+                    "INSERT INTO testapp__test_model (tenant, slug) VALUES ({tenant}, 'slug')"
+                ))
+                .await
+        };
+        insert(1).await.unwrap();
+
+        let operation = Operation::add_index()
+            .table_name(Identifier::new("testapp__test_model"))
+            .index(INDEX)
+            .build();
+        operation.forwards(&database).await.unwrap();
+        insert(2).await.unwrap();
+        assert!(insert(1).await.is_err());
+
+        operation.backwards(&database).await.unwrap();
+        insert(1).await.unwrap();
+
+        let operation = Operation::remove_index()
+            .table_name(Identifier::new("testapp__test_model"))
+            .index(INDEX)
+            .build();
+        // the index can't be created with duplicate rows
+        assert!(operation.backwards(&database).await.is_err());
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_alter_field_operation_referenced_table(test_db: &mut TestDatabase) {
+        const PARENT_FIELDS: &[Field] = &[
+            Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("count"), <i32 as DatabaseField>::TYPE),
+        ];
+        const CHILD_FIELDS: &[Field] = &[
+            Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("parent"), <i32 as DatabaseField>::TYPE).foreign_key(
+                Identifier::new("testapp__parent"),
+                Identifier::new("id"),
+                ForeignKeyOnDeletePolicy::Cascade,
+                ForeignKeyOnUpdatePolicy::Cascade,
+            ),
+        ];
+        let database = test_db.database();
+        for (table_name, fields) in [
+            ("testapp__parent", PARENT_FIELDS),
+            ("testapp__child", CHILD_FIELDS),
+        ] {
+            Operation::create_model()
+                .table_name(Identifier::new(table_name))
+                .fields(fields)
+                .build()
+                .forwards(&database)
+                .await
+                .unwrap();
+        }
+        database
+            .raw("INSERT INTO testapp__parent (count) VALUES (1)")
+            .await
+            .unwrap();
+        database
+            .raw("INSERT INTO testapp__child (parent) VALUES (1)")
+            .await
+            .unwrap();
+
+        Operation::alter_field()
+            .table_name(Identifier::new("testapp__parent"))
+            .old_field(PARENT_FIELDS[1])
+            .new_field(Field::new(
+                Identifier::new("count"),
+                <i64 as DatabaseField>::TYPE,
+            ))
+            .build()
+            .forwards(&database)
+            .await
+            .unwrap();
+
+        let result = database
+            .raw("DELETE FROM testapp__child WHERE parent = 1")
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected(), RowsNum(1));
+        let invalid_reference = database
+            .raw("INSERT INTO testapp__child (parent) VALUES (2)")
+            .await;
+        assert!(invalid_reference.is_err());
+    }
+
     #[test]
     fn test_remove_field_builder_new() {
         let builder = RemoveFieldBuilder::new();
@@ -62,15 +62,12 @@ impl<'a, T: DynMigration> MigrationSorter<'a, T> {
     }
 
     fn toposort(&mut self) -> Result<()> {
-        let lookup = Self::create_lookup_table(self.migrations)?;
+        let dependencies = Self::resolve_dependencies(self.migrations)?;
         let mut graph = Graph::new(self.migrations.len());
 
-        for (index, migration) in self.migrations.iter().enumerate() {
-            for dependency in migration.dependencies() {
-                let dependency_index = lookup
-                    .get(&MigrationLookup::from(dependency))
-                    .ok_or(MigrationSorterError::InvalidDependency(*dependency))?;
-                graph.add_edge(*dependency_index, index);
+        for (index, migration_dependencies) in dependencies.iter().enumerate() {
+            for &dependency_index in migration_dependencies {
+                graph.add_edge(dependency_index, index);
             }
         }
 
@@ -80,6 +77,28 @@ impl<'a, T: DynMigration> MigrationSorter<'a, T> {
         Ok(())
     }
 
+    /// Returns, for each migration, the indices of the migrations it directly
+    /// depends on.
+    pub(super) fn resolve_dependencies(migrations: &[T]) -> Result<Vec<Vec<usize>>> {
+        let lookup = Self::create_lookup_table(migrations)?;
+
+        migrations
+            .iter()
+            .map(|migration| {
+                migration
+                    .dependencies()
+                    .iter()
+                    .map(|dependency| {
+                        lookup
+                            .get(&MigrationLookup::from(dependency))
+                            .copied()
+                            .ok_or(MigrationSorterError::InvalidDependency(*dependency))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     fn create_lookup_table(migrations: &[T]) -> Result<HashMap<MigrationLookup<'_>, usize>> {
         let mut map = HashMap::with_capacity(migrations.len());
 
@@ -256,6 +275,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolve_dependencies() {
+        let migrations = vec![
+            TestMigration::new(
+                "app1",
+                "migration1",
+                [],
+                [Operation::create_model()
+                    .table_name(Identifier::new("model1"))
+                    .fields(&[])
+                    .build()],
+            ),
+            TestMigration::new(
+                "app2",
+                "migration1",
+                [
+                    MigrationDependency::migration("app1", "migration1"),
+                    MigrationDependency::model("app1", Identifier::new("model1")),
+                ],
+                [],
+            ),
+        ];
+
+        let dependencies = MigrationSorter::resolve_dependencies(&migrations).unwrap();
+
+        assert_eq!(dependencies, vec![vec![], vec![0, 0]]);
+    }
+
+    #[test]
+    fn resolve_dependencies_invalid() {
+        let migrations = vec![TestMigration::new(
+            "app1",
+            "migration1",
+            [MigrationDependency::migration("app1", "missing")],
+            [],
+        )];
+
+        assert_eq!(
+            MigrationSorter::resolve_dependencies(&migrations).unwrap_err(),
+            MigrationSorterError::InvalidDependency(MigrationDependency::migration(
+                "app1", "missing"
+            ))
+        );
+    }
+
     // migration names must be &'static str
     const MIGRATION_NAMES: [&str; 100] = [
         "m0", "m1", "m2", "m3", "m4", "m5", "m6", "m7", "m8", "m9", "m10", "m11", "m12", "m13",
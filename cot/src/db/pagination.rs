@@ -0,0 +1,272 @@
+//! Pagination of the database query results.
+
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+
+use serde::Serialize;
+
+use crate::db::query::Query;
+use crate::db::{Database, Model, Result};
+
+/// A helper for splitting the results of a [`Query`] into pages.
+///
+/// Page numbers start at 1. The query is executed twice for each page: once
+/// to count all the matching rows, and once to retrieve the rows of the page.
+/// To get stable pages, the query should be ordered with
+/// [`Query::order_by`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::query::Query;
+/// use cot::db::{Database, Paginator, model};
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: i32,
+///     title: String,
+/// }
+///
+/// # async fn run(db: &Database) -> cot::db::Result<()> {
+/// let mut query = Query::<Post>::new();
+/// query.order_by(PostFields::id.desc());
+///
+/// let page = Paginator::new(query, 20).page(db, 2).await?;
+/// for post in page.items() {
+///     println!("{}", post.title);
+/// }
+/// println!("page {} of {}", page.number(), page.num_pages());
+/// # Ok(())
+/// # }
+/// ```
+pub struct Paginator<T> {
+    query: Query<T>,
+    page_size: u64,
+}
+
+// manual implementation to avoid `T: Debug` in the trait bounds
+impl<T> Debug for Paginator<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Paginator")
+            .field("query", &self.query)
+            .field("page_size", &self.page_size)
+            .finish()
+    }
+}
+
+// manual implementation to avoid `T: Clone` in the trait bounds
+impl<T> Clone for Paginator<T> {
+    fn clone(&self) -> Self {
+        Self {
+            query: self.query.clone(),
+            page_size: self.page_size,
+        }
+    }
+}
+
+impl<T: Model> Paginator<T> {
+    /// Creates a new paginator returning pages of `page_size` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_size` is 0.
+    #[must_use]
+    pub fn new(query: Query<T>, page_size: u64) -> Self {
+        assert!(page_size > 0, "Page size must be greater than 0");
+
+        Self { query, page_size }
+    }
+
+    /// Returns the number of items per page.
+    #[must_use]
+    pub fn page_size(&self) -> u64 {
+        self.page_size
+    }
+
+    /// Retrieves the page with given number from the database.
+    ///
+    /// Page numbers start at 1; the number 0 is treated as 1. If the number is
+    /// larger than the number of pages, the returned page is empty, which can
+    /// be checked with [`Page::is_out_of_range`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn page(&self, db: &Database, number: u64) -> Result<Page<T>> {
+        let number = number.max(1);
+        let total_count = self.query.count(db).await?;
+
+        let items = if (number - 1) * self.page_size < total_count {
+            let mut query = self.query.clone();
+            query
+                .limit(self.page_size)
+                .offset((number - 1) * self.page_size);
+            query.all(db).await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Page {
+            items,
+            number,
+            size: self.page_size,
+            total_count,
+        })
+    }
+}
+
+/// A single page of the query results, as returned by [`Paginator::page`].
+///
+/// The page can be passed directly to a template, or serialized (e.g. as a
+/// JSON response) if the items are serializable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Page<T> {
+    items: Vec<T>,
+    number: u64,
+    #[serde(rename = "page_size")]
+    size: u64,
+    total_count: u64,
+}
+
+impl<T> Page<T> {
+    /// Returns the items on this page.
+    #[must_use]
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Consumes the page and returns the items on it.
+    #[must_use]
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    /// Returns the number of this page, starting at 1.
+    #[must_use]
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the maximum number of items per page.
+    #[must_use]
+    pub fn page_size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the total number of items on all pages.
+    #[must_use]
+    pub fn total_count(&self) -> u64 {
+        self.total_count
+    }
+
+    /// Returns the total number of pages. There is always at least one page,
+    /// even if there are no items.
+    #[must_use]
+    pub fn num_pages(&self) -> u64 {
+        self.total_count.div_ceil(self.size).max(1)
+    }
+
+    /// Returns the range of all page numbers, i.e. `1..=num_pages`.
+    #[must_use]
+    pub fn page_numbers(&self) -> RangeInclusive<u64> {
+        1..=self.num_pages()
+    }
+
+    /// Returns whether the page number is larger than the number of pages.
+    #[must_use]
+    pub fn is_out_of_range(&self) -> bool {
+        self.number > self.num_pages()
+    }
+
+    /// Returns whether there is a page after this one.
+    #[must_use]
+    pub fn has_next(&self) -> bool {
+        self.number < self.num_pages()
+    }
+
+    /// Returns whether there is a page before this one.
+    #[must_use]
+    pub fn has_previous(&self) -> bool {
+        self.number > 1
+    }
+
+    /// Returns the number of the next page, if there is one.
+    #[must_use]
+    pub fn next_page_number(&self) -> Option<u64> {
+        self.has_next().then(|| self.number + 1)
+    }
+
+    /// Returns the number of the previous page, if there is one.
+    #[must_use]
+    pub fn previous_page_number(&self) -> Option<u64> {
+        self.has_previous().then(|| self.number - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(number: u64, total_count: u64) -> Page<i32> {
+        Page {
+            items: Vec::new(),
+            number,
+            size: 10,
+            total_count,
+        }
+    }
+
+    #[test]
+    fn page_numbers() {
+        let page = page(2, 25);
+
+        assert_eq!(page.num_pages(), 3);
+        assert_eq!(page.page_numbers(), 1..=3);
+        assert!(page.has_next());
+        assert!(page.has_previous());
+        assert_eq!(page.next_page_number(), Some(3));
+        assert_eq!(page.previous_page_number(), Some(1));
+        assert!(!page.is_out_of_range());
+    }
+
+    #[test]
+    fn page_first_and_last() {
+        let first = page(1, 20);
+        let last = page(2, 20);
+
+        assert!(!first.has_previous());
+        assert_eq!(first.previous_page_number(), None);
+        assert!(!last.has_next());
+        assert_eq!(last.next_page_number(), None);
+    }
+
+    #[test]
+    fn page_empty() {
+        let page = page(1, 0);
+
+        assert_eq!(page.num_pages(), 1);
+        assert!(!page.has_next());
+        assert!(!page.is_out_of_range());
+    }
+
+    #[test]
+    fn page_out_of_range() {
+        let page = page(4, 25);
+
+        assert!(page.is_out_of_range());
+        assert!(!page.has_next());
+    }
+
+    #[test]
+    #[should_panic(expected = "Page size must be greater than 0")]
+    fn paginator_zero_page_size() {
+        #[crate::db::model]
+        struct MockModel {
+            #[model(primary_key)]
+            id: i32,
+        }
+
+        let _ = Paginator::new(Query::<MockModel>::new(), 0);
+    }
+}
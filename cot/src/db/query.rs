@@ -4,11 +4,13 @@ use std::marker::PhantomData;
 
 use derive_more::with_trait::Debug;
 use sea_query::{ExprTrait, IntoColumnRef};
+use serde::Serialize;
 
 use crate::db;
+use crate::db::search::SearchExprs;
 use crate::db::{
-    Auto, Database, DatabaseBackend, DbFieldValue, DbValue, ForeignKey, FromDbValue, Identifier,
-    Model, StatementResult, ToDbFieldValue,
+    Auto, ColumnType, Database, DatabaseBackend, DatabaseField, DbFieldValue, DbValue, ForeignKey,
+    FromDbValue, FromRow, Identifier, Json, LimitedString, Model, StatementResult, ToDbFieldValue,
 };
 
 /// A query that can be executed on a database. Can be used to filter, update,
@@ -32,8 +34,13 @@ use crate::db::{
 /// ```
 pub struct Query<T> {
     filter: Option<Expr>,
+    group_by: Vec<Identifier>,
+    order_by: Vec<OrderBy>,
     limit: Option<u64>,
     offset: Option<u64>,
+    with_deleted: bool,
+    search: Option<String>,
+    database: Option<String>,
     phantom_data: PhantomData<fn() -> T>,
 }
 
@@ -42,8 +49,13 @@ impl<T> Debug for Query<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Query")
             .field("filter", &self.filter)
+            .field("group_by", &self.group_by)
+            .field("order_by", &self.order_by)
             .field("limit", &self.limit)
             .field("offset", &self.offset)
+            .field("with_deleted", &self.with_deleted)
+            .field("search", &self.search)
+            .field("database", &self.database)
             .field("phantom_data", &self.phantom_data)
             .finish()
     }
@@ -54,8 +66,13 @@ impl<T> Clone for Query<T> {
     fn clone(&self) -> Self {
         Self {
             filter: self.filter.clone(),
+            group_by: self.group_by.clone(),
+            order_by: self.order_by.clone(),
             limit: self.limit,
             offset: self.offset,
+            with_deleted: self.with_deleted,
+            search: self.search.clone(),
+            database: self.database.clone(),
             phantom_data: PhantomData,
         }
     }
@@ -65,6 +82,11 @@ impl<T> Clone for Query<T> {
 impl<T> PartialEq for Query<T> {
     fn eq(&self, other: &Self) -> bool {
         self.filter == other.filter
+            && self.group_by == other.group_by
+            && self.order_by == other.order_by
+            && self.with_deleted == other.with_deleted
+            && self.search == other.search
+            && self.database == other.database
     }
 }
 
@@ -97,8 +119,13 @@ impl<T: Model> Query<T> {
     pub fn new() -> Self {
         Self {
             filter: None,
+            group_by: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
             offset: None,
+            with_deleted: false,
+            search: None,
+            database: None,
             phantom_data: PhantomData,
         }
     }
@@ -172,6 +199,161 @@ impl<T: Model> Query<T> {
         self
     }
 
+    /// Add an ordering to the query.
+    ///
+    /// Can be called multiple times to order by multiple fields; the
+    /// orderings are then applied in the order they were added.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::Query;
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    ///     age: i32,
+    /// }
+    ///
+    /// let query = Query::<User>::new()
+    ///     .order_by(UserFields::age.desc())
+    ///     .order_by(UserFields::name.asc());
+    /// ```
+    pub fn order_by(&mut self, order_by: OrderBy) -> &mut Self {
+        self.order_by.push(order_by);
+        self
+    }
+
+    /// Add a field to group the results by.
+    ///
+    /// This only affects the queries executed with [`Query::aggregate`]: the
+    /// aggregates are then computed separately for each group and the values
+    /// of the grouped fields are returned before the aggregates in each row.
+    /// Can be called multiple times to group by multiple fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::Query;
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    ///     age: i32,
+    /// }
+    ///
+    /// let query = Query::<User>::new().group_by(UserFields::age);
+    /// ```
+    pub fn group_by<F: Into<Identifier>>(&mut self, field: F) -> &mut Self {
+        self.group_by.push(field.into());
+        self
+    }
+
+    /// Include the soft-deleted rows in the query results.
+    ///
+    /// By default, the queries on models declared with
+    /// `#[model(soft_delete)]` skip the rows that have been deleted with
+    /// [`Query::delete`]. This method disables that filter. It has no effect
+    /// on models that don't use soft deletes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::Query;
+    ///
+    /// #[model(soft_delete)]
+    /// struct Invoice {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     number: String,
+    /// }
+    ///
+    /// let query = Query::<Invoice>::new().with_deleted();
+    /// ```
+    pub fn with_deleted(&mut self) -> &mut Self {
+        self.with_deleted = true;
+        self
+    }
+
+    /// Search for the rows matching the given term in the fields declared with
+    /// `#[model(search(fields = [...]))]`.
+    ///
+    /// On PostgreSQL, this uses its full-text search: the search fields are
+    /// converted to a `tsvector` and matched against the term parsed with
+    /// `websearch_to_tsquery` (so it can contain quoted phrases, `or`, and
+    /// words excluded with `-`), using the default text search configuration
+    /// of the database. The other backends fall back to matching the rows in
+    /// which each word of the term is contained in at least one of the search
+    /// fields, ignoring the case.
+    ///
+    /// The results are ordered by how well they match the term, after any
+    /// ordering added with [`Query::order_by`]. The search is combined with
+    /// the filter of the query, and an empty term matches all the rows. If the
+    /// model doesn't declare any search fields, no rows match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::Query;
+    ///
+    /// #[model(search(fields = ["title", "body"]))]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     title: String,
+    ///     body: String,
+    /// }
+    ///
+    /// let query = Query::<Post>::new().search("rust web framework");
+    /// ```
+    pub fn search<S: Into<String>>(&mut self, term: S) -> &mut Self {
+        self.search = Some(term.into());
+        self
+    }
+
+    /// Run the query on the database with the given name, instead of the one
+    /// chosen by the [`DatabaseRouter`](crate::db::DatabaseRouter).
+    ///
+    /// The name must be one of the databases configured in the `[database]`
+    /// section of the config (or added with
+    /// [`Database::with_database`]), or
+    /// [`DEFAULT_DATABASE`](crate::db::DEFAULT_DATABASE). Otherwise, executing
+    /// the query returns
+    /// [`DatabaseError::UnknownDatabase`](crate::db::DatabaseError::UnknownDatabase).
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::Query;
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// let query = Query::<User>::new().using("replica");
+    /// ```
+    pub fn using<N: Into<String>>(&mut self, name: N) -> &mut Self {
+        self.database = Some(name.into());
+        self
+    }
+
+    pub(super) fn database_name(&self) -> Option<&str> {
+        self.database.as_deref()
+    }
+
     /// Execute the query and return all results.
     ///
     /// # Errors
@@ -193,92 +375,1072 @@ impl<T: Model> Query<T> {
 
     /// Execute the query and return the number of results.
     ///
-    /// # Errors
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn count(&self, db: &Database) -> db::Result<u64> {
+        let db = db.route::<T>(self.database_name(), false)?;
+        let mut select = sea_query::Query::select();
+        select
+            .from(T::TABLE_NAME)
+            .expr(sea_query::Expr::col(sea_query::Asterisk).count());
+        self.add_filter_to_statement(&mut select);
+        self.add_search_to_statement(&mut select, db);
+        let row = db.fetch_option(&select).await?;
+        let count = match row {
+            #[expect(clippy::cast_sign_loss)]
+            Some(row) => row.get::<i64>(0)? as u64,
+            None => 0,
+        };
+        Ok(count)
+    }
+
+    /// Execute the query and return the sum of the values of given field.
+    ///
+    /// The sum of an integer field is always returned as an `i64`, and the
+    /// sum of a floating-point field as an `f64`. The result type should be
+    /// wrapped in an `Option`, as the sum is `NULL` if there are no matching
+    /// rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{Database, model, query};
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     age: i32,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let total_age: Option<i64> = query!(User, $age > 18).sum(db, UserFields::age).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sum<F: DatabaseField, V: FromDbValue>(
+        &self,
+        db: &Database,
+        field: FieldRef<F>,
+    ) -> db::Result<V> {
+        self.aggregate_single::<V>(db, Aggregate::sum(field)).await
+    }
+
+    /// Execute the query and return the average of the values of given field
+    /// as an `f64`, or `None` if there are no matching rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{Database, model, query};
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     age: i32,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let average_age = query!(User, $age > 18).avg(db, UserFields::age).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn avg<F: DatabaseField>(
+        &self,
+        db: &Database,
+        field: FieldRef<F>,
+    ) -> db::Result<Option<f64>> {
+        self.aggregate_single::<Option<f64>>(db, Aggregate::avg(field))
+            .await
+    }
+
+    /// Execute the query and return the minimum value of given field.
+    ///
+    /// The result type should be wrapped in an `Option`, as the minimum is
+    /// `NULL` if there are no matching rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{Database, model, query};
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     age: i32,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let youngest: Option<i32> = query!(User, $age > 18).min(db, UserFields::age).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn min<F: DatabaseField, V: FromDbValue>(
+        &self,
+        db: &Database,
+        field: FieldRef<F>,
+    ) -> db::Result<V> {
+        self.aggregate_single::<V>(db, Aggregate::min(field)).await
+    }
+
+    /// Execute the query and return the maximum value of given field.
+    ///
+    /// The result type should be wrapped in an `Option`, as the maximum is
+    /// `NULL` if there are no matching rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{Database, model, query};
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     age: i32,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let oldest: Option<i32> = query!(User, $age > 18).max(db, UserFields::age).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn max<F: DatabaseField, V: FromDbValue>(
+        &self,
+        db: &Database,
+        field: FieldRef<F>,
+    ) -> db::Result<V> {
+        self.aggregate_single::<V>(db, Aggregate::max(field)).await
+    }
+
+    /// Execute the query computing given aggregates and return the results
+    /// converted to the given type.
+    ///
+    /// Each returned row contains the values of the fields passed to
+    /// [`Query::group_by`] (in the order they were added), followed by the
+    /// values of the aggregates. If the query is not grouped, a single row is
+    /// returned. The row can be converted to a tuple or to a custom struct
+    /// implementing [`FromRow`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    ///
+    /// Returns an error if the values returned by the database can't be
+    /// converted to the given type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::query::{Aggregate, Query};
+    /// use cot::db::{Database, FromRow, Row, model};
+    ///
+    /// #[model]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     author: String,
+    ///     views: i32,
+    /// }
+    ///
+    /// struct AuthorStats {
+    ///     author: String,
+    ///     post_count: i64,
+    ///     total_views: Option<i64>,
+    /// }
+    ///
+    /// impl FromRow for AuthorStats {
+    ///     fn from_row(row: &Row) -> cot::db::Result<Self> {
+    ///         Ok(Self {
+    ///             author: row.get(0)?,
+    ///             post_count: row.get(1)?,
+    ///             total_views: row.get(2)?,
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let stats: Vec<AuthorStats> = Query::<Post>::new()
+    ///     .group_by(PostFields::author)
+    ///     .aggregate(db, &[Aggregate::count(), Aggregate::sum(PostFields::views)])
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn aggregate<R: FromRow>(
+        &self,
+        db: &Database,
+        aggregates: &[Aggregate],
+    ) -> db::Result<Vec<R>> {
+        let db = db.route::<T>(self.database_name(), false)?;
+        let mut select = sea_query::Query::select();
+        select.from(T::TABLE_NAME);
+        for &field in &self.group_by {
+            select.column(field);
+        }
+        for aggregate in aggregates {
+            select.expr(aggregate.as_sea_query_expr(db));
+        }
+        self.add_filter_to_statement(&mut select);
+        self.add_search_to_statement(&mut select, db);
+        select.group_by_columns(self.group_by.iter().copied());
+        self.add_order_by_to_statement(&mut select);
+        self.add_limit_to_statement(&mut select);
+        self.add_offset_to_statement(&mut select);
+
+        db.fetch_all(&select)
+            .await?
+            .iter()
+            .map(R::from_row)
+            .collect()
+    }
+
+    async fn aggregate_single<V: FromDbValue>(
+        &self,
+        db: &Database,
+        aggregate: Aggregate,
+    ) -> db::Result<V> {
+        let mut query = self.clone();
+        query.group_by.clear();
+        query.order_by.clear();
+        query.limit = None;
+        query.offset = None;
+        // aggregate queries without grouping always return exactly one row
+        let (value,) = query
+            .aggregate::<(V,)>(db, &[aggregate])
+            .await?
+            .pop()
+            .ok_or(sqlx::Error::RowNotFound)?;
+        Ok(value)
+    }
+
+    /// Execute the query and check if any results exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn exists<DB: DatabaseBackend>(&self, db: &DB) -> db::Result<bool> {
+        db.exists(self).await
+    }
+
+    /// Delete all rows that match the query.
+    ///
+    /// For models declared with `#[model(soft_delete)]`, the rows are not
+    /// removed from the database; instead, their `deleted_at` column is set to
+    /// the current time, which hides them from the subsequent queries. The
+    /// rows can be brought back with [`Query::restore`], or removed for good
+    /// with [`Query::hard_delete`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn delete<DB: DatabaseBackend>(&self, db: &DB) -> db::Result<StatementResult> {
+        db.delete(self).await
+    }
+
+    /// Permanently delete all rows that match the query, even if the model
+    /// uses soft deletes.
+    ///
+    /// Just like for the other queries, the soft-deleted rows are only
+    /// matched if [`Query::with_deleted`] has been called. For models that
+    /// don't use soft deletes, this is the same as [`Query::delete`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn hard_delete<DB: DatabaseBackend>(&self, db: &DB) -> db::Result<StatementResult> {
+        db.hard_delete(self).await
+    }
+
+    /// Restore all soft-deleted rows that match the query, by clearing their
+    /// `deleted_at` column.
+    ///
+    /// The soft-deleted rows are always matched by this method, regardless of
+    /// whether [`Query::with_deleted`] has been called. For models that don't
+    /// use soft deletes, this does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{Database, model, query};
+    ///
+    /// #[model(soft_delete)]
+    /// struct Invoice {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     number: String,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// query!(Invoice, $number == "2024/001").delete(db).await?;
+    /// assert!(!query!(Invoice, $number == "2024/001").exists(db).await?);
+    ///
+    /// query!(Invoice, $number == "2024/001").restore(db).await?;
+    /// assert!(query!(Invoice, $number == "2024/001").exists(db).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restore<DB: DatabaseBackend>(&self, db: &DB) -> db::Result<StatementResult> {
+        db.restore(self).await
+    }
+
+    /// Update all rows that match the query, setting the columns to the given
+    /// values.
+    ///
+    /// The rows are updated with a single `UPDATE` statement, without loading
+    /// them from the database. The assignments can be created with
+    /// [`FieldRef::set`] and [`FieldRef::set_expr`]. The limit, offset, and
+    /// ordering of the query are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::query::ExprAdd;
+    /// use cot::db::{Database, model, query};
+    ///
+    /// #[model]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     published: bool,
+    ///     views: i32,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let result = query!(Post, $published == false)
+    ///     .update_all(
+    ///         db,
+    ///         [
+    ///             PostFields::published.set(true),
+    ///             PostFields::views.set_expr(PostFields::views.add(1)),
+    ///         ],
+    ///     )
+    ///     .await?;
+    /// println!("published {} posts", result.rows_affected());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn update_all<I: IntoIterator<Item = Assignment>>(
+        &self,
+        db: &Database,
+        assignments: I,
+    ) -> db::Result<StatementResult> {
+        let values: Vec<_> = assignments
+            .into_iter()
+            .map(|assignment| (assignment.column, assignment.value.as_sea_query_expr()))
+            .collect();
+        if values.is_empty() {
+            return Ok(StatementResult {
+                rows_affected: db::RowsNum(0),
+                last_inserted_row_id: None,
+            });
+        }
+
+        let db = db.route::<T>(self.database_name(), true)?;
+        let mut update = sea_query::Query::update();
+        update.table(T::TABLE_NAME).values(values);
+        self.add_filter_to_statement(&mut update);
+        self.add_search_to_statement(&mut update, db);
+
+        db.execute_statement(&update).await
+    }
+
+    pub(super) fn add_filter_to_statement<S: sea_query::ConditionalStatement>(
+        &self,
+        statement: &mut S,
+    ) {
+        if let Some(filter) = &self.filter {
+            statement.and_where(filter.as_sea_query_expr());
+        }
+        if let Some(column) = self.soft_delete_column() {
+            statement.and_where(sea_query::Expr::col(column).is_null());
+        }
+    }
+
+    pub(super) fn filter_condition(&self) -> sea_query::Condition {
+        let mut condition = sea_query::Condition::all();
+        if let Some(filter) = &self.filter {
+            condition = condition.add(filter.as_sea_query_expr());
+        }
+        if let Some(column) = self.soft_delete_column() {
+            condition = condition.add(sea_query::Expr::col(column).is_null());
+        }
+        condition
+    }
+
+    /// Adds the condition of the search (see [`Query::search`]) to the
+    /// statement executed on the given database.
+    pub(super) fn add_search_to_statement<S: sea_query::ConditionalStatement>(
+        &self,
+        statement: &mut S,
+        db: &Database,
+    ) {
+        if let Some(condition) = self.search_condition(db) {
+            statement.and_where(condition);
+        }
+    }
+
+    /// Returns the condition of the search (see [`Query::search`]), if any.
+    pub(super) fn search_condition(&self, db: &Database) -> Option<sea_query::SimpleExpr> {
+        self.search_exprs(db).map(|search| search.condition)
+    }
+
+    /// Orders the results by their search rank (see [`Query::search`]).
+    pub(super) fn add_search_rank_to_statement(
+        &self,
+        statement: &mut sea_query::SelectStatement,
+        db: &Database,
+    ) {
+        if let Some(search) = self.search_exprs(db) {
+            statement.order_by_expr(search.rank, sea_query::Order::Desc);
+        }
+    }
+
+    fn search_exprs(&self, db: &Database) -> Option<SearchExprs> {
+        SearchExprs::new(
+            T::SEARCH_FIELDS,
+            self.search.as_deref()?,
+            db.supports_full_text_search(),
+        )
+    }
+
+    /// Returns the soft delete column of the model if the soft-deleted rows
+    /// should be excluded from the query.
+    fn soft_delete_column(&self) -> Option<Identifier> {
+        if self.with_deleted {
+            None
+        } else {
+            T::SOFT_DELETE_COLUMN
+        }
+    }
+
+    pub(super) fn add_order_by_to_statement(&self, statement: &mut sea_query::SelectStatement) {
+        for order_by in &self.order_by {
+            statement.order_by(order_by.column, order_by.order.into());
+        }
+    }
+
+    pub(super) fn add_limit_to_statement(&self, statement: &mut sea_query::SelectStatement) {
+        if let Some(limit) = self.limit {
+            statement.limit(limit);
+        }
+    }
+
+    pub(super) fn add_offset_to_statement(&self, statement: &mut sea_query::SelectStatement) {
+        if let Some(offset) = self.offset {
+            statement.offset(offset);
+        }
+    }
+}
+
+/// An expression that can be used to filter, update, or delete rows.
+///
+/// This is used to create complex queries with multiple conditions. Typically,
+/// it is only internally used by the [`cot::db::query!`] macro to create a
+/// [`Query`].
+///
+/// # Example
+///
+/// ```
+/// use cot::db::{model, query};
+/// use cot::db::query::{Expr, Query};
+///
+/// #[model]
+/// struct MyModel {
+///     #[model(primary_key)]
+///     id: i32,
+/// };
+///
+/// let expr = Expr::eq(Expr::field("id"), Expr::value(5));
+///
+/// assert_eq!(
+///     <Query<MyModel>>::new().filter(expr),
+///     query!(MyModel, $id == 5)
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// An expression containing a reference to a column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::eq(Expr::field("id"), Expr::value(5));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id == 5)
+    /// );
+    /// ```
+    Field(Identifier),
+    /// An expression containing a literal value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::ne(Expr::field("id"), Expr::value(5));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id != 5)
+    /// );
+    /// ```
+    Value(DbValue),
+    /// An `AND` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::and(
+    ///     Expr::gt(Expr::field("id"), Expr::value(10)),
+    ///     Expr::lt(Expr::field("id"), Expr::value(20))
+    /// );
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id > 10 && $id < 20)
+    /// );
+    /// ```
+    And(Box<Expr>, Box<Expr>),
+    /// An `OR` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::or(
+    ///     Expr::gt(Expr::field("id"), Expr::value(10)),
+    ///     Expr::lt(Expr::field("id"), Expr::value(20))
+    /// );
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id > 10 || $id < 20)
+    /// );
+    /// ```
+    Or(Box<Expr>, Box<Expr>),
+    /// An `=` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::eq(Expr::field("id"), Expr::value(5));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id == 5)
+    /// );
+    /// ```
+    Eq(Box<Expr>, Box<Expr>),
+    /// A `!=` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::ne(Expr::field("id"), Expr::value(5));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id != 5)
+    /// );
+    /// ```
+    Ne(Box<Expr>, Box<Expr>),
+    /// A `<` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::lt(Expr::field("id"), Expr::value(5));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id < 5)
+    /// );
+    /// ```
+    Lt(Box<Expr>, Box<Expr>),
+    /// A `<=` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::lte(Expr::field("id"), Expr::value(5));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id <= 5)
+    /// );
+    /// ```
+    Lte(Box<Expr>, Box<Expr>),
+    /// A `>` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::gt(Expr::field("id"), Expr::value(5));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id > 5)
+    /// );
+    /// ```
+    Gt(Box<Expr>, Box<Expr>),
+    /// A `>=` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::gte(Expr::field("id"), Expr::value(5));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id >= 5)
+    /// );
+    /// ```
+    Gte(Box<Expr>, Box<Expr>),
+    /// A `+` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     id_2: i32,
+    /// };
+    ///
+    /// let expr = Expr::eq(Expr::field("id"), Expr::add(Expr::field("id_2"), Expr::value(5)));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id == $id_2 + 5)
+    /// );
+    /// ```
+    Add(Box<Expr>, Box<Expr>),
+    /// A `-` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     id_2: i32,
+    /// };
+    ///
+    /// let expr = Expr::eq(Expr::field("id"), Expr::sub(Expr::field("id_2"), Expr::value(5)));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id == $id_2 - 5)
+    /// );
+    /// ```
+    Sub(Box<Expr>, Box<Expr>),
+    /// A `*` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     id_2: i32,
+    /// };
+    ///
+    /// let expr = Expr::eq(Expr::field("id"), Expr::mul(Expr::field("id_2"), Expr::value(2)));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id == $id_2 * 2)
+    /// );
+    /// ```
+    Mul(Box<Expr>, Box<Expr>),
+    /// A `/` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     id_2: i32,
+    /// };
+    ///
+    /// let expr = Expr::eq(Expr::field("id"), Expr::div(Expr::field("id_2"), Expr::value(2)));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id == $id_2 / 2)
+    /// );
+    /// ```
+    Div(Box<Expr>, Box<Expr>),
+    /// An `IN` expression with a subquery selecting a single column from a
+    /// table, filtered by another expression, i.e. `expr IN (SELECT column
+    /// FROM table WHERE filter)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::query::{Expr, Query};
+    /// use cot::db::{Identifier, model};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::in_subquery(
+    ///     Expr::field("id"),
+    ///     Identifier::new("my_model_tags"),
+    ///     Identifier::new("source_id"),
+    ///     Expr::eq(Expr::field("target_id"), Expr::value(5)),
+    /// );
+    /// let query = <Query<MyModel>>::new().filter(expr);
+    /// ```
+    InSubquery {
+        /// The expression to look for in the subquery results.
+        expr: Box<Expr>,
+        /// The table to select from.
+        table: Identifier,
+        /// The column to select.
+        column: Identifier,
+        /// The filter of the subquery.
+        filter: Box<Expr>,
+    },
+    /// A `NOT` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::not(Expr::eq(Expr::field("id"), Expr::value(5)));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, !($id == 5))
+    /// );
+    /// ```
+    Not(Box<Expr>),
+    /// An `IS NULL` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: Option<String>,
+    /// };
+    ///
+    /// let expr = Expr::is_null(Expr::field("name"));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $name.is_null())
+    /// );
+    /// ```
+    IsNull(Box<Expr>),
+    /// An `IN` expression with a list of values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::is_in(Expr::field("id"), [Expr::value(1), Expr::value(2)]);
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id.is_in([1, 2]))
+    /// );
+    /// ```
+    In(Box<Expr>, Vec<Expr>),
+    /// A `BETWEEN` expression. Both bounds are inclusive.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::between(Expr::field("id"), Expr::value(10), Expr::value(20));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id.between(10, 20))
+    /// );
+    /// ```
+    Between(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// An expression that checks whether a string contains given substring.
+    ///
+    /// This is translated to a `LIKE` expression with the special characters
+    /// of the substring escaped. Note that the case sensitivity depends on the
+    /// database backend; notably, `LIKE` is case-insensitive for ASCII
+    /// characters in SQLite and with the default collations in MySQL. Use
+    /// [`Expr::IContains`] if you need a case-insensitive match regardless of
+    /// the backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// };
+    ///
+    /// let expr = Expr::contains(Expr::field("name"), "oh");
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $name.contains("oh"))
+    /// );
+    /// ```
+    Contains(Box<Expr>, String),
+    /// An expression that checks whether a string contains given substring,
+    /// ignoring the case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// };
+    ///
+    /// let expr = Expr::icontains(Expr::field("name"), "OH");
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $name.icontains("OH"))
+    /// );
+    /// ```
+    IContains(Box<Expr>, String),
+    /// An expression that checks whether a string starts with given prefix.
+    ///
+    /// Similarly to [`Expr::Contains`], this is translated to a `LIKE`
+    /// expression, so the case sensitivity depends on the database backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// };
+    ///
+    /// let expr = Expr::starts_with(Expr::field("name"), "Jo");
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $name.starts_with("Jo"))
+    /// );
+    /// ```
+    StartsWith(Box<Expr>, String),
+    /// An expression that checks whether a JSON value contains another JSON
+    /// value, i.e. whether all the keys and values of the right-hand side are
+    /// present in the left-hand side.
+    ///
+    /// This is translated to the `@>` operator and is only supported on
+    /// PostgreSQL, where the fields of type [`Json`](crate::db::Json) are
+    /// stored as `JSONB`.
     ///
-    /// Returns an error if the query fails.
-    pub async fn count(&self, db: &Database) -> db::Result<u64> {
-        let mut select = sea_query::Query::select();
-        select
-            .from(T::TABLE_NAME)
-            .expr(sea_query::Expr::col(sea_query::Asterisk).count());
-        self.add_filter_to_statement(&mut select);
-        let row = db.fetch_option(&select).await?;
-        let count = match row {
-            #[expect(clippy::cast_sign_loss)]
-            Some(row) => row.get::<i64>(0)? as u64,
-            None => 0,
-        };
-        Ok(count)
-    }
-
-    /// Execute the query and check if any results exist.
+    /// # Example
     ///
-    /// # Errors
+    /// ```
+    /// use cot::db::query::{Expr, Query};
+    /// use cot::db::{Json, model, query};
+    /// use serde_json::json;
     ///
-    /// Returns an error if the query fails.
-    pub async fn exists<DB: DatabaseBackend>(&self, db: &DB) -> db::Result<bool> {
-        db.exists(self).await
-    }
-
-    /// Delete all rows that match the query.
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     tags: Json<serde_json::Value>,
+    /// };
     ///
-    /// # Errors
+    /// let expr = Expr::json_contains(Expr::field("tags"), Expr::value(Json(json!(["rust"]))));
     ///
-    /// Returns an error if the query fails.
-    pub async fn delete<DB: DatabaseBackend>(&self, db: &DB) -> db::Result<StatementResult> {
-        db.delete(self).await
-    }
-
-    pub(super) fn add_filter_to_statement<S: sea_query::ConditionalStatement>(
-        &self,
-        statement: &mut S,
-    ) {
-        if let Some(filter) = &self.filter {
-            statement.and_where(filter.as_sea_query_expr());
-        }
-    }
-
-    pub(super) fn add_limit_to_statement(&self, statement: &mut sea_query::SelectStatement) {
-        if let Some(limit) = self.limit {
-            statement.limit(limit);
-        }
-    }
-
-    pub(super) fn add_offset_to_statement(&self, statement: &mut sea_query::SelectStatement) {
-        if let Some(offset) = self.offset {
-            statement.offset(offset);
-        }
-    }
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $tags.contains(json!(["rust"])))
+    /// );
+    /// ```
+    JsonContains(Box<Expr>, Box<Expr>),
 }
 
-/// An expression that can be used to filter, update, or delete rows.
-///
-/// This is used to create complex queries with multiple conditions. Typically,
-/// it is only internally used by the [`cot::db::query!`] macro to create a
-/// [`Query`].
-///
-/// # Example
-///
-/// ```
-/// use cot::db::{model, query};
-/// use cot::db::query::{Expr, Query};
-///
-/// #[model]
-/// struct MyModel {
-///     #[model(primary_key)]
-///     id: i32,
-/// };
-///
-/// let expr = Expr::eq(Expr::field("id"), Expr::value(5));
-///
-/// assert_eq!(
-///     <Query<MyModel>>::new().filter(expr),
-///     query!(MyModel, $id == 5)
-/// );
-/// ```
-#[derive(Debug, Clone, PartialEq)]
-pub enum Expr {
-    /// An expression containing a reference to a column.
+impl Expr {
+    /// Create a new field expression. This represents a reference to a column
+    /// in the database.
     ///
     /// # Example
     ///
@@ -299,8 +1461,17 @@ pub enum Expr {
     ///     query!(MyModel, $id == 5)
     /// );
     /// ```
-    Field(Identifier),
-    /// An expression containing a literal value.
+    #[must_use]
+    pub fn field<T: Into<Identifier>>(identifier: T) -> Self {
+        Self::Field(identifier.into())
+    }
+
+    /// Create a new value expression. This represents a literal value that gets
+    /// passed into the SQL query.
+    ///
+    /// # Panics
+    ///
+    /// If the value provided is a [`DbFieldValue::Auto`].
     ///
     /// # Example
     ///
@@ -321,8 +1492,16 @@ pub enum Expr {
     ///     query!(MyModel, $id != 5)
     /// );
     /// ```
-    Value(DbValue),
-    /// An `AND` expression.
+    #[must_use]
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn value<T: ToDbFieldValue>(value: T) -> Self {
+        match value.to_db_field_value() {
+            DbFieldValue::Value(value) => Self::Value(value),
+            DbFieldValue::Auto => panic!("Cannot create query with a non-value field"),
+        }
+    }
+
+    /// Create a new `AND` expression.
     ///
     /// # Example
     ///
@@ -345,8 +1524,12 @@ pub enum Expr {
     ///     query!(MyModel, $id > 10 && $id < 20)
     /// );
     /// ```
-    And(Box<Expr>, Box<Expr>),
-    /// An `OR` expression.
+    #[must_use]
+    pub fn and(lhs: Self, rhs: Self) -> Self {
+        Self::And(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `OR` expression.
     ///
     /// # Example
     ///
@@ -369,8 +1552,12 @@ pub enum Expr {
     ///     query!(MyModel, $id > 10 || $id < 20)
     /// );
     /// ```
-    Or(Box<Expr>, Box<Expr>),
-    /// An `=` expression.
+    #[must_use]
+    pub fn or(lhs: Self, rhs: Self) -> Self {
+        Self::Or(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `=` expression.
     ///
     /// # Example
     ///
@@ -391,8 +1578,12 @@ pub enum Expr {
     ///     query!(MyModel, $id == 5)
     /// );
     /// ```
-    Eq(Box<Expr>, Box<Expr>),
-    /// A `!=` expression.
+    #[must_use]
+    pub fn eq(lhs: Self, rhs: Self) -> Self {
+        Self::Eq(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `!=` expression.
     ///
     /// # Example
     ///
@@ -413,8 +1604,12 @@ pub enum Expr {
     ///     query!(MyModel, $id != 5)
     /// );
     /// ```
-    Ne(Box<Expr>, Box<Expr>),
-    /// A `<` expression.
+    #[must_use]
+    pub fn ne(lhs: Self, rhs: Self) -> Self {
+        Self::Ne(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `<` expression.
     ///
     /// # Example
     ///
@@ -435,8 +1630,12 @@ pub enum Expr {
     ///     query!(MyModel, $id < 5)
     /// );
     /// ```
-    Lt(Box<Expr>, Box<Expr>),
-    /// A `<=` expression.
+    #[must_use]
+    pub fn lt(lhs: Self, rhs: Self) -> Self {
+        Self::Lt(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `<=` expression.
     ///
     /// # Example
     ///
@@ -457,8 +1656,12 @@ pub enum Expr {
     ///     query!(MyModel, $id <= 5)
     /// );
     /// ```
-    Lte(Box<Expr>, Box<Expr>),
-    /// A `>` expression.
+    #[must_use]
+    pub fn lte(lhs: Self, rhs: Self) -> Self {
+        Self::Lte(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `>` expression.
     ///
     /// # Example
     ///
@@ -479,8 +1682,12 @@ pub enum Expr {
     ///     query!(MyModel, $id > 5)
     /// );
     /// ```
-    Gt(Box<Expr>, Box<Expr>),
-    /// A `>=` expression.
+    #[must_use]
+    pub fn gt(lhs: Self, rhs: Self) -> Self {
+        Self::Gt(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `>=` expression.
     ///
     /// # Example
     ///
@@ -501,8 +1708,12 @@ pub enum Expr {
     ///     query!(MyModel, $id >= 5)
     /// );
     /// ```
-    Gte(Box<Expr>, Box<Expr>),
-    /// A `+` expression.
+    #[must_use]
+    pub fn gte(lhs: Self, rhs: Self) -> Self {
+        Self::Gte(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `+` expression.
     ///
     /// # Example
     ///
@@ -524,8 +1735,13 @@ pub enum Expr {
     ///     query!(MyModel, $id == $id_2 + 5)
     /// );
     /// ```
-    Add(Box<Expr>, Box<Expr>),
-    /// A `-` expression.
+    #[expect(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn add(lhs: Self, rhs: Self) -> Self {
+        Self::Add(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `-` expression.
     ///
     /// # Example
     ///
@@ -547,8 +1763,13 @@ pub enum Expr {
     ///     query!(MyModel, $id == $id_2 - 5)
     /// );
     /// ```
-    Sub(Box<Expr>, Box<Expr>),
-    /// A `*` expression.
+    #[expect(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn sub(lhs: Self, rhs: Self) -> Self {
+        Self::Sub(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `*` expression.
     ///
     /// # Example
     ///
@@ -570,8 +1791,74 @@ pub enum Expr {
     ///     query!(MyModel, $id == $id_2 * 2)
     /// );
     /// ```
-    Mul(Box<Expr>, Box<Expr>),
-    /// A `/` expression.
+    #[expect(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn mul(lhs: Self, rhs: Self) -> Self {
+        Self::Mul(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `/` expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::{model, query};
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     id_2: i32,
+    /// };
+    ///
+    /// let expr = Expr::eq(Expr::field("id"), Expr::div(Expr::field("id_2"), Expr::value(2)));
+    ///
+    /// assert_eq!(
+    ///     <Query<MyModel>>::new().filter(expr),
+    ///     query!(MyModel, $id == $id_2 / 2)
+    /// );
+    /// ```
+    #[expect(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn div(lhs: Self, rhs: Self) -> Self {
+        Self::Div(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create a new `IN` expression with a subquery, i.e. `expr IN (SELECT
+    /// column FROM table WHERE filter)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::query::{Expr, Query};
+    /// use cot::db::{Identifier, model};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::in_subquery(
+    ///     Expr::field("id"),
+    ///     Identifier::new("my_model_tags"),
+    ///     Identifier::new("source_id"),
+    ///     Expr::eq(Expr::field("target_id"), Expr::value(5)),
+    /// );
+    /// let query = <Query<MyModel>>::new().filter(expr);
+    /// ```
+    #[must_use]
+    pub fn in_subquery(expr: Self, table: Identifier, column: Identifier, filter: Self) -> Self {
+        Self::InSubquery {
+            expr: Box::new(expr),
+            table,
+            column,
+            filter: Box::new(filter),
+        }
+    }
+
+    /// Create a new `NOT` expression.
     ///
     /// # Example
     ///
@@ -583,22 +1870,22 @@ pub enum Expr {
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
-    ///     id_2: i32,
     /// };
     ///
-    /// let expr = Expr::eq(Expr::field("id"), Expr::div(Expr::field("id_2"), Expr::value(2)));
+    /// let expr = Expr::not(Expr::eq(Expr::field("id"), Expr::value(5)));
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id == $id_2 / 2)
+    ///     query!(MyModel, !($id == 5))
     /// );
     /// ```
-    Div(Box<Expr>, Box<Expr>),
-}
+    #[must_use]
+    #[expect(clippy::should_implement_trait)]
+    pub fn not(expr: Self) -> Self {
+        Self::Not(Box::new(expr))
+    }
 
-impl Expr {
-    /// Create a new field expression. This represents a reference to a column
-    /// in the database.
+    /// Create a new `IS NULL` expression.
     ///
     /// # Example
     ///
@@ -610,26 +1897,23 @@ impl Expr {
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
+    ///     name: Option<String>,
     /// };
     ///
-    /// let expr = Expr::eq(Expr::field("id"), Expr::value(5));
+    /// let expr = Expr::is_null(Expr::field("name"));
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id == 5)
+    ///     query!(MyModel, $name.is_null())
     /// );
     /// ```
     #[must_use]
-    pub fn field<T: Into<Identifier>>(identifier: T) -> Self {
-        Self::Field(identifier.into())
+    pub fn is_null(expr: Self) -> Self {
+        Self::IsNull(Box::new(expr))
     }
 
-    /// Create a new value expression. This represents a literal value that gets
-    /// passed into the SQL query.
-    ///
-    /// # Panics
-    ///
-    /// If the value provided is a [`DbFieldValue::Auto`].
+    /// Create a new `IN` expression with a list of values. An empty list
+    /// never matches any row.
     ///
     /// # Example
     ///
@@ -643,23 +1927,19 @@ impl Expr {
     ///     id: i32,
     /// };
     ///
-    /// let expr = Expr::ne(Expr::field("id"), Expr::value(5));
+    /// let expr = Expr::is_in(Expr::field("id"), [Expr::value(1), Expr::value(2)]);
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id != 5)
+    ///     query!(MyModel, $id.is_in([1, 2]))
     /// );
     /// ```
     #[must_use]
-    #[expect(clippy::needless_pass_by_value)]
-    pub fn value<T: ToDbFieldValue>(value: T) -> Self {
-        match value.to_db_field_value() {
-            DbFieldValue::Value(value) => Self::Value(value),
-            DbFieldValue::Auto => panic!("Cannot create query with a non-value field"),
-        }
+    pub fn is_in<I: IntoIterator<Item = Self>>(expr: Self, values: I) -> Self {
+        Self::In(Box::new(expr), values.into_iter().collect())
     }
 
-    /// Create a new `AND` expression.
+    /// Create a new `BETWEEN` expression. Both bounds are inclusive.
     ///
     /// # Example
     ///
@@ -673,21 +1953,20 @@ impl Expr {
     ///     id: i32,
     /// };
     ///
-    /// let expr = Expr::and(
-    ///     Expr::gt(Expr::field("id"), Expr::value(10)),
-    ///     Expr::lt(Expr::field("id"), Expr::value(20))
-    /// );
+    /// let expr = Expr::between(Expr::field("id"), Expr::value(10), Expr::value(20));
+    ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id > 10 && $id < 20)
+    ///     query!(MyModel, $id.between(10, 20))
     /// );
     /// ```
     #[must_use]
-    pub fn and(lhs: Self, rhs: Self) -> Self {
-        Self::And(Box::new(lhs), Box::new(rhs))
+    pub fn between(expr: Self, low: Self, high: Self) -> Self {
+        Self::Between(Box::new(expr), Box::new(low), Box::new(high))
     }
 
-    /// Create a new `OR` expression.
+    /// Create a new expression that checks whether a string contains given
+    /// substring. See [`Expr::Contains`] for the details.
     ///
     /// # Example
     ///
@@ -699,23 +1978,23 @@ impl Expr {
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
+    ///     name: String,
     /// };
     ///
-    /// let expr = Expr::or(
-    ///     Expr::gt(Expr::field("id"), Expr::value(10)),
-    ///     Expr::lt(Expr::field("id"), Expr::value(20))
-    /// );
+    /// let expr = Expr::contains(Expr::field("name"), "oh");
+    ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id > 10 || $id < 20)
+    ///     query!(MyModel, $name.contains("oh"))
     /// );
     /// ```
     #[must_use]
-    pub fn or(lhs: Self, rhs: Self) -> Self {
-        Self::Or(Box::new(lhs), Box::new(rhs))
+    pub fn contains<T: Into<String>>(expr: Self, substring: T) -> Self {
+        Self::Contains(Box::new(expr), substring.into())
     }
 
-    /// Create a new `=` expression.
+    /// Create a new expression that checks whether a string contains given
+    /// substring, ignoring the case.
     ///
     /// # Example
     ///
@@ -727,21 +2006,23 @@ impl Expr {
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
+    ///     name: String,
     /// };
     ///
-    /// let expr = Expr::eq(Expr::field("id"), Expr::value(5));
+    /// let expr = Expr::icontains(Expr::field("name"), "OH");
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id == 5)
+    ///     query!(MyModel, $name.icontains("OH"))
     /// );
     /// ```
     #[must_use]
-    pub fn eq(lhs: Self, rhs: Self) -> Self {
-        Self::Eq(Box::new(lhs), Box::new(rhs))
+    pub fn icontains<T: Into<String>>(expr: Self, substring: T) -> Self {
+        Self::IContains(Box::new(expr), substring.into())
     }
 
-    /// Create a new `!=` expression.
+    /// Create a new expression that checks whether a string starts with given
+    /// prefix. See [`Expr::StartsWith`] for the details.
     ///
     /// # Example
     ///
@@ -753,305 +2034,615 @@ impl Expr {
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
+    ///     name: String,
     /// };
     ///
-    /// let expr = Expr::ne(Expr::field("id"), Expr::value(5));
+    /// let expr = Expr::starts_with(Expr::field("name"), "Jo");
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id != 5)
+    ///     query!(MyModel, $name.starts_with("Jo"))
     /// );
     /// ```
     #[must_use]
-    pub fn ne(lhs: Self, rhs: Self) -> Self {
-        Self::Ne(Box::new(lhs), Box::new(rhs))
+    pub fn starts_with<T: Into<String>>(expr: Self, prefix: T) -> Self {
+        Self::StartsWith(Box::new(expr), prefix.into())
     }
 
-    /// Create a new `<` expression.
+    /// Create a new expression that checks whether a JSON value contains
+    /// another JSON value. See [`Expr::JsonContains`] for the details.
     ///
     /// # Example
     ///
     /// ```
-    /// use cot::db::{model, query};
     /// use cot::db::query::{Expr, Query};
+    /// use cot::db::{Json, model, query};
+    /// use serde_json::json;
     ///
     /// #[model]
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
+    ///     settings: Json<serde_json::Value>,
     /// };
     ///
-    /// let expr = Expr::lt(Expr::field("id"), Expr::value(5));
+    /// let expr = Expr::json_contains(
+    ///     Expr::field("settings"),
+    ///     Expr::value(Json(json!({"theme": "dark"}))),
+    /// );
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id < 5)
+    ///     query!(MyModel, $settings.contains(json!({"theme": "dark"})))
     /// );
     /// ```
     #[must_use]
-    pub fn lt(lhs: Self, rhs: Self) -> Self {
-        Self::Lt(Box::new(lhs), Box::new(rhs))
+    pub fn json_contains(lhs: Self, rhs: Self) -> Self {
+        Self::JsonContains(Box::new(lhs), Box::new(rhs))
     }
 
-    /// Create a new `<=` expression.
+    /// Returns the expression as a [`sea_query::SimpleExpr`].
     ///
     /// # Example
     ///
     /// ```
-    /// use cot::db::{model, query};
-    /// use cot::db::query::{Expr, Query};
-    ///
-    /// #[model]
-    /// struct MyModel {
-    ///     #[model(primary_key)]
-    ///     id: i32,
-    /// };
+    /// use cot::db::Identifier;
+    /// use cot::db::query::Expr;
+    /// use sea_query::IntoColumnRef;
     ///
-    /// let expr = Expr::lte(Expr::field("id"), Expr::value(5));
+    /// let expr = Expr::eq(Expr::field("id"), Expr::value(5));
     ///
     /// assert_eq!(
-    ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id <= 5)
+    ///     expr.as_sea_query_expr(),
+    ///     sea_query::SimpleExpr::eq(
+    ///         sea_query::SimpleExpr::Column(Identifier::new("id").into_column_ref()),
+    ///         sea_query::SimpleExpr::Value(sea_query::Value::Int(Some(5)))
+    ///     )
     /// );
     /// ```
     #[must_use]
-    pub fn lte(lhs: Self, rhs: Self) -> Self {
-        Self::Lte(Box::new(lhs), Box::new(rhs))
+    pub fn as_sea_query_expr(&self) -> sea_query::SimpleExpr {
+        match self {
+            Self::Field(identifier) => (*identifier).into_column_ref().into(),
+            Self::Value(value) => (*value).clone().into(),
+            Self::And(lhs, rhs) => lhs.as_sea_query_expr().and(rhs.as_sea_query_expr()),
+            Self::Or(lhs, rhs) => lhs.as_sea_query_expr().or(rhs.as_sea_query_expr()),
+            Self::Eq(lhs, rhs) => lhs.as_sea_query_expr().eq(rhs.as_sea_query_expr()),
+            Self::Ne(lhs, rhs) => lhs.as_sea_query_expr().ne(rhs.as_sea_query_expr()),
+            Self::Lt(lhs, rhs) => lhs.as_sea_query_expr().lt(rhs.as_sea_query_expr()),
+            Self::Lte(lhs, rhs) => lhs.as_sea_query_expr().lte(rhs.as_sea_query_expr()),
+            Self::Gt(lhs, rhs) => lhs.as_sea_query_expr().gt(rhs.as_sea_query_expr()),
+            Self::Gte(lhs, rhs) => lhs.as_sea_query_expr().gte(rhs.as_sea_query_expr()),
+            Self::Add(lhs, rhs) => lhs.as_sea_query_expr().add(rhs.as_sea_query_expr()),
+            Self::Sub(lhs, rhs) => lhs.as_sea_query_expr().sub(rhs.as_sea_query_expr()),
+            Self::Mul(lhs, rhs) => lhs.as_sea_query_expr().mul(rhs.as_sea_query_expr()),
+            Self::Div(lhs, rhs) => lhs.as_sea_query_expr().div(rhs.as_sea_query_expr()),
+            Self::InSubquery {
+                expr,
+                table,
+                column,
+                filter,
+            } => expr.as_sea_query_expr().in_subquery(
+                sea_query::Query::select()
+                    .column(*column)
+                    .from(*table)
+                    .and_where(filter.as_sea_query_expr())
+                    .to_owned(),
+            ),
+            Self::Not(expr) => expr.as_sea_query_expr().not(),
+            Self::IsNull(expr) => expr.as_sea_query_expr().is_null(),
+            Self::In(expr, values) => expr
+                .as_sea_query_expr()
+                .is_in(values.iter().map(Self::as_sea_query_expr)),
+            Self::Between(expr, low, high) => expr
+                .as_sea_query_expr()
+                .between(low.as_sea_query_expr(), high.as_sea_query_expr()),
+            Self::Contains(expr, substring) => expr
+                .as_sea_query_expr()
+                .like(like_expr(&format!("%{}%", escape_like(substring)))),
+            Self::IContains(expr, substring) => {
+                sea_query::SimpleExpr::from(sea_query::Func::lower(expr.as_sea_query_expr())).like(
+                    like_expr(&format!("%{}%", escape_like(&substring.to_lowercase()))),
+                )
+            }
+            Self::StartsWith(expr, prefix) => expr
+                .as_sea_query_expr()
+                .like(like_expr(&format!("{}%", escape_like(prefix)))),
+            Self::JsonContains(lhs, rhs) => lhs
+                .as_sea_query_expr()
+                .binary(sea_query::BinOper::Custom("@>"), rhs.as_sea_query_expr()),
+        }
+    }
+}
+
+/// The escape character used in the `LIKE` patterns generated by the
+/// [`Expr::Contains`], [`Expr::IContains`] and [`Expr::StartsWith`]
+/// expressions. Backslash is avoided on purpose, as it is treated as an escape
+/// character in MySQL string literals.
+const LIKE_ESCAPE_CHAR: char = '!';
+
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | LIKE_ESCAPE_CHAR) {
+            escaped.push(LIKE_ESCAPE_CHAR);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn like_expr(pattern: &str) -> sea_query::LikeExpr {
+    sea_query::LikeExpr::new(pattern).escape(LIKE_ESCAPE_CHAR)
+}
+
+/// An aggregate function computed over the rows matching a query.
+///
+/// This is used with [`Query::aggregate`] to compute statistics over the
+/// rows of a table, optionally grouped by some fields with
+/// [`Query::group_by`].
+///
+/// # Example
+///
+/// ```
+/// use cot::db::model;
+/// use cot::db::query::{Aggregate, Query};
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: i32,
+///     views: i32,
+/// }
+///
+/// let aggregates = [
+///     Aggregate::count(),
+///     Aggregate::sum(PostFields::views),
+///     Aggregate::max(PostFields::views),
+/// ];
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Aggregate {
+    function: AggregateFunction,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum AggregateFunction {
+    Count,
+    Sum(Identifier, ColumnType),
+    Avg(Identifier),
+    Min(Identifier),
+    Max(Identifier),
+}
+
+impl Aggregate {
+    /// Counts the rows. The result is returned as an `i64`.
+    #[must_use]
+    pub const fn count() -> Self {
+        Self {
+            function: AggregateFunction::Count,
+        }
+    }
+
+    /// Computes the sum of the values of given field. The sum of an integer
+    /// field is returned as an `i64`, and the sum of a floating-point field
+    /// as an `f64`. The result is `NULL` if there are no rows.
+    #[must_use]
+    pub fn sum<T: DatabaseField>(field: FieldRef<T>) -> Self {
+        Self {
+            function: AggregateFunction::Sum(field.into(), T::TYPE),
+        }
+    }
+
+    /// Computes the average of the values of given field. The result is
+    /// returned as an `f64`, or `NULL` if there are no rows.
+    #[must_use]
+    pub fn avg<T: DatabaseField>(field: FieldRef<T>) -> Self {
+        Self {
+            function: AggregateFunction::Avg(field.into()),
+        }
+    }
+
+    /// Computes the minimum value of given field. The result has the same
+    /// type as the field, or is `NULL` if there are no rows.
+    #[must_use]
+    pub fn min<T: DatabaseField>(field: FieldRef<T>) -> Self {
+        Self {
+            function: AggregateFunction::Min(field.into()),
+        }
+    }
+
+    /// Computes the maximum value of given field. The result has the same
+    /// type as the field, or is `NULL` if there are no rows.
+    #[must_use]
+    pub fn max<T: DatabaseField>(field: FieldRef<T>) -> Self {
+        Self {
+            function: AggregateFunction::Max(field.into()),
+        }
+    }
+
+    fn as_sea_query_expr(&self, db: &Database) -> sea_query::SimpleExpr {
+        match self.function {
+            AggregateFunction::Count => sea_query::Expr::col(sea_query::Asterisk).count(),
+            AggregateFunction::Sum(column, column_type) => {
+                // the type of the sum differs between the database backends (e.g.
+                // PostgreSQL returns `NUMERIC` when summing `BIGINT` values), so
+                // we normalize it
+                let is_float = matches!(column_type, ColumnType::Float | ColumnType::Double);
+                sea_query::Func::cast_as(
+                    sea_query::Func::sum(sea_query::Expr::col(column)),
+                    sea_query::Alias::new(db.numeric_cast_type(is_float)),
+                )
+                .into()
+            }
+            AggregateFunction::Avg(column) => sea_query::Func::cast_as(
+                sea_query::Func::avg(sea_query::Expr::col(column)),
+                sea_query::Alias::new(db.numeric_cast_type(true)),
+            )
+            .into(),
+            AggregateFunction::Min(column) => {
+                sea_query::Func::min(sea_query::Expr::col(column)).into()
+            }
+            AggregateFunction::Max(column) => {
+                sea_query::Func::max(sea_query::Expr::col(column)).into()
+            }
+        }
+    }
+}
+
+/// The direction of an [`OrderBy`] clause.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Order {
+    /// Ascending order, i.e. from the smallest to the largest value.
+    Asc,
+    /// Descending order, i.e. from the largest to the smallest value.
+    Desc,
+}
+
+impl From<Order> for sea_query::Order {
+    fn from(order: Order) -> Self {
+        match order {
+            Order::Asc => Self::Asc,
+            Order::Desc => Self::Desc,
+        }
+    }
+}
+
+/// An ordering of the query results by a single field.
+///
+/// Typically created with [`FieldRef::asc`] or [`FieldRef::desc`] and passed
+/// to [`Query::order_by`].
+///
+/// # Example
+///
+/// ```
+/// use cot::db::model;
+/// use cot::db::query::{Order, OrderBy};
+///
+/// #[model]
+/// struct User {
+///     #[model(primary_key)]
+///     id: i32,
+///     age: i32,
+/// }
+///
+/// assert_eq!(UserFields::age.desc(), OrderBy::new("age", Order::Desc));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct OrderBy {
+    column: Identifier,
+    order: Order,
+}
+
+impl OrderBy {
+    /// Creates a new ordering by given column.
+    #[must_use]
+    pub fn new<T: Into<Identifier>>(column: T, order: Order) -> Self {
+        Self {
+            column: column.into(),
+            order,
+        }
+    }
+
+    /// Returns the column to order by.
+    #[must_use]
+    pub fn column(&self) -> Identifier {
+        self.column
+    }
+
+    /// Returns the direction of the ordering.
+    #[must_use]
+    pub fn order(&self) -> Order {
+        self.order
+    }
+}
+
+/// An assignment of a value to a column, used to update rows with
+/// [`Query::update_all`].
+///
+/// Assignments are created with [`FieldRef::set`] and [`FieldRef::set_expr`].
+///
+/// # Example
+///
+/// ```
+/// use cot::db::model;
+/// use cot::db::query::{Assignment, Expr};
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: i32,
+///     title: String,
+/// }
+///
+/// let assignment = PostFields::title.set("New title");
+/// assert_eq!(assignment.value(), &Expr::value("New title".to_owned()));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    column: Identifier,
+    value: Expr,
+}
+
+impl Assignment {
+    /// Creates a new assignment of the given expression to the column.
+    #[must_use]
+    pub fn new<T: Into<Identifier>>(column: T, value: Expr) -> Self {
+        Self {
+            column: column.into(),
+            value,
+        }
+    }
+
+    /// Returns the column the value is assigned to.
+    #[must_use]
+    pub fn column(&self) -> Identifier {
+        self.column
+    }
+
+    /// Returns the expression assigned to the column.
+    #[must_use]
+    pub fn value(&self) -> &Expr {
+        &self.value
+    }
+}
+
+/// A reference to a field in a database table.
+///
+/// This is used to create expressions that reference a specific column in a
+/// table with a specific type. This allows for type-safe creation of queries
+/// with some common operators like `=`, `!=`, `+`, `-`, `*`, and `/`.
+#[derive(Debug)]
+pub struct FieldRef<T> {
+    identifier: Identifier,
+    phantom_data: PhantomData<T>,
+}
+
+impl<T: FromDbValue + ToDbFieldValue> FieldRef<T> {
+    /// Create a new field reference.
+    #[must_use]
+    pub const fn new(identifier: Identifier) -> Self {
+        Self {
+            identifier,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<T> FieldRef<T> {
+    /// Returns the field reference as an [`Expr`].
+    #[must_use]
+    pub fn as_expr(&self) -> Expr {
+        Expr::Field(self.identifier)
     }
 
-    /// Create a new `>` expression.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use cot::db::{model, query};
-    /// use cot::db::query::{Expr, Query};
-    ///
-    /// #[model]
-    /// struct MyModel {
-    ///     #[model(primary_key)]
-    ///     id: i32,
-    /// };
-    ///
-    /// let expr = Expr::gt(Expr::field("id"), Expr::value(5));
-    ///
-    /// assert_eq!(
-    ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id > 5)
-    /// );
-    /// ```
+    /// Returns an ascending ordering by this field, to be used with
+    /// [`Query::order_by`].
     #[must_use]
-    pub fn gt(lhs: Self, rhs: Self) -> Self {
-        Self::Gt(Box::new(lhs), Box::new(rhs))
+    pub fn asc(&self) -> OrderBy {
+        OrderBy::new(self.identifier, Order::Asc)
     }
 
-    /// Create a new `>=` expression.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use cot::db::{model, query};
-    /// use cot::db::query::{Expr, Query};
-    ///
-    /// #[model]
-    /// struct MyModel {
-    ///     #[model(primary_key)]
-    ///     id: i32,
-    /// };
-    ///
-    /// let expr = Expr::gte(Expr::field("id"), Expr::value(5));
-    ///
-    /// assert_eq!(
-    ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id >= 5)
-    /// );
-    /// ```
+    /// Returns a descending ordering by this field, to be used with
+    /// [`Query::order_by`].
     #[must_use]
-    pub fn gte(lhs: Self, rhs: Self) -> Self {
-        Self::Gte(Box::new(lhs), Box::new(rhs))
+    pub fn desc(&self) -> OrderBy {
+        OrderBy::new(self.identifier, Order::Desc)
     }
 
-    /// Create a new `+` expression.
+    /// Returns an assignment of the given expression to this field, to be
+    /// used with [`Query::update_all`]. The expression can reference the
+    /// fields of the row being updated, e.g. to increment a counter.
+    #[must_use]
+    pub fn set_expr(&self, value: Expr) -> Assignment {
+        Assignment::new(self.identifier, value)
+    }
+}
+
+impl<T> From<FieldRef<T>> for Identifier {
+    fn from(field: FieldRef<T>) -> Self {
+        field.identifier
+    }
+}
+
+impl<T: ToDbFieldValue + 'static> FieldRef<T> {
+    /// Returns an assignment of the given value to this field, to be used
+    /// with [`Query::update_all`].
+    #[must_use]
+    pub fn set<V: IntoField<T>>(&self, value: V) -> Assignment {
+        self.set_expr(Expr::value(value.into_field()))
+    }
+
+    /// Creates an expression that checks if the field is equal to any of the
+    /// given values.
     ///
-    /// # Example
+    /// # Examples
     ///
     /// ```
-    /// use cot::db::{model, query};
     /// use cot::db::query::{Expr, Query};
+    /// use cot::db::{model, query};
     ///
     /// #[model]
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
-    ///     id_2: i32,
     /// };
     ///
-    /// let expr = Expr::eq(Expr::field("id"), Expr::add(Expr::field("id_2"), Expr::value(5)));
+    /// let expr = <MyModel as cot::db::Model>::Fields::id.is_in([1, 2, 3]);
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id == $id_2 + 5)
+    ///     query!(MyModel, $id.is_in([1, 2, 3]))
     /// );
     /// ```
-    #[expect(clippy::should_implement_trait)]
     #[must_use]
-    pub fn add(lhs: Self, rhs: Self) -> Self {
-        Self::Add(Box::new(lhs), Box::new(rhs))
+    pub fn is_in<V: IntoField<T>, I: IntoIterator<Item = V>>(&self, values: I) -> Expr {
+        Expr::is_in(
+            self.as_expr(),
+            values
+                .into_iter()
+                .map(|value| Expr::value(value.into_field())),
+        )
     }
+}
 
-    /// Create a new `-` expression.
+impl<T: ToDbFieldValue + Ord + 'static> FieldRef<T> {
+    /// Creates an expression that checks if the field is between the given
+    /// values (inclusive).
     ///
-    /// # Example
+    /// # Examples
     ///
     /// ```
-    /// use cot::db::{model, query};
     /// use cot::db::query::{Expr, Query};
+    /// use cot::db::{model, query};
     ///
     /// #[model]
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
-    ///     id_2: i32,
     /// };
     ///
-    /// let expr = Expr::eq(Expr::field("id"), Expr::sub(Expr::field("id_2"), Expr::value(5)));
+    /// let expr = <MyModel as cot::db::Model>::Fields::id.between(10, 20);
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id == $id_2 - 5)
+    ///     query!(MyModel, $id.between(10, 20))
     /// );
     /// ```
-    #[expect(clippy::should_implement_trait)]
     #[must_use]
-    pub fn sub(lhs: Self, rhs: Self) -> Self {
-        Self::Sub(Box::new(lhs), Box::new(rhs))
+    pub fn between<V: IntoField<T>, W: IntoField<T>>(&self, low: V, high: W) -> Expr {
+        Expr::between(
+            self.as_expr(),
+            Expr::value(low.into_field()),
+            Expr::value(high.into_field()),
+        )
     }
+}
 
-    /// Create a new `*` expression.
+impl<T> FieldRef<Option<T>> {
+    /// Creates an expression that checks if the field is `NULL`.
     ///
-    /// # Example
+    /// # Examples
     ///
     /// ```
-    /// use cot::db::{model, query};
     /// use cot::db::query::{Expr, Query};
+    /// use cot::db::{model, query};
     ///
     /// #[model]
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
-    ///     id_2: i32,
+    ///     name: Option<String>,
     /// };
     ///
-    /// let expr = Expr::eq(Expr::field("id"), Expr::mul(Expr::field("id_2"), Expr::value(2)));
+    /// let expr = <MyModel as cot::db::Model>::Fields::name.is_null();
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id == $id_2 * 2)
+    ///     query!(MyModel, $name.is_null())
     /// );
     /// ```
-    #[expect(clippy::should_implement_trait)]
     #[must_use]
-    pub fn mul(lhs: Self, rhs: Self) -> Self {
-        Self::Mul(Box::new(lhs), Box::new(rhs))
+    pub fn is_null(&self) -> Expr {
+        Expr::is_null(self.as_expr())
     }
 
-    /// Create a new `/` expression.
+    /// Creates an expression that checks if the field is not `NULL`.
     ///
-    /// # Example
+    /// # Examples
     ///
     /// ```
-    /// use cot::db::{model, query};
     /// use cot::db::query::{Expr, Query};
+    /// use cot::db::{model, query};
     ///
     /// #[model]
     /// struct MyModel {
     ///     #[model(primary_key)]
     ///     id: i32,
-    ///     id_2: i32,
+    ///     name: Option<String>,
     /// };
     ///
-    /// let expr = Expr::eq(Expr::field("id"), Expr::div(Expr::field("id_2"), Expr::value(2)));
+    /// let expr = <MyModel as cot::db::Model>::Fields::name.is_not_null();
     ///
     /// assert_eq!(
     ///     <Query<MyModel>>::new().filter(expr),
-    ///     query!(MyModel, $id == $id_2 / 2)
+    ///     query!(MyModel, !$name.is_null())
     /// );
     /// ```
-    #[expect(clippy::should_implement_trait)]
     #[must_use]
-    pub fn div(lhs: Self, rhs: Self) -> Self {
-        Self::Div(Box::new(lhs), Box::new(rhs))
+    pub fn is_not_null(&self) -> Expr {
+        Expr::not(self.is_null())
     }
+}
 
-    /// Returns the expression as a [`sea_query::SimpleExpr`].
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use cot::db::Identifier;
-    /// use cot::db::query::Expr;
-    /// use sea_query::IntoColumnRef;
-    ///
-    /// let expr = Expr::eq(Expr::field("id"), Expr::value(5));
-    ///
-    /// assert_eq!(
-    ///     expr.as_sea_query_expr(),
-    ///     sea_query::SimpleExpr::eq(
-    ///         sea_query::SimpleExpr::Column(Identifier::new("id").into_column_ref()),
-    ///         sea_query::SimpleExpr::Value(sea_query::Value::Int(Some(5)))
-    ///     )
-    /// );
-    /// ```
-    #[must_use]
-    pub fn as_sea_query_expr(&self) -> sea_query::SimpleExpr {
-        match self {
-            Self::Field(identifier) => (*identifier).into_column_ref().into(),
-            Self::Value(value) => (*value).clone().into(),
-            Self::And(lhs, rhs) => lhs.as_sea_query_expr().and(rhs.as_sea_query_expr()),
-            Self::Or(lhs, rhs) => lhs.as_sea_query_expr().or(rhs.as_sea_query_expr()),
-            Self::Eq(lhs, rhs) => lhs.as_sea_query_expr().eq(rhs.as_sea_query_expr()),
-            Self::Ne(lhs, rhs) => lhs.as_sea_query_expr().ne(rhs.as_sea_query_expr()),
-            Self::Lt(lhs, rhs) => lhs.as_sea_query_expr().lt(rhs.as_sea_query_expr()),
-            Self::Lte(lhs, rhs) => lhs.as_sea_query_expr().lte(rhs.as_sea_query_expr()),
-            Self::Gt(lhs, rhs) => lhs.as_sea_query_expr().gt(rhs.as_sea_query_expr()),
-            Self::Gte(lhs, rhs) => lhs.as_sea_query_expr().gte(rhs.as_sea_query_expr()),
-            Self::Add(lhs, rhs) => lhs.as_sea_query_expr().add(rhs.as_sea_query_expr()),
-            Self::Sub(lhs, rhs) => lhs.as_sea_query_expr().sub(rhs.as_sea_query_expr()),
-            Self::Mul(lhs, rhs) => lhs.as_sea_query_expr().mul(rhs.as_sea_query_expr()),
-            Self::Div(lhs, rhs) => lhs.as_sea_query_expr().div(rhs.as_sea_query_expr()),
+macro_rules! impl_string_field_ref {
+    ([$($generics:tt)*] $ty:ty) => {
+        impl<$($generics)*> FieldRef<$ty> {
+            /// Creates an expression that checks if the field contains the
+            /// given substring. See [`Expr::Contains`] for the details.
+            #[must_use]
+            pub fn contains<V: Into<String>>(&self, substring: V) -> Expr {
+                Expr::contains(self.as_expr(), substring)
+            }
+
+            /// Creates an expression that checks if the field contains the
+            /// given substring, ignoring the case.
+            #[must_use]
+            pub fn icontains<V: Into<String>>(&self, substring: V) -> Expr {
+                Expr::icontains(self.as_expr(), substring)
+            }
+
+            /// Creates an expression that checks if the field starts with the
+            /// given prefix. See [`Expr::StartsWith`] for the details.
+            #[must_use]
+            pub fn starts_with<V: Into<String>>(&self, prefix: V) -> Expr {
+                Expr::starts_with(self.as_expr(), prefix)
+            }
         }
-    }
+    };
 }
 
-/// A reference to a field in a database table.
-///
-/// This is used to create expressions that reference a specific column in a
-/// table with a specific type. This allows for type-safe creation of queries
-/// with some common operators like `=`, `!=`, `+`, `-`, `*`, and `/`.
-#[derive(Debug)]
-pub struct FieldRef<T> {
-    identifier: Identifier,
-    phantom_data: PhantomData<T>,
-}
+impl_string_field_ref!([] String);
+impl_string_field_ref!([] Option<String>);
+impl_string_field_ref!([const LIMIT: u32] LimitedString<LIMIT>);
 
-impl<T: FromDbValue + ToDbFieldValue> FieldRef<T> {
-    /// Create a new field reference.
-    #[must_use]
-    pub const fn new(identifier: Identifier) -> Self {
-        Self {
-            identifier,
-            phantom_data: PhantomData,
+macro_rules! impl_json_field_ref {
+    ($ty:ty) => {
+        impl<T> FieldRef<$ty> {
+            /// Creates an expression that checks if the JSON field contains
+            /// the given value. See [`Expr::JsonContains`] for the details.
+            ///
+            /// This is only supported on PostgreSQL.
+            #[must_use]
+            pub fn contains<V: Serialize + Send + Sync>(&self, value: V) -> Expr {
+                Expr::json_contains(self.as_expr(), Expr::value(Json(value)))
+            }
         }
-    }
+    };
 }
 
-impl<T> FieldRef<T> {
-    /// Returns the field reference as an [`Expr`].
-    #[must_use]
-    pub fn as_expr(&self) -> Expr {
-        Expr::Field(self.identifier)
-    }
-}
+impl_json_field_ref!(Json<T>);
+impl_json_field_ref!(Option<Json<T>>);
 
 /// A trait for types that can be compared in database expressions.
 pub trait ExprEq<T> {
@@ -1477,6 +3068,85 @@ mod tests {
         assert_eq!(query.offset.unwrap(), 10);
     }
 
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn query_order_by() {
+        let mut query: Query<MockModel> = Query::new();
+        let id = FieldRef::<i32>::new(Identifier::new("id"));
+        query
+            .order_by(id.desc())
+            .order_by(OrderBy::new("name", Order::Asc));
+        assert_eq!(
+            query.order_by,
+            vec![
+                OrderBy::new("id", Order::Desc),
+                OrderBy::new("name", Order::Asc)
+            ]
+        );
+
+        let mut select = sea_query::Query::select();
+        select
+            .column(Identifier::new("id"))
+            .from(Identifier::new("my_table"));
+        query.add_order_by_to_statement(&mut select);
+        assert_eq!(
+            select.to_string(sea_query::SqliteQueryBuilder),
+            r#"SELECT "id" FROM "my_table" ORDER BY "id" DESC, "name" ASC"#
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn query_soft_delete_filter() {
+        #[model(soft_delete)]
+        struct SoftDeleteModel {
+            #[model(primary_key)]
+            id: i32,
+        }
+
+        let to_sql = |query: &Query<SoftDeleteModel>| {
+            let mut select = sea_query::Query::select();
+            select
+                .column(Identifier::new("id"))
+                .from(Identifier::new("my_table"));
+            query.add_filter_to_statement(&mut select);
+            select.to_string(sea_query::SqliteQueryBuilder)
+        };
+
+        let mut query = Query::<SoftDeleteModel>::new();
+        query.filter(Expr::eq(Expr::field("id"), Expr::value(5)));
+        assert_eq!(
+            to_sql(&query),
+            r#"SELECT "id" FROM "my_table" WHERE "id" = 5 AND "deleted_at" IS NULL"#
+        );
+
+        query.with_deleted();
+        assert_eq!(
+            to_sql(&query),
+            r#"SELECT "id" FROM "my_table" WHERE "id" = 5"#
+        );
+    }
+
+    #[test]
+    fn query_search() {
+        let mut query: Query<MockModel> = Query::new();
+        query.search("rust web");
+        assert_eq!(query.search.as_deref(), Some("rust web"));
+        assert_ne!(query, Query::new());
+    }
+
+    #[test]
+    fn query_group_by() {
+        let mut query: Query<MockModel> = Query::new();
+        query
+            .group_by("name")
+            .group_by(FieldRef::<i32>::new(Identifier::new("id")));
+        assert_eq!(
+            query.group_by,
+            vec![Identifier::new("name"), Identifier::new("id")]
+        );
+    }
+
     #[cot::test]
     async fn query_all() {
         let mut db = MockDatabaseBackend::new();
@@ -1523,6 +3193,30 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[cot::test]
+    async fn query_hard_delete() {
+        let mut db = MockDatabaseBackend::new();
+        db.expect_hard_delete()
+            .returning(|_: &Query<MockModel>| Ok(StatementResult::new(RowsNum(0))));
+        let query: Query<MockModel> = Query::new();
+
+        let result = query.hard_delete(&db).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[cot::test]
+    async fn query_restore() {
+        let mut db = MockDatabaseBackend::new();
+        db.expect_restore()
+            .returning(|_: &Query<MockModel>| Ok(StatementResult::new(RowsNum(0))));
+        let query: Query<MockModel> = Query::new();
+
+        let result = query.restore(&db).await;
+
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn expr_field() {
         let expr = Expr::field("name");
@@ -1570,4 +3264,208 @@ mod tests {
     test_expr_constructor!(expr_sub, Sub, sub);
     test_expr_constructor!(expr_mul, Mul, mul);
     test_expr_constructor!(expr_div, Div, div);
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn expr_in_subquery() {
+        let expr = Expr::in_subquery(
+            Expr::field("id"),
+            Identifier::new("join_table"),
+            Identifier::new("source_id"),
+            Expr::eq(Expr::field("target_id"), Expr::value(5)),
+        );
+
+        let sql = sea_query::Query::select()
+            .column(Identifier::new("id"))
+            .from(Identifier::new("my_table"))
+            .and_where(expr.as_sea_query_expr())
+            .to_string(sea_query::SqliteQueryBuilder);
+
+        assert_eq!(
+            sql,
+            r#"SELECT "id" FROM "my_table" WHERE "id" IN (SELECT "source_id" FROM "join_table" WHERE "target_id" = 5)"#
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn where_sql(expr: &Expr) -> String {
+        sea_query::Query::select()
+            .column(Identifier::new("id"))
+            .from(Identifier::new("my_table"))
+            .and_where(expr.as_sea_query_expr())
+            .to_string(sea_query::SqliteQueryBuilder)
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn expr_not() {
+        let expr = Expr::not(Expr::or(
+            Expr::eq(Expr::field("id"), Expr::value(5)),
+            Expr::eq(Expr::field("id"), Expr::value(6)),
+        ));
+
+        assert_eq!(
+            where_sql(&expr),
+            r#"SELECT "id" FROM "my_table" WHERE NOT ("id" = 5 OR "id" = 6)"#
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn expr_is_null() {
+        let expr = Expr::is_null(Expr::field("name"));
+
+        assert_eq!(
+            where_sql(&expr),
+            r#"SELECT "id" FROM "my_table" WHERE "name" IS NULL"#
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn expr_is_in() {
+        let expr = Expr::is_in(Expr::field("id"), [Expr::value(1), Expr::value(2)]);
+
+        assert_eq!(
+            where_sql(&expr),
+            r#"SELECT "id" FROM "my_table" WHERE "id" IN (1, 2)"#
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn expr_between() {
+        let expr = Expr::between(Expr::field("id"), Expr::value(1), Expr::value(10));
+
+        assert_eq!(
+            where_sql(&expr),
+            r#"SELECT "id" FROM "my_table" WHERE "id" BETWEEN 1 AND 10"#
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn expr_like() {
+        assert_eq!(
+            where_sql(&Expr::contains(Expr::field("name"), "50%_!")),
+            r#"SELECT "id" FROM "my_table" WHERE "name" LIKE '%50!%!_!!%' ESCAPE '!'"#
+        );
+        assert_eq!(
+            where_sql(&Expr::icontains(Expr::field("name"), "Jo")),
+            r#"SELECT "id" FROM "my_table" WHERE LOWER("name") LIKE '%jo%' ESCAPE '!'"#
+        );
+        assert_eq!(
+            where_sql(&Expr::starts_with(Expr::field("name"), "Jo")),
+            r#"SELECT "id" FROM "my_table" WHERE "name" LIKE 'Jo%' ESCAPE '!'"#
+        );
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn expr_json_contains() {
+        let expr = Expr::json_contains(
+            Expr::field("tags"),
+            Expr::value(Json(serde_json::json!([1, 2]))),
+        );
+
+        assert_eq!(
+            sea_query::Query::select()
+                .column(Identifier::new("id"))
+                .from(Identifier::new("my_table"))
+                .and_where(expr.as_sea_query_expr())
+                .to_string(sea_query::PostgresQueryBuilder),
+            r#"SELECT "id" FROM "my_table" WHERE "tags" @> '[1,2]'"#
+        );
+    }
+
+    #[test]
+    fn field_ref_json_contains() {
+        let tags = FieldRef::<Json<Vec<String>>>::new(Identifier::new("tags"));
+        let settings =
+            FieldRef::<Option<Json<serde_json::Value>>>::new(Identifier::new("settings"));
+
+        assert_eq!(
+            tags.contains(["rust"]),
+            Expr::json_contains(Expr::field("tags"), Expr::value(Json(["rust"])))
+        );
+        assert_eq!(
+            settings.contains(serde_json::json!({"theme": "dark"})),
+            Expr::json_contains(
+                Expr::field("settings"),
+                Expr::value(Json(serde_json::json!({"theme": "dark"})))
+            )
+        );
+    }
+
+    #[test]
+    fn field_ref_assignments() {
+        let id = FieldRef::<Auto<i32>>::new(Identifier::new("id"));
+        let views = FieldRef::<i32>::new(Identifier::new("views"));
+
+        let assignment = id.set(5);
+        assert_eq!(assignment.column(), Identifier::new("id"));
+        assert_eq!(assignment.value(), &Expr::value(Auto::fixed(5)));
+        assert_eq!(
+            views.set_expr(Expr::add(views.as_expr(), Expr::value(1))),
+            Assignment::new("views", Expr::add(Expr::field("views"), Expr::value(1)))
+        );
+    }
+
+    #[test]
+    fn field_ref_exprs() {
+        let id = FieldRef::<i32>::new(Identifier::new("id"));
+        let name = FieldRef::<LimitedString<10>>::new(Identifier::new("name"));
+        let nickname = FieldRef::<Option<String>>::new(Identifier::new("nickname"));
+
+        assert_eq!(
+            id.is_in([1, 2]),
+            Expr::is_in(Expr::field("id"), [Expr::value(1), Expr::value(2)])
+        );
+        assert_eq!(
+            id.between(1, 2),
+            Expr::between(Expr::field("id"), Expr::value(1), Expr::value(2))
+        );
+        assert_eq!(nickname.is_null(), Expr::is_null(Expr::field("nickname")));
+        assert_eq!(
+            nickname.is_not_null(),
+            Expr::not(Expr::is_null(Expr::field("nickname")))
+        );
+        assert_eq!(
+            nickname.contains("a"),
+            Expr::contains(Expr::field("nickname"), "a")
+        );
+        assert_eq!(name.contains("a"), Expr::contains(Expr::field("name"), "a"));
+        assert_eq!(
+            name.icontains("a"),
+            Expr::icontains(Expr::field("name"), "a")
+        );
+        assert_eq!(
+            name.starts_with("a"),
+            Expr::starts_with(Expr::field("name"), "a")
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn aggregate_sql() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        let amount = || FieldRef::<i32>::new(Identifier::new("amount"));
+        let rating = || FieldRef::<f64>::new(Identifier::new("rating"));
+
+        let sql = sea_query::Query::select()
+            .expr(Aggregate::count().as_sea_query_expr(&db))
+            .expr(Aggregate::sum(amount()).as_sea_query_expr(&db))
+            .expr(Aggregate::sum(rating()).as_sea_query_expr(&db))
+            .expr(Aggregate::avg(amount()).as_sea_query_expr(&db))
+            .expr(Aggregate::min(amount()).as_sea_query_expr(&db))
+            .expr(Aggregate::max(amount()).as_sea_query_expr(&db))
+            .from(Identifier::new("my_table"))
+            .to_string(sea_query::SqliteQueryBuilder);
+
+        assert_eq!(
+            sql,
+            r#"SELECT COUNT(*), CAST(SUM("amount") AS INTEGER), CAST(SUM("rating") AS REAL), CAST(AVG("amount") AS REAL), MIN("amount"), MAX("amount") FROM "my_table""#
+        );
+    }
 }
@@ -0,0 +1,330 @@
+//! Logging of the executed queries and the per-request query statistics.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+/// The tracing target of the events emitted for the executed queries.
+const QUERY_LOG_TARGET: &str = "cot::db::query";
+
+tokio::task_local! {
+    static CURRENT_QUERY_STATS: QueryStats;
+}
+
+/// The options of logging the queries executed by a [`Database`].
+///
+/// Every executed query emits a `DEBUG` tracing event with the `cot::db::query`
+/// target, containing the SQL, the time it took to execute, and the number of
+/// rows it returned or affected. The values bound to the query are redacted,
+/// unless [`Self::log_values`] is enabled. The queries taking longer than
+/// [`Self::slow_query_threshold`] are additionally logged at the `WARN`
+/// level.
+///
+/// In a Cot project, the options are usually set in the `[database]` section
+/// of the config (see [`DatabaseConfig`](crate::config::DatabaseConfig)).
+///
+/// [`Database`]: crate::db::Database
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::db::QueryLogging;
+///
+/// let logging = QueryLogging::new()
+///     .slow_query_threshold(Duration::from_millis(200))
+///     .log_values(true);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct QueryLogging {
+    slow_query_threshold: Option<Duration>,
+    log_values: bool,
+}
+
+impl QueryLogging {
+    /// Creates new query logging options, with the values redacted and no
+    /// slow query threshold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::QueryLogging;
+    ///
+    /// let logging = QueryLogging::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the execution time above which the queries are logged at the
+    /// `WARN` level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::QueryLogging;
+    ///
+    /// let logging = QueryLogging::new().slow_query_threshold(Duration::from_millis(500));
+    /// ```
+    #[must_use]
+    pub fn slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_query_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets whether the values bound to the queries are included in the
+    /// logs.
+    ///
+    /// The values can contain sensitive data, such as password hashes or
+    /// personal information, so this should only be enabled for debugging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::QueryLogging;
+    ///
+    /// let logging = QueryLogging::new().log_values(true);
+    /// ```
+    #[must_use]
+    pub fn log_values(mut self, log_values: bool) -> Self {
+        self.log_values = log_values;
+        self
+    }
+}
+
+/// The number of queries executed while handling a request, and the total
+/// time they took.
+///
+/// The statistics are collected by
+/// [`QueryStatsMiddleware`](crate::middleware::QueryStatsMiddleware), which
+/// also reports them in the `Server-Timing` response header, so that they are
+/// visible in the developer tools of the browser. A high number of queries
+/// for a single request often means an N+1 problem: a query executed for
+/// each row returned by another query.
+///
+/// The statistics can be accessed in the request handlers by using
+/// [`QueryStats`] as an extractor, or with [`QueryStats::current`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::QueryStats;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::{Body, StatusCode};
+///
+/// async fn index(query_stats: QueryStats) -> cot::Result<Response> {
+///     Ok(Response::new_html(
+///         StatusCode::OK,
+///         Body::fixed(format!("{} queries so far", query_stats.count())),
+///     ))
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct QueryStats(Arc<QueryStatsInner>);
+
+#[derive(Debug, Default)]
+struct QueryStatsInner {
+    count: AtomicU64,
+    duration_nanos: AtomicU64,
+}
+
+impl QueryStats {
+    /// Creates new, empty query statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::QueryStats;
+    ///
+    /// let stats = QueryStats::new();
+    /// assert_eq!(stats.count(), 0);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the statistics of the request being currently handled, if
+    /// they are collected by
+    /// [`QueryStatsMiddleware`](crate::middleware::QueryStatsMiddleware).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::QueryStats;
+    ///
+    /// if let Some(stats) = QueryStats::current() {
+    ///     println!("{} queries so far", stats.count());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        CURRENT_QUERY_STATS.try_with(Clone::clone).ok()
+    }
+
+    /// Returns the number of executed queries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::QueryStats;
+    ///
+    /// let stats = QueryStats::new();
+    /// assert_eq!(stats.count(), 0);
+    /// ```
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.0.count.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total time the executed queries took.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::QueryStats;
+    ///
+    /// let stats = QueryStats::new();
+    /// assert_eq!(stats.total_duration(), Duration::ZERO);
+    /// ```
+    #[must_use]
+    pub fn total_duration(&self) -> Duration {
+        Duration::from_nanos(self.0.duration_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Runs the future with these statistics collecting the queries it
+    /// executes.
+    pub(crate) async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_QUERY_STATS.scope(self, future).await
+    }
+
+    fn record(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.0.count.fetch_add(1, Ordering::Relaxed);
+        self.0.duration_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// A query being executed, logged when it finishes.
+#[derive(Debug)]
+pub(super) struct QueryLog<'a> {
+    logging: QueryLogging,
+    sql: &'a str,
+    values: Option<String>,
+    start: Instant,
+}
+
+impl<'a> QueryLog<'a> {
+    pub(super) fn start<V: Debug>(logging: QueryLogging, sql: &'a str, values: Option<&V>) -> Self {
+        let values = values
+            .filter(|_| logging.log_values)
+            .map(|values| format!("{values:?}"));
+
+        Self {
+            logging,
+            sql,
+            values,
+            start: Instant::now(),
+        }
+    }
+
+    /// Logs the query and records it in the statistics of the current
+    /// request. `rows` is the number of rows returned or affected by the
+    /// query.
+    pub(super) fn finish<T, E>(
+        self,
+        result: Result<T, E>,
+        rows: impl FnOnce(&T) -> u64,
+    ) -> Result<T, E> {
+        let duration = self.start.elapsed();
+        if let Some(stats) = QueryStats::current() {
+            stats.record(duration);
+        }
+
+        let rows = result.as_ref().map(rows).ok();
+        let values = self.values.as_deref().unwrap_or("[redacted]");
+        debug!(
+            target: QUERY_LOG_TARGET,
+            sql = self.sql,
+            values,
+            ?duration,
+            rows,
+            success = result.is_ok(),
+            "Executed query"
+        );
+        if let Some(threshold) = self.logging.slow_query_threshold {
+            if duration >= threshold {
+                warn!(
+                    target: QUERY_LOG_TARGET,
+                    sql = self.sql,
+                    values,
+                    ?duration,
+                    ?threshold,
+                    rows,
+                    "Slow query"
+                );
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_logging_builder() {
+        let logging = QueryLogging::new()
+            .slow_query_threshold(Duration::from_millis(100))
+            .log_values(true);
+
+        assert_eq!(
+            logging.slow_query_threshold,
+            Some(Duration::from_millis(100))
+        );
+        assert!(logging.log_values);
+    }
+
+    #[test]
+    fn query_log_values_redacted() {
+        let log = QueryLog::start(QueryLogging::new(), "SELECT ?", Some(&[1]));
+
+        assert_eq!(log.values, None);
+    }
+
+    #[test]
+    fn query_log_values() {
+        let log = QueryLog::start(QueryLogging::new().log_values(true), "SELECT ?", Some(&[1]));
+
+        assert_eq!(log.values.as_deref(), Some("[1]"));
+    }
+
+    #[cot::test]
+    async fn query_stats_scope() {
+        let stats = QueryStats::new();
+        assert!(QueryStats::current().is_none());
+
+        stats
+            .clone()
+            .scope(async {
+                let log = QueryLog::start::<()>(QueryLogging::new(), "SELECT 1", None);
+                log.finish(Ok::<_, ()>(()), |()| 1).unwrap();
+                assert_eq!(QueryStats::current().unwrap().count(), 1);
+            })
+            .await;
+
+        assert_eq!(stats.count(), 1);
+        assert!(QueryStats::current().is_none());
+    }
+}
@@ -1,4 +1,10 @@
-use crate::db::{DatabaseBackend, DatabaseError, Model, Result};
+use std::marker::PhantomData;
+
+use crate::db::query::Expr;
+use crate::db::{
+    Database, DatabaseBackend, DatabaseError, DbFieldValue, DbValue, Identifier, Model, PrimaryKey,
+    Result, ToDbFieldValue,
+};
 
 /// A foreign key to another model.
 ///
@@ -51,9 +57,9 @@ pub enum ForeignKey<T: Model> {
 
 impl<T: Model> ForeignKey<T> {
     /// Returns the primary key of the referenced model.
-    pub fn primary_key(&self) -> &T::PrimaryKey {
+    pub fn primary_key(&self) -> T::PrimaryKey {
         match self {
-            Self::PrimaryKey(pk) => pk,
+            Self::PrimaryKey(pk) => pk.clone(),
             Self::Model(model) => model.primary_key(),
         }
     }
@@ -110,6 +116,140 @@ impl<T: Model> ForeignKey<T> {
     }
 }
 
+impl<T: Model + Clone + Send + Sync> ForeignKey<T>
+where
+    T::PrimaryKey: PrimaryKey,
+{
+    /// Retrieves the models referenced by given foreign key field of all the
+    /// given models, using a single query (or a few, for large numbers of
+    /// models), and stores them in the foreign keys.
+    ///
+    /// This avoids the "N+1 queries" problem when the related models are
+    /// needed for a list of models, which happens when calling
+    /// [`Self::get`] for each of them. The foreign keys that already store
+    /// the model are left unchanged.
+    ///
+    /// The `field` function should return the foreign key field of the given
+    /// model. For the optional foreign keys, use [`Self::prefetch_optional`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DatabaseError::ForeignKeyNotFound`] error if any of the
+    /// referenced models could not be found in the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, Database, ForeignKey, Model, model};
+    ///
+    /// #[model]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     author: ForeignKey<Author>,
+    /// }
+    ///
+    /// #[derive(Clone)]
+    /// #[model]
+    /// struct Author {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     name: String,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let mut posts = Post::objects().all(db).await?;
+    /// ForeignKey::prefetch(db, &mut posts, |post| &mut post.author).await?;
+    ///
+    /// for post in &posts {
+    ///     println!("{}", post.author.model().unwrap().name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prefetch<S, DB, F>(db: &DB, models: &mut [S], mut field: F) -> Result<()>
+    where
+        DB: DatabaseBackend,
+        F: FnMut(&mut S) -> &mut Self,
+    {
+        prefetch_foreign_keys(db, models, |model| Some(field(model))).await
+    }
+
+    /// Retrieves the models referenced by given optional foreign key field of
+    /// all the given models, and stores them in the foreign keys.
+    ///
+    /// This is the same as [`Self::prefetch`], but for the fields of type
+    /// `Option<ForeignKey<T>>`. The fields set to `None` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DatabaseError::ForeignKeyNotFound`] error if any of the
+    /// referenced models could not be found in the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn prefetch_optional<S, DB, F>(db: &DB, models: &mut [S], mut field: F) -> Result<()>
+    where
+        DB: DatabaseBackend,
+        F: FnMut(&mut S) -> &mut Option<Self>,
+    {
+        prefetch_foreign_keys(db, models, |model| field(model).as_mut()).await
+    }
+}
+
+/// The maximum number of primary keys used in a single query when
+/// prefetching the related models.
+const PREFETCH_BATCH_SIZE: usize = 1000;
+
+async fn prefetch_foreign_keys<S, T, DB, F>(db: &DB, models: &mut [S], mut field: F) -> Result<()>
+where
+    T: Model + Clone + Send + Sync,
+    T::PrimaryKey: PrimaryKey,
+    DB: DatabaseBackend,
+    F: FnMut(&mut S) -> Option<&mut ForeignKey<T>>,
+{
+    let mut primary_keys: Vec<DbValue> = Vec::new();
+    for model in models.iter_mut() {
+        if let Some(ForeignKey::PrimaryKey(primary_key)) = field(model) {
+            if let DbFieldValue::Value(value) = primary_key.to_db_field_value() {
+                if !primary_keys.contains(&value) {
+                    primary_keys.push(value);
+                }
+            }
+        }
+    }
+
+    let mut targets: Vec<(DbValue, T)> = Vec::new();
+    for chunk in primary_keys.chunks(PREFETCH_BATCH_SIZE) {
+        let filter = Expr::is_in(
+            Expr::field(T::PRIMARY_KEY_NAME),
+            chunk.iter().cloned().map(Expr::Value),
+        );
+        for target in T::objects().filter(filter).all(db).await? {
+            targets.push((primary_key_value(&target)?, target));
+        }
+    }
+
+    for model in models.iter_mut() {
+        if let Some(foreign_key) = field(model) {
+            if let ForeignKey::PrimaryKey(primary_key) = foreign_key {
+                let DbFieldValue::Value(value) = primary_key.to_db_field_value() else {
+                    continue;
+                };
+                let target = targets
+                    .iter()
+                    .find(|(target_pk, _)| *target_pk == value)
+                    .map(|(_, target)| target.clone())
+                    .ok_or(DatabaseError::ForeignKeyNotFound)?;
+                *foreign_key = ForeignKey::Model(Box::new(target));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl<T: Model> PartialEq for ForeignKey<T>
 where
     T::PrimaryKey: PartialEq,
@@ -129,7 +269,441 @@ impl<T: Model> From<T> for ForeignKey<T> {
 
 impl<T: Model> From<&T> for ForeignKey<T> {
     fn from(model: &T) -> Self {
-        Self::PrimaryKey(model.primary_key().clone())
+        Self::PrimaryKey(model.primary_key())
+    }
+}
+
+/// A many-to-many relationship with another model.
+///
+/// Unlike the other fields, this is not stored as a column in the model's
+/// table. Instead, the relationship is stored in a separate join table (named
+/// after the model's table and the field) that is automatically created by the
+/// migrations generated with the Cot CLI.
+///
+/// The relationship is managed by the [`ManyToManyRef`] generated for the field
+/// in the model's `Fields` struct.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, ManyToMany, Model, model, query};
+/// use cot::request::{Request, RequestExt};
+/// use cot::response::Response;
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     tags: ManyToMany<Tag>,
+/// }
+///
+/// #[model]
+/// struct Tag {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     name: String,
+/// }
+///
+/// async fn index(request: &Request) -> cot::Result<Response> {
+///     let mut tag = Tag {
+///         id: Auto::auto(),
+///         name: "rust".to_string(),
+///     };
+///     tag.save(request.db()).await?;
+///     let mut post = Post {
+///         id: Auto::auto(),
+///         tags: ManyToMany::new(),
+///     };
+///     post.save(request.db()).await?;
+///
+///     PostFields::tags.add(request.db(), &post, &tag).await?;
+///     let tags = PostFields::tags.get(request.db(), &post).await?;
+///     let rust_posts = query!(Post, $tags.contains(&tag)).all(request.db()).await?;
+///
+///     // ...
+/// #   todo!()
+/// }
+/// ```
+#[derive(derive_more::Debug)]
+pub struct ManyToMany<T> {
+    #[debug(skip)]
+    phantom_data: PhantomData<fn() -> T>,
+}
+
+impl<T: Model> ManyToMany<T> {
+    /// Creates a new many-to-many relationship field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, ManyToMany, model};
+    ///
+    /// #[model]
+    /// struct Tag {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    /// }
+    ///
+    /// let tags = ManyToMany::<Tag>::new();
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<T: Model> Default for ManyToMany<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for ManyToMany<T> {
+    fn clone(&self) -> Self {
+        Self {
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for ManyToMany<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for ManyToMany<T> {}
+
+/// A reference to a many-to-many relationship between the `S` (source) and `T`
+/// (target) models.
+///
+/// This is generated by the [`model`](crate::db::model) macro for each
+/// [`ManyToMany`] field in the model's `Fields` struct, and is used to manage
+/// the relationship, as well as to filter the source models in queries.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, ManyToMany, model, query};
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     tags: ManyToMany<Tag>,
+/// }
+///
+/// #[model]
+/// struct Tag {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+/// }
+///
+/// assert_eq!(PostFields::tags.join_table().as_str(), "cot__post_tags");
+///
+/// let tag = Tag { id: Auto::fixed(1) };
+/// let query = query!(Post, $tags.contains(&tag));
+/// ```
+#[derive(derive_more::Debug)]
+pub struct ManyToManyRef<S, T> {
+    join_table: Identifier,
+    #[debug(skip)]
+    phantom_data: PhantomData<fn() -> (S, T)>,
+}
+
+impl<S: Model, T: Model> ManyToManyRef<S, T>
+where
+    S::PrimaryKey: PrimaryKey,
+    T::PrimaryKey: PrimaryKey,
+{
+    /// The name of the join table column referencing the source model.
+    pub const SOURCE_COLUMN: Identifier = Identifier::new("source_id");
+    /// The name of the join table column referencing the target model.
+    pub const TARGET_COLUMN: Identifier = Identifier::new("target_id");
+
+    /// Creates a new many-to-many relationship reference using given join
+    /// table.
+    #[must_use]
+    pub const fn new(join_table: Identifier) -> Self {
+        Self {
+            join_table,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Returns the name of the join table of the relationship.
+    #[must_use]
+    pub fn join_table(&self) -> Identifier {
+        self.join_table
+    }
+
+    /// Returns an expression that checks whether the source model is related
+    /// to the given target model. This is used by `$field.contains(...)` in
+    /// the [`query!`](crate::db::query!) macro.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target model has not been saved to the database.
+    #[must_use]
+    pub fn contains(&self, target: &T) -> Expr {
+        Expr::in_subquery(
+            Expr::field(S::PRIMARY_KEY_NAME),
+            self.join_table,
+            Self::SOURCE_COLUMN,
+            Expr::eq(
+                Expr::field(Self::TARGET_COLUMN),
+                Expr::value(target.primary_key()),
+            ),
+        )
+    }
+}
+
+impl<S: Model + Sync, T: Model + Sync> ManyToManyRef<S, T>
+where
+    S::PrimaryKey: PrimaryKey,
+    T::PrimaryKey: PrimaryKey,
+{
+    /// Retrieves all the target models related to given source model.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if the source model has not
+    /// been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn get<DB: DatabaseBackend>(&self, db: &DB, source: &S) -> Result<Vec<T>> {
+        let filter = Expr::in_subquery(
+            Expr::field(T::PRIMARY_KEY_NAME),
+            self.join_table,
+            Self::TARGET_COLUMN,
+            Expr::eq(
+                Expr::field(Self::SOURCE_COLUMN),
+                Expr::Value(primary_key_value(source)?),
+            ),
+        );
+
+        T::objects().filter(filter).all(db).await
+    }
+
+    /// Retrieves the target models related to each of the given source
+    /// models, using a single query (or a few, for large numbers of models).
+    ///
+    /// This avoids the "N+1 queries" problem when calling [`Self::get`] for
+    /// each of the source models. The returned vector contains the related
+    /// models of each source model, in the same order as `sources`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if any of the source models
+    /// has not been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, Database, ManyToMany, Model, model};
+    ///
+    /// #[model]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     tags: ManyToMany<Tag>,
+    /// }
+    ///
+    /// #[derive(Clone)]
+    /// #[model]
+    /// struct Tag {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     name: String,
+    /// }
+    ///
+    /// # async fn run(db: &Database) -> cot::db::Result<()> {
+    /// let posts = Post::objects().all(db).await?;
+    /// let tags = PostFields::tags.prefetch(db, &posts).await?;
+    ///
+    /// for (post, tags) in posts.iter().zip(tags) {
+    ///     println!("{}: {} tags", post.id, tags.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prefetch(&self, db: &Database, sources: &[S]) -> Result<Vec<Vec<T>>>
+    where
+        T: Clone,
+    {
+        let source_pks = sources
+            .iter()
+            .map(primary_key_value)
+            .collect::<Result<Vec<_>>>()?;
+        let mut unique_source_pks: Vec<DbValue> = Vec::new();
+        for source_pk in &source_pks {
+            if !unique_source_pks.contains(source_pk) {
+                unique_source_pks.push(source_pk.clone());
+            }
+        }
+
+        let mut links: Vec<(DbValue, T)> = Vec::new();
+        for chunk in unique_source_pks.chunks(PREFETCH_BATCH_SIZE) {
+            let statement = sea_query::Query::select()
+                .columns(
+                    T::COLUMNS
+                        .iter()
+                        .map(|column| (T::TABLE_NAME, Identifier::from(column.name.as_str()))),
+                )
+                .column((self.join_table, Self::SOURCE_COLUMN))
+                .from(T::TABLE_NAME)
+                .inner_join(
+                    self.join_table,
+                    sea_query::Expr::col((self.join_table, Self::TARGET_COLUMN))
+                        .equals((T::TABLE_NAME, T::PRIMARY_KEY_NAME)),
+                )
+                .and_where(
+                    sea_query::Expr::col((self.join_table, Self::SOURCE_COLUMN))
+                        .is_in(chunk.iter().cloned()),
+                )
+                .to_owned();
+
+            for row in db.fetch_all(&statement).await? {
+                // the source primary key is selected after all the target columns
+                let source_pk = row
+                    .get::<S::PrimaryKey>(T::COLUMNS.len())?
+                    .to_db_field_value()
+                    .expect_value("source primary key cannot be auto");
+                links.push((source_pk, T::from_db(row)?));
+            }
+        }
+
+        Ok(source_pks
+            .iter()
+            .map(|source_pk| {
+                links
+                    .iter()
+                    .filter(|(link_source_pk, _)| link_source_pk == source_pk)
+                    .map(|(_, target)| target.clone())
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Adds the target model to the relationship with given source model. If
+    /// the models are already related, this is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if any of the models has not
+    /// been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn add(&self, db: &Database, source: &S, target: &T) -> Result<()> {
+        let source_pk = primary_key_value(source)?;
+        let target_pk = primary_key_value(target)?;
+
+        let exists_statement = sea_query::Query::select()
+            .expr(sea_query::Expr::val(1))
+            .from(self.join_table)
+            .and_where(Self::link_condition(
+                source_pk.clone(),
+                Some(target_pk.clone()),
+            ))
+            .limit(1)
+            .to_owned();
+        if db.fetch_option(&exists_statement).await?.is_some() {
+            return Ok(());
+        }
+
+        let insert_statement = sea_query::Query::insert()
+            .into_table(self.join_table)
+            .columns([Self::SOURCE_COLUMN, Self::TARGET_COLUMN])
+            .values([source_pk.into(), target_pk.into()])?
+            .to_owned();
+        db.execute_statement(&insert_statement).await?;
+
+        Ok(())
+    }
+
+    /// Removes the target model from the relationship with given source
+    /// model. If the models are not related, this is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if any of the models has not
+    /// been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn remove(&self, db: &Database, source: &S, target: &T) -> Result<()> {
+        let condition =
+            Self::link_condition(primary_key_value(source)?, Some(primary_key_value(target)?));
+        self.delete_links(db, condition).await
+    }
+
+    /// Removes all the target models from the relationship with given source
+    /// model.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if the source model has not
+    /// been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn clear(&self, db: &Database, source: &S) -> Result<()> {
+        let condition = Self::link_condition(primary_key_value(source)?, None);
+        self.delete_links(db, condition).await
+    }
+
+    /// Replaces all the target models related to given source model with the
+    /// given ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if any of the models has not
+    /// been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn set<'a, I>(&self, db: &Database, source: &S, targets: I) -> Result<()>
+    where
+        I: IntoIterator<Item = &'a T> + Send,
+        I::IntoIter: Send,
+    {
+        self.clear(db, source).await?;
+        for target in targets {
+            self.add(db, source, target).await?;
+        }
+
+        Ok(())
+    }
+
+    fn link_condition(source_pk: DbValue, target_pk: Option<DbValue>) -> sea_query::SimpleExpr {
+        let condition = sea_query::Expr::col(Self::SOURCE_COLUMN).eq(source_pk);
+        match target_pk {
+            Some(target_pk) => {
+                condition.and(sea_query::Expr::col(Self::TARGET_COLUMN).eq(target_pk))
+            }
+            None => condition,
+        }
+    }
+
+    async fn delete_links(&self, db: &Database, condition: sea_query::SimpleExpr) -> Result<()> {
+        let statement = sea_query::Query::delete()
+            .from_table(self.join_table)
+            .cond_where(condition)
+            .to_owned();
+        db.execute_statement(&statement).await?;
+
+        Ok(())
+    }
+}
+
+fn primary_key_value<M: Model>(model: &M) -> Result<DbValue>
+where
+    M::PrimaryKey: PrimaryKey,
+{
+    match model.primary_key().to_db_field_value() {
+        DbFieldValue::Value(value) => Ok(value),
+        DbFieldValue::Auto => Err(DatabaseError::UnsavedModel),
     }
 }
 
@@ -152,6 +726,11 @@ pub enum ForeignKeyOnDeletePolicy {
     Cascade,
     /// Set the foreign key in the referencing row to [`None`].
     SetNone,
+    /// Set the foreign key in the referencing row to the default value of the
+    /// column. The columns created by Cot don't have a default value other
+    /// than `NULL`, so this behaves like [`Self::SetNone`] unless the default
+    /// has been defined manually.
+    SetDefault,
 }
 
 impl From<ForeignKeyOnDeletePolicy> for sea_query::ForeignKeyAction {
@@ -161,8 +740,129 @@ impl From<ForeignKeyOnDeletePolicy> for sea_query::ForeignKeyAction {
             ForeignKeyOnDeletePolicy::Restrict => Self::Restrict,
             ForeignKeyOnDeletePolicy::Cascade => Self::Cascade,
             ForeignKeyOnDeletePolicy::SetNone => Self::SetNull,
+            ForeignKeyOnDeletePolicy::SetDefault => Self::SetDefault,
+        }
+    }
+}
+
+/// A foreign key relationship between two tables, as defined by the
+/// migrations.
+///
+/// These are used to emulate the `ON DELETE` actions on the database backends
+/// that don't enforce foreign key constraints.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct ForeignKeyRelation {
+    pub(crate) table: Identifier,
+    pub(crate) column: Identifier,
+    pub(crate) to_table: Identifier,
+    pub(crate) to_column: Identifier,
+    pub(crate) on_delete: ForeignKeyOnDeletePolicy,
+}
+
+/// Emulates the `ON DELETE` actions of the foreign keys referencing the rows
+/// of `table` matching `condition`. This has to be called before these rows
+/// are deleted.
+///
+/// All the restrictions are checked before any change is made, so an error
+/// doesn't leave the database partially modified.
+pub(super) async fn emulate_on_delete(
+    db: &Database,
+    relations: &[ForeignKeyRelation],
+    table: Identifier,
+    condition: &sea_query::Condition,
+) -> Result<()> {
+    let mut actions = Vec::new();
+    collect_on_delete_actions(relations, table, condition, &mut Vec::new(), &mut actions);
+
+    for (relation, condition) in &actions {
+        if matches!(
+            relation.on_delete,
+            ForeignKeyOnDeletePolicy::NoAction | ForeignKeyOnDeletePolicy::Restrict
+        ) {
+            let statement = sea_query::Query::select()
+                .expr(sea_query::Expr::val(1))
+                .from(relation.table)
+                .cond_where(condition.clone())
+                .limit(1)
+                .to_owned();
+            if db.fetch_option(&statement).await?.is_some() {
+                return Err(DatabaseError::ForeignKeyViolation {
+                    table: relation.table.to_string(),
+                    column: relation.column.to_string(),
+                });
+            }
         }
     }
+
+    // the nested relations are collected first, as their conditions refer to the
+    // rows deleted by the actions following them
+    for (relation, condition) in actions {
+        match relation.on_delete {
+            ForeignKeyOnDeletePolicy::NoAction | ForeignKeyOnDeletePolicy::Restrict => {}
+            ForeignKeyOnDeletePolicy::Cascade => {
+                let statement = sea_query::Query::delete()
+                    .from_table(relation.table)
+                    .cond_where(condition)
+                    .to_owned();
+                db.execute_statement(&statement).await?;
+            }
+            ForeignKeyOnDeletePolicy::SetNone | ForeignKeyOnDeletePolicy::SetDefault => {
+                let statement = sea_query::Query::update()
+                    .table(relation.table)
+                    .value(
+                        relation.column,
+                        sea_query::SimpleExpr::Keyword(sea_query::Keyword::Null),
+                    )
+                    .cond_where(condition)
+                    .to_owned();
+                db.execute_statement(&statement).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects the relations referencing the rows of `table` matching
+/// `condition`, along with the conditions matching the referencing rows. The
+/// relations referencing the rows that are deleted by a cascading relation go
+/// before it.
+///
+/// Cascading relations are followed recursively, except for the ones that are
+/// already being followed, so that self-referencing tables don't cause an
+/// infinite recursion.
+fn collect_on_delete_actions(
+    relations: &[ForeignKeyRelation],
+    table: Identifier,
+    condition: &sea_query::Condition,
+    path: &mut Vec<ForeignKeyRelation>,
+    actions: &mut Vec<(ForeignKeyRelation, sea_query::Condition)>,
+) {
+    for relation in relations {
+        if relation.to_table != table || path.contains(relation) {
+            continue;
+        }
+
+        let referenced = sea_query::Query::select()
+            .column(relation.to_column)
+            .from(table)
+            .cond_where(condition.clone())
+            .to_owned();
+        let relation_condition = sea_query::Condition::all()
+            .add(sea_query::Expr::col(relation.column).in_subquery(referenced));
+        if relation.on_delete == ForeignKeyOnDeletePolicy::Cascade {
+            path.push(*relation);
+            collect_on_delete_actions(
+                relations,
+                relation.table,
+                &relation_condition,
+                path,
+                actions,
+            );
+            path.pop();
+        }
+        actions.push((*relation, relation_condition));
+    }
 }
 
 /// A foreign key on update constraint.
@@ -201,7 +901,9 @@ impl From<ForeignKeyOnUpdatePolicy> for sea_query::ForeignKeyAction {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::{Auto, model};
+    use crate::db::migrations::{Field, Operation};
+    use crate::db::{Auto, DatabaseField, model, query};
+    use crate::test::TestDatabase;
 
     #[derive(Debug, Clone, PartialEq)]
     #[model]
@@ -214,7 +916,7 @@ mod tests {
     fn test_primary_key() {
         let fk = ForeignKey::<TestModel>::PrimaryKey(Auto::fixed(1));
 
-        assert_eq!(fk.primary_key(), &Auto::fixed(1));
+        assert_eq!(fk.primary_key(), Auto::fixed(1));
     }
 
     #[test]
@@ -223,7 +925,7 @@ mod tests {
         let fk = ForeignKey::Model(Box::new(model.clone()));
 
         assert_eq!(fk.model().unwrap(), &model);
-        assert_eq!(fk.primary_key(), &Auto::fixed(1));
+        assert_eq!(fk.primary_key(), Auto::fixed(1));
     }
 
     #[test]
@@ -262,6 +964,246 @@ mod tests {
         let model = TestModel { id: Auto::fixed(1) };
         let fk: ForeignKey<TestModel> = ForeignKey::from(&model);
 
-        assert_eq!(fk.primary_key(), &Auto::fixed(1));
+        assert_eq!(fk.primary_key(), Auto::fixed(1));
+    }
+
+    #[test]
+    fn on_delete_policy_into_foreign_key_action() {
+        assert!(matches!(
+            sea_query::ForeignKeyAction::from(ForeignKeyOnDeletePolicy::SetDefault),
+            sea_query::ForeignKeyAction::SetDefault
+        ));
+        assert!(matches!(
+            sea_query::ForeignKeyAction::from(ForeignKeyOnDeletePolicy::SetNone),
+            sea_query::ForeignKeyAction::SetNull
+        ));
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Parent {
+        #[model(primary_key)]
+        id: Auto<i32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Child {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        parent: ForeignKey<Parent>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct GrandChild {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        child: Option<ForeignKey<Child>>,
+    }
+
+    /// Creates the tables for the test models without any foreign key
+    /// constraints, and returns the relations between them.
+    async fn create_tables_without_constraints(db: &Database) -> Vec<ForeignKeyRelation> {
+        const ID: Field = Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+            .primary_key()
+            .auto();
+        const OPERATIONS: &[Operation] = &[
+            Operation::create_model()
+                .table_name(Identifier::new("cot__parent"))
+                .fields(&[ID])
+                .build(),
+            Operation::create_model()
+                .table_name(Identifier::new("cot__child"))
+                .fields(&[
+                    ID,
+                    Field::new(Identifier::new("parent"), <i32 as DatabaseField>::TYPE),
+                ])
+                .build(),
+            Operation::create_model()
+                .table_name(Identifier::new("cot__grand_child"))
+                .fields(&[
+                    ID,
+                    Field::new(Identifier::new("child"), <i32 as DatabaseField>::TYPE)
+                        .set_null(true),
+                ])
+                .build(),
+        ];
+        for operation in OPERATIONS {
+            operation.forwards(db).await.unwrap();
+        }
+
+        vec![
+            ForeignKeyRelation {
+                table: Child::TABLE_NAME,
+                column: Identifier::new("parent"),
+                to_table: Parent::TABLE_NAME,
+                to_column: Parent::PRIMARY_KEY_NAME,
+                on_delete: ForeignKeyOnDeletePolicy::Cascade,
+            },
+            ForeignKeyRelation {
+                table: GrandChild::TABLE_NAME,
+                column: Identifier::new("child"),
+                to_table: Child::TABLE_NAME,
+                to_column: Child::PRIMARY_KEY_NAME,
+                on_delete: ForeignKeyOnDeletePolicy::SetNone,
+            },
+        ]
+    }
+
+    #[cot_macros::dbtest]
+    async fn emulate_on_delete_cascade_and_set_null(test_db: &mut TestDatabase) {
+        let relations = create_tables_without_constraints(test_db).await;
+
+        let mut parents = [Parent { id: Auto::auto() }, Parent { id: Auto::auto() }];
+        let mut children = Vec::new();
+        let mut grand_children = Vec::new();
+        for parent in &mut parents {
+            parent.save(&**test_db).await.unwrap();
+            let mut child = Child {
+                id: Auto::auto(),
+                parent: ForeignKey::from(&*parent),
+            };
+            child.save(&**test_db).await.unwrap();
+            let mut grand_child = GrandChild {
+                id: Auto::auto(),
+                child: Some(ForeignKey::from(&child)),
+            };
+            grand_child.save(&**test_db).await.unwrap();
+            children.push(child);
+            grand_children.push(grand_child);
+        }
+
+        let deleted_id = parents[0].id;
+        let condition = query!(Parent, $id == deleted_id).filter_condition();
+        emulate_on_delete(test_db, &relations, Parent::TABLE_NAME, &condition)
+            .await
+            .unwrap();
+        query!(Parent, $id == deleted_id)
+            .delete(&**test_db)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Parent::objects().all(&**test_db).await.unwrap(),
+            [parents[1].clone()]
+        );
+        assert_eq!(
+            Child::objects().all(&**test_db).await.unwrap(),
+            [children[1].clone()]
+        );
+        let mut orphaned = grand_children[0].clone();
+        orphaned.child = None;
+        assert_eq!(
+            GrandChild::objects().all(&**test_db).await.unwrap(),
+            [orphaned, grand_children[1].clone()]
+        );
+    }
+
+    #[cot_macros::dbtest]
+    async fn emulate_on_delete_restrict(test_db: &mut TestDatabase) {
+        let mut relations = create_tables_without_constraints(test_db).await;
+        relations[1].on_delete = ForeignKeyOnDeletePolicy::Restrict;
+
+        let mut parent = Parent { id: Auto::auto() };
+        parent.save(&**test_db).await.unwrap();
+        let mut child = Child {
+            id: Auto::auto(),
+            parent: ForeignKey::from(&parent),
+        };
+        child.save(&**test_db).await.unwrap();
+        let mut grand_child = GrandChild {
+            id: Auto::auto(),
+            child: Some(ForeignKey::from(&child)),
+        };
+        grand_child.save(&**test_db).await.unwrap();
+
+        let error = emulate_on_delete(
+            test_db,
+            &relations,
+            Parent::TABLE_NAME,
+            &sea_query::Condition::all(),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            DatabaseError::ForeignKeyViolation { ref table, ref column }
+                if table == "cot__grand_child" && column == "child"
+        ));
+        // nothing has been deleted
+        assert_eq!(Child::objects().all(&**test_db).await.unwrap(), [child]);
+    }
+
+    #[cot_macros::dbtest]
+    async fn prefetch_foreign_keys(test_db: &mut TestDatabase) {
+        create_tables_without_constraints(test_db).await;
+
+        let mut parents = [Parent { id: Auto::auto() }, Parent { id: Auto::auto() }];
+        for parent in &mut parents {
+            parent.save(&**test_db).await.unwrap();
+        }
+        let mut child = Child {
+            id: Auto::auto(),
+            parent: ForeignKey::from(&parents[0]),
+        };
+        child.save(&**test_db).await.unwrap();
+        for parent in [&parents[0], &parents[1], &parents[0]] {
+            let mut child = Child {
+                id: Auto::auto(),
+                parent: ForeignKey::from(parent),
+            };
+            child.save(&**test_db).await.unwrap();
+        }
+        let mut grand_children = [
+            GrandChild {
+                id: Auto::auto(),
+                child: Some(ForeignKey::from(&child)),
+            },
+            GrandChild {
+                id: Auto::auto(),
+                child: None,
+            },
+        ];
+        for grand_child in &mut grand_children {
+            grand_child.save(&**test_db).await.unwrap();
+        }
+
+        let mut children = Child::objects().all(&**test_db).await.unwrap();
+        ForeignKey::prefetch(&**test_db, &mut children, |child| &mut child.parent)
+            .await
+            .unwrap();
+        let parent_ids: Vec<_> = children
+            .iter()
+            .map(|child| child.parent.model().unwrap().id)
+            .collect();
+        assert_eq!(
+            parent_ids,
+            [parents[0].id, parents[0].id, parents[1].id, parents[0].id]
+        );
+
+        let mut grand_children = GrandChild::objects().all(&**test_db).await.unwrap();
+        ForeignKey::prefetch_optional(&**test_db, &mut grand_children, |grand_child| {
+            &mut grand_child.child
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            grand_children[0].child.as_ref().unwrap().model().unwrap(),
+            &child
+        );
+        assert!(grand_children[1].child.is_none());
+
+        let deleted_id = parents[1].id;
+        query!(Parent, $id == deleted_id)
+            .delete(&**test_db)
+            .await
+            .unwrap();
+        let mut children = Child::objects().all(&**test_db).await.unwrap();
+        let error = ForeignKey::prefetch(&**test_db, &mut children, |child| &mut child.parent)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DatabaseError::ForeignKeyNotFound));
     }
 }
@@ -11,17 +11,158 @@ macro_rules! impl_sea_query_db_backend {
         #[derive(Debug)]
         pub(super) struct $db_name {
             db_connection: $pool_ty,
+            /// The transaction all the statements are executed in, if any. It
+            /// is shared between the nested transactions, which are
+            /// implemented using savepoints.
+            transaction: Option<
+                std::sync::Arc<tokio::sync::Mutex<Option<sqlx::Transaction<'static, $sqlx_db_ty>>>>,
+            >,
+            /// The number of savepoints this transaction is nested in; `0` for
+            /// the outermost transaction.
+            savepoint_depth: usize,
+            query_logging: crate::db::QueryLogging,
         }
 
         impl $db_name {
-            pub(super) async fn new(url: &str) -> crate::db::Result<Self> {
-                let db_connection = <$pool_ty>::connect(url).await?;
+            pub(super) async fn new(
+                url: &str,
+                options: &crate::db::PoolOptions,
+            ) -> crate::db::Result<Self> {
+                let mut pool_options = sqlx::pool::PoolOptions::<$sqlx_db_ty>::new();
+                if let Some(max_connections) = options.max_connections {
+                    pool_options = pool_options.max_connections(max_connections);
+                }
+                if let Some(min_connections) = options.min_connections {
+                    pool_options = pool_options.min_connections(min_connections);
+                }
+                if let Some(acquire_timeout) = options.acquire_timeout {
+                    pool_options = pool_options.acquire_timeout(acquire_timeout);
+                }
+                if let Some(idle_timeout) = options.idle_timeout {
+                    pool_options = pool_options.idle_timeout(idle_timeout);
+                }
+                if let Some(max_lifetime) = options.max_lifetime {
+                    pool_options = pool_options.max_lifetime(max_lifetime);
+                }
+                let db_connection = pool_options.connect(url).await?;
 
-                let db = Self { db_connection };
+                let db = Self {
+                    db_connection,
+                    transaction: None,
+                    savepoint_depth: 0,
+                    query_logging: crate::db::QueryLogging::default(),
+                };
                 db.init().await?;
                 Ok(db)
             }
 
+            /// Starts a new transaction, or creates a savepoint if this is
+            /// already a transaction.
+            pub(super) async fn begin(&self) -> crate::db::Result<Self> {
+                match &self.transaction {
+                    Some(transaction) => {
+                        let savepoint_depth = self.savepoint_depth + 1;
+                        self.execute_sql(&format!(
+                            "SAVEPOINT {}",
+                            Self::savepoint_name(savepoint_depth)
+                        ))
+                        .await?;
+
+                        Ok(Self {
+                            db_connection: self.db_connection.clone(),
+                            transaction: Some(std::sync::Arc::clone(transaction)),
+                            savepoint_depth,
+                            query_logging: self.query_logging,
+                        })
+                    }
+                    None => {
+                        let transaction = self.db_connection.begin().await?;
+                        tracing::debug!("Transaction started");
+
+                        Ok(Self {
+                            db_connection: self.db_connection.clone(),
+                            transaction: Some(std::sync::Arc::new(tokio::sync::Mutex::new(Some(
+                                transaction,
+                            )))),
+                            savepoint_depth: 0,
+                            query_logging: self.query_logging,
+                        })
+                    }
+                }
+            }
+
+            /// Commits the transaction, or releases the savepoint if this is a
+            /// nested transaction.
+            pub(super) async fn commit(&self) -> crate::db::Result<()> {
+                if self.savepoint_depth > 0 {
+                    self.execute_sql(&format!(
+                        "RELEASE SAVEPOINT {}",
+                        Self::savepoint_name(self.savepoint_depth)
+                    ))
+                    .await?;
+                } else {
+                    self.take_transaction().await?.commit().await?;
+                    tracing::debug!("Transaction committed");
+                }
+
+                Ok(())
+            }
+
+            /// Rolls back the transaction, or rolls back to the savepoint if
+            /// this is a nested transaction.
+            pub(super) async fn rollback(&self) -> crate::db::Result<()> {
+                if self.savepoint_depth > 0 {
+                    let savepoint_name = Self::savepoint_name(self.savepoint_depth);
+                    self.execute_sql(&format!("ROLLBACK TO SAVEPOINT {savepoint_name}"))
+                        .await?;
+                    self.execute_sql(&format!("RELEASE SAVEPOINT {savepoint_name}"))
+                        .await?;
+                } else {
+                    self.take_transaction().await?.rollback().await?;
+                    tracing::debug!("Transaction rolled back");
+                }
+
+                Ok(())
+            }
+
+            pub(super) fn set_query_logging(&mut self, query_logging: crate::db::QueryLogging) {
+                self.query_logging = query_logging;
+            }
+
+            pub(super) fn is_transaction(&self) -> bool {
+                self.transaction.is_some()
+            }
+
+            async fn take_transaction(
+                &self,
+            ) -> crate::db::Result<sqlx::Transaction<'static, $sqlx_db_ty>> {
+                let transaction = self
+                    .transaction
+                    .as_ref()
+                    .expect("commit or rollback called outside of a transaction");
+
+                transaction
+                    .lock()
+                    .await
+                    .take()
+                    .ok_or(crate::db::DatabaseError::TransactionFinished)
+            }
+
+            fn savepoint_name(depth: usize) -> String {
+                format!("cot_savepoint_{depth}")
+            }
+
+            pub(super) fn pool_status(&self) -> crate::db::PoolStatus {
+                let size = self.db_connection.size();
+                let idle = u32::try_from(self.db_connection.num_idle()).unwrap_or(u32::MAX);
+
+                crate::db::PoolStatus {
+                    size,
+                    idle: idle.min(size),
+                    max_connections: self.db_connection.options().get_max_connections(),
+                }
+            }
+
             pub(super) async fn close(&self) -> crate::db::Result<()> {
                 self.db_connection.close().await;
                 Ok(())
@@ -33,9 +174,18 @@ macro_rules! impl_sea_query_db_backend {
             ) -> crate::db::Result<Option<$row_name>> {
                 let (sql, values) = Self::build_sql(statement);
 
-                let row = Self::sqlx_query_with(&sql, values)
-                    .fetch_optional(&self.db_connection)
-                    .await?;
+                let (query, log) = self.sqlx_query_with(&sql, values);
+                let row = match &self.transaction {
+                    Some(transaction) => {
+                        let mut transaction = transaction.lock().await;
+                        let transaction = transaction
+                            .as_mut()
+                            .ok_or(crate::db::DatabaseError::TransactionFinished)?;
+                        query.fetch_optional(&mut **transaction).await
+                    }
+                    None => query.fetch_optional(&self.db_connection).await,
+                };
+                let row = log.finish(row, |row| u64::from(row.is_some()))?;
                 Ok(row.map($row_name::new))
             }
 
@@ -45,23 +195,37 @@ macro_rules! impl_sea_query_db_backend {
             ) -> crate::db::Result<Vec<$row_name>> {
                 let (sql, values) = Self::build_sql(statement);
 
-                let result = Self::sqlx_query_with(&sql, values)
-                    .fetch_all(&self.db_connection)
-                    .await?
-                    .into_iter()
-                    .map($row_name::new)
-                    .collect();
-                Ok(result)
+                self.raw_fetch_all(&sql, values).await
+            }
+
+            pub(super) async fn raw_fetch_all(
+                &self,
+                sql: &str,
+                values: sea_query_binder::SqlxValues,
+            ) -> crate::db::Result<Vec<$row_name>> {
+                let (query, log) = self.sqlx_query_with(sql, values);
+                let rows = match &self.transaction {
+                    Some(transaction) => {
+                        let mut transaction = transaction.lock().await;
+                        let transaction = transaction
+                            .as_mut()
+                            .ok_or(crate::db::DatabaseError::TransactionFinished)?;
+                        query.fetch_all(&mut **transaction).await
+                    }
+                    None => query.fetch_all(&self.db_connection).await,
+                };
+                let rows = log.finish(rows, |rows| rows.len() as u64)?;
+                Ok(rows.into_iter().map($row_name::new).collect())
             }
 
             pub(super) async fn execute_statement<T: sea_query_binder::SqlxBinder + Send + Sync>(
                 &self,
                 statement: &T,
             ) -> crate::db::Result<crate::db::StatementResult> {
-                let (sql, mut values) = Self::build_sql(statement);
-                Self::prepare_values(&mut values);
+                let (sql, values) = Self::build_sql(statement);
 
-                self.execute_sqlx(Self::sqlx_query_with(&sql, values)).await
+                let (query, log) = self.sqlx_query_with(&sql, values);
+                self.execute_sqlx(query, log).await
             }
 
             pub(super) async fn execute_schema<T: sea_query::SchemaStatementBuilder>(
@@ -71,7 +235,7 @@ macro_rules! impl_sea_query_db_backend {
                 let sql = statement.build($query_builder);
                 tracing::debug!("Schema modification: {}", sql);
 
-                self.execute_sqlx(sqlx::query(&sql)).await
+                self.execute_sql(&sql).await
             }
 
             pub(super) async fn raw_with(
@@ -79,24 +243,42 @@ macro_rules! impl_sea_query_db_backend {
                 sql: &str,
                 values: sea_query_binder::SqlxValues,
             ) -> crate::db::Result<crate::db::StatementResult> {
-                self.execute_sqlx(Self::sqlx_query_with(sql, values)).await
+                let (query, log) = self.sqlx_query_with(sql, values);
+                self.execute_sqlx(query, log).await
+            }
+
+            /// Executes an SQL statement without any values bound to it.
+            async fn execute_sql(
+                &self,
+                sql: &str,
+            ) -> crate::db::Result<crate::db::StatementResult> {
+                let log = crate::db::QueryLog::start::<()>(self.query_logging, sql, None);
+                self.execute_sqlx(sqlx::query(sql), log).await
             }
 
             async fn execute_sqlx<'a, A>(
                 &self,
                 sqlx_statement: sqlx::query::Query<'a, $sqlx_db_ty, A>,
+                log: crate::db::QueryLog<'_>,
             ) -> crate::db::Result<crate::db::StatementResult>
             where
                 A: 'a + sqlx::IntoArguments<'a, $sqlx_db_ty>,
             {
-                let result = sqlx_statement.execute(&self.db_connection).await?;
-                let result = crate::db::StatementResult {
+                let result = match &self.transaction {
+                    Some(transaction) => {
+                        let mut transaction = transaction.lock().await;
+                        let transaction = transaction
+                            .as_mut()
+                            .ok_or(crate::db::DatabaseError::TransactionFinished)?;
+                        sqlx_statement.execute(&mut **transaction).await
+                    }
+                    None => sqlx_statement.execute(&self.db_connection).await,
+                };
+                let result = log.finish(result, |result| result.rows_affected())?;
+                Ok(crate::db::StatementResult {
                     rows_affected: crate::db::RowsNum(result.rows_affected()),
                     last_inserted_row_id: Self::last_inserted_row_id_for(&result),
-                };
-
-                tracing::debug!("Rows affected: {}", result.rows_affected.0);
-                Ok(result)
+                })
             }
 
             fn build_sql<T>(statement: &T) -> (String, sea_query_binder::SqlxValues)
@@ -108,14 +290,18 @@ macro_rules! impl_sea_query_db_backend {
                 (sql, values)
             }
 
-            fn sqlx_query_with(
-                sql: &str,
+            fn sqlx_query_with<'a>(
+                &self,
+                sql: &'a str,
                 mut values: sea_query_binder::SqlxValues,
-            ) -> sqlx::query::Query<'_, $sqlx_db_ty, sea_query_binder::SqlxValues> {
+            ) -> (
+                sqlx::query::Query<'a, $sqlx_db_ty, sea_query_binder::SqlxValues>,
+                crate::db::QueryLog<'a>,
+            ) {
                 Self::prepare_values(&mut values);
-                tracing::debug!("Query: `{}` (values: {:?})", sql, values);
+                let log = crate::db::QueryLog::start(self.query_logging, sql, Some(&values.0.0));
 
-                sqlx::query_with(sql, values)
+                (sqlx::query_with(sql, values), log)
             }
         }
 
@@ -142,6 +328,11 @@ macro_rules! impl_sea_query_db_backend {
                 use sqlx::Row;
                 Ok($value_ref_name::new(self.inner.try_get_raw(index)?))
             }
+
+            fn column_index(&self, name: &str) -> crate::db::Result<usize> {
+                use sqlx::{Column, Row};
+                Ok(self.inner.try_column(name)?.ordinal())
+            }
         }
 
         #[doc = "A wrapper for the internal value type used by [`"]
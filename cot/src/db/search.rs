@@ -0,0 +1,186 @@
+//! The expressions implementing [`Query::search`](crate::db::query::Query::search).
+//!
+//! On PostgreSQL, the search columns are concatenated into a `tsvector` and
+//! matched against the search term parsed with `websearch_to_tsquery`, and
+//! the rows are ranked with `ts_rank`. The other backends fall back to
+//! checking whether each word of the search term is contained in any of the
+//! columns (ignoring the case), and rank the rows by the number of the
+//! columns matching the words.
+
+use sea_query::IntoColumnRef;
+
+use crate::db::Identifier;
+use crate::db::query::Expr;
+
+/// The expressions finding and ranking the rows matching a search term.
+#[derive(Debug, Clone)]
+pub(super) struct SearchExprs {
+    /// The condition the matching rows satisfy.
+    pub(super) condition: sea_query::SimpleExpr,
+    /// The rank of a matching row; the higher, the better the row matches.
+    pub(super) rank: sea_query::SimpleExpr,
+}
+
+impl SearchExprs {
+    /// Creates the expressions searching for `term` in `columns`, or returns
+    /// `None` if the term is empty, in which case all the rows match.
+    pub(super) fn new(columns: &[Identifier], term: &str, full_text: bool) -> Option<Self> {
+        if term.trim().is_empty() {
+            return None;
+        }
+        if columns.is_empty() {
+            return Some(Self {
+                condition: sea_query::SimpleExpr::Value(1.into()).eq(0),
+                rank: sea_query::SimpleExpr::Value(0.into()),
+            });
+        }
+
+        if full_text {
+            Some(Self::full_text(columns, term))
+        } else {
+            Some(Self::like(columns, term))
+        }
+    }
+
+    fn full_text(columns: &[Identifier], term: &str) -> Self {
+        let document = columns
+            .iter()
+            .map(|&column| {
+                sea_query::SimpleExpr::from(sea_query::Func::coalesce([
+                    sea_query::Func::cast_as(
+                        column.into_column_ref(),
+                        sea_query::Alias::new("TEXT"),
+                    )
+                    .into(),
+                    sea_query::SimpleExpr::Value("".into()),
+                ]))
+            })
+            .reduce(|lhs, rhs| concat(concat(lhs, sea_query::SimpleExpr::Value(" ".into())), rhs))
+            .expect("there is at least one column");
+        let vector = sea_query::SimpleExpr::from(
+            sea_query::Func::cust(sea_query::Alias::new("to_tsvector")).arg(document),
+        );
+        let query = sea_query::SimpleExpr::from(
+            sea_query::Func::cust(sea_query::Alias::new("websearch_to_tsquery")).arg(term),
+        );
+
+        Self {
+            condition: vector
+                .clone()
+                .binary(sea_query::BinOper::Custom("@@"), query.clone()),
+            rank: sea_query::Func::cust(sea_query::Alias::new("ts_rank"))
+                .args([vector, query])
+                .into(),
+        }
+    }
+
+    fn like(columns: &[Identifier], term: &str) -> Self {
+        let mut conditions = Vec::new();
+        let mut ranks = Vec::new();
+        for word in term.split_whitespace() {
+            let matches: Vec<_> = columns
+                .iter()
+                .map(|&column| Expr::icontains(Expr::field(column), word).as_sea_query_expr())
+                .collect();
+            ranks.extend(matches.iter().map(|matches| -> sea_query::SimpleExpr {
+                sea_query::Expr::case(matches.clone(), 1).finally(0).into()
+            }));
+            conditions.push(
+                matches
+                    .into_iter()
+                    .reduce(sea_query::SimpleExpr::or)
+                    .expect("there is at least one column"),
+            );
+        }
+
+        Self {
+            condition: conditions
+                .into_iter()
+                .reduce(sea_query::SimpleExpr::and)
+                .expect("there is at least one word"),
+            rank: ranks
+                .into_iter()
+                .reduce(sea_query::SimpleExpr::add)
+                .expect("there is at least one word"),
+        }
+    }
+}
+
+fn concat(lhs: sea_query::SimpleExpr, rhs: sea_query::SimpleExpr) -> sea_query::SimpleExpr {
+    lhs.binary(sea_query::BinOper::Custom("||"), rhs)
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_query::{PostgresQueryBuilder, SqliteQueryBuilder};
+
+    use super::*;
+
+    fn to_sql<B: sea_query::QueryBuilder>(search: &SearchExprs, builder: B) -> String {
+        sea_query::Query::select()
+            .column(Identifier::new("id"))
+            .from(Identifier::new("post"))
+            .and_where(search.condition.clone())
+            .order_by_expr(search.rank.clone(), sea_query::Order::Desc)
+            .to_string(builder)
+    }
+
+    #[test]
+    fn empty_term() {
+        assert!(SearchExprs::new(&[Identifier::new("title")], "  ", true).is_none());
+        assert!(SearchExprs::new(&[Identifier::new("title")], "", false).is_none());
+    }
+
+    #[test]
+    fn no_columns() {
+        let search = SearchExprs::new(&[], "rust", false).unwrap();
+
+        assert!(to_sql(&search, SqliteQueryBuilder).contains("WHERE 1 = 0"));
+    }
+
+    #[test]
+    fn full_text() {
+        let search = SearchExprs::new(
+            &[Identifier::new("title"), Identifier::new("body")],
+            "rust web",
+            true,
+        )
+        .unwrap();
+
+        let sql = to_sql(&search, PostgresQueryBuilder);
+        assert!(sql.contains(r#"CAST("title" AS TEXT)"#), "{sql}");
+        assert!(sql.contains(r#"CAST("body" AS TEXT)"#), "{sql}");
+        assert!(sql.contains("WHERE to_tsvector("), "{sql}");
+        assert!(
+            sql.contains("@@ websearch_to_tsquery('rust web') ORDER BY ts_rank(to_tsvector("),
+            "{sql}"
+        );
+        assert!(
+            sql.ends_with(", websearch_to_tsquery('rust web')) DESC"),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn like() {
+        let search = SearchExprs::new(
+            &[Identifier::new("title"), Identifier::new("body")],
+            "Rust  web",
+            false,
+        )
+        .unwrap();
+
+        let sql = to_sql(&search, SqliteQueryBuilder);
+        for pattern in ["'%rust%'", "'%web%'"] {
+            assert!(
+                sql.contains(&format!(r#"LOWER("title") LIKE {pattern}"#)),
+                "{sql}"
+            );
+            assert!(
+                sql.contains(&format!(r#"LOWER("body") LIKE {pattern}"#)),
+                "{sql}"
+            );
+        }
+        assert_eq!(sql.matches("CASE WHEN").count(), 4, "{sql}");
+    }
+}
@@ -0,0 +1,550 @@
+//! Seeding the database with initial data.
+//!
+//! Seeders populate the database with the data the project needs to be
+//! usable, such as a default admin user or reference data (countries,
+//! currencies, categories, etc.). They are provided by the apps through
+//! [`App::seeders`](crate::App::seeders) and are typically run with the
+//! `seed` command of the project's CLI, after the migrations have been
+//! applied.
+//!
+//! Seeders are run in the order the apps are registered in, and within an
+//! app, in the order they are returned in. Each of them is run in its own
+//! transaction, so a seeder that fails doesn't leave its data half-inserted.
+//! Since seeding is usually done every time the project is deployed, seeders
+//! should be idempotent, i.e. running them multiple times should have the
+//! same effect as running them once. This can be done by checking whether
+//! the data exists before inserting it, or by using
+//! [`Model::save`](crate::db::Model::save) with fixed primary keys.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tracing::info;
+
+use crate::db::Database;
+
+/// The environment the seeders are run in.
+///
+/// This allows having different sets of seed data for development (e.g. a
+/// default admin user with a well-known password, or some sample data) and
+/// for production (e.g. only the reference data). See
+/// [`Seeder::environments`].
+///
+/// When the seeders are run with the `seed` command of the project's CLI,
+/// the environment is [`Development`](Self::Development) if
+/// [`ProjectConfig::debug`](crate::config::ProjectConfig::debug) is `true`,
+/// and [`Production`](Self::Production) otherwise, unless overridden with
+/// the `--env` option.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::seed::SeedEnvironment;
+///
+/// assert_eq!("dev".parse(), Ok(SeedEnvironment::Development));
+/// assert_eq!("production".parse(), Ok(SeedEnvironment::Production));
+/// assert_eq!(SeedEnvironment::Production.to_string(), "production");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SeedEnvironment {
+    /// The development environment.
+    Development,
+    /// The production environment.
+    Production,
+}
+
+impl SeedEnvironment {
+    /// All the seed environments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::seed::SeedEnvironment;
+    ///
+    /// assert_eq!(SeedEnvironment::ALL.len(), 2);
+    /// ```
+    pub const ALL: &'static [SeedEnvironment] = &[Self::Development, Self::Production];
+
+    /// Returns the environment that corresponds to the given value of the
+    /// [`debug`](crate::config::ProjectConfig::debug) configuration option.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::seed::SeedEnvironment;
+    ///
+    /// assert_eq!(
+    ///     SeedEnvironment::from_debug(true),
+    ///     SeedEnvironment::Development
+    /// );
+    /// assert_eq!(
+    ///     SeedEnvironment::from_debug(false),
+    ///     SeedEnvironment::Production
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn from_debug(debug: bool) -> Self {
+        if debug {
+            Self::Development
+        } else {
+            Self::Production
+        }
+    }
+
+    /// Returns the name of the environment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::seed::SeedEnvironment;
+    ///
+    /// assert_eq!(SeedEnvironment::Development.as_str(), "development");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Development => "development",
+            Self::Production => "production",
+        }
+    }
+}
+
+impl Display for SeedEnvironment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SeedEnvironment {
+    type Err = UnknownSeedEnvironment;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dev" | "development" => Ok(Self::Development),
+            "prod" | "production" => Ok(Self::Production),
+            _ => Err(UnknownSeedEnvironment(s.to_owned())),
+        }
+    }
+}
+
+/// An error returned when parsing a [`SeedEnvironment`] from an unknown
+/// name.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("unknown seed environment `{0}`; expected `development` or `production`")]
+pub struct UnknownSeedEnvironment(String);
+
+/// A routine that populates the database with initial data.
+///
+/// Seeders are provided by the apps through
+/// [`App::seeders`](crate::App::seeders). See the [module
+/// documentation](self) for more information.
+///
+/// # Examples
+///
+/// ```
+/// use async_trait::async_trait;
+/// use cot::db::seed::{SeedEnvironment, Seeder};
+/// use cot::db::{Database, Model, model};
+///
+/// #[model]
+/// struct Currency {
+///     #[model(primary_key)]
+///     code: cot::db::LimitedString<3>,
+///     name: String,
+/// }
+///
+/// struct CurrencySeeder;
+///
+/// #[async_trait]
+/// impl Seeder for CurrencySeeder {
+///     fn name(&self) -> &str {
+///         "currencies"
+///     }
+///
+///     async fn seed(&self, database: &Database) -> cot::Result<()> {
+///         for (code, name) in [("EUR", "Euro"), ("USD", "US Dollar")] {
+///             // `save` updates the existing row, so seeding again is a no-op
+///             Currency {
+///                 code: cot::db::LimitedString::new(code).unwrap(),
+///                 name: name.to_owned(),
+///             }
+///             .save(database)
+///             .await?;
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Seeder: Send + Sync {
+    /// The name of the seeder.
+    ///
+    /// This is used to identify the seeder in the logs and to run a single
+    /// seeder with the `seed` command of the project's CLI.
+    fn name(&self) -> &str;
+
+    /// The environments the seeder should be run in. By default, the seeder
+    /// is run in all the environments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use cot::db::Database;
+    /// use cot::db::seed::{SeedEnvironment, Seeder};
+    ///
+    /// struct SampleDataSeeder;
+    ///
+    /// #[async_trait]
+    /// impl Seeder for SampleDataSeeder {
+    ///     fn name(&self) -> &str {
+    ///         "sample_data"
+    ///     }
+    ///
+    ///     fn environments(&self) -> &[SeedEnvironment] {
+    ///         &[SeedEnvironment::Development]
+    ///     }
+    ///
+    ///     async fn seed(&self, database: &Database) -> cot::Result<()> {
+    ///         // insert some sample data for local development
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    fn environments(&self) -> &[SeedEnvironment] {
+        SeedEnvironment::ALL
+    }
+
+    /// Populates the database with the data.
+    ///
+    /// The database passed is a transaction which is committed if this
+    /// method succeeds, and rolled back otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data could not be inserted.
+    async fn seed(&self, database: &Database) -> crate::Result<()>;
+}
+
+/// Runs the seeders in order.
+///
+/// This is used internally by the `seed` command of the project's CLI, but
+/// can also be used directly, for instance in tests.
+///
+/// # Examples
+///
+/// ```
+/// use async_trait::async_trait;
+/// use cot::db::Database;
+/// use cot::db::seed::{SeedEngine, SeedEnvironment, Seeder};
+///
+/// struct MySeeder;
+///
+/// #[async_trait]
+/// impl Seeder for MySeeder {
+///     fn name(&self) -> &str {
+///         "my_seeder"
+///     }
+///
+///     async fn seed(&self, database: &Database) -> cot::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let engine = SeedEngine::new([Box::new(MySeeder) as Box<dyn Seeder>]);
+/// let database = Database::new("sqlite::memory:").await?;
+/// let seeded = engine
+///     .run(&database, SeedEnvironment::Development, None)
+///     .await?;
+/// assert_eq!(seeded, ["my_seeder"]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SeedEngine {
+    seeders: Vec<Box<dyn Seeder>>,
+}
+
+impl std::fmt::Debug for SeedEngine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SeedEngine")
+            .field(
+                "seeders",
+                &self.seeders.iter().map(|s| s.name()).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl SeedEngine {
+    /// Creates a new [`SeedEngine`] that runs the given seeders in the order
+    /// they are given in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::seed::SeedEngine;
+    ///
+    /// let engine = SeedEngine::new([]);
+    /// ```
+    #[must_use]
+    pub fn new<V: IntoIterator<Item = Box<dyn Seeder>>>(seeders: V) -> Self {
+        Self {
+            seeders: seeders.into_iter().collect(),
+        }
+    }
+
+    /// Returns the seeders to run in the given environment, in the order
+    /// they are run in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::seed::{SeedEngine, SeedEnvironment};
+    ///
+    /// let engine = SeedEngine::new([]);
+    /// assert_eq!(engine.seeders(SeedEnvironment::Production).count(), 0);
+    /// ```
+    pub fn seeders(&self, environment: SeedEnvironment) -> impl Iterator<Item = &dyn Seeder> {
+        self.seeders
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(move |seeder| seeder.environments().contains(&environment))
+    }
+
+    /// Runs the seeders for the given environment and returns the names of
+    /// the seeders that were run.
+    ///
+    /// If `name` is given, only the seeders with that name are run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is given, but there is no seeder with
+    /// that name for the given environment.
+    ///
+    /// Returns an error if any of the seeders fails. The seeders that were run
+    /// before the failing one are not rolled back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::seed::{SeedEngine, SeedEnvironment};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = SeedEngine::new([]);
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// let seeded = engine
+    ///     .run(&database, SeedEnvironment::Production, None)
+    ///     .await?;
+    /// assert!(seeded.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run(
+        &self,
+        database: &Database,
+        environment: SeedEnvironment,
+        name: Option<&str>,
+    ) -> crate::Result<Vec<String>> {
+        let seeders: Vec<_> = self
+            .seeders(environment)
+            .filter(|seeder| name.is_none_or(|name| seeder.name() == name))
+            .collect();
+        if let Some(name) = name {
+            if seeders.is_empty() {
+                return Err(crate::Error::custom(format!(
+                    "Seeder `{name}` not found for the {environment} environment"
+                )));
+            }
+        }
+
+        let mut seeded = Vec::with_capacity(seeders.len());
+        for seeder in seeders {
+            info!("Running seeder `{}` ({environment})", seeder.name());
+            database
+                .transaction(|transaction| async move { seeder.seed(&transaction).await })
+                .await?;
+            seeded.push(seeder.name().to_owned());
+        }
+
+        Ok(seeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Auto, Model, model, query};
+
+    #[model]
+    struct Tag {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+    }
+
+    struct TagSeeder {
+        name: &'static str,
+        environments: &'static [SeedEnvironment],
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Seeder for TagSeeder {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn environments(&self) -> &[SeedEnvironment] {
+            self.environments
+        }
+
+        async fn seed(&self, database: &Database) -> crate::Result<()> {
+            let name = self.name.to_owned();
+            if query!(Tag, $name == name.clone()).exists(database).await? {
+                return Ok(());
+            }
+            Tag {
+                id: Auto::auto(),
+                name,
+            }
+            .insert(database)
+            .await?;
+
+            if self.fail {
+                return Err(crate::Error::custom("seeding failed"));
+            }
+            Ok(())
+        }
+    }
+
+    fn seeder(name: &'static str, environments: &'static [SeedEnvironment]) -> Box<dyn Seeder> {
+        Box::new(TagSeeder {
+            name,
+            environments,
+            fail: false,
+        })
+    }
+
+    async fn test_db() -> Database {
+        let database = Database::new("sqlite::memory:").await.unwrap();
+        database
+            .raw("CREATE TABLE cot__tag (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL)")
+            .await
+            .unwrap();
+        database
+    }
+
+    async fn tag_names(database: &Database) -> Vec<String> {
+        Tag::objects()
+            .all(database)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect()
+    }
+
+    #[test]
+    fn seed_environment_from_str() {
+        assert_eq!("dev".parse(), Ok(SeedEnvironment::Development));
+        assert_eq!("development".parse(), Ok(SeedEnvironment::Development));
+        assert_eq!("prod".parse(), Ok(SeedEnvironment::Production));
+        assert_eq!("production".parse(), Ok(SeedEnvironment::Production));
+        assert_eq!(
+            "staging".parse::<SeedEnvironment>(),
+            Err(UnknownSeedEnvironment("staging".to_owned()))
+        );
+    }
+
+    #[test]
+    fn seed_engine_seeders_filters_environment() {
+        let engine = SeedEngine::new([
+            seeder("both", SeedEnvironment::ALL),
+            seeder("dev", &[SeedEnvironment::Development]),
+            seeder("prod", &[SeedEnvironment::Production]),
+        ]);
+
+        let names = |environment| {
+            engine
+                .seeders(environment)
+                .map(|seeder| seeder.name().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(SeedEnvironment::Development), ["both", "dev"]);
+        assert_eq!(names(SeedEnvironment::Production), ["both", "prod"]);
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn seed_engine_run_is_idempotent() {
+        let database = test_db().await;
+        let engine = SeedEngine::new([
+            seeder("first", SeedEnvironment::ALL),
+            seeder("dev_only", &[SeedEnvironment::Development]),
+            seeder("second", SeedEnvironment::ALL),
+        ]);
+
+        let seeded = engine
+            .run(&database, SeedEnvironment::Production, None)
+            .await
+            .unwrap();
+        assert_eq!(seeded, ["first", "second"]);
+        let seeded = engine
+            .run(&database, SeedEnvironment::Development, None)
+            .await
+            .unwrap();
+        assert_eq!(seeded, ["first", "dev_only", "second"]);
+
+        assert_eq!(tag_names(&database).await, ["first", "second", "dev_only"]);
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn seed_engine_run_single() {
+        let database = test_db().await;
+        let engine = SeedEngine::new([
+            seeder("first", SeedEnvironment::ALL),
+            seeder("dev_only", &[SeedEnvironment::Development]),
+        ]);
+
+        let seeded = engine
+            .run(&database, SeedEnvironment::Development, Some("dev_only"))
+            .await
+            .unwrap();
+        assert_eq!(seeded, ["dev_only"]);
+        assert_eq!(tag_names(&database).await, ["dev_only"]);
+
+        let result = engine
+            .run(&database, SeedEnvironment::Production, Some("dev_only"))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn seed_engine_run_rolls_back_failed_seeder() {
+        let database = test_db().await;
+        let engine = SeedEngine::new([
+            seeder("first", SeedEnvironment::ALL),
+            Box::new(TagSeeder {
+                name: "failing",
+                environments: SeedEnvironment::ALL,
+                fail: true,
+            }),
+            seeder("never_run", SeedEnvironment::ALL),
+        ]);
+
+        let result = engine
+            .run(&database, SeedEnvironment::Production, None)
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(tag_names(&database).await, ["first"]);
+    }
+}
@@ -151,6 +151,11 @@ impl_error_from_repr!(tower_sessions::session::Error);
 impl_error_from_repr!(crate::form::FormError);
 impl_error_from_repr!(crate::auth::AuthError);
 impl_error_from_repr!(crate::request::PathParamsDeserializerError);
+impl_error_from_repr!(crate::request::MultipartError);
+#[cfg(feature = "websocket")]
+impl_error_from_repr!(crate::websocket::WebSocketError);
+impl_error_from_repr!(crate::session::cookie::CookiePrefixError);
+impl_error_from_repr!(crate::config::validation::ConfigReport);
 
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -170,18 +175,30 @@ pub(crate) enum ErrorRepr {
         #[from]
         source: toml::de::Error,
     },
+    /// The config is invalid.
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(#[from] crate::config::validation::ConfigReport),
     /// An error occurred while trying to start the server.
     #[error("Could not start server: {source}")]
     StartServer { source: std::io::Error },
     /// An error occurred while trying to collect static files into a directory.
     #[error("Could not collect static files: {source}")]
     CollectStatic { source: std::io::Error },
+    /// An error occurred while trying to read a file to serve.
+    #[error("Could not read file `{}`: {source}", path.display())]
+    ReadFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
     /// An error occurred while trying to read the request body.
     #[error("Could not retrieve request body: {source}")]
     ReadRequestBody {
         #[source]
         source: Box<dyn std::error::Error + Send + Sync>,
     },
+    /// The request body is larger than the limit set for the route.
+    #[error("The request body exceeds the limit of {limit} bytes")]
+    BodyTooLarge { limit: usize },
     /// The request body had an invalid `Content-Type` header.
     #[error("Invalid content type; expected `{expected}`, found `{actual}`")]
     InvalidContentType {
@@ -214,6 +231,69 @@ pub(crate) enum ErrorRepr {
     /// An error occurred while accessing the session object.
     #[error("Error while accessing the session object")]
     SessionAccess(#[from] tower_sessions::session::Error),
+    /// The shared application state doesn't contain a value of the requested
+    /// type.
+    #[error(
+        "No value of type `{type_name}` in the application state. Did you forget to register it?"
+    )]
+    StateMissing { type_name: &'static str },
+    /// The request extensions don't contain a value of the requested type.
+    #[error(
+        "No value of type `{type_name}` in the request extensions. Did you forget to add the \
+        middleware inserting it?"
+    )]
+    ExtensionMissing { type_name: &'static str },
+    /// The `Host` header of the request is missing or invalid.
+    #[error("The request has a missing or invalid `Host` header")]
+    InvalidHost,
+    /// The `Host` header of the request is not one of the allowed hosts.
+    #[error("The host `{host}` is not allowed; add it to the `allowed_hosts` server setting")]
+    HostNotAllowed {
+        /// The host from the request.
+        host: String,
+    },
+    /// The session cookie attributes are not compatible with its prefix.
+    #[error("Invalid session cookie configuration: {0}")]
+    CookiePrefix(#[from] crate::session::cookie::CookiePrefixError),
+    /// The Redis URL is invalid.
+    #[error("Invalid Redis URL: {0}")]
+    #[cfg(feature = "redis")]
+    InvalidRedisUrl(#[source] redis::RedisError),
+    /// The Redis session store is used, but its URL is not configured.
+    #[error("The Redis session store URL is not configured")]
+    #[cfg(feature = "redis")]
+    RedisUrlMissing,
+    /// A named database is configured without its URL.
+    #[error("The URL of the `{0}` database is not configured")]
+    #[cfg(feature = "db")]
+    DatabaseUrlMissing(String),
+    /// The Redis rate limit store is used, but its URL is not configured.
+    #[error("The Redis rate limit store URL is not configured")]
+    #[cfg(feature = "redis")]
+    RedisRateLimitUrlMissing,
+    /// The Redis rate limit store failed to process a request.
+    #[error("Could not access the Redis rate limit store: {0}")]
+    #[cfg(feature = "redis")]
+    RedisRateLimit(#[source] redis::RedisError),
+    /// The locale is not available for the request.
+    #[error("Locale extension missing. Did you forget to add the LocaleMiddleware?")]
+    LocaleMissing,
+    /// The timezone is not available for the request.
+    #[error("Timezone extension missing. Did you forget to add the TimezoneMiddleware?")]
+    TimezoneMissing,
+    /// The request ID is not available for the request.
+    #[error("Request ID extension missing. Did you forget to add the RequestIdMiddleware?")]
+    RequestIdMissing,
+    /// The CSRF token is not available for the request.
+    #[error("CSRF token extension missing. Did you forget to add the CsrfMiddleware?")]
+    CsrfTokenMissing,
+    /// The database query statistics are not available for the request.
+    #[error("Query statistics extension missing. Did you forget to add the QueryStatsMiddleware?")]
+    #[cfg(feature = "db")]
+    QueryStatsMissing,
+    /// The session object is not available for the request.
+    #[error("Session extension missing. Did you forget to add the SessionMiddleware?")]
+    SessionMissing,
     /// An error occurred while parsing a form.
     #[error("Failed to process a form: {0}")]
     Form(#[from] crate::form::FormError),
@@ -224,6 +304,14 @@ pub(crate) enum ErrorRepr {
     #[error("JSON error: {0}")]
     #[cfg(feature = "json")]
     Json(serde_path_to_error::Error<serde_json::Error>),
+    /// The request body is not valid JSON.
+    #[error("Could not parse JSON: {0}")]
+    #[cfg(feature = "json")]
+    JsonParse(serde_json::Error),
+    /// The request data could not be deserialized.
+    #[error("Validation error: {0}")]
+    #[cfg(feature = "json")]
+    Validation(crate::request::extractors::ValidationError),
     /// An error occurred inside a middleware-wrapped view.
     #[error(transparent)]
     MiddlewareWrapped {
@@ -235,6 +323,13 @@ pub(crate) enum ErrorRepr {
     /// An error occurred while trying to parse query parameters.
     #[error("Could not parse query parameters: {0}")]
     QueryParametersParse(serde_path_to_error::Error<serde::de::value::Error>),
+    /// An error occurred while trying to parse a `multipart/form-data` body.
+    #[error("Could not parse multipart data: {0}")]
+    Multipart(#[from] crate::request::MultipartError),
+    /// An error occurred while accepting or using a WebSocket connection.
+    #[cfg(feature = "websocket")]
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[from] crate::websocket::WebSocketError),
     /// An error occured in an [`AdminModel`](crate::admin::AdminModel).
     #[error("Admin error: {0}")]
     AdminError(#[source] Box<dyn std::error::Error + Send + Sync>),
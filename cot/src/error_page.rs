@@ -18,6 +18,7 @@ use crate::{Result, StatusCode};
 #[derive(Debug, Clone)]
 pub(crate) enum ErrorPageTrigger {
     NotFound { message: Option<String> },
+    MethodNotAllowed { allow: http::HeaderValue },
 }
 
 #[derive(Debug)]
@@ -136,6 +137,7 @@ impl ErrorPageTemplateBuilder {
                     }
                     .to_owned(),
                     crate::router::RouteKind::Handler => "View".to_owned(),
+                    crate::router::RouteKind::Service => "Service".to_owned(),
                 },
                 name: route.name().unwrap_or_default().to_owned(),
             });
@@ -366,6 +368,20 @@ pub(super) fn build_cot_not_found_page() -> axum::response::Response {
         .expect("Building the Cot not found page should never fail")
 }
 
+/// An empty Method Not Allowed response with the given `Allow` header.
+///
+/// Returned when no custom error page is defined for `405 Method Not Allowed`
+/// or when it fails to render.
+pub(super) fn build_cot_method_not_allowed_page(
+    allow: http::HeaderValue,
+) -> axum::response::Response {
+    axum::response::Response::builder()
+        .status(StatusCode::METHOD_NOT_ALLOWED)
+        .header(http::header::ALLOW, allow)
+        .body(axum::body::Body::empty())
+        .expect("Building the Cot method not allowed page should never fail")
+}
+
 /// A last-resort error page.
 ///
 /// This page is displayed when an error occurs that prevents Cot from rendering
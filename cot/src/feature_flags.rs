@@ -0,0 +1,411 @@
+//! Feature flags evaluated per request.
+//!
+//! Feature flags allow to gate the behavior of the request handlers (e.g. to
+//! enable a new UI for some users only) without deploying a new version of
+//! the project. The flags are evaluated with
+//! [`RequestExt::flag`](crate::request::RequestExt::flag), which passes the
+//! [`FlagContext`] of the request (such as the ID of the authenticated user
+//! and the locale) to a [`FeatureFlagBackend`].
+//!
+//! By default, the flags are read from the `[features]` section of the
+//! project config:
+//!
+//! ```toml
+//! [features]
+//! new_ui = true
+//! checkout_flow = "variant_b"
+//! ```
+//!
+//! A custom backend can be used by registering a [`FeatureFlags`] instance in
+//! the application state with
+//! [`Project::register_state`](crate::Project::register_state).
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::Project;
+//! use cot::feature_flags::{FeatureFlagBackend, FeatureFlags, FlagContext, FlagValue};
+//! use cot::state::AppState;
+//!
+//! /// Enables the `new_ui` flag for the Polish users only.
+//! #[derive(Debug)]
+//! struct LocaleFlags;
+//!
+//! impl FeatureFlagBackend for LocaleFlags {
+//!     fn evaluate(&self, name: &str, context: &FlagContext<'_>) -> Option<FlagValue> {
+//!         match name {
+//!             "new_ui" => Some(FlagValue::Bool(context.locale() == Some("pl"))),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! struct MyProject;
+//! impl Project for MyProject {
+//!     fn register_state(&self, state: &mut AppState) {
+//!         state.insert(FeatureFlags::new(LocaleFlags));
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::auth::UserId;
+
+/// The value of a feature flag.
+///
+/// # Examples
+///
+/// ```
+/// use cot::feature_flags::FlagValue;
+///
+/// assert!(FlagValue::Bool(true).is_enabled());
+/// assert!(!FlagValue::Bool(false).is_enabled());
+/// assert_eq!(FlagValue::Variant("b".to_owned()).variant(), Some("b"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FlagValue {
+    /// The feature is either enabled or disabled.
+    Bool(bool),
+    /// The feature is enabled with the given variant (e.g. in A/B tests).
+    Variant(String),
+}
+
+impl FlagValue {
+    /// Returns whether the feature is enabled.
+    ///
+    /// A [`FlagValue::Variant`] is always considered enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::feature_flags::FlagValue;
+    ///
+    /// assert!(FlagValue::Bool(true).is_enabled());
+    /// assert!(FlagValue::Variant("a".to_owned()).is_enabled());
+    /// ```
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Self::Bool(enabled) => *enabled,
+            Self::Variant(_) => true,
+        }
+    }
+
+    /// Returns the variant of the feature, or `None` if the flag is a
+    /// [`FlagValue::Bool`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::feature_flags::FlagValue;
+    ///
+    /// assert_eq!(FlagValue::Variant("a".to_owned()).variant(), Some("a"));
+    /// assert_eq!(FlagValue::Bool(true).variant(), None);
+    /// ```
+    #[must_use]
+    pub fn variant(&self) -> Option<&str> {
+        match self {
+            Self::Bool(_) => None,
+            Self::Variant(variant) => Some(variant),
+        }
+    }
+}
+
+impl Default for FlagValue {
+    fn default() -> Self {
+        Self::Bool(false)
+    }
+}
+
+impl From<bool> for FlagValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// The information about the request a feature flag is evaluated for.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::UserId;
+/// use cot::feature_flags::FlagContext;
+///
+/// let context = FlagContext::new(Some(UserId::Int(1)), Some("en"));
+/// assert_eq!(context.user_id(), Some(&UserId::Int(1)));
+/// assert_eq!(context.locale(), Some("en"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagContext<'a> {
+    user_id: Option<UserId>,
+    locale: Option<&'a str>,
+}
+
+impl<'a> FlagContext<'a> {
+    /// Creates a new [`FlagContext`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::feature_flags::FlagContext;
+    ///
+    /// let context = FlagContext::new(None, None);
+    /// ```
+    #[must_use]
+    pub fn new(user_id: Option<UserId>, locale: Option<&'a str>) -> Self {
+        Self { user_id, locale }
+    }
+
+    /// Returns the ID of the authenticated user, or `None` if the user is
+    /// anonymous or [`AuthMiddleware`](crate::middleware::AuthMiddleware) is
+    /// not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::feature_flags::FlagContext;
+    ///
+    /// let context = FlagContext::new(None, None);
+    /// assert!(context.user_id().is_none());
+    /// ```
+    #[must_use]
+    pub fn user_id(&self) -> Option<&UserId> {
+        self.user_id.as_ref()
+    }
+
+    /// Returns the preferred locale of the client (the first language in the
+    /// `Accept-Language` header), or `None` if it's not known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::feature_flags::FlagContext;
+    ///
+    /// let context = FlagContext::new(None, Some("pl"));
+    /// assert_eq!(context.locale(), Some("pl"));
+    /// ```
+    #[must_use]
+    pub fn locale(&self) -> Option<&'a str> {
+        self.locale
+    }
+}
+
+/// A source of the feature flag values.
+///
+/// Implement this trait to read the flags from a custom source (e.g. a
+/// database or an external feature flag service) and to target the flags at
+/// specific users or locales, or to roll them out gradually.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::UserId;
+/// use cot::feature_flags::{FeatureFlagBackend, FlagContext, FlagValue};
+///
+/// /// Enables all the flags for the users with even IDs.
+/// #[derive(Debug)]
+/// struct HalfOfUsers;
+///
+/// impl FeatureFlagBackend for HalfOfUsers {
+///     fn evaluate(&self, _name: &str, context: &FlagContext<'_>) -> Option<FlagValue> {
+///         match context.user_id() {
+///             Some(UserId::Int(id)) => Some(FlagValue::Bool(id % 2 == 0)),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+pub trait FeatureFlagBackend: Debug + Send + Sync + 'static {
+    /// Evaluates the flag with the given name for the request described by
+    /// the `context`.
+    ///
+    /// Returns `None` if the flag is not known to the backend.
+    fn evaluate(&self, name: &str, context: &FlagContext<'_>) -> Option<FlagValue>;
+}
+
+/// A [`FeatureFlagBackend`] with a fixed set of flags that are the same for
+/// all the requests.
+///
+/// The flags read from the `[features]` section of the project config (used
+/// when no [`FeatureFlags`] provider is registered) behave the same way.
+///
+/// # Examples
+///
+/// ```
+/// use cot::feature_flags::{FeatureFlagBackend, FlagContext, FlagValue, StaticFeatureFlags};
+///
+/// let backend = StaticFeatureFlags::new([("new_ui", FlagValue::Bool(true))]);
+/// assert_eq!(
+///     backend.evaluate("new_ui", &FlagContext::new(None, None)),
+///     Some(FlagValue::Bool(true))
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaticFeatureFlags {
+    flags: HashMap<String, FlagValue>,
+}
+
+impl StaticFeatureFlags {
+    /// Creates a new [`StaticFeatureFlags`] with the given flags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::feature_flags::{FlagValue, StaticFeatureFlags};
+    ///
+    /// let backend = StaticFeatureFlags::new([
+    ///     ("new_ui", FlagValue::Bool(true)),
+    ///     ("checkout_flow", FlagValue::Variant("b".to_owned())),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn new<I, K>(flags: I) -> Self
+    where
+        I: IntoIterator<Item = (K, FlagValue)>,
+        K: Into<String>,
+    {
+        Self {
+            flags: flags
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        }
+    }
+}
+
+impl FeatureFlagBackend for StaticFeatureFlags {
+    fn evaluate(&self, name: &str, _context: &FlagContext<'_>) -> Option<FlagValue> {
+        self.flags.get(name).cloned()
+    }
+}
+
+/// The feature flag provider of a project.
+///
+/// Register it in the application state with
+/// [`Project::register_state`](crate::Project::register_state) to use a
+/// custom [`FeatureFlagBackend`]. If it's not registered, the flags are read
+/// from the project config.
+///
+/// # Examples
+///
+/// ```
+/// use cot::feature_flags::{FeatureFlags, FlagContext, FlagValue, StaticFeatureFlags};
+///
+/// let flags = FeatureFlags::new(StaticFeatureFlags::new([("new_ui", FlagValue::Bool(true))]));
+/// let context = FlagContext::new(None, None);
+/// assert!(flags.evaluate("new_ui", &context).is_enabled());
+/// assert!(!flags.evaluate("unknown", &context).is_enabled());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FeatureFlags {
+    backend: Arc<dyn FeatureFlagBackend>,
+}
+
+impl FeatureFlags {
+    /// Creates a new [`FeatureFlags`] provider using the given backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::feature_flags::{FeatureFlags, StaticFeatureFlags};
+    ///
+    /// let flags = FeatureFlags::new(StaticFeatureFlags::default());
+    /// ```
+    #[must_use]
+    pub fn new<B: FeatureFlagBackend>(backend: B) -> Self {
+        Self {
+            backend: Arc::new(backend),
+        }
+    }
+
+    /// Evaluates the flag with the given name.
+    ///
+    /// Flags unknown to the backend are disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::feature_flags::{FeatureFlags, FlagContext, FlagValue, StaticFeatureFlags};
+    ///
+    /// let flags = FeatureFlags::new(StaticFeatureFlags::default());
+    /// assert_eq!(
+    ///     flags.evaluate("new_ui", &FlagContext::new(None, None)),
+    ///     FlagValue::Bool(false)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn evaluate(&self, name: &str, context: &FlagContext<'_>) -> FlagValue {
+        self.backend.evaluate(name, context).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct LocaleFlags;
+
+    impl FeatureFlagBackend for LocaleFlags {
+        fn evaluate(&self, name: &str, context: &FlagContext<'_>) -> Option<FlagValue> {
+            (name == "new_ui").then(|| FlagValue::Bool(context.locale() == Some("pl")))
+        }
+    }
+
+    #[test]
+    fn feature_flags_custom_backend() {
+        let flags = FeatureFlags::new(LocaleFlags);
+
+        assert!(
+            flags
+                .evaluate("new_ui", &FlagContext::new(None, Some("pl")))
+                .is_enabled()
+        );
+        assert!(
+            !flags
+                .evaluate("new_ui", &FlagContext::new(None, Some("en")))
+                .is_enabled()
+        );
+        assert_eq!(
+            flags.evaluate("unknown", &FlagContext::new(None, Some("pl"))),
+            FlagValue::Bool(false)
+        );
+    }
+
+    #[test]
+    fn static_feature_flags() {
+        let backend = StaticFeatureFlags::new([
+            ("new_ui", FlagValue::Bool(true)),
+            ("checkout_flow", FlagValue::Variant("b".to_owned())),
+        ]);
+        let context = FlagContext::new(Some(UserId::Int(1)), None);
+
+        assert_eq!(
+            backend.evaluate("new_ui", &context),
+            Some(FlagValue::Bool(true))
+        );
+        assert_eq!(
+            backend
+                .evaluate("checkout_flow", &context)
+                .unwrap()
+                .variant(),
+            Some("b")
+        );
+        assert_eq!(backend.evaluate("unknown", &context), None);
+    }
+
+    #[test]
+    fn flag_value_deserialize() {
+        let flags: HashMap<String, FlagValue> =
+            toml::from_str("new_ui = true\ncheckout_flow = \"b\"").unwrap();
+
+        assert_eq!(flags["new_ui"], FlagValue::Bool(true));
+        assert_eq!(flags["checkout_flow"], FlagValue::Variant("b".to_owned()));
+    }
+}
@@ -19,6 +19,31 @@
 //!     name: String,
 //! }
 //! ```
+//!
+//! # `ModelForm` derive macro
+//!
+//! If the form mirrors a database model, the [`ModelForm`](derive@ModelForm)
+//! derive macro can generate the form from the model instead. The generated
+//! form can then be used to create and update the model instances; see the
+//! [`ModelForm`] trait for details.
+//!
+//! ```
+//! use cot::db::{Auto, model};
+//! use cot::form::ModelForm;
+//!
+//! #[derive(Debug, Clone, ModelForm)]
+//! #[model]
+//! struct Article {
+//!     #[model(primary_key)]
+//!     id: Auto<i32>,
+//!     title: String,
+//! }
+//!
+//! // `ArticleForm` is generated by the derive macro
+//! let form = ArticleForm {
+//!     title: "Hello".to_owned(),
+//! };
+//! ```
 
 /// Built-in form fields that can be used in a form.
 pub mod fields;
@@ -46,6 +71,13 @@ use bytes::Bytes;
 /// Note that even if the form is not rendered in a template, you will still be
 /// able to render the fields individually.
 ///
+/// When [`CsrfMiddleware`](crate::middleware::CsrfMiddleware) is used, the
+/// rendered form context starts with a hidden field holding the CSRF token,
+/// so the forms submitted with unsafe methods (such as `POST`) pass the CSRF
+/// protection. When rendering the fields individually, the token has to be
+/// added manually (see
+/// [`CsrfTokenField`](crate::middleware::CsrfTokenField)).
+///
 /// # Safety
 ///
 /// The implementation of [`Display`] for the form context that this derive
@@ -53,11 +85,40 @@ use bytes::Bytes;
 /// fields. If the form fields are not safe to render as HTML, the form context
 /// will not be safe to render as HTML either.
 pub use cot_macros::Form;
+/// Generate a form for a model and implement the [`ModelForm`] trait for it.
+///
+/// This macro, used on a struct annotated with
+/// [`#[model]`](crate::db::model), generates a form struct named after the
+/// model with the `Form` suffix (e.g. `ArticleForm` for the `Article` model),
+/// which derives the [`Form`](derive@Form) trait. The form contains all the
+/// fields of the model, except for the ones which are not entered by the user:
+///
+/// * the primary key, if it's an [`Auto`](crate::db::Auto) value,
+/// * the fields marked with `#[model(auto_now)]` or `#[model(auto_now_add)]`,
+/// * the field marked with `#[model(version)]`,
+/// * the many-to-many relationships,
+/// * the `deleted_at` field of the `#[model(soft_delete)]` models.
+///
+/// The form fields are rendered and validated according to their types, just
+/// as with the [`Form`](derive@Form) derive macro; this means all the form
+/// fields **must** implement the [`AsFormField`] trait, as well as [`Clone`].
+/// The `#[form(...)]` attributes on the model fields are copied over to the
+/// generated form.
+///
+/// Note that the derive has to be placed before the `#[model]` attribute.
+#[cfg(feature = "db")]
+pub use cot_macros::ModelForm;
 use thiserror::Error;
 
+#[cfg(feature = "db")]
+use crate::db;
+#[cfg(feature = "db")]
+use crate::db::{DatabaseBackend, Model};
+use crate::error::ErrorRepr;
 use crate::headers::FORM_CONTENT_TYPE;
+use crate::middleware::request_body_limit;
 use crate::request;
-use crate::request::{Request, RequestExt};
+use crate::request::{Request, RequestBodyExt, RequestExt, UploadedFile};
 
 /// Error occurred while processing a form.
 #[derive(Debug, Error)]
@@ -121,6 +182,15 @@ pub enum FormFieldValidationError {
     /// The field value is required to be true.
     #[error("This field must be checked.")]
     BooleanRequiredToBeTrue,
+    /// The uploaded file is too large.
+    #[error("The file exceeds the maximum size of {max_size} bytes.")]
+    FileTooLarge {
+        /// The maximum size of the file, in bytes.
+        max_size: u64,
+    },
+    /// The content type of the uploaded file is not accepted by the field.
+    #[error("This file type is not allowed.")]
+    FileTypeNotAllowed,
     /// The field value is invalid.
     #[error("Value is not valid for this field.")]
     InvalidValue(String),
@@ -218,11 +288,41 @@ pub trait Form: Sized {
     /// into the final types, so this context object may not include all the
     /// errors. The conversion is done in the [`Self::from_request`] method.
     ///
+    /// Both `application/x-www-form-urlencoded` and `multipart/form-data`
+    /// request bodies are supported; the files uploaded in the latter are set
+    /// with [`FormContext::set_file`].
+    ///
     /// # Errors
     ///
     /// This method should return an error if the form data could not be read
     /// from the request.
     async fn build_context(request: &mut Request) -> Result<Self::Context, FormError> {
+        let is_multipart = request
+            .content_type()
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(request::is_multipart_form_data);
+        if is_multipart {
+            let values =
+                multipart_values(request)
+                    .await
+                    .map_err(|error| FormError::RequestError {
+                        error: Box::new(error),
+                    })?;
+
+            let mut context = Self::Context::new();
+            for (field_id, value) in values {
+                let result = match value {
+                    MultipartValue::Text(value) => context.set_value(&field_id, value.into()),
+                    MultipartValue::File(file) => context.set_file(&field_id, file),
+                };
+                if let Err(err) = result {
+                    context.add_error(FormErrorTarget::Field(&field_id), err);
+                }
+            }
+
+            return Ok(context);
+        }
+
         let form_data = form_data(request)
             .await
             .map_err(|error| FormError::RequestError {
@@ -243,6 +343,112 @@ pub trait Form: Sized {
     }
 }
 
+/// A form generated from a database model.
+///
+/// This trait makes it possible to create model instances from the form
+/// submitted by the user, and to update existing instances with it. The
+/// validation is done by the [`Form`] trait, so the form has to be
+/// successfully read from the request with [`Form::from_request`] first.
+///
+/// # Deriving
+///
+/// This trait can, and should be derived using the
+/// [`ModelForm`](derive@ModelForm) derive macro, which generates the form
+/// struct for the model as well.
+///
+/// ```
+/// use cot::db::{Auto, Database, model};
+/// use cot::form::{Form, FormResult, ModelForm};
+/// use cot::request::Request;
+///
+/// #[derive(Debug, Clone, ModelForm)]
+/// #[model]
+/// struct Article {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// async fn create_article(request: &mut Request, db: &Database) -> cot::Result<()> {
+///     match ArticleForm::from_request(request).await? {
+///         FormResult::Ok(form) => {
+///             let article = form.save(db).await?;
+///             println!("created article {:?}", article.id);
+///         }
+///         FormResult::ValidationError(_context) => {
+///             // render the form again, along with the errors in the context
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "db")]
+#[async_trait]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement the `ModelForm` trait",
+    label = "`{Self}` is not a model form",
+    note = "add #[derive(cot::form::ModelForm)] to the model to generate the form"
+)]
+pub trait ModelForm: Form + Sync {
+    /// The model the form is generated from.
+    type Model: Model;
+
+    /// Creates a form prefilled with the values of a model instance.
+    ///
+    /// This is useful for rendering a form for editing an existing instance,
+    /// together with [`Form::to_context`].
+    fn from_model(model: &Self::Model) -> Self;
+
+    /// Creates a new model instance from the values of the form.
+    ///
+    /// The fields not included in the form are set to their defaults (e.g.
+    /// [`Auto::Auto`](crate::db::Auto::Auto) for the primary key); the
+    /// timestamps are set again when the instance is saved.
+    fn to_model(&self) -> Self::Model;
+
+    /// Overwrites the fields of a model instance with the values of the form.
+    ///
+    /// The fields not included in the form are left unchanged.
+    fn apply_to(&self, model: &mut Self::Model);
+
+    /// Creates a new model instance from the values of the form and inserts
+    /// it into the database.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the model instance could not be
+    /// inserted into the database, for instance because it violates a unique
+    /// constraint, or there was a problem with the database connection.
+    async fn save<DB: DatabaseBackend>(&self, db: &DB) -> db::Result<Self::Model> {
+        let mut model = self.to_model();
+        model.insert(db).await?;
+        Ok(model)
+    }
+
+    /// Updates an existing model instance with the values of the form and
+    /// saves it in the database.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the model instance could not be
+    /// updated in the database, for instance because it doesn't exist
+    /// anymore, or there was a problem with the database connection.
+    ///
+    /// This method returns [`DatabaseError::StaleObject`] if the model has a
+    /// version field and the row has been updated since the instance was read
+    /// from the database.
+    ///
+    /// [`DatabaseError::StaleObject`]: crate::db::DatabaseError::StaleObject
+    async fn update<DB: DatabaseBackend>(
+        &self,
+        model: &mut Self::Model,
+        db: &DB,
+    ) -> db::Result<()> {
+        self.apply_to(model);
+        model.update(db).await
+    }
+}
+
 /// Get the request body as bytes. If the request method is GET or HEAD, the
 /// query string is returned. Otherwise, if the request content type is
 /// `application/x-www-form-urlencoded`, then the body is read and returned.
@@ -252,7 +458,9 @@ pub trait Form: Sized {
 ///
 /// Throws an error if the request method is not GET or HEAD and the content
 /// type is not `application/x-www-form-urlencoded`.
-/// Throws an error if the request body could not be read.
+/// Throws an error if the request body could not be read, or if it's larger
+/// than the limit described in
+/// [`RequestBodyExt::buffered`](crate::request::RequestBodyExt::buffered).
 pub async fn form_data(request: &mut Request) -> crate::Result<Bytes> {
     if request.method() == http::Method::GET || request.method() == http::Method::HEAD {
         if let Some(query) = request.uri().query() {
@@ -263,11 +471,48 @@ pub async fn form_data(request: &mut Request) -> crate::Result<Bytes> {
     } else {
         request.expect_content_type(FORM_CONTENT_TYPE)?;
 
-        let body = std::mem::take(request.body_mut());
-        let bytes = body.into_bytes().await?;
+        request.buffered().await
+    }
+}
 
-        Ok(bytes)
+/// A value of a `multipart/form-data` form field.
+enum MultipartValue {
+    Text(String),
+    File(UploadedFile),
+}
+
+/// Reads the fields of a `multipart/form-data` request.
+///
+/// The file fields are read as [`UploadedFile`]s; the other fields are read
+/// into memory. The size of the whole request body, including the files, is
+/// limited the same way as the request body in [`RequestBodyExt::buffered`].
+async fn multipart_values(request: &mut Request) -> crate::Result<Vec<(String, MultipartValue)>> {
+    let limit = request_body_limit(request);
+    let mut multipart = request.multipart()?;
+    let mut remaining = limit;
+    let mut values = Vec::new();
+
+    while let Some(mut field) = multipart.next_field().await? {
+        let Some(field_id) = field.name().map(ToOwned::to_owned) else {
+            continue;
+        };
+
+        if field.file_name().is_some() {
+            values.push((field_id, MultipartValue::File(field.into_file().await?)));
+        } else {
+            let mut value = Vec::new();
+            while let Some(chunk) = field.chunk().await? {
+                remaining = remaining
+                    .checked_sub(chunk.len())
+                    .ok_or(ErrorRepr::BodyTooLarge { limit })?;
+                value.extend_from_slice(&chunk);
+            }
+            let value = String::from_utf8_lossy(&value).into_owned();
+            values.push((field_id, MultipartValue::Text(value)));
+        }
     }
+
+    Ok(values)
 }
 
 /// A trait for form contexts.
@@ -300,6 +545,24 @@ pub trait FormContext: Debug {
         value: Cow<'_, str>,
     ) -> Result<(), FormFieldValidationError>;
 
+    /// Sets the file uploaded for a form field.
+    ///
+    /// This is called instead of [`Self::set_value`] for the file fields of
+    /// `multipart/form-data` forms. The default implementation ignores the
+    /// file.
+    ///
+    /// # Errors
+    ///
+    /// This method should return an error if the field doesn't accept files.
+    fn set_file(
+        &mut self,
+        field_id: &str,
+        file: UploadedFile,
+    ) -> Result<(), FormFieldValidationError> {
+        let _ = (field_id, file);
+        Ok(())
+    }
+
     /// Adds a validation error to the form context.
     fn add_error(&mut self, target: FormErrorTarget<'_>, error: FormFieldValidationError) {
         self.errors_for_mut(target).push(error);
@@ -367,6 +630,22 @@ pub trait FormField: Display {
     /// This method should convert the value to the appropriate type for the
     /// field, such as a number for a number field.
     fn set_value(&mut self, value: Cow<'_, str>);
+
+    /// Sets the file uploaded for the form field.
+    ///
+    /// The default implementation returns an error, as most fields don't
+    /// accept files; see [`FileField`](fields::FileField) for a field that
+    /// does.
+    ///
+    /// # Errors
+    ///
+    /// This method should return an error if the field doesn't accept files.
+    fn set_file(&mut self, file: UploadedFile) -> Result<(), FormFieldValidationError> {
+        let _ = file;
+        Err(FormFieldValidationError::from_static(
+            "This field does not accept files.",
+        ))
+    }
 }
 
 /// A version of [`FormField`] that can be used in a dynamic context.
@@ -453,8 +732,18 @@ mod tests {
     use bytes::Bytes;
 
     use crate::Body;
-    use crate::form::form_data;
+    use crate::form::{Form, FormContext, FormErrorTarget, FormFieldValidationError, form_data};
     use crate::headers::FORM_CONTENT_TYPE;
+    use crate::request::UploadedFile;
+    use crate::test::TestRequestBuilder;
+
+    #[derive(Debug, Form)]
+    struct UploadForm {
+        title: String,
+        #[form(opt(max_size = 16, accept = &["text/*"]))]
+        file: UploadedFile,
+        thumbnail: Option<UploadedFile>,
+    }
 
     #[cot::test]
     async fn form_data_extract_get_empty() {
@@ -491,4 +780,146 @@ mod tests {
         let bytes = form_data(&mut request).await.unwrap();
         assert_eq!(bytes, Bytes::from_static(b"hello=world"));
     }
+
+    #[cot::test]
+    async fn form_multipart() {
+        let mut request = TestRequestBuilder::post("/")
+            .multipart_field("title", "Notes")
+            .multipart_file("file", "notes.txt", "text/plain", "Hello!")
+            .build();
+
+        let form = UploadForm::from_request(&mut request)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(form.title, "Notes");
+        assert_eq!(form.file.file_name(), Some("notes.txt"));
+        assert_eq!(form.file.content_type(), Some("text/plain"));
+        assert_eq!(form.file.bytes().await.unwrap(), "Hello!");
+        assert!(form.thumbnail.is_none());
+    }
+
+    async fn upload_form_errors(
+        request: &mut crate::request::Request,
+    ) -> <UploadForm as Form>::Context {
+        let super::FormResult::ValidationError(context) =
+            UploadForm::from_request(request).await.unwrap()
+        else {
+            panic!("expected a validation error");
+        };
+        context
+    }
+
+    #[cot::test]
+    async fn form_multipart_file_missing() {
+        let mut request = TestRequestBuilder::post("/")
+            .multipart_field("title", "Notes")
+            // browsers send an empty file when no file is selected
+            .multipart_file("file", "", "application/octet-stream", "")
+            .build();
+
+        assert_eq!(
+            upload_form_errors(&mut request)
+                .await
+                .errors_for(FormErrorTarget::Field("file")),
+            [FormFieldValidationError::Required]
+        );
+    }
+
+    #[cot::test]
+    async fn form_multipart_file_too_large() {
+        let mut request = TestRequestBuilder::post("/")
+            .multipart_field("title", "Notes")
+            .multipart_file(
+                "file",
+                "notes.txt",
+                "text/plain",
+                "Hello, this is too long!",
+            )
+            .build();
+
+        assert_eq!(
+            upload_form_errors(&mut request)
+                .await
+                .errors_for(FormErrorTarget::Field("file")),
+            [FormFieldValidationError::FileTooLarge { max_size: 16 }]
+        );
+    }
+
+    #[cot::test]
+    async fn form_multipart_file_type_not_allowed() {
+        let mut request = TestRequestBuilder::post("/")
+            .multipart_field("title", "Notes")
+            .multipart_file("file", "notes.png", "image/png", "Hello!")
+            .build();
+
+        assert_eq!(
+            upload_form_errors(&mut request)
+                .await
+                .errors_for(FormErrorTarget::Field("file")),
+            [FormFieldValidationError::FileTypeNotAllowed]
+        );
+    }
+
+    #[cot::test]
+    async fn form_multipart_file_in_text_field() {
+        let mut request = TestRequestBuilder::post("/")
+            .multipart_file("title", "title.txt", "text/plain", "Notes")
+            .multipart_file("file", "notes.txt", "text/plain", "Hello!")
+            .build();
+
+        assert_eq!(
+            upload_form_errors(&mut request)
+                .await
+                .errors_for(FormErrorTarget::Field("title")),
+            [
+                FormFieldValidationError::from_static("This field does not accept files."),
+                FormFieldValidationError::Required,
+            ]
+        );
+    }
+
+    #[cot::test]
+    async fn form_multipart_text_too_large() {
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(4)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::post("/")
+            .config(config)
+            .multipart_field("title", "Notes")
+            .build();
+
+        let error = UploadForm::from_request(&mut request).await.unwrap_err();
+
+        let super::FormError::RequestError { error } = error;
+        let response = crate::middleware::reject_too_large(Err(*error)).unwrap();
+        assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cot::test]
+    async fn form_multipart_file_exceeding_body_limit() {
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(256)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::post("/")
+            .config(config)
+            .multipart_field("title", "Notes")
+            .multipart_file("file", "notes.txt", "text/plain", "a".repeat(512))
+            .build();
+
+        let error = UploadForm::from_request(&mut request).await.unwrap_err();
+
+        let super::FormError::RequestError { error } = error;
+        let response = crate::middleware::reject_too_large(Err(*error)).unwrap();
+        assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
@@ -11,9 +11,10 @@ use cot::db::Auto;
 
 use crate::auth::{Password, PasswordHash};
 #[cfg(feature = "db")]
-use crate::db::LimitedString;
+use crate::db::{DbEnum, LimitedString};
 use crate::form::{AsFormField, FormField, FormFieldOptions, FormFieldValidationError};
 use crate::html::HtmlTag;
+use crate::request::UploadedFile;
 
 macro_rules! impl_form_field {
     ($field_type_name:ident, $field_options_type_name:ident, $purpose:literal $(, $generic_param:ident $(: $generic_param_bound:ident $(+ $generic_param_bound_more:ident)*)?)?) => {
@@ -124,6 +125,24 @@ impl<const LEN: u32> AsFormField for LimitedString<LEN> {
     }
 }
 
+#[cfg(feature = "db")]
+impl AsFormField for uuid::Uuid {
+    type Type = StringField;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        let value = check_required(field)?;
+
+        value
+            .trim()
+            .parse()
+            .map_err(|_| FormFieldValidationError::invalid_value(value))
+    }
+
+    fn to_field_value(&self) -> String {
+        self.to_string()
+    }
+}
+
 impl_form_field!(PasswordField, PasswordFieldOptions, "a password");
 
 /// Custom options for a [`PasswordField`].
@@ -436,6 +455,173 @@ impl AsFormField for bool {
     }
 }
 
+impl_form_field!(SelectField, SelectFieldOptions, "a choice from a list of options", T: SelectChoice);
+
+/// Custom options for a [`SelectField`].
+#[derive(Debug, Clone)]
+pub struct SelectFieldOptions<T> {
+    /// The options that can be chosen. If `None`, the options returned by
+    /// [`SelectChoice::default_choices`] are used.
+    pub choices: Option<Vec<T>>,
+}
+
+impl<T> Default for SelectFieldOptions<T> {
+    fn default() -> Self {
+        Self { choices: None }
+    }
+}
+
+impl<T: SelectChoice> Display for SelectField<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut tag = HtmlTag::new("select");
+        tag.attr("name", self.id());
+        tag.attr("id", self.id());
+        if self.options.required {
+            tag.bool_attr("required");
+        }
+
+        // the empty option allows leaving optional fields empty, and makes the
+        // browser ask the user to choose an option in required fields
+        if !self.options.required || self.value.is_none() {
+            let mut empty = HtmlTag::new("option");
+            empty.attr("value", "").push_str("---------");
+            tag.push_tag(&empty);
+        }
+
+        let default_choices;
+        let choices = if let Some(choices) = &self.custom_options.choices {
+            choices
+        } else {
+            default_choices = T::default_choices();
+            &default_choices
+        };
+        for choice in choices {
+            let id = choice.id();
+            let mut option = HtmlTag::new("option");
+            option.attr("value", &id);
+            if self.value.as_ref() == Some(&id) {
+                option.bool_attr("selected");
+            }
+            option.push_str(&choice.label());
+            tag.push_tag(&option);
+        }
+
+        write!(f, "{}", tag.render())
+    }
+}
+
+impl<T: SelectChoice> HtmlSafe for SelectField<T> {}
+
+/// A trait for types that can be chosen from a list of options in a
+/// [`SelectField`].
+///
+/// The trait is implemented for all the enums implementing
+/// [`DbEnum`](crate::db::DbEnum), so they can be used directly as form fields.
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::FormFieldValidationError;
+/// use cot::form::fields::SelectChoice;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Color {
+///     Red,
+///     Green,
+/// }
+///
+/// impl SelectChoice for Color {
+///     fn default_choices() -> Vec<Self> {
+///         vec![Self::Red, Self::Green]
+///     }
+///
+///     fn from_id(id: &str) -> Result<Self, FormFieldValidationError> {
+///         match id {
+///             "red" => Ok(Self::Red),
+///             "green" => Ok(Self::Green),
+///             _ => Err(FormFieldValidationError::invalid_value(id)),
+///         }
+///     }
+///
+///     fn id(&self) -> String {
+///         match self {
+///             Self::Red => "red".to_owned(),
+///             Self::Green => "green".to_owned(),
+///         }
+///     }
+///
+///     fn label(&self) -> String {
+///         match self {
+///             Self::Red => "Red".to_owned(),
+///             Self::Green => "Green".to_owned(),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(Color::from_id("red"), Ok(Color::Red));
+/// ```
+pub trait SelectChoice {
+    /// Returns the options that can be chosen, unless overridden with
+    /// [`SelectFieldOptions::choices`].
+    fn default_choices() -> Vec<Self>
+    where
+        Self: Sized;
+
+    /// Returns the option with given ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no option with given ID.
+    fn from_id(id: &str) -> Result<Self, FormFieldValidationError>
+    where
+        Self: Sized;
+
+    /// Returns the ID of the option, used as the value of the HTML
+    /// `<option>` element.
+    fn id(&self) -> String;
+
+    /// Returns the human-readable label of the option.
+    fn label(&self) -> String;
+}
+
+#[cfg(feature = "db")]
+impl<T: DbEnum> SelectChoice for T {
+    fn default_choices() -> Vec<Self> {
+        T::VARIANTS.to_vec()
+    }
+
+    fn from_id(id: &str) -> Result<Self, FormFieldValidationError> {
+        T::from_name(id).ok_or_else(|| FormFieldValidationError::invalid_value(id))
+    }
+
+    fn id(&self) -> String {
+        self.name().to_owned()
+    }
+
+    fn label(&self) -> String {
+        DbEnum::label(self).to_owned()
+    }
+}
+
+impl<T: SelectChoice> AsFormField for T {
+    type Type = SelectField<T>;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        let value = check_required(field)?;
+
+        if let Some(choices) = &field.custom_options.choices {
+            if !choices.iter().any(|choice| choice.id() == value) {
+                return Err(FormFieldValidationError::invalid_value(value));
+            }
+        }
+        T::from_id(value)
+    }
+
+    fn to_field_value(&self) -> String {
+        self.id()
+    }
+}
+
 impl<T: AsFormField> AsFormField for Option<T> {
     type Type = T::Type;
 
@@ -496,6 +682,127 @@ impl<T: AsFormField> AsFormField for Auto<T> {
     }
 }
 
+/// A form field for a file upload.
+///
+/// The files can only be uploaded in `multipart/form-data` forms, so the
+/// `<form>` element containing this field must have the
+/// `enctype="multipart/form-data"` attribute.
+#[derive(Debug)]
+pub struct FileField {
+    options: FormFieldOptions,
+    custom_options: FileFieldOptions,
+    file: Option<UploadedFile>,
+}
+
+impl FormField for FileField {
+    type CustomOptions = FileFieldOptions;
+
+    fn with_options(options: FormFieldOptions, custom_options: Self::CustomOptions) -> Self {
+        Self {
+            options,
+            custom_options,
+            file: None,
+        }
+    }
+
+    fn options(&self) -> &FormFieldOptions {
+        &self.options
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.file.as_ref().and_then(UploadedFile::file_name)
+    }
+
+    fn set_value(&mut self, _value: Cow<'_, str>) {
+        // the files can't be set from the string values; this happens when
+        // the form is not submitted as `multipart/form-data`
+    }
+
+    fn set_file(&mut self, file: UploadedFile) -> Result<(), FormFieldValidationError> {
+        self.file = Some(file);
+        Ok(())
+    }
+}
+
+/// Custom options for a [`FileField`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FileFieldOptions {
+    /// The maximum size of the file, in bytes.
+    pub max_size: Option<u64>,
+    /// The content types of the accepted files, such as `image/png`, or
+    /// `image/*` for all images. Used to set the `accept` attribute in the
+    /// HTML input element.
+    ///
+    /// Note that the content type is sent by the client, so it can't be
+    /// trusted.
+    pub accept: Option<&'static [&'static str]>,
+}
+
+impl Display for FileField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut tag = HtmlTag::input("file");
+        tag.attr("name", self.id());
+        tag.attr("id", self.id());
+        if self.options.required {
+            tag.bool_attr("required");
+        }
+        if let Some(accept) = self.custom_options.accept {
+            tag.attr("accept", &accept.join(","));
+        }
+
+        write!(f, "{}", tag.render())
+    }
+}
+
+impl HtmlSafe for FileField {}
+
+impl AsFormField for UploadedFile {
+    type Type = FileField;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        // browsers send an empty file without a name when no file is selected
+        let file = field
+            .file
+            .as_ref()
+            .filter(|file| file.file_name().is_some_and(|name| !name.is_empty()))
+            .ok_or(FormFieldValidationError::Required)?;
+
+        if let Some(max_size) = field.custom_options.max_size {
+            if file.size() > max_size {
+                return Err(FormFieldValidationError::FileTooLarge { max_size });
+            }
+        }
+        if let Some(accept) = field.custom_options.accept {
+            let content_type = file.content_type().unwrap_or_default();
+            if !accept
+                .iter()
+                .any(|accepted| content_type_matches(accepted, content_type))
+            {
+                return Err(FormFieldValidationError::FileTypeNotAllowed);
+            }
+        }
+
+        Ok(file.clone())
+    }
+
+    fn to_field_value(&self) -> String {
+        self.file_name().unwrap_or_default().to_owned()
+    }
+}
+
+/// Checks whether a content type matches an accepted one, which can be a
+/// wildcard like `image/*`.
+fn content_type_matches(accepted: &str, content_type: &str) -> bool {
+    let content_type = content_type.split(';').next().unwrap_or_default().trim();
+
+    match accepted.strip_suffix("/*") {
+        Some(accepted_type) => content_type
+            .split_once('/')
+            .is_some_and(|(type_, _)| type_.eq_ignore_ascii_case(accepted_type)),
+        None => content_type.eq_ignore_ascii_case(accepted),
+    }
+}
+
 fn check_required<T: FormField>(field: &T) -> Result<&str, FormFieldValidationError> {
     if let Some(value) = field.value() {
         if value.is_empty() {
@@ -675,6 +982,31 @@ mod tests {
         assert_eq!(value, 5);
     }
 
+    #[cfg(feature = "db")]
+    #[test]
+    fn uuid_field_clean_value() {
+        let mut field = uuid::Uuid::new_field(
+            FormFieldOptions {
+                id: "id".to_owned(),
+                name: "id".to_owned(),
+                required: true,
+            },
+            StringFieldOptions::default(),
+        );
+        field.set_value(Cow::Borrowed("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        let value = uuid::Uuid::clean_value(&field).unwrap();
+        assert_eq!(
+            value.to_field_value(),
+            "67e55044-10b1-426f-9247-bb680e5fe0c8"
+        );
+
+        field.set_value(Cow::Borrowed("not-a-uuid"));
+        assert_eq!(
+            uuid::Uuid::clean_value(&field),
+            Err(FormFieldValidationError::invalid_value("not-a-uuid"))
+        );
+    }
+
     #[test]
     fn bool_field_clean_value() {
         let mut field = BoolField::with_options(
@@ -691,4 +1023,134 @@ mod tests {
         let value = bool::clean_value(&field).unwrap();
         assert!(value);
     }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    impl SelectChoice for Color {
+        fn default_choices() -> Vec<Self> {
+            vec![Self::Red, Self::Green]
+        }
+
+        fn from_id(id: &str) -> Result<Self, FormFieldValidationError> {
+            match id {
+                "red" => Ok(Self::Red),
+                "green" => Ok(Self::Green),
+                _ => Err(FormFieldValidationError::invalid_value(id)),
+            }
+        }
+
+        fn id(&self) -> String {
+            format!("{self:?}").to_lowercase()
+        }
+
+        fn label(&self) -> String {
+            format!("{self:?}")
+        }
+    }
+
+    #[test]
+    fn select_field_render() {
+        let mut field = SelectField::<Color>::with_options(
+            FormFieldOptions {
+                id: "color".to_owned(),
+                name: "color".to_owned(),
+                required: true,
+            },
+            SelectFieldOptions::default(),
+        );
+        assert_eq!(
+            field.to_string(),
+            "<select name=\"color\" id=\"color\" required>\
+             <option value=\"\">---------</option>\
+             <option value=\"red\">Red</option>\
+             <option value=\"green\">Green</option>\
+             </select>"
+        );
+
+        field.set_value(Cow::Borrowed("green"));
+        let html = field.to_string();
+        assert!(!html.contains("---------"));
+        assert!(html.contains("<option value=\"green\" selected>Green</option>"));
+    }
+
+    #[test]
+    fn select_field_clean_value() {
+        let mut field = Color::new_field(
+            FormFieldOptions {
+                id: "color".to_owned(),
+                name: "color".to_owned(),
+                required: true,
+            },
+            SelectFieldOptions {
+                choices: Some(vec![Color::Green]),
+            },
+        );
+        assert_eq!(
+            Color::clean_value(&field),
+            Err(FormFieldValidationError::Required)
+        );
+
+        field.set_value(Cow::Borrowed("green"));
+        assert_eq!(Color::clean_value(&field), Ok(Color::Green));
+        field.set_value(Cow::Borrowed("red"));
+        assert_eq!(
+            Color::clean_value(&field),
+            Err(FormFieldValidationError::invalid_value("red"))
+        );
+        assert!(!field.to_string().contains("value=\"red\""));
+    }
+
+    #[test]
+    fn file_field_render() {
+        let field = FileField::with_options(
+            FormFieldOptions {
+                id: "avatar".to_owned(),
+                name: "Avatar".to_owned(),
+                required: true,
+            },
+            FileFieldOptions {
+                max_size: None,
+                accept: Some(&["image/png", "image/*"]),
+            },
+        );
+        let html = field.to_string();
+        assert!(html.contains("type=\"file\""));
+        assert!(html.contains("required"));
+        assert!(html.contains("accept=\"image/png,image/*\""));
+    }
+
+    #[test]
+    fn file_field_clean_value() {
+        let mut field = FileField::with_options(
+            FormFieldOptions {
+                id: "avatar".to_owned(),
+                name: "Avatar".to_owned(),
+                required: true,
+            },
+            FileFieldOptions::default(),
+        );
+        assert_eq!(
+            UploadedFile::clean_value(&field).unwrap_err(),
+            FormFieldValidationError::Required
+        );
+
+        field
+            .set_file(UploadedFile::new(Some("a.png".to_owned()), None, "png"))
+            .unwrap();
+        assert_eq!(field.value(), Some("a.png"));
+        assert_eq!(UploadedFile::clean_value(&field).unwrap().size(), 3);
+    }
+
+    #[test]
+    fn content_type_matches_accepted() {
+        assert!(content_type_matches("image/png", "image/png"));
+        assert!(content_type_matches("image/png", "IMAGE/PNG; foo=bar"));
+        assert!(content_type_matches("image/*", "image/jpeg"));
+        assert!(!content_type_matches("image/*", "text/plain"));
+        assert!(!content_type_matches("image/png", ""));
+    }
 }
@@ -5,10 +5,11 @@ use std::pin::Pin;
 use tower::util::BoxCloneSyncService;
 
 use crate::error::ErrorRepr;
-use crate::request::Request;
+use crate::middleware::reject_too_large;
 use crate::request::extractors::{FromRequest, FromRequestParts};
+use crate::request::{Accepts, MultipartError, Request};
 use crate::response::{Response, not_found_response};
-use crate::{Error, Result};
+use crate::{Body, Error, Result, StatusCode};
 
 /// A function that takes a request and returns a response.
 ///
@@ -52,13 +53,51 @@ pub(crate) fn into_box_request_handler<T, H: RequestHandler<T> + Send + Sync>(
             request: Request,
         ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send + '_>> {
             Box::pin(async move {
+                // kept to choose the representation of client error responses
+                let accept = request.headers().get(http::header::ACCEPT).cloned();
                 let response = self.0.handle(request).await;
+                let accepts = || Accepts::from_values(accept.as_ref());
 
                 match response {
                     Ok(response) => Ok(response),
                     Err(error) => match error.inner {
                         ErrorRepr::NotFound { message } => Ok(not_found_response(message)),
-                        _ => Err(error),
+                        #[cfg(feature = "json")]
+                        ErrorRepr::Validation(error) => Ok(error.as_response()),
+                        ref repr @ (ErrorRepr::PathParametersParse(_)
+                        | ErrorRepr::QueryParametersParse(_)) => Ok(client_error_response(
+                            StatusCode::BAD_REQUEST,
+                            repr,
+                            &accepts(),
+                        )),
+                        #[cfg(feature = "json")]
+                        ref repr @ ErrorRepr::JsonParse(_) => Ok(client_error_response(
+                            StatusCode::BAD_REQUEST,
+                            repr,
+                            &accepts(),
+                        )),
+                        ref repr @ (ErrorRepr::InvalidContentType { .. }
+                        | ErrorRepr::Multipart(MultipartError::InvalidContentType)) => {
+                            Ok(client_error_response(
+                                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                                repr,
+                                &accepts(),
+                            ))
+                        }
+                        ref repr @ ErrorRepr::Multipart(MultipartError::Malformed(_)) => Ok(
+                            client_error_response(StatusCode::BAD_REQUEST, repr, &accepts()),
+                        ),
+                        #[cfg(feature = "websocket")]
+                        ref repr @ ErrorRepr::WebSocket(ref error)
+                            if error.is_handshake_error() =>
+                        {
+                            Ok(client_error_response(
+                                StatusCode::BAD_REQUEST,
+                                repr,
+                                &accepts(),
+                            ))
+                        }
+                        _ => reject_too_large(Err(error)),
                     },
                 }
             })
@@ -68,6 +107,34 @@ pub(crate) fn into_box_request_handler<T, H: RequestHandler<T> + Send + Sync>(
     Inner(handler, PhantomData)
 }
 
+/// Builds a response for a request the extractors couldn't parse, with the
+/// reason as a plain text body, or as a JSON object if the client prefers
+/// JSON.
+#[cfg_attr(not(feature = "json"), expect(unused_variables))]
+fn client_error_response(status: StatusCode, error: &ErrorRepr, accepts: &Accepts) -> Response {
+    #[cfg(feature = "json")]
+    if accepts.prefers(&[
+        crate::headers::PLAIN_TEXT_CONTENT_TYPE,
+        crate::headers::JSON_CONTENT_TYPE,
+    ]) == Some(crate::headers::JSON_CONTENT_TYPE)
+    {
+        return <Response as crate::response::ResponseExt>::new_json(
+            status,
+            &serde_json::json!({ "error": error.to_string() }),
+        )
+        .expect("a string should always be serializable");
+    }
+
+    http::Response::builder()
+        .status(status)
+        .header(
+            http::header::CONTENT_TYPE,
+            crate::headers::PLAIN_TEXT_CONTENT_TYPE,
+        )
+        .body(Body::fixed(error.to_string()))
+        .expect("failed to build client error response")
+}
+
 macro_rules! impl_request_handler {
     ($($ty:ident),*) => {
         impl<T, $($ty,)* R> RequestHandler<($($ty,)*)> for T
@@ -1,4 +1,16 @@
 pub(crate) const HTML_CONTENT_TYPE: &str = "text/html; charset=utf-8";
+pub(crate) const PLAIN_TEXT_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
 pub(crate) const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
 #[cfg(feature = "json")]
 pub(crate) const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Returns whether the `Content-Type` header value is a URL-encoded form,
+/// ignoring its parameters (such as `charset`).
+pub(crate) fn is_form_content_type(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .eq_ignore_ascii_case(FORM_CONTENT_TYPE)
+}
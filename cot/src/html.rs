@@ -108,6 +108,7 @@ pub struct HtmlTag {
     tag: String,
     attributes: Vec<(String, String)>,
     boolean_attributes: Vec<String>,
+    content: Option<String>,
 }
 
 impl HtmlTag {
@@ -127,6 +128,7 @@ impl HtmlTag {
             tag: tag.to_string(),
             attributes: Vec::new(),
             boolean_attributes: Vec::new(),
+            content: None,
         }
     }
 
@@ -207,6 +209,65 @@ impl HtmlTag {
         self
     }
 
+    /// Appends text to the content of the HTML tag.
+    ///
+    /// A tag with content is rendered with a closing tag instead of being
+    /// self-closing.
+    ///
+    /// # Safety
+    ///
+    /// This function will escape the text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`String`] writer fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::HtmlTag;
+    ///
+    /// let mut tag = HtmlTag::new("option");
+    /// tag.attr("value", "1").push_str("One & only");
+    /// assert_eq!(
+    ///     tag.render().as_str(),
+    ///     "<option value=\"1\">One &#38; only</option>"
+    /// );
+    /// ```
+    pub fn push_str(&mut self, text: &str) -> &mut Self {
+        let content = self.content.get_or_insert_with(String::new);
+        askama::filters::Html
+            .write_escaped_str(content, text)
+            .expect("Failed to write HTML tag content");
+        self
+    }
+
+    /// Appends a child tag to the content of the HTML tag.
+    ///
+    /// A tag with content is rendered with a closing tag instead of being
+    /// self-closing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::HtmlTag;
+    ///
+    /// let mut option = HtmlTag::new("option");
+    /// option.attr("value", "1").push_str("One");
+    /// let mut select = HtmlTag::new("select");
+    /// select.attr("name", "number").push_tag(&option);
+    /// assert_eq!(
+    ///     select.render().as_str(),
+    ///     "<select name=\"number\"><option value=\"1\">One</option></select>"
+    /// );
+    /// ```
+    pub fn push_tag(&mut self, tag: &HtmlTag) -> &mut Self {
+        self.content
+            .get_or_insert_with(String::new)
+            .push_str(tag.render().as_str());
+        self
+    }
+
     /// Renders the HTML tag.
     ///
     /// # Panics
@@ -239,7 +300,10 @@ impl HtmlTag {
             write!(&mut result, " {key}").expect(FAIL_MSG);
         }
 
-        write!(&mut result, " />").expect(FAIL_MSG);
+        match &self.content {
+            Some(content) => write!(&mut result, ">{content}</{}>", self.tag).expect(FAIL_MSG),
+            None => write!(&mut result, " />").expect(FAIL_MSG),
+        }
         result.into()
     }
 }
@@ -296,4 +360,26 @@ mod tests {
             "<input type=\"text\" name=\"username\" />"
         );
     }
+
+    #[test]
+    fn test_html_tag_with_content() {
+        let mut option = HtmlTag::new("option");
+        option
+            .attr("value", "a")
+            .bool_attr("selected")
+            .push_str("<A>");
+        let mut select = HtmlTag::new("select");
+        select.push_tag(&option).push_str("");
+        assert_eq!(
+            select.render().as_str(),
+            "<select><option value=\"a\" selected>&#60;A&#62;</option></select>"
+        );
+    }
+
+    #[test]
+    fn test_html_tag_empty_content() {
+        let mut tag = HtmlTag::new("textarea");
+        tag.push_str("");
+        assert_eq!(tag.render().as_str(), "<textarea></textarea>");
+    }
 }
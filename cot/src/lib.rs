@@ -66,21 +66,30 @@ pub mod admin;
 pub mod auth;
 mod body;
 pub mod cli;
+pub mod clock;
 pub mod config;
+pub mod cookie;
 mod error_page;
+pub mod feature_flags;
 mod handler;
 pub mod html;
+pub mod locale;
 pub mod middleware;
 pub mod project;
 pub mod request;
 pub mod response;
 pub mod router;
+mod server;
 pub mod session;
+pub mod state;
 pub mod static_files;
 pub mod test;
+pub mod timezone;
 pub(crate) mod utils;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
-pub use body::Body;
+pub use body::{Body, BodyStream};
 pub use cot_macros::{main, test};
 pub use error::Error;
 pub use {bytes, http};
@@ -0,0 +1,245 @@
+//! Locale negotiation.
+//!
+//! The [`LocaleMiddleware`](crate::middleware::LocaleMiddleware) resolves the
+//! [`Locale`] of each request from its `Accept-Language` header and the
+//! locales supported by the project. The resolved locale can then be accessed
+//! in the request handlers with
+//! [`RequestExt::locale`](crate::request::RequestExt::locale) or by using
+//! [`Locale`] as an extractor, and passed to the templates so they can pick
+//! the right translations.
+//!
+//! # Examples
+//!
+//! ```
+//! use askama::Template;
+//! use cot::locale::Locale;
+//! use cot::response::{Response, ResponseExt};
+//! use cot::{Body, StatusCode};
+//!
+//! #[derive(Template)]
+//! #[template(source = r#"<html lang="{{ locale }}"></html>"#, ext = "html")]
+//! struct IndexTemplate {
+//!     locale: Locale,
+//! }
+//!
+//! async fn index(locale: Locale) -> cot::Result<Response> {
+//!     let rendered = IndexTemplate { locale }.render()?;
+//!     Ok(Response::new_html(StatusCode::OK, Body::fixed(rendered)))
+//! }
+//! ```
+
+use std::fmt::{Display, Formatter};
+
+use http::HeaderMap;
+
+use crate::error::ErrorRepr;
+
+/// The locale of a request, resolved by
+/// [`LocaleMiddleware`](crate::middleware::LocaleMiddleware).
+///
+/// The locale is always one of the locales supported by the project, as
+/// passed to [`LocaleMiddleware::new`](crate::middleware::LocaleMiddleware::new).
+///
+/// # Examples
+///
+/// ```
+/// use cot::locale::Locale;
+///
+/// let locale = Locale::new("pl-PL");
+/// assert_eq!(locale.as_str(), "pl-PL");
+/// assert_eq!(locale.language(), "pl");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale(String);
+
+impl Locale {
+    /// Creates a new [`Locale`] from a language tag (such as `en` or
+    /// `pt-BR`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::locale::Locale;
+    ///
+    /// let locale = Locale::new("en");
+    /// ```
+    #[must_use]
+    pub fn new(tag: impl Into<String>) -> Self {
+        Self(tag.into())
+    }
+
+    /// Returns the language tag of the locale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::locale::Locale;
+    ///
+    /// assert_eq!(Locale::new("pt-BR").as_str(), "pt-BR");
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the primary language subtag of the locale (e.g. `pt` for
+    /// `pt-BR`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::locale::Locale;
+    ///
+    /// assert_eq!(Locale::new("pt-BR").language(), "pt");
+    /// assert_eq!(Locale::new("en").language(), "en");
+    /// ```
+    #[must_use]
+    pub fn language(&self) -> &str {
+        primary_subtag(&self.0)
+    }
+
+    pub(crate) fn try_from_extensions(extensions: &http::Extensions) -> crate::Result<&Self> {
+        extensions
+            .get::<Self>()
+            .ok_or_else(|| crate::Error::new(ErrorRepr::LocaleMissing))
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Chooses the best of the `supported` locales for the `Accept-Language`
+/// header in `headers`.
+///
+/// The languages requested by the client are tried in the order of their
+/// quality values. Each of them matches a supported locale with the same tag
+/// or, failing that, with the same primary language subtag. If nothing
+/// matches, the first supported locale is returned.
+pub(crate) fn negotiate<'a>(headers: &HeaderMap, supported: &'a [Locale]) -> &'a Locale {
+    requested_languages(headers)
+        .into_iter()
+        .find_map(|tag| match_locale(tag, supported))
+        .unwrap_or(&supported[0])
+}
+
+/// Returns the language ranges of the `Accept-Language` header in `headers`,
+/// from the most to the least preferred one according to their quality
+/// values. The ranges with the quality of zero are skipped.
+pub(crate) fn requested_languages(headers: &HeaderMap) -> Vec<&str> {
+    let mut requested: Vec<(&str, f32)> = headers
+        .get_all(http::header::ACCEPT_LANGUAGE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(parse_language_range)
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect();
+    // stable sort, so that the order of the header is kept for equal
+    // qualities
+    requested.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    requested.into_iter().map(|(tag, _)| tag).collect()
+}
+
+fn parse_language_range(range: &str) -> Option<(&str, f32)> {
+    let mut parts = range.split(';');
+    let tag = parts.next()?.trim();
+    if tag.is_empty() {
+        return None;
+    }
+
+    let quality = parts
+        .filter_map(|param| param.trim().strip_prefix("q="))
+        .find_map(|quality| quality.trim().parse::<f32>().ok())
+        .unwrap_or(1.0);
+    Some((tag, quality))
+}
+
+fn match_locale<'a>(tag: &str, supported: &'a [Locale]) -> Option<&'a Locale> {
+    if tag == "*" {
+        return supported.first();
+    }
+
+    supported
+        .iter()
+        .find(|locale| locale.as_str().eq_ignore_ascii_case(tag))
+        .or_else(|| {
+            supported
+                .iter()
+                .find(|locale| locale.language().eq_ignore_ascii_case(primary_subtag(tag)))
+        })
+}
+
+fn primary_subtag(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(accept_language: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::ACCEPT_LANGUAGE,
+            accept_language.parse().unwrap(),
+        );
+        headers
+    }
+
+    fn supported() -> Vec<Locale> {
+        vec![Locale::new("en"), Locale::new("pl"), Locale::new("pt-BR")]
+    }
+
+    #[test]
+    fn negotiate_exact_match() {
+        let supported = supported();
+        assert_eq!(negotiate(&headers("pl"), &supported).as_str(), "pl");
+        assert_eq!(negotiate(&headers("pt-br"), &supported).as_str(), "pt-BR");
+    }
+
+    #[test]
+    fn negotiate_language_match() {
+        let supported = supported();
+        assert_eq!(negotiate(&headers("pl-PL"), &supported).as_str(), "pl");
+        assert_eq!(negotiate(&headers("pt-PT"), &supported).as_str(), "pt-BR");
+    }
+
+    #[test]
+    fn negotiate_quality() {
+        let supported = supported();
+        assert_eq!(
+            negotiate(&headers("de, en;q=0.5, pl;q=0.8"), &supported).as_str(),
+            "pl"
+        );
+        assert_eq!(
+            negotiate(&headers("pl;q=0, en;q=0.1"), &supported).as_str(),
+            "en"
+        );
+    }
+
+    #[test]
+    fn negotiate_fallback() {
+        let supported = supported();
+        assert_eq!(negotiate(&HeaderMap::new(), &supported).as_str(), "en");
+        assert_eq!(negotiate(&headers("de, fr"), &supported).as_str(), "en");
+        assert_eq!(negotiate(&headers("*"), &supported).as_str(), "en");
+    }
+
+    #[test]
+    fn requested_languages_order() {
+        assert_eq!(
+            requested_languages(&headers("fr-CH;q=0.9, de, fr;q=0.8, en;q=0")),
+            ["de", "fr-CH", "fr"]
+        );
+        assert!(requested_languages(&HeaderMap::new()).is_empty());
+    }
+
+    #[test]
+    fn locale_display() {
+        assert_eq!(Locale::new("pt-BR").to_string(), "pt-BR");
+    }
+}
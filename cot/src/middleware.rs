@@ -3,21 +3,114 @@
 //! Middlewares are used to modify requests and responses in a pipeline. They
 //! are used to add functionality to the request/response cycle, such as
 //! session management, adding security headers, and more.
-
+//!
+//! # Stateful middlewares
+//!
+//! Some middlewares need state that outlives a single request, such as rate
+//! limiter buckets, caches, or idempotency key stores. The
+//! [`tower::Layer::layer`] method may be called more than once, and the
+//! services it creates are cloned freely (at least once per request), so such
+//! state must never be created inside `layer()` or in the service itself.
+//!
+//! Instead, Cot middlewares follow this convention:
+//!
+//! * The state is created exactly once, when the middleware is constructed — in
+//!   `new()` or, when it depends on the project configuration, in
+//!   `from_context(context: &MiddlewareContext)`. Both are called from
+//!   [`Project::middlewares`](crate::project::Project::middlewares), which runs
+//!   once at startup. If the state should be created by the project itself (for
+//!   instance, to be accessed elsewhere), the middleware additionally accepts
+//!   an already constructed `Arc<State>`.
+//! * The middleware keeps the state in an [`Arc`](std::sync::Arc), and
+//!   `layer()` only clones that `Arc` into the service it returns.
+//! * The service derives [`Clone`], so cloning it shares the state rather than
+//!   duplicating it.
+//!
+//! [`RateLimitMiddleware`] is the reference implementation of this pattern.
+
+mod body_limit;
+#[cfg(feature = "compression")]
+mod compression;
+mod conditional_get;
+mod cookie_session;
+mod cors;
+mod csrf;
+#[cfg(feature = "compression")]
+mod decompression;
+mod from_fn;
+mod locale;
+mod maintenance;
+mod method_override;
+mod metrics;
+mod proxy_headers;
+#[cfg(feature = "db")]
+mod query_stats;
+mod rate_limit;
+mod request_id;
+mod security_headers;
+mod timeout;
+mod timezone;
+#[cfg(feature = "db")]
+mod transaction;
+
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
+pub use body_limit::{BodyLimitMiddleware, BodyLimitService};
+pub(crate) use body_limit::{
+    ensure_body_limit, override_body_limit, reject_too_large, request_body_limit,
+};
 use bytes::Bytes;
+#[cfg(feature = "compression")]
+pub use compression::{CompressionMiddleware, CompressionService};
+pub use conditional_get::{ConditionalGetMiddleware, ConditionalGetService};
+pub(crate) use conditional_get::{is_not_modified, is_range_applicable, not_modified_response};
+pub use cookie_session::CookieSessionService;
+use cookie_session::{SessionCookieCodec, SessionCookieConfig};
+pub use cors::{CorsMiddleware, CorsService};
+pub use csrf::{
+    CSRF_TOKEN_FIELD, CSRF_TOKEN_HEADER, CsrfMiddleware, CsrfService, CsrfToken, CsrfTokenField,
+};
+#[cfg(feature = "compression")]
+pub use decompression::{RequestDecompressionMiddleware, RequestDecompressionService};
+pub use from_fn::{FromFnLayer, FromFnService, Next, from_fn};
 use futures_core::future::BoxFuture;
 use futures_util::TryFutureExt;
 use http_body_util::BodyExt;
 use http_body_util::combinators::BoxBody;
+pub use locale::{LocaleMiddleware, LocaleService};
+pub use maintenance::{MaintenanceMiddleware, MaintenanceService};
+pub use method_override::{MethodOverrideMiddleware, MethodOverrideService};
+pub(crate) use metrics::BytesRead;
+pub use metrics::{BodyMetricsMiddleware, BodyMetricsService, RequestSummary};
+pub use proxy_headers::{ProxyHeadersMiddleware, ProxyHeadersService};
+#[cfg(feature = "db")]
+pub use query_stats::{QueryStatsMiddleware, QueryStatsService};
+#[cfg(feature = "redis")]
+pub use rate_limit::RedisRateLimitStore;
+pub use rate_limit::{RateLimitMiddleware, RateLimitService, RateLimitStore, RateLimiter};
+pub(crate) use request_id::RequestIdSlot;
+pub use request_id::{RequestId, RequestIdMiddleware, RequestIdService};
+pub use security_headers::{SecurityHeadersMiddleware, SecurityHeadersService};
+pub use timeout::{TimeoutMiddleware, TimeoutService};
+pub use timezone::{TimezoneMiddleware, TimezoneService};
 use tower::Service;
-use tower_sessions::{MemoryStore, SessionManagerLayer};
-
+use tower_sessions::{SessionManagerLayer, SessionStore};
+use tracing::error;
+#[cfg(feature = "db")]
+pub(crate) use transaction::RequestTransaction;
+#[cfg(feature = "db")]
+pub use transaction::{TransactionMiddleware, TransactionService};
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::{SecretKey, SessionStoreErrorPolicy, SessionStoreType};
 use crate::error::ErrorRepr;
 use crate::project::MiddlewareContext;
 use crate::request::Request;
 use crate::response::Response;
+use crate::session::cookie::{CookiePrefix, CookiePrefixError, SameSite};
+use crate::session::store::{ExpiredDeletion, MemoryStore, SessionJanitor, SessionStoreWrapper};
 use crate::{Body, Error};
 
 /// Middleware that converts a any [`http::Response`] generic type to a
@@ -249,24 +342,87 @@ where
 
 /// A middleware that provides session management.
 ///
-/// By default, it uses an in-memory store for session data.
+/// By default, it uses an in-memory store for session data. A different
+/// store can be set with [`SessionMiddleware::store`], or with
+/// [`SessionMiddleware::store_with_cleanup`] to also periodically delete the
+/// expired sessions from it. The session data can also be kept in the
+/// session cookie itself with [`SessionMiddleware::signed_cookie_store`] or
+/// [`SessionMiddleware::encrypted_cookie_store`].
+///
+/// When the session store is unavailable, the middleware either fails the
+/// request or continues with a transient session, depending on the
+/// [`SessionStoreErrorPolicy`].
+///
+/// # Panics
+///
+/// Creating the service from this middleware (which happens when the project
+/// is bootstrapped) panics if the cookie attributes are not compatible with
+/// the [`CookiePrefix`] set with [`SessionMiddleware::cookie_prefix`]. Use
+/// [`SessionMiddleware::validate`] to check them beforehand.
 #[derive(Debug, Clone)]
 pub struct SessionMiddleware {
-    inner: SessionManagerLayer<MemoryStore>,
+    store: Arc<dyn SessionStore>,
+    janitor: Option<Arc<SessionJanitor>>,
+    cookie_store: Option<SessionCookieCodec>,
+    store_error_policy: SessionStoreErrorPolicy,
+    secure: bool,
+    cookie_name: Option<String>,
+    cookie_path: Option<String>,
+    cookie_domain: Option<String>,
+    cookie_prefix: Option<CookiePrefix>,
+    cookie_http_only: bool,
+    cookie_same_site: SameSite,
+    cookie_max_age: Option<Duration>,
+    clock: Arc<dyn Clock>,
 }
 
 impl SessionMiddleware {
     /// Crates a new instance of [`SessionMiddleware`].
     #[must_use]
     pub fn new() -> Self {
-        let store = MemoryStore::default();
-        let layer = SessionManagerLayer::new(store);
-        Self { inner: layer }
+        Self {
+            store: Arc::new(MemoryStore::default()),
+            janitor: None,
+            cookie_store: None,
+            store_error_policy: SessionStoreErrorPolicy::default(),
+            secure: true,
+            cookie_name: None,
+            cookie_path: None,
+            cookie_domain: None,
+            cookie_prefix: None,
+            cookie_http_only: true,
+            cookie_same_site: SameSite::default(),
+            cookie_max_age: None,
+            clock: Arc::new(SystemClock),
+        }
     }
 
     /// Creates a new instance of [`SessionMiddleware`] from the application
     /// context.
     ///
+    /// If [`SessionMiddlewareConfig::cleanup_interval`](crate::config::SessionMiddlewareConfig::cleanup_interval)
+    /// is set, the expired sessions are periodically deleted from the store
+    /// by a [`SessionJanitor`], which is stopped when the server shuts down
+    /// gracefully. If
+    /// [`SessionMiddlewareConfig::store`](crate::config::SessionMiddlewareConfig::store)
+    /// is [`SessionStoreType::Redis`], the sessions are kept in Redis instead
+    /// of memory. With [`SessionStoreType::SignedCookie`] and
+    /// [`SessionStoreType::EncryptedCookie`], they're kept in the session
+    /// cookie, signed or encrypted with the
+    /// [`secret_key`](crate::config::ProjectConfig::secret_key) (and
+    /// accepted when signed with one of the
+    /// [`fallback_secret_keys`](crate::config::ProjectConfig::fallback_secret_keys)).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cleanup interval is set and this is called outside of a
+    /// Tokio runtime (which is never the case in
+    /// [`Project::middlewares`](crate::project::Project::middlewares)).
+    ///
+    /// Panics if the Redis session store is configured without a valid URL,
+    /// or without the `redis` feature enabled. Both are reported by the
+    /// config validation when the project is bootstrapped.
+    ///
     /// # Examples
     ///
     /// ```
@@ -289,7 +445,214 @@ impl SessionMiddleware {
     /// ```
     #[must_use]
     pub fn from_context(context: &MiddlewareContext) -> Self {
-        Self::new().secure(context.config().middlewares.session.secure)
+        let config = &context.config().middlewares.session;
+        let middleware = Self {
+            cookie_name: config.cookie_name.clone(),
+            cookie_path: config.cookie_path.clone(),
+            cookie_domain: config.cookie_domain.clone(),
+            cookie_prefix: config.cookie_prefix,
+            cookie_http_only: config.cookie_http_only,
+            cookie_same_site: config.cookie_same_site,
+            cookie_max_age: config.cookie_max_age,
+            ..Self::new()
+                .secure(config.secure)
+                .store_error_policy(config.store_error_policy)
+                .clock(Arc::clone(context.clock()))
+        };
+
+        match (config.store, config.cleanup_interval) {
+            (SessionStoreType::Memory, cleanup_interval) => {
+                let store = MemoryStore::with_clock(Arc::clone(context.clock()));
+                if let Some(interval) = cleanup_interval {
+                    let janitor = SessionJanitor::spawn(store.clone(), interval);
+                    context.background_tasks().track(janitor.stop());
+                }
+                middleware.store(store)
+            }
+            #[cfg(feature = "redis")]
+            (SessionStoreType::Redis, _) => middleware.store(
+                crate::session::store::redis::RedisStore::from_config(&config.redis)
+                    .expect("invalid Redis session store configuration")
+                    .clock(Arc::clone(context.clock())),
+            ),
+            #[cfg(not(feature = "redis"))]
+            (SessionStoreType::Redis, _) => {
+                panic!("the Redis session store requires the `redis` feature to be enabled")
+            }
+            (SessionStoreType::SignedCookie, _) => middleware.signed_cookie_store(
+                &context.config().secret_key,
+                &context.config().fallback_secret_keys,
+            ),
+            (SessionStoreType::EncryptedCookie, _) => middleware.encrypted_cookie_store(
+                &context.config().secret_key,
+                &context.config().fallback_secret_keys,
+            ),
+        }
+    }
+
+    /// Sets the store for the session data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SessionMiddleware;
+    /// use cot::session::store::MemoryStore;
+    ///
+    /// let middleware = SessionMiddleware::new().store(MemoryStore::default());
+    /// ```
+    #[must_use]
+    pub fn store<T: SessionStore>(self, store: T) -> Self {
+        Self {
+            store: Arc::new(store),
+            janitor: None,
+            cookie_store: None,
+            ..self
+        }
+    }
+
+    /// Sets the store for the session data and spawns a [`SessionJanitor`]
+    /// that deletes the expired sessions from it every `interval`.
+    ///
+    /// The janitor runs for as long as the services created by this
+    /// middleware are alive, so it's stopped when the server shuts down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime (which is never the case
+    /// in [`Project::middlewares`](crate::project::Project::middlewares)), or
+    /// if `interval` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::SessionMiddleware;
+    /// use cot::session::store::MemoryStore;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let middleware = SessionMiddleware::new()
+    ///     .store_with_cleanup(MemoryStore::default(), Duration::from_secs(3600));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn store_with_cleanup<T>(self, store: T, interval: Duration) -> Self
+    where
+        T: ExpiredDeletion + Clone,
+    {
+        let janitor = SessionJanitor::spawn(store.clone(), interval);
+        Self {
+            store: Arc::new(store),
+            janitor: Some(Arc::new(janitor)),
+            cookie_store: None,
+            ..self
+        }
+    }
+
+    /// Keeps the session data in the session cookie itself, signed with
+    /// `secret_key`, instead of a server-side store.
+    ///
+    /// The data is serialized to JSON, so it can be read (but not modified)
+    /// by the client; use [`SessionMiddleware::encrypted_cookie_store`] to
+    /// hide it. Cookies signed with one of the `fallback_keys` are accepted
+    /// too, and signed again with `secret_key`, so that the keys can be
+    /// rotated without logging the users out.
+    ///
+    /// The browsers don't accept cookies larger than 4096 bytes, so the
+    /// sessions should be kept small. Saving a session that doesn't fit is
+    /// a session store error, handled according to the
+    /// [`SessionStoreErrorPolicy`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::SecretKey;
+    /// use cot::middleware::SessionMiddleware;
+    ///
+    /// let middleware = SessionMiddleware::new()
+    ///     .signed_cookie_store(&SecretKey::from("secret"), &[SecretKey::from("old secret")]);
+    /// ```
+    #[must_use]
+    pub fn signed_cookie_store(self, secret_key: &SecretKey, fallback_keys: &[SecretKey]) -> Self {
+        Self {
+            cookie_store: Some(SessionCookieCodec::new(secret_key, fallback_keys, false)),
+            janitor: None,
+            ..self
+        }
+    }
+
+    /// Keeps the session data in the session cookie itself, encrypted with
+    /// `secret_key`, instead of a server-side store.
+    ///
+    /// This works like [`SessionMiddleware::signed_cookie_store`], except that
+    /// the client can't read the session data either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::SecretKey;
+    /// use cot::middleware::SessionMiddleware;
+    ///
+    /// let middleware =
+    ///     SessionMiddleware::new().encrypted_cookie_store(&SecretKey::from("secret"), &[]);
+    /// ```
+    #[must_use]
+    pub fn encrypted_cookie_store(
+        self,
+        secret_key: &SecretKey,
+        fallback_keys: &[SecretKey],
+    ) -> Self {
+        Self {
+            cookie_store: Some(SessionCookieCodec::new(secret_key, fallback_keys, true)),
+            janitor: None,
+            ..self
+        }
+    }
+
+    /// Sets what to do when the session store can't be accessed.
+    ///
+    /// With [`SessionStoreErrorPolicy::Fail`] (the default), the requests
+    /// that come with a session that can't be loaded are answered with
+    /// `503 Service Unavailable`. With [`SessionStoreErrorPolicy::Degrade`],
+    /// the store errors are logged and the requests are handled with a
+    /// transient, empty session instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::SessionStoreErrorPolicy;
+    /// use cot::middleware::SessionMiddleware;
+    ///
+    /// let middleware = SessionMiddleware::new().store_error_policy(SessionStoreErrorPolicy::Degrade);
+    /// ```
+    #[must_use]
+    pub fn store_error_policy(self, store_error_policy: SessionStoreErrorPolicy) -> Self {
+        Self {
+            store_error_policy,
+            ..self
+        }
+    }
+
+    /// Sets the clock used to check whether the sessions kept in the session
+    /// cookie (see [`SessionMiddleware::signed_cookie_store`]) have expired.
+    ///
+    /// By default, the [`SystemClock`] is used.
+    /// [`SessionMiddleware::from_context`] uses the clock of the project.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::middleware::SessionMiddleware;
+    /// use cot::test::TestClock;
+    ///
+    /// let middleware = SessionMiddleware::new().clock(Arc::new(TestClock::new()));
+    /// ```
+    #[must_use]
+    pub fn clock(self, clock: Arc<dyn Clock>) -> Self {
+        Self { clock, ..self }
     }
 
     /// Sets the secure flag for the session middleware.
@@ -303,12 +666,230 @@ impl SessionMiddleware {
     /// ```
     #[must_use]
     pub fn secure(self, secure: bool) -> Self {
+        Self { secure, ..self }
+    }
+
+    /// Sets the name of the session cookie. By default, `id` is used.
+    ///
+    /// If a [`CookiePrefix`] is set, it is prepended to the name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SessionMiddleware;
+    ///
+    /// let middleware = SessionMiddleware::new().cookie_name("session");
+    /// ```
+    #[must_use]
+    pub fn cookie_name(self, cookie_name: impl Into<String>) -> Self {
+        Self {
+            cookie_name: Some(cookie_name.into()),
+            ..self
+        }
+    }
+
+    /// Sets the path of the session cookie. By default, `/` is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SessionMiddleware;
+    ///
+    /// let middleware = SessionMiddleware::new().cookie_path("/app");
+    /// ```
+    #[must_use]
+    pub fn cookie_path(self, cookie_path: impl Into<String>) -> Self {
+        Self {
+            cookie_path: Some(cookie_path.into()),
+            ..self
+        }
+    }
+
+    /// Sets the domain of the session cookie. By default, the cookie is only
+    /// sent to the host that set it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SessionMiddleware;
+    ///
+    /// let middleware = SessionMiddleware::new().cookie_domain("example.com");
+    /// ```
+    #[must_use]
+    pub fn cookie_domain(self, cookie_domain: impl Into<String>) -> Self {
+        Self {
+            cookie_domain: Some(cookie_domain.into()),
+            ..self
+        }
+    }
+
+    /// Sets whether the session cookie is set with the `HttpOnly` attribute,
+    /// which hides it from the scripts running in the browser. By default,
+    /// it is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SessionMiddleware;
+    ///
+    /// let middleware = SessionMiddleware::new().cookie_http_only(false);
+    /// ```
+    #[must_use]
+    pub fn cookie_http_only(self, cookie_http_only: bool) -> Self {
+        Self {
+            cookie_http_only,
+            ..self
+        }
+    }
+
+    /// Sets the `SameSite` attribute of the session cookie. By default,
+    /// [`SameSite::Strict`] is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SessionMiddleware;
+    /// use cot::session::cookie::SameSite;
+    ///
+    /// let middleware = SessionMiddleware::new().cookie_same_site(SameSite::Lax);
+    /// ```
+    #[must_use]
+    pub fn cookie_same_site(self, cookie_same_site: SameSite) -> Self {
+        Self {
+            cookie_same_site,
+            ..self
+        }
+    }
+
+    /// Sets how long the session lasts since it was last modified.
+    ///
+    /// The session cookie is set with the corresponding `Max-Age` attribute.
+    /// By default, the cookie is deleted when the browser is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::SessionMiddleware;
+    ///
+    /// let middleware = SessionMiddleware::new().cookie_max_age(Duration::from_secs(3600));
+    /// ```
+    #[must_use]
+    pub fn cookie_max_age(self, cookie_max_age: Duration) -> Self {
         Self {
-            inner: self.inner.with_secure(secure),
+            cookie_max_age: Some(cookie_max_age),
+            ..self
         }
     }
+
+    /// Sets the `__Secure-` or `__Host-` prefix for the session cookie name.
+    ///
+    /// The browsers only accept the prefixed cookies if they are secure and,
+    /// for [`CookiePrefix::Host`], have the path set to `/` and no domain.
+    /// This is checked with [`SessionMiddleware::validate`] when the
+    /// middleware is applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SessionMiddleware;
+    /// use cot::session::cookie::CookiePrefix;
+    ///
+    /// let middleware = SessionMiddleware::new().cookie_prefix(CookiePrefix::Host);
+    /// assert!(middleware.validate().is_ok());
+    /// ```
+    #[must_use]
+    pub fn cookie_prefix(self, cookie_prefix: CookiePrefix) -> Self {
+        Self {
+            cookie_prefix: Some(cookie_prefix),
+            ..self
+        }
+    }
+
+    /// Checks whether the session cookie attributes are compatible with the
+    /// [`CookiePrefix`] set with [`SessionMiddleware::cookie_prefix`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cookie prefix is set and the other attributes
+    /// would make the browsers reject the cookie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SessionMiddleware;
+    /// use cot::session::cookie::CookiePrefix;
+    ///
+    /// let middleware = SessionMiddleware::new()
+    ///     .cookie_prefix(CookiePrefix::Host)
+    ///     .cookie_domain("example.com");
+    /// assert!(middleware.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), CookiePrefixError> {
+        match self.cookie_prefix {
+            Some(prefix) => prefix.validate(
+                self.secure,
+                self.cookie_path
+                    .as_deref()
+                    .unwrap_or(DEFAULT_SESSION_COOKIE_PATH),
+                self.cookie_domain.as_deref(),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn cookie_config(&self) -> SessionCookieConfig {
+        let cookie_name = self
+            .cookie_name
+            .as_deref()
+            .unwrap_or(DEFAULT_SESSION_COOKIE_NAME);
+
+        SessionCookieConfig {
+            name: match self.cookie_prefix {
+                Some(prefix) => prefix.apply(cookie_name),
+                None => cookie_name.to_owned(),
+            },
+            path: self
+                .cookie_path
+                .clone()
+                .unwrap_or_else(|| DEFAULT_SESSION_COOKIE_PATH.to_owned()),
+            domain: self.cookie_domain.clone(),
+            secure: self.secure,
+            http_only: self.cookie_http_only,
+            same_site: self.cookie_same_site,
+            max_age: self.cookie_max_age,
+        }
+    }
+
+    fn session_manager_layer(&self) -> SessionManagerLayer<SessionStoreWrapper> {
+        let store = SessionStoreWrapper::new(
+            Arc::clone(&self.store),
+            self.store_error_policy,
+            self.janitor.clone(),
+        );
+        let cookie = self.cookie_config();
+        let expiry = cookie.expiry();
+
+        let mut layer = SessionManagerLayer::new(store)
+            .with_secure(cookie.secure)
+            .with_http_only(cookie.http_only)
+            .with_same_site(cookie.same_site.to_cookie())
+            .with_name(cookie.name)
+            .with_path(cookie.path);
+        if let Some(domain) = cookie.domain {
+            layer = layer.with_domain(domain);
+        }
+        if let Some(expiry) = expiry {
+            layer = layer.with_expiry(expiry);
+        }
+        layer
+    }
 }
 
+const DEFAULT_SESSION_COOKIE_NAME: &str = "id";
+const DEFAULT_SESSION_COOKIE_PATH: &str = "/";
+
 impl Default for SessionMiddleware {
     fn default() -> Self {
         Self::new()
@@ -316,15 +897,33 @@ impl Default for SessionMiddleware {
 }
 
 impl<S> tower::Layer<S> for SessionMiddleware {
-    type Service = <SessionManagerLayer<MemoryStore> as tower::Layer<
-        <SessionWrapperLayer as tower::Layer<S>>::Service,
-    >>::Service;
+    type Service = tower::util::Either<
+        <SessionManagerLayer<SessionStoreWrapper> as tower::Layer<
+            <SessionWrapperLayer as tower::Layer<S>>::Service,
+        >>::Service,
+        CookieSessionService<<SessionWrapperLayer as tower::Layer<S>>::Service>,
+    >;
 
     fn layer(&self, inner: S) -> Self::Service {
-        let session_wrapper_layer = SessionWrapperLayer::new();
-        let layers = (&self.inner, session_wrapper_layer);
+        if let Err(error) = self.validate() {
+            panic!("invalid session cookie configuration: {error}");
+        }
 
-        layers.layer(inner)
+        let session_wrapper_layer = SessionWrapperLayer {
+            store_error_policy: self.store_error_policy,
+        };
+        let inner = session_wrapper_layer.layer(inner);
+
+        match &self.cookie_store {
+            Some(codec) => tower::util::Either::Right(CookieSessionService::new(
+                inner,
+                codec.clone(),
+                self.cookie_config(),
+                self.store_error_policy,
+                Arc::clone(&self.clock),
+            )),
+            None => tower::util::Either::Left(self.session_manager_layer().layer(inner)),
+        }
     }
 }
 
@@ -335,7 +934,9 @@ impl<S> tower::Layer<S> for SessionMiddleware {
 /// [`crate::session::Session`] to the request handlers. This shouldn't be
 /// useful on its own.
 #[derive(Debug, Copy, Clone)]
-pub struct SessionWrapperLayer;
+pub struct SessionWrapperLayer {
+    store_error_policy: SessionStoreErrorPolicy,
+}
 
 impl SessionWrapperLayer {
     /// Create a new [`SessionWrapperLayer`].
@@ -349,7 +950,9 @@ impl SessionWrapperLayer {
     /// ```
     #[must_use]
     pub fn new() -> Self {
-        Self {}
+        Self {
+            store_error_policy: SessionStoreErrorPolicy::default(),
+        }
     }
 }
 
@@ -363,7 +966,10 @@ impl<S> tower::Layer<S> for SessionWrapperLayer {
     type Service = SessionWrapper<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        SessionWrapper { inner }
+        SessionWrapper {
+            inner,
+            store_error_policy: self.store_error_policy,
+        }
     }
 }
 
@@ -374,6 +980,7 @@ impl<S> tower::Layer<S> for SessionWrapperLayer {
 #[derive(Debug, Clone)]
 pub struct SessionWrapper<S> {
     inner: S,
+    store_error_policy: SessionStoreErrorPolicy,
 }
 
 impl<ReqBody, ResBody, S> Service<http::Request<ReqBody>> for SessionWrapper<S>
@@ -385,21 +992,40 @@ where
 {
     type Response = S::Response;
     type Error = S::Error;
-    type Future = S::Future;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         self.inner.poll_ready(cx)
     }
 
     fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
-        let session = req
-            .extensions_mut()
-            .remove::<tower_sessions::Session>()
-            .expect("session extension must be present");
-        let session_wrapped = crate::session::Session::new(session);
-        req.extensions_mut().insert(session_wrapped);
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let store_error_policy = self.store_error_policy;
 
-        self.inner.call(req)
+        Box::pin(async move {
+            let session = req
+                .extensions_mut()
+                .remove::<tower_sessions::Session>()
+                .expect("session extension must be present");
+
+            // Load the session eagerly, so that an unavailable store fails the
+            // request before it reaches the handler. With the degrade policy, the
+            // store never returns errors, so the session can be loaded lazily.
+            if store_error_policy == SessionStoreErrorPolicy::Fail && session.id().is_some() {
+                if let Err(error) = session.load().await {
+                    error!("Failed to load the session: {error}");
+                    let mut response = http::Response::<ResBody>::default();
+                    *response.status_mut() = http::StatusCode::SERVICE_UNAVAILABLE;
+                    return Ok(response);
+                }
+            }
+
+            let session_wrapped = crate::session::Session::new(session);
+            req.extensions_mut().insert(session_wrapped);
+
+            inner.call(req).await
+        })
     }
 }
 
@@ -668,7 +1294,8 @@ mod tests {
 
     use super::*;
     use crate::auth::Auth;
-    use crate::session::Session;
+    use crate::session::store::{Id, Record};
+    use crate::session::{Session, store};
     use crate::test::TestRequestBuilder;
 
     #[tokio::test]
@@ -736,6 +1363,388 @@ mod tests {
         assert!(!cookie_value.contains("Secure;"));
     }
 
+    #[tokio::test]
+    async fn session_middleware_cookie_attributes() {
+        let svc = tower::service_fn(|req: Request<Body>| async move {
+            let session = req.extensions().get::<Session>().unwrap();
+            session.insert("test", "test").await.unwrap();
+
+            Ok::<_, Error>(Response::new(Body::empty()))
+        });
+
+        let mut svc = SessionMiddleware::new()
+            .cookie_http_only(false)
+            .cookie_same_site(SameSite::Lax)
+            .cookie_max_age(Duration::from_secs(3600))
+            .layer(svc);
+
+        let request = TestRequestBuilder::get("/").build();
+
+        let response = svc.ready().await.unwrap().call(request).await.unwrap();
+        let cookie_value = response
+            .headers()
+            .get("set-cookie")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(!cookie_value.contains("HttpOnly"));
+        assert!(cookie_value.contains("SameSite=Lax"));
+        assert!(cookie_value.contains("Max-Age=3600"));
+    }
+
+    #[tokio::test]
+    async fn session_middleware_cookie_store_attributes() {
+        let mut svc = SessionMiddleware::new()
+            .signed_cookie_store(&SecretKey::from("secret"), &[])
+            .cookie_same_site(SameSite::Lax)
+            .cookie_max_age(Duration::from_secs(3600))
+            .layer(tower::service_fn(session_counter));
+
+        let response = call_with_cookie(&mut svc, None).await;
+        let cookie = set_cookie(&response).unwrap();
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.contains("SameSite=Lax"));
+        assert!(cookie.contains("Max-Age=3600"));
+    }
+
+    #[tokio::test]
+    async fn session_middleware_cookie_host_prefix() {
+        let svc = tower::service_fn(|req: Request<Body>| async move {
+            let session = req.extensions().get::<Session>().unwrap();
+            session.insert("test", "test").await.unwrap();
+
+            Ok::<_, Error>(Response::new(Body::empty()))
+        });
+
+        let mut svc = SessionMiddleware::new()
+            .cookie_name("session")
+            .cookie_prefix(CookiePrefix::Host)
+            .layer(svc);
+
+        let request = TestRequestBuilder::get("/").build();
+
+        let response = svc.ready().await.unwrap().call(request).await.unwrap();
+        let cookie_value = response
+            .headers()
+            .get("set-cookie")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(cookie_value.starts_with("__Host-session="));
+        assert!(cookie_value.contains("Secure;"));
+        assert!(cookie_value.contains("Path=/"));
+        assert!(!cookie_value.contains("Domain="));
+    }
+
+    #[tokio::test]
+    async fn session_middleware_cookie_secure_prefix_with_domain() {
+        let svc = tower::service_fn(|req: Request<Body>| async move {
+            let session = req.extensions().get::<Session>().unwrap();
+            session.insert("test", "test").await.unwrap();
+
+            Ok::<_, Error>(Response::new(Body::empty()))
+        });
+
+        let mut svc = SessionMiddleware::new()
+            .cookie_prefix(CookiePrefix::Secure)
+            .cookie_domain("example.com")
+            .cookie_path("/app")
+            .layer(svc);
+
+        let request = TestRequestBuilder::get("/").build();
+
+        let response = svc.ready().await.unwrap().call(request).await.unwrap();
+        let cookie_value = response
+            .headers()
+            .get("set-cookie")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(cookie_value.starts_with("__Secure-id="));
+        assert!(cookie_value.contains("Domain=example.com"));
+        assert!(cookie_value.contains("Path=/app"));
+    }
+
+    #[test]
+    fn session_middleware_cookie_prefix_validate() {
+        assert!(
+            SessionMiddleware::new()
+                .cookie_prefix(CookiePrefix::Host)
+                .validate()
+                .is_ok()
+        );
+        assert_eq!(
+            SessionMiddleware::new()
+                .cookie_prefix(CookiePrefix::Host)
+                .cookie_domain("example.com")
+                .validate(),
+            Err(CookiePrefixError::DomainSet {
+                domain: "example.com".to_owned()
+            })
+        );
+        assert_eq!(
+            SessionMiddleware::new()
+                .cookie_prefix(CookiePrefix::Host)
+                .cookie_path("/app")
+                .validate(),
+            Err(CookiePrefixError::InvalidPath {
+                path: "/app".to_owned()
+            })
+        );
+        assert_eq!(
+            SessionMiddleware::new()
+                .cookie_prefix(CookiePrefix::Secure)
+                .secure(false)
+                .validate(),
+            Err(CookiePrefixError::NotSecure {
+                prefix: CookiePrefix::Secure
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid session cookie configuration")]
+    fn session_middleware_cookie_prefix_invalid_panics() {
+        let svc = tower::service_fn(|_req: Request<Body>| async move {
+            Ok::<_, Error>(Response::new(Body::empty()))
+        });
+
+        let _ = SessionMiddleware::new()
+            .cookie_prefix(CookiePrefix::Host)
+            .secure(false)
+            .layer(svc);
+    }
+
+    #[derive(Debug)]
+    struct FailingStore;
+
+    #[async_trait::async_trait]
+    impl SessionStore for FailingStore {
+        async fn save(&self, _record: &Record) -> store::Result<()> {
+            Err(store::Error::Backend("store is down".to_owned()))
+        }
+
+        async fn load(&self, _session_id: &Id) -> store::Result<Option<Record>> {
+            Err(store::Error::Backend("store is down".to_owned()))
+        }
+
+        async fn delete(&self, _session_id: &Id) -> store::Result<()> {
+            Err(store::Error::Backend("store is down".to_owned()))
+        }
+    }
+
+    fn request_with_session_cookie() -> Request<Body> {
+        let mut request = TestRequestBuilder::get("/").build();
+        request.headers_mut().insert(
+            http::header::COOKIE,
+            format!("id={}", Id::default()).parse().unwrap(),
+        );
+        request
+    }
+
+    #[tokio::test]
+    async fn session_middleware_store_error_fail() {
+        let svc = tower::service_fn(|_req: Request<Body>| async move {
+            panic!("handler should not be called");
+            #[expect(unreachable_code)]
+            Ok::<_, Error>(Response::new(Body::empty()))
+        });
+
+        let mut svc = SessionMiddleware::new()
+            .store(FailingStore)
+            .store_error_policy(SessionStoreErrorPolicy::Fail)
+            .layer(svc);
+
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(request_with_session_cookie())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn session_middleware_store_error_degrade() {
+        let svc = tower::service_fn(|req: Request<Body>| async move {
+            let session = req.extensions().get::<Session>().unwrap();
+            assert_eq!(session.get::<String>("test").await?, None);
+            session.insert("test", "test").await?;
+            assert_eq!(
+                session.get::<String>("test").await?,
+                Some("test".to_owned())
+            );
+
+            Ok::<_, Error>(Response::new(Body::empty()))
+        });
+
+        let mut svc = SessionMiddleware::new()
+            .store(FailingStore)
+            .store_error_policy(SessionStoreErrorPolicy::Degrade)
+            .layer(svc);
+
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(request_with_session_cookie())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK);
+    }
+
+    async fn call_with_cookie<S>(svc: &mut S, cookie: Option<&str>) -> Response
+    where
+        S: Service<Request<Body>, Response = Response, Error = Error>,
+    {
+        let mut request = TestRequestBuilder::get("/").build();
+        if let Some(cookie) = cookie {
+            request
+                .headers_mut()
+                .insert(http::header::COOKIE, cookie.parse().unwrap());
+        }
+        svc.ready().await.unwrap().call(request).await.unwrap()
+    }
+
+    fn set_cookie(response: &Response) -> Option<&str> {
+        response
+            .headers()
+            .get(http::header::SET_COOKIE)
+            .map(|value| value.to_str().unwrap())
+    }
+
+    /// Increments the counter kept in the session, or deletes the session
+    /// when it reaches 2.
+    async fn session_counter(req: Request<Body>) -> crate::Result<Response> {
+        let session = req.extensions().get::<Session>().unwrap();
+        let counter = session.get::<u32>("counter").await?.unwrap_or_default();
+        if counter == 2 {
+            session.flush().await?;
+        } else {
+            session.insert("counter", counter + 1).await?;
+        }
+
+        Ok(Response::new(Body::fixed(counter.to_string())))
+    }
+
+    #[tokio::test]
+    async fn session_middleware_signed_cookie_store() {
+        let mut svc = SessionMiddleware::new()
+            .signed_cookie_store(&SecretKey::from("secret"), &[])
+            .layer(tower::service_fn(session_counter));
+
+        let response = call_with_cookie(&mut svc, None).await;
+        let cookie = set_cookie(&response).unwrap().to_owned();
+        assert!(cookie.starts_with("id="));
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.contains("SameSite=Strict"));
+        assert!(cookie.contains("Secure"));
+        let cookie = cookie.split(';').next().unwrap();
+
+        let response = call_with_cookie(&mut svc, Some(cookie)).await;
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "1");
+
+        // the same cookie can be sent again, since the data lives in it
+        let response = call_with_cookie(&mut svc, Some(cookie)).await;
+        let cookie = set_cookie(&response).unwrap().to_owned();
+        let cookie = cookie.split(';').next().unwrap();
+
+        let response = call_with_cookie(&mut svc, Some(cookie)).await;
+        assert!(set_cookie(&response).unwrap().contains("Max-Age=0"));
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "2");
+    }
+
+    #[tokio::test]
+    async fn session_middleware_cookie_store_expired() {
+        let clock = crate::test::TestClock::new();
+        let mut svc = SessionMiddleware::new()
+            .signed_cookie_store(&SecretKey::from("secret"), &[])
+            .cookie_max_age(Duration::from_secs(3600))
+            .clock(Arc::new(clock.clone()))
+            .layer(tower::service_fn(session_counter));
+
+        let response = call_with_cookie(&mut svc, None).await;
+        let cookie = set_cookie(&response).unwrap().to_owned();
+        let cookie = cookie.split(';').next().unwrap();
+
+        let response = call_with_cookie(&mut svc, Some(cookie)).await;
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "1");
+
+        clock.advance(Duration::from_secs(2 * 3600));
+        let response = call_with_cookie(&mut svc, Some(cookie)).await;
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "0");
+    }
+
+    #[tokio::test]
+    async fn session_middleware_signed_cookie_store_tampered() {
+        let mut svc = SessionMiddleware::new()
+            .signed_cookie_store(&SecretKey::from("secret"), &[])
+            .layer(tower::service_fn(session_counter));
+        let mut other_svc = SessionMiddleware::new()
+            .signed_cookie_store(&SecretKey::from("other"), &[])
+            .layer(tower::service_fn(session_counter));
+
+        let response = call_with_cookie(&mut other_svc, None).await;
+        let cookie = set_cookie(&response).unwrap().to_owned();
+        let cookie = cookie.split(';').next().unwrap();
+
+        let response = call_with_cookie(&mut svc, Some(cookie)).await;
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "0");
+    }
+
+    #[tokio::test]
+    async fn session_middleware_encrypted_cookie_store_key_rotation() {
+        let mut old_svc = SessionMiddleware::new()
+            .encrypted_cookie_store(&SecretKey::from("old"), &[])
+            .layer(tower::service_fn(session_counter));
+        let mut svc = SessionMiddleware::new()
+            .encrypted_cookie_store(&SecretKey::from("new"), &[SecretKey::from("old")])
+            .layer(tower::service_fn(|req: Request<Body>| async move {
+                let session = req.extensions().get::<Session>().unwrap();
+                let counter = session.get::<u32>("counter").await?.unwrap_or_default();
+                Ok::<_, Error>(Response::new(Body::fixed(counter.to_string())))
+            }));
+
+        let response = call_with_cookie(&mut old_svc, None).await;
+        let cookie = set_cookie(&response).unwrap().to_owned();
+        let cookie = cookie.split(';').next().unwrap();
+
+        // the session isn't modified, but it's encrypted again with the new key
+        let response = call_with_cookie(&mut svc, Some(cookie)).await;
+        let new_cookie = set_cookie(&response).unwrap().to_owned();
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "1");
+        let new_cookie = new_cookie.split(';').next().unwrap();
+        assert_ne!(new_cookie, cookie);
+
+        let response = call_with_cookie(&mut svc, Some(new_cookie)).await;
+        assert!(set_cookie(&response).is_none());
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "1");
+    }
+
+    #[tokio::test]
+    async fn session_middleware_cookie_store_too_large() {
+        let handler = |req: Request<Body>| async move {
+            let session = req.extensions().get::<Session>().unwrap();
+            session.insert("data", "a".repeat(8192)).await?;
+            Ok::<_, Error>(Response::new(Body::empty()))
+        };
+
+        let mut svc = SessionMiddleware::new()
+            .signed_cookie_store(&SecretKey::from("secret"), &[])
+            .layer(tower::service_fn(handler));
+        let response = call_with_cookie(&mut svc, None).await;
+        assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+
+        let mut svc = SessionMiddleware::new()
+            .signed_cookie_store(&SecretKey::from("secret"), &[])
+            .store_error_policy(SessionStoreErrorPolicy::Degrade)
+            .layer(tower::service_fn(handler));
+        let response = call_with_cookie(&mut svc, None).await;
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert!(set_cookie(&response).is_none());
+    }
+
     #[tokio::test]
     async fn auth_middleware_adds_auth() {
         let svc = tower::service_fn(|req: Request<Body>| async move {
@@ -0,0 +1,392 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::future::BoxFuture;
+use http::StatusCode;
+use http_body::{Frame, SizeHint};
+use http_body_util::combinators::BoxBody;
+use tower::Service;
+
+use crate::config::DEFAULT_MAX_BUFFERED_BODY_SIZE;
+use crate::error::ErrorRepr;
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::{Response, ResponseExt};
+use crate::{Body, Error};
+
+/// A middleware that limits the size of the request bodies.
+///
+/// The limit is set globally with [`Self::new`] or with the
+/// `[middlewares.body_limit]` section of the config (see
+/// [`BodyLimitMiddlewareConfig`](crate::config::BodyLimitMiddlewareConfig)),
+/// and can be overridden for specific routes with
+/// [`Route::body_limit`](crate::router::Route::body_limit). The limit
+/// applies to everything that reads the body, including the extractors, so
+/// a handler never buffers more than the limit of its route.
+///
+/// Requests with a body exceeding the limit are rejected with
+/// `413 Payload Too Large`. When the request has a `Content-Length` header,
+/// this happens as soon as the body is read for the first time, without
+/// reading any of it.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::BodyLimitMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(BodyLimitMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct BodyLimitMiddleware {
+    max_bytes: usize,
+}
+
+impl BodyLimitMiddleware {
+    /// Creates a new instance of [`BodyLimitMiddleware`] limiting the request
+    /// bodies to `max_bytes` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BodyLimitMiddleware;
+    ///
+    /// let middleware = BodyLimitMiddleware::new(64 * 1024);
+    /// ```
+    #[must_use]
+    pub fn new(max_bytes: usize) -> Self {
+        Self { max_bytes }
+    }
+
+    /// Creates a new instance of [`BodyLimitMiddleware`] from the application
+    /// context, using the limit set in the `[middlewares.body_limit]`
+    /// section of the config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BodyLimitMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(BodyLimitMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::new(context.config().middlewares.body_limit.max_bytes)
+    }
+}
+
+impl<S> tower::Layer<S> for BodyLimitMiddleware {
+    type Service = BodyLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyLimitService {
+            inner,
+            max_bytes: self.max_bytes,
+        }
+    }
+}
+
+/// Service that limits the size of the request bodies.
+///
+/// Used by [`BodyLimitMiddleware`].
+#[derive(Debug, Clone)]
+pub struct BodyLimitService<S> {
+    inner: S,
+    max_bytes: usize,
+}
+
+impl<S> Service<Request> for BodyLimitService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        limit_body(&mut req, BodyLimit::new(self.max_bytes));
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move { reject_too_large(inner.call(req).await) })
+    }
+}
+
+/// The body size limit of a request, shared between the [`LimitedBody`] and
+/// the request extensions, so that the router can change it once the route
+/// is known.
+#[derive(Debug, Clone)]
+struct BodyLimit(Arc<AtomicUsize>);
+
+impl BodyLimit {
+    fn new(max_bytes: usize) -> Self {
+        Self(Arc::new(AtomicUsize::new(max_bytes)))
+    }
+
+    fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, max_bytes: usize) {
+        self.0.store(max_bytes, Ordering::Relaxed);
+    }
+}
+
+/// Returns the maximum size of the body of the request, in bytes.
+///
+/// This is the limit used when buffering the body in memory, e.g. with
+/// [`RequestBodyExt::buffered`](crate::request::RequestBodyExt::buffered).
+/// The limit of the route set with
+/// [`Route::body_limit`](crate::router::Route::body_limit) takes precedence,
+/// then the limit of [`BodyLimitMiddleware`] if it's used, and finally the
+/// [`max_buffered_body_size`](crate::config::ServerConfig::max_buffered_body_size)
+/// server configuration option.
+pub(crate) fn request_body_limit(request: &Request) -> usize {
+    if let Some(limit) = request.extensions().get::<BodyLimit>() {
+        return limit.get();
+    }
+
+    request
+        .extensions()
+        .get::<Arc<crate::ProjectContext>>()
+        .map_or(DEFAULT_MAX_BUFFERED_BODY_SIZE, |context| {
+            context.config().server.max_buffered_body_size
+        })
+}
+
+/// Limits the size of the body of the request to [`request_body_limit`],
+/// unless it's already limited by [`BodyLimitMiddleware`] or by the route.
+pub(crate) fn ensure_body_limit(request: &mut Request) {
+    if request.extensions().get::<BodyLimit>().is_none() {
+        let max_bytes = request_body_limit(request);
+        limit_body(request, BodyLimit::new(max_bytes));
+    }
+}
+
+/// Overrides the body size limit of the request with the limit of the route
+/// it has been routed to.
+///
+/// If the body isn't limited yet (i.e. [`BodyLimitMiddleware`] is not used),
+/// it gets limited here.
+pub(crate) fn override_body_limit(request: &mut Request, max_bytes: usize) {
+    if let Some(limit) = request.extensions().get::<BodyLimit>() {
+        limit.set(max_bytes);
+    } else {
+        limit_body(request, BodyLimit::new(max_bytes));
+    }
+}
+
+fn limit_body(request: &mut Request, limit: BodyLimit) {
+    let body = std::mem::take(request.body_mut());
+    *request.body_mut() = Body::wrapper(BoxBody::new(LimitedBody {
+        inner: body,
+        limit: limit.clone(),
+        read: 0,
+    }));
+    request.extensions_mut().insert(limit);
+}
+
+/// Turns the errors caused by a request body exceeding its limit into
+/// `413 Payload Too Large` responses.
+pub(crate) fn reject_too_large(result: crate::Result<Response>) -> crate::Result<Response> {
+    match result {
+        Err(error) if is_body_too_large(&error) => Ok(Response::builder()
+            .status(StatusCode::PAYLOAD_TOO_LARGE)
+            .body(Body::fixed("Payload Too Large"))
+            .expect("failed to build body limit error response")),
+        result => result,
+    }
+}
+
+fn is_body_too_large(error: &Error) -> bool {
+    let mut current: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(error) = current {
+        if let Some(error) = error.downcast_ref::<Error>() {
+            if matches!(error.inner, ErrorRepr::BodyTooLarge { .. }) {
+                return true;
+            }
+        }
+        current = error.source();
+    }
+
+    false
+}
+
+struct LimitedBody {
+    inner: Body,
+    limit: BodyLimit,
+    read: usize,
+}
+
+impl LimitedBody {
+    fn too_large(&self) -> Error {
+        Error::new(ErrorRepr::BodyTooLarge {
+            limit: self.limit.get(),
+        })
+    }
+}
+
+impl http_body::Body for LimitedBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let max_bytes = this.limit.get();
+
+        // fail early if the size of the body is known upfront
+        let remaining = usize::try_from(this.inner.size_hint().lower()).unwrap_or(usize::MAX);
+        if this.read.saturating_add(remaining) > max_bytes {
+            return Poll::Ready(Some(Err(this.too_large())));
+        }
+
+        match Pin::new(&mut this.inner).poll_frame(cx) {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let Some(data) = frame.data_ref() {
+                    this.read = this.read.saturating_add(data.len());
+                    if this.read > max_bytes {
+                        return Poll::Ready(Some(Err(this.too_large())));
+                    }
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            poll => poll,
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::stream;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    async fn read_body(request: Request) -> crate::Result<Response> {
+        let body = request.into_body().into_bytes().await?;
+        Ok(Response::new_html(StatusCode::OK, Body::fixed(body)))
+    }
+
+    fn request(body: Body) -> Request {
+        let mut request = TestRequestBuilder::post("/").build();
+        *request.body_mut() = body;
+        request
+    }
+
+    #[cot::test]
+    async fn body_within_limit() {
+        let service = BodyLimitMiddleware::new(16).layer(tower::service_fn(read_body));
+
+        let response = service
+            .oneshot(request(Body::fixed("hello")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "hello");
+    }
+
+    #[cot::test]
+    async fn body_exceeding_limit() {
+        let service = BodyLimitMiddleware::new(4).layer(tower::service_fn(read_body));
+
+        let response = service
+            .oneshot(request(Body::fixed("hello")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cot::test]
+    async fn streaming_body_exceeding_limit() {
+        let body = Body::streaming(stream::iter([
+            Ok(Bytes::from("hel")),
+            Ok(Bytes::from("lo")),
+        ]));
+        let service = BodyLimitMiddleware::new(4).layer(tower::service_fn(read_body));
+
+        let response = service.oneshot(request(body)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cot::test]
+    async fn override_raises_limit() {
+        let service = BodyLimitMiddleware::new(4).layer(tower::service_fn(|mut req: Request| {
+            override_body_limit(&mut req, 16);
+            read_body(req)
+        }));
+
+        let response = service
+            .oneshot(request(Body::fixed("hello")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn override_without_middleware() {
+        let mut req = request(Body::fixed("hello"));
+        override_body_limit(&mut req, 4);
+
+        let response = reject_too_large(read_body(req).await).unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cot::test]
+    async fn other_errors_are_kept() {
+        let result = reject_too_large(Err(Error::custom("test")));
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,947 @@
+use std::io::Write;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use bytes::Bytes;
+use futures_core::future::BoxFuture;
+use http::{HeaderMap, HeaderValue, Method, StatusCode, header};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use http_body_util::combinators::BoxBody;
+use tower::Service;
+
+use crate::config::{CompressionLevel, CompressionMiddlewareConfig};
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::Response;
+use crate::{Body, Error};
+
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_WINDOW_SIZE: u32 = 22;
+
+/// A middleware that compresses the response bodies with the best encoding
+/// accepted by the client.
+///
+/// The `br` (Brotli), `zstd` (Zstandard), `gzip` and `deflate` encodings are
+/// supported, and the one is chosen based on the `Accept-Encoding` request header (including its
+/// quality values). The bodies are compressed on the fly as they are sent, so
+/// streaming responses are compressed too, without being buffered first.
+///
+/// The responses that are not worth compressing are sent unchanged: the ones
+/// with a body smaller than the minimum size (1 kibibyte by default, see
+/// [`Self::min_size`]), the ones with a content type that is already
+/// compressed (such as images, videos or archives), the ones without a
+/// `Content-Type`, and the ones that are already encoded.
+///
+/// The minimum size and the compression level can be set with the builder
+/// methods or in the `[middlewares.compression]` section of the config (see
+/// [`CompressionMiddlewareConfig`]).
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::CompressionMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(CompressionMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct CompressionMiddleware {
+    min_size: usize,
+    level: CompressionLevel,
+}
+
+impl CompressionMiddleware {
+    /// Creates a new instance of [`CompressionMiddleware`] with the default
+    /// settings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CompressionMiddleware;
+    ///
+    /// let middleware = CompressionMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_config(&CompressionMiddlewareConfig::default())
+    }
+
+    /// Creates a new instance of [`CompressionMiddleware`] using the
+    /// `[middlewares.compression]` section of the project config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CompressionMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(CompressionMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::from_config(&context.config().middlewares.compression)
+    }
+
+    fn from_config(config: &CompressionMiddlewareConfig) -> Self {
+        Self {
+            min_size: config.min_size,
+            level: config.level,
+        }
+    }
+
+    /// Sets the minimum size of the response body to compress, in bytes.
+    ///
+    /// The responses with a body of unknown size, such as the streaming ones,
+    /// are always compressed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CompressionMiddleware;
+    ///
+    /// let middleware = CompressionMiddleware::new().min_size(256);
+    /// ```
+    #[must_use]
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Sets the compression level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::CompressionLevel;
+    /// use cot::middleware::CompressionMiddleware;
+    ///
+    /// let middleware = CompressionMiddleware::new().level(CompressionLevel::Best);
+    /// ```
+    #[must_use]
+    pub fn level(mut self, level: CompressionLevel) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl Default for CompressionMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for CompressionMiddleware {
+    type Service = CompressionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CompressionService {
+            inner,
+            min_size: self.min_size,
+            level: self.level,
+        }
+    }
+}
+
+/// Service that compresses the response body.
+///
+/// Used by [`CompressionMiddleware`].
+#[derive(Debug, Clone)]
+pub struct CompressionService<S> {
+    inner: S,
+    min_size: usize,
+    level: CompressionLevel,
+}
+
+impl<S> Service<Request> for CompressionService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let min_size = self.min_size;
+        let level = self.level;
+
+        let encoding = if req.method() == Method::HEAD {
+            None
+        } else {
+            negotiate_encoding(req.headers())
+        };
+
+        Box::pin(async move {
+            let response = inner.call(req).await?;
+            Ok(compress_response(response, encoding, min_size, level))
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Encoding {
+    Brotli,
+    Zstd,
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    /// The supported encodings, in the order of preference when the client
+    /// accepts several of them equally.
+    const PREFERENCE: [Self; 4] = [Self::Brotli, Self::Zstd, Self::Gzip, Self::Deflate];
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "br" => Some(Self::Brotli),
+            "zstd" => Some(Self::Zstd),
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            "deflate" => Some(Self::Deflate),
+            _ => None,
+        }
+    }
+
+    fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(match self {
+            Self::Brotli => "br",
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+        })
+    }
+
+    fn encoder(self, level: CompressionLevel) -> std::io::Result<Encoder> {
+        let flate_level = match level {
+            CompressionLevel::Fastest => flate2::Compression::fast(),
+            CompressionLevel::Default => flate2::Compression::default(),
+            CompressionLevel::Best => flate2::Compression::best(),
+        };
+        let brotli_quality = match level {
+            CompressionLevel::Fastest => 1,
+            CompressionLevel::Default => 4,
+            CompressionLevel::Best => 11,
+        };
+        let zstd_level = match level {
+            CompressionLevel::Fastest => 1,
+            CompressionLevel::Default => zstd::DEFAULT_COMPRESSION_LEVEL,
+            CompressionLevel::Best => 19,
+        };
+
+        Ok(match self {
+            Self::Brotli => Encoder::Brotli(Box::new(brotli::CompressorWriter::new(
+                Vec::new(),
+                BROTLI_BUFFER_SIZE,
+                brotli_quality,
+                BROTLI_WINDOW_SIZE,
+            ))),
+            Self::Zstd => Encoder::Zstd(zstd::stream::write::Encoder::new(Vec::new(), zstd_level)?),
+            Self::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(Vec::new(), flate_level)),
+            // "deflate" in HTTP actually means the zlib format
+            Self::Deflate => {
+                Encoder::Deflate(flate2::write::ZlibEncoder::new(Vec::new(), flate_level))
+            }
+        })
+    }
+}
+
+/// Chooses the encoding to compress the response with, based on the
+/// `Accept-Encoding` request header.
+fn negotiate_encoding(headers: &HeaderMap) -> Option<Encoding> {
+    let mut qualities = [None; Encoding::PREFERENCE.len()];
+    let mut wildcard = None;
+
+    for value in headers.get_all(header::ACCEPT_ENCODING) {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+
+        for item in value.split(',') {
+            let mut params = item.split(';');
+            let name = params.next().unwrap_or_default().trim();
+            let Some(quality) = parse_quality(params) else {
+                continue;
+            };
+
+            if name == "*" {
+                wildcard = Some(quality);
+            } else if let Some(encoding) = Encoding::from_name(name) {
+                let index = Encoding::PREFERENCE
+                    .iter()
+                    .position(|&preferred| preferred == encoding)
+                    .expect("all encodings are in the preference list");
+                qualities[index] = Some(quality);
+            }
+        }
+    }
+
+    let mut best: Option<(Encoding, u16)> = None;
+    for (encoding, quality) in Encoding::PREFERENCE.into_iter().zip(qualities) {
+        let quality = quality.or(wildcard).unwrap_or(0);
+        if quality > 0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+            best = Some((encoding, quality));
+        }
+    }
+
+    best.map(|(encoding, _)| encoding)
+}
+
+/// Parses the quality value from the parameters of an `Accept-Encoding` item,
+/// in thousandths. Returns `None` if the quality value is invalid.
+fn parse_quality<'a>(params: impl Iterator<Item = &'a str>) -> Option<u16> {
+    for param in params {
+        let Some((name, value)) = param.split_once('=') else {
+            continue;
+        };
+        if name.trim().eq_ignore_ascii_case("q") {
+            let quality: f32 = value.trim().parse().ok()?;
+            if !(0.0..=1.0).contains(&quality) {
+                return None;
+            }
+            #[expect(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                reason = "the quality is between 0 and 1"
+            )]
+            return Some((quality * 1000.0).round() as u16);
+        }
+    }
+
+    Some(1000)
+}
+
+fn compress_response(
+    mut response: Response,
+    encoding: Option<Encoding>,
+    min_size: usize,
+    level: CompressionLevel,
+) -> Response {
+    if !is_compressible(&response) {
+        return response;
+    }
+    add_vary_accept_encoding(response.headers_mut());
+
+    let Some(encoding) = encoding else {
+        return response;
+    };
+    let size = response.body().size_hint().exact().or_else(|| {
+        response
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    });
+    let min_size = u64::try_from(min_size).unwrap_or(u64::MAX);
+    if size.is_some_and(|size| size < min_size) {
+        return response;
+    }
+    let Ok(encoder) = encoding.encoder(level) else {
+        return response;
+    };
+
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_ENCODING, encoding.header_value());
+    headers.remove(header::CONTENT_LENGTH);
+    headers.remove(header::ACCEPT_RANGES);
+    // the compressed body is not byte-for-byte identical to the original one
+    if let Some(etag) = headers.get(header::ETAG) {
+        if !etag.as_bytes().starts_with(b"W/") {
+            let mut weak = b"W/".to_vec();
+            weak.extend_from_slice(etag.as_bytes());
+            if let Ok(weak) = HeaderValue::from_bytes(&weak) {
+                headers.insert(header::ETAG, weak);
+            }
+        }
+    }
+
+    response.map(|body| {
+        Body::wrapper(BoxBody::new(CompressedBody {
+            inner: body,
+            encoder: Some(encoder),
+            trailers: None,
+        }))
+    })
+}
+
+/// Returns whether the response can be compressed, regardless of the
+/// encodings accepted by the client.
+fn is_compressible(response: &Response) -> bool {
+    let status = response.status();
+    if status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+        || status == StatusCode::PARTIAL_CONTENT
+    {
+        return false;
+    }
+
+    let headers = response.headers();
+    if headers.contains_key(header::CONTENT_ENCODING) || headers.contains_key(header::CONTENT_RANGE)
+    {
+        return false;
+    }
+    let no_transform = headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"));
+    if no_transform {
+        return false;
+    }
+
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(is_compressible_content_type)
+}
+
+fn is_compressible_content_type(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    if essence == "image/svg+xml" {
+        return true;
+    }
+    let already_compressed = essence.starts_with("image/")
+        || essence.starts_with("audio/")
+        || essence.starts_with("video/")
+        || essence.starts_with("font/woff")
+        || matches!(
+            essence.as_str(),
+            "application/gzip"
+                | "application/x-gzip"
+                | "application/zip"
+                | "application/zstd"
+                | "application/x-brotli"
+                | "application/x-bzip2"
+                | "application/x-7z-compressed"
+                | "application/x-rar-compressed"
+                | "application/octet-stream"
+                | "application/pdf"
+        );
+    // compressing the event streams would delay the events until the
+    // compressor decides to flush its output
+    !already_compressed && essence != "text/event-stream"
+}
+
+fn add_vary_accept_encoding(headers: &mut HeaderMap) {
+    let already_varies = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|name| name == "*" || name.eq_ignore_ascii_case("accept-encoding"));
+
+    if !already_varies {
+        headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+}
+
+enum Encoder {
+    Brotli(Box<brotli::CompressorWriter<Vec<u8>>>),
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+    Deflate(flate2::write::ZlibEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    /// Compresses a chunk of the body, returning all the compressed data
+    /// produced so far, so that the client can decompress the body up to this
+    /// chunk.
+    fn compress(&mut self, data: &[u8]) -> std::io::Result<Bytes> {
+        match self {
+            Self::Brotli(encoder) => {
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(Bytes::from(std::mem::take(encoder.get_mut())))
+            }
+            Self::Zstd(encoder) => {
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(Bytes::from(std::mem::take(encoder.get_mut())))
+            }
+            Self::Gzip(encoder) => {
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(Bytes::from(std::mem::take(encoder.get_mut())))
+            }
+            Self::Deflate(encoder) => {
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(Bytes::from(std::mem::take(encoder.get_mut())))
+            }
+        }
+    }
+
+    /// Finishes the compressed stream, returning the remaining compressed
+    /// data.
+    fn finish(self) -> std::io::Result<Bytes> {
+        match self {
+            Self::Brotli(encoder) => Ok(Bytes::from(encoder.into_inner())),
+            Self::Zstd(encoder) => encoder.finish().map(Bytes::from),
+            Self::Gzip(encoder) => encoder.finish().map(Bytes::from),
+            Self::Deflate(encoder) => encoder.finish().map(Bytes::from),
+        }
+    }
+}
+
+/// A body compressing the frames of the inner body as they are polled.
+struct CompressedBody {
+    inner: Body,
+    encoder: Option<Encoder>,
+    trailers: Option<HeaderMap>,
+}
+
+impl CompressedBody {
+    fn finish(&mut self) -> Option<Result<Frame<Bytes>, Error>> {
+        let encoder = self.encoder.take()?;
+        match encoder.finish() {
+            Ok(data) if data.is_empty() => None,
+            Ok(data) => Some(Ok(Frame::data(data))),
+            Err(error) => Some(Err(Error::custom(error))),
+        }
+    }
+}
+
+impl HttpBody for CompressedBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+
+        loop {
+            let Some(encoder) = this.encoder.as_mut() else {
+                return Poll::Ready(
+                    this.trailers
+                        .take()
+                        .map(|trailers| Ok(Frame::trailers(trailers))),
+                );
+            };
+
+            match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => match encoder.compress(&data) {
+                        Ok(compressed) if compressed.is_empty() => {}
+                        Ok(compressed) => return Poll::Ready(Some(Ok(Frame::data(compressed)))),
+                        Err(error) => {
+                            this.encoder = None;
+                            return Poll::Ready(Some(Err(Error::custom(error))));
+                        }
+                    },
+                    Err(frame) => {
+                        // the compressed stream has to end before the trailers
+                        this.trailers = frame.into_trailers().ok();
+                        if let Some(frame) = this.finish() {
+                            return Poll::Ready(Some(frame));
+                        }
+                    }
+                },
+                Some(Err(error)) => {
+                    this.encoder = None;
+                    return Poll::Ready(Some(Err(error)));
+                }
+                None => {
+                    if let Some(frame) = this.finish() {
+                        return Poll::Ready(Some(frame));
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.encoder.is_none() && self.trailers.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use http_body_util::BodyExt;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    const TEXT: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ";
+
+    fn long_text() -> String {
+        TEXT.repeat(100)
+    }
+
+    fn decompress(encoding: &str, data: &[u8]) -> String {
+        let mut decoder: Box<dyn Read + '_> = match encoding {
+            "br" => Box::new(brotli::Decompressor::new(data, 4096)),
+            "zstd" => Box::new(zstd::stream::read::Decoder::new(data).unwrap()),
+            "gzip" => Box::new(flate2::read::GzDecoder::new(data)),
+            "deflate" => Box::new(flate2::read::ZlibDecoder::new(data)),
+            _ => panic!("unexpected encoding: {encoding}"),
+        };
+        let mut output = String::new();
+        decoder.read_to_string(&mut output).unwrap();
+        output
+    }
+
+    fn request(accept_encoding: Option<&str>) -> Request {
+        let mut request = TestRequestBuilder::get("/").build();
+        if let Some(accept_encoding) = accept_encoding {
+            request
+                .headers_mut()
+                .insert(header::ACCEPT_ENCODING, accept_encoding.parse().unwrap());
+        }
+        request
+    }
+
+    async fn respond(
+        middleware: CompressionMiddleware,
+        request: Request,
+        response: fn() -> Response,
+    ) -> Response {
+        let svc = tower::service_fn(move |_req: Request| async move { Ok::<_, Error>(response()) });
+
+        middleware.layer(svc).oneshot(request).await.unwrap()
+    }
+
+    fn text_response() -> Response {
+        http::Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .header(header::CONTENT_LENGTH, long_text().len())
+            .body(Body::fixed(long_text()))
+            .unwrap()
+    }
+
+    async fn assert_compressed(response: Response, encoding: &str) {
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], encoding);
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        assert!(!response.headers().contains_key(header::CONTENT_LENGTH));
+
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert!(body.len() < long_text().len());
+        assert_eq!(decompress(encoding, &body), long_text());
+    }
+
+    #[cot::test]
+    async fn compress_gzip() {
+        let response = respond(
+            CompressionMiddleware::new(),
+            request(Some("gzip")),
+            text_response,
+        )
+        .await;
+
+        assert_compressed(response, "gzip").await;
+    }
+
+    #[cot::test]
+    async fn compress_deflate() {
+        let response = respond(
+            CompressionMiddleware::new(),
+            request(Some("deflate")),
+            text_response,
+        )
+        .await;
+
+        assert_compressed(response, "deflate").await;
+    }
+
+    #[cot::test]
+    async fn compress_brotli() {
+        let response = respond(
+            CompressionMiddleware::new().level(CompressionLevel::Best),
+            request(Some("gzip, deflate, br")),
+            text_response,
+        )
+        .await;
+
+        assert_compressed(response, "br").await;
+    }
+
+    #[cot::test]
+    async fn compress_zstd() {
+        let response = respond(
+            CompressionMiddleware::new(),
+            request(Some("gzip, deflate, zstd")),
+            text_response,
+        )
+        .await;
+
+        assert_compressed(response, "zstd").await;
+    }
+
+    #[cot::test]
+    async fn compress_no_accept_encoding() {
+        let response = respond(CompressionMiddleware::new(), request(None), text_response).await;
+
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            long_text()
+        );
+    }
+
+    #[cot::test]
+    async fn compress_head_request() {
+        let mut request = request(Some("gzip"));
+        *request.method_mut() = Method::HEAD;
+
+        let response = respond(CompressionMiddleware::new(), request, text_response).await;
+
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        assert!(response.headers().contains_key(header::CONTENT_LENGTH));
+    }
+
+    #[cot::test]
+    async fn compress_small_body() {
+        let response = respond(CompressionMiddleware::new(), request(Some("gzip")), || {
+            http::Response::builder()
+                .header(header::CONTENT_TYPE, "text/plain")
+                .body(Body::fixed("small"))
+                .unwrap()
+        })
+        .await;
+
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "small");
+    }
+
+    #[cot::test]
+    async fn compress_small_body_min_size() {
+        let response = respond(
+            CompressionMiddleware::new().min_size(0),
+            request(Some("gzip")),
+            || {
+                http::Response::builder()
+                    .header(header::CONTENT_TYPE, "text/plain")
+                    .body(Body::fixed("small"))
+                    .unwrap()
+            },
+        )
+        .await;
+
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert_eq!(decompress("gzip", &body), "small");
+    }
+
+    #[cot::test]
+    async fn compress_already_compressed_content_type() {
+        let response = respond(CompressionMiddleware::new(), request(Some("gzip")), || {
+            http::Response::builder()
+                .header(header::CONTENT_TYPE, "image/png")
+                .body(Body::fixed(long_text()))
+                .unwrap()
+        })
+        .await;
+
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        assert!(!response.headers().contains_key(header::VARY));
+    }
+
+    #[cot::test]
+    async fn compress_already_encoded() {
+        let response = respond(CompressionMiddleware::new(), request(Some("gzip")), || {
+            http::Response::builder()
+                .header(header::CONTENT_TYPE, "text/plain")
+                .header(header::CONTENT_ENCODING, "br")
+                .body(Body::fixed(long_text()))
+                .unwrap()
+        })
+        .await;
+
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "br");
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            long_text()
+        );
+    }
+
+    #[cot::test]
+    async fn compress_no_content() {
+        let response = respond(CompressionMiddleware::new(), request(Some("gzip")), || {
+            http::Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .header(header::CONTENT_TYPE, "text/plain")
+                .body(Body::empty())
+                .unwrap()
+        })
+        .await;
+
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    }
+
+    #[cot::test]
+    async fn compress_weakens_etag() {
+        let response = respond(CompressionMiddleware::new(), request(Some("gzip")), || {
+            let mut response = text_response();
+            response
+                .headers_mut()
+                .insert(header::ETAG, HeaderValue::from_static("\"abc\""));
+            response
+        })
+        .await;
+
+        assert_eq!(response.headers()[header::ETAG], "W/\"abc\"");
+    }
+
+    #[cot::test]
+    async fn compress_keeps_existing_vary() {
+        let response = respond(CompressionMiddleware::new(), request(Some("gzip")), || {
+            let mut response = text_response();
+            response
+                .headers_mut()
+                .insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+            response
+        })
+        .await;
+
+        let vary: Vec<_> = response.headers().get_all(header::VARY).iter().collect();
+        assert_eq!(vary, ["Accept-Encoding"]);
+    }
+
+    #[cot::test]
+    async fn compress_streaming_body() {
+        let response = respond(CompressionMiddleware::new(), request(Some("gzip")), || {
+            let chunks = (0..100).map(|_| Ok(Bytes::from_static(TEXT.as_bytes())));
+            http::Response::builder()
+                .header(header::CONTENT_TYPE, "text/plain")
+                .body(Body::streaming(futures::stream::iter(chunks)))
+                .unwrap()
+        })
+        .await;
+
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        let mut body = response.into_body();
+        let mut compressed = Vec::new();
+        let mut frames = 0;
+        while let Some(frame) = body.frame().await {
+            compressed.extend_from_slice(&frame.unwrap().into_data().unwrap());
+            frames += 1;
+        }
+        // each chunk is compressed as soon as it's received
+        assert!(frames > 1);
+        assert_eq!(decompress("gzip", &compressed), long_text());
+    }
+
+    #[cot::test]
+    async fn compress_keeps_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", HeaderValue::from_static("abc"));
+        let mut body = CompressedBody {
+            inner: Body::wrapper(BoxBody::new(http_body_util::StreamBody::new(
+                futures::stream::iter([
+                    Ok::<_, Error>(Frame::data(Bytes::from_static(b"hello"))),
+                    Ok(Frame::trailers(trailers.clone())),
+                ]),
+            ))),
+            encoder: Some(Encoding::Gzip.encoder(CompressionLevel::Default).unwrap()),
+            trailers: None,
+        };
+
+        let mut compressed = Vec::new();
+        let mut received_trailers = None;
+        while let Some(frame) = body.frame().await {
+            let frame = frame.unwrap();
+            assert!(received_trailers.is_none());
+            match frame.into_data() {
+                Ok(data) => compressed.extend_from_slice(&data),
+                Err(frame) => received_trailers = frame.into_trailers().ok(),
+            }
+        }
+
+        assert_eq!(decompress("gzip", &compressed), "hello");
+        assert_eq!(received_trailers, Some(trailers));
+        assert!(body.is_end_stream());
+    }
+
+    fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, accept_encoding.parse().unwrap());
+        negotiate_encoding(&headers)
+    }
+
+    #[test]
+    fn negotiate_encoding_preference() {
+        assert_eq!(negotiate("gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("deflate, gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("gzip, deflate, br"), Some(Encoding::Brotli));
+        assert_eq!(negotiate("*"), Some(Encoding::Brotli));
+        assert_eq!(negotiate("identity"), None);
+        assert_eq!(negotiate("zstd"), Some(Encoding::Zstd));
+        assert_eq!(negotiate("gzip, zstd"), Some(Encoding::Zstd));
+        assert_eq!(negotiate("gzip, deflate, br, zstd"), Some(Encoding::Brotli));
+        assert_eq!(negotiate(""), None);
+        assert_eq!(negotiate_encoding(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn negotiate_encoding_quality() {
+        assert_eq!(negotiate("br;q=0.5, gzip;q=0.8"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("br;q=0, gzip;q=0"), None);
+        assert_eq!(negotiate("*;q=0.1, gzip;q=0.2"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("*, br;q=0"), Some(Encoding::Zstd));
+        assert_eq!(negotiate("br;q=0.5, zstd"), Some(Encoding::Zstd));
+        assert_eq!(negotiate("zstd;q=0.5, gzip"), Some(Encoding::Gzip));
+        assert_eq!(
+            negotiate("gzip;q=invalid, deflate"),
+            Some(Encoding::Deflate)
+        );
+        assert_eq!(negotiate("gzip; Q=1.0"), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn compressible_content_types() {
+        assert!(is_compressible_content_type("text/html; charset=utf-8"));
+        assert!(is_compressible_content_type("application/json"));
+        assert!(is_compressible_content_type("image/svg+xml"));
+        assert!(!is_compressible_content_type("image/png"));
+        assert!(!is_compressible_content_type("Video/MP4"));
+        assert!(!is_compressible_content_type("font/woff2"));
+        assert!(!is_compressible_content_type("application/zip"));
+        assert!(!is_compressible_content_type("text/event-stream"));
+    }
+}
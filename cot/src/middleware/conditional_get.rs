@@ -0,0 +1,532 @@
+use std::task::{Context, Poll};
+
+use chrono::{DateTime, FixedOffset};
+use futures_core::future::BoxFuture;
+use http::header::{
+    CACHE_CONTROL, CONTENT_LOCATION, DATE, ETAG, EXPIRES, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    IF_RANGE, LAST_MODIFIED, SET_COOKIE, VARY,
+};
+use http::{HeaderMap, HeaderValue, Method, StatusCode};
+use tower::Service;
+
+use crate::body::BodyInner;
+use crate::request::Request;
+use crate::response::{ETag, Response};
+use crate::{Body, Error};
+
+/// A middleware that answers conditional `GET` and `HEAD` requests with
+/// `304 Not Modified` responses.
+///
+/// For every successful (`200 OK`) response to a `GET` or `HEAD` request:
+///
+/// * if the response doesn't have an `ETag` header, but its body is already in
+///   memory (e.g. an HTML page rendered by the handler), a strong `ETag` is
+///   computed from the body's content (unless the response has `Cache-Control:
+///   no-store`),
+/// * if the request's `If-None-Match` header matches the `ETag` of the
+///   response, or, when there is no `If-None-Match` header, the `Last-Modified`
+///   date of the response is not later than the request's `If-Modified-Since`
+///   date, the response is replaced with an empty `304 Not Modified` response.
+///
+/// Streamed responses without an `ETag` or `Last-Modified` header set by the
+/// handler are passed through unchanged. Note that the handler still runs for
+/// every request; this middleware only saves the bandwidth needed to send the
+/// response body.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::ConditionalGetMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler.middleware(ConditionalGetMiddleware::new()).build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct ConditionalGetMiddleware;
+
+impl ConditionalGetMiddleware {
+    /// Creates a new instance of [`ConditionalGetMiddleware`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::ConditionalGetMiddleware;
+    ///
+    /// let middleware = ConditionalGetMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ConditionalGetMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for ConditionalGetMiddleware {
+    type Service = ConditionalGetService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConditionalGetService { inner }
+    }
+}
+
+/// Service that answers conditional `GET` and `HEAD` requests with
+/// `304 Not Modified` responses.
+///
+/// Used by [`ConditionalGetMiddleware`].
+#[derive(Debug, Clone)]
+pub struct ConditionalGetService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for ConditionalGetService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if req.method() != Method::GET && req.method() != Method::HEAD {
+            return Box::pin(inner.call(req));
+        }
+        let mut conditions = HeaderMap::new();
+        for name in [IF_NONE_MATCH, IF_MODIFIED_SINCE] {
+            if let Some(value) = req.headers().get(&name) {
+                conditions.insert(name, value.clone());
+            }
+        }
+
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+            if response.status() != StatusCode::OK {
+                return Ok(response);
+            }
+
+            if !response.headers().contains_key(ETAG) && !is_no_store(response.headers()) {
+                if let BodyInner::Fixed(content) = &response.body().inner {
+                    let etag = ETag::from_content(content);
+                    response.headers_mut().insert(ETAG, etag.into());
+                }
+            }
+
+            if is_not_modified(&conditions, response.headers()) {
+                Ok(not_modified_response(response))
+            } else {
+                Ok(response)
+            }
+        })
+    }
+}
+
+fn is_no_store(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-store"))
+}
+
+/// Returns whether a response with the given headers can be replaced with a
+/// `304 Not Modified` response, according to the conditional headers of the
+/// request.
+///
+/// As described in RFC 9110, `If-None-Match` takes precedence over
+/// `If-Modified-Since`, and the entity tags are compared using the weak
+/// comparison function.
+pub(crate) fn is_not_modified(request: &HeaderMap, response: &HeaderMap) -> bool {
+    if let Some(if_none_match) = request.get(IF_NONE_MATCH) {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+        if if_none_match.trim() == "*" {
+            return true;
+        }
+
+        let Some(etag) = response
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_etag)
+        else {
+            return false;
+        };
+        return parse_etag_list(if_none_match).any(|candidate| candidate.tag() == etag.tag());
+    }
+
+    let if_modified_since = request.get(IF_MODIFIED_SINCE).and_then(parse_http_date);
+    let last_modified = response.get(LAST_MODIFIED).and_then(parse_http_date);
+    match (if_modified_since, last_modified) {
+        (Some(if_modified_since), Some(last_modified)) => last_modified <= if_modified_since,
+        _ => false,
+    }
+}
+
+/// Returns whether the `Range` header of a request should be honored for a
+/// response with the given headers, according to the `If-Range` header of
+/// the request.
+///
+/// As described in RFC 9110, an entity tag in `If-Range` is compared using the
+/// strong comparison function, and a date has to exactly match the
+/// `Last-Modified` header of the response. Requests without `If-Range` always
+/// have their range applied.
+pub(crate) fn is_range_applicable(request: &HeaderMap, response: &HeaderMap) -> bool {
+    let Some(if_range) = request.get(IF_RANGE) else {
+        return true;
+    };
+    let Ok(if_range_str) = if_range.to_str() else {
+        return false;
+    };
+
+    let if_range_str = if_range_str.trim();
+    if if_range_str.starts_with('"') || if_range_str.starts_with("W/") {
+        let Some(candidate) = parse_etag(if_range_str) else {
+            return false;
+        };
+        let Some(etag) = response
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_etag)
+        else {
+            return false;
+        };
+        return !candidate.is_weak() && !etag.is_weak() && candidate.tag() == etag.tag();
+    }
+
+    let if_range = parse_http_date(if_range);
+    let last_modified = response.get(LAST_MODIFIED).and_then(parse_http_date);
+    matches!((if_range, last_modified), (Some(if_range), Some(last_modified)) if if_range == last_modified)
+}
+
+/// Turns a response into a `304 Not Modified` response, keeping only the
+/// headers that a `304` response should contain.
+pub(crate) fn not_modified_response(response: Response) -> Response {
+    const KEPT_HEADERS: [http::HeaderName; 8] = [
+        CACHE_CONTROL,
+        CONTENT_LOCATION,
+        DATE,
+        ETAG,
+        EXPIRES,
+        LAST_MODIFIED,
+        SET_COOKIE,
+        VARY,
+    ];
+
+    let (mut parts, _) = response.into_parts();
+    let headers = std::mem::take(&mut parts.headers);
+    for name in KEPT_HEADERS {
+        for value in headers.get_all(&name) {
+            parts.headers.append(name.clone(), value.clone());
+        }
+    }
+    parts.status = StatusCode::NOT_MODIFIED;
+
+    Response::from_parts(parts, Body::empty())
+}
+
+fn parse_etag(value: &str) -> Option<ETag> {
+    let mut tags = parse_etag_list(value);
+    let etag = tags.next()?;
+    tags.next().is_none().then_some(etag)
+}
+
+/// Parses a comma-separated list of entity tags, as used in the
+/// `If-None-Match` header. Parsing stops at the first invalid entry.
+fn parse_etag_list(value: &str) -> impl Iterator<Item = ETag> + '_ {
+    let mut rest = value;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start_matches([' ', '\t', ',']);
+        let (weak, quoted) = match rest.strip_prefix("W/") {
+            Some(quoted) => (true, quoted),
+            None => (false, rest),
+        };
+        let quoted = quoted.strip_prefix('"')?;
+        let end = quoted.find('"')?;
+        let tag = &quoted[..end];
+        if !tag
+            .bytes()
+            .all(|byte| byte == 0x21 || (0x23..=0x7e).contains(&byte))
+        {
+            return None;
+        }
+        rest = &quoted[end + 1..];
+
+        Some(if weak {
+            ETag::weak(tag)
+        } else {
+            ETag::strong(tag)
+        })
+    })
+}
+
+fn parse_http_date(value: &HeaderValue) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc2822(value.to_str().ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    const LAST_MODIFIED_DATE: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+    fn headers(headers: &[(http::HeaderName, &'static str)]) -> HeaderMap {
+        headers
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    async fn conditional_get(request: Request, response: fn() -> Response) -> Response {
+        let svc = tower::service_fn(move |_req: Request| async move { Ok::<_, Error>(response()) });
+
+        ConditionalGetMiddleware::new()
+            .layer(svc)
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    fn get_request(header: http::HeaderName, value: &str) -> Request {
+        let mut request = TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert(header, HeaderValue::from_str(value).unwrap());
+        request
+    }
+
+    fn hello_response() -> Response {
+        Response::new(Body::fixed("hello"))
+    }
+
+    #[test]
+    fn etag_list_parsing() {
+        let tags: Vec<_> = parse_etag_list(r#""a", W/"b",,"c,d""#).collect();
+        assert_eq!(
+            tags,
+            [ETag::strong("a"), ETag::weak("b"), ETag::strong("c,d")]
+        );
+
+        let tags: Vec<_> = parse_etag_list(r#""a", invalid, "b""#).collect();
+        assert_eq!(tags, [ETag::strong("a")]);
+
+        assert_eq!(parse_etag(r#"W/"a""#), Some(ETag::weak("a")));
+        assert_eq!(parse_etag(r#""a", "b""#), None);
+        assert_eq!(parse_etag("a"), None);
+    }
+
+    #[test]
+    fn not_modified_if_none_match() {
+        let response = headers(&[(ETAG, r#""v1""#)]);
+
+        assert!(is_not_modified(
+            &headers(&[(IF_NONE_MATCH, r#""v0", "v1""#)]),
+            &response
+        ));
+        assert!(is_not_modified(
+            &headers(&[(IF_NONE_MATCH, r#"W/"v1""#)]),
+            &response
+        ));
+        assert!(is_not_modified(
+            &headers(&[(IF_NONE_MATCH, "*")]),
+            &response
+        ));
+        assert!(!is_not_modified(
+            &headers(&[(IF_NONE_MATCH, r#""v2""#)]),
+            &response
+        ));
+        assert!(!is_not_modified(&HeaderMap::new(), &response));
+    }
+
+    #[test]
+    fn not_modified_if_modified_since() {
+        let response = headers(&[(LAST_MODIFIED, LAST_MODIFIED_DATE)]);
+
+        assert!(is_not_modified(
+            &headers(&[(IF_MODIFIED_SINCE, LAST_MODIFIED_DATE)]),
+            &response
+        ));
+        assert!(is_not_modified(
+            &headers(&[(IF_MODIFIED_SINCE, "Thu, 22 Oct 2015 07:28:00 GMT")]),
+            &response
+        ));
+        assert!(!is_not_modified(
+            &headers(&[(IF_MODIFIED_SINCE, "Tue, 20 Oct 2015 07:28:00 GMT")]),
+            &response
+        ));
+        assert!(!is_not_modified(
+            &headers(&[(IF_MODIFIED_SINCE, "invalid date")]),
+            &response
+        ));
+    }
+
+    #[test]
+    fn range_applicable_if_range() {
+        let response = headers(&[(ETAG, r#""v1""#), (LAST_MODIFIED, LAST_MODIFIED_DATE)]);
+
+        assert!(is_range_applicable(&HeaderMap::new(), &response));
+        assert!(is_range_applicable(
+            &headers(&[(IF_RANGE, r#""v1""#)]),
+            &response
+        ));
+        assert!(is_range_applicable(
+            &headers(&[(IF_RANGE, LAST_MODIFIED_DATE)]),
+            &response
+        ));
+        assert!(!is_range_applicable(
+            &headers(&[(IF_RANGE, r#""v2""#)]),
+            &response
+        ));
+        assert!(!is_range_applicable(
+            &headers(&[(IF_RANGE, r#"W/"v1""#)]),
+            &response
+        ));
+        assert!(!is_range_applicable(
+            &headers(&[(IF_RANGE, "Thu, 22 Oct 2015 07:28:00 GMT")]),
+            &response
+        ));
+        assert!(!is_range_applicable(
+            &headers(&[(IF_RANGE, "invalid date")]),
+            &response
+        ));
+        assert!(!is_range_applicable(
+            &headers(&[(IF_RANGE, r#""v1""#)]),
+            &headers(&[(ETAG, r#"W/"v1""#)])
+        ));
+    }
+
+    #[test]
+    fn not_modified_if_none_match_takes_precedence() {
+        let response = headers(&[(ETAG, r#""v1""#), (LAST_MODIFIED, LAST_MODIFIED_DATE)]);
+        let request = headers(&[
+            (IF_NONE_MATCH, r#""v2""#),
+            (IF_MODIFIED_SINCE, LAST_MODIFIED_DATE),
+        ]);
+
+        assert!(!is_not_modified(&request, &response));
+    }
+
+    #[cot::test]
+    async fn conditional_get_adds_etag() {
+        let response = conditional_get(TestRequestBuilder::get("/").build(), hello_response).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[ETAG],
+            ETag::from_content(b"hello").to_string()
+        );
+    }
+
+    #[cot::test]
+    async fn conditional_get_no_store() {
+        let response = conditional_get(TestRequestBuilder::get("/").build(), || {
+            let mut response = hello_response();
+            response
+                .headers_mut()
+                .insert(CACHE_CONTROL, HeaderValue::from_static("private, no-store"));
+            response
+        })
+        .await;
+
+        assert!(!response.headers().contains_key(ETAG));
+    }
+
+    #[cot::test]
+    async fn conditional_get_not_modified() {
+        let etag = ETag::from_content(b"hello").to_string();
+        let response = conditional_get(get_request(IF_NONE_MATCH, &etag), || {
+            let mut response = hello_response();
+            response
+                .headers_mut()
+                .insert(VARY, HeaderValue::from_static("accept-encoding"));
+            response.headers_mut().insert(
+                http::header::CONTENT_TYPE,
+                HeaderValue::from_static("text/plain"),
+            );
+            response
+        })
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[ETAG], etag);
+        assert_eq!(response.headers()[VARY], "accept-encoding");
+        assert!(!response.headers().contains_key(http::header::CONTENT_TYPE));
+        assert!(response.into_body().into_bytes().await.unwrap().is_empty());
+    }
+
+    #[cot::test]
+    async fn conditional_get_modified() {
+        let response =
+            conditional_get(get_request(IF_NONE_MATCH, r#""outdated""#), hello_response).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "hello");
+    }
+
+    #[cot::test]
+    async fn conditional_get_last_modified() {
+        let response = conditional_get(get_request(IF_MODIFIED_SINCE, LAST_MODIFIED_DATE), || {
+            let mut response = Response::new(Body::streaming(futures::stream::once(async {
+                Ok(bytes::Bytes::from("hello"))
+            })));
+            response
+                .headers_mut()
+                .insert(LAST_MODIFIED, HeaderValue::from_static(LAST_MODIFIED_DATE));
+            response
+        })
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(!response.headers().contains_key(ETAG));
+    }
+
+    #[cot::test]
+    async fn conditional_get_ignores_other_methods() {
+        let mut request = TestRequestBuilder::post("/").build();
+        request
+            .headers_mut()
+            .insert(IF_NONE_MATCH, HeaderValue::from_static("*"));
+
+        let response = conditional_get(request, hello_response).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(ETAG));
+    }
+
+    #[cot::test]
+    async fn conditional_get_ignores_errors() {
+        let response = conditional_get(get_request(IF_NONE_MATCH, "*"), || {
+            let mut response = hello_response();
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            response
+        })
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,434 @@
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use cookie::{Cookie, CookieJar, Key};
+use futures_core::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use http::{HeaderMap, HeaderValue, header};
+use sha2::Sha512;
+use tower::Service;
+use tower_sessions::Expiry;
+use tracing::{error, warn};
+
+use crate::clock::{Clock, now_offset};
+use crate::config::{SecretKey, SessionStoreErrorPolicy};
+use crate::session::cookie::SameSite;
+use crate::session::store::{Error, Id, Record, Result, SessionStore};
+
+/// The maximum size of a cookie (its name and value) accepted by the
+/// browsers.
+const MAX_COOKIE_SIZE: usize = 4096;
+
+/// Used to derive the session cookie keys from the secret keys, so that
+/// they're different from the keys used for anything else.
+const KEY_DERIVATION_CONTEXT: &[u8] = b"cot.session.cookie";
+
+/// Encodes the session records into signed (or encrypted) cookie values and
+/// decodes them back.
+#[derive(Clone)]
+pub(crate) struct SessionCookieCodec {
+    // the key derived from the current secret key first, then the keys
+    // derived from the fallback keys
+    keys: Arc<[Key]>,
+    encrypt: bool,
+}
+
+impl std::fmt::Debug for SessionCookieCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionCookieCodec")
+            .field("encrypt", &self.encrypt)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionCookieCodec {
+    pub(crate) fn new(secret_key: &SecretKey, fallback_keys: &[SecretKey], encrypt: bool) -> Self {
+        let keys = std::iter::once(secret_key)
+            .chain(fallback_keys)
+            .map(derive_key)
+            .collect();
+
+        Self { keys, encrypt }
+    }
+
+    fn encode(&self, name: &str, record: &Record) -> Result<String> {
+        let payload =
+            serde_json::to_vec(record).map_err(|error| Error::Encode(error.to_string()))?;
+        let cookie = Cookie::new(name.to_owned(), URL_SAFE_NO_PAD.encode(payload));
+
+        let mut jar = CookieJar::new();
+        if self.encrypt {
+            jar.private_mut(&self.keys[0]).add(cookie);
+        } else {
+            jar.signed_mut(&self.keys[0]).add(cookie);
+        }
+        let value = jar
+            .get(name)
+            .expect("the cookie has just been added")
+            .value()
+            .to_owned();
+
+        if name.len() + value.len() > MAX_COOKIE_SIZE {
+            return Err(Error::Encode(format!(
+                "the session cookie is larger than {MAX_COOKIE_SIZE} bytes"
+            )));
+        }
+        Ok(value)
+    }
+
+    /// Returns the record stored in the cookie value, and whether the value
+    /// has been signed with one of the fallback keys, or `None` if the value
+    /// is invalid.
+    fn decode(&self, name: &str, value: &str) -> Option<(Record, bool)> {
+        let mut jar = CookieJar::new();
+        jar.add_original(Cookie::new(name.to_owned(), value.to_owned()));
+
+        self.keys.iter().enumerate().find_map(|(index, key)| {
+            let cookie = if self.encrypt {
+                jar.private(key).get(name)
+            } else {
+                jar.signed(key).get(name)
+            }?;
+            let payload = URL_SAFE_NO_PAD.decode(cookie.value()).ok()?;
+            let record = serde_json::from_slice(&payload).ok()?;
+            Some((record, index > 0))
+        })
+    }
+}
+
+fn derive_key(secret_key: &SecretKey) -> Key {
+    let mut mac = Hmac::<Sha512>::new_from_slice(secret_key.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(KEY_DERIVATION_CONTEXT);
+    Key::from(&mac.finalize().into_bytes())
+}
+
+/// The attributes of the session cookie.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionCookieConfig {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) domain: Option<String>,
+    pub(crate) secure: bool,
+    pub(crate) http_only: bool,
+    pub(crate) same_site: SameSite,
+    pub(crate) max_age: Option<Duration>,
+}
+
+impl SessionCookieConfig {
+    /// Returns the expiry of the sessions, or `None` if they last until the
+    /// browser is closed.
+    pub(crate) fn expiry(&self) -> Option<Expiry> {
+        self.max_age().map(Expiry::OnInactivity)
+    }
+
+    fn max_age(&self) -> Option<time::Duration> {
+        self.max_age
+            .map(|max_age| time::Duration::try_from(max_age).unwrap_or(time::Duration::MAX))
+    }
+
+    fn build(&self, value: String) -> Cookie<'static> {
+        let mut cookie = Cookie::build((self.name.clone(), value))
+            .http_only(self.http_only)
+            .same_site(self.same_site.to_cookie())
+            .secure(self.secure)
+            .path(self.path.clone());
+        if let Some(domain) = &self.domain {
+            cookie = cookie.domain(domain.clone());
+        }
+        if let Some(max_age) = self.max_age() {
+            cookie = cookie.max_age(max_age);
+        }
+        cookie.build()
+    }
+}
+
+/// Service that keeps the session data in the session cookie itself.
+///
+/// Used by [`SessionMiddleware`](crate::middleware::SessionMiddleware) when a
+/// cookie store is set with
+/// [`SessionMiddleware::signed_cookie_store`](crate::middleware::SessionMiddleware::signed_cookie_store)
+/// or
+/// [`SessionMiddleware::encrypted_cookie_store`](crate::middleware::SessionMiddleware::encrypted_cookie_store).
+#[derive(Debug, Clone)]
+pub struct CookieSessionService<S> {
+    inner: S,
+    codec: SessionCookieCodec,
+    cookie: Arc<SessionCookieConfig>,
+    store_error_policy: SessionStoreErrorPolicy,
+    clock: Arc<dyn Clock>,
+}
+
+impl<S> CookieSessionService<S> {
+    pub(crate) fn new(
+        inner: S,
+        codec: SessionCookieCodec,
+        cookie: SessionCookieConfig,
+        store_error_policy: SessionStoreErrorPolicy,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            inner,
+            codec,
+            cookie: Arc::new(cookie),
+            store_error_policy,
+            clock,
+        }
+    }
+}
+
+impl<ReqBody, ResBody, S> Service<http::Request<ReqBody>> for CookieSessionService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    ReqBody: Send + 'static,
+    ResBody: Default + Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, std::result::Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let codec = self.codec.clone();
+        let cookie = Arc::clone(&self.cookie);
+        let store_error_policy = self.store_error_policy;
+        let now = now_offset(self.clock.as_ref());
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let cookie_value = request_cookie(req.headers(), &cookie.name);
+            let decoded = cookie_value.as_deref().and_then(|value| {
+                let decoded = codec.decode(&cookie.name, value);
+                if decoded.is_none() {
+                    warn!("possibly suspicious activity: invalid session cookie");
+                }
+                decoded
+            });
+            let (record, stale_key) = match decoded {
+                Some((record, stale_key)) if record.expiry_date > now => (Some(record), stale_key),
+                _ => (None, false),
+            };
+
+            let store = CookieRecordStore::default();
+            let session_id = record.as_ref().map(|record| record.id);
+            *store.record() = record;
+            let session =
+                tower_sessions::Session::new(session_id, Arc::new(store.clone()), cookie.expiry());
+            req.extensions_mut().insert(session.clone());
+
+            let mut response = inner.call(req).await?;
+
+            let set_cookie = if session.is_empty().await {
+                cookie_value.map(|_| {
+                    let mut removal = cookie.build(String::new());
+                    removal.make_removal();
+                    removal
+                })
+            } else if (session.is_modified() || stale_key) && !response.status().is_server_error() {
+                match save(&session, &store, &codec, &cookie.name).await {
+                    Ok(value) => Some(cookie.build(value)),
+                    Err(error) if store_error_policy == SessionStoreErrorPolicy::Degrade => {
+                        warn!("Failed to save the session, the changes are lost: {error}");
+                        None
+                    }
+                    Err(error) => {
+                        error!("Failed to save the session: {error}");
+                        let mut response = http::Response::<ResBody>::default();
+                        *response.status_mut() = http::StatusCode::INTERNAL_SERVER_ERROR;
+                        return Ok(response);
+                    }
+                }
+            } else {
+                None
+            };
+
+            if let Some(set_cookie) = set_cookie {
+                let value = HeaderValue::from_str(&set_cookie.to_string())
+                    .expect("the session cookie is a valid header value");
+                response.headers_mut().append(header::SET_COOKIE, value);
+            }
+            Ok(response)
+        })
+    }
+}
+
+async fn save(
+    session: &tower_sessions::Session,
+    store: &CookieRecordStore,
+    codec: &SessionCookieCodec,
+    name: &str,
+) -> Result<String> {
+    session
+        .save()
+        .await
+        .map_err(|error| Error::Backend(error.to_string()))?;
+    let record = store
+        .record()
+        .clone()
+        .ok_or_else(|| Error::Backend("the session has not been saved".to_owned()))?;
+
+    codec.encode(name, &record)
+}
+
+fn request_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(Cookie::split_parse)
+        .filter_map(std::result::Result::ok)
+        .find(|cookie| cookie.name() == name)
+        .map(|cookie| cookie.value().to_owned())
+}
+
+/// A session store holding the session of a single request, decoded from
+/// (and then encoded back into) the session cookie.
+#[derive(Debug, Clone, Default)]
+struct CookieRecordStore(Arc<Mutex<Option<Record>>>);
+
+impl CookieRecordStore {
+    fn record(&self) -> std::sync::MutexGuard<'_, Option<Record>> {
+        self.0.lock().expect("session record lock poisoned")
+    }
+}
+
+#[async_trait]
+impl SessionStore for CookieRecordStore {
+    async fn create(&self, session_record: &mut Record) -> Result<()> {
+        *self.record() = Some(session_record.clone());
+        Ok(())
+    }
+
+    async fn save(&self, session_record: &Record) -> Result<()> {
+        *self.record() = Some(session_record.clone());
+        Ok(())
+    }
+
+    async fn load(&self, session_id: &Id) -> Result<Option<Record>> {
+        Ok(self
+            .record()
+            .clone()
+            .filter(|record| record.id == *session_id))
+    }
+
+    async fn delete(&self, session_id: &Id) -> Result<()> {
+        let mut record = self.record();
+        if record
+            .as_ref()
+            .is_some_and(|record| record.id == *session_id)
+        {
+            *record = None;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    fn record() -> Record {
+        Record {
+            id: Id::default(),
+            data: HashMap::from([("user".to_owned(), "alice".into())]),
+            expiry_date: OffsetDateTime::now_utc() + time::Duration::hours(1),
+        }
+    }
+
+    #[test]
+    fn signed_roundtrip() {
+        let codec = SessionCookieCodec::new(&SecretKey::from("secret"), &[], false);
+        let record = record();
+
+        let value = codec.encode("id", &record).unwrap();
+        let (decoded, stale_key) = codec.decode("id", &value).unwrap();
+
+        assert_eq!(decoded.id, record.id);
+        assert_eq!(decoded.data, record.data);
+        assert!(!stale_key);
+    }
+
+    #[test]
+    fn signed_is_readable() {
+        let codec = SessionCookieCodec::new(&SecretKey::from("secret"), &[], false);
+
+        let value = codec.encode("id", &record()).unwrap();
+
+        // the signature is prepended to the base64-encoded payload
+        let payload = URL_SAFE_NO_PAD.decode(&value[44..]).unwrap();
+        assert!(String::from_utf8(payload).unwrap().contains("alice"));
+    }
+
+    #[test]
+    fn encrypted_roundtrip() {
+        let codec = SessionCookieCodec::new(&SecretKey::from("secret"), &[], true);
+        let record = record();
+
+        let value = codec.encode("id", &record).unwrap();
+        let (decoded, _) = codec.decode("id", &value).unwrap();
+
+        assert_eq!(decoded.data, record.data);
+        assert!(!value.contains(&URL_SAFE_NO_PAD.encode("alice")));
+    }
+
+    #[test]
+    fn tampered_value() {
+        let codec = SessionCookieCodec::new(&SecretKey::from("secret"), &[], false);
+        let value = codec.encode("id", &record()).unwrap();
+
+        let tampered = format!("{}{}", &value[..44], URL_SAFE_NO_PAD.encode("{}"));
+
+        assert!(codec.decode("id", &tampered).is_none());
+        assert!(codec.decode("id", "garbage").is_none());
+    }
+
+    #[test]
+    fn key_rotation() {
+        let old_codec = SessionCookieCodec::new(&SecretKey::from("old"), &[], false);
+        let codec =
+            SessionCookieCodec::new(&SecretKey::from("new"), &[SecretKey::from("old")], false);
+        let other_codec = SessionCookieCodec::new(&SecretKey::from("other"), &[], false);
+
+        let value = old_codec.encode("id", &record()).unwrap();
+
+        let (_, stale_key) = codec.decode("id", &value).unwrap();
+        assert!(stale_key);
+        assert!(other_codec.decode("id", &value).is_none());
+    }
+
+    #[test]
+    fn too_large() {
+        let codec = SessionCookieCodec::new(&SecretKey::from("secret"), &[], false);
+        let mut record = record();
+        record
+            .data
+            .insert("large".to_owned(), "a".repeat(MAX_COOKIE_SIZE).into());
+
+        assert!(codec.encode("id", &record).is_err());
+    }
+
+    #[test]
+    fn request_cookie_parsing() {
+        let mut headers = HeaderMap::new();
+        headers.append(header::COOKIE, HeaderValue::from_static("a=1; id=abc"));
+        headers.append(header::COOKIE, HeaderValue::from_static("b=2"));
+
+        assert_eq!(request_cookie(&headers, "id").as_deref(), Some("abc"));
+        assert_eq!(request_cookie(&headers, "b").as_deref(), Some("2"));
+        assert_eq!(request_cookie(&headers, "c"), None);
+    }
+}
@@ -0,0 +1,624 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use http::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS,
+    ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY,
+};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use tower::Service;
+
+use crate::config::CorsMiddlewareConfig;
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::Response;
+use crate::{Body, Error};
+
+const WILDCARD: &str = "*";
+
+/// A middleware that allows cross-origin requests (CORS) from the configured
+/// origins.
+///
+/// The responses to the requests coming from the allowed origins get the
+/// `Access-Control-Allow-Origin` header (and
+/// `Access-Control-Allow-Credentials`, if enabled), which lets the browsers
+/// expose them to the scripts of these origins. The preflight requests the
+/// browsers send before the requests using other methods or headers than the
+/// ones always allowed are answered by the middleware itself, with the allowed
+/// methods and headers, without calling the request handler.
+///
+/// The allowed origins, methods and headers can be set with the builder
+/// methods or in the `[middlewares.cors]` section of the config (see
+/// [`CorsMiddlewareConfig`]). By default, no origins are allowed.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::CorsMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(CorsMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CorsMiddleware {
+    config: Arc<CorsConfig>,
+}
+
+#[derive(Debug, Clone)]
+struct CorsConfig {
+    any_origin: bool,
+    allowed_origins: Vec<HeaderValue>,
+    allowed_methods: HeaderValue,
+    any_header: bool,
+    allowed_headers: Option<HeaderValue>,
+    allow_credentials: bool,
+    max_age: Option<HeaderValue>,
+}
+
+impl CorsMiddleware {
+    /// Creates a new instance of [`CorsMiddleware`] that doesn't allow any
+    /// origins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CorsMiddleware;
+    ///
+    /// let middleware = CorsMiddleware::new().allow_origins(["https://example.com"]);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_config(&CorsMiddlewareConfig::default())
+    }
+
+    /// Creates a new instance of [`CorsMiddleware`] from the application
+    /// context, using the settings from the `[middlewares.cors]` section of
+    /// the config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CorsMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(CorsMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::from_config(&context.config().middlewares.cors)
+    }
+
+    /// Creates a new instance of [`CorsMiddleware`] from the CORS middleware
+    /// configuration.
+    ///
+    /// The invalid origins (which can't be sent in a header) are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::CorsMiddlewareConfig;
+    /// use cot::middleware::CorsMiddleware;
+    ///
+    /// let config = CorsMiddlewareConfig::builder()
+    ///     .allowed_origins(vec!["https://example.com".to_owned()])
+    ///     .build();
+    /// let middleware = CorsMiddleware::from_config(&config);
+    /// ```
+    #[must_use]
+    pub fn from_config(config: &CorsMiddlewareConfig) -> Self {
+        Self {
+            config: Arc::new(CorsConfig {
+                any_origin: false,
+                allowed_origins: Vec::new(),
+                allowed_methods: join_header_values(&[]),
+                any_header: false,
+                allowed_headers: None,
+                allow_credentials: config.allow_credentials,
+                max_age: None,
+            }),
+        }
+        .allow_origins(&config.allowed_origins)
+        .allow_methods(config.allowed_methods.iter().cloned())
+        .allow_headers(config.allowed_headers.iter().cloned())
+        .with_max_age(config.max_age)
+    }
+
+    /// Sets the origins allowed to make cross-origin requests, such as
+    /// `https://example.com`, or `*` to allow any origin.
+    ///
+    /// The invalid origins (which can't be sent in a header) are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CorsMiddleware;
+    ///
+    /// let middleware =
+    ///     CorsMiddleware::new().allow_origins(["https://example.com", "https://app.example.com"]);
+    /// ```
+    #[must_use]
+    pub fn allow_origins<I>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let config = Arc::make_mut(&mut self.config);
+        config.any_origin = false;
+        config.allowed_origins.clear();
+        for origin in origins {
+            let origin = origin.as_ref();
+            if origin == WILDCARD {
+                config.any_origin = true;
+            } else if let Ok(origin) = HeaderValue::from_str(origin) {
+                config.allowed_origins.push(origin);
+            }
+        }
+        self
+    }
+
+    /// Sets the HTTP methods allowed in cross-origin requests. By default,
+    /// `GET`, `HEAD` and `POST` are allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::Method;
+    /// use cot::middleware::CorsMiddleware;
+    ///
+    /// let middleware = CorsMiddleware::new().allow_methods([Method::GET, Method::DELETE]);
+    /// ```
+    #[must_use]
+    pub fn allow_methods<I>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        let methods: Vec<_> = methods.into_iter().collect();
+        Arc::make_mut(&mut self.config).allowed_methods =
+            join_header_values(&methods.iter().map(Method::as_str).collect::<Vec<_>>());
+        self
+    }
+
+    /// Sets the request headers allowed in cross-origin requests, in addition
+    /// to the ones the browsers always allow (such as `Accept`). `*` allows
+    /// any header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::header;
+    /// use cot::middleware::CorsMiddleware;
+    ///
+    /// let middleware =
+    ///     CorsMiddleware::new().allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
+    /// ```
+    #[must_use]
+    pub fn allow_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        let headers: Vec<_> = headers.into_iter().collect();
+        let config = Arc::make_mut(&mut self.config);
+        config.any_header = headers.iter().any(|header| header == WILDCARD);
+        config.allowed_headers = (!headers.is_empty()).then(|| {
+            join_header_values(&headers.iter().map(HeaderName::as_str).collect::<Vec<_>>())
+        });
+        self
+    }
+
+    /// Sets whether the cross-origin requests can include credentials, such
+    /// as cookies. This can't be used when any origin is allowed (the
+    /// browsers reject such responses).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CorsMiddleware;
+    ///
+    /// let middleware = CorsMiddleware::new()
+    ///     .allow_origins(["https://example.com"])
+    ///     .allow_credentials(true);
+    /// ```
+    #[must_use]
+    pub fn allow_credentials(mut self, allow_credentials: bool) -> Self {
+        Arc::make_mut(&mut self.config).allow_credentials = allow_credentials;
+        self
+    }
+
+    /// Sets how long the browsers can cache the results of the preflight
+    /// requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::CorsMiddleware;
+    ///
+    /// let middleware = CorsMiddleware::new().max_age(Duration::from_secs(3600));
+    /// ```
+    #[must_use]
+    pub fn max_age(self, max_age: Duration) -> Self {
+        self.with_max_age(Some(max_age))
+    }
+
+    fn with_max_age(mut self, max_age: Option<Duration>) -> Self {
+        Arc::make_mut(&mut self.config).max_age = max_age.map(|max_age| max_age.as_secs().into());
+        self
+    }
+}
+
+impl Default for CorsMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for CorsMiddleware {
+    type Service = CorsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CorsService {
+            inner,
+            config: Arc::clone(&self.config),
+        }
+    }
+}
+
+/// Service that allows cross-origin requests from the configured origins.
+///
+/// Used by [`CorsMiddleware`].
+#[derive(Debug, Clone)]
+pub struct CorsService<S> {
+    inner: S,
+    config: Arc<CorsConfig>,
+}
+
+impl<S> Service<Request> for CorsService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let config = Arc::clone(&self.config);
+        let origin = req.headers().get(ORIGIN).cloned();
+
+        let is_preflight = req.method() == Method::OPTIONS
+            && origin.is_some()
+            && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+        if is_preflight {
+            let response = config.preflight_response(origin.as_ref(), req.headers());
+            return Box::pin(async move { Ok(response) });
+        }
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+            if let Some(origin) = origin {
+                config.add_origin_headers(&origin, response.headers_mut());
+            }
+            Ok(response)
+        })
+    }
+}
+
+impl CorsConfig {
+    fn is_allowed(&self, origin: &HeaderValue) -> bool {
+        self.any_origin || self.allowed_origins.contains(origin)
+    }
+
+    /// Adds the headers allowing the origin to read the response, if it is
+    /// allowed.
+    fn add_origin_headers(&self, origin: &HeaderValue, headers: &mut HeaderMap) {
+        let mirrors_origin = !self.any_origin || self.allow_credentials;
+        if mirrors_origin {
+            // the response depends on the origin, so it can't be cached for
+            // the other origins
+            headers.append(VARY, HeaderValue::from_name(ORIGIN));
+        }
+
+        if !self.is_allowed(origin) {
+            return;
+        }
+
+        let allowed_origin = if mirrors_origin {
+            origin.clone()
+        } else {
+            HeaderValue::from_static(WILDCARD)
+        };
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allowed_origin);
+        if self.allow_credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+
+    fn preflight_response(&self, origin: Option<&HeaderValue>, request: &HeaderMap) -> Response {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NO_CONTENT;
+
+        let headers = response.headers_mut();
+        if let Some(origin) = origin {
+            self.add_origin_headers(origin, headers);
+        }
+        headers.append(VARY, HeaderValue::from_name(ACCESS_CONTROL_REQUEST_METHOD));
+        headers.append(VARY, HeaderValue::from_name(ACCESS_CONTROL_REQUEST_HEADERS));
+        if !headers.contains_key(ACCESS_CONTROL_ALLOW_ORIGIN) {
+            return response;
+        }
+
+        headers.insert(ACCESS_CONTROL_ALLOW_METHODS, self.allowed_methods.clone());
+        let allowed_headers = if self.any_header {
+            // `*` is not supported for the requests with credentials, so the
+            // requested headers are sent back instead
+            request.get(ACCESS_CONTROL_REQUEST_HEADERS).cloned()
+        } else {
+            self.allowed_headers.clone()
+        };
+        if let Some(allowed_headers) = allowed_headers {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        }
+        if let Some(max_age) = &self.max_age {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, max_age.clone());
+        }
+
+        response
+    }
+}
+
+fn join_header_values(values: &[&str]) -> HeaderValue {
+    HeaderValue::from_str(&values.join(", "))
+        .expect("methods and header names are valid header values")
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::response::ResponseExt;
+    use crate::test::TestRequestBuilder;
+
+    async fn handler(_request: Request) -> crate::Result<Response> {
+        Ok(Response::new_html(StatusCode::OK, Body::fixed("ok")))
+    }
+
+    async fn call(middleware: CorsMiddleware, request: Request) -> Response {
+        middleware
+            .layer(tower::service_fn(handler))
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    fn request(method: Method, origin: Option<&'static str>) -> Request {
+        let mut request = TestRequestBuilder::get("/").build();
+        *request.method_mut() = method;
+        if let Some(origin) = origin {
+            request
+                .headers_mut()
+                .insert(ORIGIN, HeaderValue::from_static(origin));
+        }
+        request
+    }
+
+    fn preflight(origin: &'static str) -> Request {
+        let mut request = request(Method::OPTIONS, Some(origin));
+        request.headers_mut().insert(
+            ACCESS_CONTROL_REQUEST_METHOD,
+            HeaderValue::from_static("PUT"),
+        );
+        request.headers_mut().insert(
+            ACCESS_CONTROL_REQUEST_HEADERS,
+            HeaderValue::from_static("content-type, x-custom"),
+        );
+        request
+    }
+
+    fn header<'a>(response: &'a Response, name: &HeaderName) -> Option<&'a str> {
+        response
+            .headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap())
+    }
+
+    #[cot::test]
+    async fn allowed_origin() {
+        let middleware = CorsMiddleware::new().allow_origins(["https://example.com"]);
+
+        let response = call(
+            middleware,
+            request(Method::GET, Some("https://example.com")),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            header(&response, &ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some("https://example.com")
+        );
+        assert_eq!(header(&response, &VARY), Some("origin"));
+        assert_eq!(header(&response, &ACCESS_CONTROL_ALLOW_CREDENTIALS), None);
+    }
+
+    #[cot::test]
+    async fn disallowed_origin() {
+        let middleware = CorsMiddleware::new().allow_origins(["https://example.com"]);
+
+        let response = call(middleware, request(Method::GET, Some("https://evil.com"))).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, &ACCESS_CONTROL_ALLOW_ORIGIN), None);
+    }
+
+    #[cot::test]
+    async fn same_origin_request() {
+        let middleware = CorsMiddleware::new().allow_origins(["*"]);
+
+        let response = call(middleware, request(Method::GET, None)).await;
+
+        assert_eq!(header(&response, &ACCESS_CONTROL_ALLOW_ORIGIN), None);
+        assert_eq!(header(&response, &VARY), None);
+    }
+
+    #[cot::test]
+    async fn any_origin() {
+        let middleware = CorsMiddleware::new().allow_origins(["*"]);
+
+        let response = call(
+            middleware,
+            request(Method::GET, Some("https://example.com")),
+        )
+        .await;
+
+        assert_eq!(header(&response, &ACCESS_CONTROL_ALLOW_ORIGIN), Some("*"));
+        assert_eq!(header(&response, &VARY), None);
+    }
+
+    #[cot::test]
+    async fn credentials() {
+        let middleware = CorsMiddleware::new()
+            .allow_origins(["https://example.com"])
+            .allow_credentials(true);
+
+        let response = call(
+            middleware,
+            request(Method::POST, Some("https://example.com")),
+        )
+        .await;
+
+        assert_eq!(
+            header(&response, &ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            header(&response, &ACCESS_CONTROL_ALLOW_CREDENTIALS),
+            Some("true")
+        );
+    }
+
+    #[cot::test]
+    async fn preflight_allowed() {
+        let middleware = CorsMiddleware::new()
+            .allow_origins(["https://example.com"])
+            .allow_methods([Method::GET, Method::PUT])
+            .allow_headers([header::CONTENT_TYPE])
+            .max_age(Duration::from_secs(600));
+
+        let response = call(middleware, preflight("https://example.com")).await;
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            header(&response, &ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            header(&response, &ACCESS_CONTROL_ALLOW_METHODS),
+            Some("GET, PUT")
+        );
+        assert_eq!(
+            header(&response, &ACCESS_CONTROL_ALLOW_HEADERS),
+            Some("content-type")
+        );
+        assert_eq!(header(&response, &ACCESS_CONTROL_MAX_AGE), Some("600"));
+        assert!(response.into_body().into_bytes().await.unwrap().is_empty());
+    }
+
+    #[cot::test]
+    async fn preflight_any_header() {
+        let middleware = CorsMiddleware::new()
+            .allow_origins(["https://example.com"])
+            .allow_headers([HeaderName::from_static("*")]);
+
+        let response = call(middleware, preflight("https://example.com")).await;
+
+        assert_eq!(
+            header(&response, &ACCESS_CONTROL_ALLOW_HEADERS),
+            Some("content-type, x-custom")
+        );
+    }
+
+    #[cot::test]
+    async fn preflight_disallowed() {
+        let middleware = CorsMiddleware::new().allow_origins(["https://example.com"]);
+
+        let response = call(middleware, preflight("https://evil.com")).await;
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(header(&response, &ACCESS_CONTROL_ALLOW_ORIGIN), None);
+        assert_eq!(header(&response, &ACCESS_CONTROL_ALLOW_METHODS), None);
+    }
+
+    #[cot::test]
+    async fn options_without_preflight_headers() {
+        let middleware = CorsMiddleware::new().allow_origins(["https://example.com"]);
+
+        let response = call(
+            middleware,
+            request(Method::OPTIONS, Some("https://example.com")),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            header(&response, &ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some("https://example.com")
+        );
+    }
+
+    #[test]
+    fn from_config() {
+        let config = CorsMiddlewareConfig::builder()
+            .allowed_origins(vec!["*".to_owned(), "https://example.com".to_owned()])
+            .allowed_methods(vec![Method::DELETE])
+            .max_age(Duration::from_secs(60))
+            .build();
+
+        let middleware = CorsMiddleware::from_config(&config);
+
+        assert!(middleware.config.any_origin);
+        assert_eq!(middleware.config.allowed_origins, ["https://example.com"]);
+        assert_eq!(middleware.config.allowed_methods, "DELETE");
+        assert_eq!(middleware.config.allowed_headers, None);
+        assert_eq!(middleware.config.max_age, Some(HeaderValue::from(60)));
+    }
+}
@@ -0,0 +1,654 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use askama::filters::HtmlSafe;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use futures_core::future::BoxFuture;
+use http::{HeaderName, Method, StatusCode};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use subtle::ConstantTimeEq;
+use tower::Service;
+use tracing::warn;
+
+use crate::error::ErrorRepr;
+use crate::headers::is_form_content_type;
+use crate::request::{
+    Request, RequestBodyExt, RequestExt, is_multipart_form_data, peek_first_field,
+};
+use crate::response::Response;
+use crate::session::Session;
+use crate::{Body, Error};
+
+/// The name of the form field the CSRF token is sent in.
+pub const CSRF_TOKEN_FIELD: &str = "csrf_token";
+
+/// The name of the header the CSRF token can be sent in, e.g. by scripts
+/// making requests with `fetch`.
+pub const CSRF_TOKEN_HEADER: HeaderName = HeaderName::from_static("x-csrf-token");
+
+const CSRF_TOKEN_SESSION_KEY: &str = "__cot_csrf_token";
+
+/// The length of the CSRF tokens, in bytes, before encoding.
+const CSRF_TOKEN_LENGTH: usize = 32;
+
+tokio::task_local! {
+    static CURRENT_CSRF_TOKEN: CsrfToken;
+}
+
+/// The CSRF token of the current session, assigned by [`CsrfMiddleware`].
+///
+/// The token must be sent back with every request using an unsafe method
+/// (such as `POST`), either in the [`CSRF_TOKEN_FIELD`] form field or in the
+/// [`CSRF_TOKEN_HEADER`] header. The forms created with the
+/// [`Form`](derive@crate::form::Form) derive macro include the form field
+/// automatically when they're rendered; otherwise, the token can be accessed
+/// in the request handlers by using [`CsrfToken`] as an extractor, and
+/// passed on to the templates, or rendered as a form field with
+/// [`CsrfTokenField`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::CsrfToken;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::{Body, StatusCode};
+///
+/// async fn index(csrf_token: CsrfToken) -> cot::Result<Response> {
+///     Ok(Response::new_html(
+///         StatusCode::OK,
+///         Body::fixed(format!(
+///             r#"<form method="post"><input type="hidden" name="csrf_token" value="{csrf_token}"></form>"#
+///         )),
+///     ))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CsrfToken(Arc<str>);
+
+impl CsrfToken {
+    /// Returns the token as a string.
+    ///
+    /// The token only consists of URL-safe characters, so it doesn't need to
+    /// be escaped in HTML or URLs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CsrfToken;
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(csrf_token: CsrfToken) -> cot::Result<Response> {
+    ///     let csrf_token: &str = csrf_token.as_str();
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn generate() -> Self {
+        thread_local! {
+            static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_os_rng());
+        }
+
+        let mut bytes = [0; CSRF_TOKEN_LENGTH];
+        RNG.with_borrow_mut(|rng| rng.fill_bytes(&mut bytes));
+        Self(Arc::from(URL_SAFE_NO_PAD.encode(bytes)))
+    }
+
+    fn matches(&self, token: &[u8]) -> bool {
+        self.0.as_bytes().ct_eq(token).into()
+    }
+
+    pub(crate) fn try_from_extensions(extensions: &http::Extensions) -> crate::Result<&Self> {
+        extensions
+            .get::<Self>()
+            .ok_or_else(|| Error::new(ErrorRepr::CsrfTokenMissing))
+    }
+
+    /// Returns the token of the request being handled, if [`CsrfMiddleware`]
+    /// is used.
+    pub(crate) fn current() -> Option<Self> {
+        CURRENT_CSRF_TOKEN.try_with(Clone::clone).ok()
+    }
+
+    /// Writes the hidden form field holding the token.
+    pub(crate) fn write_form_field(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<input type=\"hidden\" name=\"{CSRF_TOKEN_FIELD}\" value=\"{}\">",
+            self.0
+        )
+    }
+}
+
+impl Display for CsrfToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The hidden form field holding the CSRF token of the request being
+/// handled.
+///
+/// This renders as nothing if [`CsrfMiddleware`] is not used, so it can be
+/// put in all the forms using unsafe methods (such as `POST`) that don't
+/// render a whole [`FormContext`](crate::form::FormContext), whether the CSRF
+/// protection is enabled or not.
+///
+/// # Examples
+///
+/// ```
+/// use askama::Template;
+/// use cot::middleware::CsrfTokenField;
+///
+/// #[derive(Template)]
+/// #[template(
+///     source = r#"<form method="post">{{ csrf_token_field }}<button>Delete</button></form>"#,
+///     ext = "html"
+/// )]
+/// struct DeleteTemplate {
+///     csrf_token_field: CsrfTokenField,
+/// }
+///
+/// let template = DeleteTemplate {
+///     csrf_token_field: CsrfTokenField,
+/// };
+/// assert_eq!(
+///     template.render()?,
+///     r#"<form method="post"><button>Delete</button></form>"#
+/// );
+/// # Ok::<(), askama::Error>(())
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CsrfTokenField;
+
+impl Display for CsrfTokenField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match CsrfToken::current() {
+            Some(token) => token.write_form_field(f),
+            None => Ok(()),
+        }
+    }
+}
+
+impl HtmlSafe for CsrfTokenField {}
+
+/// A middleware that protects against cross-site request forgery (CSRF).
+///
+/// Each session is assigned a random [`CsrfToken`], and the requests using
+/// unsafe methods (i.e. other than `GET`, `HEAD`, `OPTIONS` and `TRACE`) are
+/// rejected with `403 Forbidden` unless they come with the token, either in
+/// the [`CSRF_TOKEN_FIELD`] form field or in the [`CSRF_TOKEN_HEADER`]
+/// header. Since other sites can't read the token, they can't make the users'
+/// browsers send such requests.
+///
+/// In `multipart/form-data` forms (e.g. the ones uploading files), the token
+/// field must be the first field of the form, so that the uploaded files
+/// don't have to be read before the request reaches its handler. Otherwise,
+/// the request is rejected.
+///
+/// The forms created with the [`Form`](derive@crate::form::Form) derive macro
+/// include the token automatically when they're rendered.
+///
+/// The token is kept in the session, so this middleware must be used with
+/// (and inside of) [`SessionMiddleware`](crate::middleware::SessionMiddleware).
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::{CsrfMiddleware, SessionMiddleware};
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(CsrfMiddleware::new())
+///             .middleware(SessionMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CsrfMiddleware;
+
+impl CsrfMiddleware {
+    /// Creates a new instance of [`CsrfMiddleware`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CsrfMiddleware;
+    ///
+    /// let middleware = CsrfMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> tower::Layer<S> for CsrfMiddleware {
+    type Service = CsrfService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CsrfService { inner }
+    }
+}
+
+/// Service that protects against cross-site request forgery.
+///
+/// Used by [`CsrfMiddleware`].
+#[derive(Debug, Clone)]
+pub struct CsrfService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for CsrfService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let session = Session::try_from_extensions(req.extensions())?.clone();
+            let token = session_token(&session).await?;
+
+            if !is_safe(req.method()) && !has_valid_token(&mut req, &token).await? {
+                warn!(
+                    method = %req.method(),
+                    uri = %req.uri(),
+                    "request rejected: missing or invalid CSRF token"
+                );
+                return Ok(forbidden());
+            }
+
+            req.extensions_mut().insert(token.clone());
+            CURRENT_CSRF_TOKEN.scope(token, inner.call(req)).await
+        })
+    }
+}
+
+async fn session_token(session: &Session) -> crate::Result<CsrfToken> {
+    if let Some(token) = session.get::<String>(CSRF_TOKEN_SESSION_KEY).await? {
+        return Ok(CsrfToken(Arc::from(token)));
+    }
+
+    let token = CsrfToken::generate();
+    session
+        .insert(CSRF_TOKEN_SESSION_KEY, token.as_str())
+        .await?;
+    Ok(token)
+}
+
+fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+async fn has_valid_token(req: &mut Request, token: &CsrfToken) -> crate::Result<bool> {
+    if let Some(header) = req.headers().get(CSRF_TOKEN_HEADER) {
+        return Ok(token.matches(header.as_bytes()));
+    }
+
+    let content_type = req
+        .content_type()
+        .and_then(|content_type| content_type.to_str().ok())
+        .unwrap_or_default();
+    let is_form = is_form_content_type(content_type);
+    let is_multipart = is_multipart_form_data(content_type);
+
+    if is_form {
+        let body = req.buffered().await?;
+        Ok(crate::request::query_pairs(&body)
+            .find(|(name, _)| name == CSRF_TOKEN_FIELD)
+            .is_some_and(|(_, value)| token.matches(value.as_bytes())))
+    } else if is_multipart {
+        // the token has to be the first field, so that the rest of the body
+        // (such as the uploaded files) doesn't have to be read before the
+        // request is routed
+        Ok(peek_first_field(req, CSRF_TOKEN_FIELD)
+            .await
+            .is_some_and(|value| token.matches(&value)))
+    } else {
+        Ok(false)
+    }
+}
+
+fn forbidden() -> Response {
+    http::Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .body(Body::fixed("Forbidden: missing or invalid CSRF token"))
+        .expect("failed to build CSRF error response")
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::form::Form;
+    use crate::response::ResponseExt;
+    use crate::router::{Route, Router, RouterService};
+    use crate::test::TestRequestBuilder;
+
+    async fn echo_token(request: Request) -> crate::Result<Response> {
+        let token = CsrfToken::try_from_extensions(request.extensions())?.clone();
+        assert_eq!(CsrfToken::current(), Some(token.clone()));
+
+        Ok(Response::new_html(
+            StatusCode::OK,
+            Body::fixed(token.as_str().to_owned()),
+        ))
+    }
+
+    async fn session_token_of(request: &Request) -> CsrfToken {
+        session_token(Session::from_request(request)).await.unwrap()
+    }
+
+    #[cot::test]
+    async fn safe_method_gets_token() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let token = session_token_of(&request).await;
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            token.as_str()
+        );
+    }
+
+    #[cot::test]
+    async fn unsafe_method_without_token() {
+        let request = TestRequestBuilder::post("/").with_session().build();
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_header() {
+        let mut request = TestRequestBuilder::post("/").with_session().build();
+        let token = session_token_of(&request).await;
+        request
+            .headers_mut()
+            .insert(CSRF_TOKEN_HEADER, token.as_str().parse().unwrap());
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_invalid_header() {
+        let mut request = TestRequestBuilder::post("/").with_session().build();
+        request
+            .headers_mut()
+            .insert(CSRF_TOKEN_HEADER, "invalid".parse().unwrap());
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_form_field() {
+        let request = TestRequestBuilder::post("/").with_session().build();
+        let token = session_token_of(&request).await;
+        let request = TestRequestBuilder::post("/")
+            .with_session_from(&request)
+            .form_data(&[("name", "test"), (CSRF_TOKEN_FIELD, token.as_str())])
+            .build();
+        let service =
+            CsrfMiddleware::new().layer(tower::service_fn(|request: Request| async move {
+                // the body is still available to the handler
+                let body = request.into_body().into_bytes().await?;
+                Ok::<_, Error>(Response::new_html(StatusCode::OK, Body::fixed(body)))
+            }));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert!(body.starts_with(b"name=test&csrf_token="));
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_form_field_and_charset() {
+        let request = TestRequestBuilder::post("/").with_session().build();
+        let token = session_token_of(&request).await;
+        let mut request = TestRequestBuilder::post("/")
+            .with_session_from(&request)
+            .form_data(&[(CSRF_TOKEN_FIELD, token.as_str())])
+            .build();
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded; charset=UTF-8"
+                .parse()
+                .unwrap(),
+        );
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_multipart_form_field() {
+        let request = TestRequestBuilder::post("/").with_session().build();
+        let token = session_token_of(&request).await;
+        let request = TestRequestBuilder::post("/")
+            .with_session_from(&request)
+            .multipart_field(CSRF_TOKEN_FIELD, token.as_str())
+            .multipart_file("file", "a.txt", "text/plain", "data")
+            .build();
+        let service =
+            CsrfMiddleware::new().layer(tower::service_fn(|mut request: Request| async move {
+                // the whole body, including the token, is still available to
+                // the handler
+                let mut multipart = request.multipart()?;
+                let field = multipart.next_field().await?.expect("field missing");
+                assert_eq!(field.name(), Some(CSRF_TOKEN_FIELD));
+                let field = multipart.next_field().await?.expect("field missing");
+                Ok::<_, Error>(Response::new_html(
+                    StatusCode::OK,
+                    Body::fixed(field.bytes().await?),
+                ))
+            }));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "data");
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_multipart_form_field_not_first() {
+        let request = TestRequestBuilder::post("/").with_session().build();
+        let token = session_token_of(&request).await;
+        let request = TestRequestBuilder::post("/")
+            .with_session_from(&request)
+            .multipart_file("file", "a.txt", "text/plain", "data")
+            .multipart_field(CSRF_TOKEN_FIELD, token.as_str())
+            .build();
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_large_multipart_upload() {
+        // larger than the default `max_buffered_body_size`
+        const FILE_SIZE: usize = 3 * 1024 * 1024;
+
+        async fn upload(mut request: Request) -> crate::Result<Response> {
+            let mut multipart = request.multipart()?;
+            let mut size = 0;
+            while let Some(field) = multipart.next_field().await? {
+                if field.name() == Some("file") {
+                    size = field.into_file().await?.size();
+                }
+            }
+            Ok(Response::new_html(
+                StatusCode::OK,
+                Body::fixed(size.to_string()),
+            ))
+        }
+
+        let router =
+            Router::with_urls([Route::with_handler("/upload", upload).body_limit(4 * 1024 * 1024)]);
+        let service = CsrfMiddleware::new().layer(RouterService::new(Arc::new(router)));
+        let request = TestRequestBuilder::post("/").with_session().build();
+        let token = session_token_of(&request).await;
+        let mut request = TestRequestBuilder::post("/upload")
+            .with_session_from(&request)
+            .multipart_field(CSRF_TOKEN_FIELD, token.as_str())
+            .multipart_file(
+                "file",
+                "a.bin",
+                "application/octet-stream",
+                vec![b'a'; FILE_SIZE],
+            )
+            .build();
+        let body = std::mem::take(request.body_mut())
+            .into_bytes()
+            .await
+            .unwrap();
+        let chunks: Vec<_> = body
+            .chunks(64 * 1024)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        *request.body_mut() = Body::streaming(futures::stream::iter(chunks));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            FILE_SIZE.to_string()
+        );
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_invalid_multipart_form_field() {
+        let request = TestRequestBuilder::post("/")
+            .with_session()
+            .multipart_field(CSRF_TOKEN_FIELD, "invalid")
+            .build();
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[cot::test]
+    async fn unsafe_method_with_invalid_form_field() {
+        let request = TestRequestBuilder::post("/")
+            .with_session()
+            .form_data(&[(CSRF_TOKEN_FIELD, "invalid")])
+            .build();
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[cot::test]
+    async fn requires_session() {
+        let request = TestRequestBuilder::get("/").build();
+        let service = CsrfMiddleware::new().layer(tower::service_fn(echo_token));
+
+        assert!(service.oneshot(request).await.is_err());
+    }
+
+    #[cot::test]
+    async fn token_is_kept_in_session() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+
+        let token = session_token_of(&request).await;
+
+        assert_eq!(session_token_of(&request).await, token);
+        assert_eq!(token.as_str().len(), 43);
+    }
+
+    #[derive(Form)]
+    struct TestForm {
+        name: String,
+    }
+
+    #[cot::test]
+    async fn form_renders_token() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let token = session_token_of(&request).await;
+        let service = CsrfMiddleware::new().layer(tower::service_fn(|_: Request| async {
+            let form = TestForm {
+                name: "test".to_owned(),
+            };
+            Ok::<_, Error>(Response::new_html(
+                StatusCode::OK,
+                Body::fixed(form.to_context().to_string()),
+            ))
+        }));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        let body = response.into_body().into_bytes().await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with(&format!(
+            r#"<input type="hidden" name="csrf_token" value="{token}">"#
+        )));
+    }
+
+    #[test]
+    fn form_without_middleware() {
+        let form = TestForm {
+            name: "test".to_owned(),
+        };
+
+        assert!(!form.to_context().to_string().contains(CSRF_TOKEN_FIELD));
+    }
+
+    #[test]
+    fn no_current_token_outside_middleware() {
+        assert_eq!(CsrfToken::current(), None);
+    }
+}
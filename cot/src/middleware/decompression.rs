@@ -0,0 +1,429 @@
+use std::io::Read;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::future::BoxFuture;
+use http::{HeaderValue, StatusCode, header};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use tower::Service;
+
+use crate::request::Request;
+use crate::response::{Response, ResponseExt};
+use crate::{Body, Error};
+
+const DEFAULT_MAX_SIZE: usize = 2 * 1024 * 1024;
+const SUPPORTED_ENCODINGS: &str = "gzip, deflate, br";
+
+/// A middleware that decompresses request bodies sent with a
+/// `Content-Encoding` header.
+///
+/// The `gzip`, `deflate` and `br` (Brotli) encodings are supported, as well as
+/// any combination of them (e.g. `Content-Encoding: gzip, br`). After the body
+/// is decompressed, the `Content-Encoding` header is removed from the request,
+/// so the extractors and request handlers see a plain, uncompressed body.
+///
+/// To protect against decompression bombs, the size of the decompressed body
+/// is limited (to 2 mebibytes by default, see [`Self::max_size`]). Requests
+/// exceeding the limit are rejected with `413 Payload Too Large`. Requests
+/// using an unsupported encoding are rejected with
+/// `415 Unsupported Media Type`, and requests with a body that can't be
+/// decompressed with `400 Bad Request`.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::RequestDecompressionMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(RequestDecompressionMiddleware::new())
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct RequestDecompressionMiddleware {
+    max_size: usize,
+}
+
+impl RequestDecompressionMiddleware {
+    /// Creates a new instance of [`RequestDecompressionMiddleware`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::RequestDecompressionMiddleware;
+    ///
+    /// let middleware = RequestDecompressionMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// Sets the maximum size of the decompressed request body, in bytes.
+    ///
+    /// The same limit applies to the compressed body as it is read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::RequestDecompressionMiddleware;
+    ///
+    /// let middleware = RequestDecompressionMiddleware::new().max_size(10 * 1024 * 1024);
+    /// ```
+    #[must_use]
+    pub fn max_size(self, max_size: usize) -> Self {
+        Self { max_size }
+    }
+}
+
+impl Default for RequestDecompressionMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for RequestDecompressionMiddleware {
+    type Service = RequestDecompressionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestDecompressionService {
+            inner,
+            max_size: self.max_size,
+        }
+    }
+}
+
+/// Service that decompresses the request body.
+///
+/// Used by [`RequestDecompressionMiddleware`].
+#[derive(Debug, Clone)]
+pub struct RequestDecompressionService<S> {
+    inner: S,
+    max_size: usize,
+}
+
+impl<S> Service<Request> for RequestDecompressionService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let max_size = self.max_size;
+
+        Box::pin(async move {
+            match decompress_request(req, max_size).await {
+                Ok(req) => inner.call(req).await,
+                Err(rejection) => Ok(rejection.into_response()),
+            }
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Encoding {
+    Gzip,
+    Deflate,
+    Brotli,
+}
+
+impl Encoding {
+    /// Parses a content coding name. Returns `None` for the `identity`
+    /// coding, which doesn't need to be decoded.
+    fn from_name(name: &str) -> Result<Option<Self>, Rejection> {
+        match name.to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Ok(Some(Self::Gzip)),
+            "deflate" => Ok(Some(Self::Deflate)),
+            "br" => Ok(Some(Self::Brotli)),
+            "identity" => Ok(None),
+            _ => Err(Rejection::UnsupportedEncoding),
+        }
+    }
+
+    fn decoder<'a>(self, data: &'a [u8]) -> Box<dyn Read + 'a> {
+        const BROTLI_BUFFER_SIZE: usize = 4096;
+
+        match self {
+            Self::Gzip => Box::new(flate2::read::MultiGzDecoder::new(data)),
+            // "deflate" in HTTP actually means the zlib format
+            Self::Deflate => Box::new(flate2::read::ZlibDecoder::new(data)),
+            Self::Brotli => Box::new(brotli::Decompressor::new(data, BROTLI_BUFFER_SIZE)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Rejection {
+    TooLarge,
+    UnsupportedEncoding,
+    InvalidBody,
+}
+
+impl Rejection {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            Self::TooLarge => (StatusCode::PAYLOAD_TOO_LARGE, "Payload Too Large"),
+            Self::UnsupportedEncoding => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Unsupported Content-Encoding",
+            ),
+            Self::InvalidBody => (StatusCode::BAD_REQUEST, "Invalid compressed request body"),
+        };
+
+        let mut builder = Response::builder().status(status);
+        if self == Self::UnsupportedEncoding {
+            builder = builder.header(header::ACCEPT_ENCODING, SUPPORTED_ENCODINGS);
+        }
+        builder
+            .body(Body::fixed(message))
+            .expect("failed to build request decompression error response")
+    }
+}
+
+async fn decompress_request(req: Request, max_size: usize) -> Result<Request, Rejection> {
+    let Some(content_encoding) = req.headers().get(header::CONTENT_ENCODING) else {
+        return Ok(req);
+    };
+    let encodings = parse_encodings(content_encoding)?;
+    if encodings.is_empty() {
+        let (mut parts, body) = req.into_parts();
+        parts.headers.remove(header::CONTENT_ENCODING);
+        return Ok(Request::from_parts(parts, body));
+    }
+
+    let (mut parts, body) = req.into_parts();
+    let compressed = Limited::new(body, max_size)
+        .collect()
+        .await
+        .map_err(|error| {
+            if error.is::<LengthLimitError>() {
+                Rejection::TooLarge
+            } else {
+                Rejection::InvalidBody
+            }
+        })?
+        .to_bytes();
+
+    let decompressed =
+        tokio::task::spawn_blocking(move || decompress(compressed, &encodings, max_size))
+            .await
+            .map_err(|_| Rejection::InvalidBody)??;
+
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.insert(
+        header::CONTENT_LENGTH,
+        HeaderValue::from(decompressed.len()),
+    );
+    Ok(Request::from_parts(parts, Body::fixed(decompressed)))
+}
+
+/// Parses the `Content-Encoding` header into the list of encodings, in the
+/// order they have been applied.
+fn parse_encodings(value: &HeaderValue) -> Result<Vec<Encoding>, Rejection> {
+    let value = value.to_str().map_err(|_| Rejection::UnsupportedEncoding)?;
+
+    let mut encodings = Vec::new();
+    for name in value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        if let Some(encoding) = Encoding::from_name(name)? {
+            encodings.push(encoding);
+        }
+    }
+
+    Ok(encodings)
+}
+
+fn decompress(data: Bytes, encodings: &[Encoding], max_size: usize) -> Result<Bytes, Rejection> {
+    let mut data = data;
+
+    // the encodings are listed in the order they were applied, so they have to
+    // be removed in reverse
+    for encoding in encodings.iter().rev() {
+        let mut decompressed = Vec::new();
+        let limit = u64::try_from(max_size)
+            .unwrap_or(u64::MAX)
+            .saturating_add(1);
+        encoding
+            .decoder(&data)
+            .take(limit)
+            .read_to_end(&mut decompressed)
+            .map_err(|_| Rejection::InvalidBody)?;
+
+        if decompressed.len() > max_size {
+            return Err(Rejection::TooLarge);
+        }
+        data = Bytes::from(decompressed);
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn brotli(data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        {
+            let mut encoder = brotli::CompressorWriter::new(&mut output, 4096, 5, 22);
+            encoder.write_all(data).unwrap();
+        }
+        output
+    }
+
+    fn compressed_request(encoding: &str, body: Vec<u8>) -> Request {
+        let mut request = TestRequestBuilder::post("/").build();
+        request
+            .headers_mut()
+            .insert(header::CONTENT_ENCODING, encoding.parse().unwrap());
+        *request.body_mut() = Body::fixed(body);
+        request
+    }
+
+    async fn echo(middleware: RequestDecompressionMiddleware, request: Request) -> Response {
+        let svc = tower::service_fn(|req: Request| async move {
+            assert!(!req.headers().contains_key(header::CONTENT_ENCODING));
+            let body = req.into_body().into_bytes().await?;
+            Ok::<_, Error>(Response::new(Body::fixed(body)))
+        });
+
+        middleware.layer(svc).oneshot(request).await.unwrap()
+    }
+
+    async fn assert_echoed(response: Response, expected: &str) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), expected);
+    }
+
+    #[cot::test]
+    async fn decompress_gzip() {
+        let request = compressed_request("gzip", gzip(b"hello gzip"));
+
+        let response = echo(RequestDecompressionMiddleware::new(), request).await;
+
+        assert_echoed(response, "hello gzip").await;
+    }
+
+    #[cot::test]
+    async fn decompress_deflate() {
+        let request = compressed_request("deflate", deflate(b"hello deflate"));
+
+        let response = echo(RequestDecompressionMiddleware::new(), request).await;
+
+        assert_echoed(response, "hello deflate").await;
+    }
+
+    #[cot::test]
+    async fn decompress_brotli() {
+        let request = compressed_request("br", brotli(b"hello brotli"));
+
+        let response = echo(RequestDecompressionMiddleware::new(), request).await;
+
+        assert_echoed(response, "hello brotli").await;
+    }
+
+    #[cot::test]
+    async fn decompress_multiple_encodings() {
+        let request = compressed_request("gzip, br", brotli(&gzip(b"hello both")));
+
+        let response = echo(RequestDecompressionMiddleware::new(), request).await;
+
+        assert_echoed(response, "hello both").await;
+    }
+
+    #[cot::test]
+    async fn decompress_identity() {
+        let request = compressed_request("identity", b"hello identity".to_vec());
+
+        let response = echo(RequestDecompressionMiddleware::new(), request).await;
+
+        assert_echoed(response, "hello identity").await;
+    }
+
+    #[cot::test]
+    async fn decompress_no_encoding() {
+        let request = TestRequestBuilder::post("/").build();
+
+        let response = echo(RequestDecompressionMiddleware::new(), request).await;
+
+        assert_echoed(response, "").await;
+    }
+
+    #[cot::test]
+    async fn decompress_unsupported_encoding() {
+        let request = compressed_request("zstd", b"whatever".to_vec());
+
+        let response = echo(RequestDecompressionMiddleware::new(), request).await;
+
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(
+            response.headers()[header::ACCEPT_ENCODING],
+            SUPPORTED_ENCODINGS
+        );
+    }
+
+    #[cot::test]
+    async fn decompress_too_large() {
+        // a highly compressible body that is small when compressed
+        let request = compressed_request("gzip", gzip(&vec![0; 1024 * 1024]));
+
+        let response = echo(
+            RequestDecompressionMiddleware::new().max_size(1024),
+            request,
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cot::test]
+    async fn decompress_invalid_body() {
+        let request = compressed_request("gzip", b"not gzip".to_vec());
+
+        let response = echo(RequestDecompressionMiddleware::new(), request).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,281 @@
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use tower::util::BoxService;
+use tower::{Service, ServiceExt};
+
+use crate::Error;
+use crate::request::Request;
+use crate::response::Response;
+
+/// Creates a middleware from an async function.
+///
+/// The function is called with the request and a [`Next`] object for each
+/// request. It can either pass the request on to the rest of the middleware
+/// stack and the request handler by calling [`Next::run`], or return a
+/// response (or an error) directly, without calling the inner service at all.
+/// It can also modify the request before passing it on, or the response
+/// returned by [`Next::run`].
+///
+/// This is much simpler than implementing [`tower::Layer`] and
+/// [`tower::Service`] by hand, which makes it a good fit for small middlewares
+/// that short-circuit some requests, such as authorization guards,
+/// maintenance mode switches, or caches.
+///
+/// # Examples
+///
+/// An authorization guard rejecting the requests without a valid API key:
+///
+/// ```
+/// use cot::http::StatusCode;
+/// use cot::middleware::{Next, from_fn};
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::request::Request;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::{Body, BoxedHandler, Project};
+///
+/// async fn require_api_key(request: Request, next: Next) -> cot::Result<Response> {
+///     let is_authorized = request
+///         .headers()
+///         .get("x-api-key")
+///         .is_some_and(|key| key == "secret");
+///     if !is_authorized {
+///         return Ok(Response::new_html(
+///             StatusCode::UNAUTHORIZED,
+///             Body::fixed("Unauthorized"),
+///         ));
+///     }
+///
+///     next.run(request).await
+/// }
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler.middleware(from_fn(require_api_key)).build()
+///     }
+/// }
+/// ```
+pub fn from_fn<F, Fut>(f: F) -> FromFnLayer<F>
+where
+    F: Fn(Request, Next) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = crate::Result<Response>> + Send + 'static,
+{
+    FromFnLayer { f }
+}
+
+/// A middleware created from an async function.
+///
+/// Created by [`from_fn`].
+#[derive(Clone)]
+pub struct FromFnLayer<F> {
+    f: F,
+}
+
+impl<F> Debug for FromFnLayer<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FromFnLayer").finish_non_exhaustive()
+    }
+}
+
+impl<F: Clone, S> tower::Layer<S> for FromFnLayer<F> {
+    type Service = FromFnService<F, S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FromFnService {
+            f: self.f.clone(),
+            inner,
+        }
+    }
+}
+
+/// Service that calls an async function for each request.
+///
+/// Used by [`FromFnLayer`].
+#[derive(Clone)]
+pub struct FromFnService<F, S> {
+    f: F,
+    inner: S,
+}
+
+impl<F, S: Debug> Debug for FromFnService<F, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FromFnService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, Fut, S> Service<Request> for FromFnService<F, S>
+where
+    F: Fn(Request, Next) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = crate::Result<Response>> + Send + 'static,
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // the inner service is only polled for readiness when (and if) the
+        // function calls `next.run()`
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let next = Next {
+            inner: BoxService::new(self.inner.clone()),
+        };
+
+        Box::pin((self.f)(req, next))
+    }
+}
+
+/// The rest of the middleware stack and the request handler.
+///
+/// Passed to the function wrapped by [`from_fn`].
+pub struct Next {
+    inner: BoxService<Request, Response, Error>,
+}
+
+impl Next {
+    /// Passes the request on to the rest of the middleware stack and the
+    /// request handler, and returns their response.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by the inner service.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::Next;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    ///
+    /// async fn add_header(request: Request, next: Next) -> cot::Result<Response> {
+    ///     let mut response = next.run(request).await?;
+    ///     response
+    ///         .headers_mut()
+    ///         .insert("x-powered-by", "cot".parse().unwrap());
+    ///     Ok(response)
+    /// }
+    /// ```
+    pub async fn run(self, request: Request) -> crate::Result<Response> {
+        self.inner.oneshot(request).await
+    }
+}
+
+impl Debug for Next {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use http::StatusCode;
+    use tower::Layer;
+
+    use super::*;
+    use crate::Body;
+    use crate::response::ResponseExt;
+    use crate::test::TestRequestBuilder;
+
+    async fn require_api_key(request: Request, next: Next) -> crate::Result<Response> {
+        if request.headers().get("x-api-key").is_none() {
+            return Ok(Response::new_html(
+                StatusCode::UNAUTHORIZED,
+                Body::fixed("Unauthorized"),
+            ));
+        }
+
+        next.run(request).await
+    }
+
+    fn inner_service(
+        called: Arc<AtomicBool>,
+    ) -> impl Service<Request, Response = Response, Error = Error, Future: Send> + Clone + Send + 'static
+    {
+        tower::service_fn(move |_req: Request| {
+            let called = Arc::clone(&called);
+            async move {
+                called.store(true, Ordering::SeqCst);
+                Ok::<_, Error>(Response::new_html(StatusCode::OK, Body::fixed("OK")))
+            }
+        })
+    }
+
+    #[cot::test]
+    async fn from_fn_short_circuit() {
+        let called = Arc::new(AtomicBool::new(false));
+        let service = from_fn(require_api_key).layer(inner_service(Arc::clone(&called)));
+
+        let response = service
+            .oneshot(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(!called.load(Ordering::SeqCst));
+    }
+
+    #[cot::test]
+    async fn from_fn_calls_next() {
+        let called = Arc::new(AtomicBool::new(false));
+        let service = from_fn(require_api_key).layer(inner_service(Arc::clone(&called)));
+        let mut request = TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert("x-api-key", "secret".parse().unwrap());
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(called.load(Ordering::SeqCst));
+    }
+
+    #[cot::test]
+    async fn from_fn_modifies_response() {
+        let called = Arc::new(AtomicBool::new(false));
+        let service = from_fn(|request: Request, next: Next| async move {
+            let mut response = next.run(request).await?;
+            response
+                .headers_mut()
+                .insert("x-test", "value".parse().unwrap());
+            Ok(response)
+        })
+        .layer(inner_service(called));
+
+        let response = service
+            .oneshot(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()["x-test"], "value");
+    }
+
+    #[cot::test]
+    async fn from_fn_returns_error() {
+        let called = Arc::new(AtomicBool::new(false));
+        let service = from_fn(|_request: Request, _next: Next| async move {
+            Err::<Response, _>(Error::not_found())
+        })
+        .layer(inner_service(Arc::clone(&called)));
+
+        let result = service.oneshot(TestRequestBuilder::get("/").build()).await;
+
+        assert!(result.is_err());
+        assert!(!called.load(Ordering::SeqCst));
+    }
+}
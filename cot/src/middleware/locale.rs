@@ -0,0 +1,250 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::{HeaderMap, HeaderValue, header};
+use tower::Service;
+use tracing::warn;
+
+use crate::Error;
+use crate::locale::{Locale, negotiate};
+use crate::request::Request;
+use crate::response::Response;
+
+/// A middleware that resolves the [`Locale`] of each request and tags the
+/// responses with it.
+///
+/// The locale is chosen from the locales supported by the project based on
+/// the `Accept-Language` header of the request, and is made available to the
+/// request handlers with
+/// [`RequestExt::locale`](crate::request::RequestExt::locale) and the
+/// [`Locale`] extractor. The responses get a `Content-Language` header
+/// (unless the handler has already set one) and `Accept-Language` is added
+/// to their `Vary` header, so that caches keep a separate copy for every
+/// language.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::LocaleMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(LocaleMiddleware::new(["en", "pl", "pt-BR"]))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocaleMiddleware {
+    supported: Arc<[Locale]>,
+}
+
+impl LocaleMiddleware {
+    /// Creates a new [`LocaleMiddleware`] with the given supported locales.
+    ///
+    /// The first locale is the default one, used when none of the locales
+    /// requested by the client is supported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `supported` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::LocaleMiddleware;
+    ///
+    /// let middleware = LocaleMiddleware::new(["en", "de"]);
+    /// ```
+    #[must_use]
+    pub fn new<I, L>(supported: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: Into<String>,
+    {
+        let supported: Arc<[Locale]> = supported.into_iter().map(Locale::new).collect();
+        assert!(
+            !supported.is_empty(),
+            "at least one supported locale is required"
+        );
+
+        Self { supported }
+    }
+}
+
+impl<S> tower::Layer<S> for LocaleMiddleware {
+    type Service = LocaleService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LocaleService {
+            inner,
+            supported: Arc::clone(&self.supported),
+        }
+    }
+}
+
+/// Service that resolves the locale of the requests and tags the responses
+/// with it.
+///
+/// Used by [`LocaleMiddleware`].
+#[derive(Debug, Clone)]
+pub struct LocaleService<S> {
+    inner: S,
+    supported: Arc<[Locale]>,
+}
+
+impl<S> Service<Request> for LocaleService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let locale = negotiate(req.headers(), &self.supported).clone();
+        req.extensions_mut().insert(locale.clone());
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+            tag_response(response.headers_mut(), &locale);
+            Ok(response)
+        })
+    }
+}
+
+fn tag_response(headers: &mut HeaderMap, locale: &Locale) {
+    if !headers.contains_key(header::CONTENT_LANGUAGE) {
+        if let Ok(value) = HeaderValue::from_str(locale.as_str()) {
+            headers.insert(header::CONTENT_LANGUAGE, value);
+        } else {
+            warn!("Locale `{locale}` is not a valid header value");
+        }
+    }
+
+    let varies_by_language = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|name| name == "*" || name.eq_ignore_ascii_case("accept-language"));
+    if !varies_by_language {
+        headers.append(
+            header::VARY,
+            HeaderValue::from_static(header::ACCEPT_LANGUAGE.as_str()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::request::RequestExt;
+    use crate::response::ResponseExt;
+    use crate::test::TestRequestBuilder;
+
+    async fn localized_response(request: Request) -> Result<Response, Error> {
+        let locale = request.locale().unwrap().clone();
+        Ok(Response::new_html(
+            StatusCode::OK,
+            Body::fixed(format!("locale: {locale}")),
+        ))
+    }
+
+    fn request(accept_language: &str) -> Request {
+        let mut request = TestRequestBuilder::get("/").build();
+        request.headers_mut().insert(
+            header::ACCEPT_LANGUAGE,
+            HeaderValue::from_str(accept_language).unwrap(),
+        );
+        request
+    }
+
+    #[cot::test]
+    async fn locale_middleware_sets_headers() {
+        let service =
+            LocaleMiddleware::new(["en", "pl"]).layer(tower::service_fn(localized_response));
+
+        let response = service.oneshot(request("pl-PL, en;q=0.5")).await.unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "pl");
+        assert_eq!(response.headers()[header::VARY], "accept-language");
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "locale: pl"
+        );
+    }
+
+    #[cot::test]
+    async fn locale_middleware_default_locale() {
+        let service =
+            LocaleMiddleware::new(["en", "pl"]).layer(tower::service_fn(localized_response));
+
+        let response = service.oneshot(request("de")).await.unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "en");
+    }
+
+    #[cot::test]
+    async fn locale_middleware_keeps_handler_headers() {
+        let service =
+            LocaleMiddleware::new(["en", "pl"]).layer(tower::service_fn(|_req: Request| async {
+                let mut response = Response::new_html(StatusCode::OK, Body::empty());
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_LANGUAGE, HeaderValue::from_static("de"));
+                response
+                    .headers_mut()
+                    .insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+                Ok::<_, Error>(response)
+            }));
+
+        let response = service.oneshot(request("pl")).await.unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "de");
+        let vary: Vec<_> = response.headers().get_all(header::VARY).iter().collect();
+        assert_eq!(vary, ["Accept-Encoding", "accept-language"]);
+    }
+
+    #[test]
+    fn tag_response_does_not_duplicate_vary() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::VARY,
+            HeaderValue::from_static("Cookie, Accept-Language"),
+        );
+
+        tag_response(&mut headers, &Locale::new("en"));
+
+        assert_eq!(headers.get_all(header::VARY).iter().count(), 1);
+        assert_eq!(headers[header::CONTENT_LANGUAGE], "en");
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one supported locale is required")]
+    fn locale_middleware_requires_locales() {
+        let _ = LocaleMiddleware::new(Vec::<String>::new());
+    }
+}
@@ -0,0 +1,537 @@
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use http::header::{CACHE_CONTROL, RETRY_AFTER};
+use http::{HeaderValue, StatusCode};
+use tower::Service;
+use tracing::error;
+
+use crate::config::{IpNetwork, MaintenanceMiddlewareConfig};
+use crate::project::{ErrorPageHandler, MiddlewareContext};
+use crate::request::{Request, RequestExt};
+use crate::response::{Response, ResponseExt};
+use crate::{Body, Error};
+
+const DEFAULT_MAINTENANCE_PAGE: &str = include_str!("../../templates/503.html");
+
+/// A middleware that responds to all requests with a
+/// `503 Service Unavailable` maintenance page while the maintenance mode is
+/// enabled.
+///
+/// The maintenance mode is enabled either statically, in the config or with
+/// [`Self::enabled`], or at runtime, by creating the
+/// [flag file](Self::flag_file). The latter makes it possible to put the site
+/// into maintenance mode for the duration of a deployment or a database
+/// migration without restarting the server.
+///
+/// The requests to the [allowed paths](Self::allowed_paths) (such as health
+/// checks) and from the [allowed IP addresses](Self::allowed_ips) (such as
+/// the addresses of the administrators) are still passed to the request
+/// handlers. The maintenance page can be customized with
+/// [`Self::page_handler`].
+///
+/// The middleware can be configured with the builder methods or in the
+/// `[middlewares.maintenance]` section of the config (see
+/// [`MaintenanceMiddlewareConfig`]).
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::MaintenanceMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(MaintenanceMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MaintenanceMiddleware {
+    enabled: bool,
+    flag_file: Option<PathBuf>,
+    allowed_paths: Vec<String>,
+    allowed_ips: Vec<IpNetwork>,
+    retry_after: Option<Duration>,
+    page_handler: Arc<dyn ErrorPageHandler>,
+}
+
+impl Debug for MaintenanceMiddleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MaintenanceMiddleware")
+            .field("enabled", &self.enabled)
+            .field("flag_file", &self.flag_file)
+            .field("allowed_paths", &self.allowed_paths)
+            .field("allowed_ips", &self.allowed_ips)
+            .field("retry_after", &self.retry_after)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MaintenanceMiddleware {
+    /// Creates a new instance of [`MaintenanceMiddleware`] with the
+    /// maintenance mode disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::MaintenanceMiddleware;
+    ///
+    /// let middleware = MaintenanceMiddleware::new().enabled(true);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_config(&MaintenanceMiddlewareConfig::default())
+    }
+
+    /// Creates a new instance of [`MaintenanceMiddleware`] using the
+    /// `[middlewares.maintenance]` section of the project config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::MaintenanceMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(MaintenanceMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::from_config(&context.config().middlewares.maintenance)
+    }
+
+    fn from_config(config: &MaintenanceMiddlewareConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            flag_file: config.flag_file.clone(),
+            allowed_paths: config.allowed_paths.clone(),
+            allowed_ips: config.allowed_ips.clone(),
+            retry_after: config.retry_after,
+            page_handler: Arc::new(DefaultMaintenanceHandler),
+        }
+    }
+
+    /// Sets whether the maintenance mode is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::MaintenanceMiddleware;
+    ///
+    /// let middleware = MaintenanceMiddleware::new().enabled(true);
+    /// ```
+    #[must_use]
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Sets the path of a file that enables the maintenance mode while it
+    /// exists. The file is checked on every request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::MaintenanceMiddleware;
+    ///
+    /// let middleware = MaintenanceMiddleware::new().flag_file("/var/run/myapp/maintenance");
+    /// ```
+    #[must_use]
+    pub fn flag_file(mut self, flag_file: impl Into<PathBuf>) -> Self {
+        self.flag_file = Some(flag_file.into());
+        self
+    }
+
+    /// Sets the path prefixes that are still served when the maintenance mode
+    /// is enabled.
+    ///
+    /// A prefix matches the path itself and all the paths below it, so
+    /// `/health` matches `/health` and `/health/db`, but not `/healthz`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::MaintenanceMiddleware;
+    ///
+    /// let middleware = MaintenanceMiddleware::new().allowed_paths(["/health", "/admin"]);
+    /// ```
+    #[must_use]
+    pub fn allowed_paths<I>(mut self, allowed_paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.allowed_paths = allowed_paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the IP addresses of the clients that can still use the site when
+    /// the maintenance mode is enabled, given as single addresses or address
+    /// ranges.
+    ///
+    /// The address of the client is read with
+    /// [`RequestExt::peer_addr`](crate::request::RequestExt::peer_addr), so
+    /// [`ProxyHeadersMiddleware`](crate::middleware::ProxyHeadersMiddleware)
+    /// should be added before this middleware when running behind a reverse
+    /// proxy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::IpNetwork;
+    /// use cot::middleware::MaintenanceMiddleware;
+    ///
+    /// let middleware =
+    ///     MaintenanceMiddleware::new().allowed_ips(["192.168.0.0/16".parse::<IpNetwork>()?]);
+    /// # Ok::<(), cot::config::IpNetworkError>(())
+    /// ```
+    #[must_use]
+    pub fn allowed_ips<I>(mut self, allowed_ips: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<IpNetwork>,
+    {
+        self.allowed_ips = allowed_ips.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets how long the clients should wait before trying again, sent in the
+    /// `Retry-After` header of the maintenance responses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::MaintenanceMiddleware;
+    ///
+    /// let middleware = MaintenanceMiddleware::new().retry_after(Some(Duration::from_secs(600)));
+    /// ```
+    #[must_use]
+    pub fn retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Sets the handler rendering the maintenance page.
+    ///
+    /// The status code of the response is always set to
+    /// `503 Service Unavailable`. If the handler fails, the error is logged
+    /// and the default maintenance page is returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::MaintenanceMiddleware;
+    /// use cot::project::ErrorPageHandler;
+    /// use cot::response::{Response, ResponseExt};
+    /// use cot::{Body, StatusCode};
+    ///
+    /// struct MaintenancePage;
+    /// impl ErrorPageHandler for MaintenancePage {
+    ///     fn handle(&self) -> cot::Result<Response> {
+    ///         Ok(Response::new_html(
+    ///             StatusCode::SERVICE_UNAVAILABLE,
+    ///             Body::fixed("We'll be back soon!"),
+    ///         ))
+    ///     }
+    /// }
+    ///
+    /// let middleware = MaintenanceMiddleware::new().page_handler(MaintenancePage);
+    /// ```
+    #[must_use]
+    pub fn page_handler(mut self, page_handler: impl ErrorPageHandler + 'static) -> Self {
+        self.page_handler = Arc::new(page_handler);
+        self
+    }
+
+    fn is_allowed(&self, request: &Request) -> bool {
+        let path = request.uri().path();
+        let path_allowed = self.allowed_paths.iter().any(|prefix| {
+            path.strip_prefix(prefix.as_str()).is_some_and(|rest| {
+                rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/')
+            })
+        });
+        if path_allowed {
+            return true;
+        }
+
+        request.peer_addr().is_some_and(|addr| {
+            self.allowed_ips
+                .iter()
+                .any(|network| network.contains(addr.ip()))
+        })
+    }
+
+    async fn is_active(&self) -> bool {
+        if self.enabled {
+            return true;
+        }
+
+        match &self.flag_file {
+            Some(flag_file) => tokio::fs::try_exists(flag_file).await.unwrap_or(false),
+            None => false,
+        }
+    }
+
+    fn maintenance_response(&self) -> Response {
+        let mut response = self.page_handler.handle().unwrap_or_else(|error| {
+            error!(
+                ?error,
+                "Error occurred while rendering the maintenance page; using the default one"
+            );
+            default_maintenance_page()
+        });
+
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        let headers = response.headers_mut();
+        if let Some(retry_after) = self.retry_after {
+            headers.insert(RETRY_AFTER, HeaderValue::from(retry_after.as_secs()));
+        }
+        headers
+            .entry(CACHE_CONTROL)
+            .or_insert(HeaderValue::from_static("no-store"));
+
+        response
+    }
+}
+
+impl Default for MaintenanceMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for MaintenanceMiddleware {
+    type Service = MaintenanceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MaintenanceService {
+            inner,
+            middleware: Arc::new(self.clone()),
+        }
+    }
+}
+
+/// Service that responds to all requests with a maintenance page while the
+/// maintenance mode is enabled.
+///
+/// Used by [`MaintenanceMiddleware`].
+#[derive(Debug, Clone)]
+pub struct MaintenanceService<S> {
+    inner: S,
+    middleware: Arc<MaintenanceMiddleware>,
+}
+
+impl<S> Service<Request> for MaintenanceService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let middleware = Arc::clone(&self.middleware);
+
+        Box::pin(async move {
+            if !middleware.is_allowed(&req) && middleware.is_active().await {
+                return Ok(middleware.maintenance_response());
+            }
+
+            inner.call(req).await
+        })
+    }
+}
+
+struct DefaultMaintenanceHandler;
+impl ErrorPageHandler for DefaultMaintenanceHandler {
+    fn handle(&self) -> crate::Result<Response> {
+        Ok(default_maintenance_page())
+    }
+}
+
+fn default_maintenance_page() -> Response {
+    Response::new_html(
+        StatusCode::SERVICE_UNAVAILABLE,
+        Body::fixed(DEFAULT_MAINTENANCE_PAGE),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::server::RemoteAddr;
+    use crate::test::TestRequestBuilder;
+
+    async fn respond(middleware: MaintenanceMiddleware, request: Request) -> Response {
+        let svc = tower::service_fn(|_req: Request| async {
+            Ok::<_, Error>(Response::new(Body::fixed("handler")))
+        });
+
+        middleware.layer(svc).oneshot(request).await.unwrap()
+    }
+
+    fn request_from(path: &str, addr: &str) -> Request {
+        let mut request = TestRequestBuilder::get(path).build();
+        request
+            .extensions_mut()
+            .insert(RemoteAddr(addr.parse::<SocketAddr>().unwrap()));
+        request
+    }
+
+    #[cot::test]
+    async fn maintenance_disabled() {
+        let response = respond(
+            MaintenanceMiddleware::new(),
+            TestRequestBuilder::get("/").build(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn maintenance_enabled() {
+        let response = respond(
+            MaintenanceMiddleware::new()
+                .enabled(true)
+                .retry_after(Some(Duration::from_secs(600))),
+            TestRequestBuilder::get("/").build(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[RETRY_AFTER], "600");
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-store");
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            DEFAULT_MAINTENANCE_PAGE
+        );
+    }
+
+    #[cot::test]
+    async fn maintenance_allowed_paths() {
+        let middleware = MaintenanceMiddleware::new()
+            .enabled(true)
+            .allowed_paths(["/health", "/static/"]);
+
+        for (path, status) in [
+            ("/health", StatusCode::OK),
+            ("/health/db", StatusCode::OK),
+            ("/static/style.css", StatusCode::OK),
+            ("/healthz", StatusCode::SERVICE_UNAVAILABLE),
+            ("/", StatusCode::SERVICE_UNAVAILABLE),
+        ] {
+            let response = respond(middleware.clone(), TestRequestBuilder::get(path).build()).await;
+            assert_eq!(response.status(), status, "{path}");
+        }
+    }
+
+    #[cot::test]
+    async fn maintenance_allowed_ips() {
+        let middleware = MaintenanceMiddleware::new()
+            .enabled(true)
+            .allowed_ips(["10.0.0.0/8".parse::<IpNetwork>().unwrap()]);
+
+        let response = respond(middleware.clone(), request_from("/", "10.1.2.3:1234")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = respond(middleware, request_from("/", "192.0.2.1:1234")).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn maintenance_flag_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let flag_file = dir.path().join("maintenance");
+        let middleware = MaintenanceMiddleware::new().flag_file(&flag_file);
+
+        let response = respond(middleware.clone(), TestRequestBuilder::get("/").build()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        std::fs::write(&flag_file, "").unwrap();
+        let response = respond(middleware.clone(), TestRequestBuilder::get("/").build()).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        std::fs::remove_file(&flag_file).unwrap();
+        let response = respond(middleware, TestRequestBuilder::get("/").build()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn maintenance_page_handler() {
+        struct CustomPage;
+        impl ErrorPageHandler for CustomPage {
+            fn handle(&self) -> crate::Result<Response> {
+                Ok(Response::new_html(StatusCode::OK, Body::fixed("custom")))
+            }
+        }
+
+        struct FailingPage;
+        impl ErrorPageHandler for FailingPage {
+            fn handle(&self) -> crate::Result<Response> {
+                Err(Error::custom("rendering failed"))
+            }
+        }
+
+        let response = respond(
+            MaintenanceMiddleware::new()
+                .enabled(true)
+                .page_handler(CustomPage),
+            TestRequestBuilder::get("/").build(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "custom");
+
+        let response = respond(
+            MaintenanceMiddleware::new()
+                .enabled(true)
+                .page_handler(FailingPage),
+            TestRequestBuilder::get("/").build(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            DEFAULT_MAINTENANCE_PAGE
+        );
+    }
+}
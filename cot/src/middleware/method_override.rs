@@ -0,0 +1,373 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::{HeaderName, Method};
+use tower::Service;
+
+use crate::Error;
+use crate::headers::is_form_content_type;
+use crate::project::MiddlewareContext;
+use crate::request::{Request, RequestBodyExt, RequestExt};
+use crate::response::Response;
+
+const METHOD_OVERRIDE_HEADER: HeaderName = HeaderName::from_static("x-http-method-override");
+const METHOD_OVERRIDE_FIELD: &str = "_method";
+
+/// A middleware that allows `POST` requests to be handled as a different HTTP
+/// method.
+///
+/// HTML forms can only send `GET` and `POST` requests. This middleware makes
+/// it possible to send e.g. a `DELETE` request from a form by either setting
+/// the `X-HTTP-Method-Override` header or adding a `_method` field to the form
+/// (which is only read from `application/x-www-form-urlencoded` bodies). The
+/// header takes precedence over the form field.
+///
+/// For safety, only `POST` requests are ever rewritten, and only to one of
+/// the allowed methods (`PUT`, `PATCH` and `DELETE` by default). Any other
+/// override value is ignored and the request is passed on unchanged.
+///
+/// This middleware needs to run before the request is routed.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::MethodOverrideMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(MethodOverrideMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+///
+/// The form can then look like this:
+///
+/// ```html
+/// <form method="post" action="/items/1">
+///     <input type="hidden" name="_method" value="DELETE">
+///     <button type="submit">Delete</button>
+/// </form>
+/// ```
+#[derive(Debug, Clone)]
+pub struct MethodOverrideMiddleware {
+    allowed_methods: Arc<[Method]>,
+}
+
+impl MethodOverrideMiddleware {
+    /// Creates a new instance of [`MethodOverrideMiddleware`] allowing `PUT`,
+    /// `PATCH` and `DELETE` overrides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::MethodOverrideMiddleware;
+    ///
+    /// let middleware = MethodOverrideMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            allowed_methods: Arc::new([Method::PUT, Method::PATCH, Method::DELETE]),
+        }
+    }
+
+    /// Creates a new instance of [`MethodOverrideMiddleware`] from the
+    /// application context.
+    ///
+    /// The allowed methods are read from the
+    /// [`MethodOverrideMiddlewareConfig`](crate::config::MethodOverrideMiddlewareConfig).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::MethodOverrideMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(MethodOverrideMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::new().allowed_methods(
+            context
+                .config()
+                .middlewares
+                .method_override
+                .allowed_methods
+                .clone(),
+        )
+    }
+
+    /// Sets the HTTP methods a `POST` request is allowed to be overridden
+    /// with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::Method;
+    /// use cot::middleware::MethodOverrideMiddleware;
+    ///
+    /// let middleware = MethodOverrideMiddleware::new().allowed_methods([Method::DELETE]);
+    /// ```
+    #[must_use]
+    pub fn allowed_methods<I>(self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        Self {
+            allowed_methods: methods.into_iter().collect(),
+        }
+    }
+}
+
+impl Default for MethodOverrideMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for MethodOverrideMiddleware {
+    type Service = MethodOverrideService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MethodOverrideService {
+            inner,
+            allowed_methods: Arc::clone(&self.allowed_methods),
+        }
+    }
+}
+
+/// Service that rewrites the method of overridden `POST` requests.
+///
+/// Used by [`MethodOverrideMiddleware`].
+#[derive(Debug, Clone)]
+pub struct MethodOverrideService<S> {
+    inner: S,
+    allowed_methods: Arc<[Method]>,
+}
+
+impl<S> Service<Request> for MethodOverrideService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let allowed_methods = Arc::clone(&self.allowed_methods);
+
+        Box::pin(async move {
+            let mut req = req;
+            if req.method() == Method::POST {
+                let method = override_method(&mut req).await?;
+
+                if let Some(method) = method.filter(|method| allowed_methods.contains(method)) {
+                    *req.method_mut() = method;
+                }
+            }
+
+            inner.call(req).await
+        })
+    }
+}
+
+/// Reads the override method from the header or the form body.
+///
+/// If the body has to be read to find the `_method` field, it is buffered so
+/// that the handler can still access the form.
+async fn override_method(req: &mut Request) -> crate::Result<Option<Method>> {
+    if let Some(value) = req.headers().get(METHOD_OVERRIDE_HEADER) {
+        return Ok(parse_method(value.as_bytes()));
+    }
+
+    let is_form = req
+        .content_type()
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(is_form_content_type);
+    if !is_form {
+        return Ok(None);
+    }
+
+    let bytes = req.buffered().await?;
+    let method = form_urlencoded::parse(&bytes)
+        .find(|(key, _)| key == METHOD_OVERRIDE_FIELD)
+        .and_then(|(_, value)| parse_method(value.as_bytes()));
+
+    Ok(method)
+}
+
+fn parse_method(value: &[u8]) -> Option<Method> {
+    Method::from_bytes(value.trim_ascii().to_ascii_uppercase().as_slice()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::test::TestRequestBuilder;
+
+    async fn method_after_override(
+        middleware: MethodOverrideMiddleware,
+        request: Request,
+    ) -> Method {
+        let svc = tower::service_fn(|req: Request| async move {
+            Ok::<_, Error>(Response::new(Body::fixed(req.method().as_str().to_owned())))
+        });
+
+        let response = middleware.layer(svc).oneshot(request).await.unwrap();
+        let body = response.into_body().into_bytes().await.unwrap();
+        Method::from_bytes(&body).unwrap()
+    }
+
+    fn post_with_header(value: &str) -> Request {
+        let mut request = TestRequestBuilder::post("/").build();
+        request
+            .headers_mut()
+            .insert(METHOD_OVERRIDE_HEADER, value.parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn method_override_header() {
+        let method =
+            method_after_override(MethodOverrideMiddleware::new(), post_with_header("DELETE"))
+                .await;
+
+        assert_eq!(method, Method::DELETE);
+    }
+
+    #[tokio::test]
+    async fn method_override_header_case_insensitive() {
+        let method =
+            method_after_override(MethodOverrideMiddleware::new(), post_with_header("patch")).await;
+
+        assert_eq!(method, Method::PATCH);
+    }
+
+    #[tokio::test]
+    async fn method_override_form_field() {
+        let request = TestRequestBuilder::post("/")
+            .form_data(&[("name", "test"), ("_method", "PUT")])
+            .build();
+
+        let svc = tower::service_fn(|req: Request| async move {
+            assert_eq!(req.method(), Method::PUT);
+            let body = req.into_body().into_bytes().await?;
+            assert_eq!(body, "name=test&_method=PUT");
+            Ok::<_, Error>(Response::new(Body::empty()))
+        });
+
+        MethodOverrideMiddleware::new()
+            .layer(svc)
+            .oneshot(request)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn method_override_form_field_with_charset() {
+        let mut request = TestRequestBuilder::post("/")
+            .form_data(&[("_method", "DELETE")])
+            .build();
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded; charset=UTF-8"
+                .parse()
+                .unwrap(),
+        );
+
+        let method = method_after_override(MethodOverrideMiddleware::new(), request).await;
+
+        assert_eq!(method, Method::DELETE);
+    }
+
+    #[tokio::test]
+    async fn method_override_header_takes_precedence() {
+        let mut request = TestRequestBuilder::post("/")
+            .form_data(&[("_method", "PUT")])
+            .build();
+        request
+            .headers_mut()
+            .insert(METHOD_OVERRIDE_HEADER, "DELETE".parse().unwrap());
+
+        let method = method_after_override(MethodOverrideMiddleware::new(), request).await;
+
+        assert_eq!(method, Method::DELETE);
+    }
+
+    #[tokio::test]
+    async fn method_override_only_post() {
+        let mut request = TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert(METHOD_OVERRIDE_HEADER, "DELETE".parse().unwrap());
+
+        let method = method_after_override(MethodOverrideMiddleware::new(), request).await;
+
+        assert_eq!(method, Method::GET);
+    }
+
+    #[tokio::test]
+    async fn method_override_not_allowed() {
+        let method =
+            method_after_override(MethodOverrideMiddleware::new(), post_with_header("CONNECT"))
+                .await;
+        assert_eq!(method, Method::POST);
+
+        let middleware = MethodOverrideMiddleware::new().allowed_methods([Method::PUT]);
+        let method = method_after_override(middleware, post_with_header("DELETE")).await;
+        assert_eq!(method, Method::POST);
+    }
+
+    #[tokio::test]
+    async fn method_override_invalid_value() {
+        let method =
+            method_after_override(MethodOverrideMiddleware::new(), post_with_header("DEL ETE"))
+                .await;
+
+        assert_eq!(method, Method::POST);
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn method_override_ignores_non_form_body() {
+        let request = TestRequestBuilder::post("/")
+            .json(&serde_json::json!({"_method": "DELETE"}))
+            .build();
+
+        let method = method_after_override(MethodOverrideMiddleware::new(), request).await;
+
+        assert_eq!(method, Method::POST);
+    }
+}
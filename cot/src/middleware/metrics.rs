@@ -0,0 +1,466 @@
+use std::fmt::{Debug, Formatter};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::future::BoxFuture;
+use http::{Method, StatusCode, Uri};
+use http_body::{Frame, SizeHint};
+use http_body_util::combinators::BoxBody;
+use tower::Service;
+
+use crate::request::Request;
+use crate::response::Response;
+use crate::{Body, Error};
+
+type CompleteCallback = Arc<dyn Fn(&RequestSummary) + Send + Sync>;
+
+/// A middleware that counts the bytes of the request and response bodies.
+///
+/// The number of request body bytes read so far is available in the handlers
+/// with [`RequestExt::bytes_read`](crate::request::RequestExt::bytes_read).
+/// Once the response body has been sent (or the connection is closed before
+/// that), the callback registered with [`Self::on_complete`] is called with
+/// a [`RequestSummary`] containing the final byte counts.
+///
+/// The bytes are counted at the body level, so streaming bodies are counted
+/// correctly, too. The counts only include the bodies, not the headers. To
+/// count the bytes that are actually sent over the wire (e.g. after the
+/// response has been compressed), this middleware should be added as the last
+/// one, so that it wraps all the others.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::BodyMetricsMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(BodyMetricsMiddleware::new().on_complete(|summary| {
+///                 println!(
+///                     "{} {}: {} bytes in, {} bytes out",
+///                     summary.method(),
+///                     summary.uri(),
+///                     summary.bytes_read(),
+///                     summary.bytes_written()
+///                 );
+///             }))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct BodyMetricsMiddleware {
+    on_complete: Option<CompleteCallback>,
+}
+
+impl BodyMetricsMiddleware {
+    /// Creates a new instance of [`BodyMetricsMiddleware`] without a
+    /// completion callback.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BodyMetricsMiddleware;
+    ///
+    /// let middleware = BodyMetricsMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the callback called after the response for each request has been
+    /// completed.
+    ///
+    /// The callback is called when the response body has been fully sent or
+    /// dropped (e.g. because the client has disconnected), so it should be
+    /// fast and must not block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// use cot::middleware::BodyMetricsMiddleware;
+    ///
+    /// let total_bytes = Arc::new(AtomicU64::new(0));
+    /// let middleware = BodyMetricsMiddleware::new().on_complete(move |summary| {
+    ///     total_bytes.fetch_add(
+    ///         summary.bytes_read() + summary.bytes_written(),
+    ///         Ordering::Relaxed,
+    ///     );
+    /// });
+    /// ```
+    #[must_use]
+    pub fn on_complete<F>(self, callback: F) -> Self
+    where
+        F: Fn(&RequestSummary) + Send + Sync + 'static,
+    {
+        Self {
+            on_complete: Some(Arc::new(callback)),
+        }
+    }
+}
+
+impl Debug for BodyMetricsMiddleware {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BodyMetricsMiddleware")
+            .field("on_complete", &self.on_complete.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl<S> tower::Layer<S> for BodyMetricsMiddleware {
+    type Service = BodyMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyMetricsService {
+            inner,
+            on_complete: self.on_complete.clone(),
+        }
+    }
+}
+
+/// Service that counts the bytes of the request and response bodies.
+///
+/// Used by [`BodyMetricsMiddleware`].
+#[derive(Clone)]
+pub struct BodyMetricsService<S> {
+    inner: S,
+    on_complete: Option<CompleteCallback>,
+}
+
+impl<S: Debug> Debug for BodyMetricsService<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BodyMetricsService")
+            .field("inner", &self.inner)
+            .field("on_complete", &self.on_complete.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl<S> Service<Request> for BodyMetricsService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let on_complete = self.on_complete.clone();
+
+        let bytes_read = BytesRead::default();
+        let method = req.method().clone();
+        let uri = req.uri().clone();
+        let (mut parts, body) = req.into_parts();
+        parts.extensions.insert(bytes_read.clone());
+        let body = Body::wrapper(BoxBody::new(CountingBody::new(
+            body,
+            Arc::clone(&bytes_read.0),
+            None,
+        )));
+        let req = Request::from_parts(parts, body);
+
+        Box::pin(async move {
+            let result = inner.call(req).await;
+
+            let Some(on_complete) = on_complete else {
+                return result;
+            };
+            let mut completion = Completion {
+                on_complete,
+                method,
+                uri,
+                status: None,
+                bytes_read: bytes_read.0,
+            };
+
+            match result {
+                Ok(response) => {
+                    completion.status = Some(response.status());
+                    Ok(response.map(|body| {
+                        Body::wrapper(BoxBody::new(CountingBody::new(
+                            body,
+                            Arc::new(AtomicU64::new(0)),
+                            Some(completion),
+                        )))
+                    }))
+                }
+                Err(error) => {
+                    completion.complete(0);
+                    Err(error)
+                }
+            }
+        })
+    }
+}
+
+/// The summary of a completed request, passed to the callback registered with
+/// [`BodyMetricsMiddleware::on_complete`].
+#[derive(Debug, Clone)]
+pub struct RequestSummary {
+    method: Method,
+    uri: Uri,
+    status: Option<StatusCode>,
+    bytes_read: u64,
+    bytes_written: u64,
+}
+
+impl RequestSummary {
+    /// Returns the HTTP method of the request.
+    #[must_use]
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the URI of the request.
+    #[must_use]
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// Returns the status code of the response.
+    ///
+    /// This is `None` if the request handler returned an error. In that case,
+    /// the error page is generated by the framework outside of the middleware
+    /// stack, so its body is not included in [`Self::bytes_written`].
+    #[must_use]
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    /// Returns the number of bytes read from the request body.
+    #[must_use]
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Returns the number of bytes written to the response body.
+    #[must_use]
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
+
+/// The number of bytes read from the request body so far, stored in the
+/// request extensions.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BytesRead(Arc<AtomicU64>);
+
+impl BytesRead {
+    pub(crate) fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+struct Completion {
+    on_complete: CompleteCallback,
+    method: Method,
+    uri: Uri,
+    status: Option<StatusCode>,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl Completion {
+    fn complete(self, bytes_written: u64) {
+        let summary = RequestSummary {
+            method: self.method,
+            uri: self.uri,
+            status: self.status,
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written,
+        };
+        (self.on_complete)(&summary);
+    }
+}
+
+/// A body that counts the bytes of the data frames passing through it and
+/// calls the completion callback (if any) when it's dropped.
+struct CountingBody {
+    inner: Body,
+    count: Arc<AtomicU64>,
+    completion: Option<Completion>,
+}
+
+impl CountingBody {
+    fn new(inner: Body, count: Arc<AtomicU64>, completion: Option<Completion>) -> Self {
+        Self {
+            inner,
+            count,
+            completion,
+        }
+    }
+}
+
+impl http_body::Body for CountingBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                self.count.fetch_add(data.len() as u64, Ordering::Relaxed);
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for CountingBody {
+    fn drop(&mut self) {
+        if let Some(completion) = self.completion.take() {
+            completion.complete(self.count.load(Ordering::Relaxed));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use http_body_util::BodyExt;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::request::RequestExt;
+    use crate::test::TestRequestBuilder;
+
+    fn summaries_middleware() -> (BodyMetricsMiddleware, Arc<Mutex<Vec<RequestSummary>>>) {
+        let summaries = Arc::new(Mutex::new(Vec::new()));
+        let summaries_clone = Arc::clone(&summaries);
+        let middleware = BodyMetricsMiddleware::new().on_complete(move |summary| {
+            summaries_clone.lock().unwrap().push(summary.clone());
+        });
+        (middleware, summaries)
+    }
+
+    fn post_request(url: &str, body: Body) -> Request {
+        let mut request = TestRequestBuilder::post(url).build();
+        *request.body_mut() = body;
+        request
+    }
+
+    #[cot::test]
+    async fn counts_fixed_bodies() {
+        let (middleware, summaries) = summaries_middleware();
+        let svc = tower::service_fn(|req: Request| async move {
+            let bytes = req.into_body().into_bytes().await?;
+            Ok::<_, Error>(Response::new(Body::fixed(bytes.repeat(2))))
+        });
+        let request = post_request("/echo", Body::fixed("hello"));
+
+        let response = middleware.layer(svc).oneshot(request).await.unwrap();
+        assert!(summaries.lock().unwrap().is_empty());
+        let body = response.into_body().into_bytes().await.unwrap();
+
+        assert_eq!(body, "hellohello");
+        let summaries = summaries.lock().unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].method(), Method::POST);
+        assert_eq!(summaries[0].uri(), "/echo");
+        assert_eq!(summaries[0].status(), Some(StatusCode::OK));
+        assert_eq!(summaries[0].bytes_read(), 5);
+        assert_eq!(summaries[0].bytes_written(), 10);
+    }
+
+    #[cot::test]
+    async fn counts_streaming_bodies() {
+        let (middleware, summaries) = summaries_middleware();
+        let svc = tower::service_fn(|req: Request| async move {
+            let mut body = req.into_body();
+            while body.frame().await.transpose()?.is_some() {}
+            let stream =
+                futures::stream::iter(["abc", "de", "f"].map(|chunk| Ok(Bytes::from(chunk))));
+            Ok::<_, Error>(Response::new(Body::streaming(stream)))
+        });
+        let request_stream =
+            futures::stream::iter(["1234", "5678"].map(|chunk| Ok(Bytes::from(chunk))));
+        let request = post_request("/", Body::streaming(request_stream));
+
+        let response = middleware.layer(svc).oneshot(request).await.unwrap();
+        response.into_body().into_bytes().await.unwrap();
+
+        let summaries = summaries.lock().unwrap();
+        assert_eq!(summaries[0].bytes_read(), 8);
+        assert_eq!(summaries[0].bytes_written(), 6);
+    }
+
+    #[cot::test]
+    async fn bytes_read_in_handler() {
+        let svc = tower::service_fn(|req: Request| async move {
+            let (parts, body) = req.into_parts();
+            assert_eq!(parts.bytes_read(), Some(0));
+            body.into_bytes().await?;
+            Ok::<_, Error>(Response::new(Body::fixed(
+                parts.bytes_read().unwrap().to_string(),
+            )))
+        });
+        let request = post_request("/", Body::fixed("12345678"));
+
+        let response = BodyMetricsMiddleware::new()
+            .layer(svc)
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "8");
+    }
+
+    #[cot::test]
+    async fn reports_errors() {
+        let (middleware, summaries) = summaries_middleware();
+        let svc =
+            tower::service_fn(
+                |_req: Request| async move { Err::<Response, _>(Error::not_found()) },
+            );
+
+        let result = middleware
+            .layer(svc)
+            .oneshot(TestRequestBuilder::get("/").build())
+            .await;
+
+        assert!(result.is_err());
+        let summaries = summaries.lock().unwrap();
+        assert_eq!(summaries[0].status(), None);
+        assert_eq!(summaries[0].bytes_written(), 0);
+    }
+
+    #[cot::test]
+    async fn bytes_read_without_middleware() {
+        let request = TestRequestBuilder::get("/").build();
+
+        assert_eq!(request.bytes_read(), None);
+    }
+}
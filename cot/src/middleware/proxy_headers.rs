@@ -0,0 +1,685 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::header::{FORWARDED, HOST};
+use http::uri::{Authority, Scheme};
+use http::{HeaderMap, HeaderName, HeaderValue, Uri};
+use tower::Service;
+
+use crate::Error;
+use crate::config::IpNetwork;
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::Response;
+use crate::server::RemoteAddr;
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+const X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host");
+
+/// A middleware that makes the requests forwarded by the trusted reverse
+/// proxies look like they were received from the client directly.
+///
+/// When the application is running behind a reverse proxy (such as nginx or
+/// a load balancer), the connections come from the proxy, which describes
+/// the original request in the `Forwarded` header or the `X-Forwarded-For`,
+/// `X-Forwarded-Proto` and `X-Forwarded-Host` headers. For the requests
+/// coming from one of the trusted proxies, this middleware:
+///
+/// * replaces the [peer address](crate::request::RequestExt::peer_addr) of the
+///   request with the address of the client, skipping the trusted proxies in
+///   the chain,
+/// * sets the scheme and the authority of the request URI, as well as the
+///   `Host` header, to the ones the client used.
+///
+/// The `Forwarded` header is used if present; otherwise, the `X-Forwarded-*`
+/// headers are. These headers are removed from the requests not coming from a
+/// trusted proxy, so that the clients can't spoof them.
+///
+/// The trusted proxies are taken from the
+/// [`trusted_proxies`](crate::config::ServerConfig::trusted_proxies) server
+/// setting by [`Self::from_context`]. The middleware should be added early
+/// in the middleware stack, so that the other middlewares (such as the rate
+/// limiter) see the address of the client.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::ProxyHeadersMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(ProxyHeadersMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProxyHeadersMiddleware {
+    trusted_proxies: Arc<[IpNetwork]>,
+}
+
+impl ProxyHeadersMiddleware {
+    /// Creates a new instance of [`ProxyHeadersMiddleware`] that doesn't
+    /// trust any proxies, so it only removes the forwarding headers from the
+    /// requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::ProxyHeadersMiddleware;
+    ///
+    /// let middleware = ProxyHeadersMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            trusted_proxies: Arc::from([]),
+        }
+    }
+
+    /// Creates a new instance of [`ProxyHeadersMiddleware`] trusting the
+    /// [`trusted_proxies`](crate::config::ServerConfig::trusted_proxies) from
+    /// the server config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::ProxyHeadersMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(ProxyHeadersMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::new().trusted_proxies(context.config().server.trusted_proxies.iter().copied())
+    }
+
+    /// Sets the IP addresses (or address ranges) of the trusted reverse
+    /// proxies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::{IpAddr, Ipv4Addr};
+    ///
+    /// use cot::config::IpNetwork;
+    /// use cot::middleware::ProxyHeadersMiddleware;
+    ///
+    /// let middleware = ProxyHeadersMiddleware::new().trusted_proxies([
+    ///     IpNetwork::from(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+    ///     "10.0.0.0/8".parse()?,
+    /// ]);
+    /// # Ok::<(), cot::config::IpNetworkError>(())
+    /// ```
+    #[must_use]
+    pub fn trusted_proxies<I>(mut self, trusted_proxies: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<IpNetwork>,
+    {
+        self.trusted_proxies = trusted_proxies.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl Default for ProxyHeadersMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for ProxyHeadersMiddleware {
+    type Service = ProxyHeadersService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ProxyHeadersService {
+            inner,
+            trusted_proxies: Arc::clone(&self.trusted_proxies),
+        }
+    }
+}
+
+/// Service that applies the headers forwarded by the trusted reverse proxies
+/// to the request.
+///
+/// Used by [`ProxyHeadersMiddleware`].
+#[derive(Debug, Clone)]
+pub struct ProxyHeadersService<S> {
+    inner: S,
+    trusted_proxies: Arc<[IpNetwork]>,
+}
+
+impl<S> Service<Request> for ProxyHeadersService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        apply_forwarded(&mut req, &self.trusted_proxies);
+
+        Box::pin(async move { inner.call(req).await })
+    }
+}
+
+/// A single hop of the request on its way from the client, as described by a
+/// proxy.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Hop {
+    /// The address the proxy received the request from, or `None` if it's
+    /// unknown or obfuscated.
+    client: Option<SocketAddr>,
+    proto: Option<String>,
+    host: Option<String>,
+}
+
+fn apply_forwarded(req: &mut Request, trusted_proxies: &[IpNetwork]) {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+
+    let remote_addr = req
+        .extensions()
+        .get::<RemoteAddr>()
+        .map(|&RemoteAddr(addr)| addr);
+    let Some(remote_addr) = remote_addr.filter(|addr| is_trusted(addr.ip())) else {
+        remove_forwarded_headers(req.headers_mut());
+        return;
+    };
+
+    let hops = if req.headers().contains_key(FORWARDED) {
+        parse_forwarded(req.headers())
+    } else {
+        parse_x_forwarded(req.headers())
+    };
+
+    // walk the hops from the nearest proxy, as long as they're added by the
+    // trusted proxies; the entries before the first untrusted address could
+    // have been made up by the client
+    let mut client = remote_addr;
+    let mut proto = None;
+    let mut host = None;
+    for hop in hops.into_iter().rev() {
+        if !is_trusted(client.ip()) {
+            break;
+        }
+        proto = hop.proto.or(proto);
+        host = hop.host.or(host);
+        match hop.client {
+            Some(hop_client) => client = hop_client,
+            None => break,
+        }
+    }
+
+    req.extensions_mut().insert(RemoteAddr(client));
+
+    let scheme = proto.and_then(|proto| match proto.to_ascii_lowercase().as_str() {
+        "http" => Some(Scheme::HTTP),
+        "https" => Some(Scheme::HTTPS),
+        _ => None,
+    });
+    let authority = host
+        .and_then(|host| host.parse::<Authority>().ok())
+        // userinfo is never valid in the `Host` header
+        .filter(|host| !host.as_str().contains('@'));
+
+    if let Some(authority) = &authority {
+        let value = HeaderValue::from_str(authority.as_str())
+            .expect("authority is always a valid header value");
+        req.headers_mut().insert(HOST, value);
+    }
+    if let Some(scheme) = &scheme {
+        req.headers_mut().insert(
+            X_FORWARDED_PROTO,
+            HeaderValue::from_str(scheme.as_str()).expect("scheme is always a valid header value"),
+        );
+    }
+    set_uri_origin(req, scheme, authority);
+}
+
+/// Sets the scheme and the authority of the request URI, falling back to the
+/// current ones and the `Host` header.
+fn set_uri_origin(req: &mut Request, scheme: Option<Scheme>, authority: Option<Authority>) {
+    if scheme.is_none() && authority.is_none() {
+        return;
+    }
+
+    let authority = authority
+        .or_else(|| req.uri().authority().cloned())
+        .or_else(|| {
+            req.headers()
+                .get(HOST)
+                .and_then(|host| host.to_str().ok())
+                .and_then(|host| host.parse().ok())
+        });
+    let Some(authority) = authority else {
+        return;
+    };
+    let scheme = scheme
+        .or_else(|| req.uri().scheme().cloned())
+        .unwrap_or(Scheme::HTTP);
+
+    let mut parts = req.uri().clone().into_parts();
+    parts.scheme = Some(scheme);
+    parts.authority = Some(authority);
+    if parts.path_and_query.is_none() {
+        parts.path_and_query = Some(http::uri::PathAndQuery::from_static("/"));
+    }
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *req.uri_mut() = uri;
+    }
+}
+
+fn remove_forwarded_headers(headers: &mut HeaderMap) {
+    headers.remove(FORWARDED);
+    headers.remove(X_FORWARDED_FOR);
+    headers.remove(X_FORWARDED_PROTO);
+    headers.remove(X_FORWARDED_HOST);
+}
+
+/// Parses the `Forwarded` header (RFC 7239) into the hops, starting from the
+/// client.
+fn parse_forwarded(headers: &HeaderMap) -> Vec<Hop> {
+    header_values(headers, FORWARDED)
+        .flat_map(|value| split_unquoted(value, ','))
+        .map(|element| {
+            let mut hop = Hop::default();
+            for pair in split_unquoted(element, ';') {
+                let Some((name, value)) = pair.split_once('=') else {
+                    continue;
+                };
+                let value = unquote(value.trim());
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "for" => hop.client = parse_node(&value),
+                    "proto" => hop.proto = Some(value),
+                    "host" => hop.host = Some(value),
+                    _ => {}
+                }
+            }
+            hop
+        })
+        .collect()
+}
+
+/// Parses the `X-Forwarded-*` headers into the hops, starting from the
+/// client. The protocol and the host are only known for the nearest hop.
+fn parse_x_forwarded(headers: &HeaderMap) -> Vec<Hop> {
+    let mut hops: Vec<_> = header_values(headers, X_FORWARDED_FOR)
+        .flat_map(|value| value.split(','))
+        .map(|node| Hop {
+            client: parse_node(node.trim()),
+            ..Hop::default()
+        })
+        .collect();
+
+    let last_value = |name| {
+        header_values(headers, name)
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .last()
+            .map(str::to_owned)
+    };
+    let proto = last_value(X_FORWARDED_PROTO);
+    let host = last_value(X_FORWARDED_HOST);
+    if proto.is_some() || host.is_some() {
+        if hops.is_empty() {
+            hops.push(Hop {
+                client: None,
+                ..Hop::default()
+            });
+        }
+        let nearest = hops.last_mut().expect("hops are not empty");
+        nearest.proto = proto;
+        nearest.host = host;
+    }
+
+    hops
+}
+
+fn header_values(headers: &HeaderMap, name: HeaderName) -> impl Iterator<Item = &str> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+}
+
+/// Parses a node identifier (such as `192.0.2.1`, `192.0.2.1:8080` or
+/// `[2001:db8::1]:8080`). Returns `None` for the unknown and obfuscated
+/// identifiers. The port is set to 0 if not given.
+fn parse_node(node: &str) -> Option<SocketAddr> {
+    if let Ok(addr) = node.parse::<SocketAddr>() {
+        return Some(SocketAddr::new(addr.ip().to_canonical(), addr.port()));
+    }
+
+    let ip = node
+        .strip_prefix('[')
+        .and_then(|node| node.strip_suffix(']'))
+        .unwrap_or(node);
+    ip.parse::<IpAddr>()
+        .ok()
+        .map(|ip| SocketAddr::new(ip.to_canonical(), 0))
+}
+
+/// Splits the value on the separator, except where it's inside a quoted
+/// string.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, char) in value.char_indices() {
+        match char {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ if char == separator && !in_quotes => {
+                parts.push(&value[start..index]);
+                start = index + char.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn unquote(value: &str) -> String {
+    match value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        Some(quoted) => {
+            let mut unquoted = String::with_capacity(quoted.len());
+            let mut chars = quoted.chars();
+            while let Some(char) = chars.next() {
+                if char == '\\' {
+                    unquoted.extend(chars.next());
+                } else {
+                    unquoted.push(char);
+                }
+            }
+            unquoted
+        }
+        None => value.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::request::RequestExt;
+    use crate::test::TestRequestBuilder;
+
+    fn middleware() -> ProxyHeadersMiddleware {
+        ProxyHeadersMiddleware::new().trusted_proxies([
+            "10.0.0.0/8".parse::<IpNetwork>().unwrap(),
+            IpNetwork::from(IpAddr::from([127, 0, 0, 1])),
+        ])
+    }
+
+    fn request(remote_addr: &str, headers: &[(&'static str, &'static str)]) -> Request {
+        let mut request = TestRequestBuilder::get("/path?query=1").build();
+        request
+            .extensions_mut()
+            .insert(RemoteAddr(remote_addr.parse().unwrap()));
+        request
+            .headers_mut()
+            .insert(HOST, HeaderValue::from_static("internal:8000"));
+        for &(name, value) in headers {
+            request
+                .headers_mut()
+                .append(name, HeaderValue::from_static(value));
+        }
+        request
+    }
+
+    async fn forwarded(middleware: ProxyHeadersMiddleware, request: Request) -> Request {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = Arc::new(std::sync::Mutex::new(Some(tx)));
+        let svc = tower::service_fn(move |req: Request| {
+            tx.lock().unwrap().take().unwrap().send(req).unwrap();
+            async { Ok::<_, Error>(Response::new(Body::empty())) }
+        });
+
+        middleware.layer(svc).oneshot(request).await.unwrap();
+        rx.await.unwrap()
+    }
+
+    #[cot::test]
+    async fn x_forwarded_headers() {
+        let request = request(
+            "10.0.0.1:1234",
+            &[
+                ("x-forwarded-for", "192.0.2.1"),
+                ("x-forwarded-proto", "https"),
+                ("x-forwarded-host", "example.com"),
+            ],
+        );
+
+        let request = forwarded(middleware(), request).await;
+
+        assert_eq!(request.peer_addr(), Some("192.0.2.1:0".parse().unwrap()));
+        assert_eq!(
+            request.uri().to_string(),
+            "https://example.com/path?query=1"
+        );
+        assert_eq!(request.headers()[HOST], "example.com");
+    }
+
+    #[cot::test]
+    async fn absolute_url_uses_forwarded_origin() {
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .allowed_hosts(["example.com"])
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::get("/path").config(config).build();
+        request
+            .extensions_mut()
+            .insert(RemoteAddr("10.0.0.1:1234".parse().unwrap()));
+        request
+            .headers_mut()
+            .insert(HOST, HeaderValue::from_static("internal:8000"));
+        request
+            .headers_mut()
+            .insert(X_FORWARDED_PROTO, HeaderValue::from_static("https"));
+        request
+            .headers_mut()
+            .insert(X_FORWARDED_HOST, HeaderValue::from_static("example.com"));
+
+        let request = forwarded(middleware(), request).await;
+
+        assert_eq!(
+            request.absolute_url("/reset").unwrap(),
+            "https://example.com/reset"
+        );
+    }
+
+    #[cot::test]
+    async fn x_forwarded_for_skips_trusted_proxies() {
+        let request = request(
+            "10.0.0.1:1234",
+            &[("x-forwarded-for", "192.0.2.2, 192.0.2.1, 10.0.0.2")],
+        );
+
+        let request = forwarded(middleware(), request).await;
+
+        // the client could have added 192.0.2.2 itself
+        assert_eq!(request.peer_addr(), Some("192.0.2.1:0".parse().unwrap()));
+        // the original host is used if no forwarded host is given
+        assert_eq!(request.uri().to_string(), "/path?query=1");
+        assert_eq!(request.headers()[HOST], "internal:8000");
+    }
+
+    #[cot::test]
+    async fn x_forwarded_proto_only() {
+        let request = request("127.0.0.1:1234", &[("x-forwarded-proto", "https")]);
+
+        let request = forwarded(middleware(), request).await;
+
+        assert_eq!(request.peer_addr(), Some("127.0.0.1:1234".parse().unwrap()));
+        assert_eq!(
+            request.uri().to_string(),
+            "https://internal:8000/path?query=1"
+        );
+    }
+
+    #[cot::test]
+    async fn forwarded_header() {
+        let request = request(
+            "10.0.0.1:1234",
+            &[
+                (
+                    "forwarded",
+                    r#"for="[2001:db8::1]:4711";proto=https;host="example.com""#,
+                ),
+                ("forwarded", "for=10.0.0.2;proto=http;host=internal"),
+                // ignored when the `Forwarded` header is present
+                ("x-forwarded-for", "192.0.2.1"),
+            ],
+        );
+
+        let request = forwarded(middleware(), request).await;
+
+        assert_eq!(
+            request.peer_addr(),
+            Some("[2001:db8::1]:4711".parse().unwrap())
+        );
+        assert_eq!(
+            request.uri().to_string(),
+            "https://example.com/path?query=1"
+        );
+    }
+
+    #[cot::test]
+    async fn forwarded_header_obfuscated() {
+        let request = request(
+            "10.0.0.1:1234",
+            &[("forwarded", "for=_hidden, for=10.0.0.2")],
+        );
+
+        let request = forwarded(middleware(), request).await;
+
+        assert_eq!(request.peer_addr(), Some("10.0.0.2:0".parse().unwrap()));
+    }
+
+    #[cot::test]
+    async fn untrusted_proxy() {
+        let request = request(
+            "192.0.2.3:1234",
+            &[
+                ("x-forwarded-for", "192.0.2.1"),
+                ("x-forwarded-proto", "https"),
+                ("x-forwarded-host", "evil.example.com"),
+                ("forwarded", "for=192.0.2.1"),
+            ],
+        );
+
+        let request = forwarded(middleware(), request).await;
+
+        assert_eq!(request.peer_addr(), Some("192.0.2.3:1234".parse().unwrap()));
+        assert_eq!(request.uri().to_string(), "/path?query=1");
+        assert_eq!(request.headers()[HOST], "internal:8000");
+        assert!(!request.headers().contains_key(X_FORWARDED_FOR));
+        assert!(!request.headers().contains_key(X_FORWARDED_PROTO));
+        assert!(!request.headers().contains_key(X_FORWARDED_HOST));
+        assert!(!request.headers().contains_key(FORWARDED));
+    }
+
+    #[cot::test]
+    async fn invalid_forwarded_values() {
+        let request = request(
+            "10.0.0.1:1234",
+            &[
+                ("x-forwarded-for", "not an ip"),
+                ("x-forwarded-proto", "gopher"),
+                ("x-forwarded-host", "user@example.com"),
+            ],
+        );
+
+        let request = forwarded(middleware(), request).await;
+
+        assert_eq!(request.peer_addr(), Some("10.0.0.1:1234".parse().unwrap()));
+        assert_eq!(request.uri().to_string(), "/path?query=1");
+        assert_eq!(request.headers()[HOST], "internal:8000");
+    }
+
+    #[test]
+    fn parse_node_formats() {
+        assert_eq!(
+            parse_node("192.0.2.1"),
+            Some("192.0.2.1:0".parse().unwrap())
+        );
+        assert_eq!(
+            parse_node("192.0.2.1:8080"),
+            Some("192.0.2.1:8080".parse().unwrap())
+        );
+        assert_eq!(
+            parse_node("[2001:db8::1]"),
+            Some("[2001:db8::1]:0".parse().unwrap())
+        );
+        assert_eq!(
+            parse_node("2001:db8::1"),
+            Some("[2001:db8::1]:0".parse().unwrap())
+        );
+        assert_eq!(
+            parse_node("::ffff:192.0.2.1"),
+            Some("192.0.2.1:0".parse().unwrap())
+        );
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(parse_node("_hidden"), None);
+    }
+
+    #[test]
+    fn split_unquoted_values() {
+        assert_eq!(
+            split_unquoted(r#"for="a,b";proto=https, for=c"#, ','),
+            [r#"for="a,b";proto=https"#, "for=c"]
+        );
+        assert_eq!(unquote(r#""a\"b""#), r#"a"b"#);
+        assert_eq!(unquote("plain"), "plain");
+    }
+}
@@ -0,0 +1,258 @@
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::{HeaderName, HeaderValue};
+use tower::Service;
+use tracing::debug;
+
+use crate::Error;
+use crate::db::QueryStats;
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::Response;
+
+const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// A middleware that collects the number of database queries executed while
+/// handling each request, and the total time they took.
+///
+/// The statistics are available in the request handlers with the
+/// [`QueryStats`] extractor, logged at the `DEBUG` level once the response
+/// has been created, and, unless disabled with [`Self::server_timing`], sent
+/// back in the `Server-Timing` response header, so that they are shown in the
+/// network panel of the browser's developer tools. This makes it easy to spot
+/// the N+1 problem: pages whose query count grows with the number of the
+/// displayed items.
+///
+/// The queries are only counted if they are executed in the same task as the
+/// request handler; the queries run in spawned tasks are not included.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::QueryStatsMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(QueryStatsMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct QueryStatsMiddleware {
+    server_timing: bool,
+}
+
+impl QueryStatsMiddleware {
+    /// Creates a new instance of [`QueryStatsMiddleware`] that sends the
+    /// statistics in the `Server-Timing` response header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::QueryStatsMiddleware;
+    ///
+    /// let middleware = QueryStatsMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            server_timing: true,
+        }
+    }
+
+    /// Creates a new instance of [`QueryStatsMiddleware`] from the
+    /// application context, sending the `Server-Timing` response header only
+    /// in the debug mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::QueryStatsMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(QueryStatsMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::new().server_timing(context.config().debug)
+    }
+
+    /// Sets whether the statistics are sent in the `Server-Timing` response
+    /// header.
+    ///
+    /// The header reveals how long the database queries took to anyone who
+    /// can send requests to the application, so it's best to only enable it
+    /// in development.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::QueryStatsMiddleware;
+    ///
+    /// let middleware = QueryStatsMiddleware::new().server_timing(false);
+    /// ```
+    #[must_use]
+    pub fn server_timing(mut self, server_timing: bool) -> Self {
+        self.server_timing = server_timing;
+        self
+    }
+}
+
+impl Default for QueryStatsMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for QueryStatsMiddleware {
+    type Service = QueryStatsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        QueryStatsService {
+            inner,
+            server_timing: self.server_timing,
+        }
+    }
+}
+
+/// Service that collects the database query statistics of each request.
+///
+/// Used by [`QueryStatsMiddleware`].
+#[derive(Debug, Clone)]
+pub struct QueryStatsService<S> {
+    inner: S,
+    server_timing: bool,
+}
+
+impl<S> Service<Request> for QueryStatsService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let server_timing = self.server_timing;
+
+        Box::pin(async move {
+            let stats = QueryStats::new();
+            req.extensions_mut().insert(stats.clone());
+
+            let mut response = stats.clone().scope(inner.call(req)).await?;
+
+            let duration = stats.total_duration();
+            debug!(
+                queries = stats.count(),
+                ?duration,
+                "Executed database queries"
+            );
+            if server_timing {
+                response
+                    .headers_mut()
+                    .append(SERVER_TIMING, server_timing_value(&stats));
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+fn server_timing_value(stats: &QueryStats) -> HeaderValue {
+    let millis = stats.total_duration().as_secs_f64() * 1000.0;
+    HeaderValue::from_str(&format!(
+        "db;dur={millis:.3};desc=\"{} queries\"",
+        stats.count()
+    ))
+    .expect("the Server-Timing value is always a valid header value")
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::request::RequestExt;
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    async fn call(db: &TestDatabase, middleware: QueryStatsMiddleware) -> Response {
+        let svc = tower::service_fn(move |req: Request| async move {
+            req.db().raw("SELECT 1").await?;
+            req.db().raw("SELECT 2").await?;
+
+            let stats = req.extensions().get::<QueryStats>().unwrap();
+            assert_eq!(stats.count(), 2);
+            assert_eq!(QueryStats::current().unwrap().count(), 2);
+
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::OK;
+            Ok::<_, Error>(response)
+        });
+
+        middleware
+            .layer(svc)
+            .oneshot(TestRequestBuilder::get("/").database(db.database()).build())
+            .await
+            .unwrap()
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn query_stats_server_timing() {
+        let db = TestDatabase::new_sqlite().await.unwrap();
+
+        let response = call(&db, QueryStatsMiddleware::new()).await;
+
+        let server_timing = response
+            .headers()
+            .get(SERVER_TIMING)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(server_timing.starts_with("db;dur="));
+        assert!(server_timing.ends_with(";desc=\"2 queries\""));
+        db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn query_stats_no_server_timing() {
+        let db = TestDatabase::new_sqlite().await.unwrap();
+
+        let response = call(&db, QueryStatsMiddleware::new().server_timing(false)).await;
+
+        assert!(response.headers().get(SERVER_TIMING).is_none());
+        db.cleanup().await.unwrap();
+    }
+}
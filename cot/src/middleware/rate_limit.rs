@@ -0,0 +1,1074 @@
+#[cfg(feature = "redis")]
+mod redis;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_core::future::BoxFuture;
+use http::StatusCode;
+use http::header::RETRY_AFTER;
+use tower::Service;
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisRateLimitStore;
+use crate::clock::{Clock, SystemClock};
+use crate::config::{RateLimitKeyType, RateLimitMiddlewareConfig, RateLimitStoreType};
+use crate::project::MiddlewareContext;
+use crate::request::{Request, RequestExt};
+use crate::response::{Response, ResponseExt};
+use crate::{Body, Error};
+
+/// The key of the limit shared by all the requests.
+const GLOBAL_KEY: &str = "global";
+
+/// The number of buckets kept by a [`RateLimiter`] before the ones that are
+/// full again are removed.
+const MIN_PRUNE_THRESHOLD: usize = 1024;
+
+/// A store keeping the state of the rate limits used by a
+/// [`RateLimitMiddleware`].
+///
+/// The store keeps a separate limit for each key (such as a client IP
+/// address), allowing a fixed number of requests per period. [`RateLimiter`]
+/// keeps the limits in memory; with the `redis` feature enabled,
+/// `RedisRateLimitStore` keeps them in Redis, so that they are shared by all
+/// the instances of the application.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use async_trait::async_trait;
+/// use cot::middleware::RateLimitStore;
+///
+/// /// A store that never limits the requests.
+/// struct Unlimited;
+///
+/// #[async_trait]
+/// impl RateLimitStore for Unlimited {
+///     async fn acquire(&self, _key: &str) -> cot::Result<Result<(), Duration>> {
+///         Ok(Ok(()))
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Tries to consume a single request from the limit identified by `key`.
+    ///
+    /// Returns `Ok(Err(retry_after))` with the time after which the next
+    /// request will be allowed if the limit has been exceeded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store could not be accessed.
+    async fn acquire(&self, key: &str) -> crate::Result<Result<(), Duration>>;
+}
+
+/// An in-memory token bucket rate limiter, shared by all the services created
+/// by a [`RateLimitMiddleware`].
+///
+/// Each key (such as a client IP address) has its own bucket, which holds up
+/// to `capacity` tokens and is refilled at a constant rate so that it becomes
+/// full again after `period`. Every request consumes one token; when there
+/// are no tokens left, the request is rejected. The buckets that are full
+/// again are removed from time to time, so that the memory used by the
+/// limiter doesn't grow with the number of keys ever seen.
+///
+/// This is the state of the rate limiter. It is created once (typically in
+/// [`Project::middlewares`](crate::project::Project::middlewares)) and then
+/// shared by wrapping it in an [`Arc`] — cloning the middleware or the
+/// service it produces never creates new buckets.
+///
+/// The time used to refill the buckets is read from a [`Clock`], so that the
+/// limiter can be tested with a [`TestClock`](crate::test::TestClock).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::middleware::RateLimiter;
+///
+/// let limiter = RateLimiter::new(2, Duration::from_secs(1));
+/// assert!(limiter.try_acquire().is_ok());
+/// assert!(limiter.try_acquire().is_ok());
+/// assert!(limiter.try_acquire().is_err());
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: u32,
+    period: Duration,
+    clock: Arc<dyn Clock>,
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    buckets: HashMap<String, Bucket>,
+    prune_threshold: usize,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Bucket {
+    tokens: f64,
+    last_refill: DateTime<Utc>,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: DateTime<Utc>, capacity: f64, tokens_per_second: f64) {
+        // the clock is not guaranteed to be monotonic
+        let elapsed = (now - self.last_refill).to_std().unwrap_or(Duration::ZERO);
+        self.tokens = elapsed
+            .as_secs_f64()
+            .mul_add(tokens_per_second, self.tokens)
+            .min(capacity);
+        self.last_refill = now;
+    }
+}
+
+impl RateLimiter {
+    /// Creates a new [`RateLimiter`] allowing `capacity` requests per
+    /// `period`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or `period` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RateLimiter;
+    ///
+    /// let limiter = RateLimiter::new(100, Duration::from_secs(60));
+    /// ```
+    #[must_use]
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        Self::with_clock(capacity, period, Arc::new(SystemClock))
+    }
+
+    /// Creates a new [`RateLimiter`] allowing `capacity` requests per
+    /// `period`, reading the current time from the given [`Clock`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or `period` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RateLimiter;
+    /// use cot::test::TestClock;
+    ///
+    /// let clock = TestClock::new();
+    /// let limiter = RateLimiter::with_clock(1, Duration::from_secs(10), Arc::new(clock.clone()));
+    /// assert!(limiter.try_acquire().is_ok());
+    /// assert!(limiter.try_acquire().is_err());
+    ///
+    /// clock.advance(Duration::from_secs(10));
+    /// assert!(limiter.try_acquire().is_ok());
+    /// ```
+    #[must_use]
+    pub fn with_clock(capacity: u32, period: Duration, clock: Arc<dyn Clock>) -> Self {
+        assert!(capacity > 0, "rate limiter capacity must be positive");
+        assert!(!period.is_zero(), "rate limiter period must be positive");
+
+        Self {
+            capacity,
+            period,
+            clock,
+            buckets: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                prune_threshold: MIN_PRUNE_THRESHOLD,
+            }),
+        }
+    }
+
+    /// Tries to consume a single token from the global bucket, shared by all
+    /// the requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns the time after which a token will be available if the bucket
+    /// is currently empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RateLimiter;
+    ///
+    /// let limiter = RateLimiter::new(1, Duration::from_secs(10));
+    /// assert!(limiter.try_acquire().is_ok());
+    ///
+    /// let retry_after = limiter.try_acquire().unwrap_err();
+    /// assert!(retry_after <= Duration::from_secs(10));
+    /// ```
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_key(GLOBAL_KEY)
+    }
+
+    /// Tries to consume a single token from the bucket identified by `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the time after which a token will be available if the bucket
+    /// is currently empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RateLimiter;
+    ///
+    /// let limiter = RateLimiter::new(1, Duration::from_secs(10));
+    /// assert!(limiter.try_acquire_key("ip:192.0.2.1").is_ok());
+    /// assert!(limiter.try_acquire_key("ip:192.0.2.1").is_err());
+    /// assert!(limiter.try_acquire_key("ip:192.0.2.2").is_ok());
+    /// ```
+    pub fn try_acquire_key(&self, key: &str) -> Result<(), Duration> {
+        let now = self.clock.now();
+        let capacity = f64::from(self.capacity);
+        let tokens_per_second = capacity / self.period.as_secs_f64();
+
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        if !buckets.buckets.contains_key(key) && buckets.buckets.len() >= buckets.prune_threshold {
+            // a full bucket is no different from a new one
+            buckets.buckets.retain(|_, bucket| {
+                bucket.refill(now, capacity, tokens_per_second);
+                bucket.tokens < capacity
+            });
+            buckets.prune_threshold = (buckets.buckets.len() * 2).max(MIN_PRUNE_THRESHOLD);
+        }
+
+        let bucket = match buckets.buckets.get_mut(key) {
+            Some(bucket) => {
+                bucket.refill(now, capacity, tokens_per_second);
+                bucket
+            }
+            None => buckets.buckets.entry(key.to_owned()).or_insert(Bucket {
+                tokens: capacity,
+                last_refill: now,
+            }),
+        };
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / tokens_per_second,
+            ))
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RateLimiter {
+    async fn acquire(&self, key: &str) -> crate::Result<Result<(), Duration>> {
+        Ok(self.try_acquire_key(key))
+    }
+}
+
+type KeyFn = Arc<dyn Fn(&Request) -> String + Send + Sync>;
+
+#[derive(Clone)]
+enum KeySource {
+    Builtin(RateLimitKeyType),
+    Custom(KeyFn),
+}
+
+/// Extracts the rate limit keys from the requests.
+#[derive(Clone)]
+struct KeyExtractor {
+    source: KeySource,
+}
+
+impl KeyExtractor {
+    fn key(&self, request: &Request) -> String {
+        match &self.source {
+            KeySource::Builtin(RateLimitKeyType::Global) => GLOBAL_KEY.to_owned(),
+            KeySource::Builtin(RateLimitKeyType::ClientIp) => client_ip_key(request),
+            KeySource::Builtin(RateLimitKeyType::User) => {
+                let user_id = request
+                    .extensions()
+                    .get::<crate::auth::Auth>()
+                    .and_then(|auth| auth.user().id());
+                match user_id.and_then(|id| serde_json::to_string(&id).ok()) {
+                    Some(id) => format!("user:{id}"),
+                    None => client_ip_key(request),
+                }
+            }
+            KeySource::Builtin(RateLimitKeyType::Header(header_name)) => {
+                match request.headers().get(header_name) {
+                    Some(value) => format!("header:{}", String::from_utf8_lossy(value.as_bytes())),
+                    None => "header".to_owned(),
+                }
+            }
+            KeySource::Custom(key_fn) => key_fn(request),
+        }
+    }
+}
+
+/// Returns the key for the IP address of the client, as resolved by the
+/// [`ProxyHeadersMiddleware`](crate::middleware::ProxyHeadersMiddleware)
+/// if the application is running behind a reverse proxy.
+fn client_ip_key(request: &Request) -> String {
+    match request.peer_addr() {
+        Some(addr) => format!("ip:{}", addr.ip().to_canonical()),
+        None => "ip:unknown".to_owned(),
+    }
+}
+
+/// A rate limit overriding the default one for the requests to a path.
+#[derive(Clone)]
+struct RouteLimit {
+    path: String,
+    limiter: Arc<dyn RateLimitStore>,
+}
+
+impl RouteLimit {
+    fn matches(&self, path: &str) -> bool {
+        path.strip_prefix(self.path.trim_end_matches('/'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+/// A middleware that limits the rate of requests handled by the project.
+///
+/// Each client has its own limit: by default, the clients are told apart by
+/// their IP addresses, but they can also be limited by the authenticated
+/// user, the value of a header (such as an API key), or any other key
+/// computed from the request. Requests exceeding the limit are rejected with
+/// `429 Too Many Requests` and a `Retry-After` header, without calling the
+/// inner handler. The requests to specific paths can have their own limits
+/// (see [`RateLimitMiddleware::route`]).
+///
+/// This middleware is also the reference implementation of a stateful
+/// middleware: its state (a [`RateLimitStore`]) is created once and kept in
+/// an [`Arc`] that is shared with every service the middleware creates. See
+/// the [module documentation](crate::middleware#stateful-middlewares) for
+/// details.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::middleware::RateLimitMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(RateLimitMiddleware::new(100, Duration::from_secs(60)))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RateLimitMiddleware {
+    limiter: Arc<dyn RateLimitStore>,
+    key: KeyExtractor,
+    routes: Arc<[RouteLimit]>,
+}
+
+impl std::fmt::Debug for RateLimitMiddleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimitMiddleware")
+            .finish_non_exhaustive()
+    }
+}
+
+impl RateLimitMiddleware {
+    /// Creates a new [`RateLimitMiddleware`] allowing `capacity` requests
+    /// per `period` from each client IP address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or `period` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RateLimitMiddleware;
+    ///
+    /// let middleware = RateLimitMiddleware::new(100, Duration::from_secs(60));
+    /// ```
+    #[must_use]
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        Self::with_limiter(Arc::new(RateLimiter::new(capacity, period)))
+    }
+
+    /// Creates a new [`RateLimitMiddleware`] from the application context,
+    /// using the settings from the `[middlewares.rate_limit]` section of the
+    /// config.
+    ///
+    /// The in-memory limits read the time from the [`Clock`] of the project.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the limits has zero capacity, or if the Redis store
+    /// is configured without a valid URL or without the `redis` feature
+    /// enabled. All of these are reported by the config validation when the
+    /// project is bootstrapped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::RateLimitMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(RateLimitMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        let config = &context.config().middlewares.rate_limit;
+        let stores = ConfiguredStores::new(config, context.clock());
+
+        let mut middleware = Self::with_limiter(stores.store(None, config.capacity, config.period))
+            .key(config.key.clone());
+        for route in &config.routes {
+            middleware = middleware.route(
+                route.path.clone(),
+                stores.store(Some(&route.path), route.capacity, route.period),
+            );
+        }
+        middleware
+    }
+
+    /// Creates a new [`RateLimitMiddleware`] using an existing, shared
+    /// [`RateLimitStore`], such as a [`RateLimiter`].
+    ///
+    /// This is useful when the limiter needs to be accessed outside the
+    /// middleware, or shared between several middleware instances.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::{RateLimitMiddleware, RateLimiter};
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject {
+    ///     limiter: Arc<RateLimiter>,
+    /// }
+    ///
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(RateLimitMiddleware::with_limiter(self.limiter.clone()))
+    ///             .build()
+    ///     }
+    /// }
+    ///
+    /// let project = MyProject {
+    ///     limiter: Arc::new(RateLimiter::new(100, Duration::from_secs(60))),
+    /// };
+    /// ```
+    #[must_use]
+    pub fn with_limiter(limiter: Arc<dyn RateLimitStore>) -> Self {
+        Self {
+            limiter,
+            key: KeyExtractor {
+                source: KeySource::Builtin(RateLimitKeyType::default()),
+            },
+            routes: Arc::from([]),
+        }
+    }
+
+    /// Sets what the requests are limited by. Defaults to
+    /// [`RateLimitKeyType::ClientIp`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::RateLimitKeyType;
+    /// use cot::http::HeaderName;
+    /// use cot::middleware::RateLimitMiddleware;
+    ///
+    /// let middleware = RateLimitMiddleware::new(1000, Duration::from_secs(3600)).key(
+    ///     RateLimitKeyType::Header(HeaderName::from_static("x-api-key")),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn key(mut self, key: RateLimitKeyType) -> Self {
+        self.key.source = KeySource::Builtin(key);
+        self
+    }
+
+    /// Sets the function computing the key the requests are limited by.
+    /// The requests with the same key share a single limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RateLimitMiddleware;
+    ///
+    /// let middleware = RateLimitMiddleware::new(10, Duration::from_secs(1))
+    ///     .key_fn(|request| format!("method:{}", request.method()));
+    /// ```
+    #[must_use]
+    pub fn key_fn<F>(mut self, key_fn: F) -> Self
+    where
+        F: Fn(&Request) -> String + Send + Sync + 'static,
+    {
+        self.key.source = KeySource::Custom(Arc::new(key_fn));
+        self
+    }
+
+    /// Sets a limit overriding the default one for the requests to `path`.
+    ///
+    /// The first route whose path is a prefix of the request path (ending at
+    /// a `/`) is used, so `/api` matches `/api` and `/api/users`, but not
+    /// `/apis`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::{RateLimitMiddleware, RateLimiter};
+    ///
+    /// let middleware = RateLimitMiddleware::new(100, Duration::from_secs(60)).route(
+    ///     "/login",
+    ///     Arc::new(RateLimiter::new(5, Duration::from_secs(60))),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn route(mut self, path: impl Into<String>, limiter: Arc<dyn RateLimitStore>) -> Self {
+        let mut routes = self.routes.to_vec();
+        routes.push(RouteLimit {
+            path: path.into(),
+            limiter,
+        });
+        self.routes = routes.into();
+        self
+    }
+
+    /// Returns the default [`RateLimitStore`] shared by this middleware.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::{RateLimitMiddleware, RateLimitStore, RateLimiter};
+    ///
+    /// let limiter: Arc<dyn RateLimitStore> = Arc::new(RateLimiter::new(1, Duration::from_secs(60)));
+    /// let middleware = RateLimitMiddleware::with_limiter(Arc::clone(&limiter));
+    /// assert!(Arc::ptr_eq(middleware.limiter(), &limiter));
+    /// ```
+    #[must_use]
+    pub fn limiter(&self) -> &Arc<dyn RateLimitStore> {
+        &self.limiter
+    }
+}
+
+/// Creates the stores configured in the `[middlewares.rate_limit]` section.
+enum ConfiguredStores {
+    Memory(Arc<dyn Clock>),
+    #[cfg(feature = "redis")]
+    Redis(RedisRateLimitStore),
+}
+
+impl ConfiguredStores {
+    fn new(config: &RateLimitMiddlewareConfig, clock: &Arc<dyn Clock>) -> Self {
+        match config.store {
+            RateLimitStoreType::Memory => Self::Memory(Arc::clone(clock)),
+            #[cfg(feature = "redis")]
+            RateLimitStoreType::Redis => Self::Redis(
+                RedisRateLimitStore::from_config(&config.redis, config.capacity, config.period)
+                    .expect("invalid Redis rate limit store configuration"),
+            ),
+            #[cfg(not(feature = "redis"))]
+            RateLimitStoreType::Redis => {
+                panic!("the Redis rate limit store requires the `redis` feature to be enabled")
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "redis"), expect(unused_variables))]
+    fn store(
+        &self,
+        route: Option<&str>,
+        capacity: u32,
+        period: Duration,
+    ) -> Arc<dyn RateLimitStore> {
+        match self {
+            Self::Memory(clock) => {
+                Arc::new(RateLimiter::with_clock(capacity, period, Arc::clone(clock)))
+            }
+            #[cfg(feature = "redis")]
+            Self::Redis(store) => {
+                let store = store.clone().quota(capacity, period);
+                Arc::new(match route {
+                    Some(route) => {
+                        let key_prefix = format!("{}route:{route}:", store.get_key_prefix());
+                        store.key_prefix(key_prefix)
+                    }
+                    None => store,
+                })
+            }
+        }
+    }
+}
+
+impl<S> tower::Layer<S> for RateLimitMiddleware {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: Arc::clone(&self.limiter),
+            key: self.key.clone(),
+            routes: Arc::clone(&self.routes),
+        }
+    }
+}
+
+/// Service that rejects requests exceeding the rate limit.
+///
+/// Used by [`RateLimitMiddleware`]. Cloning this service shares the
+/// underlying [`RateLimitStore`].
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: Arc<dyn RateLimitStore>,
+    key: KeyExtractor,
+    routes: Arc<[RouteLimit]>,
+}
+
+impl<S: std::fmt::Debug> std::fmt::Debug for RateLimitService<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimitService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Service<Request> for RateLimitService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let key = self.key.key(&req);
+        let limiter = self
+            .routes
+            .iter()
+            .find(|route| route.matches(req.uri().path()))
+            .map_or(&self.limiter, |route| &route.limiter);
+        let limiter = Arc::clone(limiter);
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            if let Err(retry_after) = limiter.acquire(&key).await? {
+                return Ok(too_many_requests_response(retry_after));
+            }
+
+            inner.call(req).await
+        })
+    }
+}
+
+fn too_many_requests_response(retry_after: Duration) -> Response {
+    let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header(RETRY_AFTER, retry_after_secs.max(1))
+        .body(Body::fixed("Too Many Requests"))
+        .expect("failed to build too many requests response")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, SocketAddr};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use http::HeaderName;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::middleware::ProxyHeadersMiddleware;
+    use crate::server::RemoteAddr;
+    use crate::test::{TestClock, TestRequestBuilder};
+
+    fn counting_service(
+        counter: Arc<AtomicUsize>,
+    ) -> impl Service<Request, Response = Response, Error = Error, Future: Send> + Clone + Send + 'static
+    {
+        tower::service_fn(move |_req: Request| {
+            let counter = Arc::clone(&counter);
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, Error>(Response::new(Body::empty()))
+            }
+        })
+    }
+
+    fn request_from(path: &str, remote_addr: &str) -> Request {
+        let mut request = TestRequestBuilder::get(path).build();
+        request
+            .extensions_mut()
+            .insert(RemoteAddr(remote_addr.parse::<SocketAddr>().unwrap()));
+        request
+    }
+
+    async fn statuses(middleware: &RateLimitMiddleware, requests: Vec<Request>) -> Vec<u16> {
+        let svc = middleware.layer(counting_service(Arc::new(AtomicUsize::new(0))));
+        let mut statuses = Vec::new();
+        for request in requests {
+            let response = svc.clone().oneshot(request).await.unwrap();
+            statuses.push(response.status().as_u16());
+        }
+        statuses
+    }
+
+    #[test]
+    fn rate_limiter_refills() {
+        let clock = TestClock::new();
+        let limiter = RateLimiter::with_clock(2, Duration::from_secs(2), Arc::new(clock.clone()));
+
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        let retry_after = limiter.try_acquire().unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(1));
+
+        clock.advance(Duration::from_secs(1));
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+    }
+
+    #[test]
+    fn rate_limiter_does_not_exceed_capacity() {
+        let clock = TestClock::new();
+        let limiter = RateLimiter::with_clock(1, Duration::from_secs(1), Arc::new(clock.clone()));
+
+        clock.advance(Duration::from_secs(100));
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+    }
+
+    #[test]
+    fn rate_limiter_clock_going_backwards() {
+        let clock = TestClock::new();
+        let limiter = RateLimiter::with_clock(1, Duration::from_secs(1), Arc::new(clock.clone()));
+        let start = clock.now();
+
+        assert!(limiter.try_acquire().is_ok());
+        clock.set(start - chrono::Duration::seconds(10));
+        assert!(limiter.try_acquire().is_err());
+    }
+
+    #[test]
+    fn rate_limiter_keys_are_separate() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+
+        assert!(limiter.try_acquire_key("a").is_ok());
+        assert!(limiter.try_acquire_key("a").is_err());
+        assert!(limiter.try_acquire_key("b").is_ok());
+        assert!(limiter.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn rate_limiter_removes_full_buckets() {
+        let clock = TestClock::new();
+        let limiter = RateLimiter::with_clock(1, Duration::from_secs(1), Arc::new(clock.clone()));
+
+        for index in 0..MIN_PRUNE_THRESHOLD {
+            assert!(limiter.try_acquire_key(&index.to_string()).is_ok());
+        }
+        clock.advance(Duration::from_secs(1));
+        assert!(limiter.try_acquire_key("new").is_ok());
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.buckets.len(), 1);
+        assert!(buckets.buckets.contains_key("new"));
+    }
+
+    #[test]
+    fn rate_limiter_keeps_empty_buckets() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(3600));
+
+        for index in 0..MIN_PRUNE_THRESHOLD {
+            assert!(limiter.try_acquire_key(&index.to_string()).is_ok());
+        }
+        assert!(limiter.try_acquire_key("new").is_ok());
+        assert!(limiter.try_acquire_key("0").is_err());
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.buckets.len(), MIN_PRUNE_THRESHOLD + 1);
+        assert_eq!(buckets.prune_threshold, MIN_PRUNE_THRESHOLD * 2);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_middleware_rejects_over_limit() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let middleware = RateLimitMiddleware::new(1, Duration::from_secs(60));
+        let mut svc = middleware.layer(counting_service(Arc::clone(&counter)));
+
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "60");
+
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_state_is_shared_between_clones() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let middleware = RateLimitMiddleware::new(2, Duration::from_secs(60));
+
+        // services created by separate `layer()` calls and their clones must
+        // all use the same bucket
+        let svc1 = middleware.layer(counting_service(Arc::clone(&counter)));
+        let svc2 = middleware
+            .clone()
+            .layer(counting_service(Arc::clone(&counter)));
+        let svc3 = svc1.clone();
+
+        for svc in [svc1, svc2, svc3] {
+            svc.oneshot(TestRequestBuilder::get("/").build())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_with_shared_limiter() {
+        let limiter = Arc::new(RateLimiter::new(1, Duration::from_secs(60)));
+        let store: Arc<dyn RateLimitStore> = limiter.clone();
+        let middleware =
+            RateLimitMiddleware::with_limiter(Arc::clone(&store)).key(RateLimitKeyType::Global);
+        assert!(Arc::ptr_eq(middleware.limiter(), &store));
+
+        assert!(limiter.try_acquire().is_ok());
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let response = middleware
+            .layer(counting_service(Arc::clone(&counter)))
+            .oneshot(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_by_client_ip() {
+        let middleware = RateLimitMiddleware::new(1, Duration::from_secs(60));
+
+        let statuses = statuses(
+            &middleware,
+            vec![
+                request_from("/", "192.0.2.1:1234"),
+                request_from("/", "192.0.2.1:5678"),
+                request_from("/", "192.0.2.2:1234"),
+                request_from("/", "[::ffff:192.0.2.2]:1234"),
+            ],
+        )
+        .await;
+
+        assert_eq!(statuses, [200, 429, 200, 429]);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_by_forwarded_client_ip() {
+        // the client address is resolved by the proxy headers middleware, which
+        // comes before the rate limiter
+        let svc = ProxyHeadersMiddleware::new()
+            .trusted_proxies([IpAddr::from([10, 0, 0, 1]), IpAddr::from([10, 0, 0, 2])])
+            .layer(
+                RateLimitMiddleware::new(1, Duration::from_secs(60))
+                    .layer(counting_service(Arc::new(AtomicUsize::new(0)))),
+            );
+        let forwarded = |remote_addr, forwarded_for: &'static str| {
+            let mut request = request_from("/", remote_addr);
+            request.headers_mut().insert(
+                "x-forwarded-for",
+                http::HeaderValue::from_static(forwarded_for),
+            );
+            request
+        };
+
+        let mut statuses = Vec::new();
+        for request in [
+            forwarded("10.0.0.1:1234", "192.0.2.1"),
+            // the client can't spoof the address by adding its own entries
+            forwarded("10.0.0.1:1234", "192.0.2.2, 192.0.2.1, 10.0.0.2"),
+            forwarded("10.0.0.1:1234", "192.0.2.2"),
+            // the header is ignored for the untrusted connections
+            forwarded("192.0.2.3:1234", "192.0.2.4"),
+            request_from("/", "192.0.2.3:1234"),
+        ] {
+            let response = svc.clone().oneshot(request).await.unwrap();
+            statuses.push(response.status().as_u16());
+        }
+
+        assert_eq!(statuses, [200, 429, 200, 200, 429]);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_by_header() {
+        let middleware = RateLimitMiddleware::new(1, Duration::from_secs(60)).key(
+            RateLimitKeyType::Header(HeaderName::from_static("x-api-key")),
+        );
+        let with_key = |key: &'static str| {
+            let mut request = TestRequestBuilder::get("/").build();
+            request
+                .headers_mut()
+                .insert("x-api-key", http::HeaderValue::from_static(key));
+            request
+        };
+
+        let statuses = statuses(
+            &middleware,
+            vec![
+                with_key("first"),
+                with_key("first"),
+                with_key("second"),
+                TestRequestBuilder::get("/").build(),
+                TestRequestBuilder::get("/").build(),
+            ],
+        )
+        .await;
+
+        assert_eq!(statuses, [200, 429, 200, 200, 429]);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_by_anonymous_user() {
+        let middleware =
+            RateLimitMiddleware::new(1, Duration::from_secs(60)).key(RateLimitKeyType::User);
+
+        let statuses = statuses(
+            &middleware,
+            vec![
+                request_from("/", "192.0.2.1:1234"),
+                request_from("/", "192.0.2.2:1234"),
+                request_from("/", "192.0.2.1:1234"),
+            ],
+        )
+        .await;
+
+        assert_eq!(statuses, [200, 200, 429]);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_by_custom_key() {
+        let middleware = RateLimitMiddleware::new(1, Duration::from_secs(60))
+            .key_fn(|request| request.uri().path().to_owned());
+
+        let statuses = statuses(
+            &middleware,
+            vec![
+                TestRequestBuilder::get("/a").build(),
+                TestRequestBuilder::get("/b").build(),
+                TestRequestBuilder::get("/a").build(),
+            ],
+        )
+        .await;
+
+        assert_eq!(statuses, [200, 200, 429]);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_route_override() {
+        let middleware = RateLimitMiddleware::new(2, Duration::from_secs(60)).route(
+            "/login/",
+            Arc::new(RateLimiter::new(1, Duration::from_secs(60))),
+        );
+
+        let statuses = statuses(
+            &middleware,
+            vec![
+                TestRequestBuilder::get("/login").build(),
+                TestRequestBuilder::get("/login/reset").build(),
+                TestRequestBuilder::get("/logins").build(),
+                TestRequestBuilder::get("/").build(),
+                TestRequestBuilder::get("/").build(),
+            ],
+        )
+        .await;
+
+        assert_eq!(statuses, [200, 429, 200, 200, 429]);
+    }
+}
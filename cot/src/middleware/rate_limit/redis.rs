@@ -0,0 +1,329 @@
+//! Redis rate limit store.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ::redis::Client;
+use ::redis::aio::ConnectionManager;
+use async_trait::async_trait;
+use tokio::sync::OnceCell;
+
+use super::RateLimitStore;
+use crate::config::RedisRateLimitStoreConfig;
+use crate::error::ErrorRepr;
+
+/// The token bucket algorithm, run atomically in Redis.
+///
+/// Returns 0 if the request is allowed, or the number of milliseconds after
+/// which the next request will be allowed.
+const TOKEN_BUCKET_SCRIPT: &str = r"
+local capacity = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(bucket[1]) or capacity
+local updated = tonumber(bucket[2]) or now
+local rate = capacity / period
+tokens = math.min(capacity, tokens + math.max(0, now - updated) * rate)
+
+local retry_after = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+else
+    retry_after = math.ceil((1 - tokens) / rate)
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', tostring(now))
+redis.call('PEXPIRE', KEYS[1], period)
+return retry_after
+";
+
+/// A rate limit store keeping the token buckets in Redis, so that the limits
+/// are shared by all the instances of the application.
+///
+/// The buckets are updated atomically by a script run in Redis, using the
+/// time of the Redis server, and deleted by Redis once they're full again.
+/// The connection is established when the store is used for the first time,
+/// and re-established automatically when it's lost.
+///
+/// This is only available with the `redis` feature enabled.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use cot::middleware::{RateLimitMiddleware, RedisRateLimitStore};
+///
+/// let store = RedisRateLimitStore::new("redis://localhost:6379", 100, Duration::from_secs(60))?;
+/// let middleware = RateLimitMiddleware::with_limiter(Arc::new(store));
+/// # Ok::<(), cot::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct RedisRateLimitStore {
+    client: Client,
+    connection: Arc<OnceCell<ConnectionManager>>,
+    key_prefix: String,
+    capacity: u32,
+    period: Duration,
+}
+
+impl std::fmt::Debug for RedisRateLimitStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisRateLimitStore")
+            .field("client", &self.client)
+            .field("key_prefix", &self.key_prefix)
+            .field("capacity", &self.capacity)
+            .field("period", &self.period)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisRateLimitStore {
+    /// Creates a new Redis rate limit store connecting to the server at
+    /// `url`, allowing `capacity` requests per `period`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or `period` is shorter than a
+    /// millisecond.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RedisRateLimitStore;
+    ///
+    /// let store = RedisRateLimitStore::new("redis://localhost:6379", 100, Duration::from_secs(60))?;
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn new(url: &str, capacity: u32, period: Duration) -> crate::Result<Self> {
+        let client = Client::open(url).map_err(ErrorRepr::InvalidRedisUrl)?;
+
+        Ok(Self {
+            client,
+            connection: Arc::new(OnceCell::new()),
+            key_prefix: RedisRateLimitStoreConfig::default().key_prefix,
+            capacity: 1,
+            period: Duration::from_secs(1),
+        }
+        .quota(capacity, period))
+    }
+
+    /// Creates a new Redis rate limit store from the configuration, allowing
+    /// `capacity` requests per `period`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is not set or is invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or `period` is shorter than a
+    /// millisecond.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::RedisRateLimitStoreConfig;
+    /// use cot::middleware::RedisRateLimitStore;
+    ///
+    /// let config = RedisRateLimitStoreConfig::builder()
+    ///     .url("redis://localhost:6379")
+    ///     .build();
+    /// let store = RedisRateLimitStore::from_config(&config, 100, Duration::from_secs(60))?;
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn from_config(
+        config: &RedisRateLimitStoreConfig,
+        capacity: u32,
+        period: Duration,
+    ) -> crate::Result<Self> {
+        let url = config
+            .url
+            .as_deref()
+            .ok_or(ErrorRepr::RedisRateLimitUrlMissing)?;
+
+        Ok(Self::new(url, capacity, period)?.key_prefix(config.key_prefix.clone()))
+    }
+
+    /// Sets the prefix of the keys the buckets are stored under.
+    ///
+    /// Defaults to `cot:rate_limit:`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RedisRateLimitStore;
+    ///
+    /// let store = RedisRateLimitStore::new("redis://localhost:6379", 100, Duration::from_secs(60))?
+    ///     .key_prefix("myapp:rate_limit:");
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[must_use]
+    pub fn key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Sets the number of requests allowed per period.
+    ///
+    /// The clones of a store share its connection, so this can be used to
+    /// create the stores with different limits, such as the ones passed to
+    /// [`RateLimitMiddleware::route`](super::RateLimitMiddleware::route). The
+    /// stores with different limits should use different key prefixes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or `period` is shorter than a
+    /// millisecond.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::RedisRateLimitStore;
+    ///
+    /// let store = RedisRateLimitStore::new("redis://localhost:6379", 100, Duration::from_secs(60))?;
+    /// let login_store = store
+    ///     .clone()
+    ///     .quota(5, Duration::from_secs(60))
+    ///     .key_prefix("cot:rate_limit:login:");
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[must_use]
+    pub fn quota(mut self, capacity: u32, period: Duration) -> Self {
+        assert!(capacity > 0, "rate limiter capacity must be positive");
+        assert!(
+            period >= Duration::from_millis(1),
+            "rate limiter period must be at least one millisecond"
+        );
+
+        self.capacity = capacity;
+        self.period = period;
+        self
+    }
+
+    pub(super) fn get_key_prefix(&self) -> &str {
+        &self.key_prefix
+    }
+
+    async fn connection(&self) -> crate::Result<ConnectionManager> {
+        Ok(self
+            .connection
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .cloned()
+            .map_err(ErrorRepr::RedisRateLimit)?)
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.key_prefix)
+    }
+
+    fn period_millis(&self) -> u64 {
+        u64::try_from(self.period.as_millis()).unwrap_or(u64::MAX)
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn acquire(&self, key: &str) -> crate::Result<Result<(), Duration>> {
+        let mut connection = self.connection().await?;
+
+        let retry_after: u64 = ::redis::cmd("EVAL")
+            .arg(TOKEN_BUCKET_SCRIPT)
+            .arg(1)
+            .arg(self.key(key))
+            .arg(self.capacity)
+            .arg(self.period_millis())
+            .query_async(&mut connection)
+            .await
+            .map_err(ErrorRepr::RedisRateLimit)?;
+
+        Ok(match retry_after {
+            0 => Ok(()),
+            millis => Err(Duration::from_millis(millis)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, SystemClock};
+
+    #[test]
+    fn invalid_url() {
+        assert!(RedisRateLimitStore::new("not a url", 1, Duration::from_secs(1)).is_err());
+        assert!(
+            RedisRateLimitStore::from_config(
+                &RedisRateLimitStoreConfig::default(),
+                1,
+                Duration::from_secs(1)
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn from_config() {
+        let config = RedisRateLimitStoreConfig::builder()
+            .url("redis://localhost:6379")
+            .key_prefix("test:")
+            .build();
+
+        let store = RedisRateLimitStore::from_config(&config, 10, Duration::from_secs(60)).unwrap();
+
+        assert_eq!(store.key("ip:192.0.2.1"), "test:ip:192.0.2.1");
+        assert_eq!(store.capacity, 10);
+        assert_eq!(store.period_millis(), 60_000);
+    }
+
+    #[test]
+    fn quota_shares_connection() {
+        let store =
+            RedisRateLimitStore::new("redis://localhost", 10, Duration::from_secs(60)).unwrap();
+
+        let route_store = store.clone().quota(1, Duration::from_secs(1));
+
+        assert!(Arc::ptr_eq(&store.connection, &route_store.connection));
+        assert_eq!(route_store.capacity, 1);
+        assert_eq!(store.capacity, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "rate limiter capacity must be positive")]
+    fn zero_capacity() {
+        let _ = RedisRateLimitStore::new("redis://localhost", 0, Duration::from_secs(60));
+    }
+
+    #[cot::test]
+    #[ignore = "requires a Redis server"]
+    async fn store_limits_requests() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost".to_owned());
+        let run = SystemClock.now().timestamp_nanos_opt().unwrap();
+        let store = RedisRateLimitStore::new(&url, 2, Duration::from_secs(60))
+            .unwrap()
+            .key_prefix(format!("cot:test:rate_limit:{run}:"));
+
+        assert!(store.acquire("key").await.unwrap().is_ok());
+        assert!(store.acquire("key").await.unwrap().is_ok());
+        let retry_after = store.acquire("key").await.unwrap().unwrap_err();
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(30));
+        assert!(store.acquire("other").await.unwrap().is_ok());
+    }
+}
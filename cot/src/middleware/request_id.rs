@@ -0,0 +1,639 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter, Write};
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use tower::Service;
+use tracing::{Instrument, warn};
+
+use crate::Error;
+use crate::error::ErrorRepr;
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::Response;
+
+/// The maximum length of a request ID.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+/// The ID of a request, assigned by [`RequestIdMiddleware`].
+///
+/// The ID is added to the tracing span of the request, sent back to the
+/// client in a response header (`X-Request-Id` by default), including in the
+/// error pages, so that the users can report it and it can be looked up in
+/// the logs. It can be accessed in the request handlers by using
+/// [`RequestId`] as an extractor.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::RequestId;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::{Body, StatusCode};
+///
+/// async fn index(request_id: RequestId) -> cot::Result<Response> {
+///     Ok(Response::new_html(
+///         StatusCode::OK,
+///         Body::fixed(format!("Your request ID is {request_id}")),
+///     ))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(Arc<str>);
+
+impl RequestId {
+    /// Returns the request ID as a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::RequestId;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request_id: RequestId) -> cot::Result<Response> {
+    ///     let request_id: &str = request_id.as_str();
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the ID of the request being currently handled, if it has been
+    /// assigned by [`RequestIdMiddleware`].
+    ///
+    /// This is useful where the request is not available, such as in the
+    /// custom error page handlers (see
+    /// [`ErrorPageHandler`](crate::project::ErrorPageHandler)) or deep in the
+    /// application code. In the request handlers, prefer using [`RequestId`]
+    /// as an extractor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::RequestId;
+    /// use cot::project::ErrorPageHandler;
+    /// use cot::response::{Response, ResponseExt};
+    /// use cot::{Body, StatusCode};
+    ///
+    /// struct MyServerErrorHandler;
+    /// impl ErrorPageHandler for MyServerErrorHandler {
+    ///     fn handle(&self) -> cot::Result<Response> {
+    ///         let message = match RequestId::current() {
+    ///             Some(id) => format!("Something went wrong. Request ID: {id}"),
+    ///             None => "Something went wrong.".to_owned(),
+    ///         };
+    ///         Ok(Response::new_html(
+    ///             StatusCode::INTERNAL_SERVER_ERROR,
+    ///             Body::fixed(message),
+    ///         ))
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        CURRENT_REQUEST_ID.try_with(Clone::clone).ok()
+    }
+
+    /// Returns the request ID if it is non-empty, at most 128 bytes long, and
+    /// only consists of visible ASCII characters.
+    fn parse(id: &[u8]) -> Option<Self> {
+        let valid = !id.is_empty()
+            && id.len() <= MAX_REQUEST_ID_LENGTH
+            && id.iter().all(u8::is_ascii_graphic);
+        valid.then(|| {
+            Self(Arc::from(
+                std::str::from_utf8(id).expect("ASCII is always valid UTF-8"),
+            ))
+        })
+    }
+
+    pub(crate) fn try_from_extensions(extensions: &http::Extensions) -> crate::Result<&Self> {
+        extensions
+            .get::<Self>()
+            .ok_or_else(|| Error::new(ErrorRepr::RequestIdMissing))
+    }
+
+    fn header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.0).expect("request IDs are always valid header values")
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+type Generator = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// A middleware that assigns an ID to each request.
+///
+/// The ID is generated with the generator set with [`Self::generator`]
+/// (random version 4 UUIDs by default), or, if [`Self::trust_incoming`] is
+/// enabled, taken from the request header. It's then made available as the
+/// [`RequestId`] extractor, recorded as the `request_id` field of the tracing
+/// span of the request, and sent back in the response header, including the
+/// error pages generated by Cot.
+///
+/// The header name and whether the incoming IDs are trusted can be
+/// configured in the `[middlewares.request_id]` section of the config (see
+/// [`RequestIdMiddlewareConfig`](crate::config::RequestIdMiddlewareConfig)).
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::RequestIdMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(RequestIdMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RequestIdMiddleware {
+    header_name: HeaderName,
+    trust_incoming: bool,
+    generator: Generator,
+}
+
+impl std::fmt::Debug for RequestIdMiddleware {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestIdMiddleware")
+            .field("header_name", &self.header_name)
+            .field("trust_incoming", &self.trust_incoming)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RequestIdMiddleware {
+    /// Creates a new instance of [`RequestIdMiddleware`] generating random
+    /// version 4 UUIDs, sent in the `X-Request-Id` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::RequestIdMiddleware;
+    ///
+    /// let middleware = RequestIdMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        let config = crate::config::RequestIdMiddlewareConfig::default();
+        Self {
+            header_name: config.header_name,
+            trust_incoming: config.trust_incoming,
+            generator: Arc::new(uuid_v4),
+        }
+    }
+
+    /// Creates a new instance of [`RequestIdMiddleware`] from the application
+    /// context, using the settings from the `[middlewares.request_id]`
+    /// section of the config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::RequestIdMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(RequestIdMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        let config = &context.config().middlewares.request_id;
+        Self::new()
+            .header_name(config.header_name.clone())
+            .trust_incoming(config.trust_incoming)
+    }
+
+    /// Sets the name of the header carrying the request ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::HeaderName;
+    /// use cot::middleware::RequestIdMiddleware;
+    ///
+    /// let middleware =
+    ///     RequestIdMiddleware::new().header_name(HeaderName::from_static("x-correlation-id"));
+    /// ```
+    #[must_use]
+    pub fn header_name(mut self, header_name: HeaderName) -> Self {
+        self.header_name = header_name;
+        self
+    }
+
+    /// Sets whether to use the request ID sent by the client instead of
+    /// generating a new one.
+    ///
+    /// Only enable this when the application is running behind a proxy that
+    /// sets the request ID header. Invalid IDs (empty, longer than 128 bytes
+    /// or containing anything but visible ASCII characters) are replaced with
+    /// generated ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::RequestIdMiddleware;
+    ///
+    /// let middleware = RequestIdMiddleware::new().trust_incoming(true);
+    /// ```
+    #[must_use]
+    pub fn trust_incoming(mut self, trust_incoming: bool) -> Self {
+        self.trust_incoming = trust_incoming;
+        self
+    }
+
+    /// Sets the function generating the request IDs, such as the version 7
+    /// UUID or ULID generators from the `uuid` or `ulid` crates.
+    ///
+    /// The generated IDs must follow the same rules as the incoming ones;
+    /// invalid IDs are replaced with random version 4 UUIDs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// use cot::middleware::RequestIdMiddleware;
+    ///
+    /// static COUNTER: AtomicU64 = AtomicU64::new(0);
+    ///
+    /// let middleware = RequestIdMiddleware::new().generator(|| {
+    ///     let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    ///     format!("req-{id}")
+    /// });
+    /// ```
+    #[must_use]
+    pub fn generator<F>(mut self, generator: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        self.generator = Arc::new(generator);
+        self
+    }
+
+    fn request_id(&self, headers: &HeaderMap) -> RequestId {
+        if self.trust_incoming {
+            if let Some(id) = headers
+                .get(&self.header_name)
+                .and_then(|value| RequestId::parse(value.as_bytes()))
+            {
+                return id;
+            }
+        }
+
+        let id = (self.generator)();
+        RequestId::parse(id.as_bytes()).unwrap_or_else(|| {
+            warn!("Generated request ID `{id}` is invalid, using a random one instead");
+            RequestId(Arc::from(uuid_v4()))
+        })
+    }
+}
+
+impl Default for RequestIdMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for RequestIdMiddleware {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService {
+            inner,
+            middleware: self.clone(),
+        }
+    }
+}
+
+/// Service that assigns an ID to each request.
+///
+/// Used by [`RequestIdMiddleware`].
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+    middleware: RequestIdMiddleware,
+}
+
+impl<S> Service<Request> for RequestIdService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let header_name = self.middleware.header_name.clone();
+        let id = self.middleware.request_id(req.headers());
+
+        req.headers_mut()
+            .insert(header_name.clone(), id.header_value());
+        req.extensions_mut().insert(id.clone());
+        if let Some(slot) = req.extensions().get::<RequestIdSlot>() {
+            let _ = slot.0.set((header_name.clone(), id.clone()));
+        }
+
+        let span = tracing::info_span!("request", request_id = %id);
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(
+            CURRENT_REQUEST_ID
+                .scope(id.clone(), async move {
+                    let mut response = inner.call(req).await?;
+                    response
+                        .headers_mut()
+                        .entry(header_name)
+                        .or_insert_with(|| id.header_value());
+                    Ok(response)
+                })
+                .instrument(span),
+        )
+    }
+}
+
+/// The request ID assigned by [`RequestIdMiddleware`], shared with the
+/// top-level request handler so that it can be added to the error pages,
+/// which are generated outside of the middleware stack.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestIdSlot(Arc<OnceLock<(HeaderName, RequestId)>>);
+
+impl RequestIdSlot {
+    /// Returns the tracing span of the request, with the request ID if it's
+    /// been assigned.
+    pub(crate) fn span(&self) -> tracing::Span {
+        match self.0.get() {
+            Some((_, id)) => tracing::info_span!("request", request_id = %id),
+            None => tracing::Span::none(),
+        }
+    }
+
+    /// Runs `f` (building an error page) with the request ID available as
+    /// [`RequestId::current`], if it's been assigned.
+    pub(crate) fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        match self.0.get() {
+            Some((_, id)) => CURRENT_REQUEST_ID.sync_scope(id.clone(), f),
+            None => f(),
+        }
+    }
+
+    /// Adds the request ID header to an error page response, if the request
+    /// ID has been assigned.
+    pub(crate) fn tag_response(&self, response: &mut axum::response::Response) {
+        if let Some((header_name, id)) = self.0.get() {
+            response
+                .headers_mut()
+                .insert(header_name.clone(), id.header_value());
+        }
+    }
+}
+
+/// Generates a random version 4 UUID.
+fn uuid_v4() -> String {
+    thread_local! {
+        static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_os_rng());
+    }
+
+    let mut bytes = [0; 16];
+    RNG.with_borrow_mut(|rng| rng.fill_bytes(&mut bytes));
+    // version 4, variant 1 (RFC 9562)
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = bytes
+        .iter()
+        .fold(String::with_capacity(32), |mut hex, byte| {
+            write!(hex, "{byte:02x}").expect("writing to a String never fails");
+            hex
+        });
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::response::ResponseExt;
+    use crate::test::TestRequestBuilder;
+
+    async fn echo_request_id(request: Request) -> Result<Response, Error> {
+        let id = RequestId::try_from_extensions(request.extensions())?.clone();
+        Ok(Response::new_html(
+            StatusCode::OK,
+            Body::fixed(id.to_string()),
+        ))
+    }
+
+    fn request(incoming_id: Option<&'static str>) -> Request {
+        let mut request = TestRequestBuilder::get("/").build();
+        if let Some(id) = incoming_id {
+            request
+                .headers_mut()
+                .insert("x-request-id", HeaderValue::from_static(id));
+        }
+        request
+    }
+
+    async fn body(response: Response) -> String {
+        String::from_utf8(response.into_body().into_bytes().await.unwrap().to_vec()).unwrap()
+    }
+
+    #[cot::test]
+    async fn request_id_generated() {
+        let service = RequestIdMiddleware::new().layer(tower::service_fn(echo_request_id));
+
+        let response = service.oneshot(request(Some("incoming"))).await.unwrap();
+
+        let header = response.headers()["x-request-id"]
+            .to_str()
+            .unwrap()
+            .to_owned();
+        assert_eq!(header.len(), 36);
+        assert_ne!(header, "incoming");
+        assert_eq!(body(response).await, header);
+    }
+
+    #[cot::test]
+    async fn request_id_custom_generator() {
+        let service = RequestIdMiddleware::new()
+            .header_name(HeaderName::from_static("x-correlation-id"))
+            .generator(|| "custom-id".to_owned())
+            .layer(tower::service_fn(echo_request_id));
+
+        let response = service.oneshot(request(None)).await.unwrap();
+
+        assert_eq!(response.headers()["x-correlation-id"], "custom-id");
+        assert!(!response.headers().contains_key("x-request-id"));
+        assert_eq!(body(response).await, "custom-id");
+    }
+
+    #[cot::test]
+    async fn request_id_invalid_generator() {
+        let service = RequestIdMiddleware::new()
+            .generator(|| "not valid".to_owned())
+            .layer(tower::service_fn(echo_request_id));
+
+        let response = service.oneshot(request(None)).await.unwrap();
+
+        assert_eq!(response.headers()["x-request-id"].len(), 36);
+    }
+
+    #[cot::test]
+    async fn request_id_trusted_incoming() {
+        let service = RequestIdMiddleware::new()
+            .trust_incoming(true)
+            .layer(tower::service_fn(echo_request_id));
+
+        let response = service
+            .clone()
+            .oneshot(request(Some("from-proxy")))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-request-id"], "from-proxy");
+        assert_eq!(body(response).await, "from-proxy");
+
+        let response = service.oneshot(request(Some("a\tb"))).await.unwrap();
+        assert_ne!(response.headers()["x-request-id"], "a\tb");
+    }
+
+    #[cot::test]
+    async fn request_id_keeps_handler_header() {
+        let service = RequestIdMiddleware::new().layer(tower::service_fn(|_req: Request| async {
+            let mut response = Response::new_html(StatusCode::OK, Body::empty());
+            response
+                .headers_mut()
+                .insert("x-request-id", HeaderValue::from_static("upstream"));
+            Ok::<_, Error>(response)
+        }));
+
+        let response = service.oneshot(request(None)).await.unwrap();
+
+        assert_eq!(response.headers()["x-request-id"], "upstream");
+    }
+
+    #[cot::test]
+    async fn request_id_slot_for_errors() {
+        let service = RequestIdMiddleware::new()
+            .generator(|| "failing".to_owned())
+            .layer(tower::service_fn(|_req: Request| async {
+                Err::<Response, _>(Error::custom("test"))
+            }));
+        let slot = RequestIdSlot::default();
+        let mut req = request(None);
+        req.extensions_mut().insert(slot.clone());
+
+        assert!(service.oneshot(req).await.is_err());
+
+        let mut response = axum::response::Response::new(axum::body::Body::empty());
+        slot.tag_response(&mut response);
+        assert_eq!(response.headers()["x-request-id"], "failing");
+    }
+
+    #[cot::test]
+    async fn request_id_current() {
+        let service = RequestIdMiddleware::new()
+            .generator(|| "current".to_owned())
+            .layer(tower::service_fn(|_req: Request| async {
+                let id = RequestId::current().expect("request ID should be set");
+                Ok::<_, Error>(Response::new_html(
+                    StatusCode::OK,
+                    Body::fixed(id.to_string()),
+                ))
+            }));
+
+        let response = service.oneshot(request(None)).await.unwrap();
+
+        assert_eq!(body(response).await, "current");
+        assert_eq!(RequestId::current(), None);
+    }
+
+    #[cot::test]
+    async fn request_id_slot_scope() {
+        let service = RequestIdMiddleware::new()
+            .generator(|| "scoped".to_owned())
+            .layer(tower::service_fn(|_req: Request| async {
+                Err::<Response, _>(Error::custom("test"))
+            }));
+        let slot = RequestIdSlot::default();
+        assert_eq!(slot.scope(RequestId::current), None);
+        let mut req = request(None);
+        req.extensions_mut().insert(slot.clone());
+
+        assert!(service.oneshot(req).await.is_err());
+
+        let id = slot.scope(RequestId::current).unwrap();
+        assert_eq!(id.as_str(), "scoped");
+    }
+
+    #[test]
+    fn request_id_parse() {
+        assert!(RequestId::parse(b"abc-123").is_some());
+        assert!(RequestId::parse(b"").is_none());
+        assert!(RequestId::parse(b"with space").is_none());
+        assert!(RequestId::parse("zażółć".as_bytes()).is_none());
+        assert!(RequestId::parse(&[b'a'; MAX_REQUEST_ID_LENGTH]).is_some());
+        assert!(RequestId::parse(&[b'a'; MAX_REQUEST_ID_LENGTH + 1]).is_none());
+    }
+
+    #[test]
+    fn uuid_v4_format() {
+        let uuid = uuid_v4();
+
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(uuid, uuid_v4());
+    }
+}
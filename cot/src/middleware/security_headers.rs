@@ -0,0 +1,503 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use http::header::{
+    CONTENT_SECURITY_POLICY, CONTENT_SECURITY_POLICY_REPORT_ONLY, REFERRER_POLICY,
+    STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+};
+use http::{HeaderName, HeaderValue};
+use tower::Service;
+
+use crate::Error;
+use crate::config::{FrameOptions, SecurityHeadersMiddlewareConfig};
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::Response;
+
+/// A middleware that adds the security-related headers to the responses.
+///
+/// By default, the following headers are sent:
+///
+/// * `Strict-Transport-Security: max-age=31536000`, which tells the browsers to
+///   only connect to the site over HTTPS for a year,
+/// * `X-Content-Type-Options: nosniff`, which stops the browsers from guessing
+///   the content type of the responses,
+/// * `Referrer-Policy: strict-origin-when-cross-origin`, which only sends the
+///   origin of the page in the `Referer` header to the other sites,
+/// * `X-Frame-Options: DENY`, which stops the site from being displayed in a
+///   frame (protecting against clickjacking).
+///
+/// A `Content-Security-Policy` can be set too, but as it depends heavily on
+/// the site, it's not sent by default. The headers already set by the request
+/// handlers are left unchanged, so a handler can use a different policy for
+/// its responses.
+///
+/// The headers can be configured with the builder methods or in the
+/// `[middlewares.security]` section of the config (see
+/// [`SecurityHeadersMiddlewareConfig`]).
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::SecurityHeadersMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(SecurityHeadersMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersMiddleware {
+    config: SecurityHeadersMiddlewareConfig,
+}
+
+impl SecurityHeadersMiddleware {
+    /// Creates a new instance of [`SecurityHeadersMiddleware`] sending the
+    /// default headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware = SecurityHeadersMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_config(&SecurityHeadersMiddlewareConfig::default())
+    }
+
+    /// Creates a new instance of [`SecurityHeadersMiddleware`] using the
+    /// `[middlewares.security]` section of the project config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(SecurityHeadersMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::from_config(&context.config().middlewares.security)
+    }
+
+    /// Creates a new instance of [`SecurityHeadersMiddleware`] from the given
+    /// configuration.
+    ///
+    /// The invalid header values (such as a content security policy spanning
+    /// multiple lines) are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::SecurityHeadersMiddlewareConfig;
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let config = SecurityHeadersMiddlewareConfig::builder()
+    ///     .hsts_include_subdomains(true)
+    ///     .build();
+    /// let middleware = SecurityHeadersMiddleware::from_config(&config);
+    /// ```
+    #[must_use]
+    pub fn from_config(config: &SecurityHeadersMiddlewareConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Sets whether to send the `Strict-Transport-Security` header and how
+    /// long the browsers should remember to only connect to the site over
+    /// HTTPS. `None` disables the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware =
+    ///     SecurityHeadersMiddleware::new().hsts(Some(Duration::from_secs(2 * 365 * 24 * 60 * 60)));
+    /// ```
+    #[must_use]
+    pub fn hsts(mut self, max_age: Option<Duration>) -> Self {
+        self.config.hsts = max_age.is_some();
+        if let Some(max_age) = max_age {
+            self.config.hsts_max_age = max_age;
+        }
+        self
+    }
+
+    /// Sets whether the HSTS policy applies to all the subdomains of the site
+    /// as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware = SecurityHeadersMiddleware::new().hsts_include_subdomains(true);
+    /// ```
+    #[must_use]
+    pub fn hsts_include_subdomains(mut self, include_subdomains: bool) -> Self {
+        self.config.hsts_include_subdomains = include_subdomains;
+        self
+    }
+
+    /// Sets whether to add the `preload` directive to the HSTS header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware = SecurityHeadersMiddleware::new()
+    ///     .hsts_include_subdomains(true)
+    ///     .hsts_preload(true);
+    /// ```
+    #[must_use]
+    pub fn hsts_preload(mut self, preload: bool) -> Self {
+        self.config.hsts_preload = preload;
+        self
+    }
+
+    /// Sets whether to send the `X-Content-Type-Options: nosniff` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware = SecurityHeadersMiddleware::new().content_type_options(false);
+    /// ```
+    #[must_use]
+    pub fn content_type_options(mut self, enabled: bool) -> Self {
+        self.config.content_type_options = enabled;
+        self
+    }
+
+    /// Sets the value of the `Referrer-Policy` header. An empty string
+    /// disables the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware = SecurityHeadersMiddleware::new().referrer_policy("no-referrer");
+    /// ```
+    #[must_use]
+    pub fn referrer_policy(mut self, policy: impl Into<String>) -> Self {
+        self.config.referrer_policy = policy.into();
+        self
+    }
+
+    /// Sets whether the site can be displayed in a frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::FrameOptions;
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware = SecurityHeadersMiddleware::new().frame_options(FrameOptions::SameOrigin);
+    /// ```
+    #[must_use]
+    pub fn frame_options(mut self, frame_options: FrameOptions) -> Self {
+        self.config.frame_options = frame_options;
+        self
+    }
+
+    /// Sets the value of the `Content-Security-Policy` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware = SecurityHeadersMiddleware::new()
+    ///     .content_security_policy("default-src 'self'; img-src 'self' https:");
+    /// ```
+    #[must_use]
+    pub fn content_security_policy(mut self, policy: impl Into<String>) -> Self {
+        self.config.content_security_policy = Some(policy.into());
+        self
+    }
+
+    /// Sets whether to only report the violations of the content security
+    /// policy instead of enforcing it, by sending it in the
+    /// `Content-Security-Policy-Report-Only` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecurityHeadersMiddleware;
+    ///
+    /// let middleware = SecurityHeadersMiddleware::new()
+    ///     .content_security_policy("default-src 'self'")
+    ///     .content_security_policy_report_only(true);
+    /// ```
+    #[must_use]
+    pub fn content_security_policy_report_only(mut self, report_only: bool) -> Self {
+        self.config.content_security_policy_report_only = report_only;
+        self
+    }
+
+    fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let config = &self.config;
+        let mut headers = Vec::new();
+
+        if config.hsts {
+            let mut hsts = format!("max-age={}", config.hsts_max_age.as_secs());
+            if config.hsts_include_subdomains {
+                hsts.push_str("; includeSubDomains");
+            }
+            if config.hsts_preload {
+                hsts.push_str("; preload");
+            }
+            headers.push((
+                STRICT_TRANSPORT_SECURITY,
+                HeaderValue::from_str(&hsts).expect("HSTS header value is valid"),
+            ));
+        }
+
+        if config.content_type_options {
+            headers.push((X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")));
+        }
+
+        if !config.referrer_policy.is_empty() {
+            if let Ok(policy) = HeaderValue::from_str(&config.referrer_policy) {
+                headers.push((REFERRER_POLICY, policy));
+            }
+        }
+
+        match config.frame_options {
+            FrameOptions::Deny => headers.push((X_FRAME_OPTIONS, HeaderValue::from_static("DENY"))),
+            FrameOptions::SameOrigin => {
+                headers.push((X_FRAME_OPTIONS, HeaderValue::from_static("SAMEORIGIN")));
+            }
+            FrameOptions::Disabled => {}
+        }
+
+        if let Some(policy) = config
+            .content_security_policy
+            .as_deref()
+            .and_then(|policy| HeaderValue::from_str(policy).ok())
+        {
+            let name = if config.content_security_policy_report_only {
+                CONTENT_SECURITY_POLICY_REPORT_ONLY
+            } else {
+                CONTENT_SECURITY_POLICY
+            };
+            headers.push((name, policy));
+        }
+
+        headers
+    }
+}
+
+impl Default for SecurityHeadersMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for SecurityHeadersMiddleware {
+    type Service = SecurityHeadersService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SecurityHeadersService {
+            inner,
+            headers: self.headers().into(),
+        }
+    }
+}
+
+/// Service that adds the security-related headers to the responses.
+///
+/// Used by [`SecurityHeadersMiddleware`].
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersService<S> {
+    inner: S,
+    headers: Arc<[(HeaderName, HeaderValue)]>,
+}
+
+impl<S> Service<Request> for SecurityHeadersService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let headers = Arc::clone(&self.headers);
+
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+
+            let response_headers = response.headers_mut();
+            for (name, value) in headers.iter() {
+                if !response_headers.contains_key(name) {
+                    response_headers.insert(name.clone(), value.clone());
+                }
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::test::TestRequestBuilder;
+
+    async fn respond(middleware: SecurityHeadersMiddleware) -> Response {
+        let svc = tower::service_fn(|_req: Request| async {
+            Ok::<_, Error>(Response::new(Body::fixed("hello")))
+        });
+
+        middleware
+            .layer(svc)
+            .oneshot(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap()
+    }
+
+    #[cot::test]
+    async fn default_headers() {
+        let response = respond(SecurityHeadersMiddleware::new()).await;
+
+        let headers = response.headers();
+        assert_eq!(headers[STRICT_TRANSPORT_SECURITY], "max-age=31536000");
+        assert_eq!(headers[X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[REFERRER_POLICY], "strict-origin-when-cross-origin");
+        assert_eq!(headers[X_FRAME_OPTIONS], "DENY");
+        assert!(!headers.contains_key(CONTENT_SECURITY_POLICY));
+        assert!(!headers.contains_key(CONTENT_SECURITY_POLICY_REPORT_ONLY));
+    }
+
+    #[cot::test]
+    async fn configured_headers() {
+        let middleware = SecurityHeadersMiddleware::new()
+            .hsts(Some(Duration::from_secs(60)))
+            .hsts_include_subdomains(true)
+            .hsts_preload(true)
+            .referrer_policy("no-referrer")
+            .frame_options(FrameOptions::SameOrigin)
+            .content_security_policy("default-src 'self'");
+
+        let response = respond(middleware).await;
+
+        let headers = response.headers();
+        assert_eq!(
+            headers[STRICT_TRANSPORT_SECURITY],
+            "max-age=60; includeSubDomains; preload"
+        );
+        assert_eq!(headers[REFERRER_POLICY], "no-referrer");
+        assert_eq!(headers[X_FRAME_OPTIONS], "SAMEORIGIN");
+        assert_eq!(headers[CONTENT_SECURITY_POLICY], "default-src 'self'");
+    }
+
+    #[cot::test]
+    async fn disabled_headers() {
+        let middleware = SecurityHeadersMiddleware::new()
+            .hsts(None)
+            .content_type_options(false)
+            .referrer_policy("")
+            .frame_options(FrameOptions::Disabled);
+
+        let response = respond(middleware).await;
+
+        assert!(response.headers().is_empty());
+    }
+
+    #[cot::test]
+    async fn content_security_policy_report_only() {
+        let middleware = SecurityHeadersMiddleware::new()
+            .content_security_policy("default-src 'self'")
+            .content_security_policy_report_only(true);
+
+        let response = respond(middleware).await;
+
+        let headers = response.headers();
+        assert_eq!(
+            headers[CONTENT_SECURITY_POLICY_REPORT_ONLY],
+            "default-src 'self'"
+        );
+        assert!(!headers.contains_key(CONTENT_SECURITY_POLICY));
+    }
+
+    #[cot::test]
+    async fn handler_headers_are_kept() {
+        let svc = tower::service_fn(|_req: Request| async {
+            let response = http::Response::builder()
+                .header(X_FRAME_OPTIONS, "SAMEORIGIN")
+                .body(Body::empty())
+                .unwrap();
+            Ok::<_, Error>(response)
+        });
+
+        let response = SecurityHeadersMiddleware::new()
+            .layer(svc)
+            .oneshot(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()[X_FRAME_OPTIONS], "SAMEORIGIN");
+        assert_eq!(response.headers()[X_CONTENT_TYPE_OPTIONS], "nosniff");
+    }
+
+    #[test]
+    fn from_config() {
+        let config = SecurityHeadersMiddlewareConfig::builder()
+            .hsts(false)
+            .content_security_policy("default-src 'self'\n")
+            .build();
+
+        let headers = SecurityHeadersMiddleware::from_config(&config).headers();
+
+        let names: Vec<_> = headers.iter().map(|(name, _)| name.clone()).collect();
+        assert_eq!(
+            names,
+            [X_CONTENT_TYPE_OPTIONS, REFERRER_POLICY, X_FRAME_OPTIONS]
+        );
+    }
+}
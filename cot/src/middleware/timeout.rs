@@ -0,0 +1,276 @@
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use http::StatusCode;
+use tower::Service;
+use tracing::warn;
+
+use crate::config::TimeoutMiddlewareConfig;
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::{Response, ResponseExt};
+use crate::{Body, Error};
+
+/// A middleware that aborts the request handlers taking too long to return a
+/// response.
+///
+/// When a handler (including the middlewares added after this one) doesn't
+/// return a response within the configured time, it's dropped, and a
+/// `503 Service Unavailable` response (or another one, see
+/// [`Self::status_code`]) is returned instead. Only the time until the
+/// response headers are returned is limited; the response body can take
+/// longer to send.
+///
+/// The timeout can be set with [`Self::new`] or in the
+/// `[middlewares.timeout]` section of the config (see
+/// [`TimeoutMiddlewareConfig`]). It defaults to 30 seconds.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::TimeoutMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(TimeoutMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct TimeoutMiddleware {
+    duration: Duration,
+    status_code: StatusCode,
+}
+
+impl TimeoutMiddleware {
+    /// Creates a new instance of [`TimeoutMiddleware`] aborting the handlers
+    /// after `duration`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::TimeoutMiddleware;
+    ///
+    /// let middleware = TimeoutMiddleware::new(Duration::from_secs(10));
+    /// ```
+    #[must_use]
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            status_code: StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Creates a new instance of [`TimeoutMiddleware`] using the
+    /// `[middlewares.timeout]` section of the project config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::TimeoutMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(TimeoutMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::from_config(&context.config().middlewares.timeout)
+    }
+
+    fn from_config(config: &TimeoutMiddlewareConfig) -> Self {
+        Self::new(config.duration)
+    }
+
+    /// Sets the status code of the response returned when a handler times
+    /// out.
+    ///
+    /// Defaults to `503 Service Unavailable`. `504 Gateway Timeout` can be
+    /// used instead when the handlers mostly wait for other services.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::StatusCode;
+    /// use cot::middleware::TimeoutMiddleware;
+    ///
+    /// let middleware =
+    ///     TimeoutMiddleware::new(Duration::from_secs(10)).status_code(StatusCode::GATEWAY_TIMEOUT);
+    /// ```
+    #[must_use]
+    pub fn status_code(mut self, status_code: StatusCode) -> Self {
+        self.status_code = status_code;
+        self
+    }
+}
+
+impl Default for TimeoutMiddleware {
+    fn default() -> Self {
+        Self::from_config(&TimeoutMiddlewareConfig::default())
+    }
+}
+
+impl<S> tower::Layer<S> for TimeoutMiddleware {
+    type Service = TimeoutService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeoutService {
+            inner,
+            duration: self.duration,
+            status_code: self.status_code,
+        }
+    }
+}
+
+/// Service that aborts the request handlers taking too long to return a
+/// response.
+///
+/// Used by [`TimeoutMiddleware`].
+#[derive(Debug, Clone)]
+pub struct TimeoutService<S> {
+    inner: S,
+    duration: Duration,
+    status_code: StatusCode,
+}
+
+impl<S> Service<Request> for TimeoutService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let duration = self.duration;
+        let status_code = self.status_code;
+        let method = req.method().clone();
+        let path = req.uri().path().to_owned();
+
+        Box::pin(async move {
+            if let Ok(result) = tokio::time::timeout(duration, inner.call(req)).await {
+                result
+            } else {
+                warn!(%method, path, ?duration, "Request handler timed out");
+                Ok(timeout_response(status_code))
+            }
+        })
+    }
+}
+
+fn timeout_response(status_code: StatusCode) -> Response {
+    let message = status_code
+        .canonical_reason()
+        .unwrap_or("Request Timed Out");
+
+    Response::builder()
+        .status(status_code)
+        .body(Body::fixed(message))
+        .expect("failed to build timeout response")
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    async fn respond_after(middleware: TimeoutMiddleware, delay: Duration) -> Response {
+        let svc = tower::service_fn(move |_req: Request| async move {
+            tokio::time::sleep(delay).await;
+            Ok::<_, Error>(Response::new(Body::fixed("done")))
+        });
+
+        middleware
+            .layer(svc)
+            .oneshot(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap()
+    }
+
+    #[cot::test]
+    async fn timeout_not_exceeded() {
+        let response = respond_after(
+            TimeoutMiddleware::new(Duration::from_secs(10)),
+            Duration::ZERO,
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "done");
+    }
+
+    #[cot::test]
+    async fn timeout_exceeded() {
+        let response = respond_after(
+            TimeoutMiddleware::new(Duration::from_millis(10)),
+            Duration::from_secs(10),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "Service Unavailable"
+        );
+    }
+
+    #[cot::test]
+    async fn timeout_custom_status_code() {
+        let response = respond_after(
+            TimeoutMiddleware::new(Duration::from_millis(10))
+                .status_code(StatusCode::GATEWAY_TIMEOUT),
+            Duration::from_secs(10),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[cot::test]
+    async fn timeout_keeps_handler_errors() {
+        let svc = tower::service_fn(|_req: Request| async {
+            Err::<Response, _>(Error::custom("handler error"))
+        });
+
+        let result = TimeoutMiddleware::default()
+            .layer(svc)
+            .oneshot(TestRequestBuilder::get("/").build())
+            .await;
+
+        assert_eq!(result.unwrap_err().to_string(), "handler error");
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tower::Service;
+
+use crate::Error;
+use crate::cookie::Cookies;
+use crate::request::Request;
+use crate::response::Response;
+use crate::timezone::Timezone;
+
+/// The default name of the cookie that holds the timezone of the user.
+const DEFAULT_COOKIE_NAME: &str = "timezone";
+
+/// A middleware that resolves the active [`Timezone`] of each request.
+///
+/// The timezone is read from a cookie (named `timezone` by default) holding
+/// an offset from UTC such as `+02:00`, which is typically set by the
+/// frontend from the settings of the user's browser. If the cookie is
+/// missing or invalid, the default timezone passed to
+/// [`TimezoneMiddleware::new`] is used. The resolved timezone is made
+/// available to the request handlers with
+/// [`RequestExt::timezone`](crate::request::RequestExt::timezone) and the
+/// [`Timezone`] extractor.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::TimezoneMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+/// use cot::timezone::Timezone;
+/// use cot::{BoxedHandler, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> BoxedHandler {
+///         handler
+///             .middleware(TimezoneMiddleware::new(Timezone::UTC))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimezoneMiddleware {
+    default: Timezone,
+    cookie_name: Arc<str>,
+}
+
+impl TimezoneMiddleware {
+    /// Creates a new [`TimezoneMiddleware`] with the given default timezone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::TimezoneMiddleware;
+    ///
+    /// let middleware = TimezoneMiddleware::new("+01:00".parse().unwrap());
+    /// ```
+    #[must_use]
+    pub fn new(default: Timezone) -> Self {
+        Self {
+            default,
+            cookie_name: Arc::from(DEFAULT_COOKIE_NAME),
+        }
+    }
+
+    /// Sets the name of the cookie holding the timezone of the user.
+    ///
+    /// The default is `timezone`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::TimezoneMiddleware;
+    /// use cot::timezone::Timezone;
+    ///
+    /// let middleware = TimezoneMiddleware::new(Timezone::UTC).cookie_name("tz");
+    /// ```
+    #[must_use]
+    pub fn cookie_name(mut self, name: &str) -> Self {
+        self.cookie_name = Arc::from(name);
+        self
+    }
+}
+
+impl<S> tower::Layer<S> for TimezoneMiddleware {
+    type Service = TimezoneService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimezoneService {
+            inner,
+            default: self.default,
+            cookie_name: Arc::clone(&self.cookie_name),
+        }
+    }
+}
+
+/// Service that resolves the active timezone of the requests.
+///
+/// Used by [`TimezoneMiddleware`].
+#[derive(Debug, Clone)]
+pub struct TimezoneService<S> {
+    inner: S,
+    default: Timezone,
+    cookie_name: Arc<str>,
+}
+
+impl<S> Service<Request> for TimezoneService<S>
+where
+    S: Service<Request, Response = Response, Error = Error>,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let timezone = Cookies::from_headers(req.headers())
+            .get(&self.cookie_name)
+            .and_then(|cookie| cookie.value().parse().ok())
+            .unwrap_or(self.default);
+        req.extensions_mut().insert(timezone);
+
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderValue, StatusCode, header};
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::request::RequestExt;
+    use crate::response::ResponseExt;
+    use crate::test::TestRequestBuilder;
+
+    async fn timezone_response(request: Request) -> Result<Response, Error> {
+        let timezone = *request.timezone().unwrap();
+        Ok(Response::new_html(
+            StatusCode::OK,
+            Body::fixed(format!("timezone: {timezone}")),
+        ))
+    }
+
+    fn request(cookie: Option<&'static str>) -> Request {
+        let mut request = TestRequestBuilder::get("/").build();
+        if let Some(cookie) = cookie {
+            request
+                .headers_mut()
+                .insert(header::COOKIE, HeaderValue::from_static(cookie));
+        }
+        request
+    }
+
+    async fn resolve(middleware: TimezoneMiddleware, cookie: Option<&'static str>) -> String {
+        let service = middleware.layer(tower::service_fn(timezone_response));
+        let response = service.oneshot(request(cookie)).await.unwrap();
+        String::from_utf8(response.into_body().into_bytes().await.unwrap().to_vec()).unwrap()
+    }
+
+    #[cot::test]
+    async fn timezone_from_cookie() {
+        let middleware = TimezoneMiddleware::new(Timezone::UTC);
+
+        assert_eq!(
+            resolve(middleware, Some("theme=dark; timezone=+05:30")).await,
+            "timezone: +05:30"
+        );
+    }
+
+    #[cot::test]
+    async fn timezone_default() {
+        let default = "-03:00".parse().unwrap();
+
+        assert_eq!(
+            resolve(TimezoneMiddleware::new(default), None).await,
+            "timezone: -03:00"
+        );
+        assert_eq!(
+            resolve(
+                TimezoneMiddleware::new(default),
+                Some("timezone=Mars/Olympus")
+            )
+            .await,
+            "timezone: -03:00"
+        );
+    }
+
+    #[cot::test]
+    async fn timezone_custom_cookie_name() {
+        let middleware = TimezoneMiddleware::new(Timezone::UTC).cookie_name("tz");
+
+        assert_eq!(
+            resolve(middleware.clone(), Some("tz=+01:00")).await,
+            "timezone: +01:00"
+        );
+        assert_eq!(
+            resolve(middleware, Some("timezone=+01:00")).await,
+            "timezone: +00:00"
+        );
+    }
+}
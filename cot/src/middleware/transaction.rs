@@ -0,0 +1,208 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use tower::Service;
+use tracing::error;
+
+use crate::Error;
+use crate::db::Database;
+use crate::request::{Request, RequestExt};
+use crate::response::Response;
+
+/// A middleware that runs each request handler in a database transaction.
+///
+/// The transaction is started before the request is passed to the handler,
+/// and is available through [`RequestExt::db`] and
+/// [`RequestDb`](crate::request::extractors::RequestDb), so the handlers don't
+/// need to be modified. The transaction is committed when the handler returns
+/// a successful response, and rolled back when it returns an error or a
+/// response with a client or server error status code (`4xx` or `5xx`).
+///
+/// The middleware can be added to the whole project, or only to the routes
+/// that need it with
+/// [`Router::middleware`](crate::router::Router::middleware). If the project
+/// doesn't have a database configured, the requests are passed to the
+/// handlers unchanged.
+///
+/// For finer control over the transactions, use
+/// [`Database::transaction`] in the handlers instead.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::TransactionMiddleware;
+/// use cot::request::Request;
+/// use cot::response::Response;
+/// use cot::router::{Route, Router};
+///
+/// async fn transfer(request: Request) -> cot::Result<Response> {
+///     todo!()
+/// }
+///
+/// let scope = Router::scope("/accounts")
+///     .middleware(TransactionMiddleware::new())
+///     .route(Route::with_handler("/transfer", transfer));
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct TransactionMiddleware;
+
+impl TransactionMiddleware {
+    /// Creates a new instance of [`TransactionMiddleware`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::TransactionMiddleware;
+    ///
+    /// let middleware = TransactionMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for TransactionMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for TransactionMiddleware {
+    type Service = TransactionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TransactionService { inner }
+    }
+}
+
+/// Service that runs each request handler in a database transaction.
+///
+/// Used by [`TransactionMiddleware`].
+#[derive(Debug, Clone)]
+pub struct TransactionService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for TransactionService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let Some(database) = req.context().try_database().cloned() else {
+                return inner.call(req).await;
+            };
+
+            let transaction = Arc::new(database.begin().await?);
+            req.extensions_mut()
+                .insert(RequestTransaction(Arc::clone(&transaction)));
+
+            let result = inner.call(req).await;
+            let success = result.as_ref().is_ok_and(|response| {
+                !response.status().is_client_error() && !response.status().is_server_error()
+            });
+
+            if success {
+                transaction.commit().await?;
+            } else if let Err(rollback_error) = transaction.rollback().await {
+                error!(
+                    ?rollback_error,
+                    "Failed to roll back the request transaction"
+                );
+            }
+
+            result
+        })
+    }
+}
+
+/// The transaction the current request is handled in, returned by
+/// [`RequestExt::db`] instead of the project database.
+#[derive(Debug, Clone)]
+pub(crate) struct RequestTransaction(pub(crate) Arc<Database>);
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    async fn call_with_status(db: &TestDatabase, status: StatusCode) -> Response {
+        let svc = tower::service_fn(move |req: Request| async move {
+            assert!(req.db().is_transaction());
+            req.db()
+                .raw("INSERT INTO items (name) VALUES ('item')")
+                .await?;
+
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = status;
+            Ok::<_, Error>(response)
+        });
+
+        TransactionMiddleware::new()
+            .layer(svc)
+            .oneshot(TestRequestBuilder::get("/").database(db.database()).build())
+            .await
+            .unwrap()
+    }
+
+    /// Deletes all the items and returns how many there were.
+    async fn delete_items(db: &TestDatabase) -> u64 {
+        db.database()
+            .raw("DELETE FROM items")
+            .await
+            .unwrap()
+            .rows_affected()
+            .0
+    }
+
+    async fn test_db() -> TestDatabase {
+        let db = TestDatabase::new_sqlite().await.unwrap();
+        db.database()
+            .raw("CREATE TABLE items (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT)")
+            .await
+            .unwrap();
+        db
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn transaction_committed() {
+        let db = test_db().await;
+
+        let response = call_with_status(&db, StatusCode::OK).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(delete_items(&db).await, 1);
+        db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn transaction_rolled_back_on_error_status() {
+        let db = test_db().await;
+
+        let response = call_with_status(&db, StatusCode::INTERNAL_SERVER_ERROR).await;
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(delete_items(&db).await, 0);
+        db.cleanup().await.unwrap();
+    }
+}
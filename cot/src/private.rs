@@ -12,8 +12,24 @@
 pub use askama;
 pub use async_trait::async_trait;
 pub use bytes::Bytes;
+pub use chrono;
 pub use tokio;
 
+/// Writes the hidden form field holding the CSRF token of the current
+/// request, if [`CsrfMiddleware`](crate::middleware::CsrfMiddleware) is used.
+///
+/// This is used by the [`Form`](derive@crate::form::Form) derive macro when
+/// rendering the forms.
+///
+/// # Errors
+///
+/// Returns an error if writing to the formatter fails.
+pub fn write_csrf_token_field(f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Display::fmt(&crate::middleware::CsrfTokenField, f)
+}
+
+#[cfg(feature = "db")]
+pub use crate::db::fields::{db_enum_from_discriminant, db_enum_from_name};
 // used in the CLI
 #[cfg(feature = "db")]
 pub use crate::utils::graph::apply_permutation;
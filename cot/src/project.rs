@@ -23,13 +23,14 @@
 use std::future::poll_fn;
 use std::panic::AssertUnwindSafe;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 
 use async_trait::async_trait;
 use axum::handler::HandlerWithoutStateExt;
 use bytes::Bytes;
 use derive_more::with_trait::Debug;
 use futures_util::FutureExt;
+use http::Method;
 use http::request::Parts;
 use tower::{Layer, Service};
 use tracing::{error, info, trace};
@@ -39,20 +40,27 @@ use crate::admin::AdminModelManager;
 use crate::auth::db::DatabaseUserBackend;
 use crate::auth::{AuthBackend, NoAuthBackend};
 use crate::cli::Cli;
+use crate::clock::{Clock, SystemClock};
 #[cfg(feature = "db")]
 use crate::config::DatabaseConfig;
 use crate::config::{AuthBackendConfig, ProjectConfig};
 #[cfg(feature = "db")]
-use crate::db::Database;
-#[cfg(feature = "db")]
 use crate::db::migrations::{MigrationEngine, SyncDynMigration};
+#[cfg(feature = "db")]
+use crate::db::seed::{SeedEngine, Seeder};
+#[cfg(feature = "db")]
+use crate::db::{Database, DatabaseRouter};
 use crate::error::ErrorRepr;
 use crate::error_page::{Diagnostics, ErrorPageTrigger};
 use crate::handler::BoxedHandler;
-use crate::middleware::{IntoCotError, IntoCotErrorLayer, IntoCotResponse, IntoCotResponseLayer};
+use crate::middleware::{
+    IntoCotError, IntoCotErrorLayer, IntoCotResponse, IntoCotResponseLayer, RequestIdSlot,
+};
 use crate::request::{AppName, Request, RequestExt};
 use crate::response::{Response, ResponseExt};
-use crate::router::{Route, Router, RouterService};
+use crate::router::{Route, RouteInfo, Router, RouterService};
+use crate::state::AppState;
+use crate::utils::tasks::BackgroundTasks;
 use crate::{Body, Error, StatusCode, cli, error_page};
 
 /// A building block for a Cot project.
@@ -142,6 +150,51 @@ pub trait App: Send + Sync {
         vec![]
     }
 
+    /// Returns the seeders for the app, in the order they should be run in.
+    /// By default, it returns an empty list.
+    ///
+    /// The seeders of all the apps are run with the `seed` command of the
+    /// project's CLI, in the order the apps are registered in. See the
+    /// [`seed`](crate::db::seed) module for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use cot::App;
+    /// use cot::db::Database;
+    /// use cot::db::seed::Seeder;
+    ///
+    /// struct CategorySeeder;
+    ///
+    /// #[async_trait]
+    /// impl Seeder for CategorySeeder {
+    ///     fn name(&self) -> &str {
+    ///         "categories"
+    ///     }
+    ///
+    ///     async fn seed(&self, database: &Database) -> cot::Result<()> {
+    ///         // insert the categories if they don't exist yet
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// struct MyApp;
+    /// impl App for MyApp {
+    ///     fn name(&self) -> &str {
+    ///         "my_app"
+    ///     }
+    ///
+    ///     fn seeders(&self) -> Vec<Box<dyn Seeder>> {
+    ///         vec![Box::new(CategorySeeder)]
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "db")]
+    fn seeders(&self) -> Vec<Box<dyn Seeder>> {
+        vec![]
+    }
+
     /// Returns the admin model managers for the app. By default, it returns an
     /// empty list.
     fn admin_model_managers(&self) -> Vec<Box<dyn AdminModelManager>> {
@@ -310,6 +363,34 @@ pub trait Project {
     #[expect(unused_variables)]
     fn register_apps(&self, apps: &mut AppBuilder, context: &RegisterAppsContext) {}
 
+    /// Registers the shared application state for the project.
+    ///
+    /// The values registered here live as long as the project and can be
+    /// accessed in the request handlers with
+    /// [`RequestExt::state`](crate::request::RequestExt::state). See the
+    /// [`state`](crate::state) module documentation for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::RwLock;
+    ///
+    /// use cot::Project;
+    /// use cot::state::AppState;
+    ///
+    /// #[derive(Default)]
+    /// struct Cache(RwLock<Vec<String>>);
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn register_state(&self, state: &mut AppState) {
+    ///         state.insert(Cache::default());
+    ///     }
+    /// }
+    /// ```
+    #[expect(unused_variables)]
+    fn register_state(&self, state: &mut AppState) {}
+
     /// Sets the authentication backend to use.
     ///
     /// Note that it's typically not necessary to override this method, as it
@@ -350,6 +431,42 @@ pub trait Project {
         }
     }
 
+    /// Returns the router deciding which of the configured databases the
+    /// models use.
+    ///
+    /// The router is only useful when some named databases are configured in
+    /// [`DatabaseConfig::databases`](crate::config::DatabaseConfig::databases).
+    /// By default, there is no router, so all the models use the default
+    /// database, unless a query explicitly chooses a different one with
+    /// [`Query::using`](crate::db::query::Query::using).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::Project;
+    /// use cot::db::{DatabaseRouter, ModelInfo};
+    ///
+    /// struct ReplicaRouter;
+    /// impl DatabaseRouter for ReplicaRouter {
+    ///     fn db_for_read(&self, _model: &ModelInfo) -> Option<&str> {
+    ///         Some("replica")
+    ///     }
+    /// }
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn database_router(&self) -> Option<Arc<dyn DatabaseRouter>> {
+    ///         Some(Arc::new(ReplicaRouter))
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "db")]
+    fn database_router(&self) -> Option<Arc<dyn DatabaseRouter>> {
+        None
+    }
+
     /// Returns the middlewares for the project.
     ///
     /// This method is used to return the middlewares for the project. The
@@ -396,10 +513,9 @@ pub trait Project {
     ///
     /// # Panics
     ///
-    /// Note that this handler is exempt of the typical panic handling
-    /// machinery in Cot. This means that if this handler panics, no
-    /// response will be sent to a user. Because of that, you should
-    /// avoid panicking here and return [`Err`] instead.
+    /// If the handler panics, the panic is caught and handled the same way as
+    /// an error, but you should still avoid panicking here and return
+    /// [`Err`] instead.
     ///
     /// # Examples
     ///
@@ -441,10 +557,9 @@ pub trait Project {
     ///
     /// # Panics
     ///
-    /// Note that this handler is exempt of the typical panic handling
-    /// machinery in Cot. This means that if this handler panics, no
-    /// response will be sent to a user. Because of that, you should
-    /// avoid panicking here and return [`Err`] instead.
+    /// If the handler panics, the panic is caught and handled the same way as
+    /// an error, but you should still avoid panicking here and return
+    /// [`Err`] instead.
     ///
     /// # Examples
     ///
@@ -473,6 +588,86 @@ pub trait Project {
     fn not_found_handler(&self) -> Box<dyn ErrorPageHandler> {
         Box::new(DefaultNotFoundHandler)
     }
+
+    /// Returns the handler rendering the error page for the given status
+    /// code, or `None` to use Cot's default response.
+    ///
+    /// This is called once, when the server starts, for each of the status
+    /// codes Cot renders the error pages for: `404 Not Found`,
+    /// `405 Method Not Allowed` and `500 Internal Server Error`. The
+    /// `Allow` header is always added to the `405 Method Not Allowed`
+    /// responses, so the handler doesn't need to set it.
+    ///
+    /// The default implementation returns
+    /// [`not_found_handler`](Project::not_found_handler) for
+    /// `404 Not Found`, [`server_error_handler`](Project::server_error_handler)
+    /// for `500 Internal Server Error`, and `None` otherwise, which results in
+    /// an empty `405 Method Not Allowed` response.
+    ///
+    /// The error pages are only rendered using these handlers when the
+    /// [`debug`](crate::config::ProjectConfig::debug) mode is disabled;
+    /// otherwise, Cot displays the error pages with the diagnostic
+    /// information.
+    ///
+    /// # Errors
+    ///
+    /// If the handler returns an error (or panics), the error is logged and
+    /// Cot's default response for the status code is returned to the user
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use askama::Template;
+    /// use cot::project::{ErrorPageContext, ErrorPageHandler};
+    /// use cot::response::{Response, ResponseExt};
+    /// use cot::{Body, Project, StatusCode};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn error_handler(&self, status: StatusCode) -> Option<Box<dyn ErrorPageHandler>> {
+    ///         Some(Box::new(BrandedErrorPage))
+    ///     }
+    /// }
+    ///
+    /// #[derive(Template)]
+    /// #[template(
+    ///     source = "<h1>{{ status }}</h1><p>Sorry, we couldn't handle {{ path }}.</p>",
+    ///     ext = "html"
+    /// )]
+    /// struct ErrorTemplate<'a> {
+    ///     status: StatusCode,
+    ///     path: &'a str,
+    /// }
+    ///
+    /// struct BrandedErrorPage;
+    /// impl ErrorPageHandler for BrandedErrorPage {
+    ///     fn handle(&self) -> cot::Result<Response> {
+    ///         Ok(Response::new_html(
+    ///             StatusCode::INTERNAL_SERVER_ERROR,
+    ///             Body::fixed("Internal Server Error"),
+    ///         ))
+    ///     }
+    ///
+    ///     fn handle_with_context(&self, context: &ErrorPageContext) -> cot::Result<Response> {
+    ///         let template = ErrorTemplate {
+    ///             status: context.status(),
+    ///             path: context.path(),
+    ///         };
+    ///         Ok(Response::new_html(
+    ///             context.status(),
+    ///             Body::fixed(template.render()?),
+    ///         ))
+    ///     }
+    /// }
+    /// ```
+    fn error_handler(&self, status: StatusCode) -> Option<Box<dyn ErrorPageHandler>> {
+        match status {
+            StatusCode::NOT_FOUND => Some(self.not_found_handler()),
+            StatusCode::INTERNAL_SERVER_ERROR => Some(self.server_error_handler()),
+            _ => None,
+        }
+    }
 }
 
 /// An alias for `ProjectContext` in appropriate phase for use with the
@@ -511,9 +706,11 @@ pub type MiddlewareContext = ProjectContext<WithDatabase>;
 ///     }
 /// }
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone)]
 pub struct RootHandlerBuilder<S = RouterService> {
     handler: S,
+    #[debug("{:?}", middleware_names.lock().unwrap_or_else(PoisonError::into_inner))]
+    middleware_names: Arc<Mutex<Vec<String>>>,
 }
 
 impl<S> RootHandlerBuilder<S>
@@ -546,11 +743,55 @@ where
     ///     }
     /// }
     /// ```
+    /// The middleware is listed in [`ProjectContext::middleware_stack`] under
+    /// its type name, without the module paths. Use
+    /// [`Self::named_middleware`] to list it under a different name.
     #[must_use]
     pub fn middleware<M>(
         self,
         middleware: M,
     ) -> RootHandlerBuilder<IntoCotError<IntoCotResponse<<M as Layer<S>>::Service>>>
+    where
+        M: Layer<S>,
+    {
+        let name = short_type_name(std::any::type_name::<M>());
+        self.named_middleware(name, middleware)
+    }
+
+    /// Adds middleware to the project under the given name.
+    ///
+    /// This works the same as [`Self::middleware`], but the middleware is
+    /// listed in [`ProjectContext::middleware_stack`] under `name` instead of
+    /// its type name. This is useful for middlewares with long or unhelpful
+    /// type names, such as the ones created with
+    /// [`from_fn`](crate::middleware::from_fn).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::LiveReloadMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .named_middleware("live reload", LiveReloadMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn named_middleware<M>(
+        self,
+        name: impl Into<String>,
+        middleware: M,
+    ) -> RootHandlerBuilder<IntoCotError<IntoCotResponse<<M as Layer<S>>::Service>>>
     where
         M: Layer<S>,
     {
@@ -560,8 +801,14 @@ where
             middleware,
         );
 
+        self.middleware_names
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(name.into());
+
         RootHandlerBuilder {
             handler: layer.layer(self.handler),
+            middleware_names: self.middleware_names,
         }
     }
 
@@ -592,6 +839,27 @@ where
     }
 }
 
+/// Strips the module paths from all the path segments of a type name, e.g.
+/// turns `cot::middleware::FromFnLayer<my_crate::auth>` into
+/// `FromFnLayer<auth>`.
+fn short_type_name(name: &str) -> String {
+    let is_delimiter =
+        |c: char| matches!(c, '<' | '>' | ',' | '(' | ')' | '[' | ']' | ';' | ' ' | '&');
+
+    let mut short_name = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(index) = rest.find(is_delimiter) {
+        let (path, remainder) = rest.split_at(index);
+        let (delimiter, remainder) = remainder.split_at(1);
+        short_name.push_str(path.rsplit("::").next().unwrap_or(path));
+        short_name.push_str(delimiter);
+        rest = remainder;
+    }
+    short_name.push_str(rest.rsplit("::").next().unwrap_or(rest));
+
+    short_name
+}
+
 /// A helper struct to build the apps for the project.
 ///
 /// # Examples
@@ -692,12 +960,63 @@ impl AppBuilder {
         self.urls.push(Route::with_router(url_prefix, router));
         self.register(module);
     }
+
+    /// Registers an app with views available only on the hosts matching the
+    /// given pattern.
+    ///
+    /// This works like [`Self::register_with_views`], but the app's views
+    /// only handle the requests sent to the matching hosts. The pattern can
+    /// contain parameters, such as `{tenant}.example.com`, whose values are
+    /// available to the views as path parameters. See [`Route::host`] for
+    /// more information about the host patterns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the host pattern is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::RegisterAppsContext;
+    /// use cot::{App, Project};
+    ///
+    /// struct TenantApp;
+    ///
+    /// impl App for TenantApp {
+    ///     fn name(&self) -> &'static str {
+    ///         env!("CARGO_PKG_NAME")
+    ///     }
+    /// }
+    ///
+    /// struct SaasProject;
+    /// impl Project for SaasProject {
+    ///     fn register_apps(&self, apps: &mut cot::AppBuilder, _context: &RegisterAppsContext) {
+    ///         apps.register_with_views_on_host(TenantApp, "{tenant}.example.com", "");
+    ///     }
+    /// }
+    /// ```
+    pub fn register_with_views_on_host<T: App + 'static>(
+        &mut self,
+        module: T,
+        host_pattern: &str,
+        url_prefix: &str,
+    ) {
+        let mut router = module.router();
+        router.set_app_name(AppName(module.name().to_owned()));
+
+        self.urls
+            .push(Route::with_router(url_prefix, router).host(host_pattern));
+        self.register(module);
+    }
 }
 
 /// A trait for defining custom error page handlers.
 ///
 /// This is useful with [`Project::server_error_handler`] and
-/// [`Project::not_found_handler`].
+/// [`Project::not_found_handler`]. The ID of the failed request, if assigned
+/// by [`RequestIdMiddleware`](crate::middleware::RequestIdMiddleware), can be
+/// read in the handler with
+/// [`RequestId::current`](crate::middleware::RequestId::current).
 ///
 /// # Examples
 ///
@@ -757,6 +1076,144 @@ pub trait ErrorPageHandler: Send + Sync {
     /// }
     /// ```
     fn handle(&self) -> crate::Result<Response>;
+
+    /// Returns the error response for the request described by `context`.
+    ///
+    /// This is the method Cot calls to render the error pages. The default
+    /// implementation ignores the context and calls [`Self::handle`];
+    /// override it to include the details of the failed request, such as its
+    /// path, in the page.
+    ///
+    /// # Errors
+    ///
+    /// This method may return an error if the handler fails to build a
+    /// response. In this case, the error will be logged and a generic
+    /// error page will be returned to the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::{ErrorPageContext, ErrorPageHandler};
+    /// use cot::response::{Response, ResponseExt};
+    /// use cot::{Body, StatusCode};
+    ///
+    /// struct MyHandler;
+    /// impl ErrorPageHandler for MyHandler {
+    ///     fn handle(&self) -> cot::Result<Response> {
+    ///         Ok(Response::new_html(
+    ///             StatusCode::NOT_FOUND,
+    ///             Body::fixed("Not Found"),
+    ///         ))
+    ///     }
+    ///
+    ///     fn handle_with_context(&self, context: &ErrorPageContext) -> cot::Result<Response> {
+    ///         Ok(Response::new_html(
+    ///             context.status(),
+    ///             Body::fixed(format!("No page at {}", context.path())),
+    ///         ))
+    ///     }
+    /// }
+    /// ```
+    fn handle_with_context(&self, context: &ErrorPageContext) -> crate::Result<Response> {
+        let _ = context;
+        self.handle()
+    }
+}
+
+/// The details of a failed request, passed to
+/// [`ErrorPageHandler::handle_with_context`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::StatusCode;
+/// use cot::http::Method;
+/// use cot::project::ErrorPageContext;
+///
+/// let context = ErrorPageContext::new(StatusCode::NOT_FOUND, Method::GET, "/missing");
+/// assert_eq!(context.status(), StatusCode::NOT_FOUND);
+/// assert_eq!(context.path(), "/missing");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorPageContext {
+    status: StatusCode,
+    method: Method,
+    path: String,
+}
+
+impl ErrorPageContext {
+    /// Creates a new error page context.
+    ///
+    /// This is mostly useful for testing custom [`ErrorPageHandler`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::http::Method;
+    /// use cot::project::ErrorPageContext;
+    ///
+    /// let context = ErrorPageContext::new(StatusCode::NOT_FOUND, Method::GET, "/missing");
+    /// ```
+    #[must_use]
+    pub fn new(status: StatusCode, method: Method, path: impl Into<String>) -> Self {
+        Self {
+            status,
+            method,
+            path: path.into(),
+        }
+    }
+
+    /// Returns the status code of the error page.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::http::Method;
+    /// use cot::project::ErrorPageContext;
+    ///
+    /// let context = ErrorPageContext::new(StatusCode::NOT_FOUND, Method::GET, "/missing");
+    /// assert_eq!(context.status(), StatusCode::NOT_FOUND);
+    /// ```
+    #[must_use]
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the HTTP method of the failed request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::http::Method;
+    /// use cot::project::ErrorPageContext;
+    ///
+    /// let context = ErrorPageContext::new(StatusCode::NOT_FOUND, Method::GET, "/missing");
+    /// assert_eq!(context.method(), Method::GET);
+    /// ```
+    #[must_use]
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the path of the failed request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::http::Method;
+    /// use cot::project::ErrorPageContext;
+    ///
+    /// let context = ErrorPageContext::new(StatusCode::NOT_FOUND, Method::GET, "/missing");
+    /// assert_eq!(context.path(), "/missing");
+    /// ```
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
 }
 
 struct DefaultNotFoundHandler;
@@ -839,9 +1296,12 @@ impl Bootstrapper<Uninitialized> {
     /// ```
     #[must_use]
     pub fn new<P: Project + 'static>(project: P) -> Self {
+        let mut context = ProjectContext::new();
+        project.register_state(&mut context.state);
+
         Self {
             project: Box::new(project),
-            context: ProjectContext::new(),
+            context,
             handler: (),
         }
     }
@@ -865,31 +1325,100 @@ impl<S: BootstrapPhase> Bootstrapper<S> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn project(&self) -> &dyn Project {
-        self.project.as_ref()
+    pub fn project(&self) -> &dyn Project {
+        self.project.as_ref()
+    }
+
+    /// Returns the context for the bootstrapper.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::{Bootstrapper, WithConfig};
+    /// use cot::{App, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {}
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let bootstrapper = Bootstrapper::new(MyProject);
+    /// let context = bootstrapper.context();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn context(&self) -> &ProjectContext<S> {
+        &self.context
+    }
+
+    /// Adds a value to the shared application state.
+    ///
+    /// This is an alternative to [`Project::register_state`] for when the
+    /// value is only known at the time the project is bootstrapped. If there
+    /// already is a value of the same type in the state, it is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// use cot::Project;
+    /// use cot::project::Bootstrapper;
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {}
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let bootstrapper = Bootstrapper::new(MyProject)
+    ///     .with_state(AtomicU64::new(0))
+    ///     .with_config(cot::config::ProjectConfig::default())
+    ///     .boot()
+    ///     .await?;
+    /// assert!(bootstrapper.context().state().get::<AtomicU64>().is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_state<T: Send + Sync + 'static>(mut self, state: T) -> Self {
+        self.context.state.insert(state);
+        self
     }
 
-    /// Returns the context for the bootstrapper.
+    /// Sets the clock used by the project as the source of the current time.
+    ///
+    /// By default, the [`SystemClock`] is used. This is mostly useful in tests
+    /// to control the time seen by the time-dependent parts of the project,
+    /// using a [`TestClock`](crate::test::TestClock).
     ///
     /// # Examples
     ///
     /// ```
-    /// use cot::project::{Bootstrapper, WithConfig};
-    /// use cot::{App, Project};
+    /// use cot::Project;
+    /// use cot::clock::Clock;
+    /// use cot::project::Bootstrapper;
+    /// use cot::test::TestClock;
     ///
     /// struct MyProject;
     /// impl Project for MyProject {}
     ///
     /// # #[tokio::main]
     /// # async fn main() -> cot::Result<()> {
-    /// let bootstrapper = Bootstrapper::new(MyProject);
-    /// let context = bootstrapper.context();
+    /// let clock = TestClock::new();
+    /// let bootstrapper = Bootstrapper::new(MyProject)
+    ///     .with_clock(clock.clone())
+    ///     .with_config(cot::config::ProjectConfig::default())
+    ///     .boot()
+    ///     .await?;
+    /// assert_eq!(bootstrapper.context().clock().now(), clock.now());
     /// # Ok(())
     /// # }
     /// ```
     #[must_use]
-    pub fn context(&self) -> &ProjectContext<S> {
-        &self.context
+    pub fn with_clock<C: Clock>(mut self, clock: C) -> Self {
+        self.context.clock = Arc::new(clock);
+        self
     }
 }
 
@@ -910,10 +1439,14 @@ impl Bootstrapper<Uninitialized> {
     /// Reads the configuration of the project and moves to the next
     /// bootstrapping phase.
     ///
+    /// The configuration is validated with [`ProjectConfig::validate`]: the
+    /// warnings are logged, and the errors make this method fail, so that the
+    /// project doesn't start with an invalid configuration.
+    ///
     /// # Errors
     ///
     /// This method may return an error if it cannot read the configuration of
-    /// the project.
+    /// the project, or if the configuration is invalid.
     ///
     /// # Examples
     ///
@@ -940,6 +1473,10 @@ impl Bootstrapper<Uninitialized> {
     /// ```
     pub fn with_config_name(self, config_name: &str) -> cot::Result<Bootstrapper<WithConfig>> {
         let config = self.project.config(config_name)?;
+        config
+            .validate()
+            .into_result()
+            .map_err(ErrorRepr::InvalidConfig)?;
 
         Ok(self.with_config(config))
     }
@@ -1167,7 +1704,12 @@ impl Bootstrapper<WithApps> {
     #[expect(clippy::future_not_send)]
     pub async fn with_database(self) -> cot::Result<Bootstrapper<WithDatabase>> {
         #[cfg(feature = "db")]
-        let database = Self::init_database(&self.context.config.database).await?;
+        let database = Self::init_database(
+            &self.context.config.database,
+            &self.context.clock,
+            self.project.database_router(),
+        )
+        .await?;
         let context = self.context.with_database(
             #[cfg(feature = "db")]
             database,
@@ -1181,14 +1723,36 @@ impl Bootstrapper<WithApps> {
     }
 
     #[cfg(feature = "db")]
-    async fn init_database(config: &DatabaseConfig) -> cot::Result<Option<Arc<Database>>> {
-        match &config.url {
-            Some(url) => {
-                let database = Database::new(url.as_str()).await?;
-                Ok(Some(Arc::new(database)))
-            }
-            None => Ok(None),
+    async fn init_database(
+        config: &DatabaseConfig,
+        clock: &Arc<dyn Clock>,
+        router: Option<Arc<dyn DatabaseRouter>>,
+    ) -> cot::Result<Option<Arc<Database>>> {
+        let Some(url) = &config.url else {
+            return Ok(None);
+        };
+
+        let mut database = Database::new_with_options(url.as_str(), &config.pool_options())
+            .await?
+            .with_clock(Arc::clone(clock))
+            .with_query_logging(config.query_logging());
+        for (name, named_config) in &config.databases {
+            let named_url = named_config
+                .url
+                .as_ref()
+                .ok_or_else(|| Error::from(ErrorRepr::DatabaseUrlMissing(name.clone())))?;
+            let named_database =
+                Database::new_with_options(named_url.as_str(), &named_config.pool_options())
+                    .await?
+                    .with_clock(Arc::clone(clock))
+                    .with_query_logging(named_config.query_logging());
+            database = database.with_database(name.clone(), Arc::new(named_database));
+        }
+        if let Some(router) = router {
+            database = database.with_router(router);
         }
+
+        Ok(Some(Arc::new(database)))
     }
 }
 
@@ -1232,13 +1796,25 @@ impl Bootstrapper<WithDatabase> {
     #[expect(clippy::unused_async, clippy::future_not_send)]
     pub async fn boot(self) -> cot::Result<Bootstrapper<Initialized>> {
         let router_service = RouterService::new(Arc::clone(&self.context.router));
+        let middleware_names = Arc::new(Mutex::new(Vec::new()));
         let handler = RootHandlerBuilder {
             handler: router_service,
+            middleware_names: Arc::clone(&middleware_names),
         };
         let handler = self.project.middlewares(handler, &self.context);
+        let mut middleware_stack = std::mem::take(
+            &mut *middleware_names
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        // the middlewares added last are the outermost ones
+        middleware_stack.reverse();
 
         let auth_backend = self.project.auth_backend(&self.context);
-        let context = self.context.with_auth(auth_backend);
+        let context = self
+            .context
+            .with_auth(auth_backend)
+            .with_middleware_stack(middleware_stack);
 
         Ok(Bootstrapper {
             project: self.project,
@@ -1454,11 +2030,71 @@ pub struct ProjectContext<S: BootstrapPhase = Initialized> {
     database: S::Database,
     #[debug("..")]
     auth_backend: S::AuthBackend,
+    state: AppState,
+    clock: Arc<dyn Clock>,
+    background_tasks: BackgroundTasks,
+    middleware_stack: Vec<String>,
+}
+
+impl<S: BootstrapPhase> ProjectContext<S> {
+    /// Returns the shared application state for the project.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     let counter = request.context().state().get::<AtomicU64>();
+    ///     // can also be accessed via:
+    ///     let counter = request.state::<AtomicU64>()?;
+    ///
+    ///     // ...
+    /// #    todo!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn state(&self) -> &AppState {
+        &self.state
+    }
+
+    /// Returns the clock used by the project as the source of the current
+    /// time.
+    ///
+    /// This is the [`SystemClock`] unless a different clock has been set
+    /// with [`Bootstrapper::with_clock`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     let now = request.context().clock().now();
+    ///
+    ///     // ...
+    /// #    todo!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Returns the background tasks of the project, which are stopped when
+    /// the server shuts down gracefully.
+    pub(crate) fn background_tasks(&self) -> &BackgroundTasks {
+        &self.background_tasks
+    }
 }
 
 impl ProjectContext<Uninitialized> {
     #[must_use]
-    pub(crate) const fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             config: (),
             apps: (),
@@ -1466,6 +2102,10 @@ impl ProjectContext<Uninitialized> {
             #[cfg(feature = "db")]
             database: (),
             auth_backend: (),
+            state: AppState::new(),
+            clock: Arc::new(SystemClock),
+            background_tasks: BackgroundTasks::default(),
+            middleware_stack: Vec::new(),
         }
     }
 
@@ -1477,6 +2117,10 @@ impl ProjectContext<Uninitialized> {
             #[cfg(feature = "db")]
             database: self.database,
             auth_backend: self.auth_backend,
+            state: self.state,
+            clock: self.clock,
+            background_tasks: self.background_tasks,
+            middleware_stack: self.middleware_stack,
         }
     }
 }
@@ -1517,6 +2161,10 @@ impl ProjectContext<WithConfig> {
             #[cfg(feature = "db")]
             database: self.database,
             auth_backend: self.auth_backend,
+            state: self.state,
+            clock: self.clock,
+            background_tasks: self.background_tasks,
+            middleware_stack: self.middleware_stack,
         }
     }
 }
@@ -1556,6 +2204,10 @@ impl ProjectContext<WithApps> {
             #[cfg(feature = "db")]
             database,
             auth_backend: self.auth_backend,
+            state: self.state,
+            clock: self.clock,
+            background_tasks: self.background_tasks,
+            middleware_stack: self.middleware_stack,
         }
     }
 }
@@ -1570,6 +2222,10 @@ impl ProjectContext<WithDatabase> {
             auth_backend,
             #[cfg(feature = "db")]
             database: self.database,
+            state: self.state,
+            clock: self.clock,
+            background_tasks: self.background_tasks,
+            middleware_stack: self.middleware_stack,
         }
     }
 }
@@ -1581,6 +2237,8 @@ impl ProjectContext<Initialized> {
         router: <Initialized as BootstrapPhase>::Router,
         auth_backend: <Initialized as BootstrapPhase>::AuthBackend,
         #[cfg(feature = "db")] database: <Initialized as BootstrapPhase>::Database,
+        state: AppState,
+        clock: Arc<dyn Clock>,
     ) -> Self {
         Self {
             config,
@@ -1589,8 +2247,49 @@ impl ProjectContext<Initialized> {
             #[cfg(feature = "db")]
             database,
             auth_backend,
+            state,
+            clock,
+            background_tasks: BackgroundTasks::default(),
+            middleware_stack: Vec::new(),
         }
     }
+
+    fn with_middleware_stack(mut self, middleware_stack: Vec<String>) -> Self {
+        self.middleware_stack = middleware_stack;
+        self
+    }
+
+    /// Returns the names of the middlewares applied to the project's root
+    /// handler.
+    ///
+    /// The middlewares are listed in the order in which they process the
+    /// requests, starting with the outermost one, which is the middleware
+    /// added last in [`Project::middlewares`]. The names are the ones given
+    /// to [`RootHandlerBuilder::named_middleware`], or the type names of the
+    /// middlewares (without the module paths) when added with
+    /// [`RootHandlerBuilder::middleware`].
+    ///
+    /// The stack can also be printed with the `middlewares` CLI command.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     for name in request.context().middleware_stack() {
+    ///         println!("{name}");
+    ///     }
+    ///
+    ///     // ...
+    /// #    todo!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn middleware_stack(&self) -> &[String] {
+        &self.middleware_stack
+    }
 }
 
 impl<S: BootstrapPhase<Router = Arc<Router>>> ProjectContext<S> {
@@ -1617,6 +2316,32 @@ impl<S: BootstrapPhase<Router = Arc<Router>>> ProjectContext<S> {
     pub fn router(&self) -> &Arc<Router> {
         &self.router
     }
+
+    /// Returns the information about all the routes of the project, in the
+    /// order they are matched.
+    ///
+    /// See [`Router::route_infos`] for more information. The routes can also
+    /// be listed with the `routes` CLI command.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     for route in request.context().routes() {
+    ///         println!("{} {:?}", route.path(), route.name());
+    ///     }
+    ///
+    ///     // ...
+    /// #    todo!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        self.router.route_infos()
+    }
 }
 impl<S: BootstrapPhase<AuthBackend = Arc<dyn AuthBackend>>> ProjectContext<S> {
     /// Returns the authentication backend for the project.
@@ -1696,6 +2421,20 @@ impl<S: BootstrapPhase<Database = Option<Arc<Database>>>> ProjectContext<S> {
     }
 }
 
+/// Creates a migration engine with the migrations of all the given apps.
+#[cfg(feature = "db")]
+pub(crate) fn migration_engine(apps: &[Box<dyn App>]) -> cot::Result<MigrationEngine> {
+    let migrations: Vec<Box<SyncDynMigration>> =
+        apps.iter().flat_map(|app| app.migrations()).collect();
+    Ok(MigrationEngine::new(migrations)?)
+}
+
+/// Creates a seed engine with the seeders of all the given apps.
+#[cfg(feature = "db")]
+pub(crate) fn seed_engine(apps: &[Box<dyn App>]) -> SeedEngine {
+    SeedEngine::new(apps.iter().flat_map(|app| app.seeders()))
+}
+
 /// Runs the Cot project on the given address.
 ///
 /// This function takes a Cot project and an address string and runs the
@@ -1719,10 +2458,12 @@ pub async fn run(bootstrapper: Bootstrapper<Initialized>, address_str: &str) ->
 /// This function takes a Cot project and a [`tokio::net::TcpListener`] and
 /// runs the project on the given listener.
 ///
-/// If you need more control over the server listening socket, such as modifying
-/// the underlying buffer sizes, you can create a [`tokio::net::TcpListener`]
-/// and pass it to this function. Otherwise, [`run`] function will be more
-/// convenient.
+/// If you need more control over the server listening socket, such as binding
+/// it with custom socket options, you can create a
+/// [`tokio::net::TcpListener`] and pass it to this function. Otherwise, [`run`]
+/// function will be more convenient. Note that the buffer sizes of the
+/// accepted connections can be configured with
+/// [`ServerConfig::buffers`](crate::config::ServerConfig::buffers).
 ///
 /// # Errors
 ///
@@ -1733,20 +2474,12 @@ pub async fn run_at(
     bootstrapper: Bootstrapper<Initialized>,
     listener: tokio::net::TcpListener,
 ) -> cot::Result<()> {
-    let not_found_handler: Arc<dyn ErrorPageHandler> =
-        bootstrapper.project().not_found_handler().into();
-    let server_error_handler: Arc<dyn ErrorPageHandler> =
-        bootstrapper.project().server_error_handler().into();
+    let error_handlers = Arc::new(ErrorPageHandlers::new(bootstrapper.project()));
     let (mut context, mut project_handler) = bootstrapper.into_context_and_handler();
 
     #[cfg(feature = "db")]
     if let Some(database) = &context.database {
-        let mut migrations: Vec<Box<SyncDynMigration>> = Vec::new();
-        for app in &context.apps {
-            migrations.extend(app.migrations());
-        }
-        let migration_engine = MigrationEngine::new(migrations)?;
-        migration_engine.run(database).await?;
+        migration_engine(&context.apps)?.run(database).await?;
     }
 
     let mut apps = std::mem::take(&mut context.apps);
@@ -1760,11 +2493,14 @@ pub async fn run_at(
     let context = Arc::new(context);
     let is_debug = context.config().debug;
     let register_panic_hook = context.config().register_panic_hook;
-    #[cfg(feature = "db")]
+    let server_config = context.config().server.clone();
     let context_cleanup = context.clone();
 
     let handler = move |axum_request: axum::extract::Request| async move {
-        let request = request_axum_to_cot(axum_request, Arc::clone(&context));
+        let mut request = request_axum_to_cot(axum_request, Arc::clone(&context));
+        let request_line = (request.method().clone(), request.uri().path().to_owned());
+        let request_id = RequestIdSlot::default();
+        request.extensions_mut().insert(request_id.clone());
         let (request_parts, request) = request_parts_for_diagnostics(request);
 
         let catch_unwind_response = AssertUnwindSafe(pass_to_axum(request, &mut project_handler))
@@ -1786,7 +2522,8 @@ pub async fn run_at(
         match response {
             Ok(response) => response,
             Err(error_response) => {
-                if is_debug {
+                let _span = request_id.span().entered();
+                let mut response = if is_debug {
                     let diagnostics = Diagnostics::new(
                         context.config().clone(),
                         Arc::clone(&context.router),
@@ -1796,11 +2533,14 @@ pub async fn run_at(
                     build_cot_error_page(error_response, &diagnostics)
                 } else {
                     build_custom_error_page(
-                        &not_found_handler,
-                        &server_error_handler,
+                        &error_handlers,
                         &error_response,
+                        request_line,
+                        &request_id,
                     )
-                }
+                };
+                request_id.tag_response(&mut response);
+                response
             }
         }
     };
@@ -1820,13 +2560,17 @@ pub async fn run_at(
         };
         std::panic::set_hook(Box::new(new_hook));
     }
-    axum::serve(listener, handler.into_make_service())
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .map_err(|e| ErrorRepr::StartServer { source: e })?;
+    crate::server::serve(
+        listener,
+        handler.into_service(),
+        &server_config,
+        shutdown_signal(),
+    )
+    .await;
     if register_panic_hook {
         let _ = std::panic::take_hook();
     }
+    context_cleanup.background_tasks().shutdown().await;
     #[cfg(feature = "db")]
     if let Some(database) = &context_cleanup.database {
         database.close().await?;
@@ -1841,6 +2585,45 @@ enum ErrorResponse {
     Panic(Box<dyn std::any::Any + Send>),
 }
 
+impl ErrorResponse {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::ErrorPageTrigger(ErrorPageTrigger::NotFound { .. }) => StatusCode::NOT_FOUND,
+            Self::ErrorPageTrigger(ErrorPageTrigger::MethodNotAllowed { .. }) => {
+                StatusCode::METHOD_NOT_ALLOWED
+            }
+            Self::ErrorReturned(_) | Self::Panic(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// The custom error page handlers of a project, as returned by
+/// [`Project::error_handler`].
+struct ErrorPageHandlers {
+    not_found: Option<Box<dyn ErrorPageHandler>>,
+    method_not_allowed: Option<Box<dyn ErrorPageHandler>>,
+    server_error: Option<Box<dyn ErrorPageHandler>>,
+}
+
+impl ErrorPageHandlers {
+    fn new(project: &dyn Project) -> Self {
+        Self {
+            not_found: project.error_handler(StatusCode::NOT_FOUND),
+            method_not_allowed: project.error_handler(StatusCode::METHOD_NOT_ALLOWED),
+            server_error: project.error_handler(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+
+    fn get(&self, status: StatusCode) -> Option<&dyn ErrorPageHandler> {
+        let handler = match status {
+            StatusCode::NOT_FOUND => &self.not_found,
+            StatusCode::METHOD_NOT_ALLOWED => &self.method_not_allowed,
+            _ => &self.server_error,
+        };
+        handler.as_deref()
+    }
+}
+
 fn build_cot_error_page(
     error_response: ErrorResponse,
     diagnostics: &Diagnostics,
@@ -1850,6 +2633,9 @@ fn build_cot_error_page(
             ErrorPageTrigger::NotFound { message } => {
                 error_page::handle_not_found(message, diagnostics)
             }
+            ErrorPageTrigger::MethodNotAllowed { allow } => {
+                error_page::build_cot_method_not_allowed_page(allow)
+            }
         },
         ErrorResponse::ErrorReturned(error) => {
             error_page::handle_response_error(&error, diagnostics)
@@ -1859,35 +2645,60 @@ fn build_cot_error_page(
 }
 
 fn build_custom_error_page(
-    not_found_handler: &Arc<dyn ErrorPageHandler>,
-    server_error_handler: &Arc<dyn ErrorPageHandler>,
+    error_handlers: &ErrorPageHandlers,
     error_response: &ErrorResponse,
+    (request_method, request_path): (Method, String),
+    request_id: &RequestIdSlot,
 ) -> axum::response::Response {
-    match error_response {
+    let error_context =
+        ErrorPageContext::new(error_response.status(), request_method, request_path);
+    let default_page = || match error_response {
         ErrorResponse::ErrorPageTrigger(ErrorPageTrigger::NotFound { .. }) => {
-            not_found_handler.handle().map_or_else(
-                |error| {
-                    error!(
-                        ?error,
-                        "Error occurred while running custom 404 Not Found handler"
-                    );
-                    error_page::build_cot_not_found_page()
-                },
-                response_cot_to_axum,
-            )
+            error_page::build_cot_not_found_page()
+        }
+        ErrorResponse::ErrorPageTrigger(ErrorPageTrigger::MethodNotAllowed { allow }) => {
+            error_page::build_cot_method_not_allowed_page(allow.clone())
         }
         ErrorResponse::ErrorReturned(_) | ErrorResponse::Panic(_) => {
-            server_error_handler.handle().map_or_else(
-                |error| {
-                    error!(
-                        ?error,
-                        "Error occurred while running custom 500 Internal Server Error handler"
-                    );
+            error_page::build_cot_server_error_page()
+        }
+    };
 
-                    error_page::build_cot_server_error_page()
-                },
-                response_cot_to_axum,
-            )
+    let Some(handler) = error_handlers.get(error_context.status()) else {
+        return default_page();
+    };
+
+    let result = request_id.scope(|| {
+        std::panic::catch_unwind(AssertUnwindSafe(|| {
+            handler.handle_with_context(&error_context)
+        }))
+    });
+    match result {
+        Ok(Ok(response)) => {
+            let mut response = response_cot_to_axum(response);
+            if let ErrorResponse::ErrorPageTrigger(ErrorPageTrigger::MethodNotAllowed { allow }) =
+                error_response
+            {
+                response
+                    .headers_mut()
+                    .insert(http::header::ALLOW, allow.clone());
+            }
+            response
+        }
+        Ok(Err(error)) => {
+            error!(
+                ?error,
+                status = %error_context.status(),
+                "Error occurred while running custom error page handler"
+            );
+            default_page()
+        }
+        Err(_) => {
+            error!(
+                status = %error_context.status(),
+                "Custom error page handler panicked"
+            );
+            default_page()
         }
     }
 }
@@ -2127,4 +2938,268 @@ mod tests {
         assert_eq!(bootstrapper.context().apps.len(), 1);
         assert_eq!(bootstrapper.context().router.routes().len(), 1);
     }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn bootstrapper_middleware_stack() {
+        struct TestProject;
+        impl Project for TestProject {
+            fn middlewares(
+                &self,
+                handler: RootHandlerBuilder,
+                _context: &MiddlewareContext,
+            ) -> BoxedHandler {
+                handler
+                    .middleware(crate::middleware::SessionMiddleware::new())
+                    .named_middleware("outer", crate::middleware::AuthMiddleware::new())
+                    .build()
+            }
+        }
+
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(ProjectConfig::default())
+            .boot()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            bootstrapper.context().middleware_stack(),
+            ["outer", "SessionMiddleware"]
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
+    async fn bootstrapper_tracks_session_janitor() {
+        struct TestProject;
+        impl Project for TestProject {
+            fn middlewares(
+                &self,
+                handler: RootHandlerBuilder,
+                context: &MiddlewareContext,
+            ) -> BoxedHandler {
+                handler
+                    .middleware(crate::middleware::SessionMiddleware::from_context(context))
+                    .build()
+            }
+        }
+
+        let config = ProjectConfig::from_toml(
+            r"
+            [middlewares.session]
+            cleanup_interval = 3600
+            ",
+        )
+        .unwrap();
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(config)
+            .boot()
+            .await
+            .unwrap();
+
+        let tasks = bootstrapper.context().background_tasks();
+        assert_eq!(tasks.len(), 1);
+        tasks.shutdown().await;
+        assert_eq!(tasks.len(), 0);
+    }
+
+    #[test]
+    fn short_type_name_strips_paths() {
+        assert_eq!(short_type_name("u32"), "u32");
+        assert_eq!(
+            short_type_name("cot::middleware::SessionMiddleware"),
+            "SessionMiddleware"
+        );
+        assert_eq!(
+            short_type_name(
+                "cot::middleware::FromFnLayer<my_crate::auth, (cot::request::Request, &str)>"
+            ),
+            "FromFnLayer<auth, (Request, &str)>"
+        );
+    }
+    #[test]
+    fn bootstrapper_state() {
+        struct TestProject;
+        impl Project for TestProject {
+            fn register_state(&self, state: &mut AppState) {
+                state.insert(1_u32);
+                state.insert("registered");
+            }
+        }
+
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_state(2_u32)
+            .with_config(ProjectConfig::default());
+
+        let state = bootstrapper.context().state();
+        assert_eq!(state.get::<u32>(), Some(&2));
+        assert_eq!(state.get::<&str>(), Some(&"registered"));
+    }
+
+    #[test]
+    fn bootstrapper_clock() {
+        struct TestProject;
+        impl Project for TestProject {}
+
+        let clock = crate::test::TestClock::new();
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_clock(clock.clone())
+            .with_config(ProjectConfig::default());
+
+        let start = bootstrapper.context().clock().now();
+        clock.advance(std::time::Duration::from_secs(30));
+        assert_eq!(
+            (bootstrapper.context().clock().now() - start).num_seconds(),
+            30
+        );
+    }
+
+    #[test]
+    fn request_state_is_shared() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let mut builder = crate::test::TestRequestBuilder::get("/");
+        builder.state(AtomicU32::new(0));
+        let request_1 = builder.build();
+        let request_2 = builder.build();
+
+        request_1
+            .state::<AtomicU32>()
+            .unwrap()
+            .fetch_add(1, Ordering::Relaxed);
+
+        let counter = request_2.state::<AtomicU32>().unwrap();
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+    }
+
+    struct ContextErrorPage;
+    impl ErrorPageHandler for ContextErrorPage {
+        fn handle(&self) -> crate::Result<Response> {
+            unreachable!("`handle_with_context` is overridden")
+        }
+
+        fn handle_with_context(&self, context: &ErrorPageContext) -> crate::Result<Response> {
+            Ok(Response::new_html(
+                context.status(),
+                Body::fixed(format!("{} {}", context.method(), context.path())),
+            ))
+        }
+    }
+
+    struct FailingErrorPage;
+    impl ErrorPageHandler for FailingErrorPage {
+        fn handle(&self) -> crate::Result<Response> {
+            Err(Error::custom("error page failed"))
+        }
+    }
+
+    struct PanickingErrorPage;
+    impl ErrorPageHandler for PanickingErrorPage {
+        fn handle(&self) -> crate::Result<Response> {
+            panic!("error page panicked")
+        }
+    }
+
+    fn error_page_handlers(handler: impl Fn() -> Box<dyn ErrorPageHandler>) -> ErrorPageHandlers {
+        ErrorPageHandlers {
+            not_found: Some(handler()),
+            method_not_allowed: Some(handler()),
+            server_error: Some(handler()),
+        }
+    }
+
+    async fn error_page(
+        error_handlers: &ErrorPageHandlers,
+        error_response: ErrorResponse,
+    ) -> (StatusCode, http::HeaderMap, String) {
+        let response = build_custom_error_page(
+            error_handlers,
+            &error_response,
+            (Method::POST, "/items".to_owned()),
+            &RequestIdSlot::default(),
+        );
+        let (parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        (
+            parts.status,
+            parts.headers,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    fn method_not_allowed() -> ErrorResponse {
+        ErrorResponse::ErrorPageTrigger(ErrorPageTrigger::MethodNotAllowed {
+            allow: http::HeaderValue::from_static("GET, HEAD"),
+        })
+    }
+
+    #[test]
+    fn project_default_error_handler() {
+        assert!(TestProject.error_handler(StatusCode::NOT_FOUND).is_some());
+        assert!(
+            TestProject
+                .error_handler(StatusCode::INTERNAL_SERVER_ERROR)
+                .is_some()
+        );
+        assert!(
+            TestProject
+                .error_handler(StatusCode::METHOD_NOT_ALLOWED)
+                .is_none()
+        );
+    }
+
+    #[cot::test]
+    async fn custom_error_page_with_context() {
+        let error_handlers = error_page_handlers(|| Box::new(ContextErrorPage));
+
+        let not_found =
+            ErrorResponse::ErrorPageTrigger(ErrorPageTrigger::NotFound { message: None });
+        let (status, _, body) = error_page(&error_handlers, not_found).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, "POST /items");
+
+        let server_error = ErrorResponse::ErrorReturned(Error::custom("test"));
+        let (status, _, _) = error_page(&error_handlers, server_error).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        let (status, headers, body) = error_page(&error_handlers, method_not_allowed()).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(headers[http::header::ALLOW], "GET, HEAD");
+        assert_eq!(body, "POST /items");
+    }
+
+    #[cot::test]
+    async fn custom_error_page_fallback() {
+        for error_handlers in [
+            error_page_handlers(|| Box::new(FailingErrorPage)),
+            error_page_handlers(|| Box::new(PanickingErrorPage)),
+        ] {
+            let not_found =
+                ErrorResponse::ErrorPageTrigger(ErrorPageTrigger::NotFound { message: None });
+            let (status, _, body) = error_page(&error_handlers, not_found).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(body, include_str!("../templates/404.html"));
+
+            let server_error = ErrorResponse::ErrorReturned(Error::custom("test"));
+            let (status, _, body) = error_page(&error_handlers, server_error).await;
+            assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+            assert_eq!(body, include_str!("../templates/500.html"));
+
+            let (status, headers, body) = error_page(&error_handlers, method_not_allowed()).await;
+            assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+            assert_eq!(headers[http::header::ALLOW], "GET, HEAD");
+            assert!(body.is_empty());
+        }
+    }
+
+    #[cot::test]
+    async fn default_method_not_allowed_page() {
+        let error_handlers = ErrorPageHandlers::new(&TestProject);
+
+        let (status, headers, body) = error_page(&error_handlers, method_not_allowed()).await;
+
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(headers[http::header::ALLOW], "GET, HEAD");
+        assert!(body.is_empty());
+    }
 }
@@ -14,23 +14,37 @@
 
 use std::borrow::Cow;
 use std::future::Future;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use bytes::Bytes;
-use http::Extensions;
 use http::request::Parts;
+use http::{Extensions, HeaderMap};
 use indexmap::IndexMap;
 
+use crate::body::BodyStream;
+use crate::cookie::Cookies;
 #[cfg(feature = "db")]
 use crate::db::Database;
 use crate::error::ErrorRepr;
+use crate::feature_flags::{FeatureFlags, FlagContext, FlagValue};
+use crate::locale::{Locale, requested_languages};
+use crate::middleware::request_body_limit;
 use crate::request::extractors::FromRequestParts;
 use crate::router::Router;
+use crate::session::Session;
+use crate::timezone::Timezone;
 use crate::{Body, Result};
 
+mod accept;
 pub mod extractors;
+mod multipart;
 mod path_params_deserializer;
 
+pub use accept::Accepts;
+pub use multipart::{Multipart, MultipartError, MultipartField, UploadedFile};
+pub(crate) use multipart::{is_multipart_form_data, peek_first_field};
+
 /// HTTP request type.
 pub type Request = http::Request<Body>;
 
@@ -193,6 +207,10 @@ pub trait RequestExt: private::Sealed {
 
     /// Get the database.
     ///
+    /// If the request is handled in a transaction started by
+    /// [`TransactionMiddleware`](crate::middleware::TransactionMiddleware),
+    /// this returns the transaction handle instead.
+    ///
     /// # Examples
     ///
     /// ```
@@ -226,6 +244,42 @@ pub trait RequestExt: private::Sealed {
     #[must_use]
     fn content_type(&self) -> Option<&http::HeaderValue>;
 
+    /// Build an absolute URL for the given path (which may include a query
+    /// string).
+    ///
+    /// If the [`base_url`](crate::config::ServerConfig::base_url) is set in
+    /// the server configuration, it's used as the base of the URL. Otherwise,
+    /// the scheme is taken from the request URI (falling back to `http`), and
+    /// the host from the `Host` header (falling back to the request URI
+    /// authority). The host has to be one of the
+    /// [`allowed_hosts`](crate::config::ServerConfig::allowed_hosts), since
+    /// it's controlled by the client.
+    ///
+    /// The `X-Forwarded-*` headers are not read by this method. When the
+    /// application is running behind a reverse proxy, use
+    /// [`ProxyHeadersMiddleware`](crate::middleware::ProxyHeadersMiddleware),
+    /// which sets the request URI to the one the client used, but only for the
+    /// requests coming from the trusted proxies.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the base URL is not configured and the request
+    /// doesn't have a valid `Host` header, or the host is not allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let reset_link = request.absolute_url("/reset-password?token=abc")?;
+    ///     // ... send the link in an email
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn absolute_url(&self, path: &str) -> Result<String>;
+
     /// Expect the content type of the request to be the given value.
     ///
     /// # Errors
@@ -259,8 +313,384 @@ pub trait RequestExt: private::Sealed {
         }
     }
 
+    /// Get the session object for the request.
+    ///
+    /// The session object provides typed, serde-backed accessors for the
+    /// session data that work with any session store. See [`Session`] for
+    /// more information.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the session is not available, which means that
+    /// [`SessionMiddleware`](crate::middleware::SessionMiddleware) was not
+    /// added to the middleware stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let session = request.session()?;
+    ///     let visits: u32 = session.get("visits").await?.unwrap_or_default();
+    ///     session.insert("visits", visits + 1).await?;
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn session(&self) -> Result<&Session> {
+        Session::try_from_extensions(self.extensions())
+    }
+
+    /// Get the locale of the request, as resolved by
+    /// [`LocaleMiddleware`](crate::middleware::LocaleMiddleware).
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the locale is not available, which means that
+    /// [`LocaleMiddleware`](crate::middleware::LocaleMiddleware) was not
+    /// added to the middleware stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let locale = request.locale()?;
+    ///     let greeting = match locale.language() {
+    ///         "pl" => "Cześć!",
+    ///         _ => "Hello!",
+    ///     };
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn locale(&self) -> Result<&Locale> {
+        Locale::try_from_extensions(self.extensions())
+    }
+
+    /// Get the active timezone of the request, as resolved by
+    /// [`TimezoneMiddleware`](crate::middleware::TimezoneMiddleware).
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the timezone is not available, which means that
+    /// [`TimezoneMiddleware`](crate::middleware::TimezoneMiddleware) was not
+    /// added to the middleware stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let now = request.timezone()?.localize(&Utc::now());
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn timezone(&self) -> Result<&Timezone> {
+        Timezone::try_from_extensions(self.extensions())
+    }
+
+    /// Get the cookies sent by the client.
+    ///
+    /// The `Cookie` headers are parsed on each call, so you might want to
+    /// keep the result if you need it multiple times. [`Cookies`] can also be
+    /// used as an extractor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let cookies = request.cookies();
+    ///     let theme = cookies
+    ///         .get("theme")
+    ///         .map_or("light", |cookie| cookie.value());
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn cookies(&self) -> Cookies {
+        Cookies::from_headers(self.headers())
+    }
+
+    /// Get the media types accepted by the client, parsed from the `Accept`
+    /// header.
+    ///
+    /// [`Accepts`] can also be used as an extractor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     if request.accepts().accepts("application/json") {
+    ///         // ...
+    ///     }
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn accepts(&self) -> Accepts {
+        Accepts::from_headers(self.headers())
+    }
+
+    /// Get the media type the client prefers among the `available` ones, or
+    /// `None` if it accepts none of them.
+    ///
+    /// This is a shortcut for `request.accepts().prefers(available)`; see
+    /// [`Accepts::prefers`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::{Response, ResponseExt};
+    /// use cot::{Body, StatusCode};
+    ///
+    /// async fn greeting(request: Request) -> cot::Result<Response> {
+    ///     match request.prefers(&["text/html", "application/json"]) {
+    ///         Some("application/json") => {
+    ///             Response::new_json(StatusCode::OK, &serde_json::json!({"greeting": "Hello!"}))
+    ///         }
+    ///         _ => Ok(Response::new_html(
+    ///             StatusCode::OK,
+    ///             Body::fixed("<h1>Hello!</h1>"),
+    ///         )),
+    ///     }
+    /// }
+    /// ```
+    fn prefers<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        self.accepts().prefers(available)
+    }
+
+    /// Get a value from the shared application state.
+    ///
+    /// The value is shared by all the requests. See the
+    /// [`state`](crate::state) module documentation for more information.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if there is no value of type `T` in the application
+    /// state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let counter = request.state::<AtomicU64>()?;
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn state<T: Send + Sync + 'static>(&self) -> Result<&T> {
+        self.context().state().try_get::<T>()
+    }
+
+    /// Get a value of type `T` from the request extensions.
+    ///
+    /// Request extensions hold the data specific to a single request. They
+    /// are the intended way for middleware to pass data, such as the current
+    /// user or a request identifier, to the request handlers: the middleware
+    /// inserts the value with [`Self::insert_extension`], and the handler
+    /// retrieves it with this method or with the
+    /// [`Extension`](extractors::Extension) extractor. Unlike the
+    /// [application state](Self::state), the values are not shared between
+    /// requests.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if there is no value of type `T` in the request
+    /// extensions, which usually means that the middleware inserting it was
+    /// not added to the middleware stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Tenant(String);
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let tenant = request.extension::<Tenant>()?;
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn extension<T: Send + Sync + 'static>(&self) -> Result<&T> {
+        self.extensions().get::<T>().ok_or_else(|| {
+            ErrorRepr::ExtensionMissing {
+                type_name: std::any::type_name::<T>(),
+            }
+            .into()
+        })
+    }
+
+    /// Insert a value into the request extensions, returning the previous
+    /// value of the same type, if any.
+    ///
+    /// The value can be then retrieved with [`Self::extension`] or the
+    /// [`Extension`](extractors::Extension) extractor by the request handler
+    /// and the inner middleware. Since the values are identified by their
+    /// type, it is a good idea to wrap them in a type specific to your
+    /// application, rather than inserting a plain `String` or `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::{Next, from_fn};
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Tenant(String);
+    ///
+    /// async fn tenant_middleware(mut request: Request, next: Next) -> cot::Result<Response> {
+    ///     let tenant = request
+    ///         .headers()
+    ///         .get("X-Tenant")
+    ///         .and_then(|value| value.to_str().ok())
+    ///         .unwrap_or("default")
+    ///         .to_owned();
+    ///     request.insert_extension(Tenant(tenant));
+    ///
+    ///     next.run(request).await
+    /// }
+    ///
+    /// let middleware = from_fn(tenant_middleware);
+    /// ```
+    fn insert_extension<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T>;
+
+    /// Get the number of bytes read from the request body so far, or
+    /// [`None`] if
+    /// [`BodyMetricsMiddleware`](crate::middleware::BodyMetricsMiddleware)
+    /// was not added to the middleware stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let (parts, body) = request.into_parts();
+    ///     let data = body.into_bytes().await?;
+    ///     let bytes_read = parts.bytes_read();
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    fn bytes_read(&self) -> Option<u64> {
+        self.extensions()
+            .get::<crate::middleware::BytesRead>()
+            .map(crate::middleware::BytesRead::get)
+    }
+
+    /// Get the address of the client the request was received from, or
+    /// [`None`] if the request wasn't received by the Cot server (e.g. in
+    /// tests).
+    ///
+    /// When the application is running behind a reverse proxy, this is the
+    /// address of the proxy, unless
+    /// [`ProxyHeadersMiddleware`](crate::middleware::ProxyHeadersMiddleware)
+    /// is used to read the address of the client from the headers set by the
+    /// proxy. The port of such an address is 0 if the proxy doesn't send it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     if let Some(peer_addr) = request.peer_addr() {
+    ///         println!("Request from {}", peer_addr.ip());
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.extensions()
+            .get::<crate::server::RemoteAddr>()
+            .map(|&crate::server::RemoteAddr(addr)| addr)
+    }
+
+    /// Evaluate a feature flag for this request.
+    ///
+    /// The flag is evaluated by the
+    /// [`FeatureFlags`](crate::feature_flags::FeatureFlags) provider
+    /// registered in the application state, or, if there is none, read from
+    /// the `[features]` section of the project config. The ID of the
+    /// authenticated user and the locale of the request (as resolved by
+    /// [`LocaleMiddleware`](crate::middleware::LocaleMiddleware), or the
+    /// preferred locale of the client if it's not used) are passed to the
+    /// provider for targeting. Unknown flags are disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     if request.flag("new_ui").is_enabled() {
+    ///         // ...
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    fn flag(&self, name: &str) -> FlagValue {
+        let user_id = self
+            .extensions()
+            .get::<crate::auth::Auth>()
+            .and_then(|auth| auth.user().id());
+        let locale = match self.locale() {
+            Ok(locale) => Some(locale.as_str()),
+            Err(_) => requested_languages(self.headers())
+                .into_iter()
+                .find(|&tag| tag != "*"),
+        };
+        let context = FlagContext::new(user_id, locale);
+
+        match self.context().state().get::<FeatureFlags>() {
+            Some(flags) => flags.evaluate(name, &context),
+            None => self
+                .project_config()
+                .features
+                .get(name)
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
     #[doc(hidden)]
     fn extensions(&self) -> &Extensions;
+
+    #[doc(hidden)]
+    fn headers(&self) -> &HeaderMap;
 }
 
 impl private::Sealed for Request {}
@@ -317,18 +747,168 @@ impl RequestExt for Request {
         self.extensions_mut().get_or_insert_default::<PathParams>()
     }
 
+    fn insert_extension<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.extensions_mut().insert(value)
+    }
+
     #[cfg(feature = "db")]
     fn db(&self) -> &Arc<Database> {
-        self.context().database()
+        match self
+            .extensions()
+            .get::<crate::middleware::RequestTransaction>()
+        {
+            Some(crate::middleware::RequestTransaction(transaction)) => transaction,
+            None => self.context().database(),
+        }
     }
 
     fn content_type(&self) -> Option<&http::HeaderValue> {
         self.headers().get(http::header::CONTENT_TYPE)
     }
 
+    fn absolute_url(&self, path: &str) -> Result<String> {
+        absolute_url(self.project_config(), self.headers(), self.uri(), path)
+    }
+
     fn extensions(&self) -> &Extensions {
         self.extensions()
     }
+
+    fn headers(&self) -> &HeaderMap {
+        self.headers()
+    }
+}
+
+/// Extension trait for [`Request`] that provides helper methods for working
+/// with the request body.
+///
+/// # Sealed
+///
+/// This trait is sealed since it doesn't make sense to be implemented for types
+/// outside the context of Cot.
+pub trait RequestBodyExt: private::Sealed {
+    /// Reads the entire request body into memory and returns it.
+    ///
+    /// The body of the request is replaced with the buffered data, so it can
+    /// still be read by the request handler. Calling this method again
+    /// returns the same data without reading the body again, which makes it
+    /// possible for several middlewares and the handler to all inspect the
+    /// body.
+    ///
+    /// Note that the whole body is kept in memory for as long as the request
+    /// exists. To protect against memory exhaustion, the size of the body is
+    /// limited by the limit of the route set with
+    /// [`Route::body_limit`](crate::router::Route::body_limit) if there is
+    /// one; otherwise, by the limit of
+    /// [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware)
+    /// (the `[middlewares.body_limit]` config section) if the middleware is
+    /// used; otherwise, by the
+    /// [`max_buffered_body_size`](crate::config::ServerConfig::max_buffered_body_size)
+    /// server configuration option (2 mebibytes by default).
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if reading the body fails or if the body is larger than
+    /// the limit. In that case, the body of the request is left empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestBodyExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(mut request: Request) -> cot::Result<Response> {
+    ///     let body = request.buffered().await?;
+    ///     println!("Received {} bytes", body.len());
+    ///
+    ///     // the body can still be read
+    ///     let body = request.into_body().into_bytes().await?;
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn buffered(&mut self) -> impl Future<Output = Result<Bytes>> + Send;
+
+    /// Takes the request body and returns a stream of its data chunks.
+    ///
+    /// Unlike [`Self::buffered`], this doesn't keep the body in memory: the
+    /// chunks are read from the client only as the stream is polled, so
+    /// large uploads can be written straight to a file or to a remote storage
+    /// service. The size of the body is still limited by the
+    /// [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware) and
+    /// [`Route::body_limit`](crate::router::Route::body_limit), if set; the
+    /// stream yields an error once the limit is exceeded.
+    ///
+    /// The body of the request is left empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestBodyExt};
+    /// use cot::response::Response;
+    /// use futures::StreamExt;
+    /// use tokio::io::AsyncWriteExt;
+    ///
+    /// async fn upload(mut request: Request) -> cot::Result<Response> {
+    ///     let mut stream = request.body_stream();
+    ///     let mut file = tokio::fs::File::create("upload.bin")
+    ///         .await
+    ///         .map_err(cot::Error::custom)?;
+    ///     while let Some(chunk) = stream.next().await {
+    ///         file.write_all(&chunk?).await.map_err(cot::Error::custom)?;
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn body_stream(&mut self) -> BodyStream;
+
+    /// Takes the request body and returns a parser of its
+    /// `multipart/form-data` content, used to accept file uploads.
+    ///
+    /// See [`Multipart`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the content type of the request is not
+    /// `multipart/form-data` with a boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestBodyExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut request: Request) -> cot::Result<Response> {
+    ///     let mut multipart = request.multipart()?;
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         let file = field.into_file().await?;
+    ///         println!("Received {:?} ({} bytes)", file.file_name(), file.size());
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    fn multipart(&mut self) -> Result<Multipart>;
+}
+
+impl RequestBodyExt for Request {
+    async fn buffered(&mut self) -> Result<Bytes> {
+        let limit = request_body_limit(self);
+        let body = std::mem::take(self.body_mut());
+        let data = body.into_bytes_limited(limit).await?;
+        *self.body_mut() = Body::fixed(data.clone());
+
+        Ok(data)
+    }
+
+    fn body_stream(&mut self) -> BodyStream {
+        std::mem::take(self.body_mut()).into_stream()
+    }
+
+    fn multipart(&mut self) -> Result<Multipart> {
+        Multipart::for_request(self)
+    }
 }
 
 impl private::Sealed for Parts {}
@@ -377,18 +957,36 @@ impl RequestExt for Parts {
         self.extensions.get_or_insert_default::<PathParams>()
     }
 
+    fn insert_extension<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.extensions.insert(value)
+    }
+
     #[cfg(feature = "db")]
     fn db(&self) -> &Arc<Database> {
-        self.context().database()
+        match self
+            .extensions
+            .get::<crate::middleware::RequestTransaction>()
+        {
+            Some(crate::middleware::RequestTransaction(transaction)) => transaction,
+            None => self.context().database(),
+        }
     }
 
     fn content_type(&self) -> Option<&http::HeaderValue> {
         self.headers.get(http::header::CONTENT_TYPE)
     }
 
+    fn absolute_url(&self, path: &str) -> Result<String> {
+        absolute_url(self.project_config(), &self.headers, &self.uri, path)
+    }
+
     fn extensions(&self) -> &Extensions {
         &self.extensions
     }
+
+    fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
 }
 
 #[repr(transparent)]
@@ -645,6 +1243,64 @@ pub struct PathParamsDeserializerError(
     #[source] serde_path_to_error::Error<path_params_deserializer::PathParamsDeserializerError>,
 );
 
+fn absolute_url(
+    config: &crate::config::ProjectConfig,
+    headers: &HeaderMap,
+    uri: &http::Uri,
+    path: &str,
+) -> Result<String> {
+    let path = path.strip_prefix('/').unwrap_or(path);
+
+    if let Some(base_url) = &config.server.base_url {
+        return Ok(format!("{}/{path}", base_url.trim_end_matches('/')));
+    }
+
+    // the forwarding headers are not read here; the scheme and the host are
+    // only replaced with the forwarded ones by `ProxyHeadersMiddleware`, for
+    // the requests coming from the trusted proxies
+    let scheme = uri.scheme_str().unwrap_or("http").to_ascii_lowercase();
+
+    let host = match headers.get(http::header::HOST) {
+        Some(host) => host
+            .to_str()
+            .ok()
+            .and_then(|host| host.parse::<http::uri::Authority>().ok()),
+        None => uri.authority().cloned(),
+    };
+    // userinfo is never valid in the `Host` header
+    let host = host
+        .filter(|host| !host.as_str().contains('@'))
+        .ok_or(ErrorRepr::InvalidHost)?;
+    if !is_host_allowed(&config.server.allowed_hosts, host.host()) {
+        return Err(ErrorRepr::HostNotAllowed {
+            host: host.host().to_owned(),
+        }
+        .into());
+    }
+
+    Ok(format!("{scheme}://{host}/{path}"))
+}
+
+/// Checks whether the host matches any of the
+/// [`allowed_hosts`](crate::config::ServerConfig::allowed_hosts).
+fn is_host_allowed(allowed_hosts: &[String], host: &str) -> bool {
+    let host = host.trim_end_matches('.');
+    allowed_hosts.iter().any(|allowed| {
+        if allowed == "*" {
+            return true;
+        }
+        match allowed.strip_prefix('.') {
+            Some(domain) => {
+                host.eq_ignore_ascii_case(domain)
+                    || host.len() > domain.len()
+                        && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+                        && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+            }
+            None => host.eq_ignore_ascii_case(allowed),
+        }
+    })
+}
+
 pub(crate) fn query_pairs(bytes: &Bytes) -> impl Iterator<Item = (Cow<'_, str>, Cow<'_, str>)> {
     form_urlencoded::parse(bytes.as_ref())
 }
@@ -701,6 +1357,352 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flag_from_config() {
+        let config = crate::config::ProjectConfig::builder()
+            .features(std::collections::HashMap::from([
+                ("new_ui".to_owned(), FlagValue::Bool(true)),
+                (
+                    "checkout_flow".to_owned(),
+                    FlagValue::Variant("b".to_owned()),
+                ),
+            ]))
+            .build();
+        let request = TestRequestBuilder::get("/").config(config).build();
+
+        assert!(request.flag("new_ui").is_enabled());
+        assert_eq!(request.flag("checkout_flow").variant(), Some("b"));
+        assert_eq!(request.flag("unknown"), FlagValue::Bool(false));
+    }
+
+    #[test]
+    fn flag_from_registered_provider() {
+        #[derive(Debug)]
+        struct LocaleFlags;
+
+        impl crate::feature_flags::FeatureFlagBackend for LocaleFlags {
+            fn evaluate(&self, _name: &str, context: &FlagContext<'_>) -> Option<FlagValue> {
+                Some(FlagValue::Bool(context.locale() == Some("pl-PL")))
+            }
+        }
+
+        let config = crate::config::ProjectConfig::builder()
+            .features(std::collections::HashMap::from([(
+                "new_ui".to_owned(),
+                FlagValue::Bool(false),
+            )]))
+            .build();
+        let mut request = TestRequestBuilder::get("/")
+            .config(config)
+            .state(FeatureFlags::new(LocaleFlags))
+            .build();
+        request.headers_mut().insert(
+            http::header::ACCEPT_LANGUAGE,
+            "pl-PL, en;q=0.8".parse().unwrap(),
+        );
+
+        assert!(request.flag("new_ui").is_enabled());
+
+        let (parts, _body) = request.into_parts();
+        assert!(parts.flag("new_ui").is_enabled());
+    }
+
+    #[test]
+    fn flag_locale_from_header() {
+        #[derive(Debug)]
+        struct LocaleFlags;
+
+        impl crate::feature_flags::FeatureFlagBackend for LocaleFlags {
+            fn evaluate(&self, _name: &str, context: &FlagContext<'_>) -> Option<FlagValue> {
+                Some(FlagValue::Bool(context.locale() == Some("fr-CH")))
+            }
+        }
+
+        let request_with = |accept_language: &str| {
+            let mut request = TestRequestBuilder::get("/")
+                .state(FeatureFlags::new(LocaleFlags))
+                .build();
+            request.headers_mut().insert(
+                http::header::ACCEPT_LANGUAGE,
+                accept_language.parse().unwrap(),
+            );
+            request
+        };
+
+        assert!(
+            request_with("fr;q=0.8, fr-CH;q=0.9")
+                .flag("new_ui")
+                .is_enabled()
+        );
+        assert!(request_with("*, fr-CH;q=0.5").flag("new_ui").is_enabled());
+        assert!(!request_with("fr-CH;q=0, fr").flag("new_ui").is_enabled());
+    }
+
+    fn absolute_url_request(
+        uri: &str,
+        headers: &[(&str, &str)],
+        base_url: Option<&str>,
+        allowed_hosts: &[&str],
+    ) -> Request {
+        let mut server_config = crate::config::ServerConfig::builder();
+        if let Some(base_url) = base_url {
+            server_config.base_url(base_url);
+        }
+        server_config.allowed_hosts(allowed_hosts.iter().copied());
+        let config = crate::config::ProjectConfig::builder()
+            .server(server_config.build())
+            .build();
+        let mut request = TestRequestBuilder::get(uri).config(config).build();
+        for (name, value) in headers {
+            request.headers_mut().insert(
+                http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+        }
+        request
+    }
+
+    #[test]
+    fn absolute_url_from_host() {
+        let request =
+            absolute_url_request("/", &[("host", "example.com:8000")], None, &["example.com"]);
+
+        assert_eq!(
+            request.absolute_url("/users?page=2").unwrap(),
+            "http://example.com:8000/users?page=2"
+        );
+        assert_eq!(
+            request.absolute_url("users").unwrap(),
+            "http://example.com:8000/users"
+        );
+    }
+
+    #[test]
+    fn absolute_url_scheme_from_uri() {
+        // the URI is set by `ProxyHeadersMiddleware` for the trusted proxies
+        let request = absolute_url_request(
+            "https://example.com/",
+            &[("host", "example.com")],
+            None,
+            &["example.com"],
+        );
+
+        assert_eq!(
+            request.absolute_url("/login").unwrap(),
+            "https://example.com/login"
+        );
+    }
+
+    #[test]
+    fn absolute_url_ignores_forwarded_proto() {
+        let request = absolute_url_request(
+            "/",
+            &[("host", "example.com"), ("x-forwarded-proto", "https")],
+            None,
+            &["example.com"],
+        );
+
+        assert_eq!(
+            request.absolute_url("/login").unwrap(),
+            "http://example.com/login"
+        );
+    }
+
+    #[test]
+    fn absolute_url_configured_base_url() {
+        let request = absolute_url_request(
+            "/",
+            &[("host", "internal:8000"), ("x-forwarded-proto", "http")],
+            Some("https://example.com/"),
+            &[],
+        );
+
+        assert_eq!(
+            request.absolute_url("/callback?code=1").unwrap(),
+            "https://example.com/callback?code=1"
+        );
+    }
+
+    #[test]
+    fn absolute_url_host_not_allowed() {
+        let request =
+            absolute_url_request("/", &[("host", "attacker.com")], None, &["example.com"]);
+        let error = request.absolute_url("/").unwrap_err();
+        assert!(error.to_string().contains("attacker.com"), "{error}");
+
+        // no allowed hosts and no base URL
+        let request = absolute_url_request("/", &[("host", "example.com")], None, &[]);
+        assert!(request.absolute_url("/").is_err());
+    }
+
+    #[test]
+    fn absolute_url_invalid_host() {
+        let request = absolute_url_request("/", &[("host", "user@example.com")], None, &["*"]);
+        assert!(request.absolute_url("/").is_err());
+
+        let request = absolute_url_request("/", &[("host", "exa mple.com")], None, &["*"]);
+        assert!(request.absolute_url("/").is_err());
+    }
+
+    #[test]
+    fn absolute_url_parts() {
+        let request = absolute_url_request("/", &[("host", "example.com")], None, &["example.com"]);
+        let (parts, _body) = request.into_parts();
+
+        assert_eq!(parts.absolute_url("/").unwrap(), "http://example.com/");
+    }
+
+    #[test]
+    fn allowed_hosts() {
+        let allowed_hosts = ["example.com".to_owned(), ".example.org".to_owned()];
+
+        assert!(is_host_allowed(&allowed_hosts, "example.com"));
+        assert!(is_host_allowed(&allowed_hosts, "EXAMPLE.com."));
+        assert!(!is_host_allowed(&allowed_hosts, "www.example.com"));
+        assert!(is_host_allowed(&allowed_hosts, "example.org"));
+        assert!(is_host_allowed(&allowed_hosts, "www.example.org"));
+        assert!(!is_host_allowed(&allowed_hosts, "badexample.org"));
+        assert!(!is_host_allowed(&allowed_hosts, "example.net"));
+        assert!(is_host_allowed(&["*".to_owned()], "example.net"));
+        assert!(!is_host_allowed(&[], "example.com"));
+    }
+
+    #[cot::test]
+    async fn request_buffered_read_twice() {
+        let mut request = TestRequestBuilder::post("/").build();
+        *request.body_mut() = Body::streaming(futures::stream::iter(
+            ["Hello, ", "world!"].map(|chunk| Ok(Bytes::from(chunk))),
+        ));
+
+        assert_eq!(request.buffered().await.unwrap(), "Hello, world!");
+        assert_eq!(request.buffered().await.unwrap(), "Hello, world!");
+        assert_eq!(
+            request.into_body().into_bytes().await.unwrap(),
+            "Hello, world!"
+        );
+    }
+
+    #[cot::test]
+    async fn request_buffered_limit() {
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(4)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::post("/").config(config).build();
+        *request.body_mut() =
+            Body::streaming(futures::stream::once(async { Ok(Bytes::from("too long")) }));
+
+        assert!(request.buffered().await.is_err());
+    }
+
+    #[cot::test]
+    async fn request_buffered_limit_fixed_body() {
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(4)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::post("/").config(config).build();
+        *request.body_mut() = Body::fixed("too long");
+
+        let error = request.buffered().await.unwrap_err();
+
+        assert!(matches!(error.inner, ErrorRepr::BodyTooLarge { limit: 4 }));
+        assert!(request.into_body().into_bytes().await.unwrap().is_empty());
+    }
+
+    #[cot::test]
+    async fn request_buffered_route_limit() {
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(4)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::post("/").config(config).build();
+        *request.body_mut() = Body::streaming(futures::stream::once(async {
+            Ok(Bytes::from("not too long"))
+        }));
+        crate::middleware::override_body_limit(&mut request, 16);
+
+        assert_eq!(request.buffered().await.unwrap(), "not too long");
+    }
+
+    #[cot::test]
+    async fn request_buffered_middleware_limit() {
+        use tower::{Layer, ServiceExt};
+
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(4)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::post("/").config(config).build();
+        *request.body_mut() = Body::streaming(futures::stream::once(async {
+            Ok(Bytes::from("not too long"))
+        }));
+        let service = crate::middleware::BodyLimitMiddleware::new(16).layer(tower::service_fn(
+            |mut request: Request| async move {
+                let body = request.buffered().await?;
+                Ok::<_, crate::Error>(Response::new(Body::fixed(body)))
+            },
+        ));
+
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "not too long"
+        );
+    }
+
+    #[cot::test]
+    async fn request_body_stream() {
+        use futures::StreamExt;
+
+        let mut request = TestRequestBuilder::post("/").build();
+        *request.body_mut() = Body::streaming(futures::stream::iter(
+            ["Hello, ", "world!"].map(|chunk| Ok(Bytes::from(chunk))),
+        ));
+
+        let chunks: Vec<_> = request
+            .body_stream()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(chunks, ["Hello, ", "world!"]);
+        assert_eq!(request.into_body().into_bytes().await.unwrap(), "");
+    }
+
+    #[cot::test]
+    async fn request_body_stream_route_limit() {
+        use futures::StreamExt;
+
+        let mut request = TestRequestBuilder::post("/").build();
+        *request.body_mut() = Body::streaming(futures::stream::iter(
+            ["Hello, ", "world!"].map(|chunk| Ok(Bytes::from(chunk))),
+        ));
+        crate::middleware::override_body_limit(&mut request, 10);
+
+        let mut stream = request.body_stream();
+        assert_eq!(stream.next().await.unwrap().unwrap(), "Hello, ");
+        let error = stream.next().await.unwrap().unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("The request body exceeds the limit of 10 bytes")
+        );
+    }
+
     #[test]
     fn request_ext_app_name() {
         let mut request = TestRequestBuilder::get("/").build();
@@ -0,0 +1,310 @@
+use http::request::Parts;
+use http::{HeaderMap, HeaderValue, header};
+
+use crate::request::extractors::FromRequestParts;
+
+/// The media types accepted by the client, parsed from the `Accept` header.
+///
+/// This allows a single handler to respond with different representations
+/// of a resource (such as HTML or JSON) depending on what the client prefers.
+/// It can be used as an extractor, or retrieved with
+/// [`RequestExt::accepts`](crate::request::RequestExt::accepts).
+///
+/// The media ranges are matched as described in
+/// [RFC 9110](https://datatracker.ietf.org/doc/html/rfc9110#section-12.5.1):
+/// the most specific range matching a media type (e.g. `text/html` over
+/// `text/*` over `*/*`) determines its quality value. A request without an
+/// `Accept` header accepts any media type.
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::Accepts;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::{Body, StatusCode};
+///
+/// async fn greeting(accepts: Accepts) -> cot::Result<Response> {
+///     match accepts.prefers(&["text/html", "application/json"]) {
+///         Some("application/json") => {
+///             Response::new_json(StatusCode::OK, &serde_json::json!({"greeting": "Hello!"}))
+///         }
+///         _ => Ok(Response::new_html(
+///             StatusCode::OK,
+///             Body::fixed("<h1>Hello!</h1>"),
+///         )),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Accepts {
+    ranges: Vec<MediaRange>,
+}
+
+#[derive(Debug, Clone)]
+struct MediaRange {
+    type_: String,
+    subtype: String,
+    quality: f32,
+}
+
+impl MediaRange {
+    fn parse(range: &str) -> Option<Self> {
+        let mut parts = range.split(';');
+        let (type_, subtype) = parts.next()?.trim().split_once('/')?;
+        let (type_, subtype) = (type_.trim(), subtype.trim());
+        if type_.is_empty() || subtype.is_empty() || (type_ == "*" && subtype != "*") {
+            return None;
+        }
+
+        let quality = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|quality| quality.trim().parse::<f32>().ok())
+            .map_or(1.0, |quality| quality.clamp(0.0, 1.0));
+        Some(Self {
+            type_: type_.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            quality,
+        })
+    }
+
+    /// Returns how specific the range is for the given media type, or `None`
+    /// if it doesn't match it.
+    fn specificity(&self, type_: &str, subtype: &str) -> Option<u8> {
+        if self.type_ == "*" {
+            Some(0)
+        } else if !self.type_.eq_ignore_ascii_case(type_) {
+            None
+        } else if self.subtype == "*" {
+            Some(1)
+        } else if self.subtype.eq_ignore_ascii_case(subtype) {
+            Some(2)
+        } else {
+            None
+        }
+    }
+}
+
+impl Accepts {
+    /// Parses the `Accept` headers of a request.
+    ///
+    /// Invalid media ranges are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::{HeaderMap, HeaderValue, header};
+    /// use cot::request::Accepts;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(
+    ///     header::ACCEPT,
+    ///     HeaderValue::from_static("text/html, application/json;q=0.9"),
+    /// );
+    ///
+    /// let accepts = Accepts::from_headers(&headers);
+    /// assert_eq!(accepts.quality("application/json"), 0.9);
+    /// ```
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self::from_values(headers.get_all(header::ACCEPT))
+    }
+
+    pub(crate) fn from_values<'a, I: IntoIterator<Item = &'a HeaderValue>>(values: I) -> Self {
+        let ranges = values
+            .into_iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(MediaRange::parse)
+            .collect();
+
+        Self { ranges }
+    }
+
+    /// Returns the quality value of the given media type, between `0.0` (not
+    /// acceptable) and `1.0` (the most preferred).
+    ///
+    /// The parameters of the media type, if any, are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::{HeaderMap, HeaderValue, header};
+    /// use cot::request::Accepts;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(
+    ///     header::ACCEPT,
+    ///     HeaderValue::from_static("text/*;q=0.5, text/html, image/png;q=0"),
+    /// );
+    ///
+    /// let accepts = Accepts::from_headers(&headers);
+    /// assert_eq!(accepts.quality("text/html"), 1.0);
+    /// assert_eq!(accepts.quality("text/plain"), 0.5);
+    /// assert_eq!(accepts.quality("image/png"), 0.0);
+    /// assert_eq!(accepts.quality("application/json"), 0.0);
+    /// ```
+    #[must_use]
+    pub fn quality(&self, media_type: &str) -> f32 {
+        if self.ranges.is_empty() {
+            return 1.0;
+        }
+        let essence = media_type.split(';').next().unwrap_or_default().trim();
+        let Some((type_, subtype)) = essence.split_once('/') else {
+            return 0.0;
+        };
+
+        self.ranges
+            .iter()
+            .filter_map(|range| Some((range.specificity(type_, subtype)?, range.quality)))
+            // the first of the most specific ranges wins
+            .fold(None, |best: Option<(u8, f32)>, current| match best {
+                Some(best) if best.0 >= current.0 => Some(best),
+                _ => Some(current),
+            })
+            .map_or(0.0, |(_, quality)| quality)
+    }
+
+    /// Returns whether the client accepts the given media type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::{HeaderMap, HeaderValue, header};
+    /// use cot::request::Accepts;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(header::ACCEPT, HeaderValue::from_static("text/*"));
+    ///
+    /// let accepts = Accepts::from_headers(&headers);
+    /// assert!(accepts.accepts("text/csv"));
+    /// assert!(!accepts.accepts("application/json"));
+    /// ```
+    #[must_use]
+    pub fn accepts(&self, media_type: &str) -> bool {
+        self.quality(media_type) > 0.0
+    }
+
+    /// Returns the media type the client prefers among the `available` ones,
+    /// or `None` if it accepts none of them.
+    ///
+    /// If the client likes multiple media types equally, the one that comes
+    /// first in `available` is returned, so the types should be listed in the
+    /// order the server prefers them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::{HeaderMap, HeaderValue, header};
+    /// use cot::request::Accepts;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(
+    ///     header::ACCEPT,
+    ///     HeaderValue::from_static("application/json, text/html;q=0.8"),
+    /// );
+    ///
+    /// let accepts = Accepts::from_headers(&headers);
+    /// assert_eq!(
+    ///     accepts.prefers(&["text/html", "application/json"]),
+    ///     Some("application/json")
+    /// );
+    /// assert_eq!(accepts.prefers(&["image/png"]), None);
+    ///
+    /// // no `Accept` header: the first available type is used
+    /// let accepts = Accepts::from_headers(&HeaderMap::new());
+    /// assert_eq!(
+    ///     accepts.prefers(&["text/html", "application/json"]),
+    ///     Some("text/html")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn prefers<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        available
+            .iter()
+            .map(|media_type| (*media_type, self.quality(media_type)))
+            .filter(|(_, quality)| *quality > 0.0)
+            .fold(None, |best: Option<(&str, f32)>, current| match best {
+                Some(best) if best.1 >= current.1 => Some(best),
+                _ => Some(current),
+            })
+            .map(|(media_type, _)| media_type)
+    }
+}
+
+impl FromRequestParts for Accepts {
+    async fn from_request_parts(parts: &mut Parts) -> crate::Result<Self> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepts(accept: &'static str) -> Accepts {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(accept));
+        Accepts::from_headers(&headers)
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp)] // the compared values are parsed, not computed
+    fn quality_most_specific_range_wins() {
+        let accepts = accepts("*/*;q=0.1, text/*;q=0.5, text/html;q=0.9");
+
+        assert_eq!(accepts.quality("text/html"), 0.9);
+        assert_eq!(accepts.quality("TEXT/HTML; charset=utf-8"), 0.9);
+        assert_eq!(accepts.quality("text/plain"), 0.5);
+        assert_eq!(accepts.quality("application/json"), 0.1);
+        assert_eq!(accepts.quality("invalid"), 0.0);
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp)] // the compared values are parsed, not computed
+    fn quality_invalid_ranges_skipped() {
+        let accepts = accepts("text, */html, , application/json;q=2");
+
+        assert_eq!(accepts.quality("application/json"), 1.0);
+        assert_eq!(accepts.quality("text/html"), 0.0);
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp)] // the compared values are parsed, not computed
+    fn quality_no_accept_header() {
+        let accepts = Accepts::from_headers(&HeaderMap::new());
+
+        assert_eq!(accepts.quality("application/json"), 1.0);
+        assert!(accepts.accepts("image/png"));
+    }
+
+    #[test]
+    fn prefers() {
+        let available = ["text/html", "application/json"];
+
+        assert_eq!(
+            accepts("text/html;q=0.5, application/json").prefers(&available),
+            Some("application/json")
+        );
+        assert_eq!(accepts("*/*").prefers(&available), Some("text/html"));
+        assert_eq!(
+            accepts("application/json;q=0, */*").prefers(&available),
+            Some("text/html")
+        );
+        assert_eq!(accepts("image/*").prefers(&available), None);
+        assert_eq!(accepts("*/*").prefers(&[]), None);
+    }
+
+    #[cot::test]
+    async fn accepts_extractor() {
+        let mut request = crate::test::TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        let (mut parts, _body) = request.into_parts();
+
+        let accepts = Accepts::from_request_parts(&mut parts).await.unwrap();
+
+        assert!(accepts.accepts("application/json"));
+        assert!(!accepts.accepts("text/html"));
+    }
+}
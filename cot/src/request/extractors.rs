@@ -51,6 +51,64 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Custom extractors
+//!
+//! You can write your own extractors by implementing [`FromRequestParts`] (or
+//! [`FromRequest`], if the request body is needed). The request parts give
+//! access to the headers, the request extensions, as well as the project
+//! context and its state through [`RequestExt`]. An error returned from the
+//! extractor is returned from the request handler.
+//!
+//! ```
+//! use cot::request::RequestExt;
+//! use cot::request::extractors::FromRequestParts;
+//! use cot::response::{Response, ResponseExt};
+//! use cot::test::TestRequestBuilder;
+//! use cot::{Body, RequestHandler, StatusCode};
+//! use http::request::Parts;
+//!
+//! struct ApiKeys(Vec<String>);
+//!
+//! struct ApiKey(String);
+//!
+//! impl FromRequestParts for ApiKey {
+//!     async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+//!         let key = parts
+//!             .headers
+//!             .get("X-Api-Key")
+//!             .and_then(|value| value.to_str().ok())
+//!             .ok_or_else(|| cot::Error::custom("missing API key"))?;
+//!
+//!         if parts.state::<ApiKeys>()?.0.iter().any(|known| known == key) {
+//!             Ok(Self(key.to_owned()))
+//!         } else {
+//!             Err(cot::Error::custom("invalid API key"))
+//!         }
+//!     }
+//! }
+//!
+//! async fn my_handler(ApiKey(key): ApiKey) -> cot::Result<Response> {
+//!     Ok(Response::new_html(
+//!         StatusCode::OK,
+//!         Body::fixed(format!("Hello {key}!")),
+//!     ))
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> cot::Result<()> {
+//! let mut request = TestRequestBuilder::get("/")
+//!     .state(ApiKeys(vec!["abc".to_owned()]))
+//!     .build();
+//! request
+//!     .headers_mut()
+//!     .insert("X-Api-Key", http::HeaderValue::from_static("abc"));
+//!
+//! let response = my_handler.handle(request).await?;
+//! assert_eq!(response.into_body().into_bytes().await?, "Hello abc!");
+//! # Ok(())
+//! # }
+//! ```
 
 use std::future::Future;
 
@@ -61,10 +119,15 @@ use http::request::Parts;
 use serde::de::DeserializeOwned;
 
 use crate::auth::Auth;
-use crate::form::{Form, FormResult};
-use crate::request::RequestExt;
+use crate::form::{Form, FormError, FormResult};
+use crate::locale::Locale;
+use crate::middleware::{CsrfToken, RequestId};
+use crate::request::{RequestBodyExt, RequestExt};
+#[cfg(feature = "json")]
+use crate::response::ResponseExt;
 use crate::router::Urls;
 use crate::session::Session;
+use crate::timezone::Timezone;
 
 /// Trait for extractors that consume the request body.
 ///
@@ -118,6 +181,8 @@ impl FromRequestParts for Urls {
 ///
 /// The extractor is generic over a type that implements
 /// `serde::de::DeserializeOwned`.
+/// If the path parameters can't be deserialized, the request handler responds
+/// with `400 Bad Request` and the reason in the response body.
 ///
 /// # Examples
 ///
@@ -178,6 +243,8 @@ impl<D: DeserializeOwned> FromRequestParts for Path<D> {
 ///
 /// The extractor is generic over a type that implements
 /// `serde::de::DeserializeOwned`.
+/// If the query parameters can't be deserialized, the request handler responds
+/// with `400 Bad Request` and the reason in the response body.
 ///
 /// # Example
 ///
@@ -245,11 +312,15 @@ where
 ///
 /// # Errors
 ///
-/// Throws an error if the content type is not `application/json`.
+/// Throws an error if the content type is not `application/json`. When
+/// returned from a request handler, it is converted to a `415 Unsupported
+/// Media Type` response.
 /// Throws an error if the request body could not be read.
-/// Throws an error if the request body could not be deserialized - either
-/// because the JSON is invalid or because the deserialization to the target
-/// structure failed.
+/// Throws an error if the request body is not valid JSON; this is converted to
+/// a `400 Bad Request` response.
+/// Throws a [`ValidationError`] if the JSON could not be deserialized to the
+/// target structure; this is converted to a `422 Unprocessable Entity`
+/// response.
 ///
 /// # Example
 ///
@@ -299,17 +370,162 @@ impl<D: DeserializeOwned> FromRequest for Json<D> {
     async fn from_request(mut request: Request) -> cot::Result<Self> {
         request.expect_content_type(cot::headers::JSON_CONTENT_TYPE)?;
 
-        let body = std::mem::take(request.body_mut());
-        let bytes = body.into_bytes().await?;
+        let bytes = request.buffered().await?;
 
         let deserializer = &mut serde_json::Deserializer::from_slice(&bytes);
-        let result = serde_path_to_error::deserialize(deserializer)
-            .map_err(|error| Error::new(ErrorRepr::Json(error)))?;
+        let result = serde_path_to_error::deserialize(deserializer).map_err(|error| {
+            if error.inner().is_data() {
+                Error::new(ErrorRepr::Validation(ValidationError::from_json_error(
+                    &error,
+                )))
+            } else {
+                // a body that isn't JSON at all is not a validation error
+                Error::new(ErrorRepr::JsonParse(error.into_inner()))
+            }
+        })?;
 
         Ok(Self(result))
     }
 }
 
+/// Field-level errors that occurred while deserializing the request data.
+///
+/// This is returned by the [`Json`] extractor when the request body is valid
+/// JSON, but can't be deserialized into the target type. When returned from a request handler,
+/// it is converted to a `422 Unprocessable Entity` response with a JSON body
+/// mapping field paths to error messages, e.g.:
+///
+/// ```json
+/// {"errors": {"email": "invalid type: integer `5`, expected a string"}}
+/// ```
+///
+/// Nested fields are separated by dots and sequence elements are indexed with
+/// brackets (e.g. `user.addresses[0].city`). Errors that concern the whole
+/// value (e.g. an array sent instead of an object) are reported under the `.`
+/// key.
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::extractors::ValidationError;
+///
+/// let mut error = ValidationError::new();
+/// error.add("email", "invalid email address");
+///
+/// assert_eq!(error.errors()["email"], "invalid email address");
+/// ```
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationError {
+    errors: indexmap::IndexMap<String, String>,
+}
+
+#[cfg(feature = "json")]
+impl ValidationError {
+    const ROOT_PATH: &'static str = ".";
+
+    /// Creates a new, empty [`ValidationError`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::extractors::ValidationError;
+    ///
+    /// let error = ValidationError::new();
+    /// assert!(error.errors().is_empty());
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an error for the field at given path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::extractors::ValidationError;
+    ///
+    /// let mut error = ValidationError::new();
+    /// error.add("user.name", "missing field");
+    /// ```
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.errors.insert(field.into(), message.into());
+    }
+
+    /// Returns the errors, mapping field paths to error messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::extractors::ValidationError;
+    ///
+    /// let mut error = ValidationError::new();
+    /// error.add("email", "invalid email address");
+    ///
+    /// assert_eq!(error.errors().len(), 1);
+    /// ```
+    #[must_use]
+    pub fn errors(&self) -> &indexmap::IndexMap<String, String> {
+        &self.errors
+    }
+
+    fn from_json_error(error: &serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let inner = error.inner();
+        let message = inner.to_string();
+        let position = format!(" at line {} column {}", inner.line(), inner.column());
+        let message = message.strip_suffix(&position).unwrap_or(&message);
+
+        let path = error.path().to_string();
+        // serde reports missing fields on the parent object, but it's more
+        // useful for the client to know which field is missing
+        let path = match message
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.strip_suffix('`'))
+        {
+            Some(field) if path == Self::ROOT_PATH => field.to_owned(),
+            Some(field) => format!("{path}.{field}"),
+            None => path,
+        };
+
+        let mut validation_error = Self::new();
+        validation_error.add(path, message);
+        validation_error
+    }
+
+    pub(crate) fn as_response(&self) -> crate::response::Response {
+        let errors: serde_json::Map<String, serde_json::Value> = self
+            .errors
+            .iter()
+            .map(|(field, message)| (field.clone(), message.clone().into()))
+            .collect();
+
+        crate::response::Response::new_json(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            &serde_json::json!({ "errors": errors }),
+        )
+        .expect("validation errors should always be serializable")
+    }
+}
+
+#[cfg(feature = "json")]
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for (field, message) in &self.errors {
+            if !first {
+                write!(f, "; ")?;
+            }
+            first = false;
+            write!(f, "{field}: {message}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "json")]
+impl std::error::Error for ValidationError {}
+
 /// An extractor that gets the request body as form data and deserializes it
 /// into a type `F` implementing `cot::form::Form`.
 ///
@@ -318,10 +534,13 @@ impl<D: DeserializeOwned> FromRequest for Json<D> {
 /// # Errors
 ///
 /// Throws an error if the content type is not
-/// `application/x-www-form-urlencoded`. Throws an error if the request body
-/// could not be read. Throws an error if the request body could not be
-/// deserialized - either because the form data is invalid or because the
-/// deserialization to the target structure failed.
+/// `application/x-www-form-urlencoded` or `multipart/form-data`. When returned
+/// from a request handler, it is converted to a `415 Unsupported Media Type`
+/// response, the same as for the other extractors.
+/// Throws an error if the request body could not be read.
+///
+/// The form data that fails the validation is not an error; it is returned
+/// as [`FormResult::ValidationError`] instead.
 ///
 /// # Example
 ///
@@ -365,7 +584,64 @@ pub struct RequestForm<F: Form>(pub FormResult<F>);
 
 impl<F: Form> FromRequest for RequestForm<F> {
     async fn from_request(mut request: Request) -> cot::Result<Self> {
-        Ok(Self(F::from_request(&mut request).await?))
+        match F::from_request(&mut request).await {
+            Ok(result) => Ok(Self(result)),
+            // the request errors (such as an invalid content type) are
+            // rejected the same way as in the other extractors
+            Err(FormError::RequestError { error }) => Err(*error),
+        }
+    }
+}
+
+/// An extractor that gets a value of type `T` from the request extensions.
+///
+/// This is the extractor counterpart of
+/// [`RequestExt::extension`](crate::request::RequestExt::extension): it
+/// retrieves (a clone of) the value a middleware inserted with
+/// [`RequestExt::insert_extension`](crate::request::RequestExt::insert_extension),
+/// which is the recommended way to pass per-request data from middleware to
+/// request handlers. If the value is expensive to clone, consider wrapping
+/// it in an [`Arc`](std::sync::Arc).
+///
+/// An error is returned if there is no value of type `T` in the request
+/// extensions.
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::RequestExt;
+/// use cot::request::extractors::Extension;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::test::TestRequestBuilder;
+/// use cot::{Body, RequestHandler, StatusCode};
+///
+/// #[derive(Debug, Clone)]
+/// struct Tenant(String);
+///
+/// async fn my_handler(Extension(tenant): Extension<Tenant>) -> cot::Result<Response> {
+///     Ok(Response::new_html(
+///         StatusCode::OK,
+///         Body::fixed(format!("Hello {}!", tenant.0)),
+///     ))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let mut request = TestRequestBuilder::get("/").build();
+/// // this would usually be done by a middleware
+/// request.insert_extension(Tenant("acme".to_owned()));
+///
+/// let response = my_handler.handle(request).await?;
+/// assert_eq!(response.into_body().into_bytes().await?, "Hello acme!");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Extension<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> FromRequestParts for Extension<T> {
+    async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+        parts.extension::<T>().cloned().map(Self)
     }
 }
 
@@ -409,7 +685,42 @@ impl FromRequestParts for RequestDb {
 // extractor impls for existing types
 impl FromRequestParts for Session {
     async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
-        Ok(Session::from_extensions(&parts.extensions).clone())
+        Session::try_from_extensions(&parts.extensions).cloned()
+    }
+}
+
+impl FromRequestParts for Locale {
+    async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+        Locale::try_from_extensions(&parts.extensions).cloned()
+    }
+}
+
+impl FromRequestParts for Timezone {
+    async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+        Timezone::try_from_extensions(&parts.extensions).copied()
+    }
+}
+
+impl FromRequestParts for RequestId {
+    async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+        RequestId::try_from_extensions(&parts.extensions).cloned()
+    }
+}
+
+#[cfg(feature = "db")]
+impl FromRequestParts for crate::db::QueryStats {
+    async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+        parts
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .ok_or_else(|| Error::new(ErrorRepr::QueryStatsMissing))
+    }
+}
+
+impl FromRequestParts for CsrfToken {
+    async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+        CsrfToken::try_from_extensions(&parts.extensions).cloned()
     }
 }
 
@@ -430,11 +741,12 @@ mod tests {
     use serde::Deserialize;
 
     use super::*;
+    use crate::request::Multipart;
     use crate::request::extractors::{FromRequest, Json, Path, UrlQuery};
     use crate::response::{Response, ResponseExt};
     use crate::router::{Route, Router, Urls};
     use crate::test::TestRequestBuilder;
-    use crate::{Body, reverse};
+    use crate::{Body, RequestHandler, reverse};
 
     #[cfg(feature = "json")]
     #[cot::test]
@@ -449,6 +761,31 @@ mod tests {
         assert_eq!(data, serde_json::json!({"hello": "world"}));
     }
 
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn json_too_large() {
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(4)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::post("/").config(config).build();
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static(cot::headers::JSON_CONTENT_TYPE),
+        );
+        *request.body_mut() = Body::streaming(futures::stream::once(async {
+            Ok(bytes::Bytes::from(r#"{"hello":"world"}"#))
+        }));
+
+        let error = Json::<serde_json::Value>::from_request(request)
+            .await
+            .unwrap_err();
+        assert!(matches!(error.inner, ErrorRepr::BodyTooLarge { limit: 4 }));
+    }
+
     #[cfg(feature = "json")]
     #[cot::test]
     async fn json_empty() {
@@ -495,6 +832,163 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn json_validation_error() {
+        #[derive(Debug, Deserialize)]
+        struct TestDataInner {
+            #[expect(dead_code)]
+            email: String,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct TestData {
+            #[expect(dead_code)]
+            user: TestDataInner,
+        }
+
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, cot::headers::JSON_CONTENT_TYPE)
+            .body(Body::fixed(r#"{"user":{"email":5}}"#))
+            .unwrap();
+
+        let error = Json::<TestData>::from_request(request).await.unwrap_err();
+        let ErrorRepr::Validation(error) = error.inner else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(
+            error.errors()["user.email"],
+            "invalid type: integer `5`, expected a string"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn json_validation_error_missing_field() {
+        #[derive(Debug, Deserialize)]
+        struct TestData {
+            #[expect(dead_code)]
+            email: String,
+        }
+
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, cot::headers::JSON_CONTENT_TYPE)
+            .body(Body::fixed("{}"))
+            .unwrap();
+
+        let error = Json::<TestData>::from_request(request).await.unwrap_err();
+        let ErrorRepr::Validation(error) = error.inner else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(error.errors()["email"], "missing field `email`");
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn json_validation_error_response() {
+        #[derive(Debug, Deserialize)]
+        struct TestData {
+            #[expect(dead_code)]
+            email: String,
+        }
+
+        async fn handler(Json(_data): Json<TestData>) -> cot::Result<Response> {
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/", handler)]);
+        let request = TestRequestBuilder::post("/")
+            .json(&serde_json::json!({"email": ["not", "a", "string"]}))
+            .build();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            cot::headers::JSON_CONTENT_TYPE
+        );
+        let body: serde_json::Value =
+            serde_json::from_slice(&response.into_body().into_bytes().await.unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"errors": {"email": "invalid type: sequence, expected a string"}})
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn json_invalid_content_type_response() {
+        async fn handler(Json(_data): Json<serde_json::Value>) -> cot::Result<Response> {
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/", handler)]);
+        let request = TestRequestBuilder::post("/")
+            .form_data(&[("hello", "world")])
+            .build();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "Invalid content type; expected `application/json`, found \
+             `application/x-www-form-urlencoded`"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn json_malformed_response() {
+        async fn handler(Json(_data): Json<serde_json::Value>) -> cot::Result<Response> {
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/", handler)]);
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/")
+            .header(http::header::CONTENT_TYPE, cot::headers::JSON_CONTENT_TYPE)
+            .body(Body::fixed("{"))
+            .unwrap();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "Could not parse JSON: EOF while parsing an object at line 1 column 1"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn json_validation_error_root() {
+        #[derive(Debug, Deserialize)]
+        struct TestData {
+            #[expect(dead_code)]
+            email: String,
+        }
+
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, cot::headers::JSON_CONTENT_TYPE)
+            .body(Body::fixed("[]"))
+            .unwrap();
+
+        let error = Json::<TestData>::from_request(request).await.unwrap_err();
+        let ErrorRepr::Validation(error) = error.inner else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(
+            error.errors()["."],
+            "invalid length 0, expected struct TestData with 1 element"
+        );
+    }
+
     #[cot::test]
     async fn path_extraction() {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -519,6 +1013,235 @@ mod tests {
         assert_eq!(extracted, expected);
     }
 
+    #[cot::test]
+    async fn custom_extractor_with_state() {
+        struct Tenant(String);
+
+        impl FromRequestParts for Tenant {
+            async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+                let prefix = parts.state::<&str>()?;
+                let host = parts.uri.host().unwrap_or_default();
+                Ok(Self(format!("{prefix}{host}")))
+            }
+        }
+
+        async fn handler(Tenant(tenant): Tenant) -> cot::Result<Response> {
+            Ok(Response::new_html(
+                http::StatusCode::OK,
+                Body::fixed(tenant),
+            ))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/", handler)]);
+        let request = TestRequestBuilder::get("https://acme.example.com/")
+            .state("tenant-")
+            .build();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "tenant-acme.example.com"
+        );
+    }
+
+    #[cot::test]
+    async fn custom_extractor_error() {
+        struct AlwaysFails;
+
+        impl FromRequestParts for AlwaysFails {
+            async fn from_request_parts(_parts: &mut Parts) -> cot::Result<Self> {
+                Err(Error::custom("extraction failed"))
+            }
+        }
+
+        async fn handler(_: AlwaysFails) -> cot::Result<Response> {
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let error = handler
+            .handle(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "extraction failed");
+    }
+
+    #[cot::test]
+    async fn multipart_extraction() {
+        async fn handler(mut multipart: Multipart) -> cot::Result<Response> {
+            let field = multipart.next_field().await?.expect("field missing");
+            let file = field.into_file().await?;
+            Ok(Response::new_html(
+                http::StatusCode::OK,
+                Body::fixed(format!("{:?}: {}", file.file_name(), file.size())),
+            ))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/", handler)]);
+        let request = TestRequestBuilder::post("/")
+            .multipart_file("file", "a.txt", "text/plain", "data")
+            .build();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "Some(\"a.txt\"): 4"
+        );
+    }
+
+    #[cot::test]
+    async fn request_form_invalid_content_type_response() {
+        #[derive(Form)]
+        struct MyForm {
+            hello: String,
+        }
+
+        async fn handler(RequestForm(_form): RequestForm<MyForm>) -> cot::Result<Response> {
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/", handler)]);
+        let request = TestRequestBuilder::post("/")
+            .json(&serde_json::json!({"hello": "world"}))
+            .build();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "Invalid content type; expected `application/x-www-form-urlencoded`, found \
+             `application/json`"
+        );
+    }
+
+    #[cot::test]
+    async fn multipart_error_responses() {
+        async fn handler(mut multipart: Multipart) -> cot::Result<Response> {
+            while multipart.next_field().await?.is_some() {}
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/", handler)]);
+
+        let request = TestRequestBuilder::post("/")
+            .form_data(&[("a", "b")])
+            .build();
+        let response = router.handle(request).await.unwrap();
+        assert_eq!(response.status(), http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let mut request = TestRequestBuilder::post("/").build();
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static("multipart/form-data; boundary=xyz"),
+        );
+        *request.body_mut() = Body::fixed("--xyz\r\nbroken");
+        let response = router.handle(request).await.unwrap();
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[cot::test]
+    async fn path_parse_error_response() {
+        async fn handler(Path(_id): Path<i32>) -> cot::Result<Response> {
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/{id}/", handler)]);
+        let request = TestRequestBuilder::get("/abc/").build();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert!(body.starts_with(b"Could not parse path parameters"));
+    }
+
+    #[cot::test]
+    async fn url_query_parse_error_response() {
+        #[derive(Deserialize)]
+        struct QueryParams {
+            #[expect(dead_code)]
+            page: i32,
+        }
+
+        async fn handler(UrlQuery(_query): UrlQuery<QueryParams>) -> cot::Result<Response> {
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/", handler)]);
+        let request = TestRequestBuilder::get("/?page=first").build();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            "text/plain; charset=utf-8"
+        );
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert!(body.starts_with(b"Could not parse query parameters: page: "));
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn client_error_response_json() {
+        async fn handler(Path(_id): Path<i32>) -> cot::Result<Response> {
+            Ok(Response::new_html(http::StatusCode::OK, Body::empty()))
+        }
+
+        let router = Router::with_urls([Route::with_handler("/{id}/", handler)]);
+        let mut request = TestRequestBuilder::get("/abc/").build();
+        request.headers_mut().insert(
+            http::header::ACCEPT,
+            http::HeaderValue::from_static("application/json, text/plain;q=0.5"),
+        );
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            cot::headers::JSON_CONTENT_TYPE
+        );
+        let body = response.into_body().into_bytes().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(
+            body["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Could not parse path parameters")
+        );
+    }
+
+    #[cot::test]
+    async fn extension_extraction() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Tenant(&'static str);
+
+        let mut request = TestRequestBuilder::get("/").build();
+        assert_eq!(request.insert_extension(Tenant("acme")), None);
+        assert_eq!(
+            request.insert_extension(Tenant("initech")),
+            Some(Tenant("acme"))
+        );
+        let (mut parts, _body) = request.into_parts();
+
+        let Extension(tenant) = Extension::<Tenant>::from_request_parts(&mut parts)
+            .await
+            .unwrap();
+        assert_eq!(tenant, Tenant("initech"));
+
+        let error = Extension::<String>::from_request_parts(&mut parts)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.inner,
+            ErrorRepr::ExtensionMissing { type_name } if type_name == "alloc::string::String"
+        ));
+    }
+
     #[cot::test]
     async fn url_query_extraction() {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -0,0 +1,1272 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures_util::StreamExt;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use http_body_util::BodyExt;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+
+use crate::error::ErrorRepr;
+use crate::middleware::{ensure_body_limit, request_body_limit};
+use crate::request::Request;
+use crate::request::extractors::FromRequest;
+use crate::{Body, Result};
+
+pub(crate) const MULTIPART_FORM_DATA: &str = "multipart/form-data";
+const MAX_HEADERS_SIZE: usize = 8 * 1024;
+const DEFAULT_SPOOL_THRESHOLD: usize = 1024 * 1024;
+/// The maximum size of the beginning of the body read by
+/// [`peek_first_field`].
+const MAX_PEEK_SIZE: usize = 64 * 1024;
+
+/// An error that occurred while parsing a `multipart/form-data` request body.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum MultipartError {
+    /// The request doesn't have a `multipart/form-data` content type with a
+    /// boundary.
+    #[error("expected a `multipart/form-data` content type with a boundary")]
+    InvalidContentType,
+    /// The request body is not a valid multipart body.
+    #[error("malformed multipart body: {0}")]
+    Malformed(&'static str),
+    /// An uploaded file could not be written to or read from its temporary
+    /// file.
+    #[error("could not access the uploaded file: {0}")]
+    Io(#[source] std::io::Error),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    Preamble,
+    Boundary,
+    Headers,
+    FieldBody { read: usize },
+    End,
+}
+
+/// A streaming parser of `multipart/form-data` request bodies, used to
+/// accept file uploads.
+///
+/// The fields are read one by one with [`Self::next_field`], and the data of
+/// each field can be streamed in chunks, read into memory, or saved as an
+/// [`UploadedFile`]. Uploaded files larger than the spool threshold (1
+/// mebibyte by default, see [`Self::spool_threshold`]) are written to a
+/// temporary file instead of being kept in memory.
+///
+/// When created from a request, the total size of the request body, and so
+/// the size of every field, including the uploaded files, is limited the same
+/// way as the body buffered with
+/// [`RequestBodyExt::buffered`](crate::request::RequestBodyExt::buffered):
+/// by [`Route::body_limit`](crate::router::Route::body_limit), the
+/// [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware), or the
+/// [`max_buffered_body_size`](crate::config::ServerConfig::max_buffered_body_size)
+/// server configuration option. The size of the individual fields can be
+/// further limited with [`Self::field_size_limit`].
+///
+/// `Multipart` can be used as an extractor, or created with
+/// [`RequestBodyExt::multipart`](crate::request::RequestBodyExt::multipart).
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::Multipart;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::{Body, StatusCode};
+///
+/// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+///     while let Some(field) = multipart.next_field().await? {
+///         if field.name() == Some("avatar") {
+///             let file = field.into_file().await?;
+///             file.save_to("avatar.png").await?;
+///         }
+///     }
+///
+///     Ok(Response::new_html(StatusCode::OK, Body::fixed("Uploaded!")))
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Multipart {
+    body: Body,
+    body_finished: bool,
+    buffer: BytesMut,
+    delimiter: Bytes,
+    state: State,
+    field_size_limit: Option<usize>,
+    spool_threshold: usize,
+    temp_dir: Option<PathBuf>,
+    /// All the data read from the body, kept by [`peek_first_field`] to put
+    /// it back in front of the rest of the body.
+    replay: Option<BytesMut>,
+}
+
+impl Multipart {
+    /// Creates a new parser of a multipart `body` with the parts separated by
+    /// `boundary`.
+    ///
+    /// Most of the time, [`RequestBodyExt::multipart`] or the `Multipart`
+    /// extractor should be used instead, as they read the boundary from the
+    /// `Content-Type` header of the request.
+    ///
+    /// [`RequestBodyExt::multipart`]: crate::request::RequestBodyExt::multipart
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::request::Multipart;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let body = Body::fixed(
+    ///     "--xyz\r\n\
+    ///      Content-Disposition: form-data; name=\"name\"\r\n\
+    ///      \r\n\
+    ///      John\r\n\
+    ///      --xyz--\r\n",
+    /// );
+    /// let mut multipart = Multipart::new(body, "xyz");
+    ///
+    /// let field = multipart.next_field().await?.unwrap();
+    /// assert_eq!(field.name(), Some("name"));
+    /// assert_eq!(field.text().await?, "John");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn new(body: Body, boundary: &str) -> Self {
+        Self {
+            body,
+            body_finished: false,
+            buffer: BytesMut::new(),
+            delimiter: Bytes::from(format!("\r\n--{boundary}")),
+            state: State::Preamble,
+            field_size_limit: None,
+            spool_threshold: DEFAULT_SPOOL_THRESHOLD,
+            temp_dir: None,
+            replay: None,
+        }
+    }
+
+    pub(crate) fn for_request(request: &mut Request) -> Result<Self> {
+        let boundary = request
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_boundary)
+            .ok_or(MultipartError::InvalidContentType)?;
+
+        ensure_body_limit(request);
+        let limit = request_body_limit(request);
+
+        Ok(Self::new(std::mem::take(request.body_mut()), &boundary).field_size_limit(limit))
+    }
+
+    /// Sets the maximum size of a single field, in bytes.
+    ///
+    /// Reading a larger field fails with an error that is converted to a
+    /// `413 Payload Too Large` response when returned from a request
+    /// handler. For a `Multipart` created from a request, this defaults to
+    /// the body size limit of the request; otherwise, there is no limit by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::request::Multipart;
+    ///
+    /// let multipart = Multipart::new(Body::empty(), "xyz").field_size_limit(10 * 1024 * 1024);
+    /// ```
+    #[must_use]
+    pub fn field_size_limit(mut self, limit: usize) -> Self {
+        self.field_size_limit = Some(limit);
+        self
+    }
+
+    /// Sets the size, in bytes, above which the uploaded files are written to
+    /// a temporary file instead of being kept in memory.
+    ///
+    /// Defaults to 1 mebibyte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::request::Multipart;
+    ///
+    /// let multipart = Multipart::new(Body::empty(), "xyz").spool_threshold(64 * 1024);
+    /// ```
+    #[must_use]
+    pub fn spool_threshold(mut self, threshold: usize) -> Self {
+        self.spool_threshold = threshold;
+        self
+    }
+
+    /// Sets the directory in which the temporary files of the uploaded files
+    /// are created.
+    ///
+    /// Defaults to the temporary directory of the system (see
+    /// [`std::env::temp_dir`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::request::Multipart;
+    ///
+    /// let multipart = Multipart::new(Body::empty(), "xyz").temp_dir("/var/tmp/uploads");
+    /// ```
+    #[must_use]
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, temp_dir: P) -> Self {
+        self.temp_dir = Some(temp_dir.into());
+        self
+    }
+
+    /// Returns the next field of the multipart body, or [`None`] if there are
+    /// no more fields.
+    ///
+    /// The unread data of the previous field is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the request body could not be read or if it's not a
+    /// valid multipart body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         println!("Received field {:?}", field.name());
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    pub async fn next_field(&mut self) -> Result<Option<MultipartField<'_>>> {
+        loop {
+            match self.state {
+                State::Preamble => {
+                    let boundary = &self.delimiter[2..];
+                    if let Some(index) = find(&self.buffer, boundary) {
+                        self.buffer.advance(index + boundary.len());
+                        self.state = State::Boundary;
+                    } else {
+                        // keep the bytes that can be the beginning of the boundary
+                        let keep = boundary.len() - 1;
+                        if self.buffer.len() > keep {
+                            self.buffer.advance(self.buffer.len() - keep);
+                        }
+                        self.require_data("missing the first boundary").await?;
+                    }
+                }
+                State::Boundary => self.read_boundary_end().await?,
+                State::Headers => {
+                    let headers = self.read_headers().await?;
+                    self.state = State::FieldBody { read: 0 };
+                    return Ok(Some(MultipartField::new(self, headers)));
+                }
+                State::FieldBody { .. } => while self.read_chunk().await?.is_some() {},
+                State::End => return Ok(None),
+            }
+        }
+    }
+
+    async fn read_boundary_end(&mut self) -> Result<()> {
+        loop {
+            if self.buffer.starts_with(b"--") {
+                self.state = State::End;
+                return Ok(());
+            }
+
+            let padding = self
+                .buffer
+                .iter()
+                .take_while(|&&byte| byte == b' ' || byte == b'\t')
+                .count();
+            let line_end = &self.buffer[padding..];
+            if line_end.starts_with(b"\r\n") {
+                self.buffer.advance(padding + 2);
+                self.state = State::Headers;
+                return Ok(());
+            }
+            let is_incomplete = b"\r\n".starts_with(line_end) || b"--".starts_with(&self.buffer);
+            if !is_incomplete {
+                return Err(MultipartError::Malformed("invalid boundary line").into());
+            }
+
+            self.require_data("unexpected end after a boundary").await?;
+        }
+    }
+
+    async fn read_headers(&mut self) -> Result<HeaderMap> {
+        loop {
+            if self.buffer.starts_with(b"\r\n") {
+                self.buffer.advance(2);
+                return Ok(HeaderMap::new());
+            }
+            if let Some(index) = find(&self.buffer, b"\r\n\r\n") {
+                let headers = parse_headers(&self.buffer[..index])?;
+                self.buffer.advance(index + 4);
+                return Ok(headers);
+            }
+            if self.buffer.len() > MAX_HEADERS_SIZE {
+                return Err(MultipartError::Malformed("part headers too large").into());
+            }
+
+            self.require_data("unexpected end of part headers").await?;
+        }
+    }
+
+    async fn read_chunk(&mut self) -> Result<Option<Bytes>> {
+        let State::FieldBody { read } = self.state else {
+            return Ok(None);
+        };
+
+        loop {
+            if let Some(index) = find(&self.buffer, &self.delimiter) {
+                if index == 0 {
+                    self.buffer.advance(self.delimiter.len());
+                    self.state = State::Boundary;
+                    return Ok(None);
+                }
+                return self.take_chunk(read, index).map(Some);
+            }
+
+            // keep the bytes that can be the beginning of the delimiter
+            let keep = self.delimiter.len() - 1;
+            if self.buffer.len() > keep {
+                return self.take_chunk(read, self.buffer.len() - keep).map(Some);
+            }
+
+            self.require_data("unexpected end of a field").await?;
+        }
+    }
+
+    fn take_chunk(&mut self, read: usize, len: usize) -> Result<Bytes> {
+        let read = read + len;
+        if let Some(limit) = self.field_size_limit {
+            if read > limit {
+                return Err(ErrorRepr::BodyTooLarge { limit }.into());
+            }
+        }
+
+        self.state = State::FieldBody { read };
+        Ok(self.buffer.split_to(len).freeze())
+    }
+
+    async fn require_data(&mut self, error: &'static str) -> Result<()> {
+        while !self.body_finished {
+            match self.body.frame().await {
+                Some(frame) => {
+                    if let Ok(data) = frame?.into_data() {
+                        if !data.is_empty() {
+                            self.buffer.extend_from_slice(&data);
+                            if let Some(replay) = &mut self.replay {
+                                replay.extend_from_slice(&data);
+                                if replay.len() > MAX_PEEK_SIZE {
+                                    return Err(ErrorRepr::BodyTooLarge {
+                                        limit: MAX_PEEK_SIZE,
+                                    }
+                                    .into());
+                                }
+                            }
+                            return Ok(());
+                        }
+                    }
+                }
+                None => self.body_finished = true,
+            }
+        }
+
+        Err(MultipartError::Malformed(error).into())
+    }
+}
+
+impl FromRequest for Multipart {
+    async fn from_request(mut request: Request) -> Result<Self> {
+        Self::for_request(&mut request)
+    }
+}
+
+/// A single field of a `multipart/form-data` request body.
+///
+/// Returned by [`Multipart::next_field`].
+#[derive(Debug)]
+pub struct MultipartField<'a> {
+    multipart: &'a mut Multipart,
+    headers: HeaderMap,
+    name: Option<String>,
+    file_name: Option<String>,
+}
+
+impl<'a> MultipartField<'a> {
+    fn new(multipart: &'a mut Multipart, headers: HeaderMap) -> Self {
+        let mut name = None;
+        let mut file_name = None;
+        if let Some(disposition) = headers
+            .get(http::header::CONTENT_DISPOSITION)
+            .and_then(|value| value.to_str().ok())
+        {
+            for (param, value) in parse_header_params(disposition).1 {
+                match param.as_str() {
+                    "name" => name = Some(value),
+                    "filename" => file_name = Some(value),
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            multipart,
+            headers,
+            name,
+            file_name,
+        }
+    }
+
+    /// Returns the name of the field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         println!("Received field {:?}", field.name());
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the name of the uploaded file, if the field is a file.
+    ///
+    /// Note that the name is sent by the client and can't be trusted; in
+    /// particular, it shouldn't be used as a path without sanitizing it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         if let Some(file_name) = field.file_name() {
+    ///             println!("Received file {file_name}");
+    ///         }
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Returns the content type of the field, if given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         println!("Received {:?}", field.content_type());
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Returns all the headers of the field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         println!("Received {:?}", field.headers());
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the next chunk of the field data, or [`None`] if the whole
+    /// field has been read.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the request body could not be read, if it's not a
+    /// valid multipart body, or if the field is larger than
+    /// [`Multipart::field_size_limit`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(mut field) = multipart.next_field().await? {
+    ///         let mut size = 0;
+    ///         while let Some(chunk) = field.chunk().await? {
+    ///             size += chunk.len();
+    ///         }
+    ///         println!("Received {size} bytes");
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>> {
+        self.multipart.read_chunk().await
+    }
+
+    /// Reads the whole field data into memory.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the request body could not be read, if it's not a
+    /// valid multipart body, or if the field is larger than
+    /// [`Multipart::field_size_limit`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         let data = field.bytes().await?;
+    ///         println!("Received {} bytes", data.len());
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    pub async fn bytes(mut self) -> Result<Bytes> {
+        let mut data = BytesMut::new();
+        while let Some(chunk) = self.chunk().await? {
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data.freeze())
+    }
+
+    /// Reads the whole field data into memory as a UTF-8 string.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the request body could not be read, if it's not a
+    /// valid multipart body, if the field is larger than
+    /// [`Multipart::field_size_limit`], or if the data is not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         let text = field.text().await?;
+    ///         println!("Received {text}");
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    pub async fn text(self) -> Result<String> {
+        let data = self.bytes().await?;
+        String::from_utf8(data.into())
+            .map_err(|_| MultipartError::Malformed("a text field is not valid UTF-8").into())
+    }
+
+    /// Reads the whole field data as an [`UploadedFile`].
+    ///
+    /// The data is kept in memory, unless it's larger than
+    /// [`Multipart::spool_threshold`], in which case it's written to a
+    /// temporary file, which is removed once the [`UploadedFile`] (and all
+    /// its clones) are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the request body could not be read, if it's not a
+    /// valid multipart body, if the field is larger than
+    /// [`Multipart::field_size_limit`], or if the temporary file could not be
+    /// written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Multipart;
+    /// use cot::response::Response;
+    ///
+    /// async fn upload(mut multipart: Multipart) -> cot::Result<Response> {
+    ///     while let Some(field) = multipart.next_field().await? {
+    ///         let file = field.into_file().await?;
+    ///         println!("Received {:?} ({} bytes)", file.file_name(), file.size());
+    ///     }
+    ///     // ...
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    pub async fn into_file(mut self) -> Result<UploadedFile> {
+        let threshold = self.multipart.spool_threshold;
+        let mut data = BytesMut::new();
+        let mut temp_file = None;
+        let mut size = 0;
+
+        while let Some(chunk) = self.chunk().await? {
+            size += chunk.len() as u64;
+            if let Some((_, file)) = &mut temp_file {
+                write_temp_file(file, &chunk).await?;
+            } else {
+                data.extend_from_slice(&chunk);
+                if data.len() > threshold {
+                    let temp_dir = self
+                        .multipart
+                        .temp_dir
+                        .clone()
+                        .unwrap_or_else(std::env::temp_dir);
+                    let (path, mut file) = create_temp_file(&temp_dir).await?;
+                    write_temp_file(&mut file, &data).await?;
+                    data = BytesMut::new();
+                    temp_file = Some((path, file));
+                }
+            }
+        }
+
+        let storage = match temp_file {
+            Some((path, mut file)) => {
+                file.flush().await.map_err(MultipartError::Io)?;
+                FileStorage::TempFile(path)
+            }
+            None => FileStorage::Memory(data.freeze()),
+        };
+        let content_type = self.content_type().map(ToOwned::to_owned);
+
+        Ok(UploadedFile {
+            file_name: self.file_name,
+            content_type,
+            size,
+            storage: Arc::new(storage),
+        })
+    }
+}
+
+/// A file uploaded in a `multipart/form-data` request.
+///
+/// The file data is either kept in memory, or stored in a temporary file that
+/// is removed once the `UploadedFile` and all its clones are dropped. Use
+/// [`Self::save_to`] to keep the file.
+///
+/// Returned by [`MultipartField::into_file`], and can be used as a form field
+/// (see [`FileField`](crate::form::fields::FileField)).
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::UploadedFile;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let file = UploadedFile::new(Some("hello.txt".to_owned()), None, "Hello!");
+///
+/// assert_eq!(file.file_name(), Some("hello.txt"));
+/// assert_eq!(file.size(), 6);
+/// assert_eq!(file.bytes().await?, "Hello!");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    size: u64,
+    storage: Arc<FileStorage>,
+}
+
+#[derive(Debug)]
+enum FileStorage {
+    Memory(Bytes),
+    TempFile(TempPath),
+}
+
+impl UploadedFile {
+    /// Creates a new in-memory uploaded file.
+    ///
+    /// This is mostly useful for testing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::UploadedFile;
+    ///
+    /// let file = UploadedFile::new(
+    ///     Some("hello.txt".to_owned()),
+    ///     Some("text/plain".to_owned()),
+    ///     "Hello!",
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new(
+        file_name: Option<String>,
+        content_type: Option<String>,
+        data: impl Into<Bytes>,
+    ) -> Self {
+        let data = data.into();
+        Self {
+            file_name,
+            content_type,
+            size: data.len() as u64,
+            storage: Arc::new(FileStorage::Memory(data)),
+        }
+    }
+
+    /// Returns the name of the file, as sent by the client.
+    ///
+    /// Note that the name can't be trusted; in particular, it shouldn't be
+    /// used as a path without sanitizing it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::UploadedFile;
+    ///
+    /// let file = UploadedFile::new(Some("hello.txt".to_owned()), None, "Hello!");
+    /// assert_eq!(file.file_name(), Some("hello.txt"));
+    /// ```
+    #[must_use]
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Returns the content type of the file, as sent by the client.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::UploadedFile;
+    ///
+    /// let file = UploadedFile::new(None, Some("text/plain".to_owned()), "Hello!");
+    /// assert_eq!(file.content_type(), Some("text/plain"));
+    /// ```
+    #[must_use]
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Returns the size of the file, in bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::UploadedFile;
+    ///
+    /// let file = UploadedFile::new(None, None, "Hello!");
+    /// assert_eq!(file.size(), 6);
+    /// ```
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the path of the temporary file holding the data, or [`None`]
+    /// if the file is kept in memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::UploadedFile;
+    ///
+    /// let file = UploadedFile::new(None, None, "Hello!");
+    /// assert_eq!(file.temp_path(), None);
+    /// ```
+    #[must_use]
+    pub fn temp_path(&self) -> Option<&Path> {
+        match self.storage.as_ref() {
+            FileStorage::Memory(_) => None,
+            FileStorage::TempFile(path) => Some(&path.0),
+        }
+    }
+
+    /// Returns the data of the file, reading it from the temporary file if
+    /// needed.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the temporary file could not be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::UploadedFile;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let file = UploadedFile::new(None, None, "Hello!");
+    /// assert_eq!(file.bytes().await?, "Hello!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bytes(&self) -> Result<Bytes> {
+        match self.storage.as_ref() {
+            FileStorage::Memory(data) => Ok(data.clone()),
+            FileStorage::TempFile(path) => Ok(tokio::fs::read(&path.0)
+                .await
+                .map_err(MultipartError::Io)?
+                .into()),
+        }
+    }
+
+    /// Saves the file to the given path, overwriting the file if it exists.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the file could not be written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::request::UploadedFile;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let file = UploadedFile::new(None, None, "Hello!");
+    /// file.save_to("uploads/hello.txt").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let result = match self.storage.as_ref() {
+            FileStorage::Memory(data) => tokio::fs::write(path, data).await,
+            FileStorage::TempFile(temp_path) => tokio::fs::copy(&temp_path.0, path).await.map(drop),
+        };
+        result.map_err(|error| MultipartError::Io(error).into())
+    }
+}
+
+/// A path to a temporary file that is removed when dropped.
+#[derive(Debug)]
+struct TempPath(PathBuf);
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_file(&self.0) {
+            warn!(path = %self.0.display(), %error, "Could not remove a temporary upload file");
+        }
+    }
+}
+
+async fn create_temp_file(dir: &Path) -> Result<(TempPath, tokio::fs::File)> {
+    let mut rng = StdRng::from_os_rng();
+    loop {
+        let mut file_name = String::from("cot-upload-");
+        write!(file_name, "{:016x}", rng.next_u64()).expect("writing to a String never fails");
+        let path = dir.join(file_name);
+
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+        {
+            Ok(file) => return Ok((TempPath(path), file)),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(error) => return Err(MultipartError::Io(error).into()),
+        }
+    }
+}
+
+async fn write_temp_file(file: &mut tokio::fs::File, data: &[u8]) -> Result<()> {
+    file.write_all(data)
+        .await
+        .map_err(|error| MultipartError::Io(error).into())
+}
+
+/// Reads the value of the first field of the multipart body of the request,
+/// if the field is named `name`.
+///
+/// Only the beginning of the body is read, up to the end of the field (and
+/// at most 64 kibibytes of it), so this can be done before the request is
+/// routed and the body size limit of the route is known. The data read is
+/// then put back in front of the rest of the body, so the body can still be
+/// read as usual.
+///
+/// Returns `None` if the body is not a multipart body, or if its first field
+/// could not be read or has a different name.
+pub(crate) async fn peek_first_field(request: &mut Request, name: &str) -> Option<Bytes> {
+    let boundary = request
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_boundary)?;
+    let mut multipart = Multipart::new(std::mem::take(request.body_mut()), &boundary);
+    multipart.replay = Some(BytesMut::new());
+
+    let value = match multipart.next_field().await {
+        Ok(Some(field)) if field.name() == Some(name) => field.bytes().await.ok(),
+        _ => None,
+    };
+
+    let replay = multipart.replay.take().unwrap_or_default().freeze();
+    *request.body_mut() = if multipart.body_finished {
+        Body::fixed(replay)
+    } else {
+        let rest = multipart.body.into_stream();
+        Body::streaming(futures_util::stream::once(async { Ok(replay) }).chain(rest))
+    };
+
+    value
+}
+
+pub(crate) fn is_multipart_form_data(content_type: &str) -> bool {
+    parse_header_params(content_type)
+        .0
+        .eq_ignore_ascii_case(MULTIPART_FORM_DATA)
+}
+
+fn parse_boundary(content_type: &str) -> Option<String> {
+    let (value, params) = parse_header_params(content_type);
+    if !value.eq_ignore_ascii_case(MULTIPART_FORM_DATA) {
+        return None;
+    }
+
+    params
+        .into_iter()
+        .find(|(name, _)| name == "boundary")
+        .map(|(_, boundary)| boundary)
+        .filter(|boundary| !boundary.is_empty())
+}
+
+/// Parses a header value with parameters, such as `form-data; name="field"`,
+/// into the value and the list of parameters with lowercase names.
+fn parse_header_params(header: &str) -> (&str, Vec<(String, String)>) {
+    let (value, mut rest) = header.split_once(';').unwrap_or((header, ""));
+    let mut params = Vec::new();
+
+    loop {
+        rest = rest.trim_start_matches([' ', '\t', ';']);
+        let Some((name, remainder)) = rest.split_once('=') else {
+            break;
+        };
+        let name = name.trim().to_ascii_lowercase();
+        let remainder = remainder.trim_start();
+
+        // quoted values can contain semicolons; browsers escape the quotes
+        // inside the values as `%22`, so there is no need to handle escaping
+        let (param_value, remainder) = if let Some(quoted) = remainder.strip_prefix('"') {
+            quoted.split_once('"').unwrap_or((quoted, ""))
+        } else {
+            remainder.split_once(';').unwrap_or((remainder, ""))
+        };
+        params.push((name, param_value.trim().to_owned()));
+        rest = remainder;
+    }
+
+    (value.trim(), params)
+}
+
+fn parse_headers(data: &[u8]) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for line in data.split(|&byte| byte == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+
+        let (name, value) = line
+            .iter()
+            .position(|&byte| byte == b':')
+            .map(|index| line.split_at(index))
+            .ok_or(MultipartError::Malformed("invalid part header"))?;
+        let name = HeaderName::from_bytes(name.trim_ascii())
+            .map_err(|_| MultipartError::Malformed("invalid part header name"))?;
+        let value = HeaderValue::from_bytes(value[1..].trim_ascii())
+            .map_err(|_| MultipartError::Malformed("invalid part header value"))?;
+        headers.append(name, value);
+    }
+
+    Ok(headers)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::RequestBodyExt;
+    use crate::response::{MultipartPart, MultipartResponse};
+
+    const BODY: &str = "preamble\r\n\
+        --xyz\r\n\
+        Content-Disposition: form-data; name=\"name\"\r\n\
+        \r\n\
+        John\r\n\
+        --xyz\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"a; b.txt\"\r\n\
+        Content-Type: text/plain\r\n\
+        \r\n\
+        line 1\r\nline 2\r\n\
+        --xyz--\r\n\
+        epilogue";
+
+    fn chunked_body(data: &str, chunk_size: usize) -> Body {
+        let chunks: Vec<_> = data
+            .as_bytes()
+            .chunks(chunk_size)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        Body::streaming(futures::stream::iter(chunks))
+    }
+
+    async fn read_all(
+        mut multipart: Multipart,
+    ) -> Result<Vec<(Option<String>, Option<String>, String)>> {
+        let mut fields = Vec::new();
+        while let Some(field) = multipart.next_field().await? {
+            let name = field.name().map(ToOwned::to_owned);
+            let file_name = field.file_name().map(ToOwned::to_owned);
+            fields.push((name, file_name, field.text().await?));
+        }
+        Ok(fields)
+    }
+
+    #[cot::test]
+    async fn multipart_fields() {
+        let fields = read_all(Multipart::new(Body::fixed(BODY), "xyz"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fields,
+            [
+                (Some("name".to_owned()), None, "John".to_owned()),
+                (
+                    Some("file".to_owned()),
+                    Some("a; b.txt".to_owned()),
+                    "line 1\r\nline 2".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[cot::test]
+    async fn multipart_fields_chunked() {
+        for chunk_size in [1, 2, 3, 7, 16] {
+            let fields = read_all(Multipart::new(chunked_body(BODY, chunk_size), "xyz"))
+                .await
+                .unwrap();
+
+            assert_eq!(fields.len(), 2);
+            assert_eq!(fields[1].2, "line 1\r\nline 2");
+        }
+    }
+
+    #[cot::test]
+    async fn multipart_skips_unread_fields() {
+        let mut multipart = Multipart::new(chunked_body(BODY, 5), "xyz");
+
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("name"));
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("file"));
+        assert_eq!(field.content_type(), Some("text/plain"));
+        assert!(multipart.next_field().await.unwrap().is_none());
+    }
+
+    #[cot::test]
+    async fn multipart_response_roundtrip() {
+        let response = MultipartResponse::form_data()
+            .boundary("roundtrip")
+            .part(MultipartPart::form_field("a\"b", Body::fixed("value")))
+            .into_response();
+        let body = response.into_body();
+
+        let fields = read_all(Multipart::new(body, "roundtrip")).await.unwrap();
+
+        assert_eq!(
+            fields,
+            [(Some("a%22b".to_owned()), None, "value".to_owned())]
+        );
+    }
+
+    #[cot::test]
+    async fn multipart_unexpected_end() {
+        let body = Body::fixed("--xyz\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nabc");
+
+        let error = read_all(Multipart::new(body, "xyz")).await.unwrap_err();
+
+        assert!(matches!(
+            error.inner,
+            ErrorRepr::Multipart(MultipartError::Malformed("unexpected end of a field"))
+        ));
+    }
+
+    #[cot::test]
+    async fn multipart_invalid_boundary_line() {
+        let body = Body::fixed("--xyzabc\r\n\r\n--xyz--");
+
+        let error = read_all(Multipart::new(body, "xyz")).await.unwrap_err();
+
+        assert!(matches!(
+            error.inner,
+            ErrorRepr::Multipart(MultipartError::Malformed("invalid boundary line"))
+        ));
+    }
+
+    #[cot::test]
+    async fn multipart_field_size_limit() {
+        let error = read_all(Multipart::new(Body::fixed(BODY), "xyz").field_size_limit(5))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error.inner, ErrorRepr::BodyTooLarge { limit: 5 }));
+    }
+
+    #[cot::test]
+    async fn multipart_request_file_size_limit() {
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(100)
+                    .build(),
+            )
+            .build();
+        let mut request = crate::test::TestRequestBuilder::post("/")
+            .config(config)
+            .build();
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=xyz"),
+        );
+        let body = format!(
+            "--xyz\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+             \r\n\
+             {}\r\n\
+             --xyz--\r\n",
+            "a".repeat(128)
+        );
+        *request.body_mut() = chunked_body(&body, 16);
+
+        let mut multipart = request.multipart().unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+        let error = field.into_file().await.unwrap_err();
+
+        let response = crate::middleware::reject_too_large(Err(error)).unwrap();
+        assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cot::test]
+    async fn multipart_into_file_in_memory() {
+        let mut multipart = Multipart::new(Body::fixed(BODY), "xyz");
+        multipart.next_field().await.unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+
+        let file = field.into_file().await.unwrap();
+
+        assert_eq!(file.file_name(), Some("a; b.txt"));
+        assert_eq!(file.content_type(), Some("text/plain"));
+        assert_eq!(file.size(), 14);
+        assert!(file.temp_path().is_none());
+        assert_eq!(file.bytes().await.unwrap(), "line 1\r\nline 2");
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn multipart_into_file_spooled() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut multipart = Multipart::new(chunked_body(BODY, 4), "xyz")
+            .spool_threshold(4)
+            .temp_dir(temp_dir.path());
+        multipart.next_field().await.unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+
+        let file = field.into_file().await.unwrap();
+        let temp_path = file.temp_path().unwrap().to_owned();
+
+        assert!(temp_path.starts_with(temp_dir.path()));
+        assert_eq!(file.size(), 14);
+        assert_eq!(file.bytes().await.unwrap(), "line 1\r\nline 2");
+
+        let saved_path = temp_dir.path().join("saved.txt");
+        file.save_to(&saved_path).await.unwrap();
+        assert_eq!(std::fs::read(&saved_path).unwrap(), b"line 1\r\nline 2");
+
+        drop(file);
+        assert!(!temp_path.exists());
+    }
+
+    #[cot::test]
+    async fn peek_first_field_keeps_body() {
+        let mut request = crate::test::TestRequestBuilder::post("/").build();
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=xyz"),
+        );
+        *request.body_mut() = chunked_body(BODY, 8);
+
+        assert_eq!(peek_first_field(&mut request, "file").await, None);
+        assert_eq!(
+            peek_first_field(&mut request, "name").await,
+            Some(Bytes::from("John"))
+        );
+        assert_eq!(request.into_body().into_bytes().await.unwrap(), BODY);
+    }
+
+    #[test]
+    fn parse_boundary_content_type() {
+        assert_eq!(
+            parse_boundary("multipart/form-data; boundary=abc").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            parse_boundary("Multipart/Form-Data; charset=utf-8; BOUNDARY=\"a b;c\"").as_deref(),
+            Some("a b;c")
+        );
+        assert_eq!(parse_boundary("multipart/form-data"), None);
+        assert_eq!(parse_boundary("multipart/form-data; boundary="), None);
+        assert_eq!(parse_boundary("text/plain; boundary=abc"), None);
+    }
+}
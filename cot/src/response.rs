@@ -12,8 +12,25 @@
 //! use cot::response::ResponseExt;
 //! ```
 
+mod file;
+mod multipart;
+mod sse;
+
+use std::future::Future;
+use std::path::Path;
+
 use bytes::Bytes;
+pub use file::AttachmentSource;
+use file::serve_file;
+pub(crate) use file::{ByteRange, content_range};
+use futures_core::Stream;
+use http::HeaderMap;
+pub use multipart::{MultipartPart, MultipartResponse};
+pub use sse::{Event, KeepAlive, Sse};
 
+use crate::body::BodyInner;
+use crate::config::TextNormalizationConfig;
+use crate::cookie::Cookie;
 use crate::error_page::ErrorPageTrigger;
 use crate::headers::HTML_CONTENT_TYPE;
 #[cfg(feature = "json")]
@@ -120,8 +137,284 @@ pub trait ResponseExt: Sized + private::Sealed {
     ///
     /// * [`crate::reverse_redirect!`] – a more ergonomic way to create
     ///   redirects to internal views
+    /// * [`Self::redirect_see_other`] – the same, with a name matching the
+    ///   other redirect helpers
     #[must_use]
     fn new_redirect<T: Into<String>>(location: T) -> Self;
+
+    /// Create a new redirect response with the `308 Permanent Redirect`
+    /// status code.
+    ///
+    /// Use this when the resource has been moved for good; browsers and
+    /// search engines may cache the new location. The request method and
+    /// body are kept when following the redirect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// let response = Response::redirect_permanent("/new-location");
+    /// assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    /// assert_eq!(response.headers()["location"], "/new-location");
+    /// ```
+    #[must_use]
+    fn redirect_permanent<T: Into<String>>(location: T) -> Self;
+
+    /// Create a new redirect response with the `303 See Other` status code.
+    ///
+    /// This is the usual response after handling a form submission: the
+    /// browser follows the redirect with a `GET` request, so refreshing the
+    /// page doesn't submit the form again. This is the same as
+    /// [`Self::new_redirect`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// let response = Response::redirect_see_other("/thanks");
+    /// assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    /// assert_eq!(response.headers()["location"], "/thanks");
+    /// ```
+    #[must_use]
+    fn redirect_see_other<T: Into<String>>(location: T) -> Self;
+
+    /// Create a new redirect response with the `307 Temporary Redirect`
+    /// status code.
+    ///
+    /// Use this when the resource is temporarily available under a different
+    /// location. The request method and body are kept when following the
+    /// redirect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// let response = Response::redirect_temporary("/maintenance");
+    /// assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+    /// assert_eq!(response.headers()["location"], "/maintenance");
+    /// ```
+    #[must_use]
+    fn redirect_temporary<T: Into<String>>(location: T) -> Self;
+
+    /// Create a new response that makes the browser download the body as a
+    /// file.
+    ///
+    /// This creates a new [`Response`] object with a status code of
+    /// [`StatusCode::OK`], a `Content-Type` header guessed from the file
+    /// extension, and a `Content-Disposition: attachment` header with the
+    /// given file name. The body is sent as-is, so passing a
+    /// [`Body::streaming`] body will stream the file to the client without
+    /// buffering it in memory.
+    ///
+    /// The file name is sanitized before putting it in the header: control
+    /// characters, quotes, and path separators are removed. Non-ASCII file
+    /// names are encoded as per [RFC 5987](https://datatracker.ietf.org/doc/html/rfc5987),
+    /// with an ASCII fallback for older clients.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// let response = Response::download(Body::fixed("a,b,c"), "report.csv");
+    /// assert_eq!(
+    ///     response.headers()["content-disposition"],
+    ///     "attachment; filename=\"report.csv\"; filename*=UTF-8''report.csv"
+    /// );
+    /// assert_eq!(response.headers()["content-type"], "text/csv");
+    /// ```
+    #[must_use]
+    fn download(body: Body, filename: &str) -> Self;
+
+    /// Create a new response that makes the browser display the body inline,
+    /// while still suggesting a file name.
+    ///
+    /// This is the same as [`Self::download`], but sets the
+    /// `Content-Disposition` header to `inline`, which is useful for files
+    /// that can be displayed in the browser, such as PDFs or images.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// let response = Response::inline(Body::fixed("%PDF-1.7"), "invoice.pdf");
+    /// assert_eq!(
+    ///     response.headers()["content-disposition"],
+    ///     "inline; filename=\"invoice.pdf\"; filename*=UTF-8''invoice.pdf"
+    /// );
+    /// assert_eq!(response.headers()["content-type"], "application/pdf");
+    /// ```
+    #[must_use]
+    fn inline(body: Body, filename: &str) -> Self;
+
+    /// Create a new [Server-Sent Events](Sse) response sending the events
+    /// from the given stream.
+    ///
+    /// This is a shortcut for `Sse::new(stream).into_response()`; use [`Sse`]
+    /// directly to configure the keep-alive comments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::{Event, Response, ResponseExt};
+    ///
+    /// let events = futures::stream::iter([Event::new().data("Hello")]);
+    /// let response = Response::event_stream(events);
+    /// assert_eq!(response.headers()["content-type"], "text/event-stream");
+    /// ```
+    #[must_use]
+    fn event_stream<S: Stream<Item = Event> + Send + 'static>(stream: S) -> Self;
+
+    /// Create a new response serving the file at the given path.
+    ///
+    /// The file is streamed to the client in chunks, so it is never loaded
+    /// into memory as a whole. The response has a `Content-Type` header
+    /// guessed from the file extension, as well as `Content-Length`, `ETag`
+    /// and `Last-Modified` headers.
+    ///
+    /// The headers of the request are used to honor `Range` requests: a
+    /// request for a single byte range gets a `206 Partial Content` response
+    /// with the matching `Content-Range` header, or a `416 Range Not
+    /// Satisfiable` response if the range is outside the file. If the request
+    /// contains an `If-Range` header that doesn't match the current version
+    /// of the file, the whole file is served instead. Requests for multiple
+    /// ranges are served the whole file as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns a "not found" error if the file does not exist or is a
+    /// directory, and an error if the file could not be read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::request::Request;
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// async fn video(request: Request) -> cot::Result<Response> {
+    ///     Response::file("media/intro.mp4", request.headers()).await
+    /// }
+    /// ```
+    fn file<P: AsRef<Path> + Send>(
+        path: P,
+        request_headers: &HeaderMap,
+    ) -> impl Future<Output = crate::Result<Self>> + Send;
+
+    /// Create a new response that makes the browser download a file or a
+    /// stream of data under the given file name.
+    ///
+    /// When given a path, the file is read in chunks as the response is being
+    /// sent, so it is never loaded into memory as a whole, and the
+    /// `Content-Length` header is set to the size of the file. A [`Body`]
+    /// (such as one created with [`Body::streaming`]) is sent as-is, with a
+    /// `Content-Length` header only if its length is known upfront.
+    ///
+    /// As with [`Self::download`], the `Content-Type` header is guessed from
+    /// the extension of the file name, which is sanitized and encoded as per
+    /// [RFC 5987](https://datatracker.ietf.org/doc/html/rfc5987) in the
+    /// `Content-Disposition` header.
+    ///
+    /// # Errors
+    ///
+    /// Returns a "not found" error if the source is a path to a file that does
+    /// not exist or to a directory, and an error if the file could not be
+    /// opened.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// async fn export() -> cot::Result<Response> {
+    ///     Response::attachment(Path::new("exports/2024.csv"), "report-2024.csv").await
+    /// }
+    /// ```
+    ///
+    /// Sending a stream of data generated on the fly:
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let rows = futures::stream::iter(["a,b\n", "c,d\n"].map(|row| Ok(row.into())));
+    /// let response = Response::attachment(Body::streaming(rows), "export.csv").await?;
+    /// assert_eq!(response.headers()["content-type"], "text/csv");
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn attachment<S: Into<AttachmentSource> + Send>(
+        source: S,
+        filename: &str,
+    ) -> impl Future<Output = crate::Result<Self>> + Send;
+
+    /// Add a `Set-Cookie` header setting the given cookie to the response.
+    ///
+    /// Accepts both a [`Cookie`] and a
+    /// [`CookieBuilder`](crate::cookie::CookieBuilder). Calling this
+    /// multiple times sets multiple cookies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Path` or `Domain` attributes of the cookie contain
+    /// characters that are not allowed in HTTP headers. The name and the
+    /// value of the cookie are percent-encoded, so they can contain any
+    /// characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::{Cookie, SameSite};
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// let response = Response::new_redirect("/").with_cookie(
+    ///     Cookie::build("theme", "dark")
+    ///         .secure(true)
+    ///         .same_site(SameSite::Lax),
+    /// );
+    /// assert_eq!(
+    ///     response.headers()["set-cookie"],
+    ///     "theme=dark; SameSite=Lax; Secure"
+    /// );
+    /// ```
+    #[must_use]
+    fn with_cookie<C: Into<Cookie>>(self, cookie: C) -> Self;
+
+    /// Add a `Set-Cookie` header making the browser remove the given cookie.
+    ///
+    /// Browsers only remove the cookie if the `Path` and `Domain` attributes
+    /// match the ones the cookie was set with, so they should be set on the
+    /// given cookie as well; its value and other attributes are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Path` or `Domain` attributes of the cookie contain
+    /// characters that are not allowed in HTTP headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cookie::Cookie;
+    /// use cot::response::{Response, ResponseExt};
+    ///
+    /// let response = Response::new_redirect("/").delete_cookie(Cookie::build("theme", "").path("/"));
+    /// let header = response.headers()["set-cookie"].to_str().unwrap();
+    /// assert!(header.starts_with("theme=; Path=/; Max-Age=0"));
+    /// ```
+    #[must_use]
+    fn delete_cookie<C: Into<Cookie>>(self, cookie: C) -> Self;
 }
 
 impl private::Sealed for Response {}
@@ -158,11 +451,329 @@ impl ResponseExt for Response {
     }
 
     fn new_redirect<T: Into<String>>(location: T) -> Self {
-        http::Response::builder()
-            .status(StatusCode::SEE_OTHER)
-            .header(http::header::LOCATION, location.into())
-            .body(Body::empty())
-            .expect(RESPONSE_BUILD_FAILURE)
+        redirect(StatusCode::SEE_OTHER, location.into())
+    }
+
+    fn redirect_permanent<T: Into<String>>(location: T) -> Self {
+        redirect(StatusCode::PERMANENT_REDIRECT, location.into())
+    }
+
+    fn redirect_see_other<T: Into<String>>(location: T) -> Self {
+        redirect(StatusCode::SEE_OTHER, location.into())
+    }
+
+    fn redirect_temporary<T: Into<String>>(location: T) -> Self {
+        redirect(StatusCode::TEMPORARY_REDIRECT, location.into())
+    }
+
+    fn download(body: Body, filename: &str) -> Self {
+        file_response(body, filename, Disposition::Attachment)
+    }
+
+    fn inline(body: Body, filename: &str) -> Self {
+        file_response(body, filename, Disposition::Inline)
+    }
+
+    fn event_stream<S: Stream<Item = Event> + Send + 'static>(stream: S) -> Self {
+        Sse::new(stream).into_response()
+    }
+
+    async fn file<P: AsRef<Path> + Send>(
+        path: P,
+        request_headers: &HeaderMap,
+    ) -> crate::Result<Self> {
+        serve_file(path.as_ref(), request_headers).await
+    }
+
+    async fn attachment<S: Into<AttachmentSource> + Send>(
+        source: S,
+        filename: &str,
+    ) -> crate::Result<Self> {
+        let (body, length) = source.into().into_body().await?;
+
+        let mut response = file_response(body, filename, Disposition::Attachment);
+        if let Some(length) = length {
+            response
+                .headers_mut()
+                .insert(http::header::CONTENT_LENGTH, length.into());
+        }
+        Ok(response)
+    }
+
+    fn with_cookie<C: Into<Cookie>>(mut self, cookie: C) -> Self {
+        let value = http::HeaderValue::try_from(cookie.into().to_set_cookie_header())
+            .expect("cookie attributes contain invalid header characters");
+        self.headers_mut().append(http::header::SET_COOKIE, value);
+        self
+    }
+
+    fn delete_cookie<C: Into<Cookie>>(self, cookie: C) -> Self {
+        self.with_cookie(cookie.into().into_removal())
+    }
+}
+
+pub(crate) fn redirect(status: StatusCode, location: String) -> Response {
+    http::Response::builder()
+        .status(status)
+        .header(http::header::LOCATION, location)
+        .body(Body::empty())
+        .expect(RESPONSE_BUILD_FAILURE)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Disposition {
+    Attachment,
+    Inline,
+}
+
+impl Disposition {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Attachment => "attachment",
+            Self::Inline => "inline",
+        }
+    }
+}
+
+fn file_response(body: Body, filename: &str, disposition: Disposition) -> Response {
+    let filename = sanitize_filename(filename);
+    let mime_type = mime_guess::from_path(&filename).first_or_octet_stream();
+
+    http::Response::builder()
+        .status(StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, mime_type.to_string())
+        .header(
+            http::header::CONTENT_DISPOSITION,
+            content_disposition(disposition, &filename),
+        )
+        .body(body)
+        .expect(RESPONSE_BUILD_FAILURE)
+}
+
+/// Removes characters that could be used to inject headers or to make the
+/// client save the file outside the download directory.
+fn sanitize_filename(filename: &str) -> String {
+    let filename: String = filename
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .map(|c| if matches!(c, '/' | '\\') { '_' } else { c })
+        .collect();
+    let filename = filename.trim();
+
+    if filename.is_empty() {
+        "download".to_owned()
+    } else {
+        filename.to_owned()
+    }
+}
+
+/// Builds the `Content-Disposition` header value as described in
+/// [RFC 6266](https://datatracker.ietf.org/doc/html/rfc6266), with both the
+/// plain `filename` parameter (ASCII only) and the RFC 5987-encoded
+/// `filename*` parameter.
+fn content_disposition(disposition: Disposition, filename: &str) -> String {
+    let ascii_filename: String = filename
+        .chars()
+        .map(|c| if c.is_ascii() { c } else { '_' })
+        .collect();
+
+    format!(
+        "{}; filename=\"{}\"; filename*=UTF-8''{}",
+        disposition.as_str(),
+        ascii_filename,
+        rfc5987_encode(filename)
+    )
+}
+
+fn rfc5987_encode(value: &str) -> String {
+    use std::fmt::Write;
+
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        let is_attr_char = byte.is_ascii_alphanumeric()
+            || matches!(
+                byte,
+                b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+            );
+        if is_attr_char {
+            encoded.push(char::from(byte));
+        } else {
+            write!(encoded, "%{byte:02X}").expect("writing to a String never fails");
+        }
+    }
+    encoded
+}
+
+/// An entity tag, as used in the `ETag` header.
+///
+/// Entity tags identify a specific version of a resource and are used by
+/// clients to make conditional requests. A strong entity tag means that two
+/// representations with the same tag are byte-for-byte identical, while a weak
+/// one (serialized with the `W/` prefix) only means they are semantically
+/// equivalent. Weak tags are a good choice for dynamically generated content
+/// that may vary in insignificant ways (e.g. whitespace or the order of JSON
+/// object keys).
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::ETag;
+///
+/// let etag = ETag::strong("v1");
+/// assert_eq!(etag.to_string(), "\"v1\"");
+///
+/// let etag = ETag::weak("v1");
+/// assert_eq!(etag.to_string(), "W/\"v1\"");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ETag {
+    tag: String,
+    weak: bool,
+}
+
+impl ETag {
+    /// Creates a new strong entity tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tag contains characters that are not allowed in an
+    /// entity tag (double quotes, whitespace or control characters).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// let etag = ETag::strong("v1");
+    /// assert!(!etag.is_weak());
+    /// ```
+    #[must_use]
+    pub fn strong(tag: impl Into<String>) -> Self {
+        Self::new(tag.into(), false)
+    }
+
+    /// Creates a new weak entity tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tag contains characters that are not allowed in an
+    /// entity tag (double quotes, whitespace or control characters).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// let etag = ETag::weak("v1");
+    /// assert!(etag.is_weak());
+    /// ```
+    #[must_use]
+    pub fn weak(tag: impl Into<String>) -> Self {
+        Self::new(tag.into(), true)
+    }
+
+    /// Creates a new strong entity tag by hashing the given content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// assert_eq!(ETag::from_content(b"hello"), ETag::from_content(b"hello"));
+    /// assert_ne!(ETag::from_content(b"hello"), ETag::from_content(b"world"));
+    /// ```
+    #[must_use]
+    pub fn from_content(content: &[u8]) -> Self {
+        use std::fmt::Write;
+
+        use sha2::Digest;
+
+        let hash = sha2::Sha256::digest(content);
+        let tag = hash[..16]
+            .iter()
+            .fold(String::with_capacity(32), |mut tag, byte| {
+                write!(tag, "{byte:02x}").expect("writing to a String should never fail");
+                tag
+            });
+
+        Self::new(tag, false)
+    }
+
+    fn new(tag: String, weak: bool) -> Self {
+        assert!(
+            tag.bytes()
+                .all(|byte| byte == 0x21 || (0x23..=0x7e).contains(&byte)),
+            "invalid entity tag: {tag:?}"
+        );
+
+        Self { tag, weak }
+    }
+
+    /// Returns a weak version of this entity tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// let etag = ETag::strong("v1").into_weak();
+    /// assert_eq!(etag, ETag::weak("v1"));
+    /// ```
+    #[must_use]
+    pub fn into_weak(self) -> Self {
+        Self { weak: true, ..self }
+    }
+
+    /// Returns whether this entity tag is weak.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// assert!(ETag::weak("v1").is_weak());
+    /// assert!(!ETag::strong("v1").is_weak());
+    /// ```
+    #[must_use]
+    pub fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// Returns the opaque tag, without the quotes and the weakness indicator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// assert_eq!(ETag::weak("v1").tag(), "v1");
+    /// ```
+    #[must_use]
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+}
+
+impl std::fmt::Display for ETag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.weak {
+            write!(f, "W/\"{}\"", self.tag)
+        } else {
+            write!(f, "\"{}\"", self.tag)
+        }
+    }
+}
+
+impl From<&ETag> for http::HeaderValue {
+    fn from(etag: &ETag) -> Self {
+        http::HeaderValue::try_from(etag.to_string())
+            .expect("entity tag should always be a valid header value")
+    }
+}
+
+impl From<ETag> for http::HeaderValue {
+    fn from(etag: ETag) -> Self {
+        Self::from(&etag)
     }
 }
 
@@ -177,10 +788,55 @@ pub(crate) fn not_found_response(message: Option<String>) -> Response {
     response
 }
 
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Applies the [`TextNormalizationConfig`] to the body of a text response.
+///
+/// Streaming bodies and the responses with a non-`text/*` content type are
+/// left untouched.
+pub(crate) fn normalize_text(response: &mut Response, config: &TextNormalizationConfig) {
+    if !config.strip_bom && !config.trailing_newline {
+        return;
+    }
+
+    let is_text = response
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .trim_start()
+                .get(.."text/".len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("text/"))
+        });
+    if !is_text {
+        return;
+    }
+    let BodyInner::Fixed(data) = &mut response.body_mut().inner else {
+        return;
+    };
+
+    let mut changed = false;
+    if config.strip_bom && data.starts_with(UTF8_BOM) {
+        *data = data.slice(UTF8_BOM.len()..);
+        changed = true;
+    }
+    if config.trailing_newline && !data.is_empty() && !data.ends_with(b"\n") {
+        let mut new_data = Vec::with_capacity(data.len() + 1);
+        new_data.extend_from_slice(data);
+        new_data.push(b'\n');
+        *data = Bytes::from(new_data);
+        changed = true;
+    }
+
+    if changed {
+        response.headers_mut().remove(http::header::CONTENT_LENGTH);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::body::BodyInner;
     use crate::headers::HTML_CONTENT_TYPE;
     use crate::response::{Response, ResponseExt};
 
@@ -222,6 +878,174 @@ mod tests {
         }
     }
 
+    #[test]
+    fn response_download() {
+        let response = Response::download(Body::fixed("a,b,c"), "report.csv");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+            "text/csv"
+        );
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CONTENT_DISPOSITION)
+                .unwrap(),
+            "attachment; filename=\"report.csv\"; filename*=UTF-8''report.csv"
+        );
+    }
+
+    #[test]
+    fn response_inline() {
+        let response = Response::inline(Body::empty(), "invoice.pdf");
+
+        assert_eq!(
+            response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+            "application/pdf"
+        );
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CONTENT_DISPOSITION)
+                .unwrap(),
+            "inline; filename=\"invoice.pdf\"; filename*=UTF-8''invoice.pdf"
+        );
+    }
+
+    #[test]
+    fn response_download_unknown_extension() {
+        let response = Response::download(Body::empty(), "data.unknown-ext");
+
+        assert_eq!(
+            response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn response_download_non_ascii_filename() {
+        let response = Response::download(Body::empty(), "zażółć gęślą.txt");
+
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CONTENT_DISPOSITION)
+                .unwrap(),
+            "attachment; filename=\"za____ g__l_.txt\"; \
+            filename*=UTF-8''za%C5%BC%C3%B3%C5%82%C4%87%20g%C4%99%C5%9Bl%C4%85.txt"
+        );
+    }
+
+    #[test]
+    fn response_download_sanitizes_filename() {
+        let response = Response::download(Body::empty(), "../evil\r\nSet-Cookie: a=b\".txt");
+
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CONTENT_DISPOSITION)
+                .unwrap(),
+            "attachment; filename=\".._evilSet-Cookie: a=b.txt\"; \
+            filename*=UTF-8''.._evilSet-Cookie%3A%20a%3Db.txt"
+        );
+    }
+
+    #[test]
+    fn response_download_empty_filename() {
+        let response = Response::download(Body::empty(), "\n");
+
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CONTENT_DISPOSITION)
+                .unwrap(),
+            "attachment; filename=\"download\"; filename*=UTF-8''download"
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn response_attachment_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, "a,b,c").unwrap();
+
+        let response = Response::attachment(path, "zpráva.csv").await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[http::header::CONTENT_TYPE], "text/csv");
+        assert_eq!(response.headers()[http::header::CONTENT_LENGTH], "5");
+        assert_eq!(
+            response.headers()[http::header::CONTENT_DISPOSITION],
+            "attachment; filename=\"zpr_va.csv\"; filename*=UTF-8''zpr%C3%A1va.csv"
+        );
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "a,b,c");
+    }
+
+    #[cot::test]
+    async fn response_attachment_body() {
+        let response = Response::attachment(Body::fixed("a,b,c"), "report.csv")
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[http::header::CONTENT_LENGTH], "5");
+
+        let stream = futures::stream::iter(["a,", "b,c"].map(|chunk| Ok(chunk.into())));
+        let response = Response::attachment(Body::streaming(stream), "report.csv")
+            .await
+            .unwrap();
+        assert!(
+            !response
+                .headers()
+                .contains_key(http::header::CONTENT_LENGTH)
+        );
+        assert_eq!(
+            response.headers()[http::header::CONTENT_DISPOSITION],
+            "attachment; filename=\"report.csv\"; filename*=UTF-8''report.csv"
+        );
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "a,b,c");
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn response_attachment_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let error = Response::attachment(dir.path().join("missing.csv"), "report.csv")
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.inner,
+            crate::error::ErrorRepr::NotFound { .. }
+        ));
+    }
+
+    #[test]
+    fn response_with_cookie() {
+        let response = Response::new_redirect("/")
+            .with_cookie(Cookie::new("theme", "dark"))
+            .with_cookie(Cookie::build("user name", "a;b").http_only(true));
+
+        let cookies: Vec<_> = response
+            .headers()
+            .get_all(http::header::SET_COOKIE)
+            .iter()
+            .collect();
+        assert_eq!(cookies, ["theme=dark", "user%20name=a%3Bb; HttpOnly"]);
+    }
+
+    #[test]
+    fn response_delete_cookie() {
+        let response = Response::new_redirect("/")
+            .delete_cookie(Cookie::build("theme", "dark").path("/admin"));
+
+        let header = response.headers()[http::header::SET_COOKIE]
+            .to_str()
+            .unwrap();
+        assert!(header.starts_with("theme=; Path=/admin; Max-Age=0; Expires="));
+    }
+
     #[test]
     fn response_new_redirect() {
         let location = "http://example.com";
@@ -232,4 +1056,125 @@ mod tests {
             location
         );
     }
+
+    #[test]
+    fn response_redirect_status_variants() {
+        let cases = [
+            (
+                Response::redirect_permanent("/a"),
+                StatusCode::PERMANENT_REDIRECT,
+            ),
+            (Response::redirect_see_other("/a"), StatusCode::SEE_OTHER),
+            (
+                Response::redirect_temporary("/a"),
+                StatusCode::TEMPORARY_REDIRECT,
+            ),
+        ];
+
+        for (response, status) in cases {
+            assert_eq!(response.status(), status);
+            assert_eq!(response.headers()[http::header::LOCATION], "/a");
+        }
+    }
+
+    #[test]
+    fn etag_display() {
+        assert_eq!(ETag::strong("abc").to_string(), "\"abc\"");
+        assert_eq!(ETag::weak("abc").to_string(), "W/\"abc\"");
+        assert_eq!(ETag::strong("").to_string(), "\"\"");
+    }
+
+    #[test]
+    fn etag_from_content() {
+        let etag = ETag::from_content(b"hello");
+
+        assert!(!etag.is_weak());
+        assert_eq!(etag.tag(), "2cf24dba5fb0a30e26e83b2ac5b9e29e");
+        assert!(etag.into_weak().is_weak());
+    }
+
+    #[test]
+    fn etag_header_value() {
+        let value = http::HeaderValue::from(ETag::weak("v1"));
+
+        assert_eq!(value, "W/\"v1\"");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid entity tag")]
+    fn etag_invalid() {
+        let _ = ETag::strong("a\"b");
+    }
+
+    fn text_response(content_type: &str, body: &'static [u8]) -> Response {
+        http::Response::builder()
+            .header(http::header::CONTENT_TYPE, content_type)
+            .body(Body::fixed(body))
+            .unwrap()
+    }
+
+    fn normalization(strip_bom: bool, trailing_newline: bool) -> TextNormalizationConfig {
+        TextNormalizationConfig::builder()
+            .strip_bom(strip_bom)
+            .trailing_newline(trailing_newline)
+            .build()
+    }
+
+    async fn body_bytes(response: Response) -> Bytes {
+        response.into_body().into_bytes().await.unwrap()
+    }
+
+    #[cot::test]
+    async fn normalize_text_strip_bom() {
+        let mut response = text_response("text/plain", b"\xEF\xBB\xBFhello");
+
+        normalize_text(&mut response, &normalization(true, false));
+
+        assert_eq!(body_bytes(response).await, "hello");
+    }
+
+    #[cot::test]
+    async fn normalize_text_trailing_newline() {
+        let mut response = text_response("text/plain; charset=utf-8", b"hello");
+
+        normalize_text(&mut response, &normalization(false, true));
+
+        assert_eq!(body_bytes(response).await, "hello\n");
+    }
+
+    #[cot::test]
+    async fn normalize_text_both() {
+        let mut response = text_response("text/plain", b"\xEF\xBB\xBFhello\n");
+
+        normalize_text(&mut response, &normalization(true, true));
+
+        assert_eq!(body_bytes(response).await, "hello\n");
+    }
+
+    #[cot::test]
+    async fn normalize_text_disabled_by_default() {
+        let mut response = text_response("text/plain", b"\xEF\xBB\xBFhello");
+
+        normalize_text(&mut response, &TextNormalizationConfig::default());
+
+        assert_eq!(body_bytes(response).await, &b"\xEF\xBB\xBFhello"[..]);
+    }
+
+    #[cot::test]
+    async fn normalize_text_skips_non_text() {
+        let mut response = text_response("application/octet-stream", b"\xEF\xBB\xBFhello");
+
+        normalize_text(&mut response, &normalization(true, true));
+
+        assert_eq!(body_bytes(response).await, &b"\xEF\xBB\xBFhello"[..]);
+    }
+
+    #[cot::test]
+    async fn normalize_text_skips_empty_body() {
+        let mut response = text_response("text/plain", b"");
+
+        normalize_text(&mut response, &normalization(false, true));
+
+        assert_eq!(body_bytes(response).await, "");
+    }
 }
@@ -0,0 +1,431 @@
+use std::io::SeekFrom;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use bytes::BytesMut;
+use chrono::{DateTime, Utc};
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt};
+
+use crate::error::ErrorRepr;
+use crate::middleware::is_range_applicable;
+use crate::response::{ETag, Response, ResponseExt};
+use crate::{Body, Error};
+
+/// The size of the chunks the files are read in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Builds a response streaming the file at `path`, honoring the `Range` and
+/// `If-Range` headers of the request.
+pub(super) async fn serve_file(path: &Path, request: &HeaderMap) -> crate::Result<Response> {
+    let (mut file, metadata) = open_file(path).await?;
+
+    let length = metadata.len();
+    let mut builder = Response::builder()
+        .header(
+            header::CONTENT_TYPE,
+            mime_guess::from_path(path)
+                .first_or_octet_stream()
+                .to_string(),
+        )
+        .header(header::ACCEPT_RANGES, "bytes");
+    if let Ok(modified) = metadata.modified() {
+        builder = builder
+            .header(header::ETAG, file_etag(length, modified))
+            .header(header::LAST_MODIFIED, http_date(modified));
+    }
+
+    let range = request
+        .get(header::RANGE)
+        .filter(|_| {
+            builder
+                .headers_ref()
+                .is_some_and(|headers| is_range_applicable(request, headers))
+        })
+        .map_or(ByteRange::Full, |range| ByteRange::parse(range, length));
+    let response = match range {
+        ByteRange::Full => builder
+            .header(header::CONTENT_LENGTH, length)
+            .body(reader_body(file, length, path.to_owned())),
+        ByteRange::Partial(range) => {
+            file.seek(SeekFrom::Start(range.start))
+                .await
+                .map_err(|source| read_file_error(path, source))?;
+            let range_length = range.end - range.start;
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, content_range(&range, length))
+                .header(header::CONTENT_LENGTH, range_length)
+                .body(reader_body(file, range_length, path.to_owned()))
+        }
+        ByteRange::Unsatisfiable => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{length}"))
+            .body(Body::empty()),
+    };
+
+    Ok(response.expect("failed to build file response"))
+}
+
+/// The source of the content of an attachment response.
+///
+/// This is the type accepted by [`ResponseExt::attachment`], which is usually
+/// created implicitly from a path to a file, or from a [`Body`] (such as one
+/// created with [`Body::streaming`]) for content that doesn't come from a file.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+///
+/// use cot::Body;
+/// use cot::response::AttachmentSource;
+///
+/// let source = AttachmentSource::from(Path::new("reports/2024.csv"));
+/// let source = AttachmentSource::from(Body::fixed("a,b,c"));
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AttachmentSource {
+    /// A file on disk, which is read in chunks when the response is sent.
+    Path(PathBuf),
+    /// A body to send as-is.
+    Body(Body),
+}
+
+impl From<PathBuf> for AttachmentSource {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&Path> for AttachmentSource {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_owned())
+    }
+}
+
+impl From<Body> for AttachmentSource {
+    fn from(body: Body) -> Self {
+        Self::Body(body)
+    }
+}
+
+impl AttachmentSource {
+    /// Turns the source into a body, along with its length if known.
+    pub(super) async fn into_body(self) -> crate::Result<(Body, Option<u64>)> {
+        match self {
+            Self::Path(path) => {
+                let (file, metadata) = open_file(&path).await?;
+                let length = metadata.len();
+                Ok((reader_body(file, length, path), Some(length)))
+            }
+            Self::Body(body) => {
+                let length = http_body::Body::size_hint(&body).exact();
+                Ok((body, length))
+            }
+        }
+    }
+}
+
+/// Opens a regular file to be served.
+async fn open_file(path: &Path) -> crate::Result<(tokio::fs::File, std::fs::Metadata)> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|source| read_file_error(path, source))?;
+    let metadata = file
+        .metadata()
+        .await
+        .map_err(|source| read_file_error(path, source))?;
+    if !metadata.is_file() {
+        return Err(Error::not_found());
+    }
+
+    Ok((file, metadata))
+}
+
+/// Returns a body streaming at most `length` bytes from `reader`, in chunks,
+/// without reading everything into memory.
+fn reader_body<R>(reader: R, length: u64, path: PathBuf) -> Body
+where
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    let stream = futures_util::stream::try_unfold(
+        (reader.take(length), path),
+        |(mut reader, path)| async move {
+            let mut buffer = BytesMut::with_capacity(CHUNK_SIZE);
+            let read = reader
+                .read_buf(&mut buffer)
+                .await
+                .map_err(|source| read_file_error(&path, source))?;
+
+            Ok((read != 0).then(|| (buffer.freeze(), (reader, path))))
+        },
+    );
+
+    Body::streaming(stream)
+}
+
+fn read_file_error(path: &Path, source: std::io::Error) -> Error {
+    if source.kind() == std::io::ErrorKind::NotFound {
+        Error::not_found()
+    } else {
+        Error::new(ErrorRepr::ReadFile {
+            path: path.to_owned(),
+            source,
+        })
+    }
+}
+
+/// Computes an entity tag from the size and the modification time of a
+/// file, so that the file doesn't have to be read to compute it.
+fn file_etag(length: u64, modified: SystemTime) -> ETag {
+    let modified = modified
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+
+    ETag::strong(format!(
+        "{length:x}-{:x}.{:x}",
+        modified.as_secs(),
+        modified.subsec_nanos()
+    ))
+}
+
+/// Formats the time as an HTTP date, as defined in
+/// [RFC 9110](https://datatracker.ietf.org/doc/html/rfc9110#section-5.6.7).
+fn http_date(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// The result of parsing a `Range` request header against a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ByteRange {
+    /// The whole resource should be served, either because there was no
+    /// (valid) `Range` header, or because it requested multiple ranges,
+    /// which are not supported.
+    Full,
+    /// A single range of the resource should be served.
+    Partial(Range<u64>),
+    /// The requested range doesn't overlap with the resource.
+    Unsatisfiable,
+}
+
+impl ByteRange {
+    /// Parses a `Range` header for a resource of `length` bytes.
+    pub(crate) fn parse(value: &HeaderValue, length: u64) -> Self {
+        let Some(spec) = value
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().strip_prefix("bytes="))
+        else {
+            return Self::Full;
+        };
+        if spec.contains(',') {
+            return Self::Full;
+        }
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return Self::Full;
+        };
+
+        let (start, end) = match (start.trim(), end.trim()) {
+            ("", "") => return Self::Full,
+            ("", suffix) => {
+                let Ok(suffix) = suffix.parse::<u64>() else {
+                    return Self::Full;
+                };
+                if suffix == 0 || length == 0 {
+                    return Self::Unsatisfiable;
+                }
+                (length.saturating_sub(suffix), length)
+            }
+            (start, end) => {
+                let Ok(start) = start.parse::<u64>() else {
+                    return Self::Full;
+                };
+                let end = if end.is_empty() {
+                    length
+                } else {
+                    match end.parse::<u64>() {
+                        Ok(end) if end >= start => end.saturating_add(1).min(length),
+                        _ => return Self::Full,
+                    }
+                };
+                if start >= length {
+                    return Self::Unsatisfiable;
+                }
+                (start, end)
+            }
+        };
+
+        Self::Partial(start..end)
+    }
+}
+
+/// Formats the value of the `Content-Range` header of a partial response
+/// containing `range` of a resource of `length` bytes.
+pub(crate) fn content_range(range: &Range<u64>, length: u64) -> String {
+    format!("bytes {}-{}/{length}", range.start, range.end - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn request(headers: &[(header::HeaderName, &str)]) -> HeaderMap {
+        headers
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::try_from(*value).unwrap()))
+            .collect()
+    }
+
+    fn test_file(content: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("video.mp4");
+        std::fs::write(&path, content).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn byte_range_parse() {
+        let parse = |value: &'static str| ByteRange::parse(&HeaderValue::from_static(value), 10);
+
+        assert_eq!(parse("bytes=0-4"), ByteRange::Partial(0..5));
+        assert_eq!(parse("bytes=5-"), ByteRange::Partial(5..10));
+        assert_eq!(parse("bytes=5-100"), ByteRange::Partial(5..10));
+        assert_eq!(parse("bytes=-3"), ByteRange::Partial(7..10));
+        assert_eq!(parse("bytes=-100"), ByteRange::Partial(0..10));
+        assert_eq!(parse("bytes=10-"), ByteRange::Unsatisfiable);
+        assert_eq!(parse("bytes=-0"), ByteRange::Unsatisfiable);
+        assert_eq!(parse("bytes=4-2"), ByteRange::Full);
+        assert_eq!(parse("bytes=0-1,3-4"), ByteRange::Full);
+        assert_eq!(parse("items=0-4"), ByteRange::Full);
+        assert_eq!(parse("bytes=a-b"), ByteRange::Full);
+    }
+
+    #[test]
+    fn http_date_format() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_445_412_480);
+
+        assert_eq!(http_date(time), "Wed, 21 Oct 2015 07:28:00 GMT");
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn file_response_full() {
+        let (_dir, path) = test_file("0123456789");
+
+        let response = serve_file(&path, &HeaderMap::new()).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "video/mp4");
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], "10");
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+        assert!(response.headers().contains_key(header::ETAG));
+        assert!(response.headers().contains_key(header::LAST_MODIFIED));
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "0123456789"
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn file_response_large_file_in_chunks() {
+        let content = "x".repeat(CHUNK_SIZE * 2 + 1);
+        let (_dir, path) = test_file(&content);
+
+        let response = serve_file(&path, &HeaderMap::new()).await.unwrap();
+
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            content.as_bytes()
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn file_response_range() {
+        let (_dir, path) = test_file("0123456789");
+
+        let response = serve_file(&path, &request(&[(header::RANGE, "bytes=2-5")]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], "4");
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "2345");
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn file_response_range_not_satisfiable() {
+        let (_dir, path) = test_file("0123456789");
+
+        let response = serve_file(&path, &request(&[(header::RANGE, "bytes=20-")]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */10");
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn file_response_if_range() {
+        let (_dir, path) = test_file("0123456789");
+        let response = serve_file(&path, &HeaderMap::new()).await.unwrap();
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let last_modified = response.headers()[header::LAST_MODIFIED]
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        for if_range in [etag.as_str(), last_modified.as_str()] {
+            let response = serve_file(
+                &path,
+                &request(&[(header::RANGE, "bytes=2-5"), (header::IF_RANGE, if_range)]),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        }
+
+        let response = serve_file(
+            &path,
+            &request(&[
+                (header::RANGE, "bytes=2-5"),
+                (header::IF_RANGE, "\"outdated\""),
+            ]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "0123456789"
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `open`
+    async fn file_response_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let error = serve_file(&dir.path().join("missing.txt"), &HeaderMap::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(error.inner, ErrorRepr::NotFound { .. }));
+
+        let error = serve_file(dir.path(), &HeaderMap::new()).await.unwrap_err();
+        assert!(matches!(error.inner, ErrorRepr::NotFound { .. }));
+    }
+}
@@ -0,0 +1,423 @@
+use std::fmt::Write;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::StreamExt;
+use http::{HeaderMap, HeaderName, HeaderValue, header};
+use http_body_util::BodyExt;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::Body;
+use crate::response::Response;
+
+const MAX_BOUNDARY_LENGTH: usize = 70;
+
+/// A builder for `multipart/*` responses.
+///
+/// The parts are serialized lazily when the response body is sent, so the
+/// parts with streaming bodies are not buffered in memory.
+///
+/// # Examples
+///
+/// ```
+/// use cot::Body;
+/// use cot::http::{HeaderValue, header};
+/// use cot::response::{MultipartPart, MultipartResponse, Response};
+///
+/// async fn batch() -> Response {
+///     MultipartResponse::mixed()
+///         .part(MultipartPart::new(Body::fixed(r#"{"id": 1}"#)).header(
+///             header::CONTENT_TYPE,
+///             HeaderValue::from_static("application/json"),
+///         ))
+///         .part(MultipartPart::new(Body::fixed(r#"{"id": 2}"#)).header(
+///             header::CONTENT_TYPE,
+///             HeaderValue::from_static("application/json"),
+///         ))
+///         .into_response()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MultipartResponse {
+    subtype: &'static str,
+    boundary: String,
+    parts: Vec<MultipartPart>,
+}
+
+impl MultipartResponse {
+    /// Creates a new `multipart/mixed` response builder with a randomly
+    /// generated boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::MultipartResponse;
+    ///
+    /// let response = MultipartResponse::mixed().into_response();
+    /// ```
+    #[must_use]
+    pub fn mixed() -> Self {
+        Self::new("mixed")
+    }
+
+    /// Creates a new `multipart/form-data` response builder with a randomly
+    /// generated boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::response::{MultipartPart, MultipartResponse};
+    ///
+    /// let response = MultipartResponse::form_data()
+    ///     .part(MultipartPart::form_field("name", Body::fixed("John")))
+    ///     .into_response();
+    /// ```
+    #[must_use]
+    pub fn form_data() -> Self {
+        Self::new("form-data")
+    }
+
+    fn new(subtype: &'static str) -> Self {
+        Self {
+            subtype,
+            boundary: generate_boundary(),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Sets the boundary delimiting the parts.
+    ///
+    /// The boundary is generated randomly by default, so this is mostly
+    /// useful for testing. The boundary must not appear in any of the parts.
+    /// It is quoted in the `Content-Type` header if it contains characters
+    /// such as spaces or parentheses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the boundary is empty, is longer than 70 characters, or
+    /// contains characters not allowed by
+    /// [RFC 2046](https://datatracker.ietf.org/doc/html/rfc2046#section-5.1.1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::MultipartResponse;
+    ///
+    /// let response = MultipartResponse::mixed()
+    ///     .boundary("my-boundary")
+    ///     .into_response();
+    /// ```
+    #[must_use]
+    pub fn boundary<T: Into<String>>(self, boundary: T) -> Self {
+        let boundary = boundary.into();
+        assert!(
+            is_valid_boundary(&boundary),
+            "invalid multipart boundary: {boundary:?}"
+        );
+
+        Self { boundary, ..self }
+    }
+
+    /// Adds a part to the response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::response::{MultipartPart, MultipartResponse};
+    ///
+    /// let response = MultipartResponse::mixed()
+    ///     .part(MultipartPart::new(Body::fixed("Hello")))
+    ///     .into_response();
+    /// ```
+    #[must_use]
+    pub fn part(mut self, part: MultipartPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// Builds a `200 OK` response with the parts and the `Content-Type`
+    /// header containing the boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::MultipartResponse;
+    ///
+    /// let response = MultipartResponse::mixed()
+    ///     .boundary("my-boundary")
+    ///     .into_response();
+    /// assert_eq!(
+    ///     response.headers()["content-type"],
+    ///     "multipart/mixed; boundary=my-boundary"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn into_response(self) -> Response {
+        let content_type = self.content_type();
+        let closing_delimiter = Bytes::from(format!("--{}--\r\n", self.boundary));
+        let boundary = self.boundary;
+
+        let parts = futures_util::stream::iter(self.parts).flat_map(move |part| {
+            let head = part.head(&boundary);
+            futures_util::stream::once(async { Ok(head) })
+                .chain(part.body.into_data_stream())
+                .chain(futures_util::stream::once(async {
+                    Ok(Bytes::from_static(b"\r\n"))
+                }))
+        });
+        let body = parts.chain(futures_util::stream::once(async { Ok(closing_delimiter) }));
+
+        let mut response = Response::new(Body::streaming(body));
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, content_type);
+        response
+    }
+
+    fn content_type(&self) -> HeaderValue {
+        // some of the characters allowed in the boundary are not allowed in
+        // the parameter values unless they are quoted
+        let content_type = if self.boundary.bytes().all(is_token_byte) {
+            format!("multipart/{}; boundary={}", self.subtype, self.boundary)
+        } else {
+            format!("multipart/{}; boundary=\"{}\"", self.subtype, self.boundary)
+        };
+        HeaderValue::try_from(content_type).expect("the boundary is validated")
+    }
+}
+
+/// A single part of a [`MultipartResponse`].
+#[derive(Debug)]
+pub struct MultipartPart {
+    headers: HeaderMap,
+    body: Body,
+}
+
+impl MultipartPart {
+    /// Creates a new part with the given body and no headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::response::MultipartPart;
+    ///
+    /// let part = MultipartPart::new(Body::fixed("Hello"));
+    /// ```
+    #[must_use]
+    pub fn new(body: Body) -> Self {
+        Self {
+            headers: HeaderMap::new(),
+            body,
+        }
+    }
+
+    /// Creates a new `multipart/form-data` field with the given name.
+    ///
+    /// This sets the `Content-Disposition` header of the part to
+    /// `form-data; name="<name>"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::response::MultipartPart;
+    ///
+    /// let part = MultipartPart::form_field("name", Body::fixed("John"));
+    /// ```
+    #[must_use]
+    pub fn form_field(name: &str, body: Body) -> Self {
+        Self::new(body).header(header::CONTENT_DISPOSITION, form_data_disposition(name))
+    }
+
+    /// Adds a header to the part.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::http::{HeaderValue, header};
+    /// use cot::response::MultipartPart;
+    ///
+    /// let part = MultipartPart::new(Body::fixed("Hello"))
+    ///     .header(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+    /// ```
+    #[must_use]
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Serializes the delimiter and the headers of the part.
+    fn head(&self, boundary: &str) -> Bytes {
+        let mut head = BytesMut::new();
+        head.put_slice(b"--");
+        head.put_slice(boundary.as_bytes());
+        head.put_slice(b"\r\n");
+        for (name, value) in &self.headers {
+            head.put_slice(name.as_str().as_bytes());
+            head.put_slice(b": ");
+            head.put_slice(value.as_bytes());
+            head.put_slice(b"\r\n");
+        }
+        head.put_slice(b"\r\n");
+        head.freeze()
+    }
+}
+
+fn generate_boundary() -> String {
+    let mut rng = StdRng::from_os_rng();
+    let mut boundary = String::with_capacity(32);
+    for _ in 0..2 {
+        write!(boundary, "{:016x}", rng.next_u64()).expect("writing to a String never fails");
+    }
+    boundary
+}
+
+fn is_valid_boundary(boundary: &str) -> bool {
+    (1..=MAX_BOUNDARY_LENGTH).contains(&boundary.len())
+        && !boundary.ends_with(' ')
+        && boundary
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&byte))
+}
+
+/// Returns whether the byte is allowed in an unquoted parameter value, as
+/// defined by [RFC 2045](https://datatracker.ietf.org/doc/html/rfc2045#section-5.1).
+fn is_token_byte(byte: u8) -> bool {
+    byte.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?=".contains(&byte)
+}
+
+/// Builds the `Content-Disposition` header of a form field, escaping the
+/// field name the way browsers do.
+fn form_data_disposition(name: &str) -> HeaderValue {
+    let name = name
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A");
+    HeaderValue::try_from(format!("form-data; name=\"{name}\"")).expect("the field name is escaped")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StatusCode;
+
+    #[cot::test]
+    async fn multipart_response_structure() {
+        let stream =
+            futures::stream::iter(["Hello, ", "world!"].map(|chunk| Ok(Bytes::from(chunk))));
+        let response = MultipartResponse::mixed()
+            .boundary("test-boundary")
+            .part(
+                MultipartPart::new(Body::fixed("first"))
+                    .header(header::CONTENT_TYPE, HeaderValue::from_static("text/plain")),
+            )
+            .part(MultipartPart::new(Body::streaming(stream)))
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "multipart/mixed; boundary=test-boundary"
+        );
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "--test-boundary\r\n\
+             content-type: text/plain\r\n\
+             \r\n\
+             first\r\n\
+             --test-boundary\r\n\
+             \r\n\
+             Hello, world!\r\n\
+             --test-boundary--\r\n"
+        );
+    }
+
+    #[cot::test]
+    async fn multipart_response_form_data() {
+        let response = MultipartResponse::form_data()
+            .boundary("b")
+            .part(MultipartPart::form_field("na\"me", Body::fixed("John")))
+            .into_response();
+
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "multipart/form-data; boundary=b"
+        );
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "--b\r\n\
+             content-disposition: form-data; name=\"na%22me\"\r\n\
+             \r\n\
+             John\r\n\
+             --b--\r\n"
+        );
+    }
+
+    #[cot::test]
+    async fn multipart_response_empty() {
+        let response = MultipartResponse::mixed().boundary("b").into_response();
+
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "--b--\r\n"
+        );
+    }
+
+    #[cot::test]
+    async fn multipart_response_generated_boundary() {
+        let response = MultipartResponse::mixed().into_response();
+
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        let boundary = content_type
+            .strip_prefix("multipart/mixed; boundary=")
+            .unwrap()
+            .to_owned();
+        assert!(is_valid_boundary(&boundary));
+        assert_ne!(boundary, generate_boundary());
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            format!("--{boundary}--\r\n")
+        );
+    }
+
+    #[test]
+    fn valid_boundaries() {
+        assert!(is_valid_boundary("simple"));
+        assert!(is_valid_boundary("with spaces and (parens)"));
+        assert!(!is_valid_boundary(""));
+        assert!(!is_valid_boundary("trailing space "));
+        assert!(!is_valid_boundary("semi;colon"));
+        assert!(!is_valid_boundary(&"a".repeat(71)));
+    }
+
+    #[test]
+    fn multipart_response_quoted_boundary() {
+        let response = MultipartResponse::mixed()
+            .boundary("with spaces and (parens)")
+            .into_response();
+
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "multipart/mixed; boundary=\"with spaces and (parens)\""
+        );
+
+        let response = MultipartResponse::mixed()
+            .boundary("a=b/c?d")
+            .into_response();
+
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "multipart/mixed; boundary=\"a=b/c?d\""
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid multipart boundary")]
+    fn multipart_response_invalid_boundary() {
+        let _ = MultipartResponse::mixed().boundary("new\r\nline");
+    }
+}
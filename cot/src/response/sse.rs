@@ -0,0 +1,625 @@
+use std::fmt::{Debug, Formatter, Write};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use http::{HeaderValue, header};
+
+use crate::Body;
+use crate::response::Response;
+
+const EVENT_STREAM_CONTENT_TYPE: &str = "text/event-stream";
+const DEFAULT_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html)
+/// response.
+///
+/// The events are sent to the client as they are produced by the stream, and
+/// the response ends when the stream ends. To keep the connection from being
+/// closed by proxies while there are no events to send, a comment is sent
+/// after every 15 seconds without events (see [`Self::keep_alive`]).
+///
+/// On the client side, the events can be received with the
+/// [`EventSource`](https://developer.mozilla.org/en-US/docs/Web/API/EventSource)
+/// API.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::response::{Event, Response, Sse};
+/// use futures::StreamExt;
+///
+/// async fn ticks() -> Response {
+///     let stream = futures::stream::iter(1..=3).then(|i| async move {
+///         tokio::time::sleep(Duration::from_secs(1)).await;
+///         Event::new().event("tick").data(i.to_string())
+///     });
+///
+///     Sse::new(stream).into_response()
+/// }
+/// ```
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl<S> Debug for Sse<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sse")
+            .field("keep_alive", &self.keep_alive)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Sse<S>
+where
+    S: Stream<Item = Event> + Send + 'static,
+{
+    /// Creates a new Server-Sent Events response sending the events from the
+    /// given stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::{Event, Sse};
+    ///
+    /// let stream = futures::stream::iter([Event::new().data("Hello")]);
+    /// let response = Sse::new(stream).into_response();
+    /// ```
+    #[must_use]
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            keep_alive: Some(KeepAlive::new()),
+        }
+    }
+
+    /// Sets how the keep-alive comments are sent.
+    ///
+    /// By default, an empty comment is sent after every 15 seconds without
+    /// events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::response::{Event, KeepAlive, Sse};
+    ///
+    /// let stream = futures::stream::iter([Event::new().data("Hello")]);
+    /// let response = Sse::new(stream)
+    ///     .keep_alive(KeepAlive::new().interval(Duration::from_secs(5)))
+    ///     .into_response();
+    /// ```
+    #[must_use]
+    pub fn keep_alive(self, keep_alive: KeepAlive) -> Self {
+        Self {
+            keep_alive: Some(keep_alive),
+            ..self
+        }
+    }
+
+    /// Disables the keep-alive comments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::{Event, Sse};
+    ///
+    /// let stream = futures::stream::iter([Event::new().data("Hello")]);
+    /// let response = Sse::new(stream).without_keep_alive().into_response();
+    /// ```
+    #[must_use]
+    pub fn without_keep_alive(self) -> Self {
+        Self {
+            keep_alive: None,
+            ..self
+        }
+    }
+
+    /// Builds a `200 OK` response with the `text/event-stream` content type
+    /// and a streaming body sending the events.
+    ///
+    /// Caching of the response is disabled with the `Cache-Control` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::{Event, Sse};
+    ///
+    /// let stream = futures::stream::iter([Event::new().data("Hello")]);
+    /// let response = Sse::new(stream).into_response();
+    /// assert_eq!(response.headers()["content-type"], "text/event-stream");
+    /// ```
+    #[must_use]
+    pub fn into_response(self) -> Response {
+        let keep_alive = self.keep_alive.map(|keep_alive| {
+            let comment = Event::new().comment(keep_alive.text).to_bytes();
+            (keep_alive.interval, comment)
+        });
+
+        let body = futures_util::stream::unfold(
+            (Box::pin(self.stream), keep_alive),
+            |(mut stream, keep_alive)| async move {
+                let chunk = if let Some((interval, comment)) = &keep_alive {
+                    match tokio::time::timeout(*interval, stream.next()).await {
+                        Ok(event) => event?.to_bytes(),
+                        Err(_) => comment.clone(),
+                    }
+                } else {
+                    stream.next().await?.to_bytes()
+                };
+                Some((Ok(chunk), (stream, keep_alive)))
+            },
+        );
+
+        let mut response = Response::new(Body::streaming(body));
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(EVENT_STREAM_CONTENT_TYPE),
+        );
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        response
+    }
+}
+
+/// A single event of an [`Sse`] response.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::response::Event;
+///
+/// let event = Event::new()
+///     .id("42")
+///     .event("message")
+///     .data("Hello, world!")
+///     .retry(Duration::from_secs(10));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    id: Option<String>,
+    #[expect(
+        clippy::struct_field_names,
+        reason = "named after the `event` field of the SSE protocol"
+    )]
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// Creates a new, empty event.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Event;
+    ///
+    /// let event = Event::new().data("Hello");
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the data of the event.
+    ///
+    /// The data can contain newlines; it's then sent in multiple `data`
+    /// lines, and the client receives it joined back with `\n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Event;
+    ///
+    /// let event = Event::new().data("first line\nsecond line");
+    /// ```
+    #[must_use]
+    pub fn data<T: Into<String>>(self, data: T) -> Self {
+        Self {
+            data: Some(data.into()),
+            ..self
+        }
+    }
+
+    /// Sets the data of the event to the given value serialized as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the value cannot be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Event;
+    ///
+    /// #[derive(serde::Serialize)]
+    /// struct Progress {
+    ///     done: u32,
+    ///     total: u32,
+    /// }
+    ///
+    /// # fn main() -> cot::Result<()> {
+    /// let event = Event::new().json_data(&Progress { done: 1, total: 3 })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn json_data<T: ?Sized + serde::Serialize>(self, data: &T) -> crate::Result<Self> {
+        let value = serde_path_to_error::serialize(data, serde_json::value::Serializer)
+            .map_err(|error| crate::Error::new(crate::error::ErrorRepr::Json(error)))?;
+
+        Ok(self.data(value.to_string()))
+    }
+
+    /// Sets the type of the event.
+    ///
+    /// The client can listen for the events of a given type with
+    /// `EventSource.addEventListener()`. Events without a type have the
+    /// `message` type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type contains a newline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Event;
+    ///
+    /// let event = Event::new().event("user-joined").data("Alice");
+    /// ```
+    #[must_use]
+    pub fn event<T: Into<String>>(self, event: T) -> Self {
+        let event = event.into();
+        assert!(
+            !contains_newline(&event),
+            "SSE event type must not contain newlines: {event:?}"
+        );
+
+        Self {
+            event: Some(event),
+            ..self
+        }
+    }
+
+    /// Sets the ID of the event.
+    ///
+    /// When reconnecting, the client sends the ID of the last event it
+    /// received in the `Last-Event-ID` request header, so the server can
+    /// resume the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID contains a newline or a null character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Event;
+    ///
+    /// let event = Event::new().id("42").data("Hello");
+    /// ```
+    #[must_use]
+    pub fn id<T: Into<String>>(self, id: T) -> Self {
+        let id = id.into();
+        assert!(
+            !contains_newline(&id) && !id.contains('\0'),
+            "SSE event ID must not contain newlines or null characters: {id:?}"
+        );
+
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    /// Sets the time the client should wait before reconnecting after the
+    /// connection is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::response::Event;
+    ///
+    /// let event = Event::new().retry(Duration::from_secs(10));
+    /// ```
+    #[must_use]
+    pub fn retry(self, retry: Duration) -> Self {
+        Self {
+            retry: Some(retry),
+            ..self
+        }
+    }
+
+    /// Sets a comment sent with the event. Comments are ignored by the
+    /// client.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Event;
+    ///
+    /// let event = Event::new().comment("debug information");
+    /// ```
+    #[must_use]
+    pub fn comment<T: Into<String>>(self, comment: T) -> Self {
+        Self {
+            comment: Some(comment.into()),
+            ..self
+        }
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        let mut buf = String::new();
+        if let Some(comment) = &self.comment {
+            write_lines(&mut buf, "", comment);
+        }
+        if let Some(event) = &self.event {
+            write_field(&mut buf, "event", event);
+        }
+        if let Some(id) = &self.id {
+            write_field(&mut buf, "id", id);
+        }
+        if let Some(retry) = self.retry {
+            write_field(&mut buf, "retry", &retry.as_millis().to_string());
+        }
+        if let Some(data) = &self.data {
+            write_lines(&mut buf, "data", data);
+        }
+        buf.push('\n');
+
+        Bytes::from(buf)
+    }
+}
+
+/// Configuration of the keep-alive comments of an [`Sse`] response.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::response::KeepAlive;
+///
+/// let keep_alive = KeepAlive::new()
+///     .interval(Duration::from_secs(30))
+///     .text("keep-alive");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeepAlive {
+    interval: Duration,
+    text: String,
+}
+
+impl KeepAlive {
+    /// Creates a new keep-alive configuration sending an empty comment after
+    /// every 15 seconds without events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::KeepAlive;
+    ///
+    /// let keep_alive = KeepAlive::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            interval: DEFAULT_KEEP_ALIVE_INTERVAL,
+            text: String::new(),
+        }
+    }
+
+    /// Sets the time without events after which a keep-alive comment is sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interval is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::response::KeepAlive;
+    ///
+    /// let keep_alive = KeepAlive::new().interval(Duration::from_secs(30));
+    /// ```
+    #[must_use]
+    pub fn interval(self, interval: Duration) -> Self {
+        assert!(
+            !interval.is_zero(),
+            "SSE keep-alive interval must not be zero"
+        );
+
+        Self { interval, ..self }
+    }
+
+    /// Sets the text of the keep-alive comments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::KeepAlive;
+    ///
+    /// let keep_alive = KeepAlive::new().text("ping");
+    /// ```
+    #[must_use]
+    pub fn text<T: Into<String>>(self, text: T) -> Self {
+        Self {
+            text: text.into(),
+            ..self
+        }
+    }
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn contains_newline(value: &str) -> bool {
+    value.contains(['\r', '\n'])
+}
+
+fn write_field(buf: &mut String, name: &str, value: &str) {
+    if value.is_empty() {
+        writeln!(buf, "{name}:")
+    } else {
+        writeln!(buf, "{name}: {value}")
+    }
+    .expect("writing to a String never fails");
+}
+
+/// Writes a possibly multi-line value as a sequence of fields with the same
+/// name. `\r\n`, `\r` and `\n` are all treated as line breaks, as the client
+/// does.
+fn write_lines(buf: &mut String, name: &str, value: &str) {
+    let value = value.replace("\r\n", "\n").replace('\r', "\n");
+    for line in value.split('\n') {
+        write_field(buf, name, line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+    use crate::StatusCode;
+
+    #[test]
+    fn event_data() {
+        let event = Event::new().data("Hello");
+
+        assert_eq!(event.to_bytes(), "data: Hello\n\n");
+    }
+
+    #[test]
+    fn event_all_fields() {
+        let event = Event::new()
+            .comment("note")
+            .event("update")
+            .id("42")
+            .retry(Duration::from_millis(1500))
+            .data("first\nsecond\r\nthird\rfourth");
+
+        assert_eq!(
+            event.to_bytes(),
+            ": note\n\
+             event: update\n\
+             id: 42\n\
+             retry: 1500\n\
+             data: first\n\
+             data: second\n\
+             data: third\n\
+             data: fourth\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn event_empty_values() {
+        assert_eq!(Event::new().data("").to_bytes(), "data:\n\n");
+        assert_eq!(Event::new().comment("").to_bytes(), ":\n\n");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn event_json_data() {
+        let event = Event::new()
+            .json_data(&serde_json::json!({"a": 1}))
+            .unwrap();
+
+        assert_eq!(event.to_bytes(), "data: {\"a\":1}\n\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "SSE event type must not contain newlines")]
+    fn event_type_with_newline() {
+        let _ = Event::new().event("a\nb");
+    }
+
+    #[test]
+    #[should_panic(expected = "SSE event ID must not contain newlines")]
+    fn event_id_with_null() {
+        let _ = Event::new().id("a\0b");
+    }
+
+    #[cot::test]
+    async fn sse_response() {
+        let events = stream::iter([
+            Event::new().data("first"),
+            Event::new().event("second").data("2"),
+        ]);
+
+        let response = Sse::new(events).into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            EVENT_STREAM_CONTENT_TYPE
+        );
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "data: first\n\nevent: second\ndata: 2\n\n"
+        );
+    }
+
+    #[cot::test]
+    async fn sse_keep_alive() {
+        let events = stream::once(async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Event::new().data("done")
+        });
+
+        let response = Sse::new(events)
+            .keep_alive(
+                KeepAlive::new()
+                    .interval(Duration::from_millis(40))
+                    .text("ping"),
+            )
+            .into_response();
+        let body = response.into_body().into_bytes().await.unwrap();
+
+        assert!(body.starts_with(b": ping\n\n"));
+        assert!(body.ends_with(b"data: done\n\n"));
+    }
+
+    #[cot::test]
+    async fn sse_without_keep_alive() {
+        let events = stream::once(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Event::new().data("done")
+        });
+
+        let response = Sse::new(events)
+            .keep_alive(KeepAlive::new().interval(Duration::from_millis(10)))
+            .without_keep_alive()
+            .into_response();
+
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "data: done\n\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "SSE keep-alive interval must not be zero")]
+    fn keep_alive_zero_interval() {
+        let _ = KeepAlive::new().interval(Duration::ZERO);
+    }
+}
@@ -27,17 +27,58 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use bytes::Bytes;
+/// Derive the [`RouteRef`] trait for a struct.
+///
+/// The struct describes a named route: its path pattern and name are given in
+/// the `#[route(path = "...", name = "...")]` attribute (with an optional `app
+/// = "..."` to refer to a route of a different app), and its fields are the
+/// parameters of the path. The macro checks at compile time that every
+/// parameter of the path has a corresponding field and vice versa. The fields
+/// must implement [`Display`](std::fmt::Display), which is used to fill in the
+/// parameters when reversing the route.
+///
+/// # Examples
+///
+/// ```
+/// use cot::router::RouteRef;
+///
+/// #[derive(RouteRef)]
+/// #[route(path = "/posts/{id:i64}/{slug}", name = "post")]
+/// struct PostRoute {
+///     id: i64,
+///     slug: String,
+/// }
+///
+/// #[derive(RouteRef)]
+/// #[route(path = "/", name = "home")]
+/// struct HomeRoute;
+/// ```
+pub use cot_macros::RouteRef;
 use derive_more::with_trait::Debug;
+use http::Method;
 use http::request::Parts;
+use tower::util::BoxCloneSyncService;
+use tower::{Layer, Service, ServiceExt};
 use tracing::debug;
 
+use crate::config::TrailingSlash;
 use crate::error::ErrorRepr;
 use crate::handler::{BoxRequestHandler, RequestHandler, into_box_request_handler};
+use crate::middleware::{
+    IntoCotErrorLayer, IntoCotResponseLayer, override_body_limit, reject_too_large,
+};
 use crate::request::{AppName, PathParams, Request, RequestExt, RouteName};
-use crate::response::{Response, not_found_response};
-use crate::router::path::{CaptureResult, PathMatcher, ReverseParamMap};
-use crate::{Error, Result};
-
+use crate::response::{Response, ResponseExt, normalize_text, not_found_response, redirect};
+use crate::router::cache::CachePolicy;
+use crate::router::host::HostMatcher;
+use crate::router::method::MethodRouter;
+use crate::router::path::{CaptureResult, PathMatcher, ReverseError, ReverseParamMap};
+use crate::{Error, Result, StatusCode};
+
+pub mod cache;
+mod host;
+pub mod method;
 pub mod path;
 
 /// A router that can be used to route requests to their respective views.
@@ -116,6 +157,100 @@ impl Router {
         }
     }
 
+    /// Adds a middleware to all the routes of this router, including the
+    /// routes of the nested routers.
+    ///
+    /// Unlike the middlewares added with
+    /// [`RootHandlerBuilder::middleware`](crate::project::RootHandlerBuilder::middleware),
+    /// which apply to every request, this makes it possible to use a
+    /// middleware (e.g. authentication or rate limiting) for a single app or
+    /// group of routes only. The middleware is run after the request has been
+    /// routed, so it has access to the path parameters and the name of the
+    /// route; requests that don't match any route of this router never reach
+    /// it. See [`Route::middleware`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::TimeoutMiddleware;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn report(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler("/report", report)])
+    ///     .middleware(TimeoutMiddleware::new(std::time::Duration::from_secs(60)));
+    /// ```
+    #[must_use]
+    pub fn middleware<M, ResBody, E>(self, middleware: M) -> Self
+    where
+        M: Layer<HandlerService>,
+        M::Service:
+            Service<Request, Response = http::Response<ResBody>> + Clone + Send + Sync + 'static,
+        <M::Service as Service<Request>>::Future: Send,
+        <M::Service as Service<Request>>::Error: std::error::Error + Send + Sync + 'static,
+        ResBody: http_body::Body<Data = Bytes, Error = E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.map_routes(&middleware)
+    }
+
+    /// Starts a group of routes sharing the given URL prefix.
+    ///
+    /// The routes added to the returned [`Scope`] are nested under `prefix`,
+    /// and the middlewares added to it apply to all of them. The scope is
+    /// turned into a [`Route`] with [`Scope::into_route`] (or
+    /// [`Route::from`]), so it can be mounted in a router or another scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BodyLimitMiddleware;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn users(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// async fn items(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Router::scope("/api/v1")
+    ///     .route(Route::with_handler("/users", users))
+    ///     .route(Route::with_handler("/items", items))
+    ///     .middleware(BodyLimitMiddleware::new(64 * 1024))
+    ///     .into_route()]);
+    /// ```
+    pub fn scope(prefix: &str) -> Scope {
+        Scope::new(prefix)
+    }
+
+    fn map_routes<M, ResBody, E>(self, middleware: &M) -> Self
+    where
+        M: Layer<HandlerService>,
+        M::Service:
+            Service<Request, Response = http::Response<ResBody>> + Clone + Send + Sync + 'static,
+        <M::Service as Service<Request>>::Future: Send,
+        <M::Service as Service<Request>>::Error: std::error::Error + Send + Sync + 'static,
+        ResBody: http_body::Body<Data = Bytes, Error = E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self {
+            urls: self
+                .urls
+                .into_iter()
+                .map(|route| route.with_layer(middleware))
+                .collect(),
+            ..self
+        }
+    }
+
     pub(crate) fn set_app_name(&mut self, app_name: AppName) {
         self.app_name = Some(app_name);
     }
@@ -123,7 +258,8 @@ impl Router {
     async fn route(&self, mut request: Request, request_path: &str) -> Result<Response> {
         debug!("Routing request to {}", request_path);
 
-        if let Some(result) = self.get_handler(request_path) {
+        let host = request_host(&request).map(ToOwned::to_owned);
+        if let Some(result) = self.get_handler(host.as_deref(), request_path) {
             let mut path_params = PathParams::new();
             for (key, value) in result.params.iter().rev() {
                 path_params.insert(key.clone(), value.clone());
@@ -135,15 +271,78 @@ impl Router {
             if let Some(name) = result.name {
                 request.extensions_mut().insert(name);
             }
-            result.handler.handle(request).await
+            if let Some(mounted_path) = result.mounted_path {
+                strip_mount_prefix(&mut request, &mounted_path)?;
+            }
+            let mut response = if let Some(body_limit) = result.body_limit {
+                override_body_limit(&mut request, body_limit);
+                reject_too_large(result.handler.handle(request).await)?
+            } else {
+                result.handler.handle(request).await?
+            };
+            if let Some(cache_policy) = result.cache_policy {
+                cache_policy.apply(&mut response);
+            }
+            Ok(response)
+        } else if let Some(location) = self.trailing_slash_redirect(&request, request_path) {
+            debug!("Redirecting {} to {}", request_path, location);
+            let status = if request.method() == Method::GET || request.method() == Method::HEAD {
+                StatusCode::MOVED_PERMANENTLY
+            } else {
+                StatusCode::PERMANENT_REDIRECT
+            };
+            Ok(redirect(status, location))
         } else {
             debug!("Not found: {}", request_path);
             Ok(not_found_response(None))
         }
     }
 
-    fn get_handler(&self, request_path: &str) -> Option<HandlerFound<'_>> {
+    /// Returns the URL to redirect a request that didn't match any route to,
+    /// if adding or removing the trailing slash of its path (depending on the
+    /// [`TrailingSlash`] policy) makes it match one.
+    fn trailing_slash_redirect(&self, request: &Request, request_path: &str) -> Option<String> {
+        let policy = request
+            .extensions()
+            .get::<Arc<crate::ProjectContext>>()
+            .map_or_else(TrailingSlash::default, |context| {
+                context.config().server.trailing_slash
+            });
+        let path = request.uri().path();
+        let (new_request_path, new_path) = match policy {
+            TrailingSlash::AppendSlash if !path.ends_with('/') => {
+                (format!("{request_path}/"), format!("{path}/"))
+            }
+            TrailingSlash::StripSlash if path.len() > 1 && path.ends_with('/') => (
+                request_path.strip_suffix('/')?.to_owned(),
+                path.strip_suffix('/')?.to_owned(),
+            ),
+            TrailingSlash::Strict | TrailingSlash::AppendSlash | TrailingSlash::StripSlash => {
+                return None;
+            }
+        };
+        if new_path.is_empty() || new_path.starts_with("//") {
+            return None;
+        }
+
+        let host = request_host(request);
+        self.get_handler(host, &new_request_path)?;
+        Some(match request.uri().query() {
+            Some(query) => format!("{new_path}?{query}"),
+            None => new_path,
+        })
+    }
+
+    fn get_handler(&self, host: Option<&str>, request_path: &str) -> Option<HandlerFound<'_>> {
         for route in &self.urls {
+            let host_params = match &route.host {
+                Some(host_matcher) => match host.and_then(|host| host_matcher.capture(host)) {
+                    Some(host_params) => host_params,
+                    None => continue,
+                },
+                None => Vec::new(),
+            };
+
             if let Some(matches) = route.url.capture(request_path) {
                 let matches_fully = matches.matches_fully();
 
@@ -154,17 +353,66 @@ impl Router {
                                 handler: &**handler,
                                 app_name: self.app_name.clone(),
                                 name: route.name.clone(),
-                                params: Self::matches_to_path_params(&matches, Vec::new()),
+                                params: Self::matches_to_path_params(
+                                    &matches,
+                                    &host_params,
+                                    Vec::new(),
+                                ),
+                                mounted_path: None,
+                                cache_policy: route.cache_policy.as_deref(),
+                                body_limit: route.body_limit,
+                            });
+                        }
+                    }
+                    RouteInner::Methods(handler) => {
+                        if matches_fully {
+                            return Some(HandlerFound {
+                                handler,
+                                app_name: self.app_name.clone(),
+                                name: route.name.clone(),
+                                params: Self::matches_to_path_params(
+                                    &matches,
+                                    &host_params,
+                                    Vec::new(),
+                                ),
+                                mounted_path: None,
+                                cache_policy: route.cache_policy.as_deref(),
+                                body_limit: route.body_limit,
+                            });
+                        }
+                    }
+                    RouteInner::Service(handler) => {
+                        let remaining_path = matches.remaining_path;
+                        if remaining_path.is_empty() || remaining_path.starts_with('/') {
+                            return Some(HandlerFound {
+                                handler: &**handler,
+                                app_name: self.app_name.clone(),
+                                name: None,
+                                params: Self::matches_to_path_params(
+                                    &matches,
+                                    &host_params,
+                                    Vec::new(),
+                                ),
+                                mounted_path: Some(remaining_path.to_owned()),
+                                cache_policy: route.cache_policy.as_deref(),
+                                body_limit: route.body_limit,
                             });
                         }
                     }
                     RouteInner::Router(router) => {
-                        if let Some(result) = router.get_handler(matches.remaining_path) {
+                        if let Some(result) = router.get_handler(host, matches.remaining_path) {
                             return Some(HandlerFound {
                                 handler: result.handler,
                                 app_name: result.app_name.or_else(|| self.app_name.clone()),
                                 name: result.name,
-                                params: Self::matches_to_path_params(&matches, result.params),
+                                params: Self::matches_to_path_params(
+                                    &matches,
+                                    &host_params,
+                                    result.params,
+                                ),
+                                mounted_path: result.mounted_path,
+                                cache_policy: result.cache_policy.or(route.cache_policy.as_deref()),
+                                body_limit: result.body_limit.or(route.body_limit),
                             });
                         }
                     }
@@ -177,6 +425,7 @@ impl Router {
 
     fn matches_to_path_params(
         matches: &CaptureResult<'_, '_>,
+        host_params: &[(String, String)],
         mut path_params: Vec<(String, String)>,
     ) -> Vec<(String, String)> {
         // Adding in reverse order, since we're doing this from the bottom up (we're
@@ -184,6 +433,7 @@ impl Router {
         for param in matches.params.iter().rev() {
             path_params.push((param.name.to_owned(), param.value.clone()));
         }
+        path_params.extend(host_params.iter().rev().cloned());
         path_params
     }
 
@@ -229,6 +479,43 @@ impl Router {
             })?)
     }
 
+    /// Get a URL for a route described by a [`RouteRef`].
+    ///
+    /// The route is searched for in the app set in [`RouteRef::APP_NAME`],
+    /// falling back to `app_name` if it's `None`. This is what the
+    /// [`reverse!`] macro calls when given a [`RouteRef`] value.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the route is not registered in this
+    /// router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{RouteRef, Router};
+    ///
+    /// #[derive(RouteRef)]
+    /// #[route(path = "/users/{id}", name = "user")]
+    /// struct UserRoute {
+    ///     id: u32,
+    /// }
+    ///
+    /// async fn user(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([UserRoute::route(user)]);
+    /// let url = router.reverse_route(None, &UserRoute { id: 7 })?;
+    /// assert_eq!(url, "/users/7");
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn reverse_route<R: RouteRef>(&self, app_name: Option<&str>, route: &R) -> Result<String> {
+        self.reverse(R::APP_NAME.or(app_name), R::NAME, &route.params())
+    }
+
     /// Get a URL for a view by name.
     ///
     /// `app_name` is the name of the app that the view should be found in. If
@@ -303,7 +590,14 @@ impl Router {
         &self.urls
     }
 
-    /// Check if this router is empty.
+    /// Returns the information about all the routes handling requests in
+    /// this router, including the routes of the nested routers, in the order
+    /// they are matched.
+    ///
+    /// This is useful for debugging (e.g. to find out why a request results
+    /// in `404 Not Found`) and for building tools on top of the router, such
+    /// as API documentation generators. The routes of nested routers are
+    /// listed with their full paths.
     ///
     /// # Examples
     ///
@@ -312,125 +606,141 @@ impl Router {
     /// use cot::response::Response;
     /// use cot::router::{Route, Router};
     ///
-    /// async fn home(request: Request) -> cot::Result<Response> {
+    /// async fn user(request: Request) -> cot::Result<Response> {
     ///     todo!()
     /// }
     ///
-    /// let router = Router::empty();
-    /// assert!(router.is_empty());
+    /// let users = Router::with_urls([Route::with_handler_and_name("/{id}", user, "user")]);
+    /// let router = Router::with_urls([Route::with_router("/users", users)]);
     ///
-    /// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
-    /// assert!(!router.is_empty());
+    /// let routes = router.route_infos();
+    /// assert_eq!(routes.len(), 1);
+    /// assert_eq!(routes[0].path(), "/users/{id}");
+    /// assert_eq!(routes[0].name(), Some("user"));
     /// ```
     #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.urls.is_empty()
-    }
-}
-
-impl Default for Router {
-    fn default() -> Self {
-        Self::empty()
+    pub fn route_infos(&self) -> Vec<RouteInfo> {
+        let mut route_infos = Vec::new();
+        self.collect_route_infos(&mut route_infos, "", None, None);
+        route_infos
     }
-}
 
-#[derive(Debug)]
-struct HandlerFound<'a> {
-    #[debug("handler(...)")]
-    handler: &'a (dyn BoxRequestHandler + Send + Sync),
-    app_name: Option<AppName>,
-    name: Option<RouteName>,
-    params: Vec<(String, String)>,
-}
+    fn collect_route_infos(
+        &self,
+        route_infos: &mut Vec<RouteInfo>,
+        url_prefix: &str,
+        app_name: Option<&str>,
+        host: Option<&str>,
+    ) {
+        let app_name = self
+            .app_name
+            .as_ref()
+            .map(|app_name| app_name.0.as_str())
+            .or(app_name);
+        for route in &self.urls {
+            let path = format!("{url_prefix}{}", route.url());
+            let route_host = route.host.as_ref().map(ToString::to_string);
+            let host = route_host.as_deref().or(host);
 
-/// A service that routes requests to their respective views.
-///
-/// This is mostly an internal service used by the [`CotApp`](crate::App) to
-/// route requests to their respective views with an interface that is
-/// compatible with the [`tower::Service`] trait.
-#[derive(Debug, Clone)]
-pub struct RouterService {
-    router: Arc<Router>,
-}
+            match &route.view {
+                RouteInner::Router(router) => {
+                    router.collect_route_infos(route_infos, &path, app_name, host);
+                }
+                RouteInner::Handler(_) | RouteInner::Methods(_) | RouteInner::Service(_) => {
+                    route_infos.push(RouteInfo {
+                        path,
+                        name: route.name().map(ToOwned::to_owned),
+                        app_name: app_name.map(ToOwned::to_owned),
+                        host: host.map(ToOwned::to_owned),
+                        methods: route.methods.clone(),
+                        handler_type_name: route.handler_type_name.unwrap_or_default(),
+                        is_service: route.kind() == RouteKind::Service,
+                    });
+                }
+            }
+        }
+    }
 
-impl RouterService {
-    /// Create a new router service.
+    /// Check if this router is empty.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::sync::Arc;
-    ///
     /// use cot::request::Request;
     /// use cot::response::Response;
-    /// use cot::router::{Route, Router, RouterService};
+    /// use cot::router::{Route, Router};
     ///
     /// async fn home(request: Request) -> cot::Result<Response> {
     ///     todo!()
     /// }
     ///
+    /// let router = Router::empty();
+    /// assert!(router.is_empty());
+    ///
     /// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
-    /// let service = RouterService::new(Arc::new(router));
+    /// assert!(!router.is_empty());
     /// ```
     #[must_use]
-    pub fn new(router: Arc<Router>) -> Self {
-        Self { router }
-    }
-}
-
-impl tower::Service<Request> for RouterService {
-    type Error = Error;
-    type Future = Pin<Box<dyn Future<Output = Result<Self::Response>> + Send>>;
-    type Response = Response;
-
-    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
-        Poll::Ready(Ok(()))
-    }
-
-    fn call(&mut self, req: Request) -> Self::Future {
-        let router = self.router.clone();
-        Box::pin(async move { router.handle(req).await })
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
     }
 }
 
-// used in the reverse! macro; not part of public API
-#[doc(hidden)]
-#[must_use]
-pub fn split_view_name(view_name: &str) -> (Option<&str>, &str) {
-    let colon_pos = view_name.find(':');
-    if let Some(colon_pos) = colon_pos {
-        let app_name = &view_name[..colon_pos];
-        let view_name = &view_name[colon_pos + 1..];
-        (Some(app_name), view_name)
-    } else {
-        (None, view_name)
+impl Default for Router {
+    fn default() -> Self {
+        Self::empty()
     }
 }
 
-/// A route that can be used to route requests to their respective views.
+/// A group of routes sharing a URL prefix and middlewares, created with
+/// [`Router::scope`].
 ///
 /// # Examples
 ///
 /// ```
+/// use cot::middleware::TimeoutMiddleware;
 /// use cot::request::Request;
 /// use cot::response::Response;
 /// use cot::router::{Route, Router};
 ///
-/// async fn home(request: Request) -> cot::Result<Response> {
+/// async fn list_users(request: Request) -> cot::Result<Response> {
 ///     todo!()
 /// }
 ///
-/// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
+/// async fn report(request: Request) -> cot::Result<Response> {
+///     todo!()
+/// }
+///
+/// let router = Router::with_urls([Router::scope("/api")
+///     .route(Route::with_handler("/users", list_users))
+///     .scope(
+///         Router::scope("/admin")
+///             .route(Route::with_handler("/report", report))
+///             .middleware(TimeoutMiddleware::new(std::time::Duration::from_secs(60))),
+///     )
+///     .into_route()]);
 /// ```
-#[derive(Debug, Clone)]
-pub struct Route {
-    url: Arc<PathMatcher>,
-    view: RouteInner,
-    name: Option<RouteName>,
+#[must_use]
+#[derive(Debug)]
+pub struct Scope {
+    prefix: String,
+    routes: Vec<Route>,
+    #[debug("[...]")]
+    layers: Vec<Box<dyn FnOnce(Router) -> Router + Send + Sync>>,
 }
 
-impl Route {
-    /// Create a new route with the given handler.
+impl Scope {
+    fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_owned(),
+            routes: Vec::new(),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Adds a route to this scope.
+    ///
+    /// The URL of the route is relative to the prefix of the scope.
     ///
     /// # Examples
     ///
@@ -439,26 +749,18 @@ impl Route {
     /// use cot::response::Response;
     /// use cot::router::{Route, Router};
     ///
-    /// async fn home(request: Request) -> cot::Result<Response> {
+    /// async fn users(request: Request) -> cot::Result<Response> {
     ///     todo!()
     /// }
     ///
-    /// let route = Route::with_handler("/", home);
+    /// let scope = Router::scope("/api").route(Route::with_handler("/users", users));
     /// ```
-    #[must_use]
-    pub fn with_handler<HandlerParams, H>(url: &str, handler: H) -> Self
-    where
-        HandlerParams: 'static,
-        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
-    {
-        Self {
-            url: Arc::new(PathMatcher::new(url)),
-            view: RouteInner::Handler(Arc::new(into_box_request_handler(handler))),
-            name: None,
-        }
+    pub fn route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
     }
 
-    /// Create a new route with the given handler and name.
+    /// Adds multiple routes to this scope.
     ///
     /// # Examples
     ///
@@ -467,27 +769,28 @@ impl Route {
     /// use cot::response::Response;
     /// use cot::router::{Route, Router};
     ///
-    /// async fn home(request: Request) -> cot::Result<Response> {
+    /// async fn users(request: Request) -> cot::Result<Response> {
     ///     todo!()
     /// }
     ///
-    /// let route = Route::with_handler_and_name("/", home, "home");
+    /// async fn items(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let scope = Router::scope("/api").routes([
+    ///     Route::with_handler("/users", users),
+    ///     Route::with_handler("/items", items),
+    /// ]);
     /// ```
-    #[must_use]
-    pub fn with_handler_and_name<N, HandlerParams, H>(url: &str, handler: H, name: N) -> Self
-    where
-        N: Into<String>,
-        HandlerParams: 'static,
-        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
-    {
-        Self {
-            url: Arc::new(PathMatcher::new(url)),
-            view: RouteInner::Handler(Arc::new(into_box_request_handler(handler))),
-            name: Some(RouteName(name.into())),
-        }
+    pub fn routes<I: IntoIterator<Item = Route>>(mut self, routes: I) -> Self {
+        self.routes.extend(routes);
+        self
     }
 
-    /// Create a new route with the given router.
+    /// Nests another scope inside this one.
+    ///
+    /// The prefix of the nested scope is relative to the prefix of this one,
+    /// and the middlewares of this scope apply to the nested scope as well.
     ///
     /// # Examples
     ///
@@ -496,45 +799,57 @@ impl Route {
     /// use cot::response::Response;
     /// use cot::router::{Route, Router};
     ///
-    /// async fn home(request: Request) -> cot::Result<Response> {
+    /// async fn users(request: Request) -> cot::Result<Response> {
     ///     todo!()
     /// }
     ///
-    /// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
-    /// let route = Route::with_router("/", router);
+    /// // serves `/api/v1/users`
+    /// let scope = Router::scope("/api")
+    ///     .scope(Router::scope("/v1").route(Route::with_handler("/users", users)));
     /// ```
-    #[must_use]
-    pub fn with_router(url: &str, router: Router) -> Self {
-        Self {
-            url: Arc::new(PathMatcher::new(url)),
-            view: RouteInner::Router(router),
-            name: None,
-        }
+    pub fn scope(self, scope: Scope) -> Self {
+        self.route(scope.into_route())
     }
 
-    /// Get the URL for this route.
+    /// Adds a middleware to all the routes of this scope, including the
+    /// routes of the nested scopes and routers.
+    ///
+    /// The middleware applies regardless of whether the routes are added
+    /// before or after calling this method. See [`Router::middleware`] for
+    /// more details.
     ///
     /// # Examples
     ///
     /// ```
+    /// use cot::middleware::BodyLimitMiddleware;
     /// use cot::request::Request;
     /// use cot::response::Response;
     /// use cot::router::{Route, Router};
     ///
-    /// async fn home(request: Request) -> cot::Result<Response> {
+    /// async fn upload(request: Request) -> cot::Result<Response> {
     ///     todo!()
     /// }
     ///
-    /// let route = Route::with_handler("/test", home);
-    /// assert_eq!(route.url(), "/test");
+    /// let scope = Router::scope("/files")
+    ///     .middleware(BodyLimitMiddleware::new(10 * 1024 * 1024))
+    ///     .route(Route::with_handler("/upload", upload));
     /// ```
-    #[must_use]
-    pub fn url(&self) -> String {
-        self.url.to_string()
+    pub fn middleware<M, ResBody, E>(mut self, middleware: M) -> Self
+    where
+        M: Layer<HandlerService> + Send + Sync + 'static,
+        M::Service:
+            Service<Request, Response = http::Response<ResBody>> + Clone + Send + Sync + 'static,
+        <M::Service as Service<Request>>::Future: Send,
+        <M::Service as Service<Request>>::Error: std::error::Error + Send + Sync + 'static,
+        ResBody: http_body::Body<Data = Bytes, Error = E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.layers
+            .push(Box::new(move |router| router.middleware(middleware)));
+        self
     }
 
-    /// Get the name of this route, if it was created with the
-    /// [`Self::with_handler_and_name`] function.
+    /// Turns this scope into a [`Route`] that can be added to a [`Router`].
     ///
     /// # Examples
     ///
@@ -543,345 +858,2425 @@ impl Route {
     /// use cot::response::Response;
     /// use cot::router::{Route, Router};
     ///
-    /// async fn home(request: Request) -> cot::Result<Response> {
+    /// async fn users(request: Request) -> cot::Result<Response> {
     ///     todo!()
     /// }
     ///
-    /// let route = Route::with_handler_and_name("/", home, "home");
-    /// assert_eq!(route.name(), Some("home"));
+    /// let route = Router::scope("/api")
+    ///     .route(Route::with_handler("/users", users))
+    ///     .into_route();
+    /// assert_eq!(route.url(), "/api");
     /// ```
     #[must_use]
-    pub fn name(&self) -> Option<&str> {
-        self.name.as_ref().map(|name| name.0.as_str())
+    pub fn into_route(self) -> Route {
+        let router = self
+            .layers
+            .into_iter()
+            .fold(Router::with_urls(self.routes), |router, layer| {
+                layer(router)
+            });
+
+        Route::with_router(&self.prefix, router)
     }
+}
 
-    #[must_use]
-    pub(crate) fn kind(&self) -> RouteKind {
-        match &self.view {
-            RouteInner::Handler(_) => RouteKind::Handler,
-            RouteInner::Router(_) => RouteKind::Router,
-        }
+impl From<Scope> for Route {
+    fn from(scope: Scope) -> Self {
+        scope.into_route()
     }
+}
+
+#[derive(Debug)]
+struct HandlerFound<'a> {
+    #[debug("handler(...)")]
+    handler: &'a (dyn BoxRequestHandler + Send + Sync),
+    app_name: Option<AppName>,
+    name: Option<RouteName>,
+    params: Vec<(String, String)>,
+    /// The part of the request path that is left after the prefix a service
+    /// was mounted at, if the handler is a mounted service.
+    mounted_path: Option<String>,
+    cache_policy: Option<&'a CachePolicy>,
+    body_limit: Option<usize>,
+}
+
+/// Replaces the path of the request with the part that is left after the
+/// prefix a service was mounted at, keeping the query string intact.
+fn strip_mount_prefix(request: &mut Request, mounted_path: &str) -> Result<()> {
+    let path = if mounted_path.is_empty() {
+        "/"
+    } else {
+        mounted_path
+    };
+    let path_and_query = match request.uri().query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_owned(),
+    };
+
+    let mut parts = request.uri().clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().map_err(Error::custom)?);
+    *request.uri_mut() = http::Uri::from_parts(parts).map_err(Error::custom)?;
+    Ok(())
+}
+
+/// A service that routes requests to their respective views.
+///
+/// This is mostly an internal service used by the [`CotApp`](crate::App) to
+/// route requests to their respective views with an interface that is
+/// compatible with the [`tower::Service`] trait.
+#[derive(Debug, Clone)]
+pub struct RouterService {
+    router: Arc<Router>,
+}
 
+impl RouterService {
+    /// Create a new router service.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router, RouterService};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
+    /// let service = RouterService::new(Arc::new(router));
+    /// ```
     #[must_use]
-    pub(crate) fn router(&self) -> Option<&Router> {
-        match &self.view {
-            RouteInner::Router(router) => Some(router),
-            RouteInner::Handler(_) => None,
-        }
+    pub fn new(router: Arc<Router>) -> Self {
+        Self { router }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub(crate) enum RouteKind {
-    Handler,
-    Router,
+impl Service<Request> for RouterService {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response>> + Send>>;
+    type Response = Response;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let router = self.router.clone();
+        let text_normalization = req
+            .extensions()
+            .get::<Arc<crate::ProjectContext>>()
+            .map(|context| context.config().response.text_normalization.clone());
+
+        Box::pin(async move {
+            let mut response = router.handle(req).await?;
+            if let Some(text_normalization) = text_normalization {
+                normalize_text(&mut response, &text_normalization);
+            }
+            Ok(response)
+        })
+    }
 }
 
-#[derive(Clone)]
-enum RouteInner {
-    Handler(Arc<dyn BoxRequestHandler + Send + Sync>),
-    Router(Router),
+// used in the reverse! macro; not part of public API
+#[doc(hidden)]
+pub fn append_query<Q: serde::Serialize + ?Sized>(mut url: String, query: &Q) -> Result<String> {
+    let query = serde_html_form::to_string(query).map_err(ReverseError::InvalidQuery)?;
+    if !query.is_empty() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&query);
+    }
+    Ok(url)
 }
 
-/// Get a URL for a view by its registered name and given params.
+// used in the reverse! macro; not part of public API
+#[doc(hidden)]
+#[must_use]
+pub fn split_view_name(view_name: &str) -> (Option<&str>, &str) {
+    let colon_pos = view_name.find(':');
+    if let Some(colon_pos) = colon_pos {
+        let app_name = &view_name[..colon_pos];
+        let view_name = &view_name[colon_pos + 1..];
+        (Some(app_name), view_name)
+    } else {
+        (None, view_name)
+    }
+}
+
+/// A typed reference to a named route.
 ///
-/// If the view name has two parts separated by a colon, the first part is
-/// considered the app name. If the app name is not provided, the app name of
-/// the request is used. This means that if you don't specify the `app_name`,
-/// this macro will only return URLs for views in the same app as the current
-/// request handler.
+/// Reversing routes by their names (as in `reverse!(request, "post", id =
+/// 5)`) can only be checked at runtime: a typo in the route name or a missing
+/// parameter results in an error when the URL is generated. Types
+/// implementing this trait describe a route (its path, name and parameters)
+/// once, so the handler can be registered with [`RouteRef::route`] and the
+/// URLs generated with the [`reverse!`] macro (or [`Router::reverse_route`])
+/// by passing a value of the type. Referring to a route that doesn't exist,
+/// or with missing or mistyped parameters, then fails to compile.
 ///
-/// # Return value
+/// # Deriving
 ///
-/// Returns a [`cot::Result<String>`] that contains the URL for the view. You
-/// will typically want to append `?` to the macro call to get the URL.
+/// This trait should be derived using the [`RouteRef`](derive@RouteRef)
+/// derive macro, which checks that the fields of the struct match the
+/// parameters of the path.
 ///
 /// # Examples
 ///
 /// ```
-/// use cot::project::RegisterAppsContext;
 /// use cot::request::Request;
-/// use cot::response::{Response, ResponseExt};
-/// use cot::router::{Route, Router};
-/// use cot::{App, AppBuilder, Body, Project, StatusCode, reverse};
-///
-/// async fn home(request: Request) -> cot::Result<Response> {
-///     // any of below two lines returns the same:
-///     let url = reverse!(request, "home")?;
-///     let url = reverse!(request, "my_custom_app:home")?;
+/// use cot::request::extractors::Path;
+/// use cot::response::Response;
+/// use cot::reverse;
+/// use cot::router::{RouteRef, Router};
 ///
-///     Ok(Response::new_html(
-///         StatusCode::OK,
-///         Body::fixed(format!("Hello! The URL for this view is: {}", url)),
-///     ))
+/// #[derive(RouteRef)]
+/// #[route(path = "/posts/{id:i64}", name = "post")]
+/// struct PostRoute {
+///     id: i64,
 /// }
 ///
-/// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
-///
-/// struct MyApp;
-///
-/// impl App for MyApp {
-///     fn name(&self) -> &'static str {
-///         "my_custom_app"
-///     }
-///
-///     fn router(&self) -> Router {
-///         Router::with_urls([Route::with_handler_and_name("/", home, "home")])
-///     }
+/// async fn post(request: Request) -> cot::Result<Response> {
+///     let next = reverse!(request, PostRoute { id: 6 })?;
+///     // ...
+///     # unimplemented!()
 /// }
 ///
-/// struct MyProject;
-///
-/// impl Project for MyProject {
-///     fn register_apps(&self, apps: &mut AppBuilder, context: &RegisterAppsContext) {
-///         apps.register_with_views(MyApp, "");
-///     }
-/// }
+/// let router = Router::with_urls([PostRoute::route(post)]);
 /// ```
-#[macro_export]
-macro_rules! reverse {
-    ($request:expr, $view_name:literal $(, $($key:ident = $value:expr),*)?) => {{
-        #[allow(unused_imports)] // allow using either `Request` or `Urls` objects
-        use $crate::request::RequestExt;
-        let (app_name, view_name) = $crate::router::split_view_name($view_name);
-        let app_name = app_name.or_else(|| $request.app_name());
-        $request
-            .router()
-            .reverse(app_name, view_name, &$crate::reverse_param_map!($( $($key = $value),* )?))
-    }};
+pub trait RouteRef {
+    /// The path pattern of the route, in the format accepted by
+    /// [`Route::with_handler`].
+    const PATH: &'static str;
+
+    /// The name of the route.
+    const NAME: &'static str;
+
+    /// The name of the app the route belongs to, or `None` for the app of the
+    /// current request (in the same way as for the [`reverse!`] macro).
+    const APP_NAME: Option<&'static str>;
+
+    /// Returns the values of the path parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::RouteRef;
+    ///
+    /// #[derive(RouteRef)]
+    /// #[route(path = "/posts/{id}", name = "post")]
+    /// struct PostRoute {
+    ///     id: i64,
+    /// }
+    ///
+    /// let params = PostRoute { id: 5 }.params();
+    /// ```
+    fn params(&self) -> ReverseParamMap;
+
+    /// Creates a [`Route`] for this path and name with the given handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{RouteRef, Router};
+    ///
+    /// #[derive(RouteRef)]
+    /// #[route(path = "/", name = "home")]
+    /// struct HomeRoute;
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([HomeRoute::route(home)]);
+    /// ```
+    #[must_use]
+    fn route<HandlerParams, H>(handler: H) -> Route
+    where
+        Self: Sized,
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        Route::with_handler_and_name(Self::PATH, handler, Self::NAME)
+    }
 }
 
-/// A helper structure to allow reversing URLs from a request handler.
-///
-/// This is mainly useful as an extractor to allow reversing URLs without
-/// access to a full [`Request`] object.
+/// A route that can be used to route requests to their respective views.
 ///
 /// # Examples
 ///
 /// ```
 /// use cot::request::Request;
-/// use cot::response::{Response, ResponseExt};
-/// use cot::router::{Route, Router, Urls};
-/// use cot::test::TestRequestBuilder;
-/// use cot::{Body, RequestHandler, StatusCode, reverse};
+/// use cot::response::Response;
+/// use cot::router::{Route, Router};
 ///
-/// async fn my_handler(urls: Urls) -> cot::Result<Response> {
-///     let url = reverse!(urls, "home")?;
-///     Ok(Response::new_html(
-///         StatusCode::OK,
-///         Body::fixed(format!("{url}")),
-///     ))
+/// async fn home(request: Request) -> cot::Result<Response> {
+///     todo!()
 /// }
 ///
-/// # #[tokio::main]
-/// # async fn main() -> cot::Result<()> {
-/// let router = Router::with_urls([Route::with_handler_and_name("/", my_handler, "home")]);
-/// let request = TestRequestBuilder::get("/").router(router).build();
-///
-/// assert_eq!(
-///     my_handler
-///         .handle(request)
-///         .await?
-///         .into_body()
-///         .into_bytes()
-///         .await?,
-///     "/"
-/// );
-/// # Ok(())
-/// # }
+/// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
 /// ```
 #[derive(Debug, Clone)]
-pub struct Urls {
-    app_name: Option<String>,
-    router: Arc<Router>,
+pub struct Route {
+    url: Arc<PathMatcher>,
+    view: RouteInner,
+    name: Option<RouteName>,
+    cache_policy: Option<Arc<CachePolicy>>,
+    body_limit: Option<usize>,
+    host: Option<Arc<HostMatcher>>,
+    handler_type_name: Option<&'static str>,
+    /// The methods handled by a [`MethodRouter`], or `None` if the handler
+    /// accepts any method.
+    methods: Option<Vec<Method>>,
 }
 
-impl Urls {
-    /// Create a new `Urls` object from a [`Request`] object.
+impl Route {
+    /// Create a new route with the given handler.
     ///
     /// # Examples
     ///
     /// ```
     /// use cot::request::Request;
-    /// use cot::response::{Response, ResponseExt};
-    /// use cot::router::Urls;
-    /// use cot::{Body, StatusCode, reverse};
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
     ///
-    /// async fn my_handler(request: Request) -> cot::Result<Response> {
-    ///     let urls = Urls::from_request(&request);
-    ///     let url = reverse!(urls, "home")?;
-    ///     Ok(Response::new_html(
-    ///         StatusCode::OK,
-    ///         Body::fixed(format!("Hello! The URL for this view is: {}", url)),
-    ///     ))
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
     /// }
+    ///
+    /// let route = Route::with_handler("/", home);
     /// ```
-    pub fn from_request(request: &Request) -> Self {
+    #[must_use]
+    pub fn with_handler<HandlerParams, H>(url: &str, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        let methods = method_router_methods(&handler);
         Self {
-            app_name: request.app_name().map(ToOwned::to_owned),
-            router: Arc::clone(request.router()),
+            url: Arc::new(PathMatcher::new(url)),
+            view: RouteInner::Handler(Arc::new(into_box_request_handler(handler))),
+            name: None,
+            cache_policy: None,
+            body_limit: None,
+            host: None,
+            handler_type_name: Some(std::any::type_name::<H>()),
+            methods,
         }
     }
 
-    pub(crate) fn from_parts(request_parts: &Parts) -> Self {
-        Self {
-            app_name: request_parts.app_name().map(ToOwned::to_owned),
-            router: Arc::clone(request_parts.router()),
-        }
+    /// Create a new route with the given handler and name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_handler_and_name("/", home, "home");
+    /// ```
+    #[must_use]
+    pub fn with_handler_and_name<N, HandlerParams, H>(url: &str, handler: H, name: N) -> Self
+    where
+        N: Into<String>,
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        let methods = method_router_methods(&handler);
+        Self {
+            url: Arc::new(PathMatcher::new(url)),
+            view: RouteInner::Handler(Arc::new(into_box_request_handler(handler))),
+            name: Some(RouteName(name.into())),
+            cache_policy: None,
+            body_limit: None,
+            host: None,
+            handler_type_name: Some(std::any::type_name::<H>()),
+            methods,
+        }
+    }
+
+    /// Create a new route accepting WebSocket connections and passing them to
+    /// the given handler.
+    ///
+    /// This is a shortcut for a handler extracting
+    /// [`WebSocketUpgrade`](crate::websocket::WebSocketUpgrade) and calling
+    /// [`on_upgrade`](crate::websocket::WebSocketUpgrade::on_upgrade) with
+    /// `handler`. Requests that are not valid WebSocket upgrade requests are
+    /// rejected with `400 Bad Request`. Use the extractor directly when the
+    /// handler needs to access the request, e.g. to authenticate the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::{Route, Router};
+    /// use cot::websocket::{Message, WebSocket};
+    ///
+    /// async fn echo(mut socket: WebSocket) {
+    ///     while let Some(Ok(message)) = socket.recv().await {
+    ///         if socket.send(message).await.is_err() {
+    ///             break;
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let route = Route::with_websocket_handler("/ws", echo);
+    /// ```
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn with_websocket_handler<H, Fut>(url: &str, handler: H) -> Self
+    where
+        H: FnOnce(crate::websocket::WebSocket) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            handler_type_name: Some(std::any::type_name::<H>()),
+            methods: Some(vec![Method::GET]),
+            ..Self::with_handler(
+                url,
+                move |upgrade: crate::websocket::WebSocketUpgrade| async move {
+                    Ok(upgrade.on_upgrade(handler))
+                },
+            )
+        }
+    }
+
+    /// Create a new route dispatching the requests to different handlers based
+    /// on their HTTP method.
+    ///
+    /// The handlers are registered with [`Self::get`], [`Self::post`] and the
+    /// other method builders. The requests are handled as described in
+    /// [`MethodRouter`]: requests with a method without a handler get a `405
+    /// Method Not Allowed` response, and `HEAD` and `OPTIONS` requests are
+    /// answered automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn list_items(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// async fn create_item(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_methods("/items")
+    ///     .get(list_items)
+    ///     .post(create_item)]);
+    /// ```
+    #[must_use]
+    pub fn with_methods(url: &str) -> Self {
+        Self {
+            url: Arc::new(PathMatcher::new(url)),
+            view: RouteInner::Methods(MethodsHandler(MethodRouter::new())),
+            name: None,
+            cache_policy: None,
+            body_limit: None,
+            host: None,
+            handler_type_name: Some(std::any::type_name::<MethodRouter>()),
+            methods: Some(Vec::new()),
+        }
+    }
+
+    /// Create a new named route dispatching the requests to different
+    /// handlers based on their HTTP method.
+    ///
+    /// See [`Self::with_methods`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn show_item(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// async fn delete_item(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_methods_and_name("/items/{id}", "item")
+    ///     .get(show_item)
+    ///     .delete(delete_item);
+    /// assert_eq!(route.name(), Some("item"));
+    /// ```
+    #[must_use]
+    pub fn with_methods_and_name<N: Into<String>>(url: &str, name: N) -> Self {
+        Self {
+            name: Some(RouteName(name.into())),
+            ..Self::with_methods(url)
+        }
+    }
+
+    /// Create a new route with the given router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
+    /// let route = Route::with_router("/", router);
+    /// ```
+    #[must_use]
+    pub fn with_router(url: &str, router: Router) -> Self {
+        Self {
+            url: Arc::new(PathMatcher::new(url)),
+            view: RouteInner::Router(router),
+            name: None,
+            cache_policy: None,
+            body_limit: None,
+            host: None,
+            handler_type_name: None,
+            methods: None,
+        }
+    }
+
+    /// Create a new route that passes the requests to the given [`tower`]
+    /// service.
+    ///
+    /// This makes it possible to mount any service from the [`tower`]
+    /// ecosystem (e.g. a gRPC service or a router from a different
+    /// framework) inside a Cot project. Every request whose path starts with
+    /// `url` is passed to the service, with the `url` prefix stripped from the
+    /// request path (so mounting a service at `/api` makes it see a request
+    /// to `/api/users?page=2` as `/users?page=2`, and a request to `/api` as
+    /// `/`). The path parameters captured from `url` are still available in
+    /// the [`PathParams`] request extension.
+    ///
+    /// The response and error types of the service are converted to Cot's
+    /// [`Response`] and [`Error`] automatically, in the same way as it
+    /// is done for middlewares added with
+    /// [`RootHandlerBuilder::middleware`](crate::project::RootHandlerBuilder::middleware).
+    /// This means the service must satisfy the following bounds:
+    /// * it has to implement `Service<Request, Response =
+    ///   http::Response<ResBody>>` and be `Clone + Send + Sync + 'static`, with
+    ///   its future being `Send`,
+    /// * its error type has to implement `std::error::Error + Send + Sync +
+    ///   'static`,
+    /// * the response body `ResBody` has to implement `http_body::Body<Data =
+    ///   Bytes, Error = E> + Send + Sync + 'static`, where `E` implements
+    ///   `std::error::Error + Send + Sync + 'static`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use cot::request::Request;
+    /// use cot::response::{Response, ResponseExt};
+    /// use cot::router::{Route, Router};
+    /// use cot::{Body, StatusCode};
+    ///
+    /// let service = tower::service_fn(|request: Request| async move {
+    ///     let body = format!("Hello from {}!", request.uri().path());
+    ///     Ok::<_, Infallible>(Response::new_html(StatusCode::OK, Body::fixed(body)))
+    /// });
+    ///
+    /// let router = Router::with_urls([Route::with_service("/hello", service)]);
+    /// ```
+    #[must_use]
+    pub fn with_service<S, ResBody, E>(url: &str, service: S) -> Self
+    where
+        S: Service<Request, Response = http::Response<ResBody>> + Clone + Send + Sync + 'static,
+        S::Future: Send,
+        S::Error: std::error::Error + Send + Sync + 'static,
+        ResBody: http_body::Body<Data = Bytes, Error = E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let service = (IntoCotErrorLayer::new(), IntoCotResponseLayer::new()).layer(service);
+
+        Self {
+            url: Arc::new(PathMatcher::new(url)),
+            view: RouteInner::Service(Arc::new(ServiceHandler(BoxCloneSyncService::new(service)))),
+            name: None,
+            cache_policy: None,
+            body_limit: None,
+            host: None,
+            handler_type_name: Some(std::any::type_name::<S>()),
+            methods: None,
+        }
+    }
+
+    /// Registers a handler for requests with the given HTTP method on a route
+    /// created with [`Self::with_methods`].
+    ///
+    /// Registering a handler for a method that already has one replaces it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route wasn't created with [`Self::with_methods`] or
+    /// [`Self::with_methods_and_name`], or if a middleware has already been
+    /// added to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::Method;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::Route;
+    ///
+    /// async fn search(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_methods("/items").on(Method::from_bytes(b"QUERY").unwrap(), search);
+    /// ```
+    #[must_use]
+    pub fn on<HandlerParams, H>(mut self, method: Method, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        let RouteInner::Methods(MethodsHandler(methods)) = &mut self.view else {
+            panic!(
+                "handlers for HTTP methods can only be added to routes created with \
+                `Route::with_methods` before adding middlewares"
+            );
+        };
+        *methods = std::mem::take(methods).on(method, handler);
+        self.methods = Some(methods.methods().cloned().collect());
+        self
+    }
+
+    /// Registers a handler for `GET` requests on a route created with
+    /// [`Self::with_methods`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route wasn't created with [`Self::with_methods`] or
+    /// [`Self::with_methods_and_name`], or if a middleware has already been
+    /// added to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::Route;
+    ///
+    /// async fn list_items(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_methods("/items").get(list_items);
+    /// ```
+    #[must_use]
+    pub fn get<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::GET, handler)
+    }
+
+    /// Registers a handler for `POST` requests on a route created with
+    /// [`Self::with_methods`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route wasn't created with [`Self::with_methods`] or
+    /// [`Self::with_methods_and_name`], or if a middleware has already been
+    /// added to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::Route;
+    ///
+    /// async fn create_item(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_methods("/items").post(create_item);
+    /// ```
+    #[must_use]
+    pub fn post<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::POST, handler)
+    }
+
+    /// Registers a handler for `PUT` requests on a route created with
+    /// [`Self::with_methods`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route wasn't created with [`Self::with_methods`] or
+    /// [`Self::with_methods_and_name`], or if a middleware has already been
+    /// added to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::Route;
+    ///
+    /// async fn replace_item(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_methods("/items").put(replace_item);
+    /// ```
+    #[must_use]
+    pub fn put<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::PUT, handler)
+    }
+
+    /// Registers a handler for `PATCH` requests on a route created with
+    /// [`Self::with_methods`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route wasn't created with [`Self::with_methods`] or
+    /// [`Self::with_methods_and_name`], or if a middleware has already been
+    /// added to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::Route;
+    ///
+    /// async fn update_item(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_methods("/items").patch(update_item);
+    /// ```
+    #[must_use]
+    pub fn patch<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::PATCH, handler)
+    }
+
+    /// Registers a handler for `DELETE` requests on a route created with
+    /// [`Self::with_methods`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route wasn't created with [`Self::with_methods`] or
+    /// [`Self::with_methods_and_name`], or if a middleware has already been
+    /// added to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::Route;
+    ///
+    /// async fn delete_item(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_methods("/items").delete(delete_item);
+    /// ```
+    #[must_use]
+    pub fn delete<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::DELETE, handler)
+    }
+
+    /// Registers a handler for `OPTIONS` requests on a route created with
+    /// [`Self::with_methods`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route wasn't created with [`Self::with_methods`] or
+    /// [`Self::with_methods_and_name`], or if a middleware has already been
+    /// added to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::Route;
+    ///
+    /// async fn describe_items(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_methods("/items").options(describe_items);
+    /// ```
+    #[must_use]
+    pub fn options<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::OPTIONS, handler)
+    }
+
+    /// Set the `Cache-Control` and `Vary` headers for the responses of this
+    /// route.
+    ///
+    /// The headers are added to the responses unless the handler has already
+    /// set them. If this route contains a [`Router`], the policy applies to
+    /// all its routes that don't have a cache policy of their own. See
+    /// [`CachePolicy`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::cache::CachePolicy;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_handler("/", home)
+    ///     .cache_policy(CachePolicy::cacheable(Duration::from_secs(300)));
+    /// ```
+    #[must_use]
+    pub fn cache_policy(self, cache_policy: CachePolicy) -> Self {
+        Self {
+            cache_policy: Some(Arc::new(cache_policy)),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of the request body for this route, in bytes,
+    /// overriding the global limit of the
+    /// [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware).
+    ///
+    /// The limit also applies to the extractors reading the body. Requests
+    /// with a larger body are rejected with `413 Payload Too Large`. If this
+    /// route contains a [`Router`], the limit applies to all its routes that
+    /// don't have a limit of their own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn upload(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// const MB: usize = 1024 * 1024;
+    /// let route = Route::with_handler("/upload", upload).body_limit(50 * MB);
+    /// ```
+    #[must_use]
+    pub fn body_limit(self, max_bytes: usize) -> Self {
+        Self {
+            body_limit: Some(max_bytes),
+            ..self
+        }
+    }
+
+    /// Restricts this route to the requests sent to the hosts matching the
+    /// given pattern, such as `{tenant}.example.com`.
+    ///
+    /// The pattern consists of labels separated by dots; each label is
+    /// either a literal (compared case-insensitively) or a parameter in
+    /// braces matching a single label (e.g. a subdomain). The values of the
+    /// parameters, in lowercase, are available together with the path
+    /// parameters in the [`PathParams`] request extension and the
+    /// [`Path`](crate::request::extractors::Path) extractor. The host is
+    /// taken from the `Host` header, and the port is ignored; requests
+    /// with a host not matching the pattern are passed to the next routes.
+    ///
+    /// This is useful for multi-tenant projects, as well as for serving
+    /// different apps on different hosts: an app mounted with
+    /// [`AppBuilder::register_with_views_on_host`](crate::project::AppBuilder::register_with_views_on_host)
+    /// is only available on the given host.
+    ///
+    /// Note that the URLs generated when reversing the routes with a host
+    /// pattern don't include the host.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the host pattern is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::extractors::Path;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn dashboard(Path(tenant): Path<String>) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([
+    ///     Route::with_handler("/dashboard", dashboard).host("{tenant}.example.com")
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn host(self, host_pattern: &str) -> Self {
+        Self {
+            host: Some(Arc::new(HostMatcher::new(host_pattern))),
+            ..self
+        }
+    }
+
+    /// Adds a middleware to this route.
+    ///
+    /// The middleware only applies to the requests routed to this route. If
+    /// this route contains a [`Router`], the middleware applies to all its
+    /// routes. It is run after the request has been routed, so it has access
+    /// to the path parameters and the name of the route.
+    ///
+    /// The middleware is applied separately to every handler, so
+    /// [`Layer::layer`] is called once per handler. Middlewares with state
+    /// shared between requests (such as
+    /// [`RateLimitMiddleware`](crate::middleware::RateLimitMiddleware)) keep
+    /// it in the middleware itself, so the state is still shared between all
+    /// the routes the middleware is added to. When called several times, the
+    /// last added middleware is the outermost one, like with
+    /// [`RootHandlerBuilder::middleware`](crate::project::RootHandlerBuilder::middleware).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BodyLimitMiddleware;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn users(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let api = Router::with_urls([Route::with_handler("/users", users)]);
+    /// let router = Router::with_urls([
+    ///     Route::with_router("/api", api).middleware(BodyLimitMiddleware::new(64 * 1024))
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn middleware<M, ResBody, E>(self, middleware: M) -> Self
+    where
+        M: Layer<HandlerService>,
+        M::Service:
+            Service<Request, Response = http::Response<ResBody>> + Clone + Send + Sync + 'static,
+        <M::Service as Service<Request>>::Future: Send,
+        <M::Service as Service<Request>>::Error: std::error::Error + Send + Sync + 'static,
+        ResBody: http_body::Body<Data = Bytes, Error = E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.with_layer(&middleware)
+    }
+
+    fn with_layer<M, ResBody, E>(self, middleware: &M) -> Self
+    where
+        M: Layer<HandlerService>,
+        M::Service:
+            Service<Request, Response = http::Response<ResBody>> + Clone + Send + Sync + 'static,
+        <M::Service as Service<Request>>::Future: Send,
+        <M::Service as Service<Request>>::Error: std::error::Error + Send + Sync + 'static,
+        ResBody: http_body::Body<Data = Bytes, Error = E> + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let view = match self.view {
+            RouteInner::Handler(handler) => {
+                RouteInner::Handler(apply_middleware(handler, middleware))
+            }
+            RouteInner::Methods(handler) => {
+                RouteInner::Handler(apply_middleware(Arc::new(handler), middleware))
+            }
+            RouteInner::Service(handler) => {
+                RouteInner::Service(apply_middleware(handler, middleware))
+            }
+            RouteInner::Router(router) => RouteInner::Router(router.map_routes(middleware)),
+        };
+
+        Self { view, ..self }
+    }
+
+    /// Get the URL for this route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_handler("/test", home);
+    /// assert_eq!(route.url(), "/test");
+    /// ```
+    #[must_use]
+    pub fn url(&self) -> String {
+        self.url.to_string()
+    }
+
+    /// Get the name of this route, if it was created with the
+    /// [`Self::with_handler_and_name`] function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let route = Route::with_handler_and_name("/", home, "home");
+    /// assert_eq!(route.name(), Some("home"));
+    /// ```
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|name| name.0.as_str())
+    }
+
+    #[must_use]
+    pub(crate) fn kind(&self) -> RouteKind {
+        match &self.view {
+            RouteInner::Handler(_) | RouteInner::Methods(_) => RouteKind::Handler,
+            RouteInner::Service(_) => RouteKind::Service,
+            RouteInner::Router(_) => RouteKind::Router,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn router(&self) -> Option<&Router> {
+        match &self.view {
+            RouteInner::Router(router) => Some(router),
+            RouteInner::Handler(_) | RouteInner::Methods(_) | RouteInner::Service(_) => None,
+        }
+    }
+}
+
+/// Information about a route, as returned by [`Router::route_infos`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::Request;
+/// use cot::response::Response;
+/// use cot::router::{Route, Router};
+///
+/// async fn home(request: Request) -> cot::Result<Response> {
+///     todo!()
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
+/// let route = &router.route_infos()[0];
+/// assert_eq!(route.path(), "/");
+/// assert_eq!(route.name(), Some("home"));
+/// assert_eq!(route.methods(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo {
+    path: String,
+    name: Option<String>,
+    app_name: Option<String>,
+    host: Option<String>,
+    methods: Option<Vec<Method>>,
+    handler_type_name: &'static str,
+    is_service: bool,
+}
+
+impl RouteInfo {
+    /// Returns the full path pattern of the route, including the prefixes
+    /// of the routers it's nested in.
+    ///
+    /// For a [`tower`] service mounted with [`Route::with_service`], this is
+    /// the prefix the service is mounted at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn post(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let blog = Router::with_urls([Route::with_handler("/{slug}", post)]);
+    /// let router = Router::with_urls([Route::with_router("/blog", blog)]);
+    /// assert_eq!(router.route_infos()[0].path(), "/blog/{slug}");
+    /// ```
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the name of the route, if it has one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
+    /// assert_eq!(router.route_infos()[0].name(), Some("home"));
+    /// ```
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the name of the app the route belongs to, if it's been
+    /// registered as part of an app.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler("/", home)]);
+    /// assert_eq!(router.route_infos()[0].app_name(), None);
+    /// ```
+    #[must_use]
+    pub fn app_name(&self) -> Option<&str> {
+        self.app_name.as_deref()
+    }
+
+    /// Returns the host pattern the route is restricted to with
+    /// [`Route::host`], if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler("/", home).host("{tenant}.example.com")]);
+    /// assert_eq!(router.route_infos()[0].host(), Some("{tenant}.example.com"));
+    /// ```
+    #[must_use]
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// Returns the HTTP methods handled by the route, or `None` if the
+    /// handler accepts requests with any method.
+    ///
+    /// The methods are known for the routes created with
+    /// [`Route::with_methods`], the routes whose handler is a
+    /// [`MethodRouter`], and the WebSocket routes. The `HEAD` and `OPTIONS`
+    /// requests answered automatically by [`MethodRouter`] are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::Method;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn list_items(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_methods("/items").get(list_items)]);
+    /// assert_eq!(router.route_infos()[0].methods(), Some(&[Method::GET][..]));
+    /// ```
+    #[must_use]
+    pub fn methods(&self) -> Option<&[Method]> {
+        self.methods.as_deref()
+    }
+
+    /// Returns the type name of the handler of the route, as returned by
+    /// [`std::any::type_name`].
+    ///
+    /// This is meant for debugging only; the exact format of the name is not
+    /// guaranteed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler("/", home)]);
+    /// assert!(
+    ///     router.route_infos()[0]
+    ///         .handler_type_name()
+    ///         .ends_with("home")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn handler_type_name(&self) -> &'static str {
+        self.handler_type_name
+    }
+
+    /// Returns whether the route is a [`tower`] service mounted with
+    /// [`Route::with_service`], handling all the paths starting with
+    /// [`Self::path`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn home(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler("/", home)]);
+    /// assert!(!router.route_infos()[0].is_service());
+    /// ```
+    #[must_use]
+    pub fn is_service(&self) -> bool {
+        self.is_service
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum RouteKind {
+    Handler,
+    Service,
+    Router,
+}
+
+#[derive(Clone)]
+enum RouteInner {
+    Handler(Arc<dyn BoxRequestHandler + Send + Sync>),
+    Methods(MethodsHandler),
+    Service(Arc<dyn BoxRequestHandler + Send + Sync>),
+    Router(Router),
+}
+
+/// The handler of a route created with [`Route::with_methods`].
+#[derive(Clone)]
+struct MethodsHandler(MethodRouter);
+
+impl BoxRequestHandler for MethodsHandler {
+    fn handle(
+        &self,
+        request: Request,
+    ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send + '_>> {
+        Box::pin(RequestHandler::handle(&self.0, request))
+    }
+}
+
+/// Returns the methods handled by `handler` if it's a [`MethodRouter`].
+fn method_router_methods<H: 'static>(handler: &H) -> Option<Vec<Method>> {
+    let handler: &dyn std::any::Any = handler;
+    handler
+        .downcast_ref::<MethodRouter>()
+        .map(|methods| methods.methods().cloned().collect())
+}
+
+/// Returns the host the request was sent to, taken from the `Host` header or
+/// the request URI.
+fn request_host(request: &Request) -> Option<&str> {
+    match request.headers().get(http::header::HOST) {
+        Some(host) => host.to_str().ok(),
+        None => request.uri().host(),
+    }
+}
+
+/// Adapts a [`tower`] service mounted with [`Route::with_service`] to a
+/// request handler.
+struct ServiceHandler(BoxCloneSyncService<Request, Response, Error>);
+
+impl BoxRequestHandler for ServiceHandler {
+    fn handle(
+        &self,
+        request: Request,
+    ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send + '_>> {
+        Box::pin(self.0.clone().oneshot(request))
+    }
+}
+
+/// A request handler of a route, as seen by the middlewares added with
+/// [`Route::middleware`] and [`Router::middleware`].
+///
+/// This is the innermost service the route middlewares are applied to; it
+/// can't be constructed outside of Cot.
+#[derive(Clone, Debug)]
+pub struct HandlerService(#[debug("handler(...)")] Arc<dyn BoxRequestHandler + Send + Sync>);
+
+impl Service<Request> for HandlerService {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response>> + Send>>;
+    type Response = Response;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let handler = Arc::clone(&self.0);
+        Box::pin(async move { handler.handle(req).await })
+    }
+}
+
+fn apply_middleware<M, ResBody, E>(
+    handler: Arc<dyn BoxRequestHandler + Send + Sync>,
+    middleware: &M,
+) -> Arc<dyn BoxRequestHandler + Send + Sync>
+where
+    M: Layer<HandlerService>,
+    M::Service:
+        Service<Request, Response = http::Response<ResBody>> + Clone + Send + Sync + 'static,
+    <M::Service as Service<Request>>::Future: Send,
+    <M::Service as Service<Request>>::Error: std::error::Error + Send + Sync + 'static,
+    ResBody: http_body::Body<Data = Bytes, Error = E> + Send + Sync + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let service = middleware.layer(HandlerService(handler));
+    let service = (IntoCotErrorLayer::new(), IntoCotResponseLayer::new()).layer(service);
+
+    Arc::new(ServiceHandler(BoxCloneSyncService::new(service)))
+}
+
+/// Get a URL for a view by its registered name and given params.
+///
+/// If the view name has two parts separated by a colon, the first part is
+/// considered the app name. If the app name is not provided, the app name of
+/// the request is used. This means that if you don't specify the `app_name`,
+/// this macro will only return URLs for views in the same app as the current
+/// request handler.
+///
+/// # Return value
+///
+/// Returns a [`cot::Result<String>`] that contains the URL for the view. You
+/// will typically want to append `?` to the macro call to get the URL.
+///
+/// # Query parameters
+///
+/// Query parameters can be appended to the URL by passing a value
+/// implementing [`serde::Serialize`] (such as a struct or a slice of
+/// key-value pairs) after a semicolon, as in `reverse!(request, "list";
+/// query = &params)`. The value is serialized in the same format the
+/// [`UrlQuery`](crate::request::extractors::UrlQuery) extractor reads.
+///
+/// To get an absolute URL instead (e.g. to put in an email), use the
+/// [`reverse_absolute!`](crate::reverse_absolute) macro.
+///
+/// # Typed route references
+///
+/// Instead of a view name and parameters, a value of a type implementing
+/// [`RouteRef`] can be passed, as in `reverse!(request, PostRoute { id: 5
+/// })`. Unlike with view names, a reference to a route that doesn't exist or
+/// with missing parameters fails to compile.
+///
+/// # Examples
+///
+/// ```
+/// use cot::project::RegisterAppsContext;
+/// use cot::request::Request;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::router::{Route, Router};
+/// use cot::{App, AppBuilder, Body, Project, StatusCode, reverse};
+///
+/// async fn home(request: Request) -> cot::Result<Response> {
+///     // any of below two lines returns the same:
+///     let url = reverse!(request, "home")?;
+///     let url = reverse!(request, "my_custom_app:home")?;
+///
+///     Ok(Response::new_html(
+///         StatusCode::OK,
+///         Body::fixed(format!("Hello! The URL for this view is: {}", url)),
+///     ))
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler_and_name("/", home, "home")]);
+///
+/// struct MyApp;
+///
+/// impl App for MyApp {
+///     fn name(&self) -> &'static str {
+///         "my_custom_app"
+///     }
+///
+///     fn router(&self) -> Router {
+///         Router::with_urls([Route::with_handler_and_name("/", home, "home")])
+///     }
+/// }
+///
+/// struct MyProject;
+///
+/// impl Project for MyProject {
+///     fn register_apps(&self, apps: &mut AppBuilder, context: &RegisterAppsContext) {
+///         apps.register_with_views(MyApp, "");
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! reverse {
+    ($request:expr, $view_name:literal $(, $($key:ident = $value:expr),*)?; query = $query:expr) => {
+        $crate::reverse!($request, $view_name $(, $($key = $value),*)?)
+            .and_then(|url| $crate::router::append_query(url, $query))
+    };
+    ($request:expr, $view_name:literal $(, $($key:ident = $value:expr),*)?) => {{
+        #[allow(unused_imports)] // allow using either `Request` or `Urls` objects
+        use $crate::request::RequestExt;
+        let (app_name, view_name) = $crate::router::split_view_name($view_name);
+        let app_name = app_name.or_else(|| $request.app_name());
+        $request
+            .router()
+            .reverse(app_name, view_name, &$crate::reverse_param_map!($( $($key = $value),* )?))
+    }};
+    ($request:expr, $route:expr; query = $query:expr) => {
+        $crate::reverse!($request, $route).and_then(|url| $crate::router::append_query(url, $query))
+    };
+    ($request:expr, $route:expr) => {{
+        #[allow(unused_imports)] // allow using either `Request` or `Urls` objects
+        use $crate::request::RequestExt;
+        $request.router().reverse_route($request.app_name(), &$route)
+    }};
+}
+
+/// Get an absolute URL (including the scheme and the host) for a view by its
+/// registered name and given params.
+///
+/// This macro accepts the same arguments as the [`reverse!`] macro, including
+/// the query parameters. The relative URL is turned into an absolute one with
+/// [`RequestExt::absolute_url`](crate::request::RequestExt::absolute_url), so
+/// the base URL is taken from the
+/// [`base_url`](crate::config::ServerConfig::base_url) config option if it's
+/// set, or from the request (validated against the
+/// [`allowed_hosts`](crate::config::ServerConfig::allowed_hosts)) otherwise.
+/// Because of that, unlike [`reverse!`], it can't be used with [`Urls`].
+///
+/// # Return value
+///
+/// Returns a [`cot::Result<String>`] that contains the absolute URL for the
+/// view. You will typically want to append `?` to the macro call to get the
+/// URL.
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::Request;
+/// use cot::response::Response;
+/// use cot::reverse_absolute;
+/// use cot::router::{Route, Router};
+///
+/// async fn posts(request: Request) -> cot::Result<Response> {
+///     let next_page = reverse_absolute!(request, "posts"; query = &[("page", 2)])?;
+///     // e.g. `https://example.com/posts?page=2`
+///     # unimplemented!()
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler_and_name("/posts", posts, "posts")]);
+/// ```
+#[macro_export]
+macro_rules! reverse_absolute {
+    ($request:expr, $($args:tt)*) => {
+        $crate::reverse!($request, $($args)*).and_then(|url| {
+            use $crate::request::RequestExt;
+            $request.absolute_url(&url)
+        })
+    };
+}
+
+/// A helper structure to allow reversing URLs from a request handler.
+///
+/// This is mainly useful as an extractor to allow reversing URLs without
+/// access to a full [`Request`] object.
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::Request;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::router::{Route, Router, Urls};
+/// use cot::test::TestRequestBuilder;
+/// use cot::{Body, RequestHandler, StatusCode, reverse};
+///
+/// async fn my_handler(urls: Urls) -> cot::Result<Response> {
+///     let url = reverse!(urls, "home")?;
+///     Ok(Response::new_html(
+///         StatusCode::OK,
+///         Body::fixed(format!("{url}")),
+///     ))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let router = Router::with_urls([Route::with_handler_and_name("/", my_handler, "home")]);
+/// let request = TestRequestBuilder::get("/").router(router).build();
+///
+/// assert_eq!(
+///     my_handler
+///         .handle(request)
+///         .await?
+///         .into_body()
+///         .into_bytes()
+///         .await?,
+///     "/"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Urls {
+    app_name: Option<String>,
+    router: Arc<Router>,
+}
+
+impl Urls {
+    /// Create a new `Urls` object from a [`Request`] object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::{Response, ResponseExt};
+    /// use cot::router::Urls;
+    /// use cot::{Body, StatusCode, reverse};
+    ///
+    /// async fn my_handler(request: Request) -> cot::Result<Response> {
+    ///     let urls = Urls::from_request(&request);
+    ///     let url = reverse!(urls, "home")?;
+    ///     Ok(Response::new_html(
+    ///         StatusCode::OK,
+    ///         Body::fixed(format!("Hello! The URL for this view is: {}", url)),
+    ///     ))
+    /// }
+    /// ```
+    pub fn from_request(request: &Request) -> Self {
+        Self {
+            app_name: request.app_name().map(ToOwned::to_owned),
+            router: Arc::clone(request.router()),
+        }
+    }
+
+    pub(crate) fn from_parts(request_parts: &Parts) -> Self {
+        Self {
+            app_name: request_parts.app_name().map(ToOwned::to_owned),
+            router: Arc::clone(request_parts.router()),
+        }
+    }
+
+    /// Get the app name the current route belongs to, or [`None`] if the
+    /// request is not routed.
+    ///
+    /// This is mainly useful for providing context to reverse redirects, where
+    /// you want to redirect to a route in the same app.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    /// use cot::router::Urls;
+    ///
+    /// async fn my_handler(urls: Urls) -> cot::Result<Response> {
+    ///     let app_name = urls.app_name();
+    ///     // ... do something with the app name
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn app_name(&self) -> Option<&str> {
+        self.app_name.as_deref()
+    }
+
+    /// Get the router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    /// use cot::router::Urls;
+    ///
+    /// async fn my_handler(urls: Urls) -> cot::Result<Response> {
+    ///     let router = urls.router();
+    ///     // ... do something with the router
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn router(&self) -> &Router {
+        &self.router
+    }
+
+    /// Create a `303 See Other` redirect response to the route with the
+    /// given name and parameters.
+    ///
+    /// The name can be prefixed with an app name, such as `"blog:post"`;
+    /// otherwise, the route is searched for in the app the current route
+    /// belongs to first. This is the method equivalent of the
+    /// [`reverse_redirect!`](crate::reverse_redirect) macro, useful when
+    /// the route name or the parameters are only known at runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no route with the given name, or if the
+    /// URL cannot be generated because of missing parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::router::path::ReverseParamMap;
+    /// use cot::router::{Route, Router, Urls};
+    /// use cot::test::TestRequestBuilder;
+    /// use cot::{RequestHandler, StatusCode};
+    ///
+    /// async fn old_post(urls: Urls) -> cot::Result<Response> {
+    ///     urls.redirect_to_route("post", &ReverseParamMap::from([("id", 42)]))
+    /// }
+    ///
+    /// # async fn post() -> cot::Result<Response> { unimplemented!() }
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let router = Router::with_urls([
+    ///     Route::with_handler("/old", old_post),
+    ///     Route::with_handler_and_name("/posts/{id}", post, "post"),
+    /// ]);
+    /// let request = TestRequestBuilder::get("/old").router(router).build();
+    ///
+    /// let response = old_post.handle(request).await?;
+    /// assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    /// assert_eq!(response.headers()["location"], "/posts/42");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn redirect_to_route(&self, name: &str, params: &ReverseParamMap) -> Result<Response> {
+        let (app_name, view_name) = split_view_name(name);
+        let app_name = app_name.or(self.app_name());
+        let url = self.router.reverse(app_name, view_name, params)?;
+
+        Ok(Response::redirect_see_other(url))
+    }
+}
+
+impl Debug for RouteInner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            RouteInner::Handler(_) => f.debug_tuple("Handler").field(&"handler(...)").finish(),
+            RouteInner::Methods(MethodsHandler(methods)) => {
+                f.debug_tuple("Methods").field(methods).finish()
+            }
+            RouteInner::Service(_) => f.debug_tuple("Service").field(&"service(...)").finish(),
+            RouteInner::Router(router) => f.debug_tuple("Router").field(router).finish(),
+        }
+    }
+}
+
+/// Get a URL for a view by its registered name and given params and return a
+/// response with a redirect.
+///
+/// This macro is a shorthand for creating a response with a redirect to a URL
+/// generated by the [`reverse!`] macro.
+///
+/// # Return value
+///
+/// Returns a [`cot::Result<Response>`] that contains the URL for
+/// the view. You will typically want to append `?` to the macro call to get the
+/// [`Response`] object.
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::Request;
+/// use cot::response::Response;
+/// use cot::reverse_redirect;
+/// use cot::router::{Route, Router};
+///
+/// async fn infinite_loop(request: Request) -> cot::Result<Response> {
+///     Ok(reverse_redirect!(request, "home")?)
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler_and_name("/", infinite_loop, "home")]);
+/// ```
+#[macro_export]
+macro_rules! reverse_redirect {
+    ($request:expr, $view_name:literal $(, $($key:ident = $value:expr),*)?) => {
+        $crate::reverse!(
+            $request,
+            $view_name,
+            $( $($key = $value),* )?
+        ).map(|url| <$crate::response::Response as $crate::response::ResponseExt>::new_redirect(url))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::middleware::{Next, from_fn};
+    use crate::request::extractors::Path;
+    use crate::request::{Request, RequestBodyExt};
+    use crate::response::{Response, ResponseExt};
+    use crate::test::TestRequestBuilder;
+    use crate::{Body, StatusCode};
+
+    struct MockHandler;
+
+    impl RequestHandler for MockHandler {
+        async fn handle(&self, _request: Request) -> Result<Response> {
+            Ok(Response::new_html(
+                StatusCode::OK,
+                Body::fixed(Bytes::from("OK")),
+            ))
+        }
+    }
+
+    #[test]
+    fn router_with_urls() {
+        let route = Route::with_handler("/test", MockHandler);
+        let router = Router::with_urls(vec![route.clone()]);
+        assert_eq!(router.routes().len(), 1);
+    }
+
+    #[cot::test]
+    async fn router_route() {
+        let route = Route::with_handler("/test", MockHandler);
+        let router = Router::with_urls(vec![route.clone()]);
+        let response = router.route(test_request(), "/test").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn router_handle() {
+        let route = Route::with_handler("/test", MockHandler);
+        let router = Router::with_urls(vec![route.clone()]);
+        let response = router.handle(test_request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn sub_router_handle() {
+        let route_1 = Route::with_handler("/test", MockHandler);
+        let sub_router_1 = Router::with_urls(vec![route_1.clone()]);
+        let route_2 = Route::with_handler("/test", MockHandler);
+        let sub_router_2 = Router::with_urls(vec![route_2.clone()]);
+
+        let router = Router::with_urls(vec![
+            Route::with_router("/", sub_router_1),
+            Route::with_router("/sub", sub_router_2),
+        ]);
+        let response = router
+            .handle(TestRequestBuilder::get("/sub/test").build())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    fn echo_path_service()
+    -> impl Service<Request, Response = Response, Error = std::convert::Infallible, Future: Send>
+    + Clone
+    + Send
+    + Sync
+    + 'static {
+        tower::service_fn(|request: Request| async move {
+            let path_and_query = request.uri().path_and_query().unwrap().to_string();
+            Ok(Response::new_html(
+                StatusCode::OK,
+                Body::fixed(path_and_query),
+            ))
+        })
+    }
+
+    #[cot::test]
+    async fn router_service_normalizes_text() {
+        async fn handler(_request: Request) -> Result<Response> {
+            Ok(http::Response::builder()
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(Body::fixed("\u{feff}hello"))
+                .unwrap())
+        }
+
+        let router = Arc::new(Router::with_urls(vec![Route::with_handler("/", handler)]));
+        let config = crate::config::ProjectConfig::builder()
+            .response(
+                crate::config::ResponseConfig::builder()
+                    .text_normalization(
+                        crate::config::TextNormalizationConfig::builder()
+                            .strip_bom(true)
+                            .trailing_newline(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+        let request = TestRequestBuilder::get("/").config(config).build();
+
+        let response = RouterService::new(router).oneshot(request).await.unwrap();
+
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "hello\n");
+    }
+
+    #[cot::test]
+    async fn router_cache_policy() {
+        let router = Router::with_urls(vec![
+            Route::with_handler("/cached", MockHandler).cache_policy(
+                CachePolicy::cacheable(std::time::Duration::from_secs(300))
+                    .vary([http::header::ACCEPT_ENCODING]),
+            ),
+            Route::with_handler("/uncached", MockHandler),
+        ]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/cached").build())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers().get(http::header::CACHE_CONTROL).unwrap(),
+            "public, max-age=300"
+        );
+        assert_eq!(
+            response.headers().get(http::header::VARY).unwrap(),
+            "accept-encoding"
+        );
+
+        let response = router
+            .handle(TestRequestBuilder::get("/uncached").build())
+            .await
+            .unwrap();
+        assert!(
+            response
+                .headers()
+                .get(http::header::CACHE_CONTROL)
+                .is_none()
+        );
+        assert!(response.headers().get(http::header::VARY).is_none());
+    }
+
+    async fn echo(request: Request) -> Result<Response> {
+        let body = request.into_body().into_bytes().await?;
+        Ok(Response::new_html(StatusCode::OK, Body::fixed(body)))
+    }
+
+    fn post_request(url: &str, body: &'static str) -> Request {
+        let mut request = TestRequestBuilder::post(url).build();
+        *request.body_mut() = Body::fixed(body);
+        request
+    }
+
+    #[cot::test]
+    async fn router_body_limit() {
+        let router = Router::with_urls(vec![
+            Route::with_handler("/small", echo),
+            Route::with_handler("/upload", echo).body_limit(1024),
+        ]);
+        let service = crate::middleware::BodyLimitMiddleware::new(16)
+            .layer(RouterService::new(Arc::new(router)));
+        let medium_body = "a medium-sized request body";
+
+        let response = service
+            .clone()
+            .oneshot(post_request("/small", medium_body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = service
+            .clone()
+            .oneshot(post_request("/small", "tiny"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = service
+            .oneshot(post_request("/upload", medium_body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            medium_body
+        );
+    }
+
+    async fn hello(_request: Request) -> Result<Response> {
+        Ok(Response::new_html(StatusCode::OK, Body::fixed("hello")))
+    }
+
+    async fn tag_inner(request: Request, next: Next) -> Result<Response> {
+        tag_response(request, next, "inner").await
+    }
+
+    async fn tag_outer(request: Request, next: Next) -> Result<Response> {
+        tag_response(request, next, "outer").await
+    }
+
+    async fn tag_response(request: Request, next: Next, tag: &'static str) -> Result<Response> {
+        let route = request.route_name().unwrap_or("none").to_owned();
+        let mut response = next.run(request).await?;
+        let headers = response.headers_mut();
+        headers.append("x-tag", http::HeaderValue::from_static(tag));
+        headers.insert("x-route", http::HeaderValue::try_from(route).unwrap());
+        Ok(response)
+    }
+
+    fn tags(response: &Response) -> Vec<&str> {
+        response
+            .headers()
+            .get_all("x-tag")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect()
+    }
+
+    #[cot::test]
+    async fn route_middleware() {
+        let router = Router::with_urls(vec![
+            Route::with_handler_and_name("/tagged", hello, "tagged").middleware(from_fn(tag_inner)),
+            Route::with_handler("/untagged", hello),
+        ]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/tagged").build())
+            .await
+            .unwrap();
+        assert_eq!(tags(&response), ["inner"]);
+        assert_eq!(response.headers()["x-route"], "tagged");
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "hello");
+
+        let response = router
+            .handle(TestRequestBuilder::get("/untagged").build())
+            .await
+            .unwrap();
+        assert!(tags(&response).is_empty());
+    }
+
+    #[cot::test]
+    async fn route_middleware_order() {
+        let router = Router::with_urls(vec![
+            Route::with_handler("/", hello)
+                .middleware(from_fn(tag_inner))
+                .middleware(from_fn(tag_outer)),
+        ]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap();
+        assert_eq!(tags(&response), ["inner", "outer"]);
+    }
+
+    #[cot::test]
+    async fn router_middleware() {
+        let admin = Router::with_urls(vec![
+            Route::with_handler_and_name("/users", hello, "users"),
+            Route::with_router(
+                "/nested",
+                Router::with_urls(vec![Route::with_handler("/page", hello)]),
+            ),
+            Route::with_service(
+                "/service",
+                tower::service_fn(|_req: Request| async {
+                    Ok::<_, Error>(Response::new(Body::fixed("service")))
+                }),
+            ),
+        ])
+        .middleware(from_fn(tag_inner));
+        let router = Router::with_urls(vec![
+            Route::with_router("/admin", admin),
+            Route::with_handler("/public", hello),
+        ]);
+
+        for path in ["/admin/users", "/admin/nested/page", "/admin/service/x"] {
+            let response = router
+                .handle(TestRequestBuilder::get(path).build())
+                .await
+                .unwrap();
+            assert_eq!(tags(&response), ["inner"], "{path}");
+        }
+
+        let response = router
+            .handle(TestRequestBuilder::get("/public").build())
+            .await
+            .unwrap();
+        assert!(tags(&response).is_empty());
+
+        let response = router
+            .handle(TestRequestBuilder::get("/admin/missing").build())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(tags(&response).is_empty());
+    }
+
+    #[cot::test]
+    async fn router_buffered_body_too_large() {
+        async fn buffered(mut request: Request) -> Result<Response> {
+            let body = request.buffered().await?;
+            Ok(Response::new_html(StatusCode::OK, Body::fixed(body)))
+        }
+
+        let router = Router::with_urls(vec![Route::with_handler("/buffered", buffered)]);
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .max_buffered_body_size(4)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::post("/buffered").config(config).build();
+        *request.body_mut() =
+            Body::streaming(futures::stream::once(async { Ok(Bytes::from("too long")) }));
+
+        let response = router.handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cot::test]
+    async fn router_body_limit_without_middleware() {
+        let sub_router = Router::with_urls(vec![Route::with_handler("/echo", echo)]);
+        let router = Router::with_urls(vec![
+            Route::with_router("/limited", sub_router).body_limit(4),
+            Route::with_handler("/unlimited", echo),
+        ]);
+
+        let response = router
+            .handle(post_request("/limited/echo", "hello"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = router
+            .handle(post_request("/unlimited", "hello"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn router_cache_policy_sub_router() {
+        let sub_router = Router::with_urls(vec![
+            Route::with_handler("/default", MockHandler),
+            Route::with_handler("/own", MockHandler).cache_policy(CachePolicy::new().no_store()),
+        ]);
+        let router = Router::with_urls(vec![
+            Route::with_router("/sub", sub_router).cache_policy(CachePolicy::new().private()),
+        ]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/sub/default").build())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers().get(http::header::CACHE_CONTROL).unwrap(),
+            "private"
+        );
+
+        let response = router
+            .handle(TestRequestBuilder::get("/sub/own").build())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers().get(http::header::CACHE_CONTROL).unwrap(),
+            "no-store"
+        );
+    }
+
+    #[cot::test]
+    async fn router_service_strips_prefix() {
+        let router = Router::with_urls(vec![Route::with_service("/api", echo_path_service())]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/api/users?page=2").build())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert_eq!(body, "/users?page=2");
+
+        let response = router
+            .handle(TestRequestBuilder::get("/api").build())
+            .await
+            .unwrap();
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert_eq!(body, "/");
     }
 
-    /// Get the app name the current route belongs to, or [`None`] if the
-    /// request is not routed.
-    ///
-    /// This is mainly useful for providing context to reverse redirects, where
-    /// you want to redirect to a route in the same app.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use cot::request::{Request, RequestExt};
-    /// use cot::response::Response;
-    /// use cot::router::Urls;
-    ///
-    /// async fn my_handler(urls: Urls) -> cot::Result<Response> {
-    ///     let app_name = urls.app_name();
-    ///     // ... do something with the app name
-    ///     # unimplemented!()
-    /// }
-    /// ```
-    #[must_use]
-    pub fn app_name(&self) -> Option<&str> {
-        self.app_name.as_deref()
+    #[cot::test]
+    async fn router_service_prefix_boundary() {
+        let router = Router::with_urls(vec![Route::with_service("/api", echo_path_service())]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/apiv2").build())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
-    /// Get the router.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use cot::request::{Request, RequestExt};
-    /// use cot::response::Response;
-    /// use cot::router::Urls;
-    ///
-    /// async fn my_handler(urls: Urls) -> cot::Result<Response> {
-    ///     let router = urls.router();
-    ///     // ... do something with the router
-    ///     # unimplemented!()
-    /// }
-    /// ```
-    #[must_use]
-    pub fn router(&self) -> &Router {
-        &self.router
+    #[cot::test]
+    async fn router_service_in_sub_router() {
+        let sub_router = Router::with_urls(vec![Route::with_service(
+            "/{version}/grpc",
+            echo_path_service(),
+        )]);
+        let router = Router::with_urls(vec![Route::with_router("/api", sub_router)]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/api/v1/grpc/pkg.Service/Method").build())
+            .await
+            .unwrap();
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert_eq!(body, "/pkg.Service/Method");
     }
-}
 
-impl Debug for RouteInner {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match &self {
-            RouteInner::Handler(_) => f.debug_tuple("Handler").field(&"handler(...)").finish(),
-            RouteInner::Router(router) => f.debug_tuple("Router").field(router).finish(),
-        }
+    #[cot::test]
+    async fn router_service_error() {
+        #[derive(std::fmt::Debug, thiserror::Error)]
+        #[error("service failed")]
+        struct ServiceError;
+
+        let service =
+            tower::service_fn(|_request: Request| async move { Err::<Response, _>(ServiceError) });
+        let router = Router::with_urls(vec![Route::with_service("/", service)]);
+
+        let error = router
+            .handle(TestRequestBuilder::get("/").build())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("service failed"));
     }
-}
 
-/// Get a URL for a view by its registered name and given params and return a
-/// response with a redirect.
-///
-/// This macro is a shorthand for creating a response with a redirect to a URL
-/// generated by the [`reverse!`] macro.
-///
-/// # Return value
-///
-/// Returns a [`cot::Result<Response>`] that contains the URL for
-/// the view. You will typically want to append `?` to the macro call to get the
-/// [`Response`] object.
-///
-/// # Examples
-///
-/// ```
-/// use cot::request::Request;
-/// use cot::response::Response;
-/// use cot::reverse_redirect;
-/// use cot::router::{Route, Router};
-///
-/// async fn infinite_loop(request: Request) -> cot::Result<Response> {
-///     Ok(reverse_redirect!(request, "home")?)
-/// }
-///
-/// let router = Router::with_urls([Route::with_handler_and_name("/", infinite_loop, "home")]);
-/// ```
-#[macro_export]
-macro_rules! reverse_redirect {
-    ($request:expr, $view_name:literal $(, $($key:ident = $value:expr),*)?) => {
-        $crate::reverse!(
-            $request,
-            $view_name,
-            $( $($key = $value),* )?
-        ).map(|url| <$crate::response::Response as $crate::response::ResponseExt>::new_redirect(url))
-    };
-}
+    #[cot::test]
+    async fn router_typed_and_catch_all_params() {
+        async fn post(Path(id): Path<i64>) -> Result<Response> {
+            Ok(Response::new(Body::fixed(format!("post {id}"))))
+        }
+        async fn file(Path(path): Path<String>) -> Result<Response> {
+            Ok(Response::new(Body::fixed(format!("file {path}"))))
+        }
 
-#[cfg(test)]
-mod tests {
-    use bytes::Bytes;
+        let router = Router::with_urls(vec![
+            Route::with_handler("/posts/{id:i64}", post),
+            Route::with_handler("/files/{*path}", file),
+        ]);
 
-    use super::*;
-    use crate::request::Request;
-    use crate::response::{Response, ResponseExt};
-    use crate::test::TestRequestBuilder;
-    use crate::{Body, StatusCode};
+        let response = router
+            .handle(TestRequestBuilder::get("/posts/42").build())
+            .await
+            .unwrap();
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "post 42");
+        let response = router
+            .handle(TestRequestBuilder::get("/posts/latest").build())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
 
-    struct MockHandler;
+        let response = router
+            .handle(TestRequestBuilder::get("/files/css/style.css").build())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "file css/style.css"
+        );
+    }
 
-    impl RequestHandler for MockHandler {
-        async fn handle(&self, _request: Request) -> Result<Response> {
-            Ok(Response::new_html(
-                StatusCode::OK,
-                Body::fixed(Bytes::from("OK")),
-            ))
+    #[cot::test]
+    async fn router_host() {
+        async fn tenant(Path((tenant, page)): Path<(String, String)>) -> Result<Response> {
+            Ok(Response::new(Body::fixed(format!("{tenant} {page}"))))
         }
-    }
 
-    #[test]
-    fn router_with_urls() {
-        let route = Route::with_handler("/test", MockHandler);
-        let router = Router::with_urls(vec![route.clone()]);
-        assert_eq!(router.routes().len(), 1);
+        let tenant_router = Router::with_urls(vec![Route::with_handler("/{page}", tenant)]);
+        let router = Router::with_urls(vec![
+            Route::with_router("/t", tenant_router).host("{tenant}.example.com"),
+            Route::with_handler("/t/{page}", hello),
+        ]);
+
+        let mut request = TestRequestBuilder::get("/t/settings").build();
+        request.headers_mut().insert(
+            http::header::HOST,
+            http::HeaderValue::from_static("Acme.example.com:8000"),
+        );
+        let response = router.handle(request).await.unwrap();
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "acme settings"
+        );
+
+        for host in ["example.com", "acme.example.org"] {
+            let mut request = TestRequestBuilder::get("/t/settings").build();
+            request
+                .headers_mut()
+                .insert(http::header::HOST, http::HeaderValue::from_static(host));
+            let response = router.handle(request).await.unwrap();
+            assert_eq!(
+                response.into_body().into_bytes().await.unwrap(),
+                "hello",
+                "{host}"
+            );
+        }
     }
 
     #[cot::test]
-    async fn router_route() {
-        let route = Route::with_handler("/test", MockHandler);
-        let router = Router::with_urls(vec![route.clone()]);
-        let response = router.route(test_request(), "/test").await.unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
+    async fn router_trailing_slash() {
+        async fn handle(
+            router: &Router,
+            policy: TrailingSlash,
+            mut request: TestRequestBuilder,
+        ) -> Response {
+            let config = crate::config::ProjectConfig::builder()
+                .server(
+                    crate::config::ServerConfig::builder()
+                        .trailing_slash(policy)
+                        .build(),
+                )
+                .build();
+            router.handle(request.config(config).build()).await.unwrap()
+        }
+
+        let router = Router::with_urls(vec![
+            Route::with_handler("/about/", hello),
+            Route::with_handler("/contact", hello),
+        ]);
+
+        let response = handle(
+            &router,
+            TrailingSlash::AppendSlash,
+            TestRequestBuilder::get("/about?lang=en"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers()[http::header::LOCATION],
+            "/about/?lang=en"
+        );
+        let response = handle(
+            &router,
+            TrailingSlash::AppendSlash,
+            TestRequestBuilder::post("/about"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        let response = handle(
+            &router,
+            TrailingSlash::AppendSlash,
+            TestRequestBuilder::get("/contact/"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = handle(
+            &router,
+            TrailingSlash::StripSlash,
+            TestRequestBuilder::get("/contact/"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(response.headers()[http::header::LOCATION], "/contact");
+
+        for (policy, path) in [
+            (TrailingSlash::Strict, "/about"),
+            (TrailingSlash::Strict, "/contact/"),
+            (TrailingSlash::AppendSlash, "/missing"),
+            (TrailingSlash::StripSlash, "/missing/"),
+        ] {
+            let response = handle(&router, policy, TestRequestBuilder::get(path)).await;
+            assert_eq!(
+                response.status(),
+                StatusCode::NOT_FOUND,
+                "{policy:?} {path}"
+            );
+        }
     }
 
     #[cot::test]
-    async fn router_handle() {
-        let route = Route::with_handler("/test", MockHandler);
-        let router = Router::with_urls(vec![route.clone()]);
-        let response = router.handle(test_request()).await.unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
+    async fn router_with_methods() {
+        async fn list() -> Result<Response> {
+            Ok(Response::new(Body::fixed("list")))
+        }
+        async fn create() -> Result<Response> {
+            Ok(Response::new(Body::fixed("create")))
+        }
+
+        let router = Router::with_urls(vec![
+            Route::with_methods_and_name("/items", "items")
+                .get(list)
+                .post(create),
+        ]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/items").build())
+            .await
+            .unwrap();
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "list");
+        let response = router
+            .handle(TestRequestBuilder::post("/items").build())
+            .await
+            .unwrap();
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "create");
+
+        let mut request = TestRequestBuilder::get("/items").build();
+        *request.method_mut() = Method::PUT;
+        let response = router.handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            response.headers()[http::header::ALLOW],
+            "GET, HEAD, POST, OPTIONS"
+        );
+
+        assert_eq!(
+            router
+                .reverse(None, "items", &ReverseParamMap::new())
+                .unwrap(),
+            "/items"
+        );
     }
 
     #[cot::test]
-    async fn sub_router_handle() {
-        let route_1 = Route::with_handler("/test", MockHandler);
-        let sub_router_1 = Router::with_urls(vec![route_1.clone()]);
-        let route_2 = Route::with_handler("/test", MockHandler);
-        let sub_router_2 = Router::with_urls(vec![route_2.clone()]);
+    async fn router_scope() {
+        let router = Router::with_urls(vec![
+            Router::scope("/api")
+                .middleware(from_fn(tag_inner))
+                .route(Route::with_handler_and_name("/users", hello, "users"))
+                .scope(Router::scope("/v1").route(Route::with_handler("/items", hello)))
+                .into_route(),
+            Route::with_handler("/users", hello),
+        ]);
+
+        for path in ["/api/users", "/api/v1/items"] {
+            let response = router
+                .handle(TestRequestBuilder::get(path).build())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{path}");
+            assert_eq!(tags(&response), ["inner"], "{path}");
+        }
+
+        let response = router
+            .handle(TestRequestBuilder::get("/users").build())
+            .await
+            .unwrap();
+        assert!(tags(&response).is_empty());
+
+        assert_eq!(
+            router
+                .reverse(None, "users", &ReverseParamMap::new())
+                .unwrap(),
+            "/api/users"
+        );
+    }
+
+    #[test]
+    fn router_route_infos() {
+        let mut api = Router::with_urls(vec![
+            Route::with_methods("/items")
+                .get(hello)
+                .post(hello)
+                .host("{tenant}.example.com"),
+            Route::with_router(
+                "/v1",
+                Router::with_urls(vec![Route::with_handler_and_name("/users", hello, "users")]),
+            ),
+        ]);
+        api.set_app_name(AppName("api".to_owned()));
+        let router = Router::with_urls(vec![
+            Route::with_handler_and_name("/", hello, "index"),
+            Route::with_router("/api", api),
+        ]);
 
+        let route_infos = router.route_infos();
+
+        assert_eq!(route_infos.len(), 3);
+        assert_eq!(route_infos[0].path(), "/");
+        assert_eq!(route_infos[0].name(), Some("index"));
+        assert_eq!(route_infos[0].app_name(), None);
+        assert_eq!(route_infos[0].methods(), None);
+        assert!(route_infos[0].handler_type_name().ends_with("hello"));
+        assert_eq!(route_infos[1].path(), "/api/items");
+        assert_eq!(route_infos[1].app_name(), Some("api"));
+        assert_eq!(route_infos[1].host(), Some("{tenant}.example.com"));
+        assert_eq!(
+            route_infos[1].methods(),
+            Some(&[Method::GET, Method::POST][..])
+        );
+        assert_eq!(route_infos[2].path(), "/api/v1/users");
+        assert_eq!(route_infos[2].name(), Some("users"));
+        assert_eq!(route_infos[2].app_name(), Some("api"));
+        assert!(!route_infos[2].is_service());
+    }
+
+    #[cot::test]
+    async fn router_with_methods_middleware() {
         let router = Router::with_urls(vec![
-            Route::with_router("/", sub_router_1),
-            Route::with_router("/sub", sub_router_2),
+            Route::with_methods("/items")
+                .get(hello)
+                .middleware(from_fn(tag_inner)),
         ]);
+
         let response = router
-            .handle(TestRequestBuilder::get("/sub/test").build())
+            .handle(TestRequestBuilder::get("/items").build())
             .await
             .unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(tags(&response), ["inner"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "can only be added to routes created with `Route::with_methods`")]
+    fn route_method_builder_on_handler_route() {
+        let _ = Route::with_handler("/", MockHandler).get(MockHandler);
     }
 
     #[test]
@@ -1000,6 +3395,101 @@ mod tests {
         assert_eq!(url, "/test/123");
     }
 
+    #[test]
+    fn test_reverse_macro_query() {
+        #[derive(serde::Serialize)]
+        struct ListQuery {
+            page: u32,
+            sort: &'static str,
+        }
+
+        let router = Router::with_urls(vec![
+            Route::with_handler_and_name("/list", MockHandler, "list"),
+            Route::with_handler_and_name("/test/{id}", MockHandler, "test"),
+        ]);
+        let request = TestRequestBuilder::get("/").router(router).build();
+
+        let query = ListQuery {
+            page: 2,
+            sort: "name & date",
+        };
+        assert_eq!(
+            reverse!(request, "list"; query = &query).unwrap(),
+            "/list?page=2&sort=name+%26+date"
+        );
+        assert_eq!(
+            reverse!(request, "test", id = 1; query = &[("tab", "posts")]).unwrap(),
+            "/test/1?tab=posts"
+        );
+        let empty: [(&str, &str); 0] = [];
+        assert_eq!(reverse!(request, "list"; query = &empty).unwrap(), "/list");
+    }
+
+    #[test]
+    fn test_reverse_absolute_macro() {
+        let router = Router::with_urls(vec![Route::with_handler_and_name(
+            "/test/{id}",
+            MockHandler,
+            "test",
+        )]);
+        let config = crate::config::ProjectConfig::builder()
+            .server(
+                crate::config::ServerConfig::builder()
+                    .allowed_hosts(["example.com"])
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::get("/")
+            .router(router)
+            .config(config)
+            .build();
+        request.headers_mut().insert(
+            http::header::HOST,
+            http::HeaderValue::from_static("example.com"),
+        );
+
+        assert_eq!(
+            cot::reverse_absolute!(request, "test", id = 123).unwrap(),
+            "http://example.com/test/123"
+        );
+        assert_eq!(
+            cot::reverse_absolute!(request, "test", id = 123; query = &[("page", 2)]).unwrap(),
+            "http://example.com/test/123?page=2"
+        );
+    }
+
+    #[test]
+    fn test_reverse_macro_route_ref() {
+        #[derive(RouteRef)]
+        #[route(path = "/posts/{id:i64}/{*rest}", name = "post")]
+        struct PostRoute {
+            id: i64,
+            rest: &'static str,
+        }
+
+        #[derive(RouteRef)]
+        #[route(path = "/", name = "home", app = "main")]
+        struct HomeRoute;
+
+        let mut main = Router::with_urls(vec![HomeRoute::route(MockHandler)]);
+        main.set_app_name(AppName("main".to_owned()));
+        let router = Router::with_urls(vec![
+            PostRoute::route(MockHandler),
+            Route::with_router("/main", main),
+        ]);
+        let request = TestRequestBuilder::get("/").router(router).build();
+
+        assert_eq!(
+            reverse!(request, PostRoute { id: 5, rest: "a/b" }).unwrap(),
+            "/posts/5/a/b"
+        );
+        assert_eq!(
+            reverse!(request, PostRoute { id: 5, rest: "c" }; query = &[("page", 2)]).unwrap(),
+            "/posts/5/c?page=2"
+        );
+        assert_eq!(reverse!(request, HomeRoute).unwrap(), "/main/");
+    }
+
     #[test]
     fn test_reverse_redirect_macro() {
         let route = Route::with_handler_and_name("/test/{id}", MockHandler, "test");
@@ -1012,6 +3502,29 @@ mod tests {
         assert_eq!(response.headers().get("location").unwrap(), "/test/123");
     }
 
+    #[test]
+    fn urls_redirect_to_route() {
+        let route = Route::with_handler_and_name("/test/{id}", MockHandler, "test");
+        let router = Router::with_urls(vec![route]);
+        let request = TestRequestBuilder::get("/").router(router).build();
+        let urls = Urls::from_request(&request);
+
+        let response = urls
+            .redirect_to_route("test", &ReverseParamMap::from([("id", 123)]))
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers().get("location").unwrap(), "/test/123");
+
+        assert!(
+            urls.redirect_to_route("test", &ReverseParamMap::new())
+                .is_err()
+        );
+        assert!(
+            urls.redirect_to_route("nonexistent", &ReverseParamMap::new())
+                .is_err()
+        );
+    }
+
     fn test_request() -> Request {
         TestRequestBuilder::get("/test").build()
     }
@@ -0,0 +1,331 @@
+//! Declarative caching headers for routes.
+//!
+//! This module provides the [`CachePolicy`] type that can be attached to a
+//! route with [`Route::cache_policy`](crate::router::Route::cache_policy) to
+//! set the `Cache-Control` and `Vary` headers on the responses of the route
+//! without writing any header code in the handler.
+
+use std::time::Duration;
+
+use http::{HeaderName, HeaderValue, header};
+
+use crate::response::Response;
+
+/// The `Cache-Control` and `Vary` headers to set on the responses of a
+/// route.
+///
+/// The headers are only added to successful and redirect responses (i.e.
+/// with the status code below 400), and only if the handler hasn't already
+/// set them itself.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::http::header;
+/// use cot::request::Request;
+/// use cot::response::Response;
+/// use cot::router::cache::CachePolicy;
+/// use cot::router::{Route, Router};
+///
+/// async fn articles(request: Request) -> cot::Result<Response> {
+///     todo!()
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler("/articles", articles).cache_policy(
+///     CachePolicy::cacheable(Duration::from_secs(300)).vary([header::ACCEPT_ENCODING]),
+/// )]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CachePolicy {
+    directives: Vec<String>,
+    vary: Vec<HeaderName>,
+}
+
+impl CachePolicy {
+    /// Creates an empty [`CachePolicy`] that doesn't set any headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::header;
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new().vary([header::ACCEPT_LANGUAGE]);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a [`CachePolicy`] that allows the responses to be stored by
+    /// any cache for the given amount of time.
+    ///
+    /// This is equivalent to `Cache-Control: public, max-age=<max_age>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::cacheable(Duration::from_secs(300));
+    /// assert_eq!(policy.cache_control().unwrap(), "public, max-age=300");
+    /// ```
+    #[must_use]
+    pub fn cacheable(max_age: Duration) -> Self {
+        Self::new().public().max_age(max_age)
+    }
+
+    /// Adds the `public` directive to the `Cache-Control` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new().public();
+    /// assert_eq!(policy.cache_control().unwrap(), "public");
+    /// ```
+    #[must_use]
+    pub fn public(self) -> Self {
+        self.directive("public")
+    }
+
+    /// Adds the `private` directive to the `Cache-Control` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new().private();
+    /// assert_eq!(policy.cache_control().unwrap(), "private");
+    /// ```
+    #[must_use]
+    pub fn private(self) -> Self {
+        self.directive("private")
+    }
+
+    /// Adds the `no-cache` directive to the `Cache-Control` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new().no_cache();
+    /// assert_eq!(policy.cache_control().unwrap(), "no-cache");
+    /// ```
+    #[must_use]
+    pub fn no_cache(self) -> Self {
+        self.directive("no-cache")
+    }
+
+    /// Adds the `no-store` directive to the `Cache-Control` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new().no_store();
+    /// assert_eq!(policy.cache_control().unwrap(), "no-store");
+    /// ```
+    #[must_use]
+    pub fn no_store(self) -> Self {
+        self.directive("no-store")
+    }
+
+    /// Adds the `max-age` directive to the `Cache-Control` header.
+    ///
+    /// The duration is rounded down to whole seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new()
+    ///     .private()
+    ///     .max_age(Duration::from_secs(60));
+    /// assert_eq!(policy.cache_control().unwrap(), "private, max-age=60");
+    /// ```
+    #[must_use]
+    pub fn max_age(self, max_age: Duration) -> Self {
+        self.directive(format!("max-age={}", max_age.as_secs()))
+    }
+
+    /// Adds the given headers to the `Vary` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::header;
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new().vary([header::ACCEPT_ENCODING, header::ACCEPT_LANGUAGE]);
+    /// assert_eq!(
+    ///     policy.vary_header().unwrap(),
+    ///     "accept-encoding, accept-language"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn vary<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        for header in headers {
+            if !self.vary.contains(&header) {
+                self.vary.push(header);
+            }
+        }
+        self
+    }
+
+    /// Returns the value of the `Cache-Control` header set by this policy,
+    /// or `None` if the policy doesn't set it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// assert!(CachePolicy::new().cache_control().is_none());
+    /// assert_eq!(
+    ///     CachePolicy::new().no_store().cache_control().unwrap(),
+    ///     "no-store"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn cache_control(&self) -> Option<HeaderValue> {
+        join_header_values(self.directives.iter().map(String::as_str))
+    }
+
+    /// Returns the value of the `Vary` header set by this policy, or `None`
+    /// if the policy doesn't set it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::header;
+    /// use cot::router::cache::CachePolicy;
+    ///
+    /// assert!(CachePolicy::new().vary_header().is_none());
+    /// assert_eq!(
+    ///     CachePolicy::new()
+    ///         .vary([header::COOKIE])
+    ///         .vary_header()
+    ///         .unwrap(),
+    ///     "cookie"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn vary_header(&self) -> Option<HeaderValue> {
+        join_header_values(self.vary.iter().map(HeaderName::as_str))
+    }
+
+    fn directive(mut self, directive: impl Into<String>) -> Self {
+        self.directives.push(directive.into());
+        self
+    }
+
+    /// Sets the headers on the response, unless the response is an error or
+    /// already has them.
+    pub(crate) fn apply(&self, response: &mut Response) {
+        if response.status().is_client_error() || response.status().is_server_error() {
+            return;
+        }
+
+        let headers = response.headers_mut();
+        if let Some(cache_control) = self.cache_control() {
+            headers
+                .entry(header::CACHE_CONTROL)
+                .or_insert(cache_control);
+        }
+        if let Some(vary) = self.vary_header() {
+            headers.entry(header::VARY).or_insert(vary);
+        }
+    }
+}
+
+fn join_header_values<'a>(values: impl Iterator<Item = &'a str>) -> Option<HeaderValue> {
+    let joined = values.collect::<Vec<_>>().join(", ");
+    if joined.is_empty() {
+        None
+    } else {
+        Some(
+            HeaderValue::from_str(&joined)
+                .expect("cache directives and header names are valid header values"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::ResponseExt;
+    use crate::{Body, StatusCode};
+
+    #[test]
+    fn cache_policy_apply() {
+        let policy =
+            CachePolicy::cacheable(Duration::from_secs(300)).vary([header::ACCEPT_ENCODING]);
+        let mut response = Response::new_html(StatusCode::OK, Body::empty());
+
+        policy.apply(&mut response);
+
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=300"
+        );
+        assert_eq!(
+            response.headers().get(header::VARY).unwrap(),
+            "accept-encoding"
+        );
+    }
+
+    #[test]
+    fn cache_policy_apply_keeps_handler_headers() {
+        let policy =
+            CachePolicy::cacheable(Duration::from_secs(300)).vary([header::ACCEPT_ENCODING]);
+        let mut response = Response::new_html(StatusCode::OK, Body::empty());
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+        policy.apply(&mut response);
+
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-store"
+        );
+        assert_eq!(
+            response.headers().get(header::VARY).unwrap(),
+            "accept-encoding"
+        );
+    }
+
+    #[test]
+    fn cache_policy_apply_skips_errors() {
+        let policy = CachePolicy::cacheable(Duration::from_secs(300));
+        let mut response = Response::new_html(StatusCode::NOT_FOUND, Body::empty());
+
+        policy.apply(&mut response);
+
+        assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+    }
+
+    #[test]
+    fn cache_policy_vary_deduplicates() {
+        let policy = CachePolicy::new()
+            .vary([header::ACCEPT_ENCODING])
+            .vary([header::ACCEPT_ENCODING, header::COOKIE]);
+
+        assert_eq!(policy.vary_header().unwrap(), "accept-encoding, cookie");
+    }
+}
@@ -0,0 +1,168 @@
+//! Matching the `Host` of requests against host patterns.
+
+use std::fmt::Display;
+
+/// A pattern for the host names of requests, such as `{tenant}.example.com`.
+///
+/// The pattern consists of labels separated by dots; each label is either a
+/// literal (compared case-insensitively) or a parameter in braces, matching
+/// any single non-empty label.
+#[derive(Debug, Clone)]
+pub(super) struct HostMatcher {
+    labels: Vec<HostLabel>,
+}
+
+#[derive(Debug, Clone)]
+enum HostLabel {
+    Literal(String),
+    Param(String),
+}
+
+impl HostMatcher {
+    #[must_use]
+    pub(super) fn new(host_pattern: &str) -> Self {
+        let labels = host_pattern
+            .trim_end_matches('.')
+            .split('.')
+            .map(|label| {
+                if let Some(name) = label
+                    .strip_prefix('{')
+                    .and_then(|label| label.strip_suffix('}'))
+                {
+                    let name = name.trim();
+                    assert!(
+                        !name.is_empty()
+                            && name.starts_with(|ch: char| ch.is_alphabetic() || ch == '_')
+                            && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_'),
+                        "Invalid host parameter name: `{name}`"
+                    );
+                    HostLabel::Param(name.to_owned())
+                } else {
+                    assert!(
+                        !label.is_empty() && !label.contains(['{', '}']),
+                        "Invalid host pattern: `{host_pattern}`"
+                    );
+                    HostLabel::Literal(label.to_ascii_lowercase())
+                }
+            })
+            .collect();
+
+        Self { labels }
+    }
+
+    /// Matches the given host (which may include a port) against the pattern,
+    /// returning the values of the parameters if it matches.
+    #[must_use]
+    pub(super) fn capture(&self, host: &str) -> Option<Vec<(String, String)>> {
+        let host = strip_port(host).trim_end_matches('.');
+        let mut params = Vec::new();
+
+        let mut host_labels = host.split('.');
+        for label in &self.labels {
+            let host_label = host_labels.next().filter(|label| !label.is_empty())?;
+            match label {
+                HostLabel::Literal(literal) => {
+                    if !literal.eq_ignore_ascii_case(host_label) {
+                        return None;
+                    }
+                }
+                HostLabel::Param(name) => {
+                    params.push((name.clone(), host_label.to_ascii_lowercase()));
+                }
+            }
+        }
+        if host_labels.next().is_some() {
+            return None;
+        }
+
+        Some(params)
+    }
+}
+
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        // IPv6 address
+        return host
+            .split_once(']')
+            .map_or(host, |(address, _)| &host[..=address.len()]);
+    }
+    host.rsplit_once(':').map_or(host, |(host, _)| host)
+}
+
+impl Display for HostMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, label) in self.labels.iter().enumerate() {
+            if index > 0 {
+                f.write_str(".")?;
+            }
+            match label {
+                HostLabel::Literal(literal) => f.write_str(literal)?,
+                HostLabel::Param(name) => write!(f, "{{{name}}}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn host_matcher_literal() {
+        let matcher = HostMatcher::new("example.com");
+
+        assert_eq!(matcher.capture("example.com"), Some(params(&[])));
+        assert_eq!(matcher.capture("EXAMPLE.com:8000"), Some(params(&[])));
+        assert_eq!(matcher.capture("example.com."), Some(params(&[])));
+        assert_eq!(matcher.capture("www.example.com"), None);
+        assert_eq!(matcher.capture("example.org"), None);
+        assert_eq!(matcher.capture("com"), None);
+    }
+
+    #[test]
+    fn host_matcher_params() {
+        let matcher = HostMatcher::new("{tenant}.{region}.example.com");
+
+        assert_eq!(
+            matcher.capture("Acme.eu.example.com:443"),
+            Some(params(&[("tenant", "acme"), ("region", "eu")]))
+        );
+        assert_eq!(matcher.capture("acme.example.com"), None);
+        assert_eq!(matcher.capture(".eu.example.com"), None);
+        assert_eq!(matcher.capture("www.acme.eu.example.com"), None);
+    }
+
+    #[test]
+    fn host_matcher_ipv6() {
+        let matcher = HostMatcher::new("[::1]");
+
+        assert_eq!(matcher.capture("[::1]:8000"), Some(params(&[])));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid host parameter name: `1st`")]
+    fn host_matcher_invalid_param() {
+        let _ = HostMatcher::new("{1st}.example.com");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid host pattern: `example..com`")]
+    fn host_matcher_empty_label() {
+        let _ = HostMatcher::new("example..com");
+    }
+
+    #[test]
+    fn host_matcher_display() {
+        let matcher = HostMatcher::new("{tenant}.Example.com");
+
+        assert_eq!(matcher.to_string(), "{tenant}.example.com");
+    }
+}
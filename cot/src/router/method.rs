@@ -0,0 +1,503 @@
+//! Routing requests based on their HTTP method.
+//!
+//! This module provides the [`MethodRouter`] request handler that passes the
+//! request to one of the handlers registered for the request's HTTP method.
+
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use derive_more::with_trait::Debug;
+use http::{HeaderValue, Method};
+
+use crate::error_page::ErrorPageTrigger;
+use crate::handler::{BoxRequestHandler, RequestHandler, into_box_request_handler};
+use crate::request::{Request, RequestExt};
+use crate::response::{Response, ResponseExt};
+use crate::{Body, Result, StatusCode};
+
+/// A request handler that dispatches requests to different handlers based on
+/// their HTTP method.
+///
+/// If there is no handler registered for the request's method, a `405 Method
+/// Not Allowed` response with the `Allow` header listing the supported
+/// methods is returned. `HEAD` requests are passed to the `GET` handler
+/// (unless there is a `HEAD` handler registered), and the body of its
+/// response is dropped. `OPTIONS` requests are answered automatically with a
+/// `204 No Content` response containing the `Allow` header, unless there is
+/// an `OPTIONS` handler registered or this behavior is disabled with the
+/// [`auto_options`](crate::config::ServerConfig::auto_options) config option.
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::Request;
+/// use cot::response::Response;
+/// use cot::router::method::MethodRouter;
+/// use cot::router::{Route, Router};
+///
+/// async fn list_items(request: Request) -> cot::Result<Response> {
+///     todo!()
+/// }
+///
+/// async fn create_item(request: Request) -> cot::Result<Response> {
+///     todo!()
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler(
+///     "/items",
+///     MethodRouter::new().get(list_items).post(create_item),
+/// )]);
+/// ```
+#[derive(Clone)]
+pub struct MethodRouter {
+    handlers: Vec<(Method, Arc<dyn BoxRequestHandler + Send + Sync>)>,
+}
+
+impl MethodRouter {
+    /// Creates a new [`MethodRouter`] with no handlers registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// let router = MethodRouter::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Registers a handler for the given HTTP method.
+    ///
+    /// If there already is a handler registered for the method, it is
+    /// replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::Method;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// async fn search(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = MethodRouter::new().on(Method::from_bytes(b"QUERY").unwrap(), search);
+    /// ```
+    #[must_use]
+    pub fn on<HandlerParams, H>(mut self, method: Method, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        let handler: Arc<dyn BoxRequestHandler + Send + Sync> =
+            Arc::new(into_box_request_handler(handler));
+
+        if let Some(entry) = self.handlers.iter_mut().find(|(m, _)| *m == method) {
+            entry.1 = handler;
+        } else {
+            self.handlers.push((method, handler));
+        }
+        self
+    }
+
+    /// Registers a handler for `GET` requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = MethodRouter::new().get(index);
+    /// ```
+    #[must_use]
+    pub fn get<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::GET, handler)
+    }
+
+    /// Registers a handler for `POST` requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// async fn create(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = MethodRouter::new().post(create);
+    /// ```
+    #[must_use]
+    pub fn post<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::POST, handler)
+    }
+
+    /// Registers a handler for `PUT` requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// async fn replace(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = MethodRouter::new().put(replace);
+    /// ```
+    #[must_use]
+    pub fn put<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::PUT, handler)
+    }
+
+    /// Registers a handler for `PATCH` requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// async fn update(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = MethodRouter::new().patch(update);
+    /// ```
+    #[must_use]
+    pub fn patch<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::PATCH, handler)
+    }
+
+    /// Registers a handler for `DELETE` requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// async fn remove(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = MethodRouter::new().delete(remove);
+    /// ```
+    #[must_use]
+    pub fn delete<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::DELETE, handler)
+    }
+
+    /// Registers a handler for `OPTIONS` requests.
+    ///
+    /// This disables the automatic `OPTIONS` response for this router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// async fn preflight(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = MethodRouter::new().options(preflight);
+    /// ```
+    #[must_use]
+    pub fn options<HandlerParams, H>(self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.on(Method::OPTIONS, handler)
+    }
+
+    /// Returns the HTTP methods there are handlers registered for, in the
+    /// order they were registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http::Method;
+    /// use cot::request::Request;
+    /// use cot::response::Response;
+    /// use cot::router::method::MethodRouter;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     todo!()
+    /// }
+    ///
+    /// let router = MethodRouter::new().get(index);
+    /// assert_eq!(router.methods().collect::<Vec<_>>(), [&Method::GET]);
+    /// ```
+    pub fn methods(&self) -> impl Iterator<Item = &Method> {
+        self.handlers.iter().map(|(method, _)| method)
+    }
+
+    fn handler(&self, method: &Method) -> Option<&(dyn BoxRequestHandler + Send + Sync)> {
+        self.handlers
+            .iter()
+            .find(|(m, _)| m == method)
+            .map(|(_, handler)| &**handler)
+    }
+
+    fn allow_header(&self, auto_options: bool) -> HeaderValue {
+        let mut methods: Vec<&str> = Vec::new();
+        for method in self.methods() {
+            methods.push(method.as_str());
+            if method == Method::GET && self.handler(&Method::HEAD).is_none() {
+                methods.push(Method::HEAD.as_str());
+            }
+        }
+        if auto_options && self.handler(&Method::OPTIONS).is_none() {
+            methods.push(Method::OPTIONS.as_str());
+        }
+
+        HeaderValue::from_str(&methods.join(", "))
+            .expect("HTTP method names are valid header values")
+    }
+}
+
+impl Default for MethodRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for MethodRouter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MethodRouter")
+            .field("methods", &self.methods().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl RequestHandler for MethodRouter {
+    async fn handle(&self, request: Request) -> Result<Response> {
+        if let Some(handler) = self.handler(request.method()) {
+            return handler.handle(request).await;
+        }
+        if request.method() == Method::HEAD {
+            if let Some(handler) = self.handler(&Method::GET) {
+                return Ok(without_body(handler.handle(request).await?));
+            }
+        }
+
+        let auto_options = request.project_config().server.auto_options;
+        let status = if auto_options && request.method() == Method::OPTIONS {
+            StatusCode::NO_CONTENT
+        } else {
+            StatusCode::METHOD_NOT_ALLOWED
+        };
+
+        let allow = self.allow_header(auto_options);
+        let mut response = Response::new_html(status, Body::empty());
+        if status == StatusCode::METHOD_NOT_ALLOWED {
+            response
+                .extensions_mut()
+                .insert(ErrorPageTrigger::MethodNotAllowed {
+                    allow: allow.clone(),
+                });
+        }
+        response.headers_mut().insert(http::header::ALLOW, allow);
+        Ok(response)
+    }
+}
+
+/// Drops the body of a response to a `HEAD` request, keeping its length in
+/// the `Content-Length` header if it's known.
+fn without_body(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    if !parts.headers.contains_key(http::header::CONTENT_LENGTH) {
+        if let Some(length) = http_body::Body::size_hint(&body).exact() {
+            parts
+                .headers
+                .insert(http::header::CONTENT_LENGTH, length.into());
+        }
+    }
+
+    Response::from_parts(parts, Body::empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProjectConfig, ServerConfig};
+    use crate::test::TestRequestBuilder;
+
+    async fn handler_name(request: Request) -> Result<Response> {
+        Ok(Response::new_html(
+            StatusCode::OK,
+            Body::fixed(request.method().as_str().to_owned()),
+        ))
+    }
+
+    fn request(method: Method) -> Request {
+        let mut request = TestRequestBuilder::get("/").build();
+        *request.method_mut() = method;
+        request
+    }
+
+    #[cot::test]
+    async fn method_router_dispatch() {
+        let router = MethodRouter::new().get(handler_name).post(handler_name);
+
+        let response = router.handle(request(Method::POST)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "POST");
+    }
+
+    #[cot::test]
+    async fn method_router_auto_options() {
+        let router = MethodRouter::new().get(handler_name).post(handler_name);
+
+        let response = router.handle(request(Method::OPTIONS)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            response.headers().get(http::header::ALLOW).unwrap(),
+            "GET, HEAD, POST, OPTIONS"
+        );
+    }
+
+    #[cot::test]
+    async fn method_router_explicit_options() {
+        let router = MethodRouter::new().get(handler_name).options(handler_name);
+
+        let response = router.handle(request(Method::OPTIONS)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "OPTIONS");
+    }
+
+    #[cot::test]
+    async fn method_router_auto_options_disabled() {
+        let router = MethodRouter::new().get(handler_name).post(handler_name);
+        let config = ProjectConfig::builder()
+            .server(ServerConfig::builder().auto_options(false).build())
+            .build();
+        let mut request = TestRequestBuilder::get("/").config(config).build();
+        *request.method_mut() = Method::OPTIONS;
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            response.headers().get(http::header::ALLOW).unwrap(),
+            "GET, HEAD, POST"
+        );
+    }
+
+    #[cot::test]
+    async fn method_router_method_not_allowed() {
+        let router = MethodRouter::new().get(handler_name);
+
+        let response = router.handle(request(Method::DELETE)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            response.headers().get(http::header::ALLOW).unwrap(),
+            "GET, HEAD, OPTIONS"
+        );
+    }
+
+    #[cot::test]
+    async fn method_router_head_uses_get_handler() {
+        let router = MethodRouter::new().get(handler_name);
+
+        let response = router.handle(request(Method::HEAD)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CONTENT_LENGTH)
+                .unwrap(),
+            "4"
+        );
+        assert!(response.into_body().into_bytes().await.unwrap().is_empty());
+    }
+
+    #[cot::test]
+    async fn method_router_explicit_head() {
+        let router = MethodRouter::new()
+            .get(handler_name)
+            .on(Method::HEAD, handler_name);
+
+        let response = router.handle(request(Method::HEAD)).await.unwrap();
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "HEAD");
+
+        let response = router.handle(request(Method::DELETE)).await.unwrap();
+        assert_eq!(
+            response.headers().get(http::header::ALLOW).unwrap(),
+            "GET, HEAD, OPTIONS"
+        );
+    }
+
+    #[cot::test]
+    async fn method_router_head_without_get() {
+        let router = MethodRouter::new().post(handler_name);
+
+        let response = router.handle(request(Method::HEAD)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            response.headers().get(http::header::ALLOW).unwrap(),
+            "POST, OPTIONS"
+        );
+    }
+
+    #[test]
+    fn method_router_replace_handler() {
+        let router = MethodRouter::new()
+            .get(handler_name)
+            .post(handler_name)
+            .get(handler_name);
+
+        assert_eq!(
+            router.methods().collect::<Vec<_>>(),
+            [&Method::GET, &Method::POST]
+        );
+    }
+}
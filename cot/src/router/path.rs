@@ -3,6 +3,45 @@
 //! This module provides a path matcher that can be used to match paths against
 //! a given pattern. It also provides a way to reverse paths to their original
 //! form given a set of parameters.
+//!
+//! # Path patterns
+//!
+//! A path pattern consists of literal text and parameters enclosed in braces
+//! (literal braces can be escaped by doubling them, as in `{{` and `}}`):
+//!
+//! * `{name}` matches a single, non-empty path segment,
+//! * `{name:type}` matches a single path segment of the given type: `i64` (a
+//!   signed integer), `slug` (ASCII letters, digits, hyphens and underscores)
+//!   or `uuid` (a hyphenated UUID); a request whose segment isn't of the given
+//!   type doesn't match the route, so it results in `404 Not Found` unless some
+//!   other route matches it,
+//! * `{*name}` (a catch-all parameter) matches the non-empty rest of the path,
+//!   including slashes; it has to be at the end of the pattern.
+//!
+//! The values of the parameters can be retrieved with the
+//! [`Path`](crate::request::extractors::Path) extractor.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::request::Request;
+//! use cot::request::extractors::Path;
+//! use cot::response::Response;
+//! use cot::router::{Route, Router};
+//!
+//! async fn post(Path((id, slug)): Path<(i64, String)>) -> cot::Result<Response> {
+//!     todo!()
+//! }
+//!
+//! async fn file(Path(path): Path<String>) -> cot::Result<Response> {
+//!     todo!()
+//! }
+//!
+//! let router = Router::with_urls([
+//!     Route::with_handler("/posts/{id:i64}/{slug:slug}", post),
+//!     Route::with_handler("/files/{*path}", file),
+//! ]);
+//! ```
 
 use std::collections::HashMap;
 use std::fmt::Display;
@@ -43,6 +82,16 @@ impl PathMatcher {
             match (ch, state) {
                 (Some('{') | None, State::Literal { start }) => {
                     let literal = &path_pattern[start..index];
+                    assert!(
+                        !matches!(
+                            parts.last(),
+                            Some(PathPart::Param {
+                                kind: ParamKind::CatchAll,
+                                ..
+                            })
+                        ) || literal.is_empty(),
+                        "Catch-all parameters must be at the end of the pattern"
+                    );
                     if literal.is_empty() {
                         assert!(
                             index == 0 || ch.is_none(),
@@ -76,14 +125,24 @@ impl PathMatcher {
                     }
                 }
                 (Some('}'), State::Param { start }) => {
-                    let param_name = &path_pattern[start..index].trim();
+                    let param = path_pattern[start..index].trim();
+                    let (param_name, kind) = if let Some(param_name) = param.strip_prefix('*') {
+                        (param_name, ParamKind::CatchAll)
+                    } else if let Some((param_name, type_name)) = param.split_once(':') {
+                        let type_name = type_name.trim();
+                        let kind = ParamKind::from_type_name(type_name)
+                            .unwrap_or_else(|| panic!("Unknown parameter type: `{type_name}`"));
+                        (param_name.trim(), kind)
+                    } else {
+                        (param, ParamKind::Segment)
+                    };
                     assert!(
                         Self::is_param_name_valid(param_name),
                         "Invalid parameter name: `{param_name}`"
                     );
-
                     parts.push(PathPart::Param {
-                        name: (*param_name).to_string(),
+                        name: param_name.to_string(),
+                        kind,
                     });
                     state = State::Literal { start: index + 1 };
                 }
@@ -130,14 +189,13 @@ impl PathMatcher {
                     }
                     current_path = &current_path[s.len()..];
                 }
-                PathPart::Param { name } => {
+                PathPart::Param { name, kind } => {
                     let next_slash = current_path.find('/');
-                    let value = if let Some(next_slash) = next_slash {
-                        &current_path[..next_slash]
-                    } else {
-                        current_path
+                    let value = match (kind, next_slash) {
+                        (ParamKind::CatchAll, _) | (_, None) => current_path,
+                        (_, Some(next_slash)) => &current_path[..next_slash],
                     };
-                    if value.is_empty() {
+                    if value.is_empty() || !kind.matches(value) {
                         return None;
                     }
                     params.push(PathParam::new(name, value));
@@ -155,7 +213,7 @@ impl PathMatcher {
         for part in &self.parts {
             match part {
                 PathPart::Literal(s) => result.push_str(s),
-                PathPart::Param { name } => {
+                PathPart::Param { name, .. } => {
                     let value = params
                         .get(name)
                         .ok_or_else(|| ReverseError::MissingParam(name.clone()))?;
@@ -252,6 +310,16 @@ impl ReverseParamMap {
     }
 }
 
+impl<K: ToString, V: ToString, const N: usize> From<[(K, V); N]> for ReverseParamMap {
+    fn from(params: [(K, V); N]) -> Self {
+        let mut map = Self::new();
+        for (key, value) in params {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! reverse_param_map {
@@ -269,6 +337,9 @@ pub enum ReverseError {
     /// A parameter is missing for the reverse operation.
     #[error("Missing parameter for reverse: `{0}`")]
     MissingParam(String),
+    /// The query parameters could not be serialized.
+    #[error("Invalid query parameters for reverse: {0}")]
+    InvalidQuery(#[from] serde_html_form::ser::Error),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -295,7 +366,59 @@ impl<'matcher, 'path> CaptureResult<'matcher, 'path> {
 #[derive(Debug, Clone)]
 enum PathPart {
     Literal(String),
-    Param { name: String },
+    Param { name: String, kind: ParamKind },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ParamKind {
+    /// Any single path segment.
+    Segment,
+    /// A path segment containing a signed 64-bit integer.
+    I64,
+    /// A path segment containing ASCII letters, digits, hyphens and
+    /// underscores.
+    Slug,
+    /// A path segment containing a hyphenated UUID.
+    Uuid,
+    /// The rest of the path.
+    CatchAll,
+}
+
+impl ParamKind {
+    fn from_type_name(type_name: &str) -> Option<Self> {
+        match type_name {
+            "i64" => Some(Self::I64),
+            "slug" => Some(Self::Slug),
+            "uuid" => Some(Self::Uuid),
+            _ => None,
+        }
+    }
+
+    fn type_name(self) -> Option<&'static str> {
+        match self {
+            Self::I64 => Some("i64"),
+            Self::Slug => Some("slug"),
+            Self::Uuid => Some("uuid"),
+            Self::Segment | Self::CatchAll => None,
+        }
+    }
+
+    fn matches(self, value: &str) -> bool {
+        match self {
+            Self::Segment | Self::CatchAll => true,
+            Self::I64 => value.parse::<i64>().is_ok(),
+            Self::Slug => value
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_'),
+            Self::Uuid => {
+                value.len() == 36
+                    && value.bytes().enumerate().all(|(index, byte)| match index {
+                        8 | 13 | 18 | 23 => byte == b'-',
+                        _ => byte.is_ascii_hexdigit(),
+                    })
+            }
+        }
+    }
 }
 
 impl Display for PathPart {
@@ -305,7 +428,14 @@ impl Display for PathPart {
                 let s = s.replace('{', "{{").replace('}', "}}");
                 write!(f, "{s}")
             }
-            PathPart::Param { name } => write!(f, "{{{name}}}"),
+            PathPart::Param {
+                name,
+                kind: ParamKind::CatchAll,
+            } => write!(f, "{{*{name}}}"),
+            PathPart::Param { name, kind } => match kind.type_name() {
+                Some(type_name) => write!(f, "{{{name}:{type_name}}}"),
+                None => write!(f, "{{{name}}}"),
+            },
         }
     }
 }
@@ -471,6 +601,83 @@ mod tests {
         assert_eq!(format!("{path_parser}"), "/users/{id}/posts/{{escaped}}");
     }
 
+    #[test]
+    fn path_parser_typed_params() {
+        let path_parser = PathMatcher::new("/posts/{id:i64}/{slug:slug}/{key: uuid}");
+        assert_eq!(
+            path_parser.capture("/posts/-42/hello-world_2/67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            Some(CaptureResult::new(
+                vec![
+                    PathParam::new("id", "-42"),
+                    PathParam::new("slug", "hello-world_2"),
+                    PathParam::new("key", "67e55044-10b1-426f-9247-bb680e5fe0c8"),
+                ],
+                ""
+            ))
+        );
+        assert_eq!(
+            path_parser.capture("/posts/abc/hello/67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            None
+        );
+        assert_eq!(
+            path_parser.capture("/posts/1/hello%20world/67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            None
+        );
+        assert_eq!(
+            path_parser.capture("/posts/1/hello/67e55044-10b1-426f-9247-bb680e5fe0cx"),
+            None
+        );
+        assert_eq!(
+            path_parser.capture("/posts/1/hello/67e5504410b1426f9247bb680e5fe0c8"),
+            None
+        );
+    }
+
+    #[test]
+    fn path_parser_catch_all() {
+        let path_parser = PathMatcher::new("/files/{*path}");
+        assert_eq!(
+            path_parser.capture("/files/css/style.css"),
+            Some(CaptureResult::new(
+                vec![PathParam::new("path", "css/style.css")],
+                ""
+            ))
+        );
+        assert_eq!(
+            path_parser.capture("/files/dir/"),
+            Some(CaptureResult::new(vec![PathParam::new("path", "dir/")], ""))
+        );
+        assert_eq!(path_parser.capture("/files/"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Catch-all parameters must be at the end of the pattern")]
+    fn path_parser_catch_all_not_last() {
+        let _ = PathMatcher::new("/files/{*path}/edit");
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown parameter type: `f64`")]
+    fn path_parser_unknown_type() {
+        let _ = PathMatcher::new("/users/{id:f64}");
+    }
+
+    #[test]
+    fn path_parser_display_typed() {
+        let path_parser = PathMatcher::new("/posts/{ id : i64 }/{*rest}");
+        assert_eq!(format!("{path_parser}"), "/posts/{id:i64}/{*rest}");
+    }
+
+    #[test]
+    fn reverse_catch_all() {
+        let path_parser = PathMatcher::new("/files/{*path}");
+        let params = ReverseParamMap::from([("path", "css/style.css")]);
+        assert_eq!(
+            path_parser.reverse(&params).unwrap(),
+            "/files/css/style.css"
+        );
+    }
+
     #[test]
     fn reverse_with_valid_params() {
         let path_parser = PathMatcher::new("/users/{id}/posts/{post_id}");
@@ -0,0 +1,974 @@
+//! The HTTP server accept loop.
+//!
+//! This is a thin layer over [`hyper`] that is used by
+//! [`run_at`](crate::project::run_at) instead of [`axum::serve`] so that the
+//! connection-level settings from [`ServerConfig`] can be applied to every
+//! accepted connection.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use futures_util::FutureExt;
+use futures_util::future::{Either, Map, Ready, ready};
+use http::{StatusCode, header};
+use hyper::body::Incoming;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tower::{Service, ServiceExt};
+use tracing::{debug, error};
+
+use crate::config::{ServerBuffersConfig, ServerConfig};
+
+/// Serves HTTP/1 connections accepted from `listener` with `service` until
+/// `shutdown` completes, then waits for the open connections to finish.
+pub(crate) async fn serve<S>(
+    listener: TcpListener,
+    service: S,
+    config: &ServerConfig,
+    shutdown: impl Future<Output = ()>,
+) where
+    S: Service<axum::extract::Request, Response = axum::response::Response, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+{
+    let mut builder = hyper::server::conn::http1::Builder::new();
+    if let Some(max_buf_size) = config.buffers.http1_max_buf_size {
+        builder.max_buf_size(max_buf_size);
+    }
+
+    // hyper-util's `GracefulShutdown` doesn't support connections with upgrades
+    // (used by WebSockets), so the connections are notified about the shutdown
+    // through this channel instead
+    let (shutdown_tx, _) = tokio::sync::watch::channel(());
+    let mut shutdown = std::pin::pin!(shutdown);
+    let connection_counter = Arc::new(ConnectionCounter::default());
+
+    loop {
+        let (stream, remote_addr) = tokio::select! {
+            result = listener.accept() => match result {
+                Ok(connection) => connection,
+                Err(error) => {
+                    handle_accept_error(error).await;
+                    continue;
+                }
+            },
+            () = &mut shutdown => break,
+        };
+        debug!("Accepted connection from {remote_addr}");
+
+        let remote_ip = remote_addr.ip().to_canonical();
+        let connection_guard = match config.max_connections_per_ip {
+            Some(max_connections)
+                if !config
+                    .trusted_proxies
+                    .iter()
+                    .any(|proxy| proxy.contains(remote_ip)) =>
+            {
+                let Some(guard) = connection_counter.try_acquire(remote_ip, max_connections) else {
+                    debug!("Too many connections from {remote_ip}, closing the connection");
+                    continue;
+                };
+                Some(guard)
+            }
+            _ => None,
+        };
+
+        configure_stream(&stream, &config.buffers);
+
+        let service = UriLengthLimit::new(service.clone(), config.max_uri_length);
+        let service = RequestsPerConnectionLimit::new(service, config.max_requests_per_connection)
+            .map_request(move |request: http::Request<Incoming>| {
+                let mut request = request.map(axum::body::Body::new);
+                request.extensions_mut().insert(RemoteAddr(remote_addr));
+                request
+            });
+        let connection = builder
+            .serve_connection(
+                TokioIo::new(HttpVersionCheck::new(stream)),
+                TowerToHyperService::new(service),
+            )
+            .with_upgrades();
+        let mut shutdown_rx = shutdown_tx.subscribe();
+
+        tokio::spawn(async move {
+            let mut connection = std::pin::pin!(connection);
+            let result = tokio::select! {
+                result = connection.as_mut() => result,
+                _ = shutdown_rx.changed() => {
+                    connection.as_mut().graceful_shutdown();
+                    connection.await
+                }
+            };
+            if let Err(error) = result {
+                debug!("Failed to serve connection from {remote_addr}: {error}");
+            }
+            drop(connection_guard);
+        });
+    }
+
+    drop(listener);
+    // notify the connections, then wait until all of them drop their receivers
+    shutdown_tx.send_replace(());
+    shutdown_tx.closed().await;
+}
+
+/// The address of the peer a request was received from, added to the
+/// extensions of every request served by [`serve`].
+///
+/// When the application is running behind a reverse proxy, this is the
+/// address of the proxy rather than of the client.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct RemoteAddr(pub(crate) SocketAddr);
+
+/// The number of open connections per client IP address, shared by all the
+/// connections accepted by [`serve`].
+#[derive(Debug, Default)]
+struct ConnectionCounter {
+    connections: Mutex<HashMap<IpAddr, usize>>,
+}
+
+impl ConnectionCounter {
+    /// Registers a new connection from `ip`, unless there are already
+    /// `max_connections` connections open from it.
+    ///
+    /// The connection is unregistered when the returned guard is dropped.
+    fn try_acquire(
+        self: &Arc<Self>,
+        ip: IpAddr,
+        max_connections: usize,
+    ) -> Option<ConnectionGuard> {
+        let mut connections = self
+            .connections
+            .lock()
+            .expect("connection counter lock poisoned");
+        let count = connections.entry(ip).or_insert(0);
+        if *count >= max_connections {
+            if *count == 0 {
+                connections.remove(&ip);
+            }
+            return None;
+        }
+        *count += 1;
+
+        Some(ConnectionGuard {
+            counter: Arc::clone(self),
+            ip,
+        })
+    }
+
+    fn release(&self, ip: IpAddr) {
+        let mut connections = self
+            .connections
+            .lock()
+            .expect("connection counter lock poisoned");
+        if let Some(count) = connections.get_mut(&ip) {
+            *count -= 1;
+            if *count == 0 {
+                connections.remove(&ip);
+            }
+        }
+    }
+}
+
+/// A connection registered in a [`ConnectionCounter`].
+#[derive(Debug)]
+struct ConnectionGuard {
+    counter: Arc<ConnectionCounter>,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.counter.release(self.ip);
+    }
+}
+
+/// Rejects the requests with a URI longer than `max_uri_length` with a
+/// `414 URI Too Long` response and closes their connections.
+#[derive(Debug, Clone)]
+struct UriLengthLimit<S> {
+    inner: S,
+    max_uri_length: usize,
+}
+
+impl<S> UriLengthLimit<S> {
+    fn new(inner: S, max_uri_length: usize) -> Self {
+        Self {
+            inner,
+            max_uri_length,
+        }
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for UriLengthLimit<S>
+where
+    S: Service<http::Request<B>, Response = axum::response::Response, Error = Infallible>,
+{
+    type Response = S::Response;
+    type Error = Infallible;
+    type Future = Either<Ready<Result<Self::Response, Self::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let uri_length = uri_length(request.uri());
+        if uri_length > self.max_uri_length {
+            debug!(
+                "Rejecting request with URI length {uri_length} exceeding the limit of {}",
+                self.max_uri_length
+            );
+            return Either::Left(ready(Ok(uri_too_long_response())));
+        }
+
+        Either::Right(self.inner.call(request))
+    }
+}
+
+/// Closes the connection after `max_requests` requests have been served on it,
+/// by adding a `Connection: close` header to the response to the last one.
+///
+/// A new instance must be created for every connection.
+#[derive(Debug, Clone)]
+struct RequestsPerConnectionLimit<S> {
+    inner: S,
+    max_requests: Option<usize>,
+    // shared, because hyper clones the service for every request
+    served: Arc<AtomicUsize>,
+}
+
+impl<S> RequestsPerConnectionLimit<S> {
+    fn new(inner: S, max_requests: Option<usize>) -> Self {
+        Self {
+            inner,
+            max_requests,
+            served: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+type ResponseResult = Result<axum::response::Response, Infallible>;
+
+impl<S, B> Service<http::Request<B>> for RequestsPerConnectionLimit<S>
+where
+    S: Service<http::Request<B>, Response = axum::response::Response, Error = Infallible>,
+{
+    type Response = S::Response;
+    type Error = Infallible;
+    type Future = Either<S::Future, Map<S::Future, fn(ResponseResult) -> ResponseResult>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let Some(max_requests) = self.max_requests else {
+            return Either::Left(self.inner.call(request));
+        };
+
+        let served = self.served.fetch_add(1, Ordering::Relaxed) + 1;
+        if served < max_requests {
+            return Either::Left(self.inner.call(request));
+        }
+
+        debug!("Served {served} requests on the connection, closing it");
+        Either::Right(self.inner.call(request).map(close_connection))
+    }
+}
+
+fn close_connection(result: ResponseResult) -> ResponseResult {
+    result.map(|mut response| {
+        response
+            .headers_mut()
+            .insert(header::CONNECTION, http::HeaderValue::from_static("close"));
+        response
+    })
+}
+
+/// Returns the length of the URI as sent in the request line.
+fn uri_length(uri: &http::Uri) -> usize {
+    let scheme_length = uri
+        .scheme_str()
+        .map_or(0, |scheme| scheme.len() + "://".len());
+    let authority_length = uri
+        .authority()
+        .map_or(0, |authority| authority.as_str().len());
+    let path_and_query_length = uri
+        .path_and_query()
+        .map_or(0, |path_and_query| path_and_query.as_str().len());
+
+    scheme_length + authority_length + path_and_query_length
+}
+
+fn uri_too_long_response() -> axum::response::Response {
+    http::Response::builder()
+        .status(StatusCode::URI_TOO_LONG)
+        .header(header::CONNECTION, "close")
+        .body(axum::body::Body::empty())
+        .expect("the response should be valid")
+}
+
+/// The response sent to the clients using an unsupported HTTP version.
+const HTTP_VERSION_NOT_SUPPORTED_RESPONSE: &[u8] = b"HTTP/1.1 505 HTTP Version Not Supported\r\n\
+    connection: close\r\n\
+    content-length: 0\r\n\
+    \r\n";
+
+/// The maximum number of bytes buffered while looking for the request line of
+/// the first request. Longer request lines are passed on to hyper as they are.
+const MAX_REQUEST_LINE_LENGTH: usize = 16 * 1024;
+
+/// An IO wrapper that answers the connections using an unsupported HTTP
+/// version with `505 HTTP Version Not Supported`.
+///
+/// The server only speaks HTTP/1.0 and HTTP/1.1. On its own, hyper answers
+/// the requests with other versions with `400 Bad Request`, and drops the
+/// connections starting with the HTTP/2 connection preface without any
+/// response at all. To return a proper response instead, the request line of
+/// the first request on the connection is read before hyper sees it; if the
+/// version is supported, the buffered bytes are then replayed to hyper.
+#[derive(Debug)]
+struct HttpVersionCheck<T> {
+    inner: T,
+    state: HttpVersionCheckState,
+}
+
+#[derive(Debug)]
+enum HttpVersionCheckState {
+    /// Reading the request line of the first request.
+    Reading(BytesMut),
+    /// Passing the bytes read so far on to hyper.
+    Replaying(Bytes),
+    /// Writing the `505 HTTP Version Not Supported` response.
+    Rejecting { written: usize },
+    /// Passing all the reads through to the inner stream.
+    Passthrough,
+    /// The response has been sent; the connection is reported as closed.
+    Closed,
+}
+
+impl<T> HttpVersionCheck<T> {
+    fn new(inner: T) -> Self {
+        Self {
+            inner,
+            state: HttpVersionCheckState::Reading(BytesMut::new()),
+        }
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> AsyncRead for HttpVersionCheck<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                HttpVersionCheckState::Reading(buffer) => {
+                    if let Some(request_line) = request_line(buffer) {
+                        this.state = if is_http_version_supported(request_line) {
+                            HttpVersionCheckState::Replaying(std::mem::take(buffer).freeze())
+                        } else {
+                            debug!("Rejecting connection using an unsupported HTTP version");
+                            HttpVersionCheckState::Rejecting { written: 0 }
+                        };
+                        continue;
+                    }
+                    if buffer.len() >= MAX_REQUEST_LINE_LENGTH {
+                        this.state =
+                            HttpVersionCheckState::Replaying(std::mem::take(buffer).freeze());
+                        continue;
+                    }
+
+                    let mut chunk = [0; 1024];
+                    let mut chunk_buf = ReadBuf::new(&mut chunk);
+                    ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+                    if chunk_buf.filled().is_empty() {
+                        // EOF; let hyper handle whatever has been sent
+                        this.state =
+                            HttpVersionCheckState::Replaying(std::mem::take(buffer).freeze());
+                    } else {
+                        buffer.extend_from_slice(chunk_buf.filled());
+                    }
+                }
+                HttpVersionCheckState::Replaying(buffer) => {
+                    let length = buffer.len().min(buf.remaining());
+                    buf.put_slice(&buffer.split_to(length));
+                    if buffer.is_empty() {
+                        this.state = HttpVersionCheckState::Passthrough;
+                    }
+                    return Poll::Ready(Ok(()));
+                }
+                HttpVersionCheckState::Rejecting { written } => {
+                    while *written < HTTP_VERSION_NOT_SUPPORTED_RESPONSE.len() {
+                        let n = ready!(
+                            Pin::new(&mut this.inner)
+                                .poll_write(cx, &HTTP_VERSION_NOT_SUPPORTED_RESPONSE[*written..])
+                        )?;
+                        if n == 0 {
+                            return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+                        }
+                        *written += n;
+                    }
+                    ready!(Pin::new(&mut this.inner).poll_flush(cx))?;
+                    this.state = HttpVersionCheckState::Closed;
+                }
+                HttpVersionCheckState::Passthrough => {
+                    return Pin::new(&mut this.inner).poll_read(cx, buf);
+                }
+                HttpVersionCheckState::Closed => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for HttpVersionCheck<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+/// Returns the request line (without the line terminator) if it has been
+/// read completely.
+///
+/// Empty lines before the request line are skipped, as allowed by RFC 9112.
+fn request_line(buffer: &[u8]) -> Option<&[u8]> {
+    let start = buffer.iter().position(|&b| b != b'\r' && b != b'\n')?;
+    let buffer = &buffer[start..];
+    let end = buffer.iter().position(|&b| b == b'\n')?;
+    let line = &buffer[..end];
+    Some(line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+/// Checks whether the HTTP version in the request line is supported.
+///
+/// Only the well-formed versions (`HTTP/<digit>.<digit>`) other than HTTP/1.0
+/// and HTTP/1.1 are considered unsupported; malformed request lines are left
+/// for hyper to reject.
+fn is_http_version_supported(request_line: &[u8]) -> bool {
+    let Some(version) = request_line.rsplit(|&b| b == b' ').next() else {
+        return true;
+    };
+
+    match version {
+        [b'H', b'T', b'T', b'P', b'/', major, b'.', minor]
+            if major.is_ascii_digit() && minor.is_ascii_digit() =>
+        {
+            *major == b'1' && matches!(minor, b'0' | b'1')
+        }
+        _ => true,
+    }
+}
+
+fn configure_stream(stream: &TcpStream, buffers: &ServerBuffersConfig) {
+    if let Err(error) = stream.set_nodelay(true) {
+        debug!("Failed to set TCP_NODELAY: {error}");
+    }
+
+    let socket = socket2::SockRef::from(stream);
+    if let Some(size) = buffers.read_buffer_size {
+        if let Err(error) = socket.set_recv_buffer_size(size) {
+            error!("Failed to set the socket receive buffer size to {size}: {error}");
+        }
+    }
+    if let Some(size) = buffers.write_buffer_size {
+        if let Err(error) = socket.set_send_buffer_size(size) {
+            error!("Failed to set the socket send buffer size to {size}: {error}");
+        }
+    }
+}
+
+async fn handle_accept_error(error: std::io::Error) {
+    // errors concerning a single connection don't affect the listener
+    if matches!(
+        error.kind(),
+        std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::ConnectionReset
+    ) {
+        return;
+    }
+
+    // other errors (e.g. running out of file descriptors) are likely to happen
+    // again right away, so let's wait a bit before accepting again
+    error!("Failed to accept a connection: {error}");
+    tokio::time::sleep(Duration::from_secs(1)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::config::ServerBuffersConfig;
+
+    async fn hello_service(
+        _request: axum::extract::Request,
+    ) -> Result<axum::response::Response, Infallible> {
+        Ok(axum::response::Response::new(axum::body::Body::from(
+            "Hello world!",
+        )))
+    }
+
+    async fn get(address: SocketAddr) -> String {
+        send(
+            address,
+            "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        )
+        .await
+    }
+
+    async fn send(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_with_buffers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let config = ServerConfig::builder()
+            .buffers(
+                ServerBuffersConfig::builder()
+                    .read_buffer_size(16384)
+                    .write_buffer_size(16384)
+                    .http1_max_buf_size(16384)
+                    .build(),
+            )
+            .build();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            serve(
+                listener,
+                tower::service_fn(hello_service),
+                &config,
+                async move {
+                    let _ = shutdown_rx.await;
+                },
+            )
+            .await;
+        });
+
+        let response = get(address).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("Hello world!"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_adds_remote_addr() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let config = ServerConfig::default();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            serve(
+                listener,
+                tower::service_fn(|request: axum::extract::Request| async move {
+                    let RemoteAddr(remote_addr) = request.extensions().get().copied().unwrap();
+                    Ok::<_, Infallible>(axum::response::Response::new(axum::body::Body::from(
+                        remote_addr.ip().to_string(),
+                    )))
+                }),
+                &config,
+                async move {
+                    let _ = shutdown_rx.await;
+                },
+            )
+            .await;
+        });
+
+        let response = get(address).await;
+        assert!(response.ends_with("127.0.0.1"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_rejects_long_uri() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let config = ServerConfig::builder().max_uri_length(64).build();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            serve(
+                listener,
+                tower::service_fn(hello_service),
+                &config,
+                async move {
+                    let _ = shutdown_rx.await;
+                },
+            )
+            .await;
+        });
+
+        // the client doesn't ask to close the connection, so reading the
+        // response to the end only succeeds if the server closes it
+        let path = format!("/{}", "a".repeat(64));
+        let response = send(
+            address,
+            &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 414 URI Too Long"));
+        assert!(response.contains("connection: close"));
+
+        let path = format!("/{}", "a".repeat(63));
+        let response = send(
+            address,
+            &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn connection_counter_limits_connections() {
+        let counter = Arc::new(ConnectionCounter::default());
+        let ip = IpAddr::from([192, 0, 2, 1]);
+        let other_ip = IpAddr::from([192, 0, 2, 2]);
+
+        let first = counter.try_acquire(ip, 2).unwrap();
+        let _second = counter.try_acquire(ip, 2).unwrap();
+        assert!(counter.try_acquire(ip, 2).is_none());
+        let _other = counter.try_acquire(other_ip, 2).unwrap();
+
+        drop(first);
+        let _third = counter.try_acquire(ip, 2).unwrap();
+        assert!(counter.try_acquire(ip, 2).is_none());
+    }
+
+    #[test]
+    fn connection_counter_removes_closed() {
+        let counter = Arc::new(ConnectionCounter::default());
+        let ip = IpAddr::from([192, 0, 2, 1]);
+
+        drop(counter.try_acquire(ip, 1).unwrap());
+        assert!(counter.try_acquire(ip, 0).is_none());
+
+        assert!(counter.connections.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn connection_counter_concurrent() {
+        let counter = Arc::new(ConnectionCounter::default());
+        let ip = IpAddr::from([192, 0, 2, 1]);
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let counter = Arc::clone(&counter);
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        drop(counter.try_acquire(ip, 8).unwrap());
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert!(counter.connections.lock().unwrap().is_empty());
+    }
+
+    async fn serve_in_background(
+        config: ServerConfig,
+    ) -> (
+        SocketAddr,
+        tokio::sync::oneshot::Sender<()>,
+        tokio::task::JoinHandle<()>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            serve(
+                listener,
+                tower::service_fn(hello_service),
+                &config,
+                async move {
+                    let _ = shutdown_rx.await;
+                },
+            )
+            .await;
+        });
+
+        (address, shutdown_tx, server)
+    }
+
+    /// Opens a keep-alive connection and waits until it's served, so that the
+    /// server is guaranteed to have accepted it.
+    async fn open_connection(address: SocketAddr) -> TcpStream {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut buf = [0; 1024];
+        let mut response = Vec::new();
+        while !response.ends_with(b"Hello world!") {
+            let read = stream.read(&mut buf).await.unwrap();
+            assert_ne!(read, 0, "connection closed unexpectedly");
+            response.extend_from_slice(&buf[..read]);
+        }
+        stream
+    }
+
+    async fn try_get(address: SocketAddr) -> Option<String> {
+        let mut stream = TcpStream::connect(address).await.ok()?;
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .ok()?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.ok()?;
+        Some(response).filter(|response| !response.is_empty())
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_limits_connections_per_ip() {
+        let config = ServerConfig::builder().max_connections_per_ip(1).build();
+        let (address, shutdown_tx, server) = serve_in_background(config).await;
+
+        let connection = open_connection(address).await;
+        assert_eq!(try_get(address).await, None);
+
+        drop(connection);
+        // the server releases the slot asynchronously after noticing the
+        // connection has been closed
+        let mut response = None;
+        for _ in 0..100 {
+            response = try_get(address).await;
+            if response.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(response.unwrap().starts_with("HTTP/1.1 200 OK"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_does_not_limit_trusted_proxies() {
+        let config = ServerConfig::builder()
+            .max_connections_per_ip(1)
+            .trusted_proxies(vec![IpAddr::from([127, 0, 0, 1])])
+            .build();
+        let (address, shutdown_tx, server) = serve_in_background(config).await;
+
+        let _connection = open_connection(address).await;
+        let response = try_get(address).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_limits_requests_per_connection() {
+        let config = ServerConfig::builder()
+            .max_requests_per_connection(2)
+            .build();
+        let (address, shutdown_tx, server) = serve_in_background(config).await;
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut buf = [0; 1024];
+            let mut response = Vec::new();
+            while !response.ends_with(b"Hello world!") {
+                let read = stream.read(&mut buf).await.unwrap();
+                assert_ne!(read, 0, "connection closed unexpectedly");
+                response.extend_from_slice(&buf[..read]);
+            }
+            responses.push(String::from_utf8(response).unwrap());
+        }
+        assert!(!responses[0].contains("connection: close"));
+        assert!(responses[1].contains("connection: close"));
+
+        // the third request is not served, as the server closes the connection
+        let _ = stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await;
+        let mut rest = Vec::new();
+        let _ = stream.read_to_end(&mut rest).await;
+        assert!(rest.is_empty());
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_rejects_http2_preface() {
+        let (address, shutdown_tx, server) = serve_in_background(ServerConfig::default()).await;
+
+        let response = send(address, "PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 505 HTTP Version Not Supported"));
+        assert!(response.contains("connection: close"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_rejects_unsupported_http_version() {
+        let (address, shutdown_tx, server) = serve_in_background(ServerConfig::default()).await;
+
+        let response = send(address, "GET / HTTP/3.0\r\nHost: localhost\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 505 HTTP Version Not Supported"));
+
+        let response = send(address, "\r\nGET / HTTP/1.0\r\nHost: localhost\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.0 200 OK"));
+        assert!(response.ends_with("Hello world!"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn serve_replays_request_line_sent_in_parts() {
+        let (address, shutdown_tx, server) = serve_in_background(ServerConfig::default()).await;
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream.write_all(b"GET / HT").await.unwrap();
+        stream.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stream
+            .write_all(b"TP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn request_line_skips_empty_lines() {
+        assert_eq!(request_line(b"GET / HTTP/1.1"), None);
+        assert_eq!(request_line(b"\r\n"), None);
+        assert_eq!(
+            request_line(b"\r\nGET / HTTP/1.1\r\nHost: localhost\r\n"),
+            Some(&b"GET / HTTP/1.1"[..])
+        );
+        assert_eq!(
+            request_line(b"GET / HTTP/1.1\n"),
+            Some(&b"GET / HTTP/1.1"[..])
+        );
+    }
+
+    #[test]
+    fn http_version_supported() {
+        assert!(is_http_version_supported(b"GET / HTTP/1.1"));
+        assert!(is_http_version_supported(b"GET / HTTP/1.0"));
+        assert!(!is_http_version_supported(b"PRI * HTTP/2.0"));
+        assert!(!is_http_version_supported(b"GET / HTTP/3.0"));
+        assert!(!is_http_version_supported(b"GET / HTTP/1.2"));
+        assert!(!is_http_version_supported(b"GET / HTTP/0.9"));
+        // malformed request lines are rejected by hyper instead
+        assert!(is_http_version_supported(b"GET /"));
+        assert!(is_http_version_supported(b"GET / HTTP/11"));
+    }
+
+    #[test]
+    fn uri_length_counts_request_target() {
+        assert_eq!(uri_length(&"/".parse().unwrap()), 1);
+        assert_eq!(uri_length(&"/path?query=1".parse().unwrap()), 13);
+        assert_eq!(uri_length(&"http://example.com/path".parse().unwrap()), 23);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn configure_stream_sets_buffer_sizes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (client, server) = tokio::join!(TcpStream::connect(address), listener.accept());
+        let _client = client.unwrap();
+        let (stream, _) = server.unwrap();
+
+        let buffers = ServerBuffersConfig::builder()
+            .read_buffer_size(32768)
+            .write_buffer_size(32768)
+            .build();
+        configure_stream(&stream, &buffers);
+
+        let socket = socket2::SockRef::from(&stream);
+        // the OS may round the values (Linux doubles them), so only check they
+        // were applied at all
+        assert!(socket.recv_buffer_size().unwrap() >= 32768);
+        assert!(socket.send_buffer_size().unwrap() >= 32768);
+        assert!(stream.nodelay().unwrap());
+    }
+}
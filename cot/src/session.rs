@@ -42,11 +42,30 @@
 //! # }
 //! ```
 
+pub mod cookie;
+pub mod store;
+
 use std::ops::{Deref, DerefMut};
 
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::error::ErrorRepr;
+
 /// A session object.
 ///
-/// This is a wrapper around the `tower_sessions::Session` type.
+/// This is a wrapper around the `tower_sessions::Session` type. It provides
+/// typed accessors for the session data ([`get`](Self::get),
+/// [`insert`](Self::insert), [`remove`](Self::remove) and
+/// [`clear`](Self::clear)) that serialize the values with [`serde`] and work
+/// regardless of the session store used. The rest of the
+/// `tower_sessions::Session` API is available through [`Deref`].
+///
+/// The session is only available if
+/// [`SessionMiddleware`](crate::middleware::SessionMiddleware) is added to the
+/// middleware stack. Use
+/// [`RequestExt::session`](crate::request::RequestExt::session)
+/// or the [`Session`] extractor to get it from a request.
 ///
 /// # Examples
 ///
@@ -152,6 +171,96 @@ impl Session {
             .get::<Self>()
             .expect("Session extension missing. Did you forget to add the SessionMiddleware?")
     }
+
+    pub(crate) fn try_from_extensions(extensions: &http::Extensions) -> crate::Result<&Self> {
+        extensions
+            .get::<Self>()
+            .ok_or_else(|| ErrorRepr::SessionMissing.into())
+    }
+
+    /// Gets a value from the session, deserializing it to the given type.
+    ///
+    /// Returns `None` if there is no value for the given key.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the session could not be loaded from the store or
+    /// the value could not be deserialized to `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::Session;
+    ///
+    /// async fn visits(session: &Session) -> cot::Result<u32> {
+    ///     Ok(session.get::<u32>("visits").await?.unwrap_or_default())
+    /// }
+    /// ```
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> crate::Result<Option<T>> {
+        Ok(self.inner.get(key).await?)
+    }
+
+    /// Inserts a value into the session, serializing it with [`serde`].
+    ///
+    /// If there already is a value for the given key, it is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the session could not be loaded from the store or
+    /// the value could not be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::Session;
+    ///
+    /// async fn remember_user(session: &Session) -> cot::Result<()> {
+    ///     session.insert("user_name", "world").await
+    /// }
+    /// ```
+    pub async fn insert(&self, key: &str, value: impl Serialize) -> crate::Result<()> {
+        Ok(self.inner.insert(key, value).await?)
+    }
+
+    /// Removes a value from the session, returning it deserialized to the
+    /// given type.
+    ///
+    /// Returns `None` if there was no value for the given key.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the session could not be loaded from the store or
+    /// the value could not be deserialized to `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::Session;
+    ///
+    /// async fn take_message(session: &Session) -> cot::Result<Option<String>> {
+    ///     session.remove::<String>("message").await
+    /// }
+    /// ```
+    pub async fn remove<T: DeserializeOwned>(&self, key: &str) -> crate::Result<Option<T>> {
+        Ok(self.inner.remove(key).await?)
+    }
+
+    /// Removes all the values from the session.
+    ///
+    /// Unlike `flush`, this keeps the session itself (and its ID) intact.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::Session;
+    ///
+    /// async fn reset(session: &Session) {
+    ///     session.clear().await;
+    /// }
+    /// ```
+    pub async fn clear(&self) {
+        self.inner.clear().await;
+    }
 }
 
 impl Deref for Session {
@@ -167,3 +276,64 @@ impl DerefMut for Session {
         &mut self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::request::RequestExt;
+    use crate::test::TestRequestBuilder;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Cart {
+        items: Vec<String>,
+    }
+
+    #[cot::test]
+    async fn session_typed_accessors() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let session = request.session().unwrap();
+
+        let cart = Cart {
+            items: vec!["apple".to_owned()],
+        };
+        session.insert("cart", &cart).await.unwrap();
+        assert_eq!(session.get::<Cart>("cart").await.unwrap(), Some(cart));
+        assert_eq!(session.get::<Cart>("missing").await.unwrap(), None);
+
+        let removed = session.remove::<Cart>("cart").await.unwrap();
+        assert!(removed.is_some());
+        assert_eq!(session.get::<Cart>("cart").await.unwrap(), None);
+    }
+
+    #[cot::test]
+    async fn session_get_wrong_type() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let session = request.session().unwrap();
+
+        session.insert("count", "not a number").await.unwrap();
+        let error = session.get::<u32>("count").await.unwrap_err();
+        assert!(matches!(error.inner, ErrorRepr::SessionAccess(_)));
+    }
+
+    #[cot::test]
+    async fn session_clear() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let session = request.session().unwrap();
+
+        session.insert("a", 1).await.unwrap();
+        session.insert("b", 2).await.unwrap();
+        session.clear().await;
+
+        assert!(session.is_empty().await);
+    }
+
+    #[test]
+    fn session_missing() {
+        let request = TestRequestBuilder::get("/").build();
+
+        let error = request.session().unwrap_err();
+        assert!(matches!(error.inner, ErrorRepr::SessionMissing));
+    }
+}
@@ -0,0 +1,275 @@
+//! Session cookie attributes.
+//!
+//! Browsers give a special meaning to cookie names starting with `__Secure-`
+//! and `__Host-`: such cookies are only accepted if they are set with
+//! attributes that make them harder to overwrite by an attacker. This module
+//! provides the [`CookiePrefix`] type that can be used with
+//! [`SessionMiddleware::cookie_prefix`](crate::middleware::SessionMiddleware::cookie_prefix)
+//! or [`SessionMiddlewareConfig::cookie_prefix`](crate::config::SessionMiddlewareConfig::cookie_prefix)
+//! to use these prefixes for the session cookie. The [`SameSite`] type
+//! controls whether the session cookie is sent with cross-site requests.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A cookie name prefix enforced by the browsers.
+///
+/// See the [MDN documentation](https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Headers/Set-Cookie#cookie_prefixes)
+/// for more information.
+///
+/// # Examples
+///
+/// ```
+/// use cot::session::cookie::CookiePrefix;
+///
+/// assert_eq!(CookiePrefix::Host.apply("id"), "__Host-id");
+/// assert!(CookiePrefix::Host.validate(true, "/", None).is_ok());
+/// assert!(
+///     CookiePrefix::Host
+///         .validate(true, "/", Some("example.com"))
+///         .is_err()
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CookiePrefix {
+    /// The `__Secure-` prefix. The cookie must be set with the `Secure`
+    /// attribute.
+    Secure,
+    /// The `__Host-` prefix. The cookie must be set with the `Secure`
+    /// attribute and `Path=/`, and must not have a `Domain` attribute, which
+    /// locks it to the host that set it.
+    Host,
+}
+
+impl CookiePrefix {
+    /// Returns the prefix as a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::cookie::CookiePrefix;
+    ///
+    /// assert_eq!(CookiePrefix::Secure.as_str(), "__Secure-");
+    /// assert_eq!(CookiePrefix::Host.as_str(), "__Host-");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Secure => "__Secure-",
+            Self::Host => "__Host-",
+        }
+    }
+
+    /// Returns the cookie name with the prefix prepended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::cookie::CookiePrefix;
+    ///
+    /// assert_eq!(CookiePrefix::Secure.apply("id"), "__Secure-id");
+    /// ```
+    #[must_use]
+    pub fn apply(self, name: &str) -> String {
+        format!("{}{name}", self.as_str())
+    }
+
+    /// Checks whether a cookie with the given attributes would be accepted
+    /// by the browsers when using this prefix.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cookie is not `Secure`, or (for
+    /// [`CookiePrefix::Host`]) if the path is not `/` or the domain is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::cookie::{CookiePrefix, CookiePrefixError};
+    ///
+    /// assert!(
+    ///     CookiePrefix::Secure
+    ///         .validate(true, "/app", Some("example.com"))
+    ///         .is_ok()
+    /// );
+    /// assert_eq!(
+    ///     CookiePrefix::Secure.validate(false, "/", None),
+    ///     Err(CookiePrefixError::NotSecure {
+    ///         prefix: CookiePrefix::Secure
+    ///     })
+    /// );
+    /// ```
+    pub fn validate(
+        self,
+        secure: bool,
+        path: &str,
+        domain: Option<&str>,
+    ) -> Result<(), CookiePrefixError> {
+        if !secure {
+            return Err(CookiePrefixError::NotSecure { prefix: self });
+        }
+
+        if self == Self::Host {
+            if path != "/" {
+                return Err(CookiePrefixError::InvalidPath {
+                    path: path.to_owned(),
+                });
+            }
+            if let Some(domain) = domain {
+                return Err(CookiePrefixError::DomainSet {
+                    domain: domain.to_owned(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The `SameSite` attribute of a cookie, which controls whether the cookie
+/// is sent with cross-site requests.
+///
+/// See the [MDN documentation](https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Headers/Set-Cookie#samesitesamesite-value)
+/// for more information.
+///
+/// # Examples
+///
+/// ```
+/// use cot::session::cookie::SameSite;
+///
+/// assert_eq!(SameSite::Lax.as_str(), "Lax");
+/// assert_eq!(SameSite::default(), SameSite::Strict);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SameSite {
+    /// The cookie is only sent with same-site requests.
+    #[default]
+    Strict,
+    /// The cookie is also sent when the user navigates to the site from
+    /// another site (e.g. by following a link), but not with other
+    /// cross-site requests.
+    Lax,
+    /// The cookie is sent with all the requests, including cross-site ones.
+    /// The browsers only accept such cookies when they're `Secure`.
+    None,
+}
+
+impl SameSite {
+    /// Returns the value of the attribute as a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::cookie::SameSite;
+    ///
+    /// assert_eq!(SameSite::Strict.as_str(), "Strict");
+    /// assert_eq!(SameSite::None.as_str(), "None");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None",
+        }
+    }
+
+    pub(crate) const fn to_cookie(self) -> cookie::SameSite {
+        match self {
+            Self::Strict => cookie::SameSite::Strict,
+            Self::Lax => cookie::SameSite::Lax,
+            Self::None => cookie::SameSite::None,
+        }
+    }
+}
+
+/// An error returned when the cookie attributes are not compatible with the
+/// [`CookiePrefix`] used.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum CookiePrefixError {
+    /// Prefixed cookies must be set with the `Secure` attribute.
+    #[error("cookies with the `{}` prefix must be secure", prefix.as_str())]
+    NotSecure {
+        /// The prefix used.
+        prefix: CookiePrefix,
+    },
+    /// `__Host-` cookies must be set with `Path=/`.
+    #[error("cookies with the `__Host-` prefix must have the path set to `/`, not `{path}`")]
+    InvalidPath {
+        /// The path of the cookie.
+        path: String,
+    },
+    /// `__Host-` cookies must not have a `Domain` attribute.
+    #[error("cookies with the `__Host-` prefix must not have a domain, but `{domain}` is set")]
+    DomainSet {
+        /// The domain of the cookie.
+        domain: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secure_prefix_requires_secure() {
+        assert_eq!(
+            CookiePrefix::Secure.validate(false, "/", None),
+            Err(CookiePrefixError::NotSecure {
+                prefix: CookiePrefix::Secure
+            })
+        );
+        assert!(
+            CookiePrefix::Secure
+                .validate(true, "/admin", Some("example.com"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn host_prefix_requires_secure() {
+        assert_eq!(
+            CookiePrefix::Host.validate(false, "/", None),
+            Err(CookiePrefixError::NotSecure {
+                prefix: CookiePrefix::Host
+            })
+        );
+    }
+
+    #[test]
+    fn host_prefix_requires_root_path() {
+        assert_eq!(
+            CookiePrefix::Host.validate(true, "/admin", None),
+            Err(CookiePrefixError::InvalidPath {
+                path: "/admin".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn same_site_to_cookie() {
+        assert_eq!(SameSite::Strict.to_cookie(), cookie::SameSite::Strict);
+        assert_eq!(SameSite::Lax.to_cookie(), cookie::SameSite::Lax);
+        assert_eq!(SameSite::None.to_cookie(), cookie::SameSite::None);
+    }
+
+    #[test]
+    fn host_prefix_forbids_domain() {
+        assert_eq!(
+            CookiePrefix::Host.validate(true, "/", Some("example.com")),
+            Err(CookiePrefixError::DomainSet {
+                domain: "example.com".to_owned()
+            })
+        );
+        assert!(CookiePrefix::Host.validate(true, "/", None).is_ok());
+    }
+
+    #[test]
+    fn apply_prefix() {
+        assert_eq!(CookiePrefix::Secure.apply("session"), "__Secure-session");
+        assert_eq!(CookiePrefix::Host.apply("session"), "__Host-session");
+    }
+}
@@ -0,0 +1,408 @@
+//! Session stores.
+//!
+//! A session store is where the session data is kept between the requests.
+//! By default, [`SessionMiddleware`](crate::middleware::SessionMiddleware)
+//! keeps the sessions in memory, but any type implementing the
+//! [`SessionStore`] trait can be used instead by passing it to
+//! [`SessionMiddleware::store`](crate::middleware::SessionMiddleware::store).
+//!
+//! Stores implementing [`ExpiredDeletion`] can also get rid of the sessions
+//! that have expired. This can be done periodically in the background with
+//! a [`SessionJanitor`], or by setting
+//! [`SessionMiddlewareConfig::cleanup_interval`](crate::config::SessionMiddlewareConfig::cleanup_interval).
+//!
+//! With the `redis` feature enabled, the sessions can also be kept in Redis
+//! with the [`redis::RedisStore`].
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::{Mutex, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+pub use tower_sessions::SessionStore;
+pub use tower_sessions::session::{Id, Record};
+pub use tower_sessions::session_store::{Error, ExpiredDeletion, Result};
+use tracing::{error, warn};
+
+use crate::clock::{Clock, SystemClock, now_offset};
+use crate::config::SessionStoreErrorPolicy;
+
+/// A session store that applies the [`SessionStoreErrorPolicy`] to the
+/// errors of the store it wraps.
+///
+/// This is used internally by
+/// [`SessionMiddleware`](crate::middleware::SessionMiddleware) and shouldn't
+/// be useful on its own.
+#[derive(Debug, Clone)]
+pub struct SessionStoreWrapper {
+    store: Arc<dyn SessionStore>,
+    error_policy: SessionStoreErrorPolicy,
+    // keeps the janitor running for as long as the store is in use
+    _janitor: Option<Arc<SessionJanitor>>,
+}
+
+impl SessionStoreWrapper {
+    pub(crate) fn new(
+        store: Arc<dyn SessionStore>,
+        error_policy: SessionStoreErrorPolicy,
+        janitor: Option<Arc<SessionJanitor>>,
+    ) -> Self {
+        Self {
+            store,
+            error_policy,
+            _janitor: janitor,
+        }
+    }
+
+    fn handle_error<T>(&self, result: Result<T>, fallback: T) -> Result<T> {
+        match (result, self.error_policy) {
+            (Err(error), SessionStoreErrorPolicy::Degrade) => {
+                warn!("Session store error, continuing with a transient session: {error}");
+                Ok(fallback)
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+#[async_trait]
+impl SessionStore for SessionStoreWrapper {
+    async fn create(&self, session_record: &mut Record) -> Result<()> {
+        let result = self.store.create(session_record).await;
+        self.handle_error(result, ())
+    }
+
+    async fn save(&self, session_record: &Record) -> Result<()> {
+        let result = self.store.save(session_record).await;
+        self.handle_error(result, ())
+    }
+
+    async fn load(&self, session_id: &Id) -> Result<Option<Record>> {
+        let result = self.store.load(session_id).await;
+        self.handle_error(result, None)
+    }
+
+    async fn delete(&self, session_id: &Id) -> Result<()> {
+        let result = self.store.delete(session_id).await;
+        self.handle_error(result, ())
+    }
+}
+
+/// A session store that keeps the sessions in memory.
+///
+/// This is the store used by
+/// [`SessionMiddleware`](crate::middleware::SessionMiddleware) by default.
+/// The sessions are lost when the server is restarted, so it's mostly useful
+/// for development and testing. Expired sessions are never loaded, but they
+/// are only removed from memory by [`ExpiredDeletion::delete_expired`]. The
+/// current time is read from a [`Clock`], which is the [`SystemClock`] by
+/// default.
+///
+/// # Examples
+///
+/// ```
+/// use cot::session::store::{ExpiredDeletion, MemoryStore};
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::session::store::Result<()> {
+/// let store = MemoryStore::default();
+/// store.delete_expired().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryStore {
+    records: Arc<Mutex<HashMap<Id, Record>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+}
+
+impl MemoryStore {
+    /// Creates a new, empty [`MemoryStore`] that reads the current time from
+    /// the given [`Clock`] to tell whether the sessions have expired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::session::store::MemoryStore;
+    /// use cot::test::TestClock;
+    ///
+    /// let store = MemoryStore::with_clock(Arc::new(TestClock::new()));
+    /// ```
+    #[must_use]
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            records: Arc::default(),
+            clock,
+        }
+    }
+
+    fn is_active(&self, record: &Record) -> bool {
+        record.expiry_date > now_offset(self.clock.as_ref())
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemoryStore {
+    async fn create(&self, session_record: &mut Record) -> Result<()> {
+        let mut records = self.records.lock().await;
+        while records.contains_key(&session_record.id) {
+            // session ID collision
+            session_record.id = Id::default();
+        }
+        records.insert(session_record.id, session_record.clone());
+        Ok(())
+    }
+
+    async fn save(&self, session_record: &Record) -> Result<()> {
+        self.records
+            .lock()
+            .await
+            .insert(session_record.id, session_record.clone());
+        Ok(())
+    }
+
+    async fn load(&self, session_id: &Id) -> Result<Option<Record>> {
+        Ok(self
+            .records
+            .lock()
+            .await
+            .get(session_id)
+            .filter(|record| self.is_active(record))
+            .cloned())
+    }
+
+    async fn delete(&self, session_id: &Id) -> Result<()> {
+        self.records.lock().await.remove(session_id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExpiredDeletion for MemoryStore {
+    async fn delete_expired(&self) -> Result<()> {
+        let now = now_offset(self.clock.as_ref());
+        self.records
+            .lock()
+            .await
+            .retain(|_, record| record.expiry_date > now);
+        Ok(())
+    }
+}
+
+/// A background task that periodically deletes the expired sessions from a
+/// session store.
+///
+/// The task is stopped when the janitor is dropped, or explicitly with
+/// [`SessionJanitor::stop`], which also waits for the cleanup in progress
+/// (if any) to finish. When the janitor is set up by
+/// [`SessionMiddleware::from_context`](crate::middleware::SessionMiddleware::from_context),
+/// it's registered as a background task of the project, which is stopped
+/// when the server shuts down gracefully. When it's set up with
+/// [`SessionMiddleware::store_with_cleanup`](crate::middleware::SessionMiddleware::store_with_cleanup),
+/// it's kept alive by the session middleware instead, so it stops once the
+/// middleware is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::session::store::{MemoryStore, SessionJanitor};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let janitor = SessionJanitor::spawn(MemoryStore::default(), Duration::from_secs(3600));
+/// janitor.stop().await;
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SessionJanitor {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl SessionJanitor {
+    /// Spawns a task deleting the expired sessions from `store` every
+    /// `interval`. The first cleanup happens right away.
+    ///
+    /// Errors returned by the store are logged, and the cleanup is retried
+    /// after the next interval.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime, or if `interval` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::session::store::{MemoryStore, SessionJanitor};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let janitor = SessionJanitor::spawn(MemoryStore::default(), Duration::from_secs(3600));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn spawn<T: ExpiredDeletion>(store: T, interval: Duration) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    // either stopped explicitly or the janitor has been dropped
+                    _ = &mut stopped => break,
+                    _ = ticker.tick() => {}
+                }
+
+                if let Err(error) = store.delete_expired().await {
+                    error!("Failed to delete the expired sessions: {error}");
+                }
+            }
+        });
+
+        Self { stop, task }
+    }
+
+    /// Stops the janitor, waiting for the cleanup in progress (if any) to
+    /// finish.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::session::store::{MemoryStore, SessionJanitor};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let janitor = SessionJanitor::spawn(MemoryStore::default(), Duration::from_secs(3600));
+    /// janitor.stop().await;
+    /// # }
+    /// ```
+    pub async fn stop(self) {
+        let _ = self.stop.send(());
+        if let Err(error) = self.task.await {
+            error!("Session janitor task failed: {error}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Duration as TimeDuration, OffsetDateTime};
+
+    use super::*;
+
+    fn record(expiry_date: OffsetDateTime) -> Record {
+        Record {
+            id: Id::default(),
+            data: HashMap::default(),
+            expiry_date,
+        }
+    }
+
+    #[cot::test]
+    async fn memory_store_does_not_load_expired() {
+        let store = MemoryStore::default();
+        let mut expired = record(OffsetDateTime::now_utc() - TimeDuration::minutes(1));
+        store.create(&mut expired).await.unwrap();
+
+        assert_eq!(store.load(&expired.id).await.unwrap(), None);
+    }
+
+    #[cot::test]
+    async fn memory_store_delete_expired() {
+        let store = MemoryStore::default();
+        let mut expired = record(OffsetDateTime::now_utc() - TimeDuration::minutes(1));
+        let mut active = record(OffsetDateTime::now_utc() + TimeDuration::minutes(30));
+        store.create(&mut expired).await.unwrap();
+        store.create(&mut active).await.unwrap();
+
+        store.delete_expired().await.unwrap();
+
+        let records = store.records.lock().await;
+        assert!(!records.contains_key(&expired.id));
+        assert!(records.contains_key(&active.id));
+    }
+
+    #[cot::test]
+    async fn memory_store_uses_clock() {
+        let clock = crate::test::TestClock::new();
+        let store = MemoryStore::with_clock(Arc::new(clock.clone()));
+        let mut active = record(now_offset(&clock) + TimeDuration::minutes(30));
+        store.create(&mut active).await.unwrap();
+
+        assert!(store.load(&active.id).await.unwrap().is_some());
+
+        clock.advance(Duration::from_secs(60 * 60));
+        assert_eq!(store.load(&active.id).await.unwrap(), None);
+        store.delete_expired().await.unwrap();
+        assert!(store.records.lock().await.is_empty());
+    }
+
+    #[cot::test]
+    async fn memory_store_create_id_collision() {
+        let store = MemoryStore::default();
+        let expiry_date = OffsetDateTime::now_utc() + TimeDuration::minutes(30);
+        let mut first = record(expiry_date);
+        let mut second = record(expiry_date);
+        store.create(&mut first).await.unwrap();
+        second.id = first.id;
+        store.create(&mut second).await.unwrap();
+
+        assert_ne!(first.id, second.id);
+    }
+
+    #[cot::test]
+    async fn janitor_deletes_expired() {
+        let store = MemoryStore::default();
+        let mut expired = record(OffsetDateTime::now_utc() - TimeDuration::minutes(1));
+        store.create(&mut expired).await.unwrap();
+
+        let janitor = SessionJanitor::spawn(store.clone(), Duration::from_secs(3600));
+        // the first cleanup happens right away
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while store.records.lock().await.contains_key(&expired.id) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("expired session was not deleted");
+
+        janitor.stop().await;
+    }
+
+    #[cot::test]
+    async fn janitor_stops_when_dropped() {
+        let janitor = SessionJanitor::spawn(MemoryStore::default(), Duration::from_millis(10));
+        let task = janitor.task.abort_handle();
+
+        drop(janitor);
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !task.is_finished() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("janitor task did not stop");
+    }
+}
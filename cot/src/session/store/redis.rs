@@ -0,0 +1,392 @@
+//! Redis session store.
+//!
+//! The sessions are kept in Redis, so they survive server restarts and are
+//! shared between all the instances of the application, e.g. when it's
+//! running behind a load balancer. Redis deletes the expired sessions by
+//! itself, so there's no need for a [`SessionJanitor`](super::SessionJanitor).
+//!
+//! This module is only available with the `redis` feature enabled.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ::redis::aio::ConnectionManager;
+use ::redis::{AsyncCommands, Client, RedisError};
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use tokio::sync::OnceCell;
+
+use super::{Error, Id, Record, Result, SessionStore};
+use crate::clock::{Clock, SystemClock, now_offset};
+use crate::config::RedisSessionStoreConfig;
+use crate::error::ErrorRepr;
+
+/// A session store keeping the sessions in Redis.
+///
+/// The connection is established when the store is used for the first time,
+/// and re-established automatically when it's lost.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::middleware::SessionMiddleware;
+/// use cot::session::store::redis::RedisStore;
+///
+/// let store = RedisStore::new("redis://localhost:6379")?
+///     .key_prefix("myapp:session:")
+///     .ttl(Duration::from_secs(24 * 60 * 60));
+/// let middleware = SessionMiddleware::new().store(store);
+/// # Ok::<(), cot::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct RedisStore {
+    client: Client,
+    connection: Arc<OnceCell<ConnectionManager>>,
+    key_prefix: String,
+    ttl: Option<Duration>,
+    clock: Arc<dyn Clock>,
+}
+
+impl std::fmt::Debug for RedisStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisStore")
+            .field("client", &self.client)
+            .field("key_prefix", &self.key_prefix)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisStore {
+    /// Creates a new Redis session store connecting to the server at `url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::store::redis::RedisStore;
+    ///
+    /// let store = RedisStore::new("redis://localhost:6379")?;
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn new(url: &str) -> crate::Result<Self> {
+        let client = Client::open(url).map_err(ErrorRepr::InvalidRedisUrl)?;
+
+        Ok(Self {
+            client,
+            connection: Arc::new(OnceCell::new()),
+            key_prefix: RedisSessionStoreConfig::default().key_prefix,
+            ttl: None,
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Creates a new Redis session store from the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is not set or is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::RedisSessionStoreConfig;
+    /// use cot::session::store::redis::RedisStore;
+    ///
+    /// let config = RedisSessionStoreConfig::builder()
+    ///     .url("redis://localhost:6379")
+    ///     .build();
+    /// let store = RedisStore::from_config(&config)?;
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn from_config(config: &RedisSessionStoreConfig) -> crate::Result<Self> {
+        let url = config.url.as_deref().ok_or(ErrorRepr::RedisUrlMissing)?;
+        let store = Self::new(url)?.key_prefix(config.key_prefix.clone());
+
+        Ok(match config.ttl {
+            Some(ttl) => store.ttl(ttl),
+            None => store,
+        })
+    }
+
+    /// Sets the prefix of the keys the sessions are stored under.
+    ///
+    /// Defaults to `cot:session:`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::store::redis::RedisStore;
+    ///
+    /// let store = RedisStore::new("redis://localhost:6379")?.key_prefix("myapp:session:");
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[must_use]
+    pub fn key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Sets how long the sessions are kept after they were last saved.
+    ///
+    /// The sessions are never kept after their expiry date, which is used
+    /// alone when this is not set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::session::store::redis::RedisStore;
+    ///
+    /// let store = RedisStore::new("redis://localhost:6379")?.ttl(Duration::from_secs(3600));
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the clock used to compute when the sessions expire.
+    ///
+    /// By default, the [`SystemClock`] is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::session::store::redis::RedisStore;
+    /// use cot::test::TestClock;
+    ///
+    /// let store = RedisStore::new("redis://localhost:6379")?.clock(Arc::new(TestClock::new()));
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    async fn connection(&self) -> Result<ConnectionManager> {
+        self.connection
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .cloned()
+            .map_err(backend_error)
+    }
+
+    fn key(&self, id: &Id) -> String {
+        format!("{}{id}", self.key_prefix)
+    }
+
+    /// Returns the Unix timestamp the record should be deleted at, or `None`
+    /// if it has already expired.
+    fn expire_at(&self, record: &Record, now: OffsetDateTime) -> Option<i64> {
+        let expire_at = match self.ttl {
+            Some(ttl) => record.expiry_date.min(now + ttl),
+            None => record.expiry_date,
+        };
+
+        (expire_at > now).then(|| expire_at.unix_timestamp())
+    }
+}
+
+#[async_trait]
+impl SessionStore for RedisStore {
+    async fn create(&self, session_record: &mut Record) -> Result<()> {
+        let Some(expire_at) = self.expire_at(session_record, now_offset(self.clock.as_ref()))
+        else {
+            return Ok(());
+        };
+        let mut connection = self.connection().await?;
+
+        loop {
+            let created: Option<String> = ::redis::cmd("SET")
+                .arg(self.key(&session_record.id))
+                .arg(encode(session_record)?)
+                .arg("NX")
+                .arg("EXAT")
+                .arg(expire_at)
+                .query_async(&mut connection)
+                .await
+                .map_err(backend_error)?;
+            if created.is_some() {
+                return Ok(());
+            }
+
+            // session ID collision
+            session_record.id = Id::default();
+        }
+    }
+
+    async fn save(&self, session_record: &Record) -> Result<()> {
+        let mut connection = self.connection().await?;
+        let key = self.key(&session_record.id);
+
+        match self.expire_at(session_record, now_offset(self.clock.as_ref())) {
+            Some(expire_at) => ::redis::cmd("SET")
+                .arg(key)
+                .arg(encode(session_record)?)
+                .arg("EXAT")
+                .arg(expire_at)
+                .exec_async(&mut connection)
+                .await
+                .map_err(backend_error),
+            None => connection.del(key).await.map_err(backend_error),
+        }
+    }
+
+    async fn load(&self, session_id: &Id) -> Result<Option<Record>> {
+        let mut connection = self.connection().await?;
+
+        let data: Option<String> = connection
+            .get(self.key(session_id))
+            .await
+            .map_err(backend_error)?;
+        data.map(|data| {
+            serde_json::from_str(&data).map_err(|error| Error::Decode(error.to_string()))
+        })
+        .transpose()
+    }
+
+    async fn delete(&self, session_id: &Id) -> Result<()> {
+        let mut connection = self.connection().await?;
+
+        connection
+            .del(self.key(session_id))
+            .await
+            .map_err(backend_error)
+    }
+}
+
+fn encode(record: &Record) -> Result<String> {
+    serde_json::to_string(record).map_err(|error| Error::Encode(error.to_string()))
+}
+
+#[expect(clippy::needless_pass_by_value)] // used with `map_err`
+fn backend_error(error: RedisError) -> Error {
+    Error::Backend(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use time::Duration as TimeDuration;
+
+    use super::*;
+
+    fn record(expiry_date: OffsetDateTime) -> Record {
+        Record {
+            id: Id::default(),
+            data: HashMap::new(),
+            expiry_date,
+        }
+    }
+
+    #[test]
+    fn invalid_url() {
+        assert!(RedisStore::new("not a url").is_err());
+        assert!(RedisStore::from_config(&RedisSessionStoreConfig::default()).is_err());
+    }
+
+    #[test]
+    fn from_config() {
+        let config = RedisSessionStoreConfig::builder()
+            .url("redis://localhost:6379")
+            .key_prefix("test:")
+            .ttl(Duration::from_secs(60))
+            .build();
+
+        let store = RedisStore::from_config(&config).unwrap();
+
+        assert_eq!(store.key_prefix, "test:");
+        assert_eq!(store.ttl, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn key() {
+        let store = RedisStore::new("redis://localhost").unwrap();
+        let id = Id::default();
+
+        assert_eq!(store.key(&id), format!("cot:session:{id}"));
+    }
+
+    #[test]
+    fn expire_at() {
+        let now = OffsetDateTime::now_utc();
+        let store = RedisStore::new("redis://localhost").unwrap();
+        let record = record(now + TimeDuration::hours(2));
+
+        assert_eq!(
+            store.expire_at(&record, now),
+            Some(record.expiry_date.unix_timestamp())
+        );
+        assert_eq!(
+            store.ttl(Duration::from_secs(60)).expire_at(&record, now),
+            Some((now + TimeDuration::minutes(1)).unix_timestamp())
+        );
+    }
+
+    #[test]
+    fn clock() {
+        let clock = crate::test::TestClock::new();
+        let store = RedisStore::new("redis://localhost")
+            .unwrap()
+            .clock(Arc::new(clock.clone()));
+        let record = record(now_offset(&clock) + TimeDuration::minutes(1));
+
+        assert!(
+            store
+                .expire_at(&record, now_offset(store.clock.as_ref()))
+                .is_some()
+        );
+        clock.advance(Duration::from_secs(120));
+        assert!(
+            store
+                .expire_at(&record, now_offset(store.clock.as_ref()))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn expire_at_expired() {
+        let now = OffsetDateTime::now_utc();
+        let store = RedisStore::new("redis://localhost").unwrap();
+
+        assert_eq!(
+            store.expire_at(&record(now - TimeDuration::seconds(1)), now),
+            None
+        );
+    }
+
+    #[cot::test]
+    #[ignore = "requires a Redis server"]
+    async fn store_roundtrip() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost".to_owned());
+        let store = RedisStore::new(&url)
+            .unwrap()
+            .key_prefix("cot:test:session:");
+        let mut record = record(OffsetDateTime::now_utc() + TimeDuration::hours(1));
+        record.data.insert("key".to_owned(), "value".into());
+
+        store.create(&mut record).await.unwrap();
+        let loaded = store.load(&record.id).await.unwrap().unwrap();
+        assert_eq!(loaded.data, record.data);
+
+        record.data.insert("key".to_owned(), "changed".into());
+        store.save(&record).await.unwrap();
+        let loaded = store.load(&record.id).await.unwrap().unwrap();
+        assert_eq!(loaded.data["key"], "changed");
+
+        store.delete(&record.id).await.unwrap();
+        assert!(store.load(&record.id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,221 @@
+//! Shared application state.
+//!
+//! Application state is a set of values that live as long as the project
+//! and are shared by all the requests, such as in-memory caches, counters,
+//! or clients for external services. Each value is identified by its type,
+//! so there can be at most one value of every type in the state.
+//!
+//! The values are registered in [`Project::register_state`] (or
+//! with [`Bootstrapper::with_state`]) and can be retrieved in the request
+//! handlers using [`RequestExt::state`]. They are never cloned—every request
+//! gets a shared reference to the same value.
+//!
+//! Since the values are shared, they can only be accessed immutably. To
+//! modify the state, use types that provide interior mutability, such as
+//! atomics, [`std::sync::Mutex`] or [`std::sync::RwLock`] (or a concurrent
+//! map, like the one provided by the [`dashmap`](https://docs.rs/dashmap)
+//! crate). Make sure that the lock guards are not held across `.await`
+//! points—use the async locks from [`tokio::sync`] if you need to do that.
+//!
+//! [`Project::register_state`]: crate::Project::register_state
+//! [`Bootstrapper::with_state`]: crate::project::Bootstrapper::with_state
+//! [`RequestExt::state`]: crate::request::RequestExt::state
+//!
+//! # Examples
+//!
+//! ```
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//!
+//! use cot::request::{Request, RequestExt};
+//! use cot::response::{Response, ResponseExt};
+//! use cot::state::AppState;
+//! use cot::{Body, Project, StatusCode};
+//!
+//! #[derive(Debug, Default)]
+//! struct VisitCounter(AtomicU64);
+//!
+//! async fn count(request: Request) -> cot::Result<Response> {
+//!     let counter = request.state::<VisitCounter>()?;
+//!     let visits = counter.0.fetch_add(1, Ordering::Relaxed) + 1;
+//!
+//!     Ok(Response::new_html(
+//!         StatusCode::OK,
+//!         Body::fixed(format!("Visits: {visits}")),
+//!     ))
+//! }
+//!
+//! struct MyProject;
+//! impl Project for MyProject {
+//!     fn register_state(&self, state: &mut AppState) {
+//!         state.insert(VisitCounter::default());
+//!     }
+//! }
+//! ```
+
+use std::any::{Any, TypeId, type_name};
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use derive_more::with_trait::Debug;
+
+use crate::error::ErrorRepr;
+
+/// A set of values shared by all the requests, identified by their types.
+///
+/// See the [module documentation](self) for more information.
+///
+/// Cloning an [`AppState`] is cheap and doesn't clone the values themselves;
+/// the clone shares them with the original.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::RwLock;
+///
+/// use cot::state::AppState;
+///
+/// struct Cache(RwLock<Vec<String>>);
+///
+/// let mut state = AppState::new();
+/// state.insert(Cache(RwLock::new(Vec::new())));
+///
+/// let cache = state.get::<Cache>().unwrap();
+/// cache.0.write().unwrap().push("value".to_owned());
+/// assert_eq!(cache.0.read().unwrap().len(), 1);
+/// ```
+#[derive(Default, Clone)]
+pub struct AppState {
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl AppState {
+    /// Creates an empty [`AppState`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::state::AppState;
+    ///
+    /// let state = AppState::new();
+    /// assert!(state.is_empty());
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a value into the state.
+    ///
+    /// If there already is a value of the same type, it is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::state::AppState;
+    ///
+    /// let mut state = AppState::new();
+    /// state.insert(42_u32);
+    /// assert_eq!(state.get::<u32>(), Some(&42));
+    /// ```
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.values.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Returns a reference to the value of the given type, or `None` if
+    /// there is no such value in the state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::state::AppState;
+    ///
+    /// let mut state = AppState::new();
+    /// state.insert("hello");
+    ///
+    /// assert_eq!(state.get::<&str>(), Some(&"hello"));
+    /// assert_eq!(state.get::<u32>(), None);
+    /// ```
+    #[must_use]
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+    }
+
+    /// Returns `true` if there are no values in the state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::state::AppState;
+    ///
+    /// let mut state = AppState::new();
+    /// assert!(state.is_empty());
+    ///
+    /// state.insert(1_i64);
+    /// assert!(!state.is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub(crate) fn try_get<T: Send + Sync + 'static>(&self) -> crate::Result<&T> {
+        self.get::<T>().ok_or_else(|| {
+            ErrorRepr::StateMissing {
+                type_name: type_name::<T>(),
+            }
+            .into()
+        })
+    }
+}
+
+impl Debug for AppState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppState")
+            .field("len", &self.values.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn app_state_replace() {
+        let mut state = AppState::new();
+        state.insert(1_u32);
+        state.insert(2_u32);
+
+        assert_eq!(state.get::<u32>(), Some(&2));
+    }
+
+    #[test]
+    fn app_state_clone_is_shared() {
+        let mut state = AppState::new();
+        state.insert(AtomicU32::new(0));
+        let cloned = state.clone();
+
+        cloned
+            .get::<AtomicU32>()
+            .unwrap()
+            .fetch_add(1, Ordering::Relaxed);
+
+        assert_eq!(state.get::<AtomicU32>().unwrap().load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn app_state_missing() {
+        let state = AppState::new();
+
+        let error = state.try_get::<u32>().unwrap_err();
+        assert!(matches!(
+            error.inner,
+            ErrorRepr::StateMissing { type_name: "u32" }
+        ));
+    }
+}
@@ -12,13 +12,14 @@ use std::task::{Context, Poll};
 
 use bytes::Bytes;
 use futures_core::ready;
-use http::{Request, header};
+use http::{HeaderMap, Method, Request, StatusCode, header};
 use pin_project_lite::pin_project;
 use tower::Service;
 
 use crate::Body;
+use crate::middleware::{is_not_modified, is_range_applicable, not_modified_response};
 use crate::project::MiddlewareContext;
-use crate::response::{Response, ResponseExt};
+use crate::response::{ByteRange, ETag, Response, ResponseExt, content_range};
 
 /// Macro to define static files by specifying their paths.
 ///
@@ -132,23 +133,87 @@ impl From<&MiddlewareContext> for StaticFiles {
 struct File {
     content: Bytes,
     mime_type: mime_guess::Mime,
+    etag: ETag,
 }
 
 impl File {
     #[must_use]
     fn new(content: impl Into<Bytes>, mime_type: mime_guess::Mime) -> Self {
+        let content = content.into();
+        let etag = ETag::from_content(&content);
+
         Self {
-            content: content.into(),
+            content,
             mime_type,
+            etag,
         }
     }
 
+    /// Builds the response serving the file.
+    ///
+    /// `request` contains the headers of the request if it can be served
+    /// partially, i.e. if it's a `GET` request.
     #[must_use]
-    fn as_response(&self) -> Response {
-        Response::builder()
+    fn as_response(&self, options: StaticFilesOptions, request: Option<&HeaderMap>) -> Response {
+        let etag = if options.weak_etags {
+            self.etag.clone().into_weak()
+        } else {
+            self.etag.clone()
+        };
+
+        let mut builder = Response::builder()
             .header(header::CONTENT_TYPE, self.mime_type.to_string())
-            .body(Body::fixed(self.content.clone()))
-            .expect("failed to build static file response")
+            .header(header::ETAG, etag);
+        if options.accept_ranges {
+            builder = builder.header(header::ACCEPT_RANGES, "bytes");
+        }
+
+        let length = self.content.len() as u64;
+        let range = request
+            .filter(|_| options.accept_ranges)
+            .and_then(|request| {
+                let range = request.get(header::RANGE)?;
+                let applicable = builder
+                    .headers_ref()
+                    .is_some_and(|headers| is_range_applicable(request, headers));
+                applicable.then_some(range)
+            })
+            .map_or(ByteRange::Full, |range| ByteRange::parse(range, length));
+        let response = match range {
+            ByteRange::Full => builder.body(Body::fixed(self.content.clone())),
+            ByteRange::Partial(range) => {
+                let content_range = content_range(&range, length);
+                let range = usize::try_from(range.start).expect("range start within file")
+                    ..usize::try_from(range.end).expect("range end within file");
+                builder
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_RANGE, content_range)
+                    .body(Body::fixed(self.content.slice(range)))
+            }
+            ByteRange::Unsatisfiable => builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{length}"))
+                .body(Body::empty()),
+        };
+
+        response.expect("failed to build static file response")
+    }
+}
+
+/// Options for serving static files, shared by the middleware and its
+/// services.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct StaticFilesOptions {
+    accept_ranges: bool,
+    weak_etags: bool,
+}
+
+impl Default for StaticFilesOptions {
+    fn default() -> Self {
+        Self {
+            accept_ranges: true,
+            weak_etags: false,
+        }
     }
 }
 
@@ -161,9 +226,16 @@ impl File {
 /// If a request is made to a path starting with `/static/`, the middleware
 /// checks if the file exists in the static files collection. If it does, the
 /// file is served. Otherwise, the request is passed to the inner service.
+///
+/// Every file is served with a strong `ETag` computed from its content, and
+/// `GET` and `HEAD` requests with a matching `If-None-Match` header get a
+/// `304 Not Modified` response. By default, the middleware also advertises
+/// `Accept-Ranges: bytes` and honors single-range `Range` requests, responding
+/// with `206 Partial Content`.
 #[derive(Debug, Clone)]
 pub struct StaticFilesMiddleware {
     static_files: Arc<StaticFiles>,
+    options: StaticFilesOptions,
 }
 
 impl StaticFilesMiddleware {
@@ -171,17 +243,88 @@ impl StaticFilesMiddleware {
     /// context.
     #[must_use]
     pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::new(Arc::new(StaticFiles::from(context)))
+    }
+
+    fn new(static_files: Arc<StaticFiles>) -> Self {
         Self {
-            static_files: Arc::new(StaticFiles::from(context)),
+            static_files,
+            options: StaticFilesOptions::default(),
         }
     }
+
+    /// Sets whether the static files support range requests.
+    ///
+    /// When enabled (the default), responses contain the
+    /// `Accept-Ranges: bytes` header and requests with a single-range `Range`
+    /// header get a `206 Partial Content` response. When disabled, the header
+    /// is not sent and the `Range` header is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::static_files::StaticFilesMiddleware;
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(StaticFilesMiddleware::from_context(context).accept_ranges(false))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn accept_ranges(mut self, accept_ranges: bool) -> Self {
+        self.options.accept_ranges = accept_ranges;
+        self
+    }
+
+    /// Sets whether the `ETag`s of the static files should be weak.
+    ///
+    /// By default, strong entity tags are used, as the served content is
+    /// always byte-for-byte identical for a given file. Weak tags might be
+    /// preferable if the responses are further transformed, e.g. compressed,
+    /// by other middlewares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::{MiddlewareContext, RootHandlerBuilder};
+    /// use cot::static_files::StaticFilesMiddleware;
+    /// use cot::{BoxedHandler, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> BoxedHandler {
+    ///         handler
+    ///             .middleware(StaticFilesMiddleware::from_context(context).weak_etags(true))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn weak_etags(mut self, weak_etags: bool) -> Self {
+        self.options.weak_etags = weak_etags;
+        self
+    }
 }
 
 impl<S> tower::Layer<S> for StaticFilesMiddleware {
     type Service = StaticFilesService<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        StaticFilesService::new(Arc::clone(&self.static_files), inner)
+        StaticFilesService::new(Arc::clone(&self.static_files), self.options, inner)
     }
 }
 
@@ -189,15 +332,17 @@ impl<S> tower::Layer<S> for StaticFilesMiddleware {
 #[derive(Clone, Debug)]
 pub struct StaticFilesService<S> {
     static_files: Arc<StaticFiles>,
+    options: StaticFilesOptions,
     inner: S,
 }
 
 impl<S> StaticFilesService<S> {
     /// Create a new static files service.
     #[must_use]
-    fn new(static_files: Arc<StaticFiles>, inner: S) -> Self {
+    fn new(static_files: Arc<StaticFiles>, options: StaticFilesOptions, inner: S) -> Self {
         Self {
             static_files,
+            options,
             inner,
         }
     }
@@ -221,9 +366,18 @@ where
 
         let path = req.uri().path();
         let file_contents = if let Some(stripped_path) = path.strip_prefix(STATIC_PATH) {
-            self.static_files
-                .get_file(stripped_path)
-                .map(File::as_response)
+            // `Range` is only defined for GET requests
+            let range_request = (req.method() == Method::GET).then(|| req.headers());
+            let conditional = req.method() == Method::GET || req.method() == Method::HEAD;
+
+            self.static_files.get_file(stripped_path).map(|file| {
+                let response = file.as_response(self.options, range_request);
+                if conditional && is_not_modified(req.headers(), response.headers()) {
+                    not_modified_response(response)
+                } else {
+                    response
+                }
+            })
         } else {
             None
         };
@@ -305,10 +459,15 @@ mod tests {
     async fn file_as_response() {
         let file = File::new("This is a test file", mime_guess::mime::TEXT_PLAIN);
 
-        let response = file.as_response();
+        let response = file.as_response(StaticFilesOptions::default(), None);
 
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(response.headers()["content-type"], "text/plain");
+        assert_eq!(response.headers()["accept-ranges"], "bytes");
+        assert_eq!(
+            response.headers()["etag"],
+            ETag::from_content(b"This is a test file").to_string()
+        );
         assert_eq!(
             response.into_body().into_bytes().await.unwrap(),
             Bytes::from("This is a test file")
@@ -324,9 +483,7 @@ mod tests {
     #[cot::test]
     async fn static_files_middleware() {
         let static_files = Arc::new(create_static_files());
-        let middleware = StaticFilesMiddleware {
-            static_files: Arc::clone(&static_files),
-        };
+        let middleware = StaticFilesMiddleware::new(Arc::clone(&static_files));
 
         let service = middleware.layer(tower::service_fn(|_req| async {
             Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
@@ -350,9 +507,7 @@ mod tests {
     #[cot::test]
     async fn static_files_middleware_not_found() {
         let static_files = Arc::new(create_static_files());
-        let middleware = StaticFilesMiddleware {
-            static_files: Arc::clone(&static_files),
-        };
+        let middleware = StaticFilesMiddleware::new(Arc::clone(&static_files));
         let service = middleware.layer(tower::service_fn(|_req| async {
             Ok::<_, std::convert::Infallible>(Response::new(Body::fixed("test")))
         }));
@@ -371,6 +526,190 @@ mod tests {
         );
     }
 
+    async fn static_file_response(
+        middleware: StaticFilesMiddleware,
+        request: Request<Body>,
+    ) -> Response {
+        let service = middleware.layer(tower::service_fn(|_req| async {
+            Ok::<_, std::convert::Infallible>(Response::new(Body::fixed("test")))
+        }));
+
+        service.oneshot(request).await.unwrap()
+    }
+
+    fn range_request(uri: &str, range: &str) -> Request<Body> {
+        Request::builder()
+            .uri(uri)
+            .header(header::RANGE, range)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_accept_ranges() {
+        let middleware = StaticFilesMiddleware::new(Arc::new(create_static_files()));
+
+        let response = static_file_response(
+            middleware.clone(),
+            Request::builder()
+                .uri("/static/test.txt")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.headers()["accept-ranges"], "bytes");
+
+        // not served by the static files middleware, so not range-capable
+        let response = static_file_response(
+            middleware,
+            Request::builder()
+                .uri("/static/nonexistent.txt")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert!(!response.headers().contains_key("accept-ranges"));
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_accept_ranges_disabled() {
+        let middleware =
+            StaticFilesMiddleware::new(Arc::new(create_static_files())).accept_ranges(false);
+
+        let response =
+            static_file_response(middleware, range_request("/static/test.txt", "bytes=0-3")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("accept-ranges"));
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            Bytes::from("This is a test file")
+        );
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_range() {
+        let middleware = StaticFilesMiddleware::new(Arc::new(create_static_files()));
+
+        let response =
+            static_file_response(middleware, range_request("/static/test.txt", "bytes=5-8")).await;
+
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()["content-range"], "bytes 5-8/19");
+        assert_eq!(response.headers()["accept-ranges"], "bytes");
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            Bytes::from("is a")
+        );
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_range_not_satisfiable() {
+        let middleware = StaticFilesMiddleware::new(Arc::new(create_static_files()));
+
+        let response =
+            static_file_response(middleware, range_request("/static/test.txt", "bytes=100-")).await;
+
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()["content-range"], "bytes */19");
+    }
+
+    fn if_range_request(if_range: &str) -> Request<Body> {
+        Request::builder()
+            .uri("/static/test.txt")
+            .header(header::RANGE, "bytes=5-8")
+            .header(header::IF_RANGE, if_range)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_if_range() {
+        let middleware = StaticFilesMiddleware::new(Arc::new(create_static_files()));
+        let etag = ETag::from_content(b"This is a test file").to_string();
+
+        let response = static_file_response(middleware.clone(), if_range_request(&etag)).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()["content-range"], "bytes 5-8/19");
+
+        let response = static_file_response(middleware, if_range_request("\"outdated\"")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            Bytes::from("This is a test file")
+        );
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_if_range_weak_etag() {
+        let middleware =
+            StaticFilesMiddleware::new(Arc::new(create_static_files())).weak_etags(true);
+        let etag = ETag::from_content(b"This is a test file")
+            .into_weak()
+            .to_string();
+
+        // weak entity tags never match in `If-Range`
+        let response = static_file_response(middleware, if_range_request(&etag)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_weak_etags() {
+        let middleware =
+            StaticFilesMiddleware::new(Arc::new(create_static_files())).weak_etags(true);
+
+        let response = static_file_response(
+            middleware,
+            Request::builder()
+                .uri("/static/test.txt")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(
+            response.headers()["etag"],
+            ETag::from_content(b"This is a test file")
+                .into_weak()
+                .to_string()
+        );
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_if_none_match() {
+        let middleware = StaticFilesMiddleware::new(Arc::new(create_static_files()));
+        let etag = ETag::from_content(b"This is a test file").to_string();
+
+        let response = static_file_response(
+            middleware.clone(),
+            Request::builder()
+                .uri("/static/test.txt")
+                .header(header::IF_NONE_MATCH, &etag)
+                .header(header::RANGE, "bytes=5-8")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()["etag"], etag);
+        assert!(!response.headers().contains_key("content-type"));
+        assert!(response.into_body().into_bytes().await.unwrap().is_empty());
+
+        let response = static_file_response(
+            middleware,
+            Request::builder()
+                .uri("/static/test.txt")
+                .header(header::IF_NONE_MATCH, "\"outdated\"")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
     #[cot::test]
     #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `sqlite3_open_v2`
     async fn static_files_middleware_from_context() {
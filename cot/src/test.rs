@@ -5,17 +5,21 @@ use std::future::poll_fn;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use derive_more::Debug;
 use tower::Service;
-use tower_sessions::MemoryStore;
 
 #[cfg(feature = "db")]
 use crate::auth::db::DatabaseUserBackend;
 use crate::auth::{Auth, AuthBackend, NoAuthBackend, User, UserId};
+use crate::clock::{Clock, SystemClock};
 use crate::config::ProjectConfig;
 #[cfg(feature = "db")]
 use crate::db::Database;
 #[cfg(feature = "db")]
+use crate::db::fixtures::{Fixture, FixtureSchema};
+#[cfg(feature = "db")]
 use crate::db::migrations::{
     DynMigration, MigrationDependency, MigrationEngine, MigrationWrapper, Operation,
 };
@@ -25,6 +29,8 @@ use crate::request::Request;
 use crate::response::Response;
 use crate::router::Router;
 use crate::session::Session;
+use crate::session::store::MemoryStore;
+use crate::state::AppState;
 use crate::{Body, Bootstrapper, Project, ProjectContext, Result};
 
 /// A test client for making requests to a Cot project.
@@ -197,15 +203,26 @@ pub struct TestRequestBuilder {
     router: Option<Router>,
     session: Option<Session>,
     config: Option<Arc<ProjectConfig>>,
+    state: AppState,
+    clock: Option<Arc<dyn Clock>>,
     auth_backend: Option<AuthBackendWrapper>,
     auth: Option<Auth>,
     #[cfg(feature = "db")]
     database: Option<Arc<Database>>,
     form_data: Option<Vec<(String, String)>>,
+    multipart_parts: Option<Vec<TestMultipartPart>>,
     #[cfg(feature = "json")]
     json_data: Option<String>,
 }
 
+/// A part of a `multipart/form-data` body built by [`TestRequestBuilder`].
+#[derive(Debug, Clone)]
+struct TestMultipartPart {
+    name: String,
+    file: Option<(String, String)>,
+    data: Bytes,
+}
+
 /// A wrapper over an auth backend that is cloneable.
 #[derive(Debug, Clone)]
 struct AuthBackendWrapper {
@@ -249,11 +266,14 @@ impl Default for TestRequestBuilder {
             router: None,
             session: None,
             config: None,
+            state: AppState::new(),
+            clock: None,
             auth_backend: None,
             auth: None,
             #[cfg(feature = "db")]
             database: None,
             form_data: None,
+            multipart_parts: None,
             #[cfg(feature = "json")]
             json_data: None,
         }
@@ -427,6 +447,46 @@ impl TestRequestBuilder {
         self
     }
 
+    /// Add a value to the shared application state for the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// use cot::request::RequestExt;
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// let request = TestRequestBuilder::get("/")
+    ///     .state(AtomicU64::new(0))
+    ///     .build();
+    /// assert!(request.state::<AtomicU64>().is_ok());
+    /// ```
+    pub fn state<T: Send + Sync + 'static>(&mut self, state: T) -> &mut Self {
+        self.state.insert(state);
+        self
+    }
+
+    /// Set the clock used as the source of the current time for the request.
+    ///
+    /// By default, the [`SystemClock`] is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::clock::Clock;
+    /// use cot::request::RequestExt;
+    /// use cot::test::{TestClock, TestRequestBuilder};
+    ///
+    /// let clock = TestClock::new();
+    /// let request = TestRequestBuilder::get("/").clock(clock.clone()).build();
+    /// assert_eq!(request.context().clock().now(), clock.now());
+    /// ```
+    pub fn clock<C: Clock>(&mut self, clock: C) -> &mut Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
     /// Add a session support to the request builder.
     ///
     /// # Examples
@@ -605,6 +665,60 @@ impl TestRequestBuilder {
         self
     }
 
+    /// Add a text field to the `multipart/form-data` body of the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// let request = TestRequestBuilder::post("/")
+    ///     .multipart_field("name", "Alice")
+    ///     .build();
+    /// ```
+    pub fn multipart_field<N: Into<String>, V: Into<String>>(
+        &mut self,
+        name: N,
+        value: V,
+    ) -> &mut Self {
+        self.multipart_parts
+            .get_or_insert_default()
+            .push(TestMultipartPart {
+                name: name.into(),
+                file: None,
+                data: Bytes::from(value.into()),
+            });
+        self
+    }
+
+    /// Add a file to the `multipart/form-data` body of the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// let request = TestRequestBuilder::post("/")
+    ///     .multipart_file("avatar", "avatar.png", "image/png", &b"\x89PNG"[..])
+    ///     .build();
+    /// ```
+    pub fn multipart_file<D: Into<Bytes>>(
+        &mut self,
+        name: &str,
+        file_name: &str,
+        content_type: &str,
+        data: D,
+    ) -> &mut Self {
+        self.multipart_parts
+            .get_or_insert_default()
+            .push(TestMultipartPart {
+                name: name.to_owned(),
+                file: Some((file_name.to_owned(), content_type.to_owned())),
+                data: data.into(),
+            });
+        self
+    }
+
     /// Add JSON data to the request builder.
     ///
     /// # Examples
@@ -688,6 +802,8 @@ impl TestRequestBuilder {
             auth_backend,
             #[cfg(feature = "db")]
             self.database.clone(),
+            self.state.clone(),
+            self.clock.clone().unwrap_or_else(|| Arc::new(SystemClock)),
         );
         prepare_request(&mut request, Arc::new(context));
 
@@ -716,6 +832,40 @@ impl TestRequestBuilder {
             );
         }
 
+        if let Some(parts) = &self.multipart_parts {
+            const BOUNDARY: &str = "cot-test-boundary";
+            const MULTIPART_CONTENT_TYPE: &str = "multipart/form-data; boundary=cot-test-boundary";
+
+            let mut body = Vec::new();
+            for part in parts {
+                body.extend_from_slice(format!("--{BOUNDARY}\r\n").as_bytes());
+                let name = part.name.replace('"', "%22");
+                match &part.file {
+                    Some((file_name, content_type)) => body.extend_from_slice(
+                        format!(
+                            "Content-Disposition: form-data; name=\"{name}\"; filename=\"{}\"\r\n\
+                             Content-Type: {content_type}\r\n\r\n",
+                            file_name.replace('"', "%22")
+                        )
+                        .as_bytes(),
+                    ),
+                    None => body.extend_from_slice(
+                        format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n")
+                            .as_bytes(),
+                    ),
+                }
+                body.extend_from_slice(&part.data);
+                body.extend_from_slice(b"\r\n");
+            }
+            body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+            *request.body_mut() = Body::fixed(body);
+            request.headers_mut().insert(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static(MULTIPART_CONTENT_TYPE),
+            );
+        }
+
         #[cfg(feature = "json")]
         if let Some(json_data) = &self.json_data {
             *request.body_mut() = Body::fixed(json_data.clone());
@@ -729,6 +879,136 @@ impl TestRequestBuilder {
     }
 }
 
+/// A [`Clock`] that only moves when it is told to.
+///
+/// This is useful for testing the time-dependent parts of a project (such as
+/// expiry logic) deterministically. The clock can be passed to
+/// [`Bootstrapper::with_clock`] or [`TestRequestBuilder::clock`]; all the
+/// clones of a [`TestClock`] share the same time, so the test can keep a clone
+/// and advance it while the project is running.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::clock::Clock;
+/// use cot::test::TestClock;
+///
+/// let clock = TestClock::new();
+/// let start = clock.now();
+///
+/// clock.advance(Duration::from_secs(60));
+/// assert_eq!((clock.now() - start).num_seconds(), 60);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TestClock {
+    now: Arc<std::sync::Mutex<DateTime<Utc>>>,
+}
+
+impl TestClock {
+    /// Create a new test clock, stopped at the current system time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::TestClock;
+    ///
+    /// let clock = TestClock::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::at(Utc::now())
+    }
+
+    /// Create a new test clock, stopped at the given time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cot::clock::Clock;
+    /// use cot::test::TestClock;
+    ///
+    /// let time = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    /// let clock = TestClock::at(time);
+    /// assert_eq!(clock.now(), time);
+    /// ```
+    #[must_use]
+    pub fn at(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(std::sync::Mutex::new(now)),
+        }
+    }
+
+    /// Move the clock forward by the given duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting time is out of the range supported by
+    /// [`DateTime`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::clock::Clock;
+    /// use cot::test::TestClock;
+    ///
+    /// let clock = TestClock::new();
+    /// let start = clock.now();
+    ///
+    /// clock.advance(Duration::from_secs(5));
+    /// assert_eq!((clock.now() - start).num_seconds(), 5);
+    /// ```
+    pub fn advance(&self, duration: std::time::Duration) {
+        let duration =
+            chrono::Duration::from_std(duration).expect("duration should be in the valid range");
+        let mut now = self.lock();
+        *now = now
+            .checked_add_signed(duration)
+            .expect("time should be in the valid range");
+    }
+
+    /// Set the clock to the given time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cot::clock::Clock;
+    /// use cot::test::TestClock;
+    ///
+    /// let clock = TestClock::new();
+    /// let time = Utc.with_ymd_and_hms(2030, 6, 15, 12, 0, 0).unwrap();
+    ///
+    /// clock.set(time);
+    /// assert_eq!(clock.now(), time);
+    /// ```
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.lock() = now;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DateTime<Utc>> {
+        self.now
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.lock()
+    }
+}
+
 /// A test database.
 ///
 /// This is used to create a separate database for testing and run migrations on
@@ -758,6 +1038,7 @@ pub struct TestDatabase {
     database: Arc<Database>,
     kind: TestDatabaseKind,
     migrations: Vec<MigrationWrapper>,
+    fixture_schema: FixtureSchema,
 }
 
 #[cfg(feature = "db")]
@@ -767,6 +1048,7 @@ impl TestDatabase {
             database: Arc::new(database),
             kind,
             migrations: Vec::new(),
+            fixture_schema: FixtureSchema::default(),
         }
     }
 
@@ -1030,10 +1312,112 @@ impl TestDatabase {
                 .run(&self.database())
                 .await
                 .expect("Failed to run migrations");
+            self.fixture_schema.add_migrations(&engine);
         }
         self
     }
 
+    /// Load a fixture into the test database.
+    ///
+    /// The tables of the fixture have to be created by the migrations run
+    /// with [`Self::run_migrations`]. See the
+    /// [`fixtures`](crate::db::fixtures) module for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fixture could not be loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::fixtures::{Fixture, FixtureFormat};
+    /// use cot::test::TestDatabase;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let mut test_database = TestDatabase::new_sqlite().await?;
+    /// test_database.with_auth().run_migrations().await;
+    ///
+    /// let fixture = Fixture::from_format(
+    ///     r#"[{
+    ///         "table": "cot__database_user",
+    ///         "fields": {"id": 1, "username": "alice", "password": "not a real hash"}
+    ///     }]"#,
+    ///     FixtureFormat::Json,
+    /// )?;
+    /// test_database.load_fixture(&fixture).await;
+    ///
+    /// let fixture = test_database.dump_fixture().await;
+    /// assert_eq!(fixture.records()[0].fields["username"], "alice");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn load_fixture(&mut self, fixture: &Fixture) -> &mut Self {
+        fixture
+            .load(&self.database, &self.fixture_schema)
+            .await
+            .expect("Failed to load the fixture");
+        self
+    }
+
+    /// Dump all the tables created by the migrations run with
+    /// [`Self::run_migrations`] to a fixture.
+    ///
+    /// This is useful for checking the state of the database at the end of a
+    /// test.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fixture could not be dumped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::TestDatabase;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let mut test_database = TestDatabase::new_sqlite().await?;
+    /// test_database.with_auth().run_migrations().await;
+    ///
+    /// let fixture = test_database.dump_fixture().await;
+    /// assert!(fixture.records().is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn dump_fixture(&self) -> Fixture {
+        Fixture::dump(&self.database, &self.fixture_schema, &[])
+            .await
+            .expect("Failed to dump the fixture")
+    }
+
+    /// Sets the clock used by the database as the source of the current time
+    /// for the `auto_now` and `auto_now_add` model fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the database is already shared, i.e. if the [`Arc`]
+    /// returned by [`Self::database`] is still alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::{TestClock, TestDatabase};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let mut test_database = TestDatabase::new_sqlite().await?;
+    /// test_database.with_clock(TestClock::new());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_clock<C: Clock>(&mut self, clock: C) -> &mut Self {
+        Arc::get_mut(&mut self.database)
+            .expect("the database should not be shared when setting the clock")
+            .set_clock(Arc::new(clock));
+        self
+    }
+
     /// Get the database.
     ///
     /// # Examples
@@ -0,0 +1,198 @@
+//! Timezone of the requests.
+//!
+//! The dates and times are stored in the database in UTC (see
+//! [`DateTime<Utc>`](chrono::DateTime) model fields), which makes them
+//! unambiguous regardless of where the server is deployed. When displaying
+//! them, however, they should usually be converted to the timezone of the
+//! user.
+//!
+//! The [`TimezoneMiddleware`](crate::middleware::TimezoneMiddleware) resolves
+//! the active [`Timezone`] of each request, which can then be accessed in the
+//! request handlers with
+//! [`RequestExt::timezone`](crate::request::RequestExt::timezone) or by using
+//! [`Timezone`] as an extractor.
+//!
+//! # Examples
+//!
+//! ```
+//! use chrono::{DateTime, Utc};
+//! use cot::response::{Response, ResponseExt};
+//! use cot::timezone::Timezone;
+//! use cot::{Body, StatusCode};
+//!
+//! async fn index(timezone: Timezone) -> cot::Result<Response> {
+//!     let published_at: DateTime<Utc> = Utc::now();
+//!     let local = timezone.localize(&published_at);
+//!     Ok(Response::new_html(
+//!         StatusCode::OK,
+//!         Body::fixed(format!("Published at {}", local.format("%Y-%m-%d %H:%M"))),
+//!     ))
+//! }
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, TimeZone};
+
+use crate::error::ErrorRepr;
+
+/// The active timezone of a request, resolved by
+/// [`TimezoneMiddleware`](crate::middleware::TimezoneMiddleware).
+///
+/// The timezone is represented as a fixed offset from UTC, such as `+02:00`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use cot::timezone::Timezone;
+///
+/// let timezone: Timezone = "+02:00".parse().unwrap();
+/// let datetime = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+///
+/// assert_eq!(
+///     timezone.localize(&datetime).to_rfc3339(),
+///     "2025-01-01T14:00:00+02:00"
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Timezone(FixedOffset);
+
+impl Timezone {
+    /// The UTC timezone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::timezone::Timezone;
+    ///
+    /// assert_eq!(Timezone::UTC.to_string(), "+00:00");
+    /// ```
+    pub const UTC: Self = Self(FixedOffset::east_opt(0).expect("UTC offset is always valid"));
+
+    /// Creates a new [`Timezone`] with the given offset from UTC.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::FixedOffset;
+    /// use cot::timezone::Timezone;
+    ///
+    /// let timezone = Timezone::new(FixedOffset::west_opt(5 * 3600).unwrap());
+    /// assert_eq!(timezone.to_string(), "-05:00");
+    /// ```
+    #[must_use]
+    pub const fn new(offset: FixedOffset) -> Self {
+        Self(offset)
+    }
+
+    /// Returns the offset of the timezone from UTC.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::FixedOffset;
+    /// use cot::timezone::Timezone;
+    ///
+    /// let offset = FixedOffset::east_opt(3600).unwrap();
+    /// assert_eq!(Timezone::new(offset).offset(), offset);
+    /// ```
+    #[must_use]
+    pub const fn offset(&self) -> FixedOffset {
+        self.0
+    }
+
+    /// Converts a date and time to this timezone, e.g. for displaying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cot::timezone::Timezone;
+    ///
+    /// let timezone: Timezone = "-03:30".parse().unwrap();
+    /// let datetime = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    ///
+    /// assert_eq!(
+    ///     timezone.localize(&datetime).to_rfc3339(),
+    ///     "2025-01-01T08:30:00-03:30"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn localize<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> DateTime<FixedOffset> {
+        datetime.with_timezone(&self.0)
+    }
+
+    pub(crate) fn try_from_extensions(extensions: &http::Extensions) -> crate::Result<&Self> {
+        extensions
+            .get::<Self>()
+            .ok_or_else(|| crate::Error::new(ErrorRepr::TimezoneMissing))
+    }
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Self::UTC
+    }
+}
+
+impl From<FixedOffset> for Timezone {
+    fn from(offset: FixedOffset) -> Self {
+        Self::new(offset)
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = chrono::ParseError;
+
+    /// Parses an offset from UTC, such as `+02:00`, `-0530` or `+01`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(Self)
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "+02:00".parse::<Timezone>().unwrap().offset(),
+            FixedOffset::east_opt(2 * 3600).unwrap()
+        );
+        assert_eq!(
+            "-0530".parse::<Timezone>().unwrap().offset(),
+            FixedOffset::west_opt(5 * 3600 + 30 * 60).unwrap()
+        );
+        assert_eq!(" +00:00 ".parse::<Timezone>().unwrap(), Timezone::UTC);
+        assert!("Europe/Warsaw".parse::<Timezone>().is_err());
+        assert!("".parse::<Timezone>().is_err());
+    }
+
+    #[test]
+    fn localize() {
+        let timezone: Timezone = "+09:00".parse().unwrap();
+        let datetime = Utc.with_ymd_and_hms(2025, 12, 31, 20, 0, 0).unwrap();
+
+        let local = timezone.localize(&datetime);
+
+        assert_eq!(local.to_rfc3339(), "2026-01-01T05:00:00+09:00");
+        assert_eq!(local, datetime);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Timezone::default().to_string(), "+00:00");
+        assert_eq!("-08:00".parse::<Timezone>().unwrap().to_string(), "-08:00");
+    }
+}
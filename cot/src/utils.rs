@@ -1,2 +1,3 @@
 #[cfg(feature = "db")]
 pub(crate) mod graph;
+pub(crate) mod tasks;
@@ -0,0 +1,77 @@
+use std::future::Future;
+use std::sync::Mutex;
+
+use futures_core::future::BoxFuture;
+
+/// The background tasks started by the project (such as the
+/// [`SessionJanitor`](crate::session::store::SessionJanitor) of the session
+/// middleware), which are stopped when the server shuts down gracefully.
+#[derive(Default)]
+pub(crate) struct BackgroundTasks {
+    stops: Mutex<Vec<BoxFuture<'static, ()>>>,
+}
+
+impl std::fmt::Debug for BackgroundTasks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackgroundTasks")
+            .field("len", &self.lock().len())
+            .finish()
+    }
+}
+
+impl BackgroundTasks {
+    /// Registers a background task, given the future that stops it and waits
+    /// for it to finish.
+    pub(crate) fn track<F>(&self, stop: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.lock().push(Box::pin(stop));
+    }
+
+    /// Stops all the registered tasks, waiting for each of them to finish.
+    pub(crate) async fn shutdown(&self) {
+        let stops = std::mem::take(&mut *self.lock());
+        for stop in stops {
+            stop.await;
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<BoxFuture<'static, ()>>> {
+        self.stops
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[cot::test]
+    async fn shutdown_stops_tasks() {
+        let tasks = BackgroundTasks::default();
+        let stopped = Arc::new(AtomicUsize::new(0));
+        for _ in 0..2 {
+            let stopped = Arc::clone(&stopped);
+            tasks.track(async move {
+                stopped.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        tasks.shutdown().await;
+        assert_eq!(stopped.load(Ordering::Relaxed), 2);
+
+        // the tasks are only stopped once
+        tasks.shutdown().await;
+        assert_eq!(stopped.load(Ordering::Relaxed), 2);
+    }
+}
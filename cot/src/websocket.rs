@@ -0,0 +1,1349 @@
+//! WebSocket support.
+//!
+//! A WebSocket connection starts as a regular HTTP `GET` request, which is
+//! then upgraded to a persistent, bidirectional connection. The request is
+//! accepted with the [`WebSocketUpgrade`] extractor, which returns the
+//! response that completes the handshake and runs a callback with the
+//! [`WebSocket`] once the connection is upgraded:
+//!
+//! ```
+//! use cot::response::Response;
+//! use cot::websocket::{Message, WebSocket, WebSocketUpgrade};
+//!
+//! async fn chat(upgrade: WebSocketUpgrade) -> cot::Result<Response> {
+//!     Ok(upgrade.on_upgrade(echo))
+//! }
+//!
+//! async fn echo(mut socket: WebSocket) {
+//!     while let Some(Ok(message)) = socket.recv().await {
+//!         if let Message::Text(text) = message {
+//!             if socket.send(Message::Text(text)).await.is_err() {
+//!                 break;
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! When the handler doesn't need anything from the request, it can be
+//! registered with
+//! [`Route::with_websocket_handler`](crate::router::Route::with_websocket_handler)
+//! instead:
+//!
+//! ```
+//! use cot::router::{Route, Router};
+//! use cot::websocket::WebSocket;
+//!
+//! async fn echo(mut socket: WebSocket) {
+//!     while let Some(Ok(message)) = socket.recv().await {
+//!         if socket.send(message).await.is_err() {
+//!             break;
+//!         }
+//!     }
+//! }
+//!
+//! let router = Router::with_urls([Route::with_websocket_handler("/ws", echo)]);
+//! ```
+
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+
+use base64::Engine;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::request::Parts;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header};
+use hyper::upgrade::OnUpgrade;
+use hyper_util::rt::TokioIo;
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::debug;
+
+use crate::Body;
+use crate::request::extractors::FromRequestParts;
+use crate::response::Response;
+
+/// The GUID appended to the client key to compute the accept key, as defined
+/// by [RFC 6455](https://datatracker.ietf.org/doc/html/rfc6455#section-1.3).
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+const MAX_CONTROL_PAYLOAD_SIZE: usize = 125;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// An error that can occur while accepting or using a WebSocket connection.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum WebSocketError {
+    /// The upgrade request doesn't use the `GET` method.
+    #[error("WebSocket upgrade requests must use the GET method")]
+    MethodNotGet,
+    /// The `Connection: upgrade` or `Upgrade: websocket` header is missing.
+    #[error("The request is not a WebSocket upgrade request")]
+    NotUpgradeRequest,
+    /// The WebSocket version requested by the client is not supported.
+    #[error("Unsupported WebSocket version; only version 13 is supported")]
+    UnsupportedVersion,
+    /// The `Sec-WebSocket-Key` header is missing.
+    #[error("The Sec-WebSocket-Key header is missing")]
+    MissingKey,
+    /// The connection the request was received on can't be upgraded (e.g.
+    /// the request was not received by the Cot server).
+    #[error("The connection can't be upgraded")]
+    NotUpgradable,
+    /// The peer violated the WebSocket protocol.
+    #[error("WebSocket protocol error: {0}")]
+    Protocol(&'static str),
+    /// A text message received from the peer is not valid UTF-8.
+    #[error("WebSocket text message is not valid UTF-8")]
+    InvalidUtf8,
+    /// A message received from the peer is larger than the limit.
+    #[error("WebSocket message exceeds the limit of {limit} bytes")]
+    MessageTooLarge {
+        /// The maximum size of a message, in bytes.
+        limit: usize,
+    },
+    /// The connection has already been closed.
+    #[error("The WebSocket connection is closed")]
+    ConnectionClosed,
+    /// Reading from or writing to the connection failed.
+    #[error("WebSocket I/O error: {0}")]
+    Io(#[source] std::io::Error),
+}
+
+impl WebSocketError {
+    /// Returns `true` if the error was caused by an invalid upgrade request.
+    pub(crate) fn is_handshake_error(&self) -> bool {
+        matches!(
+            self,
+            Self::MethodNotGet
+                | Self::NotUpgradeRequest
+                | Self::UnsupportedVersion
+                | Self::MissingKey
+        )
+    }
+
+    /// The close code sent to the peer when this error is caused by it.
+    fn close_code(&self) -> Option<u16> {
+        match self {
+            Self::Protocol(_) => Some(CloseFrame::PROTOCOL_ERROR),
+            Self::InvalidUtf8 => Some(CloseFrame::INVALID_DATA),
+            Self::MessageTooLarge { .. } => Some(CloseFrame::MESSAGE_TOO_BIG),
+            _ => None,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, WebSocketError>;
+
+/// An extractor accepting WebSocket upgrade requests.
+///
+/// Extracting fails with `400 Bad Request` if the request is not a valid
+/// WebSocket upgrade request. The handshake is completed by returning the
+/// response created by [`Self::on_upgrade`] from the request handler.
+///
+/// See the [module documentation](self) for more information.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::Response;
+/// use cot::websocket::{WebSocket, WebSocketUpgrade};
+///
+/// async fn ws(upgrade: WebSocketUpgrade) -> cot::Result<Response> {
+///     Ok(upgrade.on_upgrade(|socket: WebSocket| async move {
+///         // ...
+///     }))
+/// }
+/// ```
+pub struct WebSocketUpgrade {
+    on_upgrade: OnUpgrade,
+    key: HeaderValue,
+    requested_protocols: Vec<String>,
+    protocol: Option<String>,
+    max_message_size: usize,
+}
+
+impl Debug for WebSocketUpgrade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketUpgrade")
+            .field("requested_protocols", &self.requested_protocols)
+            .field("protocol", &self.protocol)
+            .field("max_message_size", &self.max_message_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebSocketUpgrade {
+    /// Sets the subprotocols supported by the server, in the order of
+    /// preference.
+    ///
+    /// The first of them that was also requested by the client (in the
+    /// `Sec-WebSocket-Protocol` header) is selected and sent back to the
+    /// client. If none of them was requested, no subprotocol is selected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::websocket::{WebSocket, WebSocketUpgrade};
+    ///
+    /// async fn ws(upgrade: WebSocketUpgrade) -> cot::Result<Response> {
+    ///     Ok(upgrade
+    ///         .protocols(["graphql-transport-ws"])
+    ///         .on_upgrade(|socket: WebSocket| async move {
+    ///             assert_eq!(socket.protocol(), Some("graphql-transport-ws"));
+    ///         }))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn protocols<I, T>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.protocol = protocols
+            .into_iter()
+            .map(Into::into)
+            .find(|protocol| self.requested_protocols.contains(protocol));
+        self
+    }
+
+    /// Returns the subprotocols requested by the client.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::websocket::{WebSocket, WebSocketUpgrade};
+    ///
+    /// async fn ws(upgrade: WebSocketUpgrade) -> cot::Result<Response> {
+    ///     println!("Requested: {:?}", upgrade.requested_protocols());
+    ///     Ok(upgrade.on_upgrade(|socket: WebSocket| async move {}))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn requested_protocols(&self) -> &[String] {
+        &self.requested_protocols
+    }
+
+    /// Sets the maximum size of a message received from the client, in
+    /// bytes.
+    ///
+    /// Receiving a larger message fails with
+    /// [`WebSocketError::MessageTooLarge`] and closes the connection. The
+    /// default is 64 mebibytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::websocket::{WebSocket, WebSocketUpgrade};
+    ///
+    /// async fn ws(upgrade: WebSocketUpgrade) -> cot::Result<Response> {
+    ///     Ok(upgrade
+    ///         .max_message_size(64 * 1024)
+    ///         .on_upgrade(|socket: WebSocket| async move {}))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn max_message_size(self, max_message_size: usize) -> Self {
+        Self {
+            max_message_size,
+            ..self
+        }
+    }
+
+    /// Completes the handshake, returning the `101 Switching Protocols`
+    /// response that must be returned from the request handler.
+    ///
+    /// Once the response is sent, `callback` is run in a new task with the
+    /// upgraded connection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::websocket::{Message, WebSocket, WebSocketUpgrade};
+    ///
+    /// async fn ws(upgrade: WebSocketUpgrade) -> cot::Result<Response> {
+    ///     Ok(upgrade.on_upgrade(|mut socket: WebSocket| async move {
+    ///         let _ = socket.send(Message::Text("Hello!".to_owned())).await;
+    ///     }))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn on_upgrade<F, Fut>(self, callback: F) -> Response
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+        let headers = response.headers_mut();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(
+            header::SEC_WEBSOCKET_ACCEPT,
+            accept_key(self.key.as_bytes()),
+        );
+        // the protocol comes from a request header, so it's always a valid value
+        if let Some(Ok(protocol)) = self.protocol.as_deref().map(HeaderValue::try_from) {
+            headers.insert(header::SEC_WEBSOCKET_PROTOCOL, protocol);
+        }
+
+        let Self {
+            on_upgrade,
+            protocol,
+            max_message_size,
+            ..
+        } = self;
+        tokio::spawn(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => {
+                    let socket = WebSocket::new(TokioIo::new(upgraded), protocol, max_message_size);
+                    callback(socket).await;
+                }
+                Err(error) => debug!("Failed to upgrade the connection: {error}"),
+            }
+        });
+
+        response
+    }
+}
+
+impl FromRequestParts for WebSocketUpgrade {
+    async fn from_request_parts(parts: &mut Parts) -> cot::Result<Self> {
+        if parts.method != Method::GET {
+            return Err(WebSocketError::MethodNotGet.into());
+        }
+        if !header_contains_token(&parts.headers, &header::CONNECTION, "upgrade")
+            || !header_contains_token(&parts.headers, &header::UPGRADE, "websocket")
+        {
+            return Err(WebSocketError::NotUpgradeRequest.into());
+        }
+        if parts
+            .headers
+            .get(header::SEC_WEBSOCKET_VERSION)
+            .is_none_or(|version| version != "13")
+        {
+            return Err(WebSocketError::UnsupportedVersion.into());
+        }
+        let key = parts
+            .headers
+            .get(header::SEC_WEBSOCKET_KEY)
+            .cloned()
+            .ok_or(WebSocketError::MissingKey)?;
+        let requested_protocols = parts
+            .headers
+            .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|protocol| !protocol.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+        let on_upgrade = parts
+            .extensions
+            .remove::<OnUpgrade>()
+            .ok_or(WebSocketError::NotUpgradable)?;
+
+        Ok(Self {
+            on_upgrade,
+            key,
+            requested_protocols,
+            protocol: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        })
+    }
+}
+
+/// A message sent or received over a [`WebSocket`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::websocket::Message;
+///
+/// let message = Message::from("Hello");
+/// assert_eq!(message, Message::Text("Hello".to_owned()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// A UTF-8 text message.
+    Text(String),
+    /// A binary message.
+    Binary(Bytes),
+    /// A ping. When a ping is received, a pong with the same payload is sent
+    /// back automatically. The payload can't be longer than 125 bytes.
+    Ping(Bytes),
+    /// A pong, sent in response to a ping. The payload can't be longer than
+    /// 125 bytes.
+    Pong(Bytes),
+    /// A request to close the connection. When it's received, the connection
+    /// is closed once it's echoed back (which happens automatically).
+    Close(Option<CloseFrame>),
+}
+
+impl From<String> for Message {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for Message {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_owned())
+    }
+}
+
+impl From<Bytes> for Message {
+    fn from(data: Bytes) -> Self {
+        Self::Binary(data)
+    }
+}
+
+impl From<Vec<u8>> for Message {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Binary(Bytes::from(data))
+    }
+}
+
+/// The status code and reason of a [`Message::Close`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::websocket::{CloseFrame, Message};
+///
+/// let message = Message::Close(Some(CloseFrame::new(CloseFrame::NORMAL, "bye")));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseFrame {
+    code: u16,
+    reason: String,
+}
+
+impl CloseFrame {
+    /// The connection was closed normally.
+    pub const NORMAL: u16 = 1000;
+    /// The endpoint is going away, e.g. the server is shutting down.
+    pub const GOING_AWAY: u16 = 1001;
+    /// The endpoint received a message violating the protocol.
+    pub const PROTOCOL_ERROR: u16 = 1002;
+    /// The endpoint received data it can't accept, e.g. text that is not
+    /// valid UTF-8.
+    pub const INVALID_DATA: u16 = 1007;
+    /// The endpoint received a message violating its policy.
+    pub const POLICY_VIOLATION: u16 = 1008;
+    /// The endpoint received a message that is too big to process.
+    pub const MESSAGE_TOO_BIG: u16 = 1009;
+    /// The server encountered an unexpected condition.
+    pub const INTERNAL_ERROR: u16 = 1011;
+
+    /// Creates a new close frame with the given status code and reason.
+    ///
+    /// The reason can't be longer than 123 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::CloseFrame;
+    ///
+    /// let frame = CloseFrame::new(CloseFrame::GOING_AWAY, "server restart");
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(code: u16, reason: T) -> Self {
+        Self {
+            code,
+            reason: reason.into(),
+        }
+    }
+
+    /// Returns the status code of the close frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::CloseFrame;
+    ///
+    /// let frame = CloseFrame::new(CloseFrame::NORMAL, "bye");
+    /// assert_eq!(frame.code(), 1000);
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// Returns the reason of the close frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::CloseFrame;
+    ///
+    /// let frame = CloseFrame::new(CloseFrame::NORMAL, "bye");
+    /// assert_eq!(frame.reason(), "bye");
+    /// ```
+    #[must_use]
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+trait WebSocketStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> WebSocketStream for T {}
+
+/// A frame received from the peer.
+#[derive(Debug)]
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Bytes,
+}
+
+/// An upgraded WebSocket connection.
+///
+/// Created by [`WebSocketUpgrade::on_upgrade`]. The connection is closed
+/// when this is dropped, but it's better to close it gracefully by sending
+/// a [`Message::Close`] first.
+///
+/// # Examples
+///
+/// ```
+/// use cot::websocket::{Message, WebSocket};
+///
+/// async fn echo(mut socket: WebSocket) {
+///     while let Some(Ok(message)) = socket.recv().await {
+///         if socket.send(message).await.is_err() {
+///             break;
+///         }
+///     }
+/// }
+/// ```
+pub struct WebSocket {
+    stream: Box<dyn WebSocketStream>,
+    read_buffer: BytesMut,
+    protocol: Option<String>,
+    max_message_size: usize,
+    fragmented: Option<(u8, BytesMut)>,
+    close_sent: bool,
+    close_received: bool,
+}
+
+impl Debug for WebSocket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocket")
+            .field("protocol", &self.protocol)
+            .field("max_message_size", &self.max_message_size)
+            .field("close_sent", &self.close_sent)
+            .field("close_received", &self.close_received)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebSocket {
+    fn new<S: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
+        stream: S,
+        protocol: Option<String>,
+        max_message_size: usize,
+    ) -> Self {
+        Self {
+            stream: Box::new(stream),
+            read_buffer: BytesMut::new(),
+            protocol,
+            max_message_size,
+            fragmented: None,
+            close_sent: false,
+            close_received: false,
+        }
+    }
+
+    /// Returns the subprotocol selected with
+    /// [`WebSocketUpgrade::protocols`], if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::WebSocket;
+    ///
+    /// async fn handler(socket: WebSocket) {
+    ///     println!("Protocol: {:?}", socket.protocol());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+
+    /// Receives the next message from the peer.
+    ///
+    /// Returns `None` once the connection is closed: after a
+    /// [`Message::Close`] has been received, or when the peer closes the
+    /// connection without one. Pings are answered automatically, but they
+    /// are still returned, as are the pongs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the connection fails, or if the
+    /// peer violates the protocol or sends a message that is too large; in
+    /// the latter cases the connection is closed with the appropriate status
+    /// code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::{Message, WebSocket};
+    ///
+    /// async fn handler(mut socket: WebSocket) -> cot::Result<()> {
+    ///     while let Some(message) = socket.recv().await {
+    ///         if let Message::Text(text) = message? {
+    ///             println!("Received: {text}");
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn recv(&mut self) -> Option<cot::Result<Message>> {
+        if self.close_received {
+            return None;
+        }
+
+        match self.recv_message().await {
+            Ok(message) => message.map(Ok),
+            Err(error) => {
+                if let Some(code) = error.close_code() {
+                    if !self.close_sent {
+                        let frame = CloseFrame::new(code, "");
+                        let _ = self.send(Message::Close(Some(frame))).await;
+                    }
+                    self.close_received = true;
+                }
+                Some(Err(error.into()))
+            }
+        }
+    }
+
+    /// Sends a message to the peer.
+    ///
+    /// Sending a [`Message::Close`] starts the closing handshake; no other
+    /// messages can be sent afterwards, and [`Self::recv`] returns `None`
+    /// once the peer confirms the close.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the connection fails, if the
+    /// connection has already been closed, or if the payload of a control
+    /// message is too long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::{Message, WebSocket};
+    ///
+    /// async fn handler(mut socket: WebSocket) -> cot::Result<()> {
+    ///     socket.send(Message::from("Hello!")).await?;
+    ///     socket.send(Message::Close(None)).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn send(&mut self, message: Message) -> cot::Result<()> {
+        if self.close_sent {
+            return Err(WebSocketError::ConnectionClosed.into());
+        }
+
+        let (opcode, payload) = match message {
+            Message::Text(text) => (OPCODE_TEXT, Bytes::from(text)),
+            Message::Binary(data) => (OPCODE_BINARY, data),
+            Message::Ping(data) => (OPCODE_PING, data),
+            Message::Pong(data) => (OPCODE_PONG, data),
+            Message::Close(frame) => {
+                let mut payload = BytesMut::new();
+                if let Some(frame) = frame {
+                    payload.put_u16(frame.code);
+                    payload.put_slice(frame.reason.as_bytes());
+                }
+                self.close_sent = true;
+                (OPCODE_CLOSE, payload.freeze())
+            }
+        };
+        if is_control(opcode) && payload.len() > MAX_CONTROL_PAYLOAD_SIZE {
+            return Err(WebSocketError::Protocol("control frame payload is too long").into());
+        }
+
+        self.write_frame(opcode, &payload).await?;
+        Ok(())
+    }
+
+    async fn recv_message(&mut self) -> Result<Option<Message>> {
+        loop {
+            let Some(frame) = self.read_frame().await? else {
+                if self.fragmented.is_some() {
+                    return Err(WebSocketError::Protocol("connection closed mid-message"));
+                }
+                self.close_received = true;
+                return Ok(None);
+            };
+
+            match frame.opcode {
+                OPCODE_TEXT | OPCODE_BINARY => {
+                    if self.fragmented.is_some() {
+                        return Err(WebSocketError::Protocol(
+                            "new message started before the previous one ended",
+                        ));
+                    }
+                    if frame.fin {
+                        return message_from_data(frame.opcode, frame.payload).map(Some);
+                    }
+                    self.fragmented = Some((frame.opcode, BytesMut::from(&frame.payload[..])));
+                }
+                OPCODE_CONTINUATION => {
+                    let Some((_, data)) = &mut self.fragmented else {
+                        return Err(WebSocketError::Protocol("unexpected continuation frame"));
+                    };
+                    if data.len() + frame.payload.len() > self.max_message_size {
+                        return Err(WebSocketError::MessageTooLarge {
+                            limit: self.max_message_size,
+                        });
+                    }
+                    data.put_slice(&frame.payload);
+                    if frame.fin {
+                        let (opcode, data) = self.fragmented.take().expect("checked above");
+                        return message_from_data(opcode, data.freeze()).map(Some);
+                    }
+                }
+                OPCODE_PING => {
+                    if !self.close_sent {
+                        self.write_frame(OPCODE_PONG, &frame.payload).await?;
+                    }
+                    return Ok(Some(Message::Ping(frame.payload)));
+                }
+                OPCODE_PONG => return Ok(Some(Message::Pong(frame.payload))),
+                OPCODE_CLOSE => {
+                    let close_frame = parse_close_payload(frame.payload)?;
+                    self.close_received = true;
+                    if !self.close_sent {
+                        self.close_sent = true;
+                        let mut payload = BytesMut::new();
+                        if let Some(close_frame) = &close_frame {
+                            payload.put_u16(close_frame.code);
+                        }
+                        self.write_frame(OPCODE_CLOSE, &payload).await?;
+                    }
+                    return Ok(Some(Message::Close(close_frame)));
+                }
+                _ => unreachable!("invalid opcodes are rejected when reading the frame"),
+            }
+        }
+    }
+
+    /// Reads a single frame, returning `None` if the connection is closed
+    /// before a new frame starts.
+    async fn read_frame(&mut self) -> Result<Option<Frame>> {
+        loop {
+            if let Some(frame) = self.parse_frame()? {
+                return Ok(Some(frame));
+            }
+
+            let read = self
+                .stream
+                .read_buf(&mut self.read_buffer)
+                .await
+                .map_err(WebSocketError::Io)?;
+            if read == 0 {
+                return if self.read_buffer.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(WebSocketError::Protocol("connection closed mid-frame"))
+                };
+            }
+        }
+    }
+
+    /// Parses a frame from the read buffer, returning `None` if the buffer
+    /// doesn't contain a whole frame yet.
+    fn parse_frame(&mut self) -> Result<Option<Frame>> {
+        let buffer = &self.read_buffer[..];
+        if buffer.len() < 2 {
+            return Ok(None);
+        }
+
+        let fin = buffer[0] & 0x80 != 0;
+        if buffer[0] & 0x70 != 0 {
+            return Err(WebSocketError::Protocol("reserved bits are set"));
+        }
+        let opcode = buffer[0] & 0x0F;
+        if !matches!(
+            opcode,
+            OPCODE_CONTINUATION
+                | OPCODE_TEXT
+                | OPCODE_BINARY
+                | OPCODE_CLOSE
+                | OPCODE_PING
+                | OPCODE_PONG
+        ) {
+            return Err(WebSocketError::Protocol("unknown opcode"));
+        }
+        if buffer[1] & 0x80 == 0 {
+            return Err(WebSocketError::Protocol("client frames must be masked"));
+        }
+
+        let (length, length_size) = match buffer[1] & 0x7F {
+            126 => {
+                let Some(length) = buffer.get(2..4) else {
+                    return Ok(None);
+                };
+                (u64::from(u16::from_be_bytes([length[0], length[1]])), 2)
+            }
+            127 => {
+                let Some(length) = buffer.get(2..10) else {
+                    return Ok(None);
+                };
+                let length = u64::from_be_bytes(length.try_into().expect("the slice has 8 bytes"));
+                if length & (1 << 63) != 0 {
+                    return Err(WebSocketError::Protocol("invalid frame length"));
+                }
+                (length, 8)
+            }
+            length => (u64::from(length), 0),
+        };
+        if is_control(opcode) && (!fin || length > MAX_CONTROL_PAYLOAD_SIZE as u64) {
+            return Err(WebSocketError::Protocol(
+                "control frames must not be fragmented or longer than 125 bytes",
+            ));
+        }
+        let length = usize::try_from(length)
+            .ok()
+            .filter(|&length| length <= self.max_message_size)
+            .ok_or(WebSocketError::MessageTooLarge {
+                limit: self.max_message_size,
+            })?;
+
+        let header_size = 2 + length_size + 4;
+        if buffer.len() < header_size + length {
+            self.read_buffer
+                .reserve(header_size + length - buffer.len());
+            return Ok(None);
+        }
+
+        let mask: [u8; 4] = buffer[header_size - 4..header_size]
+            .try_into()
+            .expect("the slice has 4 bytes");
+        self.read_buffer.advance(header_size);
+        let mut payload = self.read_buffer.split_to(length);
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+
+        Ok(Some(Frame {
+            fin,
+            opcode,
+            payload: payload.freeze(),
+        }))
+    }
+
+    async fn write_frame(&mut self, opcode: u8, payload: &[u8]) -> Result<()> {
+        let mut header = BytesMut::with_capacity(10);
+        header.put_u8(0x80 | opcode);
+        if payload.len() < 126 {
+            header.put_u8(u8::try_from(payload.len()).expect("checked above"));
+        } else if let Ok(length) = u16::try_from(payload.len()) {
+            header.put_u8(126);
+            header.put_u16(length);
+        } else {
+            header.put_u8(127);
+            header.put_u64(payload.len() as u64);
+        }
+
+        async {
+            self.stream.write_all(&header).await?;
+            self.stream.write_all(payload).await?;
+            self.stream.flush().await
+        }
+        .await
+        .map_err(WebSocketError::Io)
+    }
+}
+
+fn is_control(opcode: u8) -> bool {
+    opcode & 0x08 != 0
+}
+
+fn message_from_data(opcode: u8, data: Bytes) -> Result<Message> {
+    if opcode == OPCODE_TEXT {
+        String::from_utf8(data.into())
+            .map(Message::Text)
+            .map_err(|_| WebSocketError::InvalidUtf8)
+    } else {
+        Ok(Message::Binary(data))
+    }
+}
+
+fn parse_close_payload(mut payload: Bytes) -> Result<Option<CloseFrame>> {
+    match payload.len() {
+        0 => Ok(None),
+        1 => Err(WebSocketError::Protocol("invalid close frame")),
+        _ => {
+            let code = payload.get_u16();
+            let reason =
+                String::from_utf8(payload.into()).map_err(|_| WebSocketError::InvalidUtf8)?;
+            Ok(Some(CloseFrame { code, reason }))
+        }
+    }
+}
+
+/// Computes the `Sec-WebSocket-Accept` header value for the given
+/// `Sec-WebSocket-Key`.
+fn accept_key(key: &[u8]) -> HeaderValue {
+    let mut sha1 = Sha1::new();
+    sha1.update(key);
+    sha1.update(WEBSOCKET_GUID.as_bytes());
+    let accept = base64::engine::general_purpose::STANDARD.encode(sha1.finalize());
+
+    HeaderValue::try_from(accept).expect("base64 is a valid header value")
+}
+
+/// Checks whether any of the comma-separated values of the header is equal
+/// to `token`, ignoring the case.
+fn header_contains_token(headers: &HeaderMap, name: &HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim().eq_ignore_ascii_case(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::DuplexStream;
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    /// Serializes a masked client frame.
+    fn client_frame(fin: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![if fin { 0x80 } else { 0 } | opcode];
+        if payload.len() < 126 {
+            frame.push(0x80 | u8::try_from(payload.len()).unwrap());
+        } else {
+            frame.push(0x80 | 0x7e);
+            frame.extend_from_slice(&u16::try_from(payload.len()).unwrap().to_be_bytes());
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(
+            payload
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| byte ^ mask[i % 4]),
+        );
+        frame
+    }
+
+    fn socket(max_message_size: usize) -> (WebSocket, DuplexStream) {
+        let (server, client) = tokio::io::duplex(1024 * 1024);
+        (WebSocket::new(server, None, max_message_size), client)
+    }
+
+    async fn read_server_frame(client: &mut DuplexStream) -> (u8, Vec<u8>) {
+        let mut header = [0; 2];
+        client.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[1] & 0x80, 0, "server frames must not be masked");
+        let length = match header[1] {
+            126 => usize::from(client.read_u16().await.unwrap()),
+            127 => usize::try_from(client.read_u64().await.unwrap()).unwrap(),
+            length => usize::from(length),
+        };
+        let mut payload = vec![0; length];
+        client.read_exact(&mut payload).await.unwrap();
+        (header[0], payload)
+    }
+
+    fn upgrade_request() -> crate::request::Request {
+        let mut request = TestRequestBuilder::get("/ws").build();
+        let headers = request.headers_mut();
+        headers.insert(
+            header::CONNECTION,
+            HeaderValue::from_static("keep-alive, Upgrade"),
+        );
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(
+            header::SEC_WEBSOCKET_VERSION,
+            HeaderValue::from_static("13"),
+        );
+        headers.insert(
+            header::SEC_WEBSOCKET_KEY,
+            HeaderValue::from_static("dGhlIHNhbXBsZSBub25jZQ=="),
+        );
+        request
+    }
+
+    #[test]
+    fn accept_key_rfc_example() {
+        assert_eq!(
+            accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn header_tokens() {
+        let mut headers = HeaderMap::new();
+        headers.append(header::CONNECTION, HeaderValue::from_static("keep-alive"));
+        headers.append(header::CONNECTION, HeaderValue::from_static("foo, Upgrade"));
+
+        assert!(header_contains_token(
+            &headers,
+            &header::CONNECTION,
+            "upgrade"
+        ));
+        assert!(!header_contains_token(
+            &headers,
+            &header::CONNECTION,
+            "close"
+        ));
+    }
+
+    #[cot::test]
+    async fn upgrade_invalid_requests() {
+        async fn extract(request: crate::request::Request) -> WebSocketError {
+            let (mut parts, _) = request.into_parts();
+            let error = WebSocketUpgrade::from_request_parts(&mut parts)
+                .await
+                .unwrap_err();
+            match error.inner {
+                crate::error::ErrorRepr::WebSocket(error) => error,
+                other => panic!("unexpected error: {other:?}"),
+            }
+        }
+
+        let mut request = upgrade_request();
+        *request.method_mut() = Method::POST;
+        assert!(matches!(
+            extract(request).await,
+            WebSocketError::MethodNotGet
+        ));
+
+        let mut request = upgrade_request();
+        request.headers_mut().remove(header::UPGRADE);
+        assert!(matches!(
+            extract(request).await,
+            WebSocketError::NotUpgradeRequest
+        ));
+
+        let mut request = upgrade_request();
+        request
+            .headers_mut()
+            .insert(header::SEC_WEBSOCKET_VERSION, HeaderValue::from_static("8"));
+        assert!(matches!(
+            extract(request).await,
+            WebSocketError::UnsupportedVersion
+        ));
+
+        let mut request = upgrade_request();
+        request.headers_mut().remove(header::SEC_WEBSOCKET_KEY);
+        assert!(matches!(extract(request).await, WebSocketError::MissingKey));
+
+        // the test requests are not received through a real connection
+        assert!(matches!(
+            extract(upgrade_request()).await,
+            WebSocketError::NotUpgradable
+        ));
+    }
+
+    #[cot::test]
+    async fn websocket_route_rejects_non_upgrade_requests() {
+        let router =
+            crate::router::Router::with_urls([crate::router::Route::with_websocket_handler(
+                "/ws",
+                |_socket: WebSocket| async {},
+            )]);
+        let request = TestRequestBuilder::get("/ws").build();
+
+        let response = router.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cot::test]
+    async fn recv_text_and_binary() {
+        let (mut socket, mut client) = socket(1024);
+        client
+            .write_all(&client_frame(true, OPCODE_TEXT, b"Hello"))
+            .await
+            .unwrap();
+        client
+            .write_all(&client_frame(true, OPCODE_BINARY, &[1, 2, 3]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            socket.recv().await.unwrap().unwrap(),
+            Message::Text("Hello".to_owned())
+        );
+        assert_eq!(
+            socket.recv().await.unwrap().unwrap(),
+            Message::Binary(Bytes::from_static(&[1, 2, 3]))
+        );
+    }
+
+    #[cot::test]
+    async fn recv_fragmented_with_interleaved_ping() {
+        let (mut socket, mut client) = socket(1024);
+        let mut data = client_frame(false, OPCODE_TEXT, b"Hel");
+        data.extend(client_frame(true, OPCODE_PING, b"p"));
+        data.extend(client_frame(false, OPCODE_CONTINUATION, b"lo, "));
+        data.extend(client_frame(true, OPCODE_CONTINUATION, b"world!"));
+        client.write_all(&data).await.unwrap();
+
+        assert_eq!(
+            socket.recv().await.unwrap().unwrap(),
+            Message::Ping(Bytes::from_static(b"p"))
+        );
+        assert_eq!(
+            read_server_frame(&mut client).await,
+            (0x80 | OPCODE_PONG, b"p".to_vec())
+        );
+        assert_eq!(
+            socket.recv().await.unwrap().unwrap(),
+            Message::Text("Hello, world!".to_owned())
+        );
+    }
+
+    #[cot::test]
+    async fn recv_byte_by_byte() {
+        let (mut socket, mut client) = socket(1024);
+        let payload = "x".repeat(300);
+        let frame = client_frame(true, OPCODE_TEXT, payload.as_bytes());
+
+        let writer = tokio::spawn(async move {
+            for byte in frame {
+                client.write_all(&[byte]).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+            client
+        });
+
+        assert_eq!(
+            socket.recv().await.unwrap().unwrap(),
+            Message::Text(payload)
+        );
+        writer.await.unwrap();
+    }
+
+    #[cot::test]
+    async fn send_messages() {
+        let (mut socket, mut client) = socket(1024);
+
+        socket.send(Message::from("Hi")).await.unwrap();
+        socket
+            .send(Message::Binary(Bytes::from(vec![7; 200])))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            read_server_frame(&mut client).await,
+            (0x80 | OPCODE_TEXT, b"Hi".to_vec())
+        );
+        assert_eq!(
+            read_server_frame(&mut client).await,
+            (0x80 | OPCODE_BINARY, vec![7; 200])
+        );
+    }
+
+    #[cot::test]
+    async fn send_control_too_long() {
+        let (mut socket, _client) = socket(1024);
+
+        let error = socket
+            .send(Message::Ping(Bytes::from(vec![0; 126])))
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("control frame payload"));
+    }
+
+    #[cot::test]
+    async fn close_initiated_by_client() {
+        let (mut socket, mut client) = socket(1024);
+        let mut payload = 1000_u16.to_be_bytes().to_vec();
+        payload.extend_from_slice(b"bye");
+        client
+            .write_all(&client_frame(true, OPCODE_CLOSE, &payload))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            socket.recv().await.unwrap().unwrap(),
+            Message::Close(Some(CloseFrame::new(CloseFrame::NORMAL, "bye")))
+        );
+        assert_eq!(
+            read_server_frame(&mut client).await,
+            (0x80 | OPCODE_CLOSE, 1000_u16.to_be_bytes().to_vec())
+        );
+        assert!(socket.recv().await.is_none());
+        assert!(socket.send(Message::from("late")).await.is_err());
+    }
+
+    #[cot::test]
+    async fn close_initiated_by_server() {
+        let (mut socket, mut client) = socket(1024);
+
+        socket
+            .send(Message::Close(Some(CloseFrame::new(
+                CloseFrame::GOING_AWAY,
+                "",
+            ))))
+            .await
+            .unwrap();
+        assert_eq!(
+            read_server_frame(&mut client).await,
+            (0x80 | OPCODE_CLOSE, 1001_u16.to_be_bytes().to_vec())
+        );
+
+        client
+            .write_all(&client_frame(true, OPCODE_CLOSE, &[]))
+            .await
+            .unwrap();
+        assert_eq!(socket.recv().await.unwrap().unwrap(), Message::Close(None));
+        assert!(socket.recv().await.is_none());
+    }
+
+    #[cot::test]
+    async fn recv_eof() {
+        let (mut socket, client) = socket(1024);
+        drop(client);
+
+        assert!(socket.recv().await.is_none());
+    }
+
+    #[cot::test]
+    async fn recv_unmasked_frame() {
+        let (mut socket, mut client) = socket(1024);
+        client.write_all(&[0x81, 0x01, b'a']).await.unwrap();
+
+        let error = socket.recv().await.unwrap().unwrap_err();
+
+        assert!(error.to_string().contains("must be masked"));
+        assert_eq!(
+            read_server_frame(&mut client).await,
+            (0x80 | OPCODE_CLOSE, 1002_u16.to_be_bytes().to_vec())
+        );
+        assert!(socket.recv().await.is_none());
+    }
+
+    #[cot::test]
+    async fn recv_invalid_utf8() {
+        let (mut socket, mut client) = socket(1024);
+        client
+            .write_all(&client_frame(true, OPCODE_TEXT, &[0xFF, 0xFE]))
+            .await
+            .unwrap();
+
+        let error = socket.recv().await.unwrap().unwrap_err();
+
+        assert!(error.to_string().contains("not valid UTF-8"));
+        assert_eq!(
+            read_server_frame(&mut client).await,
+            (0x80 | OPCODE_CLOSE, 1007_u16.to_be_bytes().to_vec())
+        );
+    }
+
+    #[cot::test]
+    async fn recv_message_too_large() {
+        let (mut socket, mut client) = socket(8);
+        let mut data = client_frame(false, OPCODE_BINARY, b"12345");
+        data.extend(client_frame(true, OPCODE_CONTINUATION, b"6789"));
+        client.write_all(&data).await.unwrap();
+
+        let error = socket.recv().await.unwrap().unwrap_err();
+
+        assert!(error.to_string().contains("exceeds the limit of 8 bytes"));
+        assert_eq!(
+            read_server_frame(&mut client).await,
+            (0x80 | OPCODE_CLOSE, 1009_u16.to_be_bytes().to_vec())
+        );
+    }
+
+    #[cot::test]
+    async fn recv_unexpected_continuation() {
+        let (mut socket, mut client) = socket(1024);
+        client
+            .write_all(&client_frame(true, OPCODE_CONTINUATION, b"a"))
+            .await
+            .unwrap();
+
+        let error = socket.recv().await.unwrap().unwrap_err();
+
+        assert!(error.to_string().contains("unexpected continuation frame"));
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn upgrade_over_tcp() {
+        use tokio::net::{TcpListener, TcpStream};
+
+        async fn echo(mut socket: WebSocket) {
+            while let Some(Ok(message)) = socket.recv().await {
+                if socket.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let service = tower::service_fn(|request: axum::extract::Request| async move {
+            let (mut parts, _) = request.into_parts();
+            let response = match WebSocketUpgrade::from_request_parts(&mut parts).await {
+                Ok(upgrade) => upgrade.protocols(["chat"]).on_upgrade(echo),
+                Err(error) => panic!("upgrade failed: {error}"),
+            };
+            Ok::<_, std::convert::Infallible>(response.map(axum::body::Body::new))
+        });
+        let config = crate::config::ServerConfig::default();
+        tokio::spawn(async move {
+            crate::server::serve(listener, service, &config, std::future::pending()).await;
+        });
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                b"GET /ws HTTP/1.1\r\n\
+                  Host: localhost\r\n\
+                  Connection: Upgrade\r\n\
+                  Upgrade: websocket\r\n\
+                  Sec-WebSocket-Version: 13\r\n\
+                  Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  Sec-WebSocket-Protocol: other, chat\r\n\
+                  \r\n",
+            )
+            .await
+            .unwrap();
+
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            response.push(stream.read_u8().await.unwrap());
+        }
+        let response = String::from_utf8(response).unwrap().to_lowercase();
+        assert!(response.starts_with("http/1.1 101 switching protocols\r\n"));
+        assert!(response.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo=\r\n"));
+        assert!(response.contains("sec-websocket-protocol: chat\r\n"));
+
+        stream
+            .write_all(&client_frame(true, OPCODE_TEXT, b"ping?"))
+            .await
+            .unwrap();
+        let mut reply = [0; 7];
+        stream.read_exact(&mut reply).await.unwrap();
+        assert_eq!(reply, [0x81, 5, b'p', b'i', b'n', b'g', b'?']);
+    }
+}
@@ -1,7 +1,8 @@
 use std::borrow::Cow;
 
-use cot::auth::db::{DatabaseUser, DatabaseUserCredentials};
+use cot::auth::db::{DatabaseUser, DatabaseUserCredentials, DatabaseUserSeeder};
 use cot::auth::{Auth, Password};
+use cot::db::seed::{SeedEngine, SeedEnvironment, Seeder};
 use cot::request::RequestExt;
 use cot::test::{TestDatabase, TestRequestBuilder};
 
@@ -65,3 +66,47 @@ async fn database_user(test_db: &mut TestDatabase) {
     auth.logout().await.unwrap();
     assert!(!auth.user().is_authenticated());
 }
+
+#[cot_macros::dbtest]
+async fn database_user_seeder(test_db: &mut TestDatabase) {
+    test_db.with_auth().run_migrations().await;
+    let seeders: [Box<dyn Seeder>; 2] = [
+        Box::new(DatabaseUserSeeder::new("admin", "admin")),
+        Box::new(
+            DatabaseUserSeeder::new("operator", "operator")
+                .with_environments(&[SeedEnvironment::Production]),
+        ),
+    ];
+    let engine = SeedEngine::new(seeders);
+
+    let seeded = engine
+        .run(test_db, SeedEnvironment::Development, None)
+        .await
+        .unwrap();
+    assert_eq!(seeded, ["database_user"]);
+    // running the seeders again doesn't create the user twice
+    engine
+        .run(test_db, SeedEnvironment::Development, None)
+        .await
+        .unwrap();
+
+    let admin = DatabaseUser::get_by_username(&**test_db, "admin")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        DatabaseUser::authenticate(
+            &**test_db,
+            &DatabaseUserCredentials::new("admin".to_string(), Password::new("admin"))
+        )
+        .await
+        .unwrap()
+        .is_some_and(|user| user.id() == admin.id())
+    );
+    assert!(
+        DatabaseUser::get_by_username(&**test_db, "operator")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
@@ -1,13 +1,18 @@
 #![cfg(feature = "fake")]
 #![cfg_attr(miri, ignore)]
 
-use cot::db::migrations::{Field, Operation};
-use cot::db::query::ExprEq;
+use cot::clock::Clock;
+use cot::db::fixtures::{Fixture, FixtureError, FixtureFormat, FixtureRecord, FixtureSchema};
+use cot::db::migrations::{Field, MigrationEngine, Operation};
+use cot::db::query::{Aggregate, ExprEq, Query};
 use cot::db::{
-    Auto, Database, DatabaseError, DatabaseField, ForeignKey, ForeignKeyOnDeletePolicy,
-    ForeignKeyOnUpdatePolicy, Identifier, LimitedString, Model, model, query,
+    Auto, ColumnType, CustomDbField, DEFAULT_DATABASE, Database, DatabaseBackend, DatabaseError,
+    DatabaseField, DatabaseRouter, DbEnum, DbValue, ForeignKey, ForeignKeyOnDeletePolicy,
+    ForeignKeyOnUpdatePolicy, FromRow, Identifier, Json, LimitedString, ManyToMany, Model,
+    ModelHooks, ModelInfo, Paginator, Row, RowsNum, ToDbValue, model, query,
 };
-use cot::test::TestDatabase;
+use cot::form::{DynFormField, Form, FormContext, ModelForm};
+use cot::test::{TestClock, TestDatabase, TestMigration, TestRequestBuilder};
 use fake::rand::SeedableRng;
 use fake::rand::rngs::StdRng;
 use fake::{Dummy, Fake, Faker};
@@ -133,98 +138,1159 @@ async fn model_macro_filtering(test_db: &mut TestDatabase) {
     assert!(objects.is_empty());
 }
 
-async fn migrate_test_model(db: &Database) {
-    CREATE_TEST_MODEL.forwards(db).await.unwrap();
+#[cot_macros::dbtest]
+#[expect(clippy::too_many_lines)] // it's mostly a list of simple assertions
+async fn model_macro_filtering_expressions(test_db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Person {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+        age: i32,
+        nickname: Option<String>,
+    }
+
+    const CREATE_PERSON: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__person"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("age"), <i32 as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("nickname"),
+                <Option<String> as DatabaseField>::TYPE,
+            )
+            .set_null(<Option<String> as DatabaseField>::NULLABLE),
+        ])
+        .build();
+
+    async fn names(db: &Database, query: &Query<Person>) -> Vec<String> {
+        let mut names: Vec<_> = query
+            .all(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|person| person.name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    CREATE_PERSON.forwards(test_db).await.unwrap();
+    for (name, age, nickname) in [
+        ("John", 25, Some("Johnny")),
+        ("Jane", 31, None),
+        ("Joan", 42, Some("50%_off")),
+        ("Bob", 19, None),
+    ] {
+        let mut person = Person {
+            id: Auto::auto(),
+            name: name.to_owned(),
+            age,
+            nickname: nickname.map(ToOwned::to_owned),
+        };
+        person.save(&**test_db).await.unwrap();
+    }
+
+    assert_eq!(
+        names(test_db, query!(Person, $age >= 25 && $age < 42)).await,
+        ["Jane", "John"]
+    );
+    assert_eq!(
+        names(test_db, query!(Person, $age.between(25, 42))).await,
+        ["Jane", "Joan", "John"]
+    );
+    assert_eq!(
+        names(test_db, query!(Person, $age.is_in([19, 42, 100]))).await,
+        ["Bob", "Joan"]
+    );
+    assert!(
+        names(test_db, query!(Person, $age.is_in(Vec::<i32>::new())))
+            .await
+            .is_empty()
+    );
+    assert_eq!(
+        names(test_db, query!(Person, $nickname.is_null())).await,
+        ["Bob", "Jane"]
+    );
+    assert_eq!(
+        names(test_db, query!(Person, !$nickname.is_null())).await,
+        ["Joan", "John"]
+    );
+    assert_eq!(
+        names(test_db, query!(Person, $name.starts_with("Jo"))).await,
+        ["Joan", "John"]
+    );
+    assert_eq!(
+        names(test_db, query!(Person, $name.contains("a"))).await,
+        ["Jane", "Joan"]
+    );
+    assert_eq!(
+        names(test_db, query!(Person, $name.icontains("JO"))).await,
+        ["Joan", "John"]
+    );
+    // special `LIKE` characters are matched literally
+    assert_eq!(
+        names(test_db, query!(Person, $nickname.contains("%_"))).await,
+        ["Joan"]
+    );
+    assert!(
+        names(test_db, query!(Person, $nickname.contains("5_%")))
+            .await
+            .is_empty()
+    );
+    assert_eq!(
+        names(
+            test_db,
+            query!(Person, !($name.starts_with("J") || $age > 40) || $age == 31)
+        )
+        .await,
+        ["Bob", "Jane"]
+    );
 }
 
-const CREATE_TEST_MODEL: Operation = Operation::create_model()
-    .table_name(Identifier::new("cot__test_model"))
-    .fields(&[
-        Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
-            .primary_key()
-            .auto(),
-        Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
-    ])
-    .build();
+#[cot_macros::dbtest]
+#[expect(clippy::too_many_lines)] // it's mostly the model definition and assertions
+async fn model_aggregates(test_db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Sale {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        region: String,
+        amount: i32,
+        rating: f64,
+    }
 
-macro_rules! all_fields_migration_field {
-    ($name:ident, $ty:ty) => {
-        Field::new(
-            Identifier::new(concat!("field_", stringify!($name))),
-            <$ty as DatabaseField>::TYPE,
+    #[derive(Debug, PartialEq)]
+    struct RegionStats {
+        region: String,
+        count: i64,
+        total: Option<i64>,
+    }
+
+    impl FromRow for RegionStats {
+        fn from_row(row: &Row) -> cot::db::Result<Self> {
+            Ok(Self {
+                region: row.get(0)?,
+                count: row.get(1)?,
+                total: row.get(2)?,
+            })
+        }
+    }
+
+    const CREATE_SALE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__sale"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("region"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("amount"), <i32 as DatabaseField>::TYPE),
+            Field::new(Identifier::new("rating"), <f64 as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    CREATE_SALE.forwards(test_db).await.unwrap();
+
+    let empty = Query::<Sale>::new();
+    assert_eq!(
+        empty
+            .sum::<_, Option<i64>>(test_db, <Sale as Model>::Fields::amount)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        empty
+            .avg(test_db, <Sale as Model>::Fields::amount)
+            .await
+            .unwrap(),
+        None
+    );
+
+    for (region, amount, rating) in [
+        ("north", 10, 1.5),
+        ("north", 20, 2.0),
+        ("south", 5, 4.0),
+        ("east", 7, 3.0),
+    ] {
+        let mut sale = Sale {
+            id: Auto::auto(),
+            region: region.to_owned(),
+            amount,
+            rating,
+        };
+        sale.save(&**test_db).await.unwrap();
+    }
+
+    let all = Query::<Sale>::new();
+    assert_eq!(all.count(test_db).await.unwrap(), 4);
+    assert_eq!(
+        all.sum::<_, Option<i64>>(test_db, <Sale as Model>::Fields::amount)
+            .await
+            .unwrap(),
+        Some(42)
+    );
+    assert_eq!(
+        all.sum::<_, Option<f64>>(test_db, <Sale as Model>::Fields::rating)
+            .await
+            .unwrap(),
+        Some(10.5)
+    );
+    assert_eq!(
+        all.avg(test_db, <Sale as Model>::Fields::amount)
+            .await
+            .unwrap(),
+        Some(10.5)
+    );
+    assert_eq!(
+        all.min::<_, Option<i32>>(test_db, <Sale as Model>::Fields::amount)
+            .await
+            .unwrap(),
+        Some(5)
+    );
+    assert_eq!(
+        query!(Sale, $region == "north")
+            .max::<_, Option<i32>>(test_db, <Sale as Model>::Fields::amount)
+            .await
+            .unwrap(),
+        Some(20)
+    );
+
+    let (count, max_rating): (i64, Option<f64>) = query!(Sale, $amount > 5)
+        .aggregate(
+            test_db,
+            &[
+                Aggregate::count(),
+                Aggregate::max(<Sale as Model>::Fields::rating),
+            ],
         )
-        .set_null(<$ty as DatabaseField>::NULLABLE)
+        .await
+        .unwrap()
+        .remove(0);
+    assert_eq!((count, max_rating), (3, Some(3.0)));
+
+    let mut stats: Vec<RegionStats> = query!(Sale, $region != "east")
+        .group_by(<Sale as Model>::Fields::region)
+        .aggregate(
+            test_db,
+            &[
+                Aggregate::count(),
+                Aggregate::sum(<Sale as Model>::Fields::amount),
+            ],
+        )
+        .await
+        .unwrap();
+    stats.sort_by(|a, b| a.region.cmp(&b.region));
+    assert_eq!(
+        stats,
+        [
+            RegionStats {
+                region: "north".to_owned(),
+                count: 2,
+                total: Some(30),
+            },
+            RegionStats {
+                region: "south".to_owned(),
+                count: 1,
+                total: Some(5),
+            },
+        ]
+    );
+}
+
+#[cot_macros::dbtest]
+async fn model_ordering_and_pagination(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    for name in ["c", "a", "e", "b", "d"] {
+        let mut model = TestModel {
+            id: Auto::auto(),
+            name: name.to_owned(),
+        };
+        model.save(&**test_db).await.unwrap();
+    }
+
+    let names = |models: &[TestModel]| {
+        models
+            .iter()
+            .map(|model| model.name.clone())
+            .collect::<Vec<_>>()
     };
-    ($ty:ty) => {
-        Field::new(
-            Identifier::new(concat!("field_", stringify!($ty))),
-            <$ty as DatabaseField>::TYPE,
+
+    let mut query = Query::<TestModel>::new();
+    query.order_by(<TestModel as Model>::Fields::name.desc());
+    assert_eq!(
+        names(&query.all(&**test_db).await.unwrap()),
+        ["e", "d", "c", "b", "a"]
+    );
+    assert_eq!(
+        query.get(&**test_db).await.unwrap().unwrap().name,
+        "e".to_owned()
+    );
+
+    let mut query = Query::<TestModel>::new();
+    query
+        .order_by(<TestModel as Model>::Fields::name.asc())
+        .limit(2)
+        .offset(1);
+    assert_eq!(names(&query.all(&**test_db).await.unwrap()), ["b", "c"]);
+
+    let mut query = Query::<TestModel>::new();
+    query.order_by(<TestModel as Model>::Fields::name.asc());
+    let paginator = Paginator::new(query, 2);
+
+    let page = paginator.page(test_db, 1).await.unwrap();
+    assert_eq!(names(page.items()), ["a", "b"]);
+    assert_eq!(page.total_count(), 5);
+    assert_eq!(page.num_pages(), 3);
+    assert_eq!(page.next_page_number(), Some(2));
+
+    let page = paginator.page(test_db, 3).await.unwrap();
+    assert_eq!(names(page.items()), ["e"]);
+    assert!(!page.has_next());
+    assert_eq!(page.previous_page_number(), Some(2));
+
+    let page = paginator.page(test_db, 4).await.unwrap();
+    assert!(page.items().is_empty());
+    assert!(page.is_out_of_range());
+}
+
+#[cot_macros::dbtest]
+async fn full_text_search(test_db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model(search(fields = ["title", "body"]))]
+    struct Article {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        title: String,
+        body: String,
+    }
+
+    const CREATE_ARTICLE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__article"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("body"), <String as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    CREATE_ARTICLE.forwards(test_db).await.unwrap();
+    for (title, body) in [
+        ("Systems programming", "Memory safety in Rust."),
+        ("Cooking pasta", "Boil the water and add salt."),
+        (
+            "Rust web frameworks",
+            "Cot is a web framework written in Rust.",
+        ),
+    ] {
+        let mut article = Article {
+            id: Auto::auto(),
+            title: title.to_owned(),
+            body: body.to_owned(),
+        };
+        article.save(&**test_db).await.unwrap();
+    }
+
+    let titles = |articles: Vec<Article>| -> Vec<String> {
+        articles.into_iter().map(|article| article.title).collect()
+    };
+
+    // the articles mentioning the term in more fields are ranked higher
+    let found = Article::objects()
+        .search("rust")
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(
+        titles(found),
+        ["Rust web frameworks", "Systems programming"]
+    );
+    assert_eq!(
+        Article::objects()
+            .search("rust")
+            .count(test_db)
+            .await
+            .unwrap(),
+        2
+    );
+
+    // all the words must match
+    let found = Article::objects()
+        .search("rust web")
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(titles(found), ["Rust web frameworks"]);
+
+    // the search is combined with the filter
+    let found = query!(Article, $title == "Systems programming")
+        .search("rust")
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(titles(found), ["Systems programming"]);
+
+    let found = Article::objects()
+        .search("javascript")
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert!(found.is_empty());
+    assert_eq!(
+        Article::objects()
+            .search("  ")
+            .count(test_db)
+            .await
+            .unwrap(),
+        3
+    );
+}
+
+#[cot_macros::dbtest]
+async fn transactions(test_db: &mut TestDatabase) {
+    async fn names(db: &Database) -> Vec<String> {
+        let mut query = Query::<TestModel>::new();
+        query.order_by(<TestModel as Model>::Fields::name.asc());
+        query
+            .all(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|model| model.name)
+            .collect()
+    }
+
+    migrate_test_model(&*test_db).await;
+
+    let model = |name: &str| TestModel {
+        id: Auto::auto(),
+        name: name.to_owned(),
+    };
+
+    // commit
+    let mut committed = model("committed");
+    test_db
+        .transaction(|tx| async move {
+            committed.save(&tx).await?;
+            assert_eq!(TestModel::objects().count(&tx).await?, 1);
+            Ok::<_, DatabaseError>(())
+        })
+        .await
+        .unwrap();
+    assert_eq!(names(test_db).await, ["committed"]);
+
+    // rollback
+    let mut rolled_back = model("rolled back");
+    let result = test_db
+        .transaction(|tx| async move {
+            rolled_back.save(&tx).await?;
+            Err::<(), _>(DatabaseError::ForeignKeyNotFound)
+        })
+        .await;
+    assert!(matches!(result, Err(DatabaseError::ForeignKeyNotFound)));
+    assert_eq!(names(test_db).await, ["committed"]);
+
+    // nested transactions
+    test_db
+        .transaction(|tx| async move {
+            model("outer").save(&tx).await?;
+
+            tx.transaction(|inner| async move {
+                model("inner committed").save(&inner).await?;
+                Ok::<_, DatabaseError>(())
+            })
+            .await?;
+
+            let result = tx
+                .transaction(|inner| async move {
+                    model("inner rolled back").save(&inner).await?;
+                    Err::<(), _>(DatabaseError::ForeignKeyNotFound)
+                })
+                .await;
+            assert!(result.is_err());
+
+            assert_eq!(names(&tx).await, ["committed", "inner committed", "outer"]);
+            Ok::<_, DatabaseError>(())
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        names(test_db).await,
+        ["committed", "inner committed", "outer"]
+    );
+}
+
+#[cot_macros::dbtest]
+async fn raw_queries(test_db: &mut TestDatabase) {
+    #[derive(Debug, PartialEq, FromRow)]
+    struct NamedRow {
+        id: i32,
+        #[from_row(rename = "name")]
+        model_name: String,
+    }
+
+    #[derive(Debug, PartialEq, FromRow)]
+    struct TupleRow(String, i64);
+
+    migrate_test_model(&*test_db).await;
+
+    let result = test_db
+        .raw_execute(
+            "INSERT INTO cot__test_model (id, name) VALUES (1, 'a'), (2, 'b'), (3, 'b')",
+            &[],
         )
-        .set_null(<$ty as DatabaseField>::NULLABLE)
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected().0, 3);
+
+    let rows: Vec<NamedRow> = test_db
+        .raw_query(
+            "SELECT name, id FROM cot__test_model WHERE id < 3 ORDER BY id",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            NamedRow {
+                id: 1,
+                model_name: "a".to_owned(),
+            },
+            NamedRow {
+                id: 2,
+                model_name: "b".to_owned(),
+            },
+        ]
+    );
+
+    let rows: Vec<TupleRow> = test_db
+        .raw_query(
+            "SELECT name, COUNT(*) FROM cot__test_model GROUP BY name ORDER BY name",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![TupleRow("a".to_owned(), 1), TupleRow("b".to_owned(), 2)]
+    );
+
+    let result = test_db
+        .raw_query::<NamedRow>("SELECT id FROM cot__test_model", &[])
+        .await;
+    assert!(result.is_err());
+}
+
+#[cot_macros::dbtest]
+async fn bulk_operations(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let mut models: Vec<_> = (0..100)
+        .map(|i| TestModel {
+            id: Auto::auto(),
+            name: format!("model {i}"),
+        })
+        .collect();
+    TestModel::bulk_create(&**test_db, &mut models)
+        .await
+        .unwrap();
+
+    let ids: std::collections::HashSet<_> = models.iter().map(|model| model.id.unwrap()).collect();
+    assert_eq!(ids.len(), 100);
+    assert_eq!(TestModel::objects().count(test_db).await.unwrap(), 100);
+    let model = TestModel::get_by_primary_key(&**test_db, models[42].id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(model.name, "model 42");
+
+    // upsert: update the existing rows, insert a new one
+    let mut upserted = vec![
+        TestModel {
+            id: models[0].id,
+            name: "updated".to_owned(),
+        },
+        TestModel {
+            id: Auto::fixed(1000),
+            name: "new".to_owned(),
+        },
+    ];
+    TestModel::bulk_save(&**test_db, &mut upserted)
+        .await
+        .unwrap();
+    assert_eq!(TestModel::objects().count(test_db).await.unwrap(), 101);
+    let model = TestModel::get_by_primary_key(&**test_db, models[0].id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(model.name, "updated");
+
+    // update all
+    let fields = <TestModel as Model>::Fields::name;
+    let result = query!(TestModel, $name == "new")
+        .update_all(test_db, [fields.set("renamed")])
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected().0, 1);
+    assert!(
+        query!(TestModel, $name == "renamed")
+            .exists(&**test_db)
+            .await
+            .unwrap()
+    );
+
+    // delete all
+    let result = Query::<TestModel>::new().delete(&**test_db).await.unwrap();
+    assert_eq!(result.rows_affected().0, 101);
+}
+
+#[cot_macros::dbtest]
+async fn unique_constraints(test_db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model(index(fields = ["slug"]), unique(fields = ["tenant", "slug"]))]
+    struct Page {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        tenant: i32,
+        slug: String,
+        #[model(unique)]
+        title: String,
+    }
+
+    const CREATE_PAGE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__page"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("tenant"), <i32 as DatabaseField>::TYPE),
+            Field::new(Identifier::new("slug"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE).unique(),
+        ])
+        .build();
+
+    assert_eq!(Page::INDEXES.len(), 2);
+    assert_eq!(
+        Page::INDEXES[1].name().as_str(),
+        "cot__page_tenant_slug_uniq"
+    );
+    CREATE_PAGE.forwards(test_db).await.unwrap();
+    for index in Page::INDEXES {
+        Operation::add_index()
+            .table_name(Page::TABLE_NAME)
+            .index(*index)
+            .build()
+            .forwards(test_db)
+            .await
+            .unwrap();
+    }
+
+    let page = |tenant: i32, slug: &str, title: &str| Page {
+        id: Auto::auto(),
+        tenant,
+        slug: slug.to_owned(),
+        title: title.to_owned(),
     };
+    page(1, "home", "Home").save(&**test_db).await.unwrap();
+    page(2, "home", "Home 2").save(&**test_db).await.unwrap();
+
+    let error = page(1, "home", "Home 3")
+        .save(&**test_db)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            error,
+            DatabaseError::UniqueViolation { ref table, ref columns }
+                if table == "cot__page" && columns == &["tenant", "slug"]
+        ),
+        "unexpected error: {error:?}"
+    );
+
+    let mut other = page(1, "about", "About");
+    other.save(&**test_db).await.unwrap();
+    other.title = "Home".to_owned();
+    let error = other.save(&**test_db).await.unwrap_err();
+    assert!(
+        matches!(
+            error,
+            DatabaseError::UniqueViolation { ref columns, .. } if columns == &["title"]
+        ),
+        "unexpected error: {error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "A row with the same value of `title` already exists in table `cot__page`"
+    );
 }
 
-#[derive(Debug, PartialEq, Dummy)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Settings {
+    theme: String,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 #[model]
-struct AllFieldsModel {
-    #[dummy(expr = "Auto::auto()")]
+struct Profile {
     #[model(primary_key)]
     id: Auto<i32>,
-    field_bool: bool,
-    field_i8: i8,
-    field_i16: i16,
-    field_i32: i32,
-    field_i64: i64,
-    field_u8: u8,
-    field_u16: u16,
-    field_u32: u32,
-    // SQLite only allows us to store signed integers, so we're generating numbers that do not
-    // exceed i64::MAX
-    #[dummy(faker = "0..i64::MAX as u64")]
-    field_u64: u64,
-    field_f32: f32,
-    field_f64: f64,
-    field_date: chrono::NaiveDate,
-    field_time: chrono::NaiveTime,
-    #[dummy(faker = "fake::chrono::Precision::<6>")]
-    field_datetime: chrono::NaiveDateTime,
-    #[dummy(faker = "fake::chrono::Precision::<6>")]
-    field_datetime_timezone: chrono::DateTime<chrono::FixedOffset>,
-    field_string: String,
-    field_blob: Vec<u8>,
-    field_option: Option<String>,
-    field_limited_string: LimitedString<10>,
-}
-
-async fn migrate_all_fields_model(db: &Database) {
-    CREATE_ALL_FIELDS_MODEL.forwards(db).await.unwrap();
+    settings: Json<Settings>,
+    extra: Option<Json<serde_json::Value>>,
 }
 
-const CREATE_ALL_FIELDS_MODEL: Operation = Operation::create_model()
-    .table_name(Identifier::new("cot__all_fields_model"))
+const CREATE_PROFILE: Operation = Operation::create_model()
+    .table_name(Identifier::new("cot__profile"))
     .fields(&[
         Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
             .primary_key()
             .auto(),
-        all_fields_migration_field!(bool),
-        all_fields_migration_field!(i8),
-        all_fields_migration_field!(i16),
-        all_fields_migration_field!(i32),
-        all_fields_migration_field!(i64),
-        all_fields_migration_field!(u8),
-        all_fields_migration_field!(u16),
-        all_fields_migration_field!(u32),
-        all_fields_migration_field!(u64),
-        all_fields_migration_field!(f32),
-        all_fields_migration_field!(f64),
-        all_fields_migration_field!(date, chrono::NaiveDate),
-        all_fields_migration_field!(time, chrono::NaiveTime),
-        all_fields_migration_field!(datetime, chrono::NaiveDateTime),
-        all_fields_migration_field!(datetime_timezone, chrono::DateTime<chrono::FixedOffset>),
-        all_fields_migration_field!(string, String),
-        all_fields_migration_field!(blob, Vec<u8>),
-        all_fields_migration_field!(option, Option<String>),
-        all_fields_migration_field!(limited_string, LimitedString<10>),
+        Field::new(
+            Identifier::new("settings"),
+            <Json<Settings> as DatabaseField>::TYPE,
+        ),
+        Field::new(
+            Identifier::new("extra"),
+            <Option<Json<serde_json::Value>> as DatabaseField>::TYPE,
+        )
+        .set_null(<Option<Json<serde_json::Value>> as DatabaseField>::NULLABLE),
+    ])
+    .build();
+
+fn profile(theme: &str, tags: &[&str], extra: Option<serde_json::Value>) -> Profile {
+    Profile {
+        id: Auto::auto(),
+        settings: Json(Settings {
+            theme: theme.to_owned(),
+            tags: tags.iter().map(|&tag| tag.to_owned()).collect(),
+        }),
+        extra: extra.map(Json),
+    }
+}
+
+#[cot_macros::dbtest]
+async fn json_fields(test_db: &mut TestDatabase) {
+    CREATE_PROFILE.forwards(test_db).await.unwrap();
+
+    let mut dark = profile(
+        "dark",
+        &["rust", "web"],
+        Some(serde_json::json!({"beta": true, "limits": [1, 2]})),
+    );
+    dark.save(&**test_db).await.unwrap();
+    let mut light = profile("light", &[], None);
+    light.save(&**test_db).await.unwrap();
+
+    let profiles = Profile::objects().all(&**test_db).await.unwrap();
+    assert_eq!(profiles, vec![dark.clone(), light.clone()]);
+
+    light.settings.0.tags.push("new".to_owned());
+    light.save(&**test_db).await.unwrap();
+    let id = light.id;
+    let saved = query!(Profile, $id == id)
+        .get(&**test_db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(saved.settings.tags, ["new"]);
+}
+
+#[cfg(feature = "postgres")]
+#[ignore]
+#[tokio::test]
+async fn json_contains_postgres() {
+    let test_db = TestDatabase::new_postgres("json_contains_postgres")
+        .await
+        .unwrap();
+    CREATE_PROFILE.forwards(&test_db).await.unwrap();
+
+    let mut dark = profile("dark", &["rust", "web"], None);
+    dark.save(&*test_db).await.unwrap();
+    let mut light = profile(
+        "light",
+        &["python"],
+        Some(serde_json::json!({"beta": true})),
+    );
+    light.save(&*test_db).await.unwrap();
+
+    let found = query!(Profile, $settings.contains(serde_json::json!({"tags": ["rust"]})))
+        .all(&*test_db)
+        .await
+        .unwrap();
+    assert_eq!(found, vec![dark.clone()]);
+    let found = query!(Profile, $extra.contains(serde_json::json!({"beta": true})))
+        .all(&*test_db)
+        .await
+        .unwrap();
+    assert_eq!(found, vec![light.clone()]);
+
+    test_db.cleanup().await.unwrap();
+}
+
+#[cot_macros::dbtest]
+async fn auto_now_fields(test_db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Article {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        title: String,
+        #[model(auto_now_add)]
+        created_at: chrono::DateTime<chrono::Utc>,
+        #[model(auto_now)]
+        updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    }
+
+    const CREATE_ARTICLE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__article"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("created_at"),
+                <chrono::DateTime<chrono::Utc> as DatabaseField>::TYPE,
+            ),
+            Field::new(
+                Identifier::new("updated_at"),
+                <Option<chrono::DateTime<chrono::Utc>> as DatabaseField>::TYPE,
+            )
+            .set_null(<Option<chrono::DateTime<chrono::Utc>> as DatabaseField>::NULLABLE),
+        ])
+        .build();
+
+    let start = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2025, 1, 1, 12, 0, 0).unwrap();
+    let clock = TestClock::at(start);
+    test_db.with_clock(clock.clone());
+    CREATE_ARTICLE.forwards(test_db).await.unwrap();
+
+    let mut article = Article {
+        id: Auto::auto(),
+        title: "Hello".to_owned(),
+        created_at: chrono::DateTime::UNIX_EPOCH,
+        updated_at: None,
+    };
+    article.save(&**test_db).await.unwrap();
+    assert_eq!(article.created_at, start);
+    assert_eq!(article.updated_at, Some(start));
+
+    clock.advance(std::time::Duration::from_secs(3600));
+    article.title = "Hello, world".to_owned();
+    article.save(&**test_db).await.unwrap();
+    clock.advance(std::time::Duration::from_secs(3600));
+    article.update(&**test_db).await.unwrap();
+    let later = clock.now();
+    assert_eq!(article.created_at, start);
+    assert_eq!(article.updated_at, Some(later));
+
+    let id = article.id;
+    let saved = query!(Article, $id == id)
+        .get(&**test_db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(saved, article);
+
+    let mut articles = vec![Article {
+        id: Auto::auto(),
+        title: "Bulk".to_owned(),
+        created_at: chrono::DateTime::UNIX_EPOCH,
+        updated_at: None,
+    }];
+    Article::bulk_create(&**test_db, &mut articles)
+        .await
+        .unwrap();
+    assert_eq!(articles[0].created_at, later);
+    assert_eq!(articles[0].updated_at, Some(later));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DbEnum)]
+enum TaskStatus {
+    Todo,
+    InProgress,
+    #[db_enum(rename = "finished")]
+    Done,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DbEnum)]
+#[db_enum(repr = "integer")]
+enum TaskPriority {
+    Low = 1,
+    High = 5,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[model]
+struct Task {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    status: TaskStatus,
+    priority: Option<TaskPriority>,
+}
+
+const CREATE_TASK: Operation = Operation::create_model()
+    .table_name(Identifier::new("cot__task"))
+    .fields(&[
+        Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+            .primary_key()
+            .auto(),
+        Field::new(
+            Identifier::new("status"),
+            <TaskStatus as DatabaseField>::TYPE,
+        ),
+        Field::new(
+            Identifier::new("priority"),
+            <Option<TaskPriority> as DatabaseField>::TYPE,
+        )
+        .set_null(<Option<TaskPriority> as DatabaseField>::NULLABLE),
+    ])
+    .build();
+
+#[cot_macros::dbtest]
+async fn db_enum_fields(test_db: &mut TestDatabase) {
+    CREATE_TASK.forwards(test_db).await.unwrap();
+
+    let mut todo = Task {
+        id: Auto::auto(),
+        status: TaskStatus::Todo,
+        priority: Some(TaskPriority::High),
+    };
+    todo.save(&**test_db).await.unwrap();
+    let mut done = Task {
+        id: Auto::auto(),
+        status: TaskStatus::Done,
+        priority: None,
+    };
+    done.save(&**test_db).await.unwrap();
+
+    assert_eq!(
+        Task::objects().all(&**test_db).await.unwrap(),
+        vec![todo.clone(), done.clone()]
+    );
+    let found = query!(Task, $status == TaskStatus::Done)
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(found, vec![done]);
+    let found = query!(Task, $priority == Some(TaskPriority::High))
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(found, vec![todo]);
+
+    test_db
+        .raw("UPDATE cot__task SET status = 'archived'")
+        .await
+        .unwrap();
+    let error = Task::objects().all(&**test_db).await.unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::ValueDecode(_)),
+        "unexpected error: {error:?}"
+    );
+    assert!(
+        error
+            .to_string()
+            .contains("`archived` is not a valid value")
+    );
+}
+
+/// An amount of money stored in cents; negative amounts are rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Price(i64);
+
+impl CustomDbField for Price {
+    type Repr = i64;
+
+    fn to_repr(&self) -> i64 {
+        self.0
+    }
+
+    fn from_repr(cents: i64) -> cot::db::Result<Self> {
+        if cents < 0 {
+            return Err(DatabaseError::value_decode(std::io::Error::other(
+                "negative price",
+            )));
+        }
+        Ok(Self(cents))
+    }
+}
+
+impl ToDbValue for Price {
+    fn to_db_value(&self) -> DbValue {
+        self.to_repr().into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[model]
+struct Product {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    price: Price,
+    sale_price: Option<Price>,
+}
+
+const CREATE_PRODUCT: Operation = Operation::create_model()
+    .table_name(Identifier::new("cot__product"))
+    .fields(&[
+        Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+            .primary_key()
+            .auto(),
+        Field::new(Identifier::new("price"), <Price as DatabaseField>::TYPE),
+        Field::new(
+            Identifier::new("sale_price"),
+            <Option<Price> as DatabaseField>::TYPE,
+        )
+        .set_null(<Option<Price> as DatabaseField>::NULLABLE),
+    ])
+    .build();
+
+#[cot_macros::dbtest]
+async fn custom_db_fields(test_db: &mut TestDatabase) {
+    CREATE_PRODUCT.forwards(test_db).await.unwrap();
+    assert_eq!(<Price as DatabaseField>::TYPE, ColumnType::BigInteger);
+
+    let mut regular = Product {
+        id: Auto::auto(),
+        price: Price(1999),
+        sale_price: None,
+    };
+    regular.save(&**test_db).await.unwrap();
+    let mut discounted = Product {
+        id: Auto::auto(),
+        price: Price(2500),
+        sale_price: Some(Price(1500)),
+    };
+    discounted.save(&**test_db).await.unwrap();
+
+    assert_eq!(
+        Product::objects().all(&**test_db).await.unwrap(),
+        vec![regular.clone(), discounted.clone()]
+    );
+    let found = query!(Product, $price == Price(1999))
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(found, vec![regular]);
+    let found = query!(Product, $sale_price == Some(Price(1500)))
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(found, vec![discounted]);
+
+    test_db
+        .raw("UPDATE cot__product SET price = -1")
+        .await
+        .unwrap();
+    let error = Product::objects().all(&**test_db).await.unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::ValueDecode(_)),
+        "unexpected error: {error:?}"
+    );
+}
+
+async fn migrate_test_model(db: &Database) {
+    CREATE_TEST_MODEL.forwards(db).await.unwrap();
+}
+
+const CREATE_TEST_MODEL: Operation = Operation::create_model()
+    .table_name(Identifier::new("cot__test_model"))
+    .fields(&[
+        Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+            .primary_key()
+            .auto(),
+        Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+    ])
+    .build();
+
+macro_rules! all_fields_migration_field {
+    ($name:ident, $ty:ty) => {
+        Field::new(
+            Identifier::new(concat!("field_", stringify!($name))),
+            <$ty as DatabaseField>::TYPE,
+        )
+        .set_null(<$ty as DatabaseField>::NULLABLE)
+    };
+    ($ty:ty) => {
+        Field::new(
+            Identifier::new(concat!("field_", stringify!($ty))),
+            <$ty as DatabaseField>::TYPE,
+        )
+        .set_null(<$ty as DatabaseField>::NULLABLE)
+    };
+}
+
+#[derive(Debug, PartialEq, Dummy)]
+#[model]
+struct AllFieldsModel {
+    #[dummy(expr = "Auto::auto()")]
+    #[model(primary_key)]
+    id: Auto<i32>,
+    field_bool: bool,
+    field_i8: i8,
+    field_i16: i16,
+    field_i32: i32,
+    field_i64: i64,
+    field_u8: u8,
+    field_u16: u16,
+    field_u32: u32,
+    // SQLite only allows us to store signed integers, so we're generating numbers that do not
+    // exceed i64::MAX
+    #[dummy(faker = "0..i64::MAX as u64")]
+    field_u64: u64,
+    field_f32: f32,
+    field_f64: f64,
+    field_date: chrono::NaiveDate,
+    field_time: chrono::NaiveTime,
+    #[dummy(faker = "fake::chrono::Precision::<6>")]
+    field_datetime: chrono::NaiveDateTime,
+    #[dummy(faker = "fake::chrono::Precision::<6>")]
+    field_datetime_timezone: chrono::DateTime<chrono::FixedOffset>,
+    field_string: String,
+    field_blob: Vec<u8>,
+    field_option: Option<String>,
+    field_limited_string: LimitedString<10>,
+}
+
+async fn migrate_all_fields_model(db: &Database) {
+    CREATE_ALL_FIELDS_MODEL.forwards(db).await.unwrap();
+}
+
+const CREATE_ALL_FIELDS_MODEL: Operation = Operation::create_model()
+    .table_name(Identifier::new("cot__all_fields_model"))
+    .fields(&[
+        Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+            .primary_key()
+            .auto(),
+        all_fields_migration_field!(bool),
+        all_fields_migration_field!(i8),
+        all_fields_migration_field!(i16),
+        all_fields_migration_field!(i32),
+        all_fields_migration_field!(i64),
+        all_fields_migration_field!(u8),
+        all_fields_migration_field!(u16),
+        all_fields_migration_field!(u32),
+        all_fields_migration_field!(u64),
+        all_fields_migration_field!(f32),
+        all_fields_migration_field!(f64),
+        all_fields_migration_field!(date, chrono::NaiveDate),
+        all_fields_migration_field!(time, chrono::NaiveTime),
+        all_fields_migration_field!(datetime, chrono::NaiveDateTime),
+        all_fields_migration_field!(datetime_timezone, chrono::DateTime<chrono::FixedOffset>),
+        all_fields_migration_field!(string, String),
+        all_fields_migration_field!(blob, Vec<u8>),
+        all_fields_migration_field!(option, Option<String>),
+        all_fields_migration_field!(limited_string, LimitedString<10>),
     ])
     .build();
 
@@ -232,59 +1298,621 @@ const CREATE_ALL_FIELDS_MODEL: Operation = Operation::create_model()
 async fn all_fields_model(db: &mut TestDatabase) {
     migrate_all_fields_model(db).await;
 
-    assert_eq!(AllFieldsModel::objects().all(&**db).await.unwrap(), vec![]);
+    assert_eq!(AllFieldsModel::objects().all(&**db).await.unwrap(), vec![]);
+
+    let r = &mut StdRng::seed_from_u64(123_785);
+    let mut models = (0..100)
+        .map(|_| Faker.fake_with_rng(r))
+        .collect::<Vec<AllFieldsModel>>();
+    for model in &mut models {
+        model.save(&**db).await.unwrap();
+    }
+
+    let mut models_from_db: Vec<_> = AllFieldsModel::objects().all(&**db).await.unwrap();
+    normalize_datetimes(&mut models);
+    normalize_datetimes(&mut models_from_db);
+
+    assert_eq!(models.len(), models_from_db.len());
+    for model in &models {
+        assert!(
+            models_from_db.contains(model),
+            "Could not find model {model:?} in models_from_db: {models_from_db:?}",
+        );
+    }
+}
+
+/// Normalize the datetimes to UTC.
+fn normalize_datetimes(data: &mut Vec<AllFieldsModel>) {
+    for model in data {
+        model.field_datetime_timezone = model.field_datetime_timezone.with_timezone(
+            &chrono::FixedOffset::east_opt(0).expect("UTC timezone is always valid"),
+        );
+    }
+}
+
+#[cot_macros::dbtest]
+async fn foreign_keys(db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Artist {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Track {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        artist: ForeignKey<Artist>,
+        name: String,
+    }
+
+    const CREATE_ARTIST: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__artist"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        ])
+        .build();
+    const CREATE_TRACK: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__track"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(
+                Identifier::new("artist"),
+                <ForeignKey<Artist> as DatabaseField>::TYPE,
+            )
+            .foreign_key(
+                <Artist as Model>::TABLE_NAME,
+                <Artist as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Restrict,
+                ForeignKeyOnUpdatePolicy::Restrict,
+            ),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    CREATE_ARTIST.forwards(db).await.unwrap();
+    CREATE_TRACK.forwards(db).await.unwrap();
+
+    let mut artist = Artist {
+        id: Auto::auto(),
+        name: "artist".to_owned(),
+    };
+    artist.save(&**db).await.unwrap();
+
+    let mut track = Track {
+        id: Auto::auto(),
+        artist: ForeignKey::from(&artist),
+        name: "track".to_owned(),
+    };
+    track.save(&**db).await.unwrap();
+
+    let mut track = Track::objects().all(&**db).await.unwrap()[0].clone();
+    let artist_from_db = track.artist.get(&**db).await.unwrap();
+    assert_eq!(artist_from_db, &artist);
+
+    let error = query!(Artist, $id == artist.id)
+        .delete(&**db)
+        .await
+        .unwrap_err();
+    // expected foreign key violation
+    assert!(matches!(error, DatabaseError::DatabaseEngineError(_)));
+
+    query!(Track, $artist == &artist)
+        .delete(&**db)
+        .await
+        .unwrap();
+    query!(Artist, $id == artist.id)
+        .delete(&**db)
+        .await
+        .unwrap();
+    // no error should be thrown
+}
+
+#[cot_macros::dbtest]
+async fn non_integer_primary_keys(db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Document {
+        #[model(primary_key)]
+        id: Auto<uuid::Uuid>,
+        title: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Label {
+        #[model(primary_key)]
+        code: LimitedString<16>,
+        document: ForeignKey<Document>,
+    }
+
+    const CREATE_DOCUMENT: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__document"))
+        .fields(&[
+            Field::new(
+                Identifier::new("id"),
+                <Auto<uuid::Uuid> as DatabaseField>::TYPE,
+            )
+            .primary_key()
+            .auto(),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+        ])
+        .build();
+    const CREATE_LABEL: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__label"))
+        .fields(&[
+            Field::new(
+                Identifier::new("code"),
+                <LimitedString<16> as DatabaseField>::TYPE,
+            )
+            .primary_key(),
+            Field::new(
+                Identifier::new("document"),
+                <ForeignKey<Document> as DatabaseField>::TYPE,
+            )
+            .foreign_key(
+                <Document as Model>::TABLE_NAME,
+                <Document as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Cascade,
+                ForeignKeyOnUpdatePolicy::Restrict,
+            ),
+        ])
+        .build();
+
+    CREATE_DOCUMENT.forwards(db).await.unwrap();
+    CREATE_LABEL.forwards(db).await.unwrap();
+
+    let mut document = Document {
+        id: Auto::auto(),
+        title: "Report".to_owned(),
+    };
+    document.save(&**db).await.unwrap();
+    let Auto::Fixed(id) = document.id else {
+        panic!("the primary key should be generated on insert");
+    };
+    let mut documents = vec![
+        Document {
+            id: Auto::auto(),
+            title: "Bulk".to_owned(),
+        },
+        Document {
+            id: Auto::fixed(uuid::Uuid::nil()),
+            title: "Nil".to_owned(),
+        },
+    ];
+    Document::bulk_create(&**db, &mut documents).await.unwrap();
+    assert_ne!(documents[0].id, Auto::fixed(id));
+    assert_eq!(documents[1].id, Auto::fixed(uuid::Uuid::nil()));
+
+    let from_db = Document::get_by_primary_key(&**db, Auto::fixed(id))
+        .await
+        .unwrap();
+    assert_eq!(from_db, Some(document.clone()));
+
+    let mut label = Label {
+        code: LimitedString::new("urgent").unwrap(),
+        document: ForeignKey::from(&document),
+    };
+    label.save(&**db).await.unwrap();
+    label.save(&**db).await.unwrap();
+
+    let mut labels = query!(Label, $document == &document)
+        .all(&**db)
+        .await
+        .unwrap();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].code, "urgent");
+    assert_eq!(labels[0].document.get(&**db).await.unwrap(), &document);
+
+    query!(Document, $id == Auto::fixed(id))
+        .delete(&**db)
+        .await
+        .unwrap();
+    assert_eq!(Label::objects().count(db).await.unwrap(), 0);
+}
+
+#[cot_macros::dbtest]
+async fn model_hooks(test_db: &mut TestDatabase) {
+    static EVENTS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model(hooks)]
+    struct Page {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        title: String,
+        slug: String,
+        locked: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl ModelHooks for Page {
+        async fn pre_save<DB: DatabaseBackend>(&mut self, _db: &DB) -> cot::db::Result<()> {
+            self.slug = self.title.to_lowercase().replace(' ', "-");
+            Ok(())
+        }
+
+        async fn post_save<DB: DatabaseBackend>(&mut self, _db: &DB) -> cot::db::Result<()> {
+            EVENTS.lock().unwrap().push(format!("saved {}", self.id));
+            Ok(())
+        }
+
+        async fn pre_delete<DB: DatabaseBackend>(&mut self, _db: &DB) -> cot::db::Result<()> {
+            if self.locked {
+                return Err(DatabaseError::value_decode(std::io::Error::other(
+                    "locked pages cannot be deleted",
+                )));
+            }
+            Ok(())
+        }
+
+        async fn post_delete<DB: DatabaseBackend>(&mut self, _db: &DB) -> cot::db::Result<()> {
+            EVENTS.lock().unwrap().push(format!("deleted {}", self.id));
+            Ok(())
+        }
+    }
+
+    const CREATE_PAGE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__page"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("slug"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("locked"), <bool as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    CREATE_PAGE.forwards(test_db).await.unwrap();
+    EVENTS.lock().unwrap().clear();
+
+    let mut page = Page {
+        id: Auto::fixed(1),
+        title: "About Us".to_owned(),
+        slug: String::new(),
+        locked: false,
+    };
+    page.save(&**test_db).await.unwrap();
+    assert_eq!(page.slug, "about-us");
+    let mut locked = Page {
+        id: Auto::fixed(2),
+        title: "Terms".to_owned(),
+        slug: String::new(),
+        locked: true,
+    };
+    locked.insert(&**test_db).await.unwrap();
+
+    let from_db = Page::get_by_primary_key(&**test_db, Auto::fixed(1))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(from_db.slug, "about-us");
+
+    page.delete(&**test_db).await.unwrap();
+    locked.delete(&**test_db).await.unwrap_err();
+    assert_eq!(Page::objects().all(&**test_db).await.unwrap(), [locked]);
+    let error = page.delete(&**test_db).await.unwrap_err();
+    assert!(matches!(error, DatabaseError::RecordNotFound { .. }));
+
+    assert_eq!(*EVENTS.lock().unwrap(), ["saved 1", "saved 2", "deleted 1"]);
+}
+
+#[cot_macros::dbtest]
+async fn soft_delete(test_db: &mut TestDatabase) {
+    #[model(soft_delete)]
+    #[derive(Debug, Clone, PartialEq)]
+    struct Invoice {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        number: String,
+    }
+
+    const CREATE_INVOICE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__invoice"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("number"), <String as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("deleted_at"),
+                <Option<chrono::DateTime<chrono::Utc>> as DatabaseField>::TYPE,
+            )
+            .set_null(<Option<chrono::DateTime<chrono::Utc>> as DatabaseField>::NULLABLE),
+        ])
+        .build();
+
+    let now = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2025, 1, 1, 12, 0, 0).unwrap();
+    test_db.with_clock(TestClock::at(now));
+    CREATE_INVOICE.forwards(test_db).await.unwrap();
+
+    let mut invoices: Vec<_> = ["2025/001", "2025/002"]
+        .into_iter()
+        .map(|number| Invoice {
+            id: Auto::auto(),
+            number: number.to_owned(),
+            deleted_at: None,
+        })
+        .collect();
+    Invoice::bulk_create(&**test_db, &mut invoices)
+        .await
+        .unwrap();
+    let deleted_id = invoices[0].id;
+
+    // soft delete
+    let result = query!(Invoice, $id == deleted_id)
+        .delete(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected().0, 1);
+    assert_eq!(
+        Invoice::objects().all(&**test_db).await.unwrap(),
+        [invoices[1].clone()]
+    );
+    assert_eq!(
+        Invoice::get_by_primary_key(&**test_db, deleted_id)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(Invoice::objects().count(test_db).await.unwrap(), 1);
+
+    let all = Invoice::objects()
+        .with_deleted()
+        .order_by(InvoiceFields::number.asc())
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].deleted_at, Some(now));
+    assert_eq!(all[1].deleted_at, None);
+
+    // restore
+    let result = query!(Invoice, $id == deleted_id)
+        .restore(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected().0, 1);
+    assert_eq!(Invoice::objects().count(test_db).await.unwrap(), 2);
+
+    // hard delete
+    Invoice::objects().delete(&**test_db).await.unwrap();
+    query!(Invoice, $id == deleted_id)
+        .with_deleted()
+        .hard_delete(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(
+        Invoice::objects()
+            .with_deleted()
+            .count(test_db)
+            .await
+            .unwrap(),
+        1
+    );
+}
+
+#[cot_macros::dbtest]
+async fn optimistic_locking(test_db: &mut TestDatabase) {
+    #[model]
+    #[derive(Debug, Clone, PartialEq)]
+    struct Article {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        body: String,
+        #[model(version)]
+        version: i64,
+    }
+
+    const CREATE_ARTICLE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__article"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("body"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("version"), <i64 as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    CREATE_ARTICLE.forwards(test_db).await.unwrap();
+
+    let mut article = Article {
+        id: Auto::auto(),
+        body: "first draft".to_owned(),
+        version: 0,
+    };
+    article.insert(&**test_db).await.unwrap();
+    assert_eq!(article.version, 0);
+
+    let mut first_copy = article.clone();
+    let mut second_copy = article.clone();
+
+    first_copy.body = "second draft".to_owned();
+    first_copy.save(&**test_db).await.unwrap();
+    assert_eq!(first_copy.version, 1);
+
+    // saving a copy read before the first one was saved fails
+    second_copy.body = "conflicting draft".to_owned();
+    let error = second_copy.save(&**test_db).await.unwrap_err();
+    assert!(matches!(
+        error,
+        DatabaseError::StaleObject { version: 0, .. }
+    ));
+    let error = second_copy.update(&**test_db).await.unwrap_err();
+    assert!(matches!(
+        error,
+        DatabaseError::StaleObject { version: 0, .. }
+    ));
+    let error = Article::bulk_save(&**test_db, std::slice::from_mut(&mut second_copy))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        DatabaseError::StaleObject { version: 0, .. }
+    ));
+    assert_eq!(second_copy.version, 0);
+    assert_eq!(
+        Article::objects().all(&**test_db).await.unwrap(),
+        [first_copy.clone()]
+    );
+
+    // updating the fresh copy succeeds
+    let mut fresh_copy = Article::get_by_primary_key(&**test_db, article.id)
+        .await
+        .unwrap()
+        .unwrap();
+    fresh_copy.body = "final draft".to_owned();
+    fresh_copy.update(&**test_db).await.unwrap();
+    assert_eq!(fresh_copy.version, 2);
+    assert_eq!(
+        Article::objects().all(&**test_db).await.unwrap(),
+        [fresh_copy.clone()]
+    );
+
+    // saving an instance that doesn't exist yet inserts it
+    let mut new_article = Article {
+        id: Auto::fixed(100),
+        body: "another article".to_owned(),
+        version: 0,
+    };
+    new_article.save(&**test_db).await.unwrap();
+    assert_eq!(new_article.version, 0);
+    assert_eq!(Article::objects().count(test_db).await.unwrap(), 2);
+
+    // updating an instance that doesn't exist is still an error
+    let mut missing_article = Article {
+        id: Auto::fixed(200),
+        body: "missing article".to_owned(),
+        version: 0,
+    };
+    let error = missing_article.update(&**test_db).await.unwrap_err();
+    assert!(matches!(error, DatabaseError::RecordNotFound { .. }));
+}
+
+#[cot_macros::dbtest]
+async fn model_forms(test_db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq, ModelForm)]
+    #[model]
+    struct Article {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        title: String,
+        body: Option<String>,
+        #[model(auto_now_add)]
+        created_at: chrono::DateTime<chrono::Utc>,
+        #[model(version)]
+        version: i64,
+    }
+
+    const CREATE_ARTICLE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__article"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("body"),
+                <Option<String> as DatabaseField>::TYPE,
+            )
+            .set_null(<Option<String> as DatabaseField>::NULLABLE),
+            Field::new(
+                Identifier::new("created_at"),
+                <chrono::DateTime<chrono::Utc> as DatabaseField>::TYPE,
+            ),
+            Field::new(Identifier::new("version"), <i64 as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    let start = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2025, 1, 1, 12, 0, 0).unwrap();
+    test_db.with_clock(TestClock::at(start));
+    CREATE_ARTICLE.forwards(test_db).await.unwrap();
 
-    let r = &mut StdRng::seed_from_u64(123_785);
-    let mut models = (0..100)
-        .map(|_| Faker.fake_with_rng(r))
-        .collect::<Vec<AllFieldsModel>>();
-    for model in &mut models {
-        model.save(&**db).await.unwrap();
-    }
+    // the fields not entered by the user are not a part of the form
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[
+            ("id", "100"),
+            ("title", "Hello"),
+            ("created_at", "2000-01-01T00:00:00Z"),
+            ("version", "100"),
+        ])
+        .build();
+    let form = ArticleForm::from_request(&mut request)
+        .await
+        .unwrap()
+        .unwrap();
+    let context = form.to_context();
+    let field_ids: Vec<_> = context.fields().map(DynFormField::dyn_id).collect();
+    assert_eq!(field_ids, ["title", "body"]);
 
-    let mut models_from_db: Vec<_> = AllFieldsModel::objects().all(&**db).await.unwrap();
-    normalize_datetimes(&mut models);
-    normalize_datetimes(&mut models_from_db);
+    let article = form.save(&**test_db).await.unwrap();
+    assert!(matches!(article.id, Auto::Fixed(_)));
+    assert_eq!(article.title, "Hello");
+    assert_eq!(article.body, None);
+    assert_eq!(article.created_at, start);
+    assert_eq!(article.version, 0);
+    assert_eq!(
+        Article::objects().all(&**test_db).await.unwrap(),
+        std::slice::from_ref(&article)
+    );
 
-    assert_eq!(models.len(), models_from_db.len());
-    for model in &models {
-        assert!(
-            models_from_db.contains(model),
-            "Could not find model {model:?} in models_from_db: {models_from_db:?}",
-        );
-    }
-}
+    // updating keeps the fields not included in the form
+    let mut form = ArticleForm::from_model(&article);
+    assert_eq!(form.title, "Hello");
+    form.body = Some("Hello, world".to_owned());
+    let mut updated = article.clone();
+    form.update(&mut updated, &**test_db).await.unwrap();
+    assert_eq!(updated.id, article.id);
+    assert_eq!(updated.body.as_deref(), Some("Hello, world"));
+    assert_eq!(updated.created_at, start);
+    assert_eq!(updated.version, 1);
+    assert_eq!(
+        Article::objects().all(&**test_db).await.unwrap(),
+        [updated.clone()]
+    );
 
-/// Normalize the datetimes to UTC.
-fn normalize_datetimes(data: &mut Vec<AllFieldsModel>) {
-    for model in data {
-        model.field_datetime_timezone = model.field_datetime_timezone.with_timezone(
-            &chrono::FixedOffset::east_opt(0).expect("UTC timezone is always valid"),
-        );
-    }
+    // the version check still applies to stale instances
+    let mut stale = article.clone();
+    let error = form.update(&mut stale, &**test_db).await.unwrap_err();
+    assert!(matches!(
+        error,
+        DatabaseError::StaleObject { version: 0, .. }
+    ));
 }
 
 #[cot_macros::dbtest]
-async fn foreign_keys(db: &mut TestDatabase) {
-    #[derive(Debug, Clone, PartialEq)]
+#[expect(clippy::too_many_lines)] // it's mostly the table definitions and assertions
+async fn composite_primary_key(test_db: &mut TestDatabase) {
     #[model]
-    struct Artist {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Warehouse {
         #[model(primary_key)]
         id: Auto<i32>,
         name: String,
     }
 
-    #[derive(Debug, Clone, PartialEq)]
     #[model]
-    struct Track {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Stock {
         #[model(primary_key)]
-        id: Auto<i32>,
-        artist: ForeignKey<Artist>,
-        name: String,
+        warehouse: ForeignKey<Warehouse>,
+        #[model(primary_key)]
+        sku: LimitedString<32>,
+        quantity: i32,
     }
 
-    const CREATE_ARTIST: Operation = Operation::create_model()
-        .table_name(Identifier::new("cot__artist"))
+    const CREATE_WAREHOUSE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__warehouse"))
         .fields(&[
             Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
                 .primary_key()
@@ -292,62 +1920,101 @@ async fn foreign_keys(db: &mut TestDatabase) {
             Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
         ])
         .build();
-    const CREATE_TRACK: Operation = Operation::create_model()
-        .table_name(Identifier::new("cot__track"))
+    const CREATE_STOCK: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__stock"))
         .fields(&[
-            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
-                .primary_key()
-                .auto(),
             Field::new(
-                Identifier::new("artist"),
-                <ForeignKey<Artist> as DatabaseField>::TYPE,
+                Identifier::new("warehouse"),
+                <ForeignKey<Warehouse> as DatabaseField>::TYPE,
             )
+            .primary_key()
             .foreign_key(
-                <Artist as Model>::TABLE_NAME,
-                <Artist as Model>::PRIMARY_KEY_NAME,
-                ForeignKeyOnDeletePolicy::Restrict,
+                <Warehouse as Model>::TABLE_NAME,
+                <Warehouse as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Cascade,
                 ForeignKeyOnUpdatePolicy::Restrict,
             ),
-            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("sku"),
+                <LimitedString<32> as DatabaseField>::TYPE,
+            )
+            .primary_key(),
+            Field::new(Identifier::new("quantity"), <i32 as DatabaseField>::TYPE),
         ])
         .build();
 
-    CREATE_ARTIST.forwards(db).await.unwrap();
-    CREATE_TRACK.forwards(db).await.unwrap();
+    CREATE_WAREHOUSE.forwards(test_db).await.unwrap();
+    CREATE_STOCK.forwards(test_db).await.unwrap();
+    assert_eq!(
+        Stock::PRIMARY_KEY_COLUMNS,
+        [Identifier::new("warehouse"), Identifier::new("sku")]
+    );
 
-    let mut artist = Artist {
+    let mut warehouse = Warehouse {
         id: Auto::auto(),
-        name: "artist".to_owned(),
+        name: "Main".to_owned(),
     };
-    artist.save(&**db).await.unwrap();
-
-    let mut track = Track {
-        id: Auto::auto(),
-        artist: ForeignKey::from(&artist),
-        name: "track".to_owned(),
+    warehouse.save(&**test_db).await.unwrap();
+    let sku = |sku: &str| LimitedString::<32>::new(sku).unwrap();
+    let mut apples = Stock {
+        warehouse: ForeignKey::from(&warehouse),
+        sku: sku("apple"),
+        quantity: 10,
     };
-    track.save(&**db).await.unwrap();
-
-    let mut track = Track::objects().all(&**db).await.unwrap()[0].clone();
-    let artist_from_db = track.artist.get(&**db).await.unwrap();
-    assert_eq!(artist_from_db, &artist);
+    apples.insert(&**test_db).await.unwrap();
+    let mut pears = Stock {
+        warehouse: ForeignKey::from(&warehouse),
+        sku: sku("pear"),
+        quantity: 5,
+    };
+    pears.save(&**test_db).await.unwrap();
 
-    let error = query!(Artist, $id == artist.id)
-        .delete(&**db)
-        .await
-        .unwrap_err();
-    // expected foreign key violation
-    assert!(matches!(error, DatabaseError::DatabaseEngineError(_)));
+    // saving an instance with an existing primary key updates the row
+    let mut more_apples = Stock {
+        quantity: 15,
+        ..apples.clone()
+    };
+    more_apples.save(&**test_db).await.unwrap();
+    pears.quantity = 3;
+    pears.update(&**test_db).await.unwrap();
 
-    query!(Track, $artist == &artist)
-        .delete(&**db)
+    let found = Stock::get_by_primary_key(&**test_db, (ForeignKey::from(&warehouse), sku("apple")))
         .await
+        .unwrap()
         .unwrap();
-    query!(Artist, $id == artist.id)
-        .delete(&**db)
+    assert_eq!(found.quantity, 15);
+    assert_eq!(
+        found.warehouse.clone().get(&**test_db).await.unwrap(),
+        &warehouse
+    );
+    let found = Stock::get_by_primary_key(&**test_db, (ForeignKey::from(&warehouse), sku("pear")))
         .await
+        .unwrap()
         .unwrap();
-    // no error should be thrown
+    assert_eq!(found.quantity, 3);
+    assert_eq!(Stock::objects().count(test_db).await.unwrap(), 2);
+
+    let mut missing = Stock {
+        warehouse: ForeignKey::from(&warehouse),
+        sku: sku("plum"),
+        quantity: 1,
+    };
+    let error = missing.update(&**test_db).await.unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::RecordNotFound { .. }),
+        "unexpected error: {error:?}"
+    );
+
+    apples.delete(&**test_db).await.unwrap();
+    assert_eq!(
+        Stock::objects().all(&**test_db).await.unwrap(),
+        vec![pears.clone()]
+    );
+    let error = apples.delete(&**test_db).await.unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::RecordNotFound { .. }),
+        "unexpected error: {error:?}"
+    );
 }
 
 #[cot_macros::dbtest]
@@ -504,3 +2171,396 @@ async fn foreign_keys_cascade(db: &mut TestDatabase) {
         .unwrap();
     assert!(Child::objects().all(&**db).await.unwrap().is_empty());
 }
+
+#[cot_macros::dbtest]
+#[expect(clippy::too_many_lines)] // it's mostly the table definitions
+async fn many_to_many(db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Post {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        tags: ManyToMany<Tag>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Tag {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+    }
+
+    const CREATE_POST: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__post"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+        ])
+        .build();
+    const CREATE_TAG: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__tag"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        ])
+        .build();
+    const CREATE_POST_TAGS: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__post_tags"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(
+                Identifier::new("source_id"),
+                <ForeignKey<Post> as DatabaseField>::TYPE,
+            )
+            .foreign_key(
+                <Post as Model>::TABLE_NAME,
+                <Post as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Cascade,
+                ForeignKeyOnUpdatePolicy::Cascade,
+            ),
+            Field::new(
+                Identifier::new("target_id"),
+                <ForeignKey<Tag> as DatabaseField>::TYPE,
+            )
+            .foreign_key(
+                <Tag as Model>::TABLE_NAME,
+                <Tag as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Cascade,
+                ForeignKeyOnUpdatePolicy::Cascade,
+            ),
+        ])
+        .build();
+
+    CREATE_POST.forwards(db).await.unwrap();
+    CREATE_TAG.forwards(db).await.unwrap();
+    CREATE_POST_TAGS.forwards(db).await.unwrap();
+
+    let mut post = Post {
+        id: Auto::auto(),
+        tags: ManyToMany::new(),
+    };
+    post.save(&**db).await.unwrap();
+    let mut tags = Vec::new();
+    for name in ["rust", "web", "orm"] {
+        let mut tag = Tag {
+            id: Auto::auto(),
+            name: name.to_owned(),
+        };
+        tag.save(&**db).await.unwrap();
+        tags.push(tag);
+    }
+
+    PostFields::tags.add(db, &post, &tags[0]).await.unwrap();
+    PostFields::tags.add(db, &post, &tags[1]).await.unwrap();
+    // adding the same link twice is a no-op
+    PostFields::tags.add(db, &post, &tags[1]).await.unwrap();
+    assert_eq!(
+        PostFields::tags.get(&**db, &post).await.unwrap(),
+        &tags[..2]
+    );
+
+    let posts = query!(Post, $tags.contains(&tags[1]))
+        .all(&**db)
+        .await
+        .unwrap();
+    assert_eq!(posts, [post.clone()]);
+    let posts = query!(Post, $tags.contains(&tags[2]))
+        .all(&**db)
+        .await
+        .unwrap();
+    assert!(posts.is_empty());
+
+    let mut other_post = Post {
+        id: Auto::auto(),
+        tags: ManyToMany::new(),
+    };
+    other_post.save(&**db).await.unwrap();
+    PostFields::tags
+        .add(db, &other_post, &tags[2])
+        .await
+        .unwrap();
+    let prefetched = PostFields::tags
+        .prefetch(db, &[post.clone(), other_post, post.clone()])
+        .await
+        .unwrap();
+    assert_eq!(
+        prefetched,
+        [
+            tags[..2].to_vec(),
+            vec![tags[2].clone()],
+            tags[..2].to_vec()
+        ]
+    );
+
+    PostFields::tags.remove(db, &post, &tags[0]).await.unwrap();
+    assert_eq!(
+        PostFields::tags.get(&**db, &post).await.unwrap(),
+        &tags[1..2]
+    );
+
+    PostFields::tags.set(db, &post, &tags[1..]).await.unwrap();
+    assert_eq!(
+        PostFields::tags.get(&**db, &post).await.unwrap(),
+        &tags[1..]
+    );
+
+    PostFields::tags.clear(db, &post).await.unwrap();
+    assert!(PostFields::tags.get(&**db, &post).await.unwrap().is_empty());
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn multiple_databases_sqlite() {
+    struct ReplicaRouter;
+
+    impl DatabaseRouter for ReplicaRouter {
+        fn db_for_read(&self, _model: &ModelInfo) -> Option<&str> {
+            Some("replica")
+        }
+    }
+
+    let replica = Database::new("sqlite::memory:").await.unwrap();
+    migrate_test_model(&replica).await;
+    let db = Database::new("sqlite::memory:")
+        .await
+        .unwrap()
+        .with_database("replica", std::sync::Arc::new(replica))
+        .with_router(std::sync::Arc::new(ReplicaRouter));
+    migrate_test_model(&db).await;
+
+    // writes go to the default database, reads to the replica
+    let mut model = TestModel {
+        id: Auto::auto(),
+        name: "primary".to_owned(),
+    };
+    model.save(&db).await.unwrap();
+    assert_eq!(TestModel::objects().all(&db).await.unwrap(), vec![]);
+    assert_eq!(TestModel::objects().count(&db).await.unwrap(), 0);
+
+    let objects = TestModel::objects()
+        .using(DEFAULT_DATABASE)
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(objects, vec![model]);
+
+    TestModel::objects()
+        .using("replica")
+        .update_all(&db, [TestModelFields::name.set("replicated")])
+        .await
+        .unwrap();
+    let result = TestModel::objects()
+        .using("replica")
+        .delete(&db)
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected(), RowsNum(0));
+
+    let error = TestModel::objects()
+        .using("analytics")
+        .all(&db)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, DatabaseError::UnknownDatabase { name } if name == "analytics"));
+
+    db.close().await.unwrap();
+}
+
+#[cot_macros::dbtest]
+async fn introspection(test_db: &mut TestDatabase) {
+    const CREATE_ARTICLE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__article"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(
+                Identifier::new("title"),
+                <LimitedString<100> as DatabaseField>::TYPE,
+            )
+            .unique(),
+            Field::new(
+                Identifier::new("body"),
+                <Option<String> as DatabaseField>::TYPE,
+            )
+            .set_null(<Option<String> as DatabaseField>::NULLABLE),
+        ])
+        .build();
+
+    CREATE_ARTICLE.forwards(test_db).await.unwrap();
+    migrate_test_model(test_db).await;
+
+    let tables = test_db.introspect().await.unwrap();
+
+    let table_names: Vec<_> = tables.iter().map(|table| table.name.as_str()).collect();
+    assert_eq!(table_names, ["cot__article", "cot__test_model"]);
+    let article = &tables[0];
+    let columns: Vec<_> = article
+        .columns
+        .iter()
+        .map(|column| {
+            (
+                column.name.as_str(),
+                column.column_type,
+                column.nullable,
+                column.primary_key,
+                column.auto_increment,
+            )
+        })
+        .collect();
+    assert_eq!(
+        columns,
+        [
+            ("id", Some(ColumnType::Integer), false, true, true),
+            ("title", Some(ColumnType::String(100)), false, false, false),
+            ("body", Some(ColumnType::Text), true, false, false),
+        ]
+    );
+    assert_eq!(article.indexes.len(), 1);
+    assert_eq!(article.indexes[0].columns, ["title"]);
+    assert!(article.indexes[0].unique);
+}
+
+#[cot_macros::dbtest]
+#[expect(clippy::too_many_lines)] // it's mostly the table definitions and assertions
+async fn fixtures(test_db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Category {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+        parent: Option<ForeignKey<Category>>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Product {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        category: ForeignKey<Category>,
+        name: String,
+    }
+
+    const CREATE_CATEGORY: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__category"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("parent"),
+                <Option<ForeignKey<Category>> as DatabaseField>::TYPE,
+            )
+            .set_null(<Option<ForeignKey<Category>> as DatabaseField>::NULLABLE)
+            .foreign_key(
+                <Category as Model>::TABLE_NAME,
+                <Category as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Restrict,
+                ForeignKeyOnUpdatePolicy::Restrict,
+            ),
+        ])
+        .build();
+    const CREATE_PRODUCT: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__product"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(
+                Identifier::new("category"),
+                <ForeignKey<Category> as DatabaseField>::TYPE,
+            )
+            .foreign_key(
+                <Category as Model>::TABLE_NAME,
+                <Category as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Restrict,
+                ForeignKeyOnUpdatePolicy::Restrict,
+            ),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    test_db
+        .add_migrations([TestMigration::new(
+            "cot",
+            "0001_initial",
+            vec![],
+            vec![CREATE_CATEGORY, CREATE_PRODUCT],
+        )])
+        .run_migrations()
+        .await;
+
+    // records are deliberately out of order: children before their parents
+    let fixture = Fixture::from_format(
+        r#"[
+            {"table": "cot__product", "fields": {"id": 1, "category": 2, "name": "Laptop"}},
+            {"table": "cot__category", "fields": {"id": 2, "name": "Computers", "parent": 1}},
+            {"table": "cot__category", "fields": {"id": 1, "name": "Electronics", "parent": null}}
+        ]"#,
+        FixtureFormat::Json,
+    )
+    .unwrap();
+    test_db.load_fixture(&fixture).await;
+
+    let products = Product::objects().all(&**test_db).await.unwrap();
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].name, "Laptop");
+    assert_eq!(products[0].category.primary_key(), Auto::fixed(2));
+    let computers = query!(Category, $id == 2)
+        .get(&**test_db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        computers.parent.map(|parent| parent.primary_key()),
+        Some(Auto::fixed(1))
+    );
+
+    // loading the dumped data again updates the existing rows
+    let dumped = test_db.dump_fixture().await;
+    let tables: Vec<_> = dumped
+        .records()
+        .iter()
+        .map(|record| record.table.as_str())
+        .collect();
+    assert_eq!(tables, ["cot__category", "cot__category", "cot__product"]);
+    test_db.load_fixture(&dumped).await;
+    assert_eq!(Category::objects().count(test_db).await.unwrap(), 2);
+    assert_eq!(test_db.dump_fixture().await, dumped);
+
+    // auto-incremented values continue after the loaded primary keys
+    let mut category = Category {
+        id: Auto::auto(),
+        name: "Books".to_owned(),
+        parent: None,
+    };
+    category.save(&**test_db).await.unwrap();
+    assert_eq!(category.id, Auto::fixed(3));
+
+    let unknown_table = Fixture::new(vec![FixtureRecord::new(
+        "cot__unknown",
+        serde_json::Map::new(),
+    )]);
+    let schema = FixtureSchema::new(
+        &MigrationEngine::new([TestMigration::new(
+            "cot",
+            "0001_initial",
+            vec![],
+            vec![CREATE_CATEGORY, CREATE_PRODUCT],
+        )])
+        .unwrap(),
+    );
+    let error = unknown_table.load(test_db, &schema).await.unwrap_err();
+    assert!(matches!(
+        error,
+        DatabaseError::FixtureError(FixtureError::UnknownTable { .. })
+    ));
+}
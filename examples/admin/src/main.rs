@@ -22,7 +22,7 @@ use cot::static_files::StaticFilesMiddleware;
 use cot::{App, AppBuilder, Body, BoxedHandler, Project, ProjectContext, StatusCode};
 
 #[derive(Debug, Clone, Form, AdminModel)]
-#[model]
+#[model(search(fields = ["title"]))]
 struct TodoItem {
     #[model(primary_key)]
     id: Auto<i32>,